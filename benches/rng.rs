@@ -0,0 +1,36 @@
+//! Compares generating passwords directly from the operating system's random
+//! number generator with generating them from a reseeding ChaCha20 generator.
+//!
+//! Run with `cargo bench --bench rng`.
+
+use passgen::{ChaChaDrbg, PasswordSpec};
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use std::hint::black_box;
+use std::num::NonZeroUsize;
+use std::time::Instant;
+
+const NUM_PASSWORDS: usize = 200_000;
+const PASSWORD_LEN: usize = 16;
+
+fn bench(name: &str, spec: &PasswordSpec, rng: impl RngCore + CryptoRng) {
+    let start = Instant::now();
+    for password in spec.passwords_with_rng(rng) {
+        black_box(password.unwrap());
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+    let rate = NUM_PASSWORDS as f64 / elapsed;
+    println!("{:<8} {:>8.3} s {:>12.0} passwords/s", name, elapsed, rate);
+}
+
+fn main() {
+    let spec =
+        PasswordSpec::builder()
+            .length(NonZeroUsize::new(PASSWORD_LEN).unwrap())
+            .count(NonZeroUsize::new(NUM_PASSWORDS).unwrap())
+            .build()
+            .unwrap();
+    bench("os", &spec, OsRng);
+    let interval = ChaChaDrbg::DEFAULT_RESEED_INTERVAL;
+    bench("chacha", &spec, ChaChaDrbg::new(interval).unwrap());
+}
@@ -0,0 +1,87 @@
+#ifndef PASSGEN_H
+#define PASSGEN_H
+
+#include <stddef.h>
+
+/**
+ * Success.
+ */
+#define PASSGEN_OK 0
+
+/**
+ * A pointer argument was null or a string was not valid UTF-8.
+ */
+#define PASSGEN_ERR_ARGUMENT 1
+
+/**
+ * The specification is malformed, or its policy cannot be satisfied by its
+ * character set and length.
+ */
+#define PASSGEN_ERR_SPEC 2
+
+/**
+ * No password satisfying the policy was found in the attempts allowed.
+ */
+#define PASSGEN_ERR_POLICY 3
+
+/**
+ * The password entropy is below the required number of bits.
+ */
+#define PASSGEN_ERR_ENTROPY 4
+
+/**
+ * The random number generator failed or failed a health test.
+ */
+#define PASSGEN_ERR_RNG 5
+
+/**
+ * The output buffer is too small for the passwords.
+ */
+#define PASSGEN_ERR_BUFFER 6
+
+/**
+ * Any other failure.
+ */
+#define PASSGEN_ERR_OTHER 7
+
+/**
+ * Generates passwords from the JSON specification into `out_buf`, one per
+ * line and NUL-terminated. Returns `PASSGEN_OK` or an error code; on error
+ * the buffer holds an empty string.
+ *
+ * # Safety
+ *
+ * `spec_json` must be a NUL-terminated string and `out_buf` must point to
+ * `len` writable bytes.
+ */
+int passgen_generate(const char *spec_json, char *out_buf, size_t len);
+
+/**
+ * Generates passwords from the JSON specification into a newly allocated
+ * string, one per line, which must be released with `passgen_free`. Returns
+ * null on error and stores the error code in `status` if it is not null.
+ *
+ * # Safety
+ *
+ * `spec_json` must be a NUL-terminated string and `status` must be null or
+ * point to a writable int.
+ */
+char *passgen_generate_alloc(const char *spec_json, int *status);
+
+/**
+ * Overwrites and frees a string returned by `passgen_generate_alloc`. Does
+ * nothing if `passwords` is null.
+ *
+ * # Safety
+ *
+ * `passwords` must be null or a string returned by `passgen_generate_alloc`
+ * that has not already been freed.
+ */
+void passgen_free(char *passwords);
+
+/**
+ * Returns a static description of an error code.
+ */
+const char *passgen_strerror(int code);
+
+#endif  /* PASSGEN_H */
@@ -0,0 +1,157 @@
+//! C bindings for passgen.
+//!
+//! Specifications are passed as JSON objects in the form `PasswordSpec`
+//! deserializes from, with the fields `charset` (a charset specification),
+//! `length`, `min_length`, `max_length`, `count`, `policy`, `max_attempts`
+//! and `timeout_secs`, all optional. Passwords are returned one per line.
+
+use passgen::{PassgenError, PasswordSpec};
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::ptr;
+use std::slice;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Success.
+pub const PASSGEN_OK: c_int = 0;
+/// A pointer argument was null or a string was not valid UTF-8.
+pub const PASSGEN_ERR_ARGUMENT: c_int = 1;
+/// The specification is malformed, or its policy cannot be satisfied by its
+/// character set and length.
+pub const PASSGEN_ERR_SPEC: c_int = 2;
+/// No password satisfying the policy was found in the attempts allowed.
+pub const PASSGEN_ERR_POLICY: c_int = 3;
+/// The password entropy is below the required number of bits.
+pub const PASSGEN_ERR_ENTROPY: c_int = 4;
+/// The random number generator failed or failed a health test.
+pub const PASSGEN_ERR_RNG: c_int = 5;
+/// The output buffer is too small for the passwords.
+pub const PASSGEN_ERR_BUFFER: c_int = 6;
+/// Any other failure.
+pub const PASSGEN_ERR_OTHER: c_int = 7;
+
+fn error_code(err: &PassgenError) -> c_int {
+    match err {
+        PassgenError::InvalidSpec(_) => PASSGEN_ERR_SPEC,
+        PassgenError::PolicyViolation(_) => PASSGEN_ERR_POLICY,
+        PassgenError::InsufficientEntropy { .. } => PASSGEN_ERR_ENTROPY,
+        PassgenError::RngFailure(_) => PASSGEN_ERR_RNG,
+        PassgenError::HealthTestFailure(_) => PASSGEN_ERR_RNG,
+        PassgenError::SelfTestFailure(_) => PASSGEN_ERR_OTHER,
+        PassgenError::AttemptsExhausted { .. } => PASSGEN_ERR_POLICY,
+    }
+}
+
+unsafe fn generate(
+    spec_json: *const c_char,
+) -> Result<Zeroizing<String>, c_int> {
+    if spec_json.is_null() {
+        return Err(PASSGEN_ERR_ARGUMENT);
+    }
+    let spec_json =
+        CStr::from_ptr(spec_json).to_str().map_err(|_| PASSGEN_ERR_ARGUMENT)?;
+    // Deserializing validates the spec as the builder does, but its errors do
+    // not tell a malformed spec from an unsatisfiable policy.
+    let spec =
+        serde_json::from_str::<PasswordSpec>(spec_json)
+            .map_err(|_| PASSGEN_ERR_SPEC)?;
+    let mut output = Zeroizing::new(String::new());
+    for password in spec.passwords() {
+        let mut password = password.map_err(|err| error_code(&err))?;
+        output.push_str(&password);
+        output.push('\n');
+        password.zeroize();
+    }
+    Ok(output)
+}
+
+/// Generates passwords from the JSON specification into `out_buf`, one per
+/// line and NUL-terminated. Returns `PASSGEN_OK` or an error code; on error
+/// the buffer holds an empty string.
+///
+/// # Safety
+///
+/// `spec_json` must be a NUL-terminated string and `out_buf` must point to
+/// `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn passgen_generate(
+    spec_json: *const c_char,
+    out_buf: *mut c_char,
+    len: usize,
+) -> c_int {
+    if out_buf.is_null() || len == 0 {
+        return PASSGEN_ERR_ARGUMENT;
+    }
+    let out = slice::from_raw_parts_mut(out_buf as *mut u8, len);
+    out[0] = 0;
+    let output =
+        match generate(spec_json) {
+            Ok(output) => output,
+            Err(code) => return code,
+        };
+    if output.len() >= len {
+        return PASSGEN_ERR_BUFFER;
+    }
+    out[..output.len()].copy_from_slice(output.as_bytes());
+    out[output.len()] = 0;
+    PASSGEN_OK
+}
+
+/// Generates passwords from the JSON specification into a newly allocated
+/// string, one per line, which must be released with `passgen_free`. Returns
+/// null on error and stores the error code in `status` if it is not null.
+///
+/// # Safety
+///
+/// `spec_json` must be a NUL-terminated string and `status` must be null or
+/// point to a writable int.
+#[no_mangle]
+pub unsafe extern "C" fn passgen_generate_alloc(
+    spec_json: *const c_char,
+    status: *mut c_int,
+) -> *mut c_char {
+    let (result, code) =
+        match generate(spec_json) {
+            Ok(output) => {
+                // Passwords never contain NUL.
+                let output = CString::new(output.as_bytes()).unwrap();
+                (output.into_raw(), PASSGEN_OK)
+            },
+            Err(code) => (ptr::null_mut(), code),
+        };
+    if !status.is_null() {
+        *status = code;
+    }
+    result
+}
+
+/// Overwrites and frees a string returned by `passgen_generate_alloc`. Does
+/// nothing if `passwords` is null.
+///
+/// # Safety
+///
+/// `passwords` must be null or a string returned by `passgen_generate_alloc`
+/// that has not already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn passgen_free(passwords: *mut c_char) {
+    if passwords.is_null() {
+        return;
+    }
+    CString::from_raw(passwords).into_bytes().zeroize();
+}
+
+/// Returns a static description of an error code.
+#[no_mangle]
+pub extern "C" fn passgen_strerror(code: c_int) -> *const c_char {
+    let msg: &'static CStr =
+        match code {
+            PASSGEN_OK => c"success",
+            PASSGEN_ERR_ARGUMENT => c"invalid argument",
+            PASSGEN_ERR_SPEC => c"invalid specification",
+            PASSGEN_ERR_POLICY => c"policy cannot be satisfied",
+            PASSGEN_ERR_ENTROPY => c"insufficient entropy",
+            PASSGEN_ERR_RNG => c"random number generator failure",
+            PASSGEN_ERR_BUFFER => c"output buffer too small",
+            _ => c"unknown error",
+        };
+    msg.as_ptr()
+}
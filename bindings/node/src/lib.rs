@@ -0,0 +1,106 @@
+//! Node.js bindings for passgen.
+
+use napi::bindgen_prelude::AsyncTask;
+use napi::{Env, Error, Result, Task};
+use napi_derive::napi;
+use passgen::{CharClass, PassgenError, PasswordSpec, Policy};
+use std::num::NonZeroUsize;
+
+/// The passwords to generate. `charset` is a charset specification and
+/// defaults to all typeable characters. `length` sets both `minLength` and
+/// `maxLength`, which default to 24. `required` lists the character classes
+/// ("lowercase", "uppercase", "digits", "symbols") every password must
+/// contain.
+#[napi(object)]
+pub struct Spec {
+    pub charset: Option<String>,
+    pub length: Option<u32>,
+    pub min_length: Option<u32>,
+    pub max_length: Option<u32>,
+    pub count: Option<u32>,
+    pub required: Option<Vec<String>>,
+}
+
+fn to_napi_err(err: PassgenError) -> Error {
+    Error::from_reason(err.to_string())
+}
+
+fn non_zero(value: u32, name: &str) -> Result<NonZeroUsize> {
+    NonZeroUsize::new(value as usize).ok_or_else(|| {
+        Error::from_reason(format!("{} must not be zero", name))
+    })
+}
+
+fn char_class(name: &str) -> Result<CharClass> {
+    match name {
+        "lowercase" => Ok(CharClass::Lowercase),
+        "uppercase" => Ok(CharClass::Uppercase),
+        "digits" => Ok(CharClass::Digits),
+        "symbols" => Ok(CharClass::Symbols),
+        _ => {
+            let msg = format!("unknown character class: {:?}", name);
+            Err(Error::from_reason(msg))
+        },
+    }
+}
+
+fn build(spec: Spec) -> Result<PasswordSpec> {
+    let required =
+        spec.required.unwrap_or_default().iter()
+            .map(|name| char_class(name))
+            .collect::<Result<Vec<_>>>()?;
+    let policy = Policy { required, ..Policy::default() };
+    let mut builder = PasswordSpec::builder().policy(policy);
+    if let Some(charset_spec) = spec.charset {
+        let charset =
+            passgen::parse_charset_spec(&charset_spec).map_err(to_napi_err)?;
+        builder = builder.charset(charset);
+    }
+    if let Some(password_len) = spec.length {
+        builder = builder.length(non_zero(password_len, "length")?);
+    }
+    if let Some(min_len) = spec.min_length {
+        builder = builder.min_length(non_zero(min_len, "minLength")?);
+    }
+    if let Some(max_len) = spec.max_length {
+        builder = builder.max_length(non_zero(max_len, "maxLength")?);
+    }
+    if let Some(num_passwords) = spec.count {
+        builder = builder.count(non_zero(num_passwords, "count")?);
+    }
+    builder.build().map_err(to_napi_err)
+}
+
+/// Generates passwords synchronously.
+#[napi]
+pub fn generate(spec: Spec) -> Result<Vec<String>> {
+    build(spec)?.generate().map_err(to_napi_err)
+}
+
+/// The work behind `generateAsync`. An invalid spec is carried through as
+/// its message so that it rejects the promise rather than throwing.
+pub struct GenerateTask(std::result::Result<PasswordSpec, String>);
+
+impl Task for GenerateTask {
+    type Output = Vec<String>;
+    type JsValue = Vec<String>;
+
+    fn compute(&mut self) -> Result<Vec<String>> {
+        match &self.0 {
+            Ok(spec) => spec.generate().map_err(to_napi_err),
+            Err(msg) => Err(Error::from_reason(msg.clone())),
+        }
+    }
+
+    fn resolve(&mut self, _: Env, output: Vec<String>) -> Result<Vec<String>> {
+        Ok(output)
+    }
+}
+
+/// Generates passwords on the libuv thread pool, resolving to the list of
+/// passwords.
+#[napi(ts_return_type = "Promise<string[]>")]
+pub fn generate_async(spec: Spec) -> AsyncTask<GenerateTask> {
+    let spec = build(spec).map_err(|err| err.reason);
+    AsyncTask::new(GenerateTask(spec))
+}
@@ -0,0 +1,164 @@
+//! Python bindings for passgen.
+
+use ::passgen::{CharClass, Charset, PassgenError, PasswordSpec, Policy};
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use std::num::NonZeroUsize;
+
+create_exception!(
+    passgen,
+    Error,
+    PyException,
+    "Base class for passgen errors."
+);
+create_exception!(
+    passgen,
+    InvalidSpecError,
+    Error,
+    "The specification is malformed."
+);
+create_exception!(
+    passgen,
+    PolicyViolationError,
+    Error,
+    "The policy cannot be satisfied by the character set and length."
+);
+create_exception!(
+    passgen,
+    InsufficientEntropyError,
+    Error,
+    "The password entropy is below the required number of bits."
+);
+create_exception!(
+    passgen,
+    RngError,
+    Error,
+    "The random number generator failed or failed a health test."
+);
+
+fn to_py_err(err: PassgenError) -> PyErr {
+    let msg = err.to_string();
+    match err {
+        PassgenError::InvalidSpec(_) => InvalidSpecError::new_err(msg),
+        PassgenError::PolicyViolation(_) => PolicyViolationError::new_err(msg),
+        PassgenError::InsufficientEntropy { .. } =>
+            InsufficientEntropyError::new_err(msg),
+        PassgenError::RngFailure(_) => RngError::new_err(msg),
+        PassgenError::HealthTestFailure(_) => RngError::new_err(msg),
+        PassgenError::SelfTestFailure(_) => Error::new_err(msg),
+        PassgenError::AttemptsExhausted { .. } =>
+            PolicyViolationError::new_err(msg),
+    }
+}
+
+fn non_zero(value: usize, name: &str) -> PyResult<NonZeroUsize> {
+    NonZeroUsize::new(value).ok_or_else(|| {
+        InvalidSpecError::new_err(format!("{} must not be zero", name))
+    })
+}
+
+fn char_class(name: &str) -> PyResult<CharClass> {
+    match name {
+        "lowercase" => Ok(CharClass::Lowercase),
+        "uppercase" => Ok(CharClass::Uppercase),
+        "digits" => Ok(CharClass::Digits),
+        "symbols" => Ok(CharClass::Symbols),
+        _ => {
+            let msg = format!("unknown character class: {:?}", name);
+            Err(InvalidSpecError::new_err(msg))
+        },
+    }
+}
+
+/// Generates passwords and returns them as a list.
+///
+/// `charset` is a charset specification and defaults to all typeable
+/// characters. `length` sets both `min_length` and `max_length`, which
+/// default to 24. `required` lists the character classes ("lowercase",
+/// "uppercase", "digits", "symbols") every password must contain.
+#[pyfunction]
+#[pyo3(signature = (
+    charset=None,
+    length=None,
+    min_length=None,
+    max_length=None,
+    count=1,
+    required=Vec::new(),
+))]
+fn generate(
+    py: Python<'_>,
+    charset: Option<String>,
+    length: Option<usize>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    count: usize,
+    required: Vec<String>,
+) -> PyResult<Vec<String>> {
+    let required =
+        required.iter()
+            .map(|name| char_class(name))
+            .collect::<PyResult<Vec<_>>>()?;
+    let mut builder =
+        PasswordSpec::builder()
+            .count(non_zero(count, "count")?)
+            .policy(Policy { required, ..Policy::default() });
+    if let Some(charset_spec) = charset {
+        let charset =
+            ::passgen::parse_charset_spec(&charset_spec).map_err(to_py_err)?;
+        builder = builder.charset(charset);
+    }
+    if let Some(password_len) = length {
+        builder = builder.length(non_zero(password_len, "length")?);
+    }
+    if let Some(min_len) = min_length {
+        builder = builder.min_length(non_zero(min_len, "min_length")?);
+    }
+    if let Some(max_len) = max_length {
+        builder = builder.max_length(non_zero(max_len, "max_length")?);
+    }
+    let spec = builder.build().map_err(to_py_err)?;
+    py.allow_threads(|| spec.generate()).map_err(to_py_err)
+}
+
+/// Returns the characters of a charset specification, in order.
+#[pyfunction]
+fn parse_charset_spec(charset_spec: String) -> PyResult<String> {
+    let charset =
+        ::passgen::parse_charset_spec(&charset_spec).map_err(to_py_err)?;
+    Ok(charset.iter().copied().map(char::from).collect())
+}
+
+/// Returns the shortest charset specification for the characters.
+#[pyfunction]
+fn format_charset_spec(charset: &str) -> PyResult<String> {
+    let charset = Charset::new(charset.as_bytes()).map_err(to_py_err)?;
+    Ok(charset.to_string())
+}
+
+/// Returns the entropy in bits of a password of this length drawn uniformly
+/// from the characters of a charset specification.
+#[pyfunction]
+fn entropy_bits(charset: String, length: usize) -> PyResult<f64> {
+    let charset = ::passgen::parse_charset_spec(&charset).map_err(to_py_err)?;
+    Ok(::passgen::entropy_bits(&charset, length))
+}
+
+/// Random password generation from a character set specification.
+#[pymodule]
+fn passgen(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = module.py();
+    module.add_function(wrap_pyfunction!(generate, module)?)?;
+    module.add_function(wrap_pyfunction!(parse_charset_spec, module)?)?;
+    module.add_function(wrap_pyfunction!(format_charset_spec, module)?)?;
+    module.add_function(wrap_pyfunction!(entropy_bits, module)?)?;
+    module.add("Error", py.get_type::<Error>())?;
+    module.add("InvalidSpecError", py.get_type::<InvalidSpecError>())?;
+    module.add("PolicyViolationError", py.get_type::<PolicyViolationError>())?;
+    module.add(
+        "InsufficientEntropyError",
+        py.get_type::<InsufficientEntropyError>(),
+    )?;
+    module.add("RngError", py.get_type::<RngError>())?;
+    Ok(())
+}
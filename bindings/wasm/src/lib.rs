@@ -0,0 +1,14 @@
+//! WebAssembly bindings for passgen.
+
+use passgen::PasswordSpec;
+use wasm_bindgen::prelude::*;
+
+/// Generates passwords from a JSON specification with the fields `charset`
+/// (a charset specification), `length`, `min_length`, `max_length`, `count`
+/// and `policy`, all optional. Randomness comes from the host's
+/// `crypto.getRandomValues`.
+#[wasm_bindgen]
+pub fn generate(spec_json: &str) -> Result<Vec<String>, JsError> {
+    let spec = serde_json::from_str::<PasswordSpec>(spec_json)?;
+    Ok(spec.generate()?)
+}
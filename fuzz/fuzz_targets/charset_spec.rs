@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use passgen::{format_charset_spec, parse_charset_spec, Charset};
+
+fuzz_target!(|charset_spec: &str| {
+    if let Ok(charset) = parse_charset_spec(charset_spec) {
+        let formatted = format_charset_spec(&charset);
+        assert_eq!(parse_charset_spec(&formatted).unwrap(), charset);
+        assert_eq!(Charset::new(charset.as_bytes()).unwrap(), charset);
+    }
+});
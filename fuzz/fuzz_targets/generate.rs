@@ -0,0 +1,22 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use passgen::PasswordSpec;
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::SeedableRng;
+
+fuzz_target!(|input: (PasswordSpec, [u8; 32])| {
+    let (spec, seed) = input;
+    let rng = ChaCha20Rng::from_seed(seed);
+    let mut num_passwords = 0;
+    for password in spec.passwords_with_rng(rng) {
+        let password = password.unwrap();
+        let len = password.len();
+        assert!(spec.min_length().get() <= len);
+        assert!(len <= spec.max_length().get());
+        assert!(password.bytes().all(|byte| spec.charset().contains(&byte)));
+        assert!(spec.policy().is_satisfied_by(password.as_bytes()));
+        num_passwords += 1;
+    }
+    assert_eq!(num_passwords, spec.count().get());
+});
@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use passgen::{Generator, PatternGenerator};
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::SeedableRng;
+
+fuzz_target!(|pattern: &str| {
+    if let Ok(generator) = PatternGenerator::new(pattern) {
+        let mut rng = ChaCha20Rng::from_seed([0; 32]);
+        let mut password = String::new();
+        generator.generate(&mut rng, &mut password).unwrap();
+        assert!(password.len() <= pattern.len());
+    }
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use passgen::PasswordSpec;
+
+fuzz_target!(|spec_json: &str| {
+    if let Ok(spec) = serde_json::from_str::<PasswordSpec>(spec_json) {
+        let serialized = serde_json::to_string(&spec).unwrap();
+        serde_json::from_str::<PasswordSpec>(&serialized).unwrap();
+    }
+});
@@ -0,0 +1,182 @@
+use aes::cipher::{BlockEncrypt, KeyInit};
+use aes::Aes256;
+use anyhow::{anyhow, Context, Result};
+use hmac::{Hmac, Mac};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use sha2::Sha256;
+use std::process::{Command, Stdio};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::i18n::trf;
+
+// Secrets are encrypted as ansible-vault encrypt_string does, in version 1.1
+// of the vault format: AES-256 in counter mode under keys derived from the
+// vault password with PBKDF2-HMAC-SHA-256, and an HMAC-SHA-256 of the
+// ciphertext, all hex-encoded twice over.
+
+const HEADER: &str = "$ANSIBLE_VAULT;1.1;AES256";
+const SALT_LEN: usize = 32;
+const ITERATIONS: u32 = 10000;
+// The cipher key, the HMAC key and the initial counter block.
+const KEY_MATERIAL_LEN: usize = 80;
+const LINE_LEN: usize = 80;
+// The indentation ansible-vault gives the lines of the block scalar.
+const INDENT: &str = "          ";
+
+/// Reads the vault password from the file, or from the output of the file
+/// if it is executable, as Ansible does.
+pub fn read_password(path: &Path) -> Result<Zeroizing<String>> {
+    let context = || trf("could not read \"{}\"", &[&path.display()]);
+    let metadata = fs::metadata(path).with_context(context)?;
+    let executable = is_executable(&metadata);
+    let password =
+        if executable {
+            // A bare file name would be looked up in PATH.
+            let program = Path::new(".").join(path);
+            let output =
+                Command::new(program)
+                    .stdin(Stdio::null())
+                    .stderr(Stdio::inherit())
+                    .output()
+                    .with_context(|| {
+                        trf("could not run \"{}\"", &[&path.display()])
+                    })?;
+            let stdout = Zeroizing::new(output.stdout);
+            if !output.status.success() {
+                let path = path.display();
+                let msg = trf("{} exited with {}", &[&path, &output.status]);
+                return Err(anyhow!(msg));
+            }
+            let password = String::from_utf8_lossy(&stdout);
+            Zeroizing::new(password.trim_end_matches(['\r', '\n']).to_string())
+        } else {
+            let password =
+                Zeroizing::new(fs::read(path).with_context(context)?);
+            let password = String::from_utf8_lossy(&password);
+            Zeroizing::new(password.trim().to_string())
+        };
+    if password.is_empty() {
+        let path = path.display();
+        let msg = trf("the vault password in \"{}\" is empty", &[&path]);
+        return Err(anyhow!(msg));
+    }
+    Ok(password)
+}
+
+/// Encrypts the secret with the vault password, giving the !vault tagged
+/// string to paste into a YAML file.
+pub fn encrypt_string(secret: &str, password: &str) -> Result<String> {
+    let mut salt = [0; SALT_LEN];
+    OsRng.try_fill_bytes(&mut salt)?;
+    encrypt_with_salt(secret, password, &salt)
+}
+
+fn encrypt_with_salt(
+    secret: &str,
+    password: &str,
+    salt: &[u8; SALT_LEN],
+) -> Result<String> {
+    let keys = derive_keys(password.as_bytes(), salt);
+    let (cipher_key, rest) = keys.split_at(32);
+    let (hmac_key, counter) = rest.split_at(32);
+    // PKCS #7 padding, which Ansible adds even in counter mode.
+    let padding = 16 - secret.len() % 16;
+    let mut ciphertext =
+        Zeroizing::new(Vec::with_capacity(secret.len() + padding));
+    ciphertext.extend_from_slice(secret.as_bytes());
+    ciphertext.resize(secret.len() + padding, padding as u8);
+    let cipher = Aes256::new(cipher_key.into());
+    let mut counter = u128::from_be_bytes(counter.try_into().unwrap());
+    for block in ciphertext.chunks_mut(16) {
+        let mut keystream = counter.to_be_bytes().into();
+        cipher.encrypt_block(&mut keystream);
+        for (byte, key) in block.iter_mut().zip(&keystream) {
+            *byte ^= key;
+        }
+        keystream.zeroize();
+        counter = counter.wrapping_add(1);
+    }
+    counter.zeroize();
+    // HMAC takes keys of any length.
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(hmac_key).unwrap();
+    mac.update(&ciphertext);
+    let mac = mac.finalize().into_bytes();
+    let mut payload = hex(salt);
+    payload.push('\n');
+    payload.push_str(&hex(&mac));
+    payload.push('\n');
+    payload.push_str(&hex(&ciphertext));
+    let payload = hex(payload.as_bytes());
+    let mut out = format!("!vault |\n{}{}\n", INDENT, HEADER);
+    for line in payload.as_bytes().chunks(LINE_LEN) {
+        // The payload is hexadecimal digits.
+        let line = std::str::from_utf8(line).unwrap();
+        writeln!(out, "{}{}", INDENT, line)?;
+    }
+    Ok(out)
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    std::os::unix::fs::PermissionsExt::mode(&metadata.permissions()) & 0o111
+        != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_: &fs::Metadata) -> bool {
+    false
+}
+
+fn derive_keys(
+    password: &[u8],
+    salt: &[u8],
+) -> Zeroizing<[u8; KEY_MATERIAL_LEN]> {
+    let mut keys = Zeroizing::new([0; KEY_MATERIAL_LEN]);
+    pbkdf2::pbkdf2_hmac::<Sha256>(password, salt, ITERATIONS, &mut keys[..]);
+    keys
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        write!(out, "{:02x}", byte).unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Computed with Python's hashlib and cryptography as Ansible's
+    // VaultAES256 does it, for a salt of the bytes 0 to 31.
+    #[test]
+    fn matches_vault_1_1_encryption() {
+        let mut salt = [0; SALT_LEN];
+        for (idx, byte) in salt.iter_mut().enumerate() {
+            *byte = idx as u8;
+        }
+        let encrypted =
+            encrypt_with_salt("hunter2", "vault password", &salt).unwrap();
+        let payload = [
+            "3030303130323033303430353036303730383039306130623063306430653066\
+             3130313131323133",
+            "3134313531363137313831393161316231633164316531660a65653931666436\
+             6538636663336232",
+            "3262333962303738373935376634336634663534653436666335626639303566\
+             3333653935656634",
+            "3331663437353463300a62313462356565666365626131333930386636373537\
+             3130613466333134",
+            "3235",
+        ];
+        let mut expected = format!("!vault |\n{}{}\n", INDENT, HEADER);
+        for line in payload {
+            expected.push_str(&format!("{}{}\n", INDENT, line));
+        }
+        assert_eq!(encrypted, expected);
+    }
+}
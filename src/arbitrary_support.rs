@@ -0,0 +1,97 @@
+use arbitrary::{Arbitrary, Error, Unstructured};
+use std::num::NonZeroUsize;
+
+use crate::{
+    Charset,
+    CharsetGenerator,
+    PasswordSpec,
+    PatternGenerator,
+    PinGenerator,
+    Policy,
+};
+
+// Lengths and counts are kept small so that fuzz targets can generate the
+// passwords they are given without spending their time in the sampler.
+// Values that fail validation are rejected as incorrectly formatted input.
+
+const MAX_PASSWORD_LEN: usize = 64;
+const MAX_NUM_PASSWORDS: usize = 8;
+
+fn non_zero_up_to(
+    u: &mut Unstructured<'_>,
+    min: NonZeroUsize,
+    max: usize,
+) -> arbitrary::Result<NonZeroUsize> {
+    let value = u.int_in_range(min.get()..=max)?;
+    Ok(NonZeroUsize::new(value).unwrap())
+}
+
+fn lengths(
+    u: &mut Unstructured<'_>,
+) -> arbitrary::Result<(NonZeroUsize, NonZeroUsize)> {
+    let min_len = non_zero_up_to(u, NonZeroUsize::MIN, MAX_PASSWORD_LEN)?;
+    let max_len = non_zero_up_to(u, min_len, MAX_PASSWORD_LEN)?;
+    Ok((min_len, max_len))
+}
+
+impl<'a> Arbitrary<'a> for Charset {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mask = u128::arbitrary(u)?;
+        let chars =
+            Charset::typeable().iter()
+                .copied()
+                .filter(|&byte| mask & (1 << byte) != 0)
+                .collect::<Vec<_>>();
+        Charset::new(chars).map_err(|_| Error::IncorrectFormat)
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        u128::size_hint(depth)
+    }
+}
+
+impl<'a> Arbitrary<'a> for CharsetGenerator {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let charset = Charset::arbitrary(u)?;
+        let (min_len, max_len) = lengths(u)?;
+        let policy = Policy::arbitrary(u)?;
+        CharsetGenerator::new(charset, min_len, max_len, policy)
+            .map_err(|_| Error::IncorrectFormat)
+    }
+}
+
+impl<'a> Arbitrary<'a> for PasswordSpec {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let charset = Charset::arbitrary(u)?;
+        let (min_len, max_len) = lengths(u)?;
+        let num_passwords =
+            non_zero_up_to(u, NonZeroUsize::MIN, MAX_NUM_PASSWORDS)?;
+        let policy = Policy::arbitrary(u)?;
+        PasswordSpec::builder()
+            .charset(charset)
+            .min_length(min_len)
+            .max_length(max_len)
+            .count(num_passwords)
+            .policy(policy)
+            .build()
+            .map_err(|_| Error::IncorrectFormat)
+    }
+}
+
+impl<'a> Arbitrary<'a> for PatternGenerator {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let pattern = <&str>::arbitrary(u)?;
+        PatternGenerator::new(pattern).map_err(|_| Error::IncorrectFormat)
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <&str>::size_hint(depth)
+    }
+}
+
+impl<'a> Arbitrary<'a> for PinGenerator {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let pin_len = non_zero_up_to(u, NonZeroUsize::MIN, MAX_PASSWORD_LEN)?;
+        Ok(PinGenerator::new(pin_len).reject_weak(u.arbitrary()?))
+    }
+}
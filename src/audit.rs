@@ -0,0 +1,105 @@
+use anyhow::{Context, Result};
+use passgen::Preset;
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::i18n::trf;
+
+// The audit log records, one JSON object to a line, each time passwords
+// were generated: when, under what label, how many and to what
+// specification, so that the way credentials are made can be evidenced
+// afterwards. It never records the passwords, nor anything worked out from
+// them, such as hashes.
+
+/// One set of passwords generated.
+#[derive(Serialize)]
+pub struct AuditEntry<'a> {
+    pub label: Option<&'a str>,
+    pub preset: Option<Preset>,
+    // The name of the field generated, as in records.
+    pub kind: &'a str,
+    pub count: usize,
+    // The entropy of the weakest password possible.
+    pub entropy_bits: f64,
+    pub min_entropy_bits: Option<f64>,
+    pub rng: String,
+    pub description: &'a [String],
+}
+
+// The entry as written, stamped with the time.
+#[derive(Serialize)]
+struct Stamped<'a> {
+    time: String,
+    #[serde(flatten)]
+    entry: &'a AuditEntry<'a>,
+}
+
+/// An audit log opened for appending.
+pub struct AuditLog {
+    file: File,
+    path: PathBuf,
+}
+
+impl AuditLog {
+    /// Opens the log, creating it readable only by the user if missing.
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut options = OpenOptions::new();
+        options.append(true).create(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let file = options.open(path).with_context(|| {
+            trf("could not open \"{}\"", &[&path.display()])
+        })?;
+        Ok(AuditLog { file, path: path.to_path_buf() })
+    }
+
+    /// Appends the entry, stamped with the current time.
+    pub fn record(&mut self, entry: &AuditEntry) -> Result<()> {
+        let time = timestamp(SystemTime::now());
+        let mut line = serde_json::to_string(&Stamped { time, entry })?;
+        line.push('\n');
+        // Written at once, so that entries appended by runs at the same time
+        // are not interleaved.
+        self.file.write_all(line.as_bytes()).with_context(|| {
+            trf("could not write \"{}\"", &[&self.path.display()])
+        })
+    }
+}
+
+/// Formats the time in UTC as RFC 3339, to the second, working out the
+/// date from the days since the epoch in the proleptic Gregorian calendar.
+pub fn timestamp(time: SystemTime) -> String {
+    let secs =
+        time.duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+    let (days, secs) = (secs / 86_400, secs % 86_400);
+    // Counted from 1 March 0000, so that leap days fall at the end of each
+    // year, in eras of 400 years.
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524
+            - day_of_era / 146_096)
+            / 365;
+    let day_of_year =
+        day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month =
+        if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+    )
+}
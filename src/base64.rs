@@ -0,0 +1,73 @@
+use zeroize::{Zeroize, Zeroizing};
+
+// Base64 with the standard alphabet and padding, as in RFC 4648.
+
+const DIGITS: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn encode(bytes: &[u8], out: &mut String) {
+    for chunk in bytes.chunks(3) {
+        let mut group = [0; 3];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let bits = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+        for idx in 0..4 {
+            if idx <= chunk.len() {
+                let digit = (bits >> (18 - 6 * idx)) & 0x3f;
+                out.push(char::from(DIGITS[digit as usize]));
+            } else {
+                out.push('=');
+            }
+        }
+    }
+}
+
+pub fn decode(text: &str) -> Option<Zeroizing<Vec<u8>>> {
+    let text = text.trim_end_matches('=');
+    let mut bytes = Zeroizing::new(Vec::with_capacity(text.len() * 3 / 4));
+    let mut bits = 0u32;
+    let mut num_bits = 0;
+    for &byte in text.as_bytes() {
+        let digit = DIGITS.iter().position(|&digit| digit == byte)?;
+        bits = (bits << 6) | digit as u32;
+        num_bits += 6;
+        if num_bits >= 8 {
+            num_bits -= 8;
+            bytes.push((bits >> num_bits) as u8);
+        }
+    }
+    bits.zeroize();
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The test vectors of RFC 4648, section 10.
+    const VECTORS: &[(&str, &str)] = &[
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy"),
+    ];
+
+    #[test]
+    fn encodes_rfc_4648_vectors() {
+        for (bytes, text) in VECTORS {
+            let mut encoded = String::new();
+            encode(bytes.as_bytes(), &mut encoded);
+            assert_eq!(encoded, *text);
+        }
+    }
+
+    #[test]
+    fn decodes_rfc_4648_vectors() {
+        for (bytes, text) in VECTORS {
+            assert_eq!(decode(text).unwrap().as_slice(), bytes.as_bytes());
+        }
+        assert!(decode("Zm9v!").is_none());
+    }
+}
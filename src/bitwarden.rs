@@ -0,0 +1,139 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use zeroize::Zeroizing;
+
+use crate::base64;
+use crate::i18n::{tr, trf};
+
+// Items are created through bw, the Bitwarden command-line client, which
+// also serves Vaultwarden. It must be logged in with its vault unlocked,
+// usually by exporting the session key bw unlock prints as BW_SESSION. The
+// item passes to bw on standard input as the Base64 encoding of its JSON,
+// as bw encode would give it, never through its arguments.
+
+const BW: &str = "bw";
+// The item type of logins.
+const LOGIN: u8 = 1;
+
+/// A login to create in the vault.
+pub struct LoginItem<'a> {
+    pub name: &'a str,
+    pub username: Option<&'a str>,
+    pub password: &'a str,
+    pub uri: Option<&'a str>,
+    /// A TOTP secret in Base32.
+    pub totp_secret: Option<&'a str>,
+}
+
+pub struct Vault;
+
+#[derive(Deserialize)]
+struct Status {
+    status: String,
+}
+
+// The fields of an item that bw create needs, as bw get template item
+// gives them.
+#[derive(Serialize)]
+struct Item<'a> {
+    #[serde(rename = "type")]
+    kind: u8,
+    name: &'a str,
+    notes: Option<&'a str>,
+    favorite: bool,
+    login: Login<'a>,
+    reprompt: u8,
+}
+
+#[derive(Serialize)]
+struct Login<'a> {
+    uris: Vec<Uri<'a>>,
+    username: Option<&'a str>,
+    password: &'a str,
+    totp: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct Uri<'a> {
+    // How the URI is matched, or null for the vault's default.
+    #[serde(rename = "match")]
+    match_kind: Option<u8>,
+    uri: &'a str,
+}
+
+impl Vault {
+    /// Checks that bw is logged in and its vault unlocked.
+    pub fn open() -> Result<Self> {
+        let output =
+            bw().args(["status"])
+                .stderr(Stdio::inherit())
+                .output()
+                .map_err(could_not_run)?;
+        check_status(output.status)?;
+        let status = serde_json::from_slice::<Status>(&output.stdout)?;
+        match status.status.as_str() {
+            "unlocked" => Ok(Vault),
+            "locked" => {
+                let msg = tr(
+                    "the Bitwarden vault is locked; unlock it with bw unlock \
+                     and export BW_SESSION",
+                );
+                Err(anyhow!(msg))
+            },
+            _ => Err(anyhow!(tr("bw is not logged in; log in with bw login"))),
+        }
+    }
+
+    /// Creates the login item.
+    pub fn create(&self, item: &LoginItem) -> Result<()> {
+        let uris =
+            item.uri.iter().map(|&uri| Uri { match_kind: None, uri });
+        let item = Item {
+            kind: LOGIN,
+            name: item.name,
+            notes: None,
+            favorite: false,
+            login: Login {
+                uris: uris.collect(),
+                username: item.username,
+                password: item.password,
+                totp: item.totp_secret,
+            },
+            reprompt: 0,
+        };
+        let json = Zeroizing::new(serde_json::to_string(&item)?);
+        let mut encoded = Zeroizing::new(String::new());
+        base64::encode(json.as_bytes(), &mut encoded);
+        // bw prints the item it created, password and all.
+        let mut child =
+            bw().args(["create", "item"])
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .spawn()
+                .map_err(could_not_run)?;
+        let mut stdin = child.stdin.take().unwrap();
+        stdin.write_all(encoded.as_bytes())?;
+        drop(stdin);
+        check_status(child.wait()?)
+    }
+}
+
+fn bw() -> Command {
+    let mut command = Command::new(BW);
+    command.arg("--nointeraction");
+    command.stdin(Stdio::null());
+    command
+}
+
+fn could_not_run(err: std::io::Error) -> anyhow::Error {
+    anyhow!(trf("could not run {}: {}", &[&BW, &err]))
+}
+
+fn check_status(status: std::process::ExitStatus) -> Result<()> {
+    if !status.success() {
+        return Err(anyhow!(trf("{} exited with {}", &[&BW, &status])));
+    }
+    Ok(())
+}
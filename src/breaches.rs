@@ -0,0 +1,277 @@
+use anyhow::{anyhow, Context, Result};
+use sha1_smol::Sha1;
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use crate::i18n::{tr, trf};
+
+// Breach lists are Have I Been Pwned's Pwned Passwords dumps ordered by
+// hash, with one line of an uppercase hexadecimal SHA-1 hash, a colon and a
+// count per password, or Bloom filters built from them. Either is mapped
+// into memory when opened, so that lookups work inside the sandbox and only
+// touch the pages they need.
+
+const HASH_HEX_LEN: usize = 40;
+const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+const BLOOM_MAGIC: &[u8; 8] = b"PGBLOOM1";
+// The magic, the number of hash functions and the number of bits.
+const BLOOM_HEADER_LEN: usize = 8 + 8 + 8;
+
+/// Whether a password appears in a breach list.
+pub enum Appearance {
+    Absent,
+    /// Seen this many times.
+    Seen(u64),
+    /// Found in a Bloom filter, which may be a false positive.
+    Likely,
+}
+
+pub struct BreachList {
+    data: Mapping,
+    bloom: Option<Bloom>,
+}
+
+struct Bloom {
+    num_hashes: u64,
+    num_bits: u64,
+}
+
+impl BreachList {
+    /// Maps a dump or a Bloom filter built from one.
+    pub fn open(path: &Path) -> Result<Self> {
+        let context = || trf("could not read \"{}\"", &[&path.display()]);
+        let file = File::open(path).with_context(context)?;
+        let data = Mapping::new(&file).with_context(context)?;
+        let bloom =
+            if data.starts_with(BLOOM_MAGIC) {
+                Some(Bloom::parse(&data)?)
+            } else {
+                None
+            };
+        Ok(BreachList { data, bloom })
+    }
+
+    pub fn lookup(&self, password: &str) -> Result<Appearance> {
+        let digest = Sha1::from(password).digest().bytes();
+        if let Some(bloom) = &self.bloom {
+            let bits = &self.data[BLOOM_HEADER_LEN..];
+            let found =
+                bloom.positions(&digest).all(|pos| {
+                    bits[(pos / 8) as usize] & (1 << (pos % 8)) != 0
+                });
+            let appearance =
+                if found { Appearance::Likely } else { Appearance::Absent };
+            return Ok(appearance);
+        }
+        let mut target = [0u8; HASH_HEX_LEN];
+        for (idx, &byte) in digest.iter().enumerate() {
+            target[idx * 2] = HEX_DIGITS[(byte >> 4) as usize];
+            target[idx * 2 + 1] = HEX_DIGITS[(byte & 0xf) as usize];
+        }
+        // A binary search over byte offsets, comparing the whole line
+        // around each midpoint. The low end is always the start of a line.
+        let data = &self.data[..];
+        let (mut lo, mut hi) = (0, data.len());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let start =
+                data[lo..mid].iter().rposition(|&byte| byte == b'\n')
+                    .map_or(lo, |idx| lo + idx + 1);
+            let end =
+                data[mid..].iter().position(|&byte| byte == b'\n')
+                    .map_or(data.len(), |idx| mid + idx);
+            let line = &data[start..end];
+            let hash = &line[..line.len().min(HASH_HEX_LEN)];
+            match hash.cmp(&target[..]) {
+                Ordering::Less => lo = end + 1,
+                Ordering::Greater => hi = start,
+                Ordering::Equal => {
+                    let count =
+                        std::str::from_utf8(&line[HASH_HEX_LEN..])
+                            .ok()
+                            .and_then(|rest| rest.strip_prefix(':'))
+                            .and_then(|count| count.trim().parse().ok())
+                            .ok_or_else(|| {
+                                anyhow!(tr("the breach list is malformed"))
+                            })?;
+                    return Ok(Appearance::Seen(count));
+                },
+            }
+        }
+        Ok(Appearance::Absent)
+    }
+}
+
+impl Bloom {
+    // Sizes a filter for this many hashes and false positive rate.
+    fn with_capacity(num_items: u64, fp_rate: f64) -> Self {
+        let ln2 = std::f64::consts::LN_2;
+        let num_items = num_items.max(1) as f64;
+        let num_bits = (-num_items * fp_rate.ln() / (ln2 * ln2)).ceil();
+        let num_hashes = (num_bits / num_items * ln2).round().max(1.0);
+        Bloom { num_hashes: num_hashes as u64, num_bits: num_bits as u64 }
+    }
+
+    fn parse(data: &[u8]) -> Result<Self> {
+        let malformed = || anyhow!(tr("the breach list is malformed"));
+        let header = data.get(..BLOOM_HEADER_LEN).ok_or_else(malformed)?;
+        let field = |idx: usize| {
+            let bytes = header[8 + idx * 8..16 + idx * 8].try_into().unwrap();
+            u64::from_le_bytes(bytes)
+        };
+        let bloom = Bloom { num_hashes: field(0), num_bits: field(1) };
+        let len = BLOOM_HEADER_LEN as u64 + bloom.num_bits.div_ceil(8);
+        if bloom.num_hashes == 0 || bloom.num_bits == 0 {
+            return Err(malformed());
+        }
+        if data.len() as u64 != len {
+            return Err(malformed());
+        }
+        Ok(bloom)
+    }
+
+    // Derives the bit positions from two halves of the hash, as in Kirsch
+    // and Mitzenmacher's double hashing. SHA-1 output is uniform enough
+    // that no further hashing is needed.
+    fn positions(&self, digest: &[u8; 20]) -> impl Iterator<Item = u64> {
+        let h1 = u64::from_le_bytes(digest[..8].try_into().unwrap());
+        let h2 = u64::from_le_bytes(digest[8..16].try_into().unwrap()) | 1;
+        let num_bits = self.num_bits;
+        (0..self.num_hashes).map(move |idx| {
+            h1.wrapping_add(idx.wrapping_mul(h2)) % num_bits
+        })
+    }
+}
+
+/// Builds a Bloom filter with the false positive rate from a dump ordered
+/// by hash, returning the number of hashes added.
+pub fn build_bloom(dump: &Path, out: &Path, fp_rate: f64) -> Result<u64> {
+    let read_context = || trf("could not read \"{}\"", &[&dump.display()]);
+    let open = || -> Result<_> {
+        let file = File::open(dump).with_context(read_context)?;
+        Ok(BufReader::new(file).lines())
+    };
+    let mut num_items = 0;
+    for line in open()? {
+        if !line.with_context(read_context)?.trim().is_empty() {
+            num_items += 1;
+        }
+    }
+    let bloom = Bloom::with_capacity(num_items, fp_rate);
+    let mut bits = vec![0u8; bloom.num_bits.div_ceil(8) as usize];
+    for line in open()? {
+        let line = line.with_context(read_context)?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let digest =
+            line.get(..HASH_HEX_LEN)
+                .and_then(parse_hash)
+                .ok_or_else(|| anyhow!(tr("the breach list is malformed")))?;
+        for pos in bloom.positions(&digest) {
+            bits[(pos / 8) as usize] |= 1 << (pos % 8);
+        }
+    }
+    let write_context = || trf("could not write \"{}\"", &[&out.display()]);
+    let write = || -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(out)?);
+        writer.write_all(BLOOM_MAGIC)?;
+        writer.write_all(&bloom.num_hashes.to_le_bytes())?;
+        writer.write_all(&bloom.num_bits.to_le_bytes())?;
+        writer.write_all(&bits)?;
+        writer.flush()
+    };
+    write().with_context(write_context)?;
+    Ok(num_items)
+}
+
+fn parse_hash(hex: &str) -> Option<[u8; 20]> {
+    let mut digest = [0u8; 20];
+    for (idx, byte) in digest.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(idx * 2..idx * 2 + 2)?, 16).ok()?;
+    }
+    Some(digest)
+}
+
+// A read-only view of a whole file.
+#[cfg(unix)]
+struct Mapping {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+#[cfg(unix)]
+impl Mapping {
+    fn new(file: &File) -> io::Result<Self> {
+        use std::os::unix::io::AsRawFd;
+        let len = usize::try_from(file.metadata()?.len()).map_err(|_| {
+            io::Error::new(io::ErrorKind::OutOfMemory, "file too large")
+        })?;
+        if len == 0 {
+            let ptr = std::ptr::null_mut();
+            return Ok(Mapping { ptr, len });
+        }
+        let ptr =
+            unsafe {
+                libc::mmap(
+                    std::ptr::null_mut(),
+                    len,
+                    libc::PROT_READ,
+                    libc::MAP_PRIVATE,
+                    file.as_raw_fd(),
+                    0,
+                )
+            };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Mapping { ptr, len })
+    }
+}
+
+#[cfg(unix)]
+impl std::ops::Deref for Mapping {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        if self.len > 0 {
+            unsafe { libc::munmap(self.ptr, self.len) };
+        }
+    }
+}
+
+// Elsewhere the file is read whole, which suits Bloom filters better than
+// full dumps.
+#[cfg(not(unix))]
+struct Mapping(Vec<u8>);
+
+#[cfg(not(unix))]
+impl Mapping {
+    fn new(file: &File) -> io::Result<Self> {
+        let mut data = Vec::new();
+        io::Read::read_to_end(&mut &*file, &mut data)?;
+        Ok(Mapping(data))
+    }
+}
+
+#[cfg(not(unix))]
+impl std::ops::Deref for Mapping {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
@@ -0,0 +1,104 @@
+use anyhow::{Context, Result};
+use indicatif::ProgressBar;
+use passgen::Generator;
+use rand_core::CryptoRngCore;
+use std::io::Write;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use zeroize::Zeroizing;
+
+use crate::i18n::tr;
+
+// Bulk jobs are generated in batches of passwords one to a line, each by
+// whichever thread claims it, drawing from a generator of its own. Batches
+// are passed to the writer over a channel holding a few for each thread,
+// and threads wait while it is full, so that however slowly the output is
+// read, memory stays flat. The writer makes one write for each batch rather
+// than one for each line.
+
+/// The fewest passwords generated as a bulk job.
+pub const BULK_THRESHOLD: usize = 100_000;
+const BATCH_LEN: usize = 1024;
+const BATCHES_PER_THREAD: usize = 2;
+
+// Passwords and their line endings, and how many there are.
+type Batch = (Zeroizing<Vec<u8>>, usize);
+
+/// Writes the passwords one to a line, generating them on the threads,
+/// each of which passes a function using its generator to `with_rng`.
+pub fn write_lines<W>(
+    out: &mut dyn Write,
+    generator: &(dyn Generator + Sync),
+    count: NonZeroUsize,
+    max_len: usize,
+    threads: usize,
+    progress: &ProgressBar,
+    with_rng: W,
+) -> Result<()>
+where
+    W: Fn(&mut dyn FnMut(&mut dyn CryptoRngCore) -> Result<()>) -> Result<()>
+        + Sync,
+{
+    let remaining = AtomicUsize::new(count.get());
+    // The most a batch may take without growing: each character may take
+    // four bytes.
+    let batch_capacity = BATCH_LEN * (max_len * 4 + 1);
+    let (sender, batches) =
+        mpsc::sync_channel::<Result<Batch>>(threads * BATCHES_PER_THREAD);
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            let sender = sender.clone();
+            let (remaining, with_rng) = (&remaining, &with_rng);
+            let generate = move || {
+                let result = with_rng(&mut |rng| {
+                    let mut password = Zeroizing::new(String::new());
+                    loop {
+                        let count = claim(remaining);
+                        if count == 0 {
+                            return Ok(());
+                        }
+                        let mut lines =
+                            Zeroizing::new(Vec::with_capacity(batch_capacity));
+                        for _ in 0..count {
+                            password.clear();
+                            generator.generate(rng, &mut password)?;
+                            lines.extend_from_slice(password.as_bytes());
+                            lines.push(b'\n');
+                        }
+                        // Fails only once the writer has stopped.
+                        if sender.send(Ok((lines, count))).is_err() {
+                            return Ok(());
+                        }
+                    }
+                });
+                // The writer may already have stopped for an error of its
+                // own.
+                if let Err(err) = result {
+                    let _ = sender.send(Err(err));
+                }
+            };
+            thread::Builder::new()
+                .spawn_scoped(scope, generate)
+                .context(tr("could not start a thread"))?;
+        }
+        // The channel closes once every thread has finished.
+        drop(sender);
+        for batch in batches {
+            let (lines, count) = batch?;
+            out.write_all(&lines)?;
+            progress.inc(count as u64);
+        }
+        Ok(())
+    })
+}
+
+// Claims the next batch's passwords, returning how many it has.
+fn claim(remaining: &AtomicUsize) -> usize {
+    let left =
+        remaining.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |left| {
+            Some(left.saturating_sub(BATCH_LEN))
+        });
+    left.unwrap_or_else(|left| left).min(BATCH_LEN)
+}
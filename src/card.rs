@@ -0,0 +1,132 @@
+use rand_core::CryptoRngCore;
+use std::num::NonZeroUsize;
+
+use crate::charset::Charset;
+use crate::memory::SecretBuf;
+use crate::sampler::sample;
+use crate::Result;
+
+/// The number of rows on a password card, labelled `A` to `Z`.
+pub const CARD_ROWS: usize = 26;
+
+/// A tabula recta for the password card scheme: a grid of random
+/// characters with rows labelled `A` to `Z` and columns numbered from 1,
+/// from which passwords are read along a path starting at a memorised cell.
+pub struct PasswordCard {
+    num_columns: NonZeroUsize,
+    cells: SecretBuf,
+}
+
+impl PasswordCard {
+    /// Fills a card of this many columns with characters drawn from the
+    /// character set.
+    pub fn generate(
+        rng: &mut dyn CryptoRngCore,
+        charset: &Charset,
+        num_columns: NonZeroUsize,
+    ) -> Result<Self> {
+        let mut card = PasswordCard::zeroed(num_columns);
+        sample(rng, charset, &mut card.cells)?;
+        Ok(card)
+    }
+
+    pub(crate) fn zeroed(num_columns: NonZeroUsize) -> Self {
+        let cells = SecretBuf::zeroed(CARD_ROWS * num_columns.get());
+        PasswordCard { num_columns, cells }
+    }
+
+    #[cfg(feature = "derive")]
+    pub(crate) fn cells_mut(&mut self) -> &mut [u8] {
+        &mut self.cells
+    }
+
+    /// Returns the number of columns.
+    pub fn num_columns(&self) -> NonZeroUsize {
+        self.num_columns
+    }
+
+    /// Returns the characters of a row, counting from 0 for `A`.
+    pub fn row(&self, row_idx: usize) -> &[u8] {
+        self.cells.chunks(self.num_columns.get()).nth(row_idx).unwrap_or(&[])
+    }
+
+    fn rows(&self) -> impl Iterator<Item = (char, &[u8])> {
+        ('A'..='Z').zip(self.cells.chunks(self.num_columns.get()))
+    }
+
+    /// Renders the card as plain text, one row per line under a line of
+    /// column numbers.
+    pub fn to_text(&self) -> String {
+        let width = self.num_columns.to_string().len();
+        let mut text = " ".to_string();
+        for column in 1..=self.num_columns.get() {
+            text.push_str(&format!(" {:>width$}", column, width = width));
+        }
+        text.push('\n');
+        for (label, row) in self.rows() {
+            text.push(label);
+            for &byte in row {
+                let cell = char::from(byte);
+                text.push_str(&format!(" {:>width$}", cell, width = width));
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Renders the card as a standalone HTML page laid out for printing.
+    pub fn to_html(&self) -> String {
+        let mut html = String::from(HTML_HEADER);
+        html.push_str("<tr><th></th>");
+        for column in 1..=self.num_columns.get() {
+            html.push_str(&format!("<th>{}</th>", column));
+        }
+        html.push_str("</tr>\n");
+        for (label, row) in self.rows() {
+            html.push_str(&format!("<tr><th>{}</th>", label));
+            for &byte in row {
+                html.push_str("<td>");
+                html.push_str(&escape_html(byte));
+                html.push_str("</td>");
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str(HTML_FOOTER);
+        html
+    }
+}
+
+const HTML_HEADER: &str = "\
+<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Password card</title>
+<style>
+table { border-collapse: collapse; font-family: monospace; font-size: 12pt; }
+th, td { border: 1px solid #888; padding: 2px 5px; text-align: center; }
+tr:nth-child(odd) td { background: #eee; }
+@media print { tr:nth-child(odd) td { print-color-adjust: exact; } }
+</style>
+</head>
+<body>
+<table>
+";
+
+const HTML_FOOTER: &str = "\
+</table>
+</body>
+</html>
+";
+
+fn escape_html(byte: u8) -> String {
+    match byte {
+        b'&' => "&amp;".to_string(),
+        b'<' => "&lt;".to_string(),
+        b'>' => "&gt;".to_string(),
+        b'"' => "&quot;".to_string(),
+        b'\'' => "&#39;".to_string(),
+        b' ' => "&nbsp;".to_string(),
+        _ => char::from(byte).to_string(),
+    }
+}
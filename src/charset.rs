@@ -0,0 +1,285 @@
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+use tracing::debug;
+
+use crate::{invalid_spec, PassgenError, Result};
+
+use self::CharsetParserState::*;
+
+#[derive(Copy, Clone)]
+enum CharsetParserState {
+    Start,
+    Char(u8),
+    Escape,
+    Range(u8),
+    RangeEscape(u8),
+}
+
+const HYPHEN: u8 = 45;
+const BACKSLASH: u8 = 92;
+const CARET: u8 = 94;
+
+const TYPEABLE: u128 = 0x7fff_ffff_ffff_ffff_ffff_ffff_0000_0000;
+
+pub(crate) fn typeable_charset() -> Vec<u8> {
+    chars_of(TYPEABLE)
+}
+
+pub(crate) fn is_typeable(byte: u8) -> bool {
+    byte < 128 && TYPEABLE & (1 << byte) != 0
+}
+
+// Returns the bytes whose bits are set in an ASCII bitmask, in order.
+pub(crate) fn chars_of(mask: u128) -> Vec<u8> {
+    (0..128).filter(|&byte| mask & (1 << byte) != 0).collect()
+}
+
+/// A non-empty set of typeable ASCII characters, dereferencing to its
+/// characters in ascending order.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Charset {
+    chars: Vec<u8>,
+}
+
+impl Charset {
+    /// Creates a character set from these characters, failing if there are
+    /// none or any is not typeable ASCII. Duplicates are ignored.
+    pub fn new(chars: impl Into<Vec<u8>>) -> Result<Self> {
+        let mut chars = chars.into();
+        chars.sort_unstable();
+        chars.dedup();
+        if chars.is_empty() {
+            return Err(invalid_spec("character set is empty"));
+        }
+        if !chars.iter().all(|&byte| is_typeable(byte)) {
+            return Err(invalid_spec("found untypeable or non-ASCII character"));
+        }
+        Ok(Charset { chars })
+    }
+
+    /// Parses a charset specification. See [`parse_charset_spec`].
+    pub fn parse(charset_spec: &str) -> Result<Self> {
+        parse_charset_spec(charset_spec)
+    }
+
+    /// Returns the typeable ASCII characters, from space to tilde.
+    pub fn typeable() -> Self {
+        Charset { chars: typeable_charset() }
+    }
+
+    /// Returns the characters in ascending order.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.chars
+    }
+
+    /// Returns this character set without the given characters, failing if
+    /// none would remain.
+    pub fn without(&self, excluded: &[u8]) -> Result<Self> {
+        let mut chars = self.chars.clone();
+        chars.retain(|byte| !excluded.contains(byte));
+        Charset::new(chars)
+    }
+}
+
+impl Deref for Charset {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.chars
+    }
+}
+
+impl AsRef<[u8]> for Charset {
+    fn as_ref(&self) -> &[u8] {
+        &self.chars
+    }
+}
+
+impl FromStr for Charset {
+    type Err = PassgenError;
+
+    fn from_str(charset_spec: &str) -> Result<Self> {
+        parse_charset_spec(charset_spec)
+    }
+}
+
+impl fmt::Display for Charset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", format_charset_spec(&self.chars))
+    }
+}
+
+/// Parses a charset specification into a character set.
+///
+/// The specification language is a subset of the character set language for
+/// regular expressions: characters and ranges, with literal hyphens and
+/// backslashes escaped, and an initial caret to invert the set with respect
+/// to typeable characters.
+pub fn parse_charset_spec(charset_spec: &str) -> Result<Charset> {
+    fn err_escape_hyphen() -> Result<Charset> {
+        Err(invalid_spec("hyphens must be escaped"))
+    }
+    fn err_invalid_escape(byte: u8) -> Result<Charset> {
+        let msg = format!("invalid escape sequence: \"\\{}\"", byte as char);
+        Err(invalid_spec(msg))
+    }
+    if charset_spec.is_empty() {
+        return Err(invalid_spec("empty charset specification"));
+    }
+    let bytes = charset_spec.as_bytes();
+    let invert = bytes[0] == CARET;
+    let mut bytes = bytes.iter();
+    if invert {
+        bytes.next();
+    }
+    let mut state = Start;
+    let mut result = 0u128;
+    for &byte in bytes {
+        if !is_typeable(byte) {
+            return Err(invalid_spec("found untypeable or non-ASCII character"));
+        }
+        match (state, byte) {
+            (Start, HYPHEN) => {
+                return err_escape_hyphen();
+            },
+            (Start, BACKSLASH) => {
+                state = Escape;
+            },
+            (Start, byte) => {
+                result |= 1 << byte;
+                state = Char(byte);
+            },
+            (Char(prev), HYPHEN) => {
+                state = Range(prev);
+            },
+            (Char(_), BACKSLASH) => {
+                state = Escape;
+            },
+            (Char(_), byte) => {
+                result |= 1 << byte;
+                state = Char(byte);
+            },
+            (Escape, byte) => {
+                if byte != HYPHEN && byte != BACKSLASH {
+                    return err_invalid_escape(byte);
+                }
+                result |= 1 << byte;
+                state = Char(byte);
+            },
+            (Range(_), HYPHEN) => {
+                return err_escape_hyphen();
+            },
+            (Range(start), BACKSLASH) => {
+                state = RangeEscape(start);
+            },
+            (Range(start), end) => {
+                for byte in (start + 1)..=end {
+                    result |= 1 << byte;
+                }
+                state = Start;
+            },
+            (RangeEscape(start), end) => {
+                if byte != HYPHEN && byte != BACKSLASH {
+                    return err_invalid_escape(byte);
+                }
+                for byte in (start + 1)..=end {
+                    result |= 1 << byte;
+                }
+                state = Start;
+            }
+        }
+    }
+    match state {
+        Escape | RangeEscape(_) =>
+            Err(invalid_spec("unterminated escape sequence")),
+        Range(_) =>
+            Err(invalid_spec("unterminated character range")),
+        _ => {
+            if invert {
+                result = TYPEABLE & !result;
+            }
+            if result == 0 {
+                return Err(invalid_spec("character set is empty"))
+            }
+            debug!(
+                charset_spec = %charset_spec,
+                invert,
+                size = result.count_ones(),
+                "parsed charset specification",
+            );
+            Ok(Charset { chars: chars_of(result) })
+        }
+    }
+}
+
+/// Formats a sorted character set as the shortest charset specification
+/// that parses back to it.
+pub fn format_charset_spec(charset: &[u8]) -> String {
+    fn push_byte(spec: &mut String, byte: u8) {
+        if byte == HYPHEN || byte == BACKSLASH {
+            spec.push('\\');
+        }
+        spec.push(byte as char);
+    }
+    let mut groups = Vec::<(u8, u8)>::new();
+    for &byte in charset {
+        match groups.last_mut() {
+            Some((_, end)) if *end + 1 == byte => {
+                *end = byte;
+            },
+            _ => {
+                groups.push((byte, byte));
+            },
+        }
+    }
+    if let Some(&(CARET, _)) = groups.first() {
+        if groups.len() == 1 {
+            let typeable = typeable_charset();
+            let complement =
+                typeable.into_iter()
+                    .filter(|byte| !charset.contains(byte))
+                    .collect::<Vec<_>>();
+            return format!("^{}", format_charset_spec(&complement));
+        }
+        groups.rotate_left(1);
+    }
+    let mut spec = String::new();
+    for (start, end) in groups {
+        push_byte(&mut spec, start);
+        if end > start + 1 {
+            spec.push('-');
+        }
+        if end > start {
+            push_byte(&mut spec, end);
+        }
+    }
+    spec
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(charset_spec: &str) -> Vec<u8> {
+        parse_charset_spec(charset_spec).unwrap().to_vec()
+    }
+
+    // A range starts at the character just before the hyphen, not at the
+    // start of the run of characters it ends.
+    #[test]
+    fn ranges_start_after_runs() {
+        assert_eq!(parse("_a-c"), b"_abc");
+        assert_eq!(parse("xya-c"), b"abcxy");
+        assert_eq!(parse("\\-a-c"), b"-abc");
+        assert_eq!(parse("a-cx-z"), b"abcxyz");
+    }
+
+    #[test]
+    fn formatted_specs_parse_back() {
+        for charset_spec in ["_a-c", "0-9A-Z\\-", "^a-z", "!#%", "\\\\"] {
+            let chars = parse(charset_spec);
+            assert_eq!(parse(&format_charset_spec(&chars)), chars);
+        }
+    }
+}
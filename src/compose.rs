@@ -0,0 +1,254 @@
+use rand_core::CryptoRngCore;
+use std::fmt;
+use std::num::NonZeroUsize;
+
+use crate::generator::Generator;
+use crate::policy::CharClass;
+use crate::sampler::random_below;
+use crate::{invalid_spec, Result};
+
+// Each character is drawn from a class chosen with its share, then
+// uniformly from the class, so that the classes make up a password in
+// their shares on average and in no set order. Characters are therefore
+// not equally likely, and the entropy is worked out from the probability of
+// each.
+
+const CLASS_NAMES: &[&str] =
+    &["lowercase", "uppercase", "letters", "digits", "symbols"];
+
+/// Generates passwords of a random length in a range whose characters come
+/// from classes in given proportions on average.
+#[derive(Clone, Debug)]
+pub struct ComposedGenerator {
+    // The classes by name, with their characters and percentage shares.
+    parts: Vec<(&'static str, Vec<u8>, u32)>,
+    min_len: NonZeroUsize,
+    max_len: NonZeroUsize,
+}
+
+impl ComposedGenerator {
+    /// Parses a composition such as `letters:60%,digits:25%,symbols:15%`,
+    /// of the classes lowercase, uppercase, letters, digits and symbols
+    /// with whole percentages adding up to 100, failing if it is malformed
+    /// or the lengths are out of order.
+    pub fn new(
+        composition: &str,
+        min_len: NonZeroUsize,
+        max_len: NonZeroUsize,
+    ) -> Result<Self> {
+        if min_len > max_len {
+            return Err(invalid_spec("minimum length exceeds maximum length"));
+        }
+        let mut parts = Vec::<(&str, Vec<u8>, u32)>::new();
+        for part in composition.split(',') {
+            let Some((name, share)) = part.trim().split_once(':') else {
+                let msg = "composition parts must be class:share";
+                return Err(invalid_spec(msg));
+            };
+            let Some(&name) =
+                CLASS_NAMES.iter().find(|&&known| known == name.trim())
+            else {
+                return Err(invalid_spec("unknown class in composition"));
+            };
+            let share = share.trim();
+            let share = share.strip_suffix('%').unwrap_or(share);
+            let share =
+                match share.parse::<u32>() {
+                    Ok(share) if share > 0 => share,
+                    _ => {
+                        let msg = "composition shares must be whole \
+                                   percentages above 0";
+                        return Err(invalid_spec(msg));
+                    },
+                };
+            if parts.iter().any(|&(other, _, _)| other == name) {
+                return Err(invalid_spec("class repeated in composition"));
+            }
+            parts.push((name, class_chars(name), share));
+        }
+        if parts.iter().map(|&(_, _, share)| share).sum::<u32>() != 100 {
+            let msg = "composition shares must add up to 100%";
+            return Err(invalid_spec(msg));
+        }
+        Ok(ComposedGenerator { parts, min_len, max_len })
+    }
+
+    /// Returns the minimum password length.
+    pub fn min_length(&self) -> NonZeroUsize {
+        self.min_len
+    }
+
+    /// Returns the maximum password length.
+    pub fn max_length(&self) -> NonZeroUsize {
+        self.max_len
+    }
+
+    /// Returns the average entropy in bits of the shortest passwords, which
+    /// is more than that of the likeliest.
+    pub fn average_entropy_bits(&self) -> f64 {
+        let per_char =
+            self.char_probabilities()
+                .iter()
+                .filter(|&&prob| prob > 0.0)
+                .map(|&prob| -prob * prob.log2())
+                .sum::<f64>();
+        self.min_len.get() as f64 * per_char
+    }
+
+    // The probability of drawing each ASCII character, summed over the
+    // classes it is in.
+    fn char_probabilities(&self) -> [f64; 128] {
+        let mut probs = [0.0; 128];
+        for (_, chars, share) in &self.parts {
+            let prob = f64::from(*share) / 100.0 / chars.len() as f64;
+            for &byte in chars {
+                probs[usize::from(byte)] += prob;
+            }
+        }
+        probs
+    }
+}
+
+impl fmt::Display for ComposedGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, (name, _, share)) in self.parts.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}:{}%", name, share)?;
+        }
+        Ok(())
+    }
+}
+
+impl Generator for ComposedGenerator {
+    fn generate(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+    ) -> Result<()> {
+        let min_len = self.min_len.get();
+        let range = self.max_len.get() - min_len + 1;
+        let password_len = min_len + random_below(rng, range)?;
+        for _ in 0..password_len {
+            let mut point = random_below(rng, 100)? as u32;
+            for (_, chars, share) in &self.parts {
+                if point < *share {
+                    let byte = chars[random_below(rng, chars.len())?];
+                    out.push(char::from(byte));
+                    break;
+                }
+                point -= share;
+            }
+        }
+        Ok(())
+    }
+
+    // The likeliest password of the shortest length has the likeliest
+    // character throughout.
+    fn entropy_bits(&self) -> f64 {
+        let likeliest =
+            self.char_probabilities().into_iter().fold(0.0, f64::max);
+        -(self.min_len.get() as f64) * likeliest.log2()
+    }
+}
+
+fn class_chars(name: &str) -> Vec<u8> {
+    match name {
+        "lowercase" => CharClass::Lowercase.chars(),
+        "uppercase" => CharClass::Uppercase.chars(),
+        "letters" => {
+            let mut chars = CharClass::Uppercase.chars();
+            chars.extend(CharClass::Lowercase.chars());
+            chars
+        },
+        "digits" => CharClass::Digits.chars(),
+        _ => CharClass::Symbols.chars(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::ChaCha20Rng;
+    use rand::SeedableRng;
+
+    fn composed(composition: &str, min: usize, max: usize) -> Result<String> {
+        let min = NonZeroUsize::new(min).unwrap();
+        let max = NonZeroUsize::new(max).unwrap();
+        ComposedGenerator::new(composition, min, max).map(|g| g.to_string())
+    }
+
+    #[test]
+    fn compositions_are_parsed() {
+        let composition = "letters:60%,digits:25%,symbols:15%";
+        assert_eq!(composed(composition, 8, 8).unwrap(), composition);
+        let spaced = " lowercase : 50 , uppercase:50% ";
+        let normal = "lowercase:50%,uppercase:50%";
+        assert_eq!(composed(spaced, 8, 8).unwrap(), normal);
+    }
+
+    #[test]
+    fn malformed_compositions_are_rejected() {
+        for composition in [
+            "letters",
+            "letters:100%,",
+            "vowels:100%",
+            "letters:0%,digits:100%",
+            "letters:60.5%,digits:39.5%",
+            "letters:-10%,digits:110%",
+            "letters:50%,letters:50%",
+            "letters:60%,digits:30%",
+            "letters:60%,digits:50%",
+        ] {
+            assert!(composed(composition, 8, 8).is_err(), "{}", composition);
+        }
+        assert!(composed("digits:100%", 9, 8).is_err());
+    }
+
+    #[test]
+    fn characters_come_from_the_classes_in_their_shares() {
+        let min = NonZeroUsize::new(10).unwrap();
+        let max = NonZeroUsize::new(14).unwrap();
+        let composition = "uppercase:70%,digits:30%";
+        let generator = ComposedGenerator::new(composition, min, max).unwrap();
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let mut lengths = [0; 15];
+        let (mut total, mut upper, mut digits) = (0, 0, 0);
+        for _ in 0..1000 {
+            let mut out = String::new();
+            generator.generate(&mut rng, &mut out).unwrap();
+            lengths[out.len()] += 1;
+            total += out.len();
+            upper += out.bytes().filter(u8::is_ascii_uppercase).count();
+            digits += out.bytes().filter(u8::is_ascii_digit).count();
+            assert_eq!(upper + digits, total);
+        }
+        assert!(lengths[..10].iter().all(|&count| count == 0));
+        assert!(lengths[10..].iter().all(|&count| count > 150));
+        let share = upper as f64 / (upper + digits) as f64;
+        assert!((share - 0.7).abs() < 0.02, "{}", share);
+    }
+
+    #[test]
+    fn entropy_follows_the_character_probabilities() {
+        let len = NonZeroUsize::new(10).unwrap();
+        let even = "lowercase:50%,uppercase:50%";
+        let generator = ComposedGenerator::new(even, len, len).unwrap();
+        let expected = 10.0 * 52f64.log2();
+        assert!((generator.entropy_bits() - expected).abs() < 1e-9);
+        assert!((generator.average_entropy_bits() - expected).abs() < 1e-9);
+        // Lowercase letters are in both classes, so each is three times as
+        // likely as an uppercase one.
+        let uneven = "lowercase:50%,letters:50%";
+        let generator = ComposedGenerator::new(uneven, len, len).unwrap();
+        let lower = 0.75 / 26.0_f64;
+        let upper = 0.25 / 26.0_f64;
+        let expected = -10.0 * lower.log2();
+        assert!((generator.entropy_bits() - expected).abs() < 1e-9);
+        let per_char = -26.0 * (lower * lower.log2() + upper * upper.log2());
+        let average = generator.average_entropy_bits();
+        assert!((average - 10.0 * per_char).abs() < 1e-9);
+        assert!(average > generator.entropy_bits());
+    }
+}
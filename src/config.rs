@@ -0,0 +1,155 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::confusables;
+use crate::generators;
+use crate::profiles::Profiles;
+use crate::pwquality::Settings;
+
+// Configuration files are validated by parsing them as the commands reading
+// them do, which reject unknown fields and settings, so that what passes
+// here is read the same way there. Where the parser knows it, a problem is
+// reported at its line and column.
+
+/// A file passgen reads settings from.
+#[derive(Clone, Copy)]
+pub enum ConfigFile {
+    Profiles,
+    Generators,
+    Confusables,
+    Pwquality,
+}
+
+/// What is wrong with a file, and where.
+pub struct Problem {
+    line: Option<usize>,
+    column: Option<usize>,
+    message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "{}:", line)?;
+        }
+        if let Some(column) = self.column {
+            write!(f, "{}:", column)?;
+        }
+        write!(f, " {}", self.message)
+    }
+}
+
+/// Reads and parses the file, returning the first problem with it.
+pub fn validate(kind: ConfigFile, path: &Path) -> Option<Problem> {
+    let text =
+        match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) => return Some(problem(None, &err.into())),
+        };
+    let result =
+        match kind {
+            ConfigFile::Profiles => Profiles::from_json(&text).map(drop),
+            ConfigFile::Generators => generators::register(&text),
+            ConfigFile::Confusables => confusables::parse(&text).map(drop),
+            ConfigFile::Pwquality => {
+                return Settings::parse(&text)
+                    .err()
+                    .map(|(line, err)| problem(Some(line), &err));
+            },
+        };
+    result.err().map(|err| problem(None, &err))
+}
+
+fn problem(line: Option<usize>, err: &anyhow::Error) -> Problem {
+    let message = format!("{:#}", err);
+    match err.downcast_ref::<serde_json::Error>() {
+        Some(json_err) if json_err.line() > 0 => {
+            // serde_json ends its messages with the location, given here
+            // before them instead.
+            let (line, column) = (json_err.line(), json_err.column());
+            let location = format!(" at line {} column {}", line, column);
+            let message =
+                message.strip_suffix(&location).unwrap_or(&message).to_string();
+            Problem { line: Some(line), column: Some(column), message }
+        },
+        _ => Problem { line, column: None, message },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    // Each test writes its own file, since tests run concurrently.
+    fn validate_text(
+        kind: ConfigFile,
+        name: &str,
+        text: &str,
+    ) -> Option<Problem> {
+        let name = format!("passgen-{}-{}", std::process::id(), name);
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, text).unwrap();
+        let problem = validate(kind, &path);
+        fs::remove_file(&path).unwrap();
+        problem
+    }
+
+    #[test]
+    fn valid_files_have_no_problem() {
+        let profiles = r#"{"profiles": []}"#;
+        let problem =
+            validate_text(ConfigFile::Profiles, "valid-profiles", profiles);
+        assert!(problem.is_none());
+        let problem = validate_text(ConfigFile::Generators, "valid-gen", "{}");
+        assert!(problem.is_none());
+        let confusables = r#"{"ocr": "0O1l"}"#;
+        let problem =
+            validate_text(ConfigFile::Confusables, "valid-conf", confusables);
+        assert!(problem.is_none());
+        let pwquality = "# comment\nminlen = 12\n";
+        let problem =
+            validate_text(ConfigFile::Pwquality, "valid-pwq", pwquality);
+        assert!(problem.is_none());
+    }
+
+    #[test]
+    fn json_problems_are_located() {
+        let profiles = "{\n  \"profiles\": [],\n  \"colour\": 1\n}";
+        let problem =
+            validate_text(ConfigFile::Profiles, "bad-profiles", profiles)
+                .unwrap();
+        assert_eq!((problem.line, problem.column), (Some(3), Some(10)));
+        assert!(problem.message.contains("colour"));
+        assert!(!problem.message.contains(" at line "));
+        assert!(problem.to_string().starts_with("3:10: "));
+        let generators = "{\"x\": {\"pattern\": 5}}";
+        let problem =
+            validate_text(ConfigFile::Generators, "bad-gen", generators)
+                .unwrap();
+        assert_eq!(problem.line, Some(1));
+        let problem =
+            validate_text(ConfigFile::Confusables, "bad-conf", "{\"ocr\": 0")
+                .unwrap();
+        assert_eq!(problem.line, Some(1));
+    }
+
+    #[test]
+    fn pwquality_problems_have_a_line_only() {
+        let pwquality = "minlen = 12\n\ncolour = 1\n";
+        let problem =
+            validate_text(ConfigFile::Pwquality, "bad-pwq", pwquality)
+                .unwrap();
+        assert_eq!((problem.line, problem.column), (Some(3), None));
+        assert!(problem.to_string().starts_with("3: "));
+    }
+
+    #[test]
+    fn unreadable_files_have_no_location() {
+        let path = PathBuf::from("/nonexistent/passgen/profiles.json");
+        let problem = validate(ConfigFile::Profiles, &path).unwrap();
+        assert_eq!((problem.line, problem.column), (None, None));
+        assert!(problem.to_string().starts_with(' '));
+    }
+}
@@ -0,0 +1,73 @@
+use anyhow::{anyhow, Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::trf;
+use crate::profiles::Profiles;
+
+// Each profile names the characters most often misread for one another in a
+// medium, all of which it excludes. More can be defined, or the built-in
+// ones replaced, in a JSON object from each name to its characters, as in
+// {"terminal": "0O1l"}.
+
+const BUILT_IN: &[(&str, &str)] = &[
+    // Capital I, lowercase L, one and the bar look alike in many sans-serif
+    // fonts, as do capital O and zero.
+    ("sans-serif", "Il1|O0"),
+    // Read back from a form printed or filled in by hand in capitals, or by
+    // OCR: shapes that differ by a stroke or a curve, and small marks.
+    ("printed-form", "0OQD1Il|2Z5S8B6GUV`',.;:"),
+    // Handwriting blurs the same shapes further, and the case of letters
+    // written the same way in both.
+    (
+        "handwritten",
+        "0OoD1lI7|2Zz5Ss6bG9gqUuVvCcKkPpWwXx-_~`',.;:\"",
+    ),
+];
+
+/// Returns the confusables file beside the profiles file.
+pub fn default_path() -> Option<PathBuf> {
+    Profiles::default_path()
+        .map(|path| path.with_file_name("confusables.json"))
+}
+
+/// Returns the characters the named profiles exclude, reading any defined
+/// in the file over the built-in ones and treating a missing file as
+/// defining none.
+pub fn excluded(names: &[String], path: Option<&Path>) -> Result<String> {
+    let mut profiles =
+        BUILT_IN.iter()
+            .map(|&(name, chars)| (name.to_string(), chars.to_string()))
+            .collect::<BTreeMap<_, _>>();
+    if let Some(path) = path {
+        profiles.extend(load(path)?);
+    }
+    let mut excluded = String::new();
+    for name in names {
+        let chars = profiles.get(name).ok_or_else(|| {
+            anyhow!(trf("unknown confusables profile: \"{}\"", &[name]))
+        })?;
+        excluded.push_str(chars);
+    }
+    Ok(excluded)
+}
+
+fn load(path: &Path) -> Result<BTreeMap<String, String>> {
+    let context = || trf("could not read \"{}\"", &[&path.display()]);
+    let json =
+        match fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Ok(BTreeMap::new());
+            },
+            Err(err) => return Err(err).with_context(context),
+        };
+    parse(&json).with_context(context)
+}
+
+/// Parses a JSON object from each profile's name to its characters.
+pub fn parse(json: &str) -> Result<BTreeMap<String, String>> {
+    Ok(serde_json::from_str(json)?)
+}
@@ -0,0 +1,63 @@
+// Windows consoles read the bytes programs write in the console's code
+// page, by default an OEM one with few symbols and no other scripts, so
+// that passwords piped through another program to the console, as to more,
+// or printed by the programs passgen runs, show garbled. The console is
+// switched to UTF-8, in which passgen writes everything, while it runs.
+// Standard output and error written to the console directly are unaffected,
+// since Rust writes them with the console's wide character API. Elsewhere
+// terminals are taken to be UTF-8 already.
+
+#[cfg(windows)]
+const CP_UTF8: u32 = 65001;
+
+/// Switches the console to UTF-8 until dropped.
+#[cfg(windows)]
+pub struct Utf8Console {
+    input: u32,
+    output: u32,
+}
+
+#[cfg(windows)]
+impl Utf8Console {
+    pub fn new() -> Self {
+        use windows_sys::Win32::System::Console::{
+            GetConsoleCP, GetConsoleOutputCP, SetConsoleCP, SetConsoleOutputCP,
+        };
+        // Without a console, these return 0 and the switch fails, leaving
+        // nothing to restore.
+        let input = unsafe { GetConsoleCP() };
+        let output = unsafe { GetConsoleOutputCP() };
+        unsafe {
+            SetConsoleCP(CP_UTF8);
+            SetConsoleOutputCP(CP_UTF8);
+        }
+        Utf8Console { input, output }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for Utf8Console {
+    fn drop(&mut self) {
+        use windows_sys::Win32::System::Console::{
+            SetConsoleCP, SetConsoleOutputCP,
+        };
+        unsafe {
+            if self.input != 0 {
+                SetConsoleCP(self.input);
+            }
+            if self.output != 0 {
+                SetConsoleOutputCP(self.output);
+            }
+        }
+    }
+}
+
+#[cfg(not(windows))]
+pub struct Utf8Console;
+
+#[cfg(not(windows))]
+impl Utf8Console {
+    pub fn new() -> Self {
+        Utf8Console
+    }
+}
@@ -0,0 +1,150 @@
+use anyhow::Result;
+use rand::RngCore;
+use sha2::{Digest, Sha512};
+use zeroize::Zeroizing;
+
+// SHA-512 crypt as Ulrich Drepper specified it, the $6$ scheme every libc
+// crypt supports, so that chpasswd -e and newusers can take its hashes
+// wherever they run. The rounds are left at the scheme's default of 5000,
+// which the hash then need not state.
+
+const ALPHABET: &[u8; 64] =
+    b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+const SALT_LEN: usize = 16;
+const ROUNDS: usize = 5000;
+
+// The order the bytes of the digest are encoded in, three at a time.
+const DIGEST_ORDER: [[usize; 3]; 21] = [
+    [0, 21, 42], [22, 43, 1], [44, 2, 23], [3, 24, 45], [25, 46, 4],
+    [47, 5, 26], [6, 27, 48], [28, 49, 7], [50, 8, 29], [9, 30, 51],
+    [31, 52, 10], [53, 11, 32], [12, 33, 54], [34, 55, 13], [56, 14, 35],
+    [15, 36, 57], [37, 58, 16], [59, 17, 38], [18, 39, 60], [40, 61, 19],
+    [62, 20, 41],
+];
+
+/// Hashes the password with a random salt, giving the hash as crypt
+/// writes it.
+pub fn sha512_crypt(password: &str, rng: &mut impl RngCore) -> Result<String> {
+    let mut salt = [0; SALT_LEN];
+    rng.try_fill_bytes(&mut salt)?;
+    for byte in &mut salt {
+        *byte = ALPHABET[usize::from(*byte % 64)];
+    }
+    Ok(crypt(password, &salt))
+}
+
+// Hashes the password with the salt, which is at most 16 characters of the
+// alphabet.
+fn crypt(password: &str, salt: &[u8]) -> String {
+    let digest = hash(password.as_bytes(), salt);
+    let mut out = String::from("$6$");
+    out.extend(salt.iter().map(|&byte| char::from(byte)));
+    out.push('$');
+    for [first, second, third] in DIGEST_ORDER {
+        let bytes = [digest[first], digest[second], digest[third]];
+        encode(&bytes, 4, &mut out);
+    }
+    encode(&[0, 0, digest[63]], 2, &mut out);
+    out
+}
+
+fn hash(password: &[u8], salt: &[u8]) -> Zeroizing<[u8; 64]> {
+    let len = password.len();
+    let alternate =
+        Sha512::new()
+            .chain_update(password)
+            .chain_update(salt)
+            .chain_update(password)
+            .finalize();
+    let alternate = Zeroizing::new(<[u8; 64]>::from(alternate));
+    let mut hasher = Sha512::new();
+    hasher.update(password);
+    hasher.update(salt);
+    update_repeated(&mut hasher, &*alternate, len);
+    let mut bits = len;
+    while bits > 0 {
+        if bits & 1 == 1 {
+            hasher.update(&alternate[..]);
+        } else {
+            hasher.update(password);
+        }
+        bits >>= 1;
+    }
+    let mut digest = Zeroizing::new(<[u8; 64]>::from(hasher.finalize()));
+    let mut hasher = Sha512::new();
+    for _ in 0..len {
+        hasher.update(password);
+    }
+    let password_digest = Zeroizing::new(<[u8; 64]>::from(hasher.finalize()));
+    let mut hasher = Sha512::new();
+    for _ in 0..16 + usize::from(digest[0]) {
+        hasher.update(salt);
+    }
+    let salt_digest = <[u8; 64]>::from(hasher.finalize());
+    let salt_digest = &salt_digest[..salt.len()];
+    for round in 0..ROUNDS {
+        let mut hasher = Sha512::new();
+        if round % 2 == 1 {
+            update_repeated(&mut hasher, &*password_digest, len);
+        } else {
+            hasher.update(&digest[..]);
+        }
+        if round % 3 != 0 {
+            hasher.update(salt_digest);
+        }
+        if round % 7 != 0 {
+            update_repeated(&mut hasher, &*password_digest, len);
+        }
+        if round % 2 == 1 {
+            hasher.update(&digest[..]);
+        } else {
+            update_repeated(&mut hasher, &*password_digest, len);
+        }
+        *digest = <[u8; 64]>::from(hasher.finalize());
+    }
+    digest
+}
+
+// Hashes the first len bytes of the bytes repeated end to end.
+fn update_repeated(hasher: &mut Sha512, bytes: &[u8], len: usize) {
+    let mut remaining = len;
+    while remaining > 0 {
+        let num_taken = remaining.min(bytes.len());
+        hasher.update(&bytes[..num_taken]);
+        remaining -= num_taken;
+    }
+}
+
+// Encodes the three bytes, the last the least significant, as their low
+// characters, least significant first.
+fn encode(bytes: &[u8; 3], num_chars: usize, out: &mut String) {
+    let mut word =
+        u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8
+            | u32::from(bytes[2]);
+    for _ in 0..num_chars {
+        out.push(char::from(ALPHABET[(word & 0x3f) as usize]));
+        word >>= 6;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test vectors from "Unix crypt using SHA-256 and SHA-512", for the
+    // default rounds. The second salt is the spec's "toolongsaltstring"
+    // cut to 16 characters, as crypt cuts it.
+    #[test]
+    fn matches_spec_vectors() {
+        assert_eq!(
+            crypt("Hello world!", b"saltstring"),
+            "$6$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJ\
+             uesI68u4OTLiBFdcbYEdFCoEOfaS35inz1",
+        );
+        assert_eq!(
+            crypt("This is just a test", b"toolongsaltstrin"),
+            "$6$toolongsaltstrin$lQ8jolhgVRVhY4b5pZKaysCLi0QBxGoNeKQzQ3glMhwll\
+             F7oGDZxUhx1yxdYcz/e1JSbq3y6JMxxl8audkUEm0",
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use tracing::debug;
+use zeroize::Zeroizing;
+
+use crate::i18n::trf;
+
+// Requests arrive over a Unix socket only its owner can connect to, as lines
+// of JSON each answered by a line of JSON. Every connection has a thread
+// reading its requests, which it passes to the thread that owns the random
+// number generator, so that requests are answered one at a time without
+// anything having to be shared between threads.
+
+// Far more than any request needs.
+const MAX_LINE_LEN: usize = 64 * 1024;
+
+type Request = (Zeroizing<Vec<u8>>, Sender<Zeroizing<String>>);
+
+/// Listens on the socket, replacing it if no daemon is still listening on
+/// it.
+pub fn bind(path: &Path) -> Result<UnixListener> {
+    if let Ok(metadata) = fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            let path = path.display();
+            let msg = trf("\"{}\" exists and is not a socket", &[&path]);
+            return Err(anyhow!(msg));
+        }
+        if UnixStream::connect(path).is_ok() {
+            let path = path.display();
+            let msg = trf("a daemon is already listening on \"{}\"", &[&path]);
+            return Err(anyhow!(msg));
+        }
+        fs::remove_file(path).with_context(|| {
+            trf("could not remove \"{}\"", &[&path.display()])
+        })?;
+    }
+    // The socket is created with mode 0600 rather than changed to it, so
+    // that no other user can connect in between.
+    let umask = unsafe { libc::umask(0o177) };
+    let listener = UnixListener::bind(path);
+    unsafe { libc::umask(umask) };
+    listener.with_context(|| {
+        trf("could not listen on \"{}\"", &[&path.display()])
+    })
+}
+
+/// Serves requests forever, answering each line with the line the handler
+/// gives for it.
+pub fn serve(
+    listener: UnixListener,
+    mut handler: impl FnMut(&[u8]) -> Result<Zeroizing<String>>,
+) -> Result<()> {
+    let (sender, requests) = mpsc::channel::<Request>();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream =
+                match stream {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        debug!(%err, "could not accept a connection");
+                        continue;
+                    },
+                };
+            let sender = sender.clone();
+            thread::spawn(move || {
+                if let Err(err) = read_requests(&stream, &sender) {
+                    debug!(%err, "could not serve a connection");
+                }
+            });
+        }
+    });
+    for (line, reply) in requests {
+        // The connection may have closed while waiting.
+        let _ = reply.send(handler(&line)?);
+    }
+    Ok(())
+}
+
+// Passes on each request on the connection, writing back its response,
+// until the connection is closed.
+fn read_requests(
+    mut stream: &UnixStream,
+    sender: &Sender<Request>,
+) -> io::Result<()> {
+    let mut chunk = Zeroizing::new([0; 1024]);
+    // Never reallocated, so that no copies of requests are left.
+    let mut buf =
+        Zeroizing::new(Vec::with_capacity(MAX_LINE_LEN + chunk.len()));
+    loop {
+        while let Some(pos) = buf.iter().position(|&byte| byte == b'\n') {
+            let line = Zeroizing::new(buf[..pos].to_vec());
+            buf.drain(..=pos);
+            let (reply, response) = mpsc::channel();
+            if sender.send((line, reply)).is_err() {
+                return Ok(());
+            }
+            let Ok(response) = response.recv() else {
+                return Ok(());
+            };
+            stream.write_all(response.as_bytes())?;
+            stream.write_all(b"\n")?;
+        }
+        if buf.len() > MAX_LINE_LEN {
+            let msg = format!("request of over {} bytes", MAX_LINE_LEN);
+            return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+        }
+        let num_read = stream.read(&mut chunk[..])?;
+        if num_read == 0 {
+            return Ok(());
+        }
+        buf.extend_from_slice(&chunk[..num_read]);
+    }
+}
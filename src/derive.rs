@@ -0,0 +1,183 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use std::fmt;
+use std::num::NonZeroUsize;
+
+use crate::card::PasswordCard;
+use crate::charset::Charset;
+use crate::generator::CharsetGenerator;
+use crate::memory::SecretBuf;
+use crate::sampler::random_below;
+use crate::{invalid_spec, Result};
+
+// Every input to the derivation is fixed per version rather than taken from
+// the generator's defaults, so that the same inputs derive the same password
+// in every release. Changing any of them needs a new version, and old
+// versions stay supported.
+
+const SEED_LEN: usize = 32;
+
+/// A version of the derivation algorithm. Each version derives the same
+/// passwords in every release that supports it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DerivationVersion {
+    /// Argon2id with 64 MiB of memory, 3 iterations and 1 lane, seeding
+    /// ChaCha20.
+    V1,
+}
+
+struct VersionParams {
+    domain: &'static [u8],
+    card_domain: &'static [u8],
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+impl DerivationVersion {
+    /// The version used for new passwords.
+    pub const CURRENT: DerivationVersion = DerivationVersion::V1;
+
+    /// Returns the version with this number, failing if this release does
+    /// not support it.
+    pub fn from_number(number: u32) -> Result<Self> {
+        match number {
+            1 => Ok(DerivationVersion::V1),
+            _ => {
+                let msg =
+                    format!("derivation version {} is not supported", number);
+                Err(invalid_spec(msg))
+            },
+        }
+    }
+
+    /// Returns the version's number.
+    pub fn number(self) -> u32 {
+        match self {
+            DerivationVersion::V1 => 1,
+        }
+    }
+
+    fn params(self) -> VersionParams {
+        match self {
+            DerivationVersion::V1 =>
+                VersionParams {
+                    domain: b"passgen derive v1",
+                    card_domain: b"passgen card v1",
+                    memory_kib: 64 * 1024,
+                    iterations: 3,
+                    parallelism: 1,
+                },
+        }
+    }
+}
+
+impl fmt::Display for DerivationVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+/// The site, login and counter a password is derived for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SiteContext {
+    /// The site's domain name, compared case-insensitively.
+    pub site: String,
+    /// The login name on the site.
+    pub login: String,
+    /// Incremented to rotate the password without changing the master
+    /// password.
+    pub counter: u32,
+}
+
+impl SiteContext {
+    // Length-prefixes each field so that no two contexts share a salt.
+    fn salt(&self, domain: &[u8]) -> Vec<u8> {
+        let site = self.site.to_lowercase();
+        let mut salt = domain.to_vec();
+        for field in [site.as_bytes(), self.login.as_bytes()] {
+            salt.extend_from_slice(&(field.len() as u64).to_be_bytes());
+            salt.extend_from_slice(field);
+        }
+        salt.extend_from_slice(&self.counter.to_be_bytes());
+        salt
+    }
+}
+
+/// Derives a password for a site deterministically from a master password,
+/// as a stateless password manager.
+///
+/// The master password is stretched with Argon2id, salted with the site
+/// context and parameterised by the version, into a seed for ChaCha20.
+/// Characters are drawn from the seeded generator by rejection sampling, and
+/// the whole password is redrawn until it satisfies the policy, so the
+/// result depends only on the inputs and never on the crate's features.
+pub fn derive_password(
+    version: DerivationVersion,
+    master_password: &[u8],
+    context: &SiteContext,
+    generator: &CharsetGenerator,
+) -> Result<String> {
+    let salt = context.salt(version.params().domain);
+    let mut rng = seeded_rng(version, master_password, &salt)?;
+    let charset = generator.charset();
+    let min_len = generator.min_length().get();
+    let range = generator.max_length().get() - min_len + 1;
+    let password_len = min_len + random_below(&mut rng, range)?;
+    let mut candidate = SecretBuf::zeroed(password_len);
+    loop {
+        for byte in candidate.iter_mut() {
+            *byte = charset[random_below(&mut rng, charset.len())?];
+        }
+        if generator.policy().is_satisfied_by(&candidate) {
+            break;
+        }
+    }
+    Ok(candidate.iter().copied().map(char::from).collect())
+}
+
+/// Derives a password card deterministically from a master password, so
+/// that a lost card can be printed again.
+///
+/// The seed is derived as for [`derive_password`], salted instead with the
+/// character set and number of columns, and the cells are drawn row by row.
+pub fn derive_card(
+    version: DerivationVersion,
+    master_password: &[u8],
+    charset: &Charset,
+    num_columns: NonZeroUsize,
+) -> Result<PasswordCard> {
+    let mut salt = version.params().card_domain.to_vec();
+    salt.extend_from_slice(&(charset.len() as u64).to_be_bytes());
+    salt.extend_from_slice(charset);
+    salt.extend_from_slice(&(num_columns.get() as u64).to_be_bytes());
+    let mut rng = seeded_rng(version, master_password, &salt)?;
+    let mut card = PasswordCard::zeroed(num_columns);
+    for byte in card.cells_mut() {
+        *byte = charset[random_below(&mut rng, charset.len())?];
+    }
+    Ok(card)
+}
+
+// Stretches the master password with Argon2id into a seed for ChaCha20.
+fn seeded_rng(
+    version: DerivationVersion,
+    master_password: &[u8],
+    salt: &[u8],
+) -> Result<ChaCha20Rng> {
+    if master_password.is_empty() {
+        return Err(invalid_spec("master password is empty"));
+    }
+    let VersionParams { memory_kib, iterations, parallelism, .. } =
+        version.params();
+    let params =
+        Params::new(memory_kib, iterations, parallelism, Some(SEED_LEN))
+            .map_err(|err| invalid_spec(err.to_string()))?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+    let mut seed = SecretBuf::zeroed(SEED_LEN);
+    argon2.hash_password_into(master_password, salt, &mut seed)
+        .map_err(|err| invalid_spec(err.to_string()))?;
+    Ok(ChaCha20Rng::from_seed(seed[..].try_into().unwrap()))
+}
@@ -0,0 +1,85 @@
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use tracing::debug;
+
+use crate::health::{HealthTestedRng, HealthTests};
+
+/// A ChaCha20 generator seeded from the operating system and reseeded after
+/// every fixed number of bytes of output.
+///
+/// Drawing from the operating system once per interval rather than once per
+/// request saves system calls in bulk jobs. Output from before the last
+/// reseed cannot be recovered from the current state, so a compromise of the
+/// process exposes at most one interval of passwords.
+///
+/// The seeds, the generator's entropy input, pass through the continuous
+/// health tests; its output does not, since it is no noise source and a
+/// bulk job would eventually trip any cutoff by chance.
+pub struct ChaChaDrbg {
+    chacha: ChaCha20Rng,
+    source: HealthTestedRng<OsRng>,
+    reseed_interval: u64,
+    bytes_until_reseed: u64,
+}
+
+impl ChaChaDrbg {
+    /// The default number of bytes of output between reseeds.
+    pub const DEFAULT_RESEED_INTERVAL: u64 = 1 << 20;
+
+    /// Seeds the generator from the operating system, to be reseeded after
+    /// every `reseed_interval` bytes of output.
+    pub fn new(reseed_interval: u64) -> crate::Result<Self> {
+        ChaChaDrbg::with_health_tests(reseed_interval, HealthTests::default())
+    }
+
+    /// Seeds the generator as [`ChaChaDrbg::new`] does, running these
+    /// tests on every seed drawn.
+    pub fn with_health_tests(
+        reseed_interval: u64,
+        tests: HealthTests,
+    ) -> crate::Result<Self> {
+        let mut source = HealthTestedRng::new(OsRng, tests);
+        let chacha =
+            ChaCha20Rng::from_rng(&mut source).map_err(crate::rng_failure)?;
+        let bytes_until_reseed = reseed_interval;
+        Ok(ChaChaDrbg { chacha, source, reseed_interval, bytes_until_reseed })
+    }
+
+    /// Returns the number of bytes of output between reseeds.
+    pub fn reseed_interval(&self) -> u64 {
+        self.reseed_interval
+    }
+}
+
+impl RngCore for ChaChaDrbg {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(err) = self.try_fill_bytes(dest) {
+            panic!("random number generator failed: {}", err);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        for chunk in dest.chunks_mut(self.reseed_interval.max(1) as usize) {
+            if self.bytes_until_reseed < chunk.len() as u64 {
+                self.chacha = ChaCha20Rng::from_rng(&mut self.source)?;
+                self.bytes_until_reseed = self.reseed_interval;
+                debug!(self.reseed_interval, "reseeded generator");
+            }
+            self.chacha.fill_bytes(chunk);
+            self.bytes_until_reseed =
+                self.bytes_until_reseed.saturating_sub(chunk.len() as u64);
+        }
+        Ok(())
+    }
+}
+
+impl CryptoRng for ChaChaDrbg {}
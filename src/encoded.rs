@@ -0,0 +1,342 @@
+use rand_core::CryptoRngCore;
+use std::fmt;
+use std::num::NonZeroUsize;
+
+use crate::generator::{entropy_bits, Generator};
+use crate::memory::SecretBuf;
+use crate::sampler::sample;
+use crate::{invalid_spec, rng_failure, Result};
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+const BASE64URL_DIGITS: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+const BASE32_DIGITS: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+const BASE58_DIGITS: &[u8; 58] =
+    b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const CROCKFORD_DIGITS: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const CROCKFORD_CHECK_SYMBOLS: &[u8; 37] =
+    b"0123456789ABCDEFGHJKMNPQRSTVWXYZ*~$=U";
+
+/// The text encoding of a random token's bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TokenEncoding {
+    /// Lowercase hexadecimal.
+    Hex,
+    /// The URL-safe Base64 alphabet of RFC 4648, without padding.
+    Base64Url,
+    /// The Base32 alphabet of RFC 4648, without padding, as used for TOTP
+    /// secrets.
+    Base32,
+}
+
+impl TokenEncoding {
+    /// Returns the length of the encoding of this many bytes.
+    pub fn encoded_len(self, num_bytes: usize) -> usize {
+        match self {
+            TokenEncoding::Hex => num_bytes * 2,
+            TokenEncoding::Base64Url => (num_bytes * 4).div_ceil(3),
+            TokenEncoding::Base32 => (num_bytes * 8).div_ceil(5),
+        }
+    }
+
+    fn encode(self, bytes: &[u8], out: &mut String) {
+        match self {
+            TokenEncoding::Hex => {
+                for &byte in bytes {
+                    out.push(char::from(HEX_DIGITS[(byte >> 4) as usize]));
+                    out.push(char::from(HEX_DIGITS[(byte & 0xf) as usize]));
+                }
+            },
+            TokenEncoding::Base64Url => {
+                for chunk in bytes.chunks(3) {
+                    let mut group = [0u8; 3];
+                    group[..chunk.len()].copy_from_slice(chunk);
+                    let bits =
+                        u32::from_be_bytes([0, group[0], group[1], group[2]]);
+                    for idx in 0..chunk.len() + 1 {
+                        let digit = (bits >> (18 - 6 * idx)) & 0x3f;
+                        out.push(char::from(BASE64URL_DIGITS[digit as usize]));
+                    }
+                }
+            },
+            TokenEncoding::Base32 => {
+                for chunk in bytes.chunks(5) {
+                    let mut group = [0u8; 8];
+                    group[3..3 + chunk.len()].copy_from_slice(chunk);
+                    let bits = u64::from_be_bytes(group);
+                    for idx in 0..(chunk.len() * 8).div_ceil(5) {
+                        let digit = (bits >> (35 - 5 * idx)) & 0x1f;
+                        out.push(char::from(BASE32_DIGITS[digit as usize]));
+                    }
+                }
+            },
+        }
+    }
+}
+
+impl fmt::Display for TokenEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TokenEncoding::Hex => write!(f, "hex"),
+            TokenEncoding::Base64Url => write!(f, "base64url"),
+            TokenEncoding::Base32 => write!(f, "base32"),
+        }
+    }
+}
+
+/// Generates tokens of random bytes in a text encoding, after an optional
+/// fixed prefix.
+#[derive(Clone, Debug)]
+pub struct TokenGenerator {
+    encoding: TokenEncoding,
+    num_bytes: NonZeroUsize,
+    prefix: String,
+}
+
+impl TokenGenerator {
+    /// Creates a generator for tokens of this many random bytes.
+    pub fn new(encoding: TokenEncoding, num_bytes: NonZeroUsize) -> Self {
+        TokenGenerator { encoding, num_bytes, prefix: String::new() }
+    }
+
+    /// Sets the text written before each token.
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Returns the encoding of the random bytes.
+    pub fn encoding(&self) -> TokenEncoding {
+        self.encoding
+    }
+
+    /// Returns the number of random bytes in each token.
+    pub fn num_bytes(&self) -> NonZeroUsize {
+        self.num_bytes
+    }
+
+    /// Returns the length of each token in characters, including the prefix.
+    pub fn length(&self) -> usize {
+        let encoded_len = self.encoding.encoded_len(self.num_bytes.get());
+        self.prefix.chars().count() + encoded_len
+    }
+}
+
+impl Generator for TokenGenerator {
+    fn generate(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+    ) -> Result<()> {
+        let mut bytes = SecretBuf::zeroed(self.num_bytes.get());
+        rng.try_fill_bytes(&mut bytes).map_err(rng_failure)?;
+        out.push_str(&self.prefix);
+        self.encoding.encode(&bytes, out);
+        Ok(())
+    }
+
+    fn entropy_bits(&self) -> f64 {
+        self.num_bytes.get() as f64 * 8.0
+    }
+}
+
+/// The alphabet of a random identifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IdAlphabet {
+    /// The Bitcoin Base58 alphabet, without 0, O, I and l.
+    Base58,
+    /// Crockford's Base32 alphabet, without I, L, O and U.
+    Crockford,
+}
+
+impl IdAlphabet {
+    fn digits(self) -> &'static [u8] {
+        match self {
+            IdAlphabet::Base58 => BASE58_DIGITS,
+            IdAlphabet::Crockford => CROCKFORD_DIGITS,
+        }
+    }
+}
+
+impl fmt::Display for IdAlphabet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IdAlphabet::Base58 => write!(f, "base58"),
+            IdAlphabet::Crockford => write!(f, "crockford base32"),
+        }
+    }
+}
+
+/// Generates identifiers of characters drawn uniformly from an alphabet,
+/// after an optional fixed prefix and with an optional check symbol.
+#[derive(Clone, Debug)]
+pub struct IdGenerator {
+    alphabet: IdAlphabet,
+    id_len: NonZeroUsize,
+    check: bool,
+    prefix: String,
+}
+
+impl IdGenerator {
+    /// Creates a generator for identifiers of this many random characters,
+    /// failing if a check symbol is asked for with an alphabet that has
+    /// none. Only Crockford's Base32 defines one, the value of the
+    /// identifier modulo 37.
+    pub fn new(
+        alphabet: IdAlphabet,
+        id_len: NonZeroUsize,
+        check: bool,
+    ) -> Result<Self> {
+        if check && alphabet != IdAlphabet::Crockford {
+            let msg = format!("{} has no check symbol", alphabet);
+            return Err(invalid_spec(msg));
+        }
+        Ok(IdGenerator { alphabet, id_len, check, prefix: String::new() })
+    }
+
+    /// Sets the text written before each identifier.
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Returns the alphabet of the random characters.
+    pub fn alphabet(&self) -> IdAlphabet {
+        self.alphabet
+    }
+
+    /// Returns the number of random characters in each identifier.
+    pub fn id_length(&self) -> NonZeroUsize {
+        self.id_len
+    }
+
+    /// Returns whether each identifier ends with a check symbol.
+    pub fn has_check(&self) -> bool {
+        self.check
+    }
+
+    /// Returns the length of each identifier in characters, including the
+    /// prefix and check symbol.
+    pub fn length(&self) -> usize {
+        self.prefix.chars().count() + self.id_len.get() + self.check as usize
+    }
+}
+
+impl Generator for IdGenerator {
+    fn generate(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+    ) -> Result<()> {
+        let mut id = SecretBuf::zeroed(self.id_len.get());
+        sample(rng, self.alphabet.digits(), &mut id)?;
+        out.push_str(&self.prefix);
+        out.extend(id.iter().copied().map(char::from));
+        if self.check {
+            out.push(check_symbol(&id));
+        }
+        Ok(())
+    }
+
+    fn entropy_bits(&self) -> f64 {
+        entropy_bits(self.alphabet.digits(), self.id_len.get())
+    }
+}
+
+// The check symbol of a Crockford Base32 identifier, its value modulo 37.
+fn check_symbol(id: &[u8]) -> char {
+    let remainder =
+        id.iter().fold(0, |acc, byte| {
+            let value =
+                CROCKFORD_DIGITS.iter().position(|d| d == byte).unwrap();
+            (acc * 32 + value) % 37
+        });
+    char::from(CROCKFORD_CHECK_SYMBOLS[remainder])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::ChaCha20Rng;
+    use rand::SeedableRng;
+
+    fn encode(encoding: TokenEncoding, bytes: &[u8]) -> String {
+        let mut encoded = String::new();
+        encoding.encode(bytes, &mut encoded);
+        assert_eq!(encoded.len(), encoding.encoded_len(bytes.len()));
+        encoded
+    }
+
+    fn ids(generator: &IdGenerator) -> Vec<String> {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        (0..100)
+            .map(|_| {
+                let mut id = String::new();
+                generator.generate(&mut rng, &mut id).unwrap();
+                id
+            })
+            .collect()
+    }
+
+    // The test vectors of RFC 4648, section 10, without padding.
+    #[test]
+    fn encodes_rfc_4648_vectors() {
+        let vectors = [
+            ("", "", "", ""),
+            ("f", "66", "Zg", "MY"),
+            ("fo", "666f", "Zm8", "MZXQ"),
+            ("foo", "666f6f", "Zm9v", "MZXW6"),
+            ("foob", "666f6f62", "Zm9vYg", "MZXW6YQ"),
+            ("fooba", "666f6f6261", "Zm9vYmE", "MZXW6YTB"),
+            ("foobar", "666f6f626172", "Zm9vYmFy", "MZXW6YTBOI"),
+        ];
+        for (bytes, hex, base64url, base32) in vectors {
+            let bytes = bytes.as_bytes();
+            assert_eq!(encode(TokenEncoding::Hex, bytes), hex);
+            assert_eq!(encode(TokenEncoding::Base64Url, bytes), base64url);
+            assert_eq!(encode(TokenEncoding::Base32, bytes), base32);
+        }
+        assert_eq!(encode(TokenEncoding::Base64Url, &[0xfb, 0xff]), "-_8");
+    }
+
+    #[test]
+    fn base58_ids_avoid_confusable_characters() {
+        let id_len = NonZeroUsize::new(22).unwrap();
+        let generator =
+            IdGenerator::new(IdAlphabet::Base58, id_len, false).unwrap();
+        for id in ids(&generator.prefix("id_")) {
+            let id = id.strip_prefix("id_").unwrap();
+            assert_eq!(id.len(), 22);
+            assert!(id.bytes().all(|byte| BASE58_DIGITS.contains(&byte)));
+            assert!(!id.contains(['0', 'O', 'I', 'l']));
+        }
+        assert!(IdGenerator::new(IdAlphabet::Base58, id_len, true).is_err());
+    }
+
+    // 1234 is 16J in Crockford's Base32, and 1234 modulo 37 is 13, or D.
+    #[test]
+    fn check_symbol_is_the_value_modulo_37() {
+        assert_eq!(check_symbol(b"16J"), 'D');
+        assert_eq!(check_symbol(b"0"), '0');
+        assert_eq!(check_symbol(b"10"), '*');
+        assert_eq!(check_symbol(b"14"), 'U');
+        assert_eq!(check_symbol(b"ZZZZZZZZZZZZZZZZZZZZZZZZZZ"), 'M');
+    }
+
+    #[test]
+    fn crockford_ids_end_with_their_check_symbol() {
+        let id_len = NonZeroUsize::new(26).unwrap();
+        let generator =
+            IdGenerator::new(IdAlphabet::Crockford, id_len, true).unwrap();
+        assert_eq!(generator.length(), 27);
+        for id in ids(&generator) {
+            let (id, check) = id.split_at(26);
+            assert!(id.bytes().all(|byte| CROCKFORD_DIGITS.contains(&byte)));
+            assert!(!id.contains(['I', 'L', 'O', 'U']));
+            assert_eq!(check.chars().next(), Some(check_symbol(id.as_bytes())));
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::{anyhow, Result};
+use std::io::{self, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+use crate::i18n::trf;
+
+// Output is piped through age or gpg, which writes armored ciphertext to
+// standard output, so the plaintext only ever passes through the pipe.
+// Credentials for systemd services are encrypted by systemd-creds the same
+// way, to the host's key or TPM as it chooses.
+
+const AGE_RECIPIENT_PREFIX: &str = "age1";
+const SYSTEMD_CREDS: &str = "systemd-creds";
+
+pub struct Encryptor {
+    program: &'static str,
+    child: Child,
+    stdin: Option<ChildStdin>,
+}
+
+impl Encryptor {
+    /// Starts age for age public keys and gpg for any other recipient.
+    pub fn spawn(recipient: &str) -> Result<Self> {
+        let (program, args): (_, &[&str]) =
+            if recipient.starts_with(AGE_RECIPIENT_PREFIX) {
+                ("age", &["--armor", "--recipient"])
+            } else {
+                ("gpg", &["--armor", "--batch", "--encrypt", "--recipient"])
+            };
+        let mut child =
+            Command::new(program)
+                .args(args)
+                .arg(recipient)
+                .stdin(Stdio::piped())
+                .spawn()
+                .map_err(|err| {
+                    anyhow!(trf("could not run {}: {}", &[&program, &err]))
+                })?;
+        let stdin = child.stdin.take();
+        Ok(Encryptor { program, child, stdin })
+    }
+
+    /// Closes the input and waits for the ciphertext to be written.
+    pub fn finish(mut self) -> Result<()> {
+        drop(self.stdin.take());
+        let status = self.child.wait()?;
+        if !status.success() {
+            let msg = trf("{} exited with {}", &[&self.program, &status]);
+            return Err(anyhow!(msg));
+        }
+        Ok(())
+    }
+
+    /// Returns the process ID of age or gpg.
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    fn stdin(&mut self) -> &mut ChildStdin {
+        self.stdin.as_mut().unwrap()
+    }
+}
+
+impl Write for Encryptor {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stdin().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stdin().flush()
+    }
+}
+
+/// Encrypts the secret as the systemd credential of that name, written to
+/// standard output.
+pub fn encrypt_credential(name: &str, secret: &str) -> Result<()> {
+    let name_arg = format!("--name={}", name);
+    let mut child =
+        Command::new(SYSTEMD_CREDS)
+            .args(["encrypt", &name_arg, "-", "-"])
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|err| {
+                anyhow!(trf("could not run {}: {}", &[&SYSTEMD_CREDS, &err]))
+            })?;
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(secret.as_bytes())?;
+    drop(stdin);
+    let status = child.wait()?;
+    if !status.success() {
+        let msg = trf("{} exited with {}", &[&SYSTEMD_CREDS, &status]);
+        return Err(anyhow!(msg));
+    }
+    Ok(())
+}
+
+// On failure, stops the encryptor before it can write partial output.
+impl Drop for Encryptor {
+    fn drop(&mut self) {
+        if self.stdin.is_some() {
+            let _ = self.child.kill();
+            drop(self.stdin.take());
+            let _ = self.child.wait();
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::fmt;
+use tracing::{debug, warn};
+
+use crate::PassgenError;
+
+/// What to do when the operating system's random number generator fails.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RngFailurePolicy {
+    /// Fail with [`PassgenError::RngFailure`].
+    #[default]
+    Fail,
+    /// Draw from a ChaCha20 generator seeded from the operating system
+    /// before the failure, reseeding it once the operating system recovers.
+    ChaCha,
+}
+
+impl fmt::Display for RngFailurePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name =
+            match self {
+                RngFailurePolicy::Fail => "fail",
+                RngFailurePolicy::ChaCha => "fall back to chacha20",
+            };
+        write!(f, "{}", name)
+    }
+}
+
+/// The operating system's random number generator, with a policy for when it
+/// fails.
+pub struct FallbackRng {
+    policy: RngFailurePolicy,
+    chacha: Option<ChaCha20Rng>,
+    stale: bool,
+    fell_back: bool,
+}
+
+impl FallbackRng {
+    /// Creates the generator, seeding the fallback from the operating system
+    /// now if the policy has one.
+    pub fn new(policy: RngFailurePolicy) -> crate::Result<Self> {
+        let chacha =
+            match policy {
+                RngFailurePolicy::Fail => None,
+                RngFailurePolicy::ChaCha => Some(seed_chacha()?),
+            };
+        Ok(FallbackRng { policy, chacha, stale: false, fell_back: false })
+    }
+
+    /// Returns the policy for failures.
+    pub fn policy(&self) -> RngFailurePolicy {
+        self.policy
+    }
+
+    /// Returns whether any output came from the fallback.
+    pub fn fell_back(&self) -> bool {
+        self.fell_back
+    }
+}
+
+fn seed_chacha() -> crate::Result<ChaCha20Rng> {
+    ChaCha20Rng::from_rng(OsRng).map_err(PassgenError::RngFailure)
+}
+
+impl RngCore for FallbackRng {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(err) = self.try_fill_bytes(dest) {
+            panic!("random number generator failed: {}", err);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        let err =
+            match OsRng.try_fill_bytes(dest) {
+                Ok(()) => {
+                    // The fallback's state has been used, so replace it
+                    // while the operating system is available.
+                    if self.stale {
+                        if let Ok(chacha) = seed_chacha() {
+                            debug!("reseeded fallback generator");
+                            self.chacha = Some(chacha);
+                            self.stale = false;
+                        }
+                    }
+                    return Ok(());
+                },
+                Err(err) => err,
+            };
+        match &mut self.chacha {
+            Some(chacha) => {
+                warn!(%err, "operating system generator failed, falling back");
+                chacha.fill_bytes(dest);
+                self.stale = true;
+                self.fell_back = true;
+                Ok(())
+            },
+            None => Err(err),
+        }
+    }
+}
+
+impl CryptoRng for FallbackRng {}
@@ -0,0 +1,518 @@
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use rand_core::CryptoRngCore;
+use std::collections::HashSet;
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::time::{Duration, Instant};
+use tracing::debug;
+use zeroize::Zeroize;
+
+use crate::charset::Charset;
+use crate::memory::SecretBuf;
+use crate::observer::{GenerationObserver, ObservedRng};
+use crate::policy::{Policy, Preset};
+use crate::provenance::EntropySource;
+use crate::sampler::{random_below, sample};
+use crate::{invalid_spec, PassgenError, Result};
+
+/// Returns the entropy in bits of a password of this length drawn uniformly
+/// from the character set.
+pub fn entropy_bits(charset: &[u8], password_len: usize) -> f64 {
+    password_len as f64 * (charset.len() as f64).log2()
+}
+
+/// A strategy for generating one password at a time.
+pub trait Generator {
+    /// Appends a newly generated password to `out`.
+    fn generate(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+    ) -> Result<()>;
+
+    /// Appends a newly generated password to `out`, reporting events that
+    /// happen inside the generator to the observer.
+    fn generate_observed(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+        observer: &dyn GenerationObserver,
+    ) -> Result<()> {
+        let _ = observer;
+        self.generate(rng, out)
+    }
+
+    /// Returns the entropy in bits of the weakest password this generator
+    /// can produce.
+    fn entropy_bits(&self) -> f64;
+}
+
+/// Generates passwords of a random length in a range from a character set,
+/// resampling each until it satisfies a policy or a limit on resampling is
+/// reached.
+#[derive(Clone, Debug)]
+pub struct CharsetGenerator {
+    charset: Charset,
+    min_len: NonZeroUsize,
+    max_len: NonZeroUsize,
+    policy: Policy,
+    max_attempts: Option<NonZeroU64>,
+    timeout: Option<Duration>,
+}
+
+impl CharsetGenerator {
+    /// Validates the settings, failing if the lengths are out of order or the
+    /// policy cannot be satisfied.
+    pub fn new(
+        charset: Charset,
+        min_len: NonZeroUsize,
+        max_len: NonZeroUsize,
+        policy: Policy,
+    ) -> Result<Self> {
+        if min_len > max_len {
+            return Err(invalid_spec("minimum length exceeds maximum length"));
+        }
+        for &class in policy.required.iter() {
+            if !charset.iter().any(|&byte| class.contains(byte)) {
+                let msg = format!("character set has no {} characters", class);
+                return Err(PassgenError::PolicyViolation(msg));
+            }
+        }
+        if min_len.get() < policy.required.len() {
+            let msg =
+                "password length is less than the number of required classes";
+            return Err(PassgenError::PolicyViolation(msg.to_string()));
+        }
+        if policy.max_length.is_some_and(|policy_max| max_len > policy_max) {
+            let msg = "maximum length exceeds the policy's maximum length";
+            return Err(PassgenError::PolicyViolation(msg.to_string()));
+        }
+        if !charset.iter().any(|&byte| policy.is_allowed_at_ends(byte)) {
+            let msg = "every character is forbidden at the ends";
+            return Err(PassgenError::PolicyViolation(msg.to_string()));
+        }
+        // Classes with no characters allowed at the ends must fit between
+        // them.
+        let num_inner_classes =
+            policy.required.iter()
+                .filter(|&&class| {
+                    !charset.iter().any(|&byte| {
+                        class.contains(byte) && policy.is_allowed_at_ends(byte)
+                    })
+                })
+                .count();
+        if num_inner_classes > 0 && min_len.get() < num_inner_classes + 2 {
+            let msg =
+                "password length leaves too little room between the ends";
+            return Err(PassgenError::PolicyViolation(msg.to_string()));
+        }
+        Ok(CharsetGenerator {
+            charset,
+            min_len,
+            max_len,
+            policy,
+            max_attempts: None,
+            timeout: None,
+        })
+    }
+
+    /// Gives up on a password after drawing this many candidates for it.
+    pub fn with_max_attempts(mut self, max_attempts: NonZeroU64) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// Gives up on a password after drawing candidates for it for this
+    /// long.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Returns the characters passwords are drawn from.
+    pub fn charset(&self) -> &Charset {
+        &self.charset
+    }
+
+    /// Returns the minimum password length.
+    pub fn min_length(&self) -> NonZeroUsize {
+        self.min_len
+    }
+
+    /// Returns the maximum password length.
+    pub fn max_length(&self) -> NonZeroUsize {
+        self.max_len
+    }
+
+    /// Returns the policy every password satisfies.
+    pub fn policy(&self) -> &Policy {
+        &self.policy
+    }
+
+    /// Returns the most candidates drawn for a password, if limited.
+    pub fn max_attempts(&self) -> Option<NonZeroU64> {
+        self.max_attempts
+    }
+
+    /// Returns the longest time spent drawing candidates for a password, if
+    /// limited.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns the fraction of candidates of this length that satisfy the
+    /// policy.
+    pub fn acceptance_rate(&self, password_len: usize) -> f64 {
+        let accepted = self.policy.entropy_bits(&self.charset, password_len);
+        (accepted - entropy_bits(&self.charset, password_len)).exp2()
+    }
+}
+
+impl Generator for CharsetGenerator {
+    fn generate(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+    ) -> Result<()> {
+        self.generate_observed(rng, out, &())
+    }
+
+    fn generate_observed(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+        observer: &dyn GenerationObserver,
+    ) -> Result<()> {
+        let min_len = self.min_len.get();
+        let range = self.max_len.get() - min_len + 1;
+        let password_len = min_len + random_below(rng, range)?;
+        let mut candidate = SecretBuf::zeroed(password_len);
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        let mut attempt = 1_u64;
+        loop {
+            sample(rng, &self.charset, &mut candidate)?;
+            if self.policy.is_satisfied_by(&candidate) {
+                break;
+            }
+            debug!(attempt, password_len, "candidate rejected by policy");
+            observer.candidate_rejected(attempt as usize);
+            let out_of_attempts =
+                self.max_attempts
+                    .is_some_and(|max_attempts| attempt >= max_attempts.get());
+            let out_of_time =
+                deadline.is_some_and(|deadline| Instant::now() >= deadline);
+            if out_of_attempts || out_of_time {
+                return Err(PassgenError::AttemptsExhausted {
+                    attempts: attempt,
+                    acceptance_rate: self.acceptance_rate(password_len),
+                });
+            }
+            attempt += 1;
+        }
+        out.extend(candidate.iter().copied().map(char::from));
+        Ok(())
+    }
+
+    fn entropy_bits(&self) -> f64 {
+        self.policy.entropy_bits(&self.charset, self.min_len.get())
+    }
+}
+
+const DEFAULT_PASSWORD_LEN: NonZeroUsize = NonZeroUsize::new(24).unwrap();
+const DEFAULT_NUM_PASSWORDS: NonZeroUsize = NonZeroUsize::MIN;
+
+/// A validated description of the passwords to generate.
+#[derive(Clone, Debug)]
+pub struct PasswordSpec {
+    generator: CharsetGenerator,
+    num_passwords: NonZeroUsize,
+}
+
+impl PasswordSpec {
+    /// Returns a builder for 24-character passwords from the typeable
+    /// characters.
+    pub fn builder() -> PasswordSpecBuilder {
+        PasswordSpecBuilder::default()
+    }
+
+    /// Returns the generator each password is drawn from.
+    pub fn generator(&self) -> &CharsetGenerator {
+        &self.generator
+    }
+
+    /// Returns the characters passwords are drawn from.
+    pub fn charset(&self) -> &Charset {
+        self.generator.charset()
+    }
+
+    /// Returns the minimum password length.
+    pub fn min_length(&self) -> NonZeroUsize {
+        self.generator.min_length()
+    }
+
+    /// Returns the maximum password length.
+    pub fn max_length(&self) -> NonZeroUsize {
+        self.generator.max_length()
+    }
+
+    /// Returns the number of passwords to generate.
+    pub fn count(&self) -> NonZeroUsize {
+        self.num_passwords
+    }
+
+    /// Returns the most candidates drawn for a password, if limited.
+    pub fn max_attempts(&self) -> Option<NonZeroU64> {
+        self.generator.max_attempts()
+    }
+
+    /// Returns the longest time spent drawing candidates for a password, if
+    /// limited.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.generator.timeout()
+    }
+
+    /// Returns the policy every password satisfies.
+    pub fn policy(&self) -> &Policy {
+        self.generator.policy()
+    }
+
+    /// Generates all the passwords using the operating system's random
+    /// number generator.
+    pub fn generate(&self) -> Result<Vec<String>> {
+        self.passwords().collect()
+    }
+
+    /// Returns an iterator over the passwords using the operating system's
+    /// random number generator.
+    pub fn passwords(&self) -> Passwords<'_, OsRng> {
+        self.passwords_with_rng(OsRng).with_source(EntropySource::Os)
+    }
+
+    /// Returns an iterator over the passwords using the given random number
+    /// generator, whose source is unspecified until set with
+    /// [`Passwords::with_source`].
+    pub fn passwords_with_rng<R>(&self, rng: R) -> Passwords<'_, R>
+    where
+        R: RngCore + CryptoRng,
+    {
+        debug!(
+            charset_size = self.charset().len(),
+            min_len = self.min_length(),
+            max_len = self.max_length(),
+            num_passwords = self.count(),
+            required = ?self.policy().required,
+            "generating passwords",
+        );
+        let remaining = self.num_passwords.get();
+        Passwords::new(&self.generator, remaining, rng)
+    }
+}
+
+/// A builder for [`PasswordSpec`].
+#[derive(Clone, Debug)]
+pub struct PasswordSpecBuilder {
+    charset: Option<Charset>,
+    min_len: NonZeroUsize,
+    max_len: NonZeroUsize,
+    num_passwords: NonZeroUsize,
+    policy: Policy,
+    max_attempts: Option<NonZeroU64>,
+    timeout: Option<Duration>,
+}
+
+impl Default for PasswordSpecBuilder {
+    fn default() -> Self {
+        PasswordSpecBuilder {
+            charset: None,
+            min_len: DEFAULT_PASSWORD_LEN,
+            max_len: DEFAULT_PASSWORD_LEN,
+            num_passwords: DEFAULT_NUM_PASSWORDS,
+            policy: Policy::default(),
+            max_attempts: None,
+            timeout: None,
+        }
+    }
+}
+
+impl PasswordSpecBuilder {
+    /// Sets the characters to draw from.
+    pub fn charset(mut self, charset: Charset) -> Self {
+        self.charset = Some(charset);
+        self
+    }
+
+    /// Sets both the minimum and maximum password length.
+    pub fn length(mut self, password_len: NonZeroUsize) -> Self {
+        self.min_len = password_len;
+        self.max_len = password_len;
+        self
+    }
+
+    /// Sets the minimum password length.
+    pub fn min_length(mut self, min_len: NonZeroUsize) -> Self {
+        self.min_len = min_len;
+        self
+    }
+
+    /// Sets the maximum password length.
+    pub fn max_length(mut self, max_len: NonZeroUsize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Sets the number of passwords to generate.
+    pub fn count(mut self, num_passwords: NonZeroUsize) -> Self {
+        self.num_passwords = num_passwords;
+        self
+    }
+
+    /// Sets the policy every password must satisfy.
+    pub fn policy(mut self, policy: Policy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Gives up on a password after drawing this many candidates for it,
+    /// rather than drawing until one satisfies the policy.
+    pub fn max_attempts(mut self, max_attempts: NonZeroU64) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// Gives up on a password after drawing candidates for it for this
+    /// long, rather than drawing until one satisfies the policy.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the character set and policy to those of the preset.
+    pub fn preset(mut self, preset: Preset) -> Self {
+        self.charset = Some(preset.charset());
+        self.policy = preset.policy();
+        self
+    }
+
+    /// Validates the settings, failing if the lengths are out of order or the
+    /// policy cannot be satisfied.
+    pub fn build(self) -> Result<PasswordSpec> {
+        let PasswordSpecBuilder {
+            charset,
+            min_len,
+            max_len,
+            num_passwords,
+            policy,
+            max_attempts,
+            timeout,
+        } = self;
+        let charset = charset.unwrap_or_else(Charset::typeable);
+        let mut generator =
+            CharsetGenerator::new(charset, min_len, max_len, policy)?;
+        generator.max_attempts = max_attempts;
+        generator.timeout = timeout;
+        Ok(PasswordSpec { generator, num_passwords })
+    }
+}
+
+/// An iterator over passwords drawn from a generator.
+pub struct Passwords<'a, R> {
+    generator: &'a dyn Generator,
+    remaining: usize,
+    rng: R,
+    source: EntropySource,
+    observer: &'a dyn GenerationObserver,
+    // The passwords returned so far, when they must be distinct.
+    seen: Option<HashSet<String>>,
+}
+
+impl<'a, R: RngCore + CryptoRng> Passwords<'a, R> {
+    /// Returns an iterator over this many passwords from any generator.
+    pub fn new(generator: &'a dyn Generator, count: usize, rng: R) -> Self {
+        let source = EntropySource::Unspecified;
+        Passwords {
+            generator,
+            remaining: count,
+            rng,
+            source,
+            observer: &(),
+            seen: None,
+        }
+    }
+
+    /// Redraws any password equal to one already returned, so that the
+    /// passwords are distinct. Fails if the generator's entropy allows fewer
+    /// distinct passwords than remain.
+    pub fn unique(mut self) -> Result<Self> {
+        let num_distinct = self.generator.entropy_bits().exp2().round();
+        if self.remaining as f64 > num_distinct {
+            let msg = "more passwords requested than can be distinct";
+            return Err(invalid_spec(msg));
+        }
+        self.seen = Some(HashSet::new());
+        Ok(self)
+    }
+
+    /// Records where the random number generator's output comes from.
+    pub fn with_source(mut self, source: EntropySource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Returns where the random number generator's output comes from.
+    pub fn source(&self) -> EntropySource {
+        self.source
+    }
+
+    /// Reports generation events for the remaining passwords to the
+    /// observer.
+    pub fn observe(mut self, observer: &'a dyn GenerationObserver) -> Self {
+        self.observer = observer;
+        self
+    }
+}
+
+impl<R: RngCore + CryptoRng> Iterator for Passwords<'_, R> {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Result<String>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let observer = self.observer;
+        let mut rng = ObservedRng { rng: &mut self.rng, observer };
+        let mut password = String::new();
+        loop {
+            let result =
+                self.generator
+                    .generate_observed(&mut rng, &mut password, observer);
+            if let Err(err) = result {
+                return Some(Err(err));
+            }
+            let Some(seen) = &mut self.seen else {
+                break;
+            };
+            if seen.insert(password.clone()) {
+                break;
+            }
+            debug!("duplicate password, retrying");
+            password.zeroize();
+        }
+        observer.password_emitted(password.chars().count());
+        Some(Ok(password))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<R> Drop for Passwords<'_, R> {
+    fn drop(&mut self) {
+        if let Some(seen) = &mut self.seen {
+            for mut password in seen.drain() {
+                password.zeroize();
+            }
+        }
+    }
+}
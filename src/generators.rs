@@ -0,0 +1,105 @@
+use anyhow::{anyhow, Context, Result};
+use passgen::{
+    register_generator,
+    registered_generators,
+    CharsetGenerator,
+    Generator,
+    PassphraseGenerator,
+    PatternGenerator,
+    PinGenerator,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::trf;
+use crate::profiles::Profiles;
+
+// Generators can be defined in a JSON object from each name to an object
+// with one field, naming the kind of generator and holding its settings as
+// they are stored elsewhere, as in {"mainframe": {"pattern": "uddddddd"}}.
+// They are registered just as a crate using the library registers its own.
+
+/// A generator as defined in the file.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "lowercase", deny_unknown_fields)]
+pub enum Definition {
+    Charset(CharsetGenerator),
+    Pattern(PatternGenerator),
+    Passphrase(PassphraseGenerator),
+    Pin(PinGenerator),
+}
+
+/// Returns the generators file beside the profiles file.
+pub fn default_path() -> Option<PathBuf> {
+    Profiles::default_path().map(|path| path.with_file_name("generators.json"))
+}
+
+/// Registers the generators defined in the file, treating a missing file as
+/// defining none.
+pub fn load(path: &Path) -> Result<()> {
+    let context = || trf("could not read \"{}\"", &[&path.display()]);
+    let json =
+        match fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err).with_context(context),
+        };
+    register(&json).with_context(context)
+}
+
+/// Registers the generators the JSON defines, failing on the first that is
+/// invalid or whose name is taken.
+pub fn register(json: &str) -> Result<()> {
+    let definitions =
+        serde_json::from_str::<BTreeMap<String, Definition>>(json)?;
+    for (name, definition) in definitions {
+        let generator: Box<dyn Generator + Send + Sync> =
+            match definition {
+                Definition::Charset(generator) => Box::new(generator),
+                Definition::Pattern(generator) => Box::new(generator),
+                Definition::Passphrase(generator) => Box::new(generator),
+                Definition::Pin(generator) => Box::new(generator),
+            };
+        register_generator(&name, generator)?;
+    }
+    Ok(())
+}
+
+/// Adds the definition to the file under the name, failing if a generator
+/// of that name is registered or already defined there. The file is written
+/// to a temporary file that is moved into place.
+pub fn define(path: &Path, name: &str, definition: &Definition) -> Result<()> {
+    let read_context = || trf("could not read \"{}\"", &[&path.display()]);
+    let mut definitions =
+        match fs::read_to_string(path) {
+            Ok(json) => {
+                serde_json::from_str::<BTreeMap<String, Value>>(&json)
+                    .with_context(read_context)?
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound =>
+                BTreeMap::new(),
+            Err(err) => return Err(err).with_context(read_context),
+        };
+    let taken =
+        definitions.contains_key(name)
+            || registered_generators().iter().any(|taken| taken == name);
+    if taken {
+        let msg = trf("generator already defined: \"{}\"", &[&name]);
+        return Err(anyhow!(msg));
+    }
+    definitions.insert(name.to_string(), serde_json::to_value(definition)?);
+    let mut json = serde_json::to_string_pretty(&definitions)?;
+    json.push('\n');
+    let context = || trf("could not write \"{}\"", &[&path.display()]);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(context)?;
+    }
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, json).with_context(context)?;
+    fs::rename(&temp_path, path).with_context(context)?;
+    Ok(())
+}
@@ -0,0 +1,361 @@
+use anyhow::Result;
+use futures_core::Stream;
+use passgen::{estimate_entropy, Generator, PasswordSpec};
+use rand::rngs::OsRng;
+use std::convert::Infallible;
+use std::future::{self, Ready};
+use std::net::{Ipv4Addr, TcpListener};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll};
+use tokio::sync::mpsc;
+use tonic::body::BoxBody;
+use tonic::codec::ProstCodec;
+use tonic::codegen::{http, Body, BoxFuture, Service, StdError};
+use tonic::server::{
+    Grpc, NamedService, ServerStreamingService, UnaryService,
+};
+use tonic::transport::server::TcpIncoming;
+use tonic::{Request, Response, Status};
+use zeroize::Zeroize;
+
+use crate::i18n::tr;
+use crate::limits::LimitExceeded;
+use crate::server::Clients;
+
+// The service of proto/passgen.proto, written out by hand as tonic-build
+// would generate it. Calls are limited as the serve command's requests
+// are, by client address and by token, sharing the clients under a lock.
+// Generated passwords are sent through a small channel that the generating
+// thread blocks on while it is full, so that a bulk request is generated
+// no faster than the client reads it.
+
+const SERVICE_NAME: &str = "passgen.v1.Passgen";
+// The passwords generated ahead of the client.
+const STREAM_BUFFER: usize = 64;
+
+/// A password specification, as for --batch.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Spec {
+    #[prost(string, optional, tag = "1")]
+    pub charset: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub mode: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub exclude: Option<String>,
+    #[prost(uint64, optional, tag = "4")]
+    pub length: Option<u64>,
+    #[prost(uint64, optional, tag = "5")]
+    pub min_length: Option<u64>,
+    #[prost(uint64, optional, tag = "6")]
+    pub max_length: Option<u64>,
+    #[prost(double, optional, tag = "7")]
+    pub min_entropy: Option<f64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct GenerateRequest {
+    #[prost(message, optional, tag = "1")]
+    spec: Option<Spec>,
+    #[prost(uint64, optional, tag = "2")]
+    count: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct GenerateResponse {
+    #[prost(string, tag = "1")]
+    password: String,
+    #[prost(double, tag = "2")]
+    entropy_bits: f64,
+}
+
+impl Drop for GenerateResponse {
+    fn drop(&mut self) {
+        self.password.zeroize();
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct CheckRequest {
+    #[prost(string, tag = "1")]
+    password: String,
+}
+
+impl Drop for CheckRequest {
+    fn drop(&mut self) {
+        self.password.zeroize();
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct CheckResponse {
+    #[prost(double, tag = "1")]
+    entropy_bits: f64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct EntropyRequest {
+    #[prost(message, optional, tag = "1")]
+    spec: Option<Spec>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct EntropyResponse {
+    #[prost(double, tag = "1")]
+    entropy_bits: f64,
+    #[prost(uint64, tag = "2")]
+    charset_size: u64,
+}
+
+/// What the service needs from the caller.
+pub struct Backend {
+    /// Builds the specification of a call, or explains why it cannot.
+    pub spec: fn(Spec) -> std::result::Result<PasswordSpec, String>,
+    /// Returns a random number generator that has passed its start-up
+    /// tests.
+    pub rng: fn() -> Result<OsRng>,
+}
+
+/// Serves the service on the listener forever.
+pub fn serve(
+    listener: TcpListener,
+    clients: Clients,
+    backend: Backend,
+) -> Result<()> {
+    let service = Passgen(Arc::new(Inner {
+        clients: Mutex::new(clients),
+        backend,
+    }));
+    let runtime =
+        tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
+    runtime.block_on(async move {
+        listener.set_nonblocking(true)?;
+        let listener = tokio::net::TcpListener::from_std(listener)?;
+        let incoming = TcpIncoming::from_listener(listener, true, None)
+            .map_err(|err| anyhow::anyhow!(err))?;
+        tonic::transport::Server::builder()
+            .add_service(service)
+            .serve_with_incoming(incoming)
+            .await?;
+        Ok(())
+    })
+}
+
+#[derive(Clone)]
+struct Passgen(Arc<Inner>);
+
+// Statuses are large, so are boxed until they are returned to tonic.
+type Reply<T> = Result<Response<T>, Box<Status>>;
+
+struct Inner {
+    clients: Mutex<Clients>,
+    backend: Backend,
+}
+
+impl Inner {
+    // Counts the call against its client's rate, returning the token it
+    // carries.
+    fn admit<T>(&self, request: &Request<T>) -> Result<usize, Box<Status>> {
+        let addr =
+            request.remote_addr()
+                .map_or(Ipv4Addr::LOCALHOST.into(), |addr| addr.ip());
+        let bearer =
+            request.metadata()
+                .get("authorization")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.split_once(' '))
+                .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("bearer"))
+                .map(|(_, bearer)| bearer.trim());
+        let mut clients = self.lock();
+        if let Some(retry_after) = clients.admit(addr) {
+            let err = LimitExceeded { retry_after, quota: false };
+            return Err(Status::resource_exhausted(err.to_string()).into());
+        }
+        clients.authorize(bearer).ok_or_else(|| {
+            Status::unauthenticated(tr("a valid bearer token is needed")).into()
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Clients> {
+        self.clients.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn spec(&self, spec: Option<Spec>) -> Result<PasswordSpec, Box<Status>> {
+        (self.backend.spec)(spec.unwrap_or_default())
+            .map_err(|msg| Status::invalid_argument(msg).into())
+    }
+
+    fn generate(
+        &self,
+        request: Request<GenerateRequest>,
+    ) -> Reply<Passwords> {
+        let token = self.admit(&request)?;
+        let request = request.into_inner();
+        let count = request.count.unwrap_or(1);
+        if count == 0 {
+            let msg = tr("number of passwords must not be zero");
+            return Err(Status::invalid_argument(msg).into());
+        }
+        let spec = self.spec(request.spec.clone())?;
+        self.lock().quota(token).take(count).map_err(|err| {
+            let status =
+                match err.downcast_ref::<LimitExceeded>() {
+                    Some(err) => Status::resource_exhausted(err.to_string()),
+                    None => Status::invalid_argument(err.to_string()),
+                };
+            Box::new(status)
+        })?;
+        let rng = self.backend.rng;
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        tokio::task::spawn_blocking(move || {
+            let mut rng =
+                match rng() {
+                    Ok(rng) => rng,
+                    Err(err) => {
+                        let status = Status::internal(err.to_string());
+                        let _ = sender.blocking_send(Err(status));
+                        return;
+                    },
+                };
+            let entropy_bits = spec.generator().entropy_bits();
+            for _ in 0..count {
+                // Never reallocated, so that no copies are left.
+                let mut password =
+                    String::with_capacity(spec.max_length().get() * 4);
+                let result =
+                    spec.generator()
+                        .generate(&mut rng, &mut password)
+                        .map(|()| GenerateResponse { password, entropy_bits })
+                        .map_err(|err| Status::internal(err.to_string()));
+                let failed = result.is_err();
+                // Blocks while the channel is full, and fails once the
+                // client has gone.
+                if sender.blocking_send(result).is_err() || failed {
+                    return;
+                }
+            }
+        });
+        Ok(Response::new(Passwords(receiver)))
+    }
+
+    fn check(
+        &self,
+        request: Request<CheckRequest>,
+    ) -> Reply<CheckResponse> {
+        self.admit(&request)?;
+        let entropy_bits = estimate_entropy(&request.get_ref().password);
+        Ok(Response::new(CheckResponse { entropy_bits }))
+    }
+
+    fn entropy(
+        &self,
+        request: Request<EntropyRequest>,
+    ) -> Reply<EntropyResponse> {
+        self.admit(&request)?;
+        let spec = self.spec(request.into_inner().spec)?;
+        Ok(Response::new(EntropyResponse {
+            entropy_bits: spec.generator().entropy_bits(),
+            charset_size: spec.charset().len() as u64,
+        }))
+    }
+}
+
+// The passwords of a Generate call, as they are generated.
+struct Passwords(mpsc::Receiver<Result<GenerateResponse, Status>>);
+
+impl Stream for Passwords {
+    type Item = Result<GenerateResponse, Status>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.0.poll_recv(cx)
+    }
+}
+
+struct GenerateMethod(Arc<Inner>);
+
+impl ServerStreamingService<GenerateRequest> for GenerateMethod {
+    type Response = GenerateResponse;
+    type ResponseStream = Passwords;
+    type Future = Ready<Result<Response<Passwords>, Status>>;
+
+    fn call(&mut self, request: Request<GenerateRequest>) -> Self::Future {
+        future::ready(self.0.generate(request).map_err(|status| *status))
+    }
+}
+
+struct CheckMethod(Arc<Inner>);
+
+impl UnaryService<CheckRequest> for CheckMethod {
+    type Response = CheckResponse;
+    type Future = Ready<Result<Response<CheckResponse>, Status>>;
+
+    fn call(&mut self, request: Request<CheckRequest>) -> Self::Future {
+        future::ready(self.0.check(request).map_err(|status| *status))
+    }
+}
+
+struct EntropyMethod(Arc<Inner>);
+
+impl UnaryService<EntropyRequest> for EntropyMethod {
+    type Response = EntropyResponse;
+    type Future = Ready<Result<Response<EntropyResponse>, Status>>;
+
+    fn call(&mut self, request: Request<EntropyRequest>) -> Self::Future {
+        future::ready(self.0.entropy(request).map_err(|status| *status))
+    }
+}
+
+impl<B> Service<http::Request<B>> for Passgen
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(
+        &mut self,
+        _: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let inner = Arc::clone(&self.0);
+        Box::pin(async move {
+            let method =
+                request.uri()
+                    .path()
+                    .strip_prefix(&format!("/{}/", SERVICE_NAME))
+                    .map(str::to_string);
+            let response =
+                match method.as_deref() {
+                    Some("Generate") => {
+                        Grpc::new(ProstCodec::default())
+                            .server_streaming(GenerateMethod(inner), request)
+                            .await
+                    },
+                    Some("Check") => {
+                        Grpc::new(ProstCodec::default())
+                            .unary(CheckMethod(inner), request)
+                            .await
+                    },
+                    Some("Entropy") => {
+                        Grpc::new(ProstCodec::default())
+                            .unary(EntropyMethod(inner), request)
+                            .await
+                    },
+                    _ => Status::unimplemented("").into_http(),
+                };
+            Ok(response)
+        })
+    }
+}
+
+impl NamedService for Passgen {
+    const NAME: &'static str = SERVICE_NAME;
+}
@@ -0,0 +1,877 @@
+use std::collections::HashMap;
+use std::fmt;
+
+// The estimate follows zxcvbn (Wheeler, "zxcvbn: Low-Budget Password
+// Strength Estimation", USENIX Security 2016). The password is matched
+// against patterns people use: ranked dictionary words, forwards, backwards
+// or with l33t substitutions; runs of adjacent keys; repeats; sequences;
+// and years and dates. Each match is worth the guesses an attacker trying
+// that pattern would need to reach it, and the password is worth the
+// fewest guesses of any way of covering it with matches, the gaps filled by
+// brute force. Only the built-in lists ship; more words can be added.
+
+// Common passwords, from most to least common.
+const PASSWORDS: &[&str] = &[
+    "123456", "password", "12345678", "qwerty", "123456789", "12345",
+    "1234", "111111", "1234567", "dragon", "123123", "baseball", "abc123",
+    "football", "monkey", "letmein", "696969", "shadow", "master", "666666",
+    "qwertyuiop", "123321", "mustang", "1234567890", "michael", "654321",
+    "superman", "1qaz2wsx", "7777777", "121212", "000000", "qazwsx",
+    "123qwe", "killer", "trustno1", "jordan", "jennifer", "zxcvbnm",
+    "asdfgh", "hunter", "buster", "soccer", "harley", "batman", "andrew",
+    "tigger", "sunshine", "iloveyou", "2000", "charlie", "robert",
+    "thomas", "hockey", "ranger", "daniel", "starwars", "klaster",
+    "112233", "george", "computer", "michelle", "jessica", "pepper",
+    "1111", "zxcvbn", "555555", "11111111", "131313", "freedom", "777777",
+    "pass", "maggie", "159753", "aaaaaa", "ginger", "princess", "joshua",
+    "cheese", "amanda", "summer", "love", "ashley", "nicole", "chelsea",
+    "biteme", "matthew", "access", "yankees", "987654321", "dallas",
+    "austin", "thunder", "taylor", "matrix", "admin", "welcome", "login",
+    "passw0rd", "hello", "secret", "solo", "princess1", "qwerty123",
+    "password1", "abcdef", "abcd1234", "welcome1", "monkey1", "dragon1",
+];
+
+// Common English words and names, from most to least common.
+const WORDS: &[&str] = &[
+    "the", "be", "and", "of", "a", "in", "to", "have", "it", "i", "that",
+    "for", "you", "he", "with", "on", "do", "say", "this", "they", "at",
+    "but", "we", "his", "from", "not", "by", "she", "or", "as", "what",
+    "go", "their", "can", "who", "get", "if", "would", "her", "all", "my",
+    "make", "about", "know", "will", "up", "one", "time", "there", "year",
+    "so", "think", "when", "which", "them", "some", "me", "people", "take",
+    "out", "into", "just", "see", "him", "your", "come", "could", "now",
+    "than", "like", "other", "how", "then", "its", "our", "two", "more",
+    "these", "want", "way", "look", "first", "also", "new", "because",
+    "day", "use", "no", "man", "find", "here", "thing", "give", "many",
+    "well", "only", "those", "tell", "very", "even", "back", "any", "good",
+    "woman", "through", "us", "life", "child", "work", "down", "may",
+    "after", "should", "call", "world", "over", "school", "still", "try",
+    "last", "ask", "need", "too", "feel", "three", "state", "never",
+    "become", "between", "high", "really", "something", "another",
+    "family", "own", "leave", "put", "old", "while", "mean", "keep",
+    "student", "why", "let", "great", "same", "big", "group", "begin",
+    "seem", "country", "help", "talk", "where", "turn", "problem", "every",
+    "start", "hand", "might", "show", "part", "against", "place", "such",
+    "again", "few", "case", "week", "company", "system", "each", "right",
+    "program", "hear", "question", "during", "play", "government", "run",
+    "small", "number", "off", "always", "move", "night", "live", "point",
+    "believe", "hold", "today", "bring", "happen", "next", "without",
+    "before", "large", "million", "must", "home", "under", "water", "room",
+    "write", "mother", "area", "national", "money", "story", "young",
+    "fact", "month", "different", "lot", "study", "book", "eye", "job",
+    "word", "though", "business", "issue", "side", "kind", "four", "head",
+    "far", "black", "long", "both", "little", "house", "yes", "since",
+    "provide", "service", "around", "friend", "important", "father", "sit",
+    "away", "until", "power", "hour", "game", "often", "yet", "line",
+    "end", "among", "ever", "stand", "bad", "lose", "however", "member",
+    "pay", "law", "meet", "car", "city", "almost", "include", "continue",
+    "set", "later", "community", "much", "name", "five", "once", "white",
+    "least", "president", "learn", "real", "change", "team", "minute",
+    "best", "several", "idea", "kid", "body", "information", "nothing",
+    "ago", "lead", "social", "understand", "whether", "watch", "together",
+    "follow", "parent", "stop", "face", "anything", "create", "public",
+    "already", "speak", "others", "read", "level", "allow", "add", "office",
+    "spend", "door", "health", "person", "art", "sure", "war", "history",
+    "party", "within", "grow", "result", "open", "morning", "walk",
+    "reason", "low", "win", "research", "girl", "guy", "early", "food",
+    "moment", "himself", "air", "teacher", "force", "offer", "enough",
+    "education", "across", "although", "remember", "foot", "second", "boy",
+    "maybe", "toward", "able", "age", "policy", "everything", "love",
+    "process", "music", "including", "consider", "appear", "actually",
+    "buy", "probably", "human", "wait", "serve", "market", "die", "send",
+    "expect", "sense", "build", "stay", "fall", "oh", "nation", "plan",
+    "cut", "college", "interest", "death", "course", "someone",
+    "experience", "behind", "reach", "local", "kill", "six", "remain",
+    "effect", "yeah", "suggest", "class", "control", "raise", "care",
+    "perhaps", "late", "hard", "field", "else", "pass", "former", "sell",
+    "major", "sometimes", "require", "along", "development", "themselves",
+    "report", "role", "better", "economic", "effort", "decide", "rate",
+    "strong", "possible", "heart", "drug", "show", "leader", "light",
+    "voice", "wife", "whole", "police", "mind", "finally", "pull",
+    "return", "free", "military", "price", "less", "according", "decision",
+    "explain", "son", "hope", "develop", "view", "relationship", "carry",
+    "town", "road", "drive", "arm", "true", "federal", "break", "difference",
+    "thank", "receive", "value", "international", "building", "action",
+    "full", "model", "join", "season", "society", "tax", "director",
+    "position", "player", "agree", "especially", "record", "pick", "wear",
+    "paper", "special", "space", "ground", "form", "support", "event",
+    "official", "whose", "matter", "everyone", "center", "couple", "site",
+    "project", "hit", "base", "activity", "star", "table", "court",
+    "produce", "eat", "american", "teach", "oil", "half", "situation",
+    "easy", "cost", "industry", "figure", "street", "image", "itself",
+    "phone", "either", "data", "cover", "quite", "picture", "clear",
+    "practice", "piece", "land", "recent", "describe", "product", "doctor",
+    "wall", "patient", "worker", "news", "test", "movie", "certain",
+    "north", "personal", "simply", "third", "technology", "catch", "step",
+    "baby", "computer", "type", "attention", "draw", "film", "tree",
+    "source", "red", "nearly", "organization", "choose", "cause", "hair",
+    "century", "evidence", "window", "difficult", "listen", "soon",
+    "culture", "billion", "chance", "brother", "energy", "period", "summer",
+    "realize", "hundred", "available", "plant", "likely", "opportunity",
+    "term", "short", "letter", "condition", "choice", "single", "rule",
+    "daughter", "administration", "south", "husband", "floor", "campaign",
+    "material", "population", "economy", "medical", "hospital", "church",
+    "close", "thousand", "risk", "current", "fire", "future", "wrong",
+    "involve", "defense", "anyone", "increase", "security", "bank",
+    "myself", "certainly", "west", "sport", "board", "seek", "per",
+    "subject", "officer", "private", "rest", "behavior", "deal",
+    "performance", "fight", "throw", "top", "quickly", "past", "goal",
+    "bed", "order", "author", "fill", "represent", "focus", "foreign",
+    "drop", "blood", "upon", "agency", "push", "nature", "color", "store",
+    "reduce", "sound", "note", "fine", "near", "movement", "page", "enter",
+    "share", "common", "poor", "natural", "race", "concern", "series",
+    "significant", "similar", "hot", "language", "usually", "response",
+    "dead", "rise", "animal", "factor", "decade", "article", "shoot",
+    "east", "save", "seven", "artist", "away", "scene", "stock", "career",
+    "despite", "central", "eight", "thus", "treatment", "beyond", "happy",
+    "exactly", "protect", "approach", "lie", "size", "dog", "fund",
+    "serious", "occur", "media", "ready", "sign", "thought", "list",
+    "individual", "simple", "quality", "pressure", "accept", "answer",
+    "resource", "identify", "left", "meeting", "determine", "prepare",
+    "disease", "whatever", "success", "argue", "cup", "particularly",
+    "amount", "ability", "staff", "recognize", "indicate", "character",
+    "growth", "loss", "degree", "wonder", "attack", "herself", "region",
+    "television", "box", "training", "pretty", "trade", "deal", "election",
+    "everybody", "physical", "lay", "general", "feeling", "standard",
+    "bill", "message", "fail", "outside", "arrive", "analysis", "benefit",
+    "sex", "forward", "lawyer", "present", "section", "environmental",
+    "glass", "skill", "sister", "professor", "operation", "financial",
+    "crime", "stage", "ok", "compare", "authority", "miss", "design",
+    "sort", "act", "ten", "knowledge", "gun", "station", "blue", "state",
+    "strategy", "clearly", "discuss", "indeed", "truth", "song", "example",
+    "democratic", "check", "environment", "leg", "dark", "various", "rather",
+    "laugh", "guess", "executive", "prove", "hang", "entire", "rock",
+    "forget", "claim", "remove", "manager", "enjoy", "network", "legal",
+    "religious", "cold", "final", "main", "science", "green", "memory",
+    "card", "above", "seat", "cell", "establish", "nice", "trial", "expert",
+    "spring", "firm", "radio", "visit", "management", "avoid", "imagine",
+    "tonight", "huge", "ball", "finish", "yourself", "theory", "impact",
+    "respond", "statement", "maintain", "charge", "popular", "traditional",
+    "onto", "reveal", "direction", "weapon", "employee", "cultural",
+    "contain", "peace", "pain", "apply", "play", "measure", "wide", "shake",
+    "fly", "interview", "manage", "chair", "fish", "particular", "camera",
+    "structure", "politics", "perform", "bit", "weight", "suddenly",
+    "discover", "candidate", "production", "treat", "trip", "evening",
+    "affect", "inside", "conference", "unit", "style", "adult", "worry",
+    "range", "mention", "deep", "edge", "specific", "writer", "trouble",
+    "necessary", "throughout", "challenge", "fear", "shoulder",
+    "institution", "middle", "sea", "dream", "bar", "beautiful", "property",
+    "instead", "improve", "stuff", "james", "john", "robert", "michael",
+    "william", "david", "richard", "joseph", "thomas", "charles", "mary",
+    "patricia", "linda", "barbara", "elizabeth", "jennifer", "maria",
+    "susan", "margaret", "dorothy", "sarah", "karen", "nancy", "lisa",
+    "anna", "emma", "olivia", "sophia", "liam", "noah", "oliver", "jack",
+    "harry", "george", "charlie", "alice", "peter", "paul", "mark", "monkey",
+    "dragon", "tiger", "lion", "bear", "eagle", "wolf", "horse", "cat",
+    "pizza", "coffee", "apple", "orange", "banana", "cherry", "chocolate",
+    "winter", "autumn", "sunday", "monday", "friday", "january", "july",
+    "december", "london", "paris", "berlin", "texas", "california",
+];
+
+// Characters that stand for letters in l33t speak.
+const L33T: &[(char, &str)] = &[
+    ('4', "a"),
+    ('@', "a"),
+    ('8', "b"),
+    ('(', "c"),
+    ('{', "c"),
+    ('[', "c"),
+    ('<', "c"),
+    ('3', "e"),
+    ('6', "g"),
+    ('9', "g"),
+    ('1', "il"),
+    ('!', "i"),
+    ('|', "il"),
+    ('7', "lt"),
+    ('0', "o"),
+    ('$', "s"),
+    ('5', "s"),
+    ('+', "t"),
+    ('%', "x"),
+    ('2', "z"),
+];
+
+// The QWERTY layout's rows, unshifted and shifted, each starting a quarter
+// of a key further right than the one above.
+const QWERTY: &[(&str, &str)] = &[
+    ("`1234567890-=", "~!@#$%^&*()_+"),
+    ("qwertyuiop[]\\", "QWERTYUIOP{}|"),
+    ("asdfghjkl;'", "ASDFGHJKL:\""),
+    ("zxcvbnm,./", "ZXCVBNM<>?"),
+];
+// Of the graph above: its keys and their average number of neighbours.
+const QWERTY_KEYS: f64 = 47.0;
+const QWERTY_AVERAGE_DEGREE: f64 = 4.6;
+
+const REFERENCE_YEAR: i32 = 2026;
+const MIN_YEAR_SPACE: f64 = 20.0;
+const BRUTEFORCE_CARDINALITY: f64 = 10.0;
+const MIN_SUBMATCH_GUESSES_SINGLE_CHAR: f64 = 10.0;
+const MIN_SUBMATCH_GUESSES_MULTI_CHAR: f64 = 50.0;
+// The guesses an attacker is assumed to spend on each pattern before
+// trying longer sequences of patterns.
+const MIN_GUESSES_BEFORE_GROWING_SEQUENCE: f64 = 10_000.0;
+// Later characters are guessed by brute force, which keeps the search
+// quadratic in a bounded length.
+const MAX_MATCHED_LEN: usize = 100;
+
+/// A kind of pattern in a password.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GuessPattern {
+    /// A word of a ranked list, where 1 is the most common.
+    Dictionary {
+        /// The word's rank.
+        rank: usize,
+        /// Whether the word is written backwards.
+        reversed: bool,
+        /// Whether characters stand in for letters, as 4 for a.
+        l33t: bool,
+    },
+    /// A run of keys next to each other on a QWERTY keyboard.
+    Spatial {
+        /// The number of changes of direction.
+        turns: usize,
+        /// The number of shifted keys.
+        shifted: usize,
+    },
+    /// A substring written several times over.
+    Repeat {
+        /// The number of times it is written.
+        count: usize,
+    },
+    /// A run of letters or digits at a steady step, as abc or 97531.
+    Sequence {
+        /// Whether the run counts upwards.
+        ascending: bool,
+    },
+    /// A year, or a date with one.
+    Date,
+    /// Characters matching no pattern.
+    Bruteforce,
+}
+
+impl fmt::Display for GuessPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GuessPattern::Dictionary { rank, reversed, l33t } => {
+                write!(f, "dictionary word of rank {}", rank)?;
+                if *reversed {
+                    write!(f, ", reversed")?;
+                }
+                if *l33t {
+                    write!(f, ", l33t")?;
+                }
+                Ok(())
+            },
+            GuessPattern::Spatial { turns, shifted } => {
+                write!(f, "keyboard run, {} turns, {} shifted", turns, shifted)
+            },
+            GuessPattern::Repeat { count } => {
+                write!(f, "repeated {} times", count)
+            },
+            GuessPattern::Sequence { ascending: true } => write!(f, "sequence"),
+            GuessPattern::Sequence { ascending: false } => {
+                write!(f, "descending sequence")
+            },
+            GuessPattern::Date => write!(f, "date"),
+            GuessPattern::Bruteforce => write!(f, "brute force"),
+        }
+    }
+}
+
+/// A part of a password guessed as one pattern.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PatternMatch {
+    /// The index of the first character.
+    pub start: usize,
+    /// The index after the last character.
+    pub end: usize,
+    /// The pattern.
+    pub pattern: GuessPattern,
+    /// The guesses needed to reach this part with this pattern.
+    pub guesses: f64,
+}
+
+/// How many guesses an attacker trying the patterns people use would need
+/// to find a password.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GuessEstimate {
+    /// The guesses needed.
+    pub guesses: f64,
+    /// The matches the guesses are counted over, in order.
+    pub matches: Vec<PatternMatch>,
+}
+
+impl GuessEstimate {
+    /// Returns the base-10 logarithm of the guesses.
+    pub fn guesses_log10(&self) -> f64 {
+        self.guesses.log10()
+    }
+}
+
+/// Ranked word lists to match passwords against, besides the built-in ones.
+#[derive(Clone, Debug, Default)]
+pub struct Dictionaries {
+    lists: Vec<HashMap<String, usize>>,
+}
+
+impl Dictionaries {
+    /// Returns the built-in lists of common passwords and of common words.
+    pub fn built_in() -> Self {
+        let mut dictionaries = Dictionaries::default();
+        dictionaries.add(PASSWORDS.iter().copied());
+        dictionaries.add(WORDS.iter().copied());
+        dictionaries
+    }
+
+    /// Adds a list of words, from most to least common. Words are matched
+    /// in lowercase.
+    pub fn add<'a>(&mut self, words: impl IntoIterator<Item = &'a str>) {
+        let mut list = HashMap::new();
+        for word in words {
+            let rank = list.len() + 1;
+            list.entry(word.to_lowercase()).or_insert(rank);
+        }
+        self.lists.push(list);
+    }
+
+    fn rank(&self, word: &str) -> Option<usize> {
+        self.lists.iter().filter_map(|list| list.get(word).copied()).min()
+    }
+}
+
+/// Estimates the guesses needed to find the password, matching it against
+/// the built-in word lists.
+pub fn estimate_guesses(password: &str) -> GuessEstimate {
+    estimate_guesses_with(password, &Dictionaries::built_in())
+}
+
+/// Estimates the guesses needed to find the password, matching it against
+/// these word lists.
+pub fn estimate_guesses_with(
+    password: &str,
+    dictionaries: &Dictionaries,
+) -> GuessEstimate {
+    let chars = password.chars().collect::<Vec<_>>();
+    let matched_len = chars.len().min(MAX_MATCHED_LEN);
+    let mut matches = Vec::new();
+    dictionary_matches(&chars[..matched_len], dictionaries, &mut matches);
+    spatial_matches(&chars[..matched_len], &mut matches);
+    repeat_matches(&chars[..matched_len], dictionaries, &mut matches);
+    sequence_matches(&chars[..matched_len], &mut matches);
+    date_matches(&chars[..matched_len], &mut matches);
+    most_guessable(chars.len(), matches)
+}
+
+fn dictionary_matches(
+    chars: &[char],
+    dictionaries: &Dictionaries,
+    matches: &mut Vec<PatternMatch>,
+) {
+    let lower =
+        chars.iter()
+            .map(|ch| ch.to_lowercase().next().unwrap_or(*ch))
+            .collect::<Vec<_>>();
+    for start in 0..chars.len() {
+        for end in start + 1..=chars.len() {
+            let token = &lower[start..end];
+            let variations = uppercase_variations(&chars[start..end]);
+            let word = token.iter().collect::<String>();
+            if let Some(rank) = dictionaries.rank(&word) {
+                let pattern =
+                    GuessPattern::Dictionary {
+                        rank,
+                        reversed: false,
+                        l33t: false,
+                    };
+                let guesses = rank as f64 * variations;
+                matches.push(PatternMatch { start, end, pattern, guesses });
+            }
+            let reversed = token.iter().rev().collect::<String>();
+            if reversed != word {
+                if let Some(rank) = dictionaries.rank(&reversed) {
+                    let pattern =
+                        GuessPattern::Dictionary {
+                            rank,
+                            reversed: true,
+                            l33t: false,
+                        };
+                    let guesses = rank as f64 * variations * 2.0;
+                    matches.push(PatternMatch {
+                        start,
+                        end,
+                        pattern,
+                        guesses,
+                    });
+                }
+            }
+            for (word, l33t_variations) in unl33t(token) {
+                if let Some(rank) = dictionaries.rank(&word) {
+                    let pattern =
+                        GuessPattern::Dictionary {
+                            rank,
+                            reversed: false,
+                            l33t: true,
+                        };
+                    let guesses = rank as f64 * variations * l33t_variations;
+                    matches.push(PatternMatch {
+                        start,
+                        end,
+                        pattern,
+                        guesses,
+                    });
+                }
+            }
+        }
+    }
+}
+
+// The ways of capitalising a word that an attacker would try before this
+// one: all lowercase is tried first, then a capital at the start or end or
+// all capitals, then mixtures.
+fn uppercase_variations(chars: &[char]) -> f64 {
+    let upper = chars.iter().filter(|ch| ch.is_uppercase()).count();
+    let lower = chars.iter().filter(|ch| ch.is_lowercase()).count();
+    if upper == 0 {
+        return 1.0;
+    }
+    let first_only = chars[0].is_uppercase() && upper == 1;
+    let last_only = chars[chars.len() - 1].is_uppercase() && upper == 1;
+    if first_only || last_only || lower == 0 {
+        return 2.0;
+    }
+    (1..=upper.min(lower))
+        .map(|idx| binomial(upper + lower, idx))
+        .sum()
+}
+
+// The words the token could be with its l33t characters read as letters,
+// and the variations an attacker would try for each. Tokens with more than
+// a few ambiguous characters are not read at all.
+fn unl33t(token: &[char]) -> Vec<(String, f64)> {
+    let subs =
+        token.iter()
+            .map(|ch| L33T.iter().find(|(l33t, _)| l33t == ch))
+            .collect::<Vec<_>>();
+    if subs.iter().all(Option::is_none) {
+        return Vec::new();
+    }
+    let num_ambiguous =
+        subs.iter().flatten().filter(|(_, letters)| letters.len() > 1).count();
+    if num_ambiguous > 4 {
+        return Vec::new();
+    }
+    let mut words = vec![String::new()];
+    for (ch, sub) in token.iter().zip(&subs) {
+        words =
+            match sub {
+                Some((_, letters)) => {
+                    words.iter()
+                        .flat_map(|word| {
+                            letters.chars().map(move |letter| {
+                                let mut word = word.clone();
+                                word.push(letter);
+                                word
+                            })
+                        })
+                        .collect()
+                },
+                None => {
+                    for word in &mut words {
+                        word.push(*ch);
+                    }
+                    words
+                },
+            };
+    }
+    words.into_iter()
+        .map(|word| {
+            let variations = l33t_variations(token, &word);
+            (word, variations)
+        })
+        .collect()
+}
+
+// For each letter substituted, the ways of substituting some of its
+// occurrences.
+fn l33t_variations(token: &[char], word: &str) -> f64 {
+    let mut counts = HashMap::<(char, char), usize>::new();
+    for (&ch, letter) in token.iter().zip(word.chars()) {
+        *counts.entry((ch, letter)).or_default() += 1;
+    }
+    let mut variations = 1.0;
+    for (&(ch, letter), &subbed) in &counts {
+        if ch == letter {
+            continue;
+        }
+        let unsubbed = counts.get(&(letter, letter)).copied().unwrap_or(0);
+        variations *=
+            if unsubbed == 0 {
+                2.0
+            } else {
+                (1..=subbed.min(unsubbed))
+                    .map(|idx| binomial(subbed + unsubbed, idx))
+                    .sum()
+            };
+    }
+    variations
+}
+
+// Where each key is, and whether it is shifted.
+fn key_position(ch: char) -> Option<(usize, usize, bool)> {
+    QWERTY.iter().enumerate().find_map(|(row, (plain, shifted))| {
+        if let Some(col) = plain.chars().position(|key| key == ch) {
+            return Some((row, col, false));
+        }
+        shifted.chars().position(|key| key == ch).map(|col| (row, col, true))
+    })
+}
+
+// The direction from one key to the next if they are neighbours: left,
+// right, up-left, up-right, down-left or down-right, as rows are staggered.
+fn direction(from: (usize, usize), to: (usize, usize)) -> Option<usize> {
+    let (row, col) = (from.0 as isize, from.1 as isize);
+    let (to_row, to_col) = (to.0 as isize, to.1 as isize);
+    [
+        (row, col - 1),
+        (row, col + 1),
+        (row - 1, col),
+        (row - 1, col + 1),
+        (row + 1, col - 1),
+        (row + 1, col),
+    ]
+    .iter()
+    .position(|&neighbour| neighbour == (to_row, to_col))
+}
+
+fn spatial_matches(chars: &[char], matches: &mut Vec<PatternMatch>) {
+    let mut start = 0;
+    while start + 2 < chars.len() {
+        let Some((row, col, shifted)) = key_position(chars[start]) else {
+            start += 1;
+            continue;
+        };
+        let mut end = start + 1;
+        let mut position = (row, col);
+        let mut last_direction = None;
+        let mut turns = 0;
+        let mut num_shifted = usize::from(shifted);
+        while end < chars.len() {
+            let Some((row, col, shifted)) = key_position(chars[end]) else {
+                break;
+            };
+            let Some(direction) = direction(position, (row, col)) else {
+                break;
+            };
+            if last_direction != Some(direction) {
+                turns += 1;
+                last_direction = Some(direction);
+            }
+            num_shifted += usize::from(shifted);
+            position = (row, col);
+            end += 1;
+        }
+        if end - start >= 3 {
+            let len = end - start;
+            let guesses = spatial_guesses(len, turns, num_shifted);
+            let pattern = GuessPattern::Spatial { turns, shifted: num_shifted };
+            matches.push(PatternMatch { start, end, pattern, guesses });
+            start = end - 1;
+        } else {
+            start += 1;
+        }
+    }
+}
+
+// Runs of up to this length, starting on any key and turning up to this
+// often, with any of these keys shifted.
+fn spatial_guesses(len: usize, turns: usize, shifted: usize) -> f64 {
+    let mut guesses = 0.0;
+    for run_len in 2..=len {
+        for num_turns in 1..=turns.min(run_len - 1) {
+            guesses +=
+                binomial(run_len - 1, num_turns - 1)
+                    * QWERTY_KEYS
+                    * QWERTY_AVERAGE_DEGREE.powi(num_turns as i32);
+        }
+    }
+    let unshifted = len - shifted;
+    if shifted > 0 {
+        guesses *=
+            if unshifted == 0 {
+                2.0
+            } else {
+                (1..=shifted.min(unshifted))
+                    .map(|idx| binomial(len, idx))
+                    .sum()
+            };
+    }
+    guesses
+}
+
+// The longest repeat starting at each position, counted as the guesses of
+// the repeated part times the number of repeats.
+fn repeat_matches(
+    chars: &[char],
+    dictionaries: &Dictionaries,
+    matches: &mut Vec<PatternMatch>,
+) {
+    let mut start = 0;
+    while start < chars.len() {
+        let mut best = None;
+        for base_len in 1..=(chars.len() - start) / 2 {
+            let base = &chars[start..start + base_len];
+            let count =
+                chars[start..]
+                    .chunks(base_len)
+                    .take_while(|chunk| *chunk == base)
+                    .count();
+            let span = base_len * count;
+            let longer = best.is_none_or(|(_, best_span, _)| span > best_span);
+            if count >= 2 && longer {
+                best = Some((base_len, span, count));
+            }
+        }
+        match best {
+            Some((base_len, span, count)) => {
+                let base =
+                    chars[start..start + base_len].iter().collect::<String>();
+                let base_guesses =
+                    estimate_guesses_with(&base, dictionaries).guesses;
+                let pattern = GuessPattern::Repeat { count };
+                let guesses = base_guesses * count as f64;
+                let end = start + span;
+                matches.push(PatternMatch { start, end, pattern, guesses });
+                start = end;
+            },
+            None => start += 1,
+        }
+    }
+}
+
+fn sequence_matches(chars: &[char], matches: &mut Vec<PatternMatch>) {
+    let mut start = 0;
+    while start + 2 < chars.len() {
+        let step = chars[start + 1] as i32 - chars[start] as i32;
+        let kind = sequence_kind(chars[start]);
+        let same_kind = |ch: char| sequence_kind(ch) == kind;
+        let mut end = start + 1;
+        while end < chars.len()
+            && same_kind(chars[end])
+            && chars[end] as i32 - chars[end - 1] as i32 == step
+        {
+            end += 1;
+        }
+        if end - start >= 3 && step != 0 && step.abs() <= 5 && kind.is_some() {
+            let first = chars[start];
+            let base =
+                if "aAzZ019".contains(first) {
+                    4.0
+                } else if first.is_ascii_digit() {
+                    10.0
+                } else {
+                    26.0
+                };
+            let ascending = step > 0;
+            let direction = if ascending { 1.0 } else { 2.0 };
+            let guesses = base * direction * (end - start) as f64;
+            let pattern = GuessPattern::Sequence { ascending };
+            matches.push(PatternMatch { start, end, pattern, guesses });
+            start = end - 1;
+        } else {
+            start += 1;
+        }
+    }
+}
+
+fn sequence_kind(ch: char) -> Option<u8> {
+    match ch {
+        'a'..='z' => Some(0),
+        'A'..='Z' => Some(1),
+        '0'..='9' => Some(2),
+        _ => None,
+    }
+}
+
+// Four-digit years, and dates of a day and month, in either order, before
+// or after a four-digit year, run together or separated.
+fn date_matches(chars: &[char], matches: &mut Vec<PatternMatch>) {
+    let digits_at = |start: usize, len: usize| {
+        let digits = chars.get(start..start + len)?;
+        if !digits.iter().all(char::is_ascii_digit) {
+            return None;
+        }
+        digits.iter().collect::<String>().parse::<u32>().ok()
+    };
+    let is_day_month =
+        |day: u32, month: u32| {
+            (1..=31).contains(&day) && (1..=12).contains(&month)
+        };
+    for start in 0..chars.len() {
+        if let Some(year) = digits_at(start, 4).filter(|&y| is_year(y)) {
+            let end = start + 4;
+            let guesses = year_space(year);
+            let pattern = GuessPattern::Date;
+            matches.push(PatternMatch { start, end, pattern, guesses });
+        }
+        for (year_first, separated) in
+            [(false, false), (false, true), (true, false), (true, true)]
+        {
+            let lens = if year_first { [4, 2, 2] } else { [2, 2, 4] };
+            let sep = usize::from(separated);
+            let offsets = [0, lens[0] + sep, lens[0] + lens[1] + 2 * sep];
+            if separated {
+                let first_sep = chars.get(start + lens[0]).copied();
+                let second_sep = chars.get(start + offsets[2] - 1).copied();
+                let valid =
+                    first_sep.is_some_and(|ch| "/-. _".contains(ch))
+                        && first_sep == second_sep;
+                if !valid {
+                    continue;
+                }
+            }
+            let mut parts = [0; 3];
+            for (part, (offset, len)) in
+                parts.iter_mut().zip(offsets.iter().zip(lens))
+            {
+                match digits_at(start + offset, len) {
+                    Some(value) => *part = value,
+                    None => break,
+                }
+            }
+            let (year, first, second) =
+                if year_first {
+                    (parts[0], parts[1], parts[2])
+                } else {
+                    (parts[2], parts[0], parts[1])
+                };
+            let valid =
+                is_year(year)
+                    && (is_day_month(first, second)
+                        || is_day_month(second, first));
+            if valid {
+                let end = start + offsets[2] + lens[2];
+                let mut guesses = 365.0 * year_space(year);
+                if separated {
+                    guesses *= 4.0;
+                }
+                let pattern = GuessPattern::Date;
+                matches.push(PatternMatch { start, end, pattern, guesses });
+            }
+        }
+    }
+}
+
+fn is_year(year: u32) -> bool {
+    (1900..=2049).contains(&year)
+}
+
+fn year_space(year: u32) -> f64 {
+    let distance = (year as i32 - REFERENCE_YEAR).abs() as f64;
+    distance.max(MIN_YEAR_SPACE)
+}
+
+// Finds the covering of the password by matches and brute force that an
+// attacker reaches in the fewest guesses, counting for a covering of l
+// matches l! times the product of their guesses, for the orders they could
+// come in, plus the guesses spent on shorter coverings.
+fn most_guessable(len: usize, mut matches: Vec<PatternMatch>) -> GuessEstimate {
+    if len == 0 {
+        return GuessEstimate { guesses: 1.0, matches: Vec::new() };
+    }
+    for candidate in &mut matches {
+        if candidate.end - candidate.start < len {
+            let min_guesses =
+                if candidate.end - candidate.start == 1 {
+                    MIN_SUBMATCH_GUESSES_SINGLE_CHAR
+                } else {
+                    MIN_SUBMATCH_GUESSES_MULTI_CHAR
+                };
+            candidate.guesses = candidate.guesses.max(min_guesses);
+        }
+    }
+    for start in 0..len {
+        for end in start + 1..=len {
+            let guesses = BRUTEFORCE_CARDINALITY.powi((end - start) as i32);
+            let guesses =
+                if end - start < len {
+                    guesses.max(MIN_SUBMATCH_GUESSES_SINGLE_CHAR + 1.0)
+                } else {
+                    guesses
+                };
+            let pattern = GuessPattern::Bruteforce;
+            matches.push(PatternMatch { start, end, pattern, guesses });
+        }
+    }
+    // best[end][count]: the least log10 of the product of guesses of count
+    // matches covering the first end characters, and the last match.
+    let mut best = vec![vec![None::<(f64, usize)>; len + 1]; len + 1];
+    best[0][0] = Some((0.0, usize::MAX));
+    let mut by_end = vec![Vec::new(); len + 1];
+    for (idx, candidate) in matches.iter().enumerate() {
+        by_end[candidate.end].push(idx);
+    }
+    for end in 1..=len {
+        for &idx in &by_end[end] {
+            let candidate = &matches[idx];
+            // Brute force never follows brute force, which would only be a
+            // longer brute force counted as more matches.
+            for count in 0..len {
+                let Some((log_product, last)) = best[candidate.start][count]
+                else {
+                    continue;
+                };
+                let after_bruteforce =
+                    last != usize::MAX
+                        && matches[last].pattern == GuessPattern::Bruteforce;
+                let is_bruteforce =
+                    candidate.pattern == GuessPattern::Bruteforce;
+                if after_bruteforce && is_bruteforce {
+                    continue;
+                }
+                let log_product = log_product + candidate.guesses.log10();
+                let slot = &mut best[end][count + 1];
+                if slot.is_none_or(|(other, _)| log_product < other) {
+                    *slot = Some((log_product, idx));
+                }
+            }
+        }
+    }
+    let (count, log_guesses) =
+        (1..=len)
+            .filter_map(|count| {
+                let (log_product, _) = best[len][count]?;
+                let log_factorial =
+                    (1..=count).map(|n| (n as f64).log10()).sum::<f64>();
+                let log_sequence =
+                    (count - 1) as f64
+                        * MIN_GUESSES_BEFORE_GROWING_SEQUENCE.log10();
+                let log_guesses =
+                    log_sum(log_factorial + log_product, log_sequence);
+                Some((count, log_guesses))
+            })
+            .min_by(|(_, left), (_, right)| left.total_cmp(right))
+            .unwrap_or((1, len as f64));
+    let mut sequence = Vec::new();
+    let mut end = len;
+    for count in (1..=count).rev() {
+        let Some((_, idx)) = best[end][count] else {
+            break;
+        };
+        sequence.push(matches[idx].clone());
+        end = matches[idx].start;
+    }
+    sequence.reverse();
+    GuessEstimate { guesses: 10f64.powf(log_guesses), matches: sequence }
+}
+
+// The log10 of the sum of two numbers given as log10s.
+fn log_sum(left: f64, right: f64) -> f64 {
+    let (high, low) = if left > right { (left, right) } else { (right, left) };
+    high + (1.0 + 10f64.powf(low - high)).log10()
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1.0, |value, idx| value * (n - idx) as f64 / (idx + 1) as f64)
+}
@@ -0,0 +1,51 @@
+use std::io;
+
+// Keeps generated passwords out of core dumps and away from a casually
+// attached debugger. A privileged debugger can still read the process's
+// memory.
+
+/// Disables core dumps and, where the platform allows it, debugger
+/// attachment.
+#[cfg(unix)]
+pub fn harden() -> io::Result<()> {
+    let limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    if unsafe { libc::setrlimit(libc::RLIMIT_CORE, &limit) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    deny_attach()
+}
+
+// Windows writes crash dumps through Windows Error Reporting, which is
+// configured per user or system rather than per process.
+#[cfg(not(unix))]
+pub fn harden() -> io::Result<()> {
+    Ok(())
+}
+
+// Non-dumpable processes cannot be attached to by unprivileged processes
+// and never dump core, whatever the resource limit.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn deny_attach() -> io::Result<()> {
+    if unsafe { libc::prctl(libc::PR_SET_DUMPABLE, 0) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn deny_attach() -> io::Result<()> {
+    let request = libc::PT_DENY_ATTACH;
+    let result = unsafe { libc::ptrace(request, 0, std::ptr::null_mut(), 0) };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(all(
+    unix,
+    not(any(target_os = "linux", target_os = "android", target_os = "macos")),
+))]
+fn deny_attach() -> io::Result<()> {
+    Ok(())
+}
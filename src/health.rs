@@ -0,0 +1,229 @@
+use rand::{CryptoRng, RngCore};
+use zeroize::Zeroize;
+
+use crate::{PassgenError, Result};
+
+/// The assumed min-entropy per byte of output, in bits.
+pub const DEFAULT_MIN_ENTROPY: f64 = 8.0;
+/// The base-2 logarithm of the false positive probability.
+pub const DEFAULT_ALPHA_LOG2: f64 = -30.0;
+/// The window size of the adaptive proportion test.
+pub const DEFAULT_WINDOW: usize = 512;
+
+/// The repetition count test of NIST SP 800-90B section 4.4.1.
+pub struct RepetitionCountTest {
+    cutoff: usize,
+    last: Option<u8>,
+    run: usize,
+}
+
+impl RepetitionCountTest {
+    /// Creates a test for a source with this min-entropy per sample and
+    /// false positive probability.
+    pub fn new(min_entropy: f64, alpha_log2: f64) -> Self {
+        let cutoff = 1 + (-alpha_log2 / min_entropy).ceil() as usize;
+        RepetitionCountTest { cutoff, last: None, run: 0 }
+    }
+
+    /// Returns the count at which the test fails.
+    pub fn cutoff(&self) -> usize {
+        self.cutoff
+    }
+
+    /// Feeds one sample to the test, failing if it reaches the cutoff.
+    pub fn feed(&mut self, sample: u8) -> Result<()> {
+        if self.last == Some(sample) {
+            self.run += 1;
+        } else {
+            self.last = Some(sample);
+            self.run = 1;
+        }
+        if self.run >= self.cutoff {
+            let msg = format!(
+                "repetition count test failed: {} consecutive samples of {}",
+                self.run,
+                sample,
+            );
+            return Err(PassgenError::HealthTestFailure(msg));
+        }
+        Ok(())
+    }
+}
+
+/// The adaptive proportion test of NIST SP 800-90B section 4.4.2.
+pub struct AdaptiveProportionTest {
+    window: usize,
+    cutoff: usize,
+    first: u8,
+    seen: usize,
+    count: usize,
+}
+
+impl AdaptiveProportionTest {
+    /// Creates a test for a source with this min-entropy per sample and
+    /// false positive probability, over windows of this many samples.
+    pub fn new(min_entropy: f64, alpha_log2: f64, window: usize) -> Self {
+        let p = (-min_entropy).exp2();
+        let cutoff = 1 + critical_binomial(window, p, alpha_log2.exp2());
+        AdaptiveProportionTest { window, cutoff, first: 0, seen: 0, count: 0 }
+    }
+
+    /// Returns the count at which the test fails.
+    pub fn cutoff(&self) -> usize {
+        self.cutoff
+    }
+
+    /// Returns the number of samples in each window.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Feeds one sample to the test, failing if it reaches the cutoff.
+    pub fn feed(&mut self, sample: u8) -> Result<()> {
+        if self.seen == 0 {
+            self.first = sample;
+            self.count = 1;
+        } else if sample == self.first {
+            self.count += 1;
+            if self.count >= self.cutoff {
+                let msg = format!(
+                    "adaptive proportion test failed: {} samples of {} in a \
+                     window of {}",
+                    self.count,
+                    sample,
+                    self.window,
+                );
+                return Err(PassgenError::HealthTestFailure(msg));
+            }
+        }
+        self.seen = (self.seen + 1) % self.window;
+        Ok(())
+    }
+}
+
+/// Both continuous health tests run together.
+pub struct HealthTests {
+    rct: RepetitionCountTest,
+    apt: AdaptiveProportionTest,
+}
+
+impl HealthTests {
+    /// Creates both tests with these parameters.
+    pub fn new(min_entropy: f64, alpha_log2: f64, window: usize) -> Self {
+        HealthTests {
+            rct: RepetitionCountTest::new(min_entropy, alpha_log2),
+            apt: AdaptiveProportionTest::new(min_entropy, alpha_log2, window),
+        }
+    }
+
+    /// Returns the repetition count test.
+    pub fn repetition_count(&self) -> &RepetitionCountTest {
+        &self.rct
+    }
+
+    /// Returns the adaptive proportion test.
+    pub fn adaptive_proportion(&self) -> &AdaptiveProportionTest {
+        &self.apt
+    }
+
+    /// Feeds the samples to both tests, failing on the first failure.
+    pub fn feed(&mut self, samples: &[u8]) -> Result<()> {
+        for &sample in samples {
+            self.rct.feed(sample)?;
+            self.apt.feed(sample)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for HealthTests {
+    fn default() -> Self {
+        let min_entropy = DEFAULT_MIN_ENTROPY;
+        HealthTests::new(min_entropy, DEFAULT_ALPHA_LOG2, DEFAULT_WINDOW)
+    }
+}
+
+/// A random number generator whose output passes through the continuous
+/// health tests. Once a test fails, every later draw fails too, and output
+/// that failed is zeroed rather than returned.
+///
+/// It is meant for entropy input, such as the seeds of a deterministic
+/// generator, rather than for every byte of generated passwords, where the
+/// false positives of a long run would add up.
+pub struct HealthTestedRng<R> {
+    rng: R,
+    tests: HealthTests,
+    failure: Option<String>,
+}
+
+impl<R: RngCore> HealthTestedRng<R> {
+    /// Tests the output of `rng` with these tests.
+    pub fn new(rng: R, tests: HealthTests) -> Self {
+        HealthTestedRng { rng, tests, failure: None }
+    }
+
+    /// Returns the tests.
+    pub fn tests(&self) -> &HealthTests {
+        &self.tests
+    }
+
+    /// Draws and discards this many samples, failing if they do not pass the
+    /// tests, as the start-up tests of SP 800-90B section 4.3 require before
+    /// any output is used.
+    pub fn startup(&mut self, num_samples: usize) -> Result<()> {
+        let mut samples = vec![0u8; num_samples];
+        let result = self.try_fill_bytes(&mut samples);
+        samples.zeroize();
+        result.map_err(crate::rng_failure)
+    }
+}
+
+impl<R: RngCore> RngCore for HealthTestedRng<R> {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(err) = self.try_fill_bytes(dest) {
+            panic!("random number generator failed: {}", err);
+        }
+    }
+
+    fn try_fill_bytes(
+        &mut self,
+        dest: &mut [u8],
+    ) -> std::result::Result<(), rand::Error> {
+        if let Some(msg) = &self.failure {
+            let err = PassgenError::HealthTestFailure(msg.clone());
+            return Err(rand::Error::new(err));
+        }
+        self.rng.try_fill_bytes(dest)?;
+        if let Err(err) = self.tests.feed(dest) {
+            dest.zeroize();
+            if let PassgenError::HealthTestFailure(msg) = &err {
+                self.failure = Some(msg.clone());
+            }
+            return Err(rand::Error::new(err));
+        }
+        Ok(())
+    }
+}
+
+impl<R: CryptoRng> CryptoRng for HealthTestedRng<R> {}
+
+// Smallest k such that P(X <= k) >= 1 - alpha for X ~ B(n, p).
+fn critical_binomial(n: usize, p: f64, alpha: f64) -> usize {
+    let mut pmf = (1.0 - p).powi(n as i32);
+    let mut cdf = pmf;
+    let mut k = 0;
+    while cdf < 1.0 - alpha && k < n {
+        pmf *= (n - k) as f64 / (k + 1) as f64 * p / (1.0 - p);
+        cdf += pmf;
+        k += 1;
+    }
+    k
+}
@@ -0,0 +1,122 @@
+use anyhow::{anyhow, Result};
+use sha1_smol::Sha1;
+use std::cell::Cell;
+use tracing::debug;
+use zeroize::Zeroizing;
+
+use crate::breaches::{Appearance, BreachList};
+use crate::i18n::{tr, trf};
+
+// Passwords are checked against Have I Been Pwned's Pwned Passwords with
+// k-anonymity: only the first five hexadecimal digits of a password's SHA-1
+// hash are sent, and the service returns the suffixes of every breached
+// hash with that prefix, padded with decoys so that the response size gives
+// nothing away. A local breach list can be checked instead, with nothing
+// sent at all.
+
+#[cfg(feature = "hibp")]
+const RANGE_URL: &str = "https://api.pwnedpasswords.com/range/";
+const PREFIX_LEN: usize = 5;
+// Generated passwords are almost never breached, so a run of breached
+// passwords means the password is too short to be worth checking.
+const MAX_BREACHED_IN_A_ROW: usize = 100;
+
+pub struct PwnedPasswords {
+    #[cfg(feature = "hibp")]
+    agent: ureq::Agent,
+    // A local breach list, checked instead of the online service.
+    offline: Option<BreachList>,
+    num_breached: Cell<usize>,
+    in_a_row: Cell<usize>,
+}
+
+impl PwnedPasswords {
+    pub fn online() -> Self {
+        PwnedPasswords {
+            #[cfg(feature = "hibp")]
+            agent: ureq::Agent::config_builder().build().into(),
+            offline: None,
+            num_breached: Cell::new(0),
+            in_a_row: Cell::new(0),
+        }
+    }
+
+    pub fn offline(list: BreachList) -> Self {
+        PwnedPasswords { offline: Some(list), ..PwnedPasswords::online() }
+    }
+
+    /// Returns whether the password appears in breaches.
+    pub fn lookup(&self, password: &str) -> Result<Appearance> {
+        if let Some(list) = &self.offline {
+            return list.lookup(password);
+        }
+        let mut hash =
+            Zeroizing::new(Sha1::from(password).digest().to_string());
+        hash.make_ascii_uppercase();
+        let (prefix, suffix) = hash.split_at(PREFIX_LEN);
+        let range = Zeroizing::new(self.fetch_range(prefix)?);
+        for line in range.lines() {
+            let Some((line_suffix, count)) = line.trim().split_once(':') else {
+                continue;
+            };
+            if line_suffix.eq_ignore_ascii_case(suffix) {
+                let count = count.parse().map_err(|_| {
+                    anyhow!(tr("Have I Been Pwned sent an invalid response"))
+                })?;
+                // Padding lines have a count of zero.
+                if count > 0 {
+                    return Ok(Appearance::Seen(count));
+                }
+            }
+        }
+        Ok(Appearance::Absent)
+    }
+
+    /// Returns whether a generated password appears in breaches and so
+    /// should be redrawn. Fails if too many in a row do.
+    pub fn breached(&self, password: &str) -> Result<bool> {
+        if let Appearance::Absent = self.lookup(password)? {
+            self.in_a_row.set(0);
+            return Ok(false);
+        }
+        debug!("password appears in breaches, retrying");
+        self.num_breached.set(self.num_breached.get() + 1);
+        self.in_a_row.set(self.in_a_row.get() + 1);
+        if self.in_a_row.get() == MAX_BREACHED_IN_A_ROW {
+            let msg = trf(
+                "{} passwords in a row appear in breaches; consider a longer \
+                 password",
+                &[&MAX_BREACHED_IN_A_ROW],
+            );
+            return Err(anyhow!(msg));
+        }
+        Ok(true)
+    }
+
+    /// Returns the number of generated passwords found in breaches.
+    pub fn num_breached(&self) -> usize {
+        self.num_breached.get()
+    }
+
+    #[cfg(feature = "hibp")]
+    fn fetch_range(&self, prefix: &str) -> Result<String> {
+        let user_agent = concat!("passgen/", env!("CARGO_PKG_VERSION"));
+        let url = format!("{}{}", RANGE_URL, prefix);
+        let query = || -> std::result::Result<_, ureq::Error> {
+            self.agent.get(&url)
+                .header("User-Agent", user_agent)
+                .header("Add-Padding", "true")
+                .call()?
+                .body_mut()
+                .read_to_string()
+        };
+        query().map_err(|err| {
+            anyhow!(trf("could not query Have I Been Pwned: {}", &[&err]))
+        })
+    }
+
+    #[cfg(not(feature = "hibp"))]
+    fn fetch_range(&self, _prefix: &str) -> Result<String> {
+        Err(anyhow!(tr("this build does not support --check-hibp")))
+    }
+}
@@ -0,0 +1,43 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::token::EntropyToken;
+use crate::{PassgenError, Result};
+
+/// The Linux kernel's hardware random number generator device.
+pub const HWRNG_PATH: &str = "/dev/hwrng";
+
+/// A hardware random number generator device, such as [`HWRNG_PATH`] on
+/// Linux.
+#[derive(Debug)]
+pub struct HwRng {
+    device: File,
+}
+
+impl HwRng {
+    /// Opens the device at [`HWRNG_PATH`].
+    pub fn open() -> Result<Self> {
+        HwRng::open_path(HWRNG_PATH)
+    }
+
+    /// Opens the device at the given path.
+    pub fn open_path(path: impl AsRef<Path>) -> Result<Self> {
+        let device = File::open(path).map_err(rng_failure)?;
+        Ok(HwRng { device })
+    }
+}
+
+fn rng_failure(err: io::Error) -> PassgenError {
+    PassgenError::RngFailure(rand::Error::new(err))
+}
+
+impl EntropyToken for HwRng {
+    fn name(&self) -> &str {
+        "hwrng"
+    }
+
+    fn fill(&mut self, dest: &mut [u8]) -> Result<()> {
+        self.device.read_exact(dest).map_err(rng_failure)
+    }
+}
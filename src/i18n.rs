@@ -0,0 +1,2520 @@
+use std::env;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use passgen::PassgenError;
+
+// Messages are looked up by their English text, as with gettext, so a
+// message without a translation is shown in English.
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Language {
+    English,
+    German,
+}
+
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+fn language() -> Language {
+    *LANGUAGE.get_or_init(|| {
+        let locale =
+            ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+                .filter_map(|var| env::var(var).ok())
+                .find(|value| !value.is_empty())
+                .unwrap_or_default();
+        match locale.split(['_', '.', '@']).next() {
+            Some("de") => Language::German,
+            _ => Language::English,
+        }
+    })
+}
+
+pub fn tr(msgid: &str) -> &str {
+    let catalog =
+        match language() {
+            Language::English => return msgid,
+            Language::German => GERMAN,
+        };
+    catalog.iter()
+        .find(|(english, _)| *english == msgid)
+        .map(|(_, translated)| *translated)
+        .unwrap_or(msgid)
+}
+
+pub fn trf(msgid: &str, args: &[&dyn Display]) -> String {
+    let mut pieces = tr(msgid).split("{}");
+    let mut result = pieces.next().unwrap_or("").to_string();
+    let mut args = args.iter();
+    for piece in pieces {
+        if let Some(arg) = args.next() {
+            result.push_str(&arg.to_string());
+        }
+        result.push_str(piece);
+    }
+    result
+}
+
+// Translates a message the library formatted from a msgid, which is found
+// by matching the text around its placeholders, and the arguments by what
+// the placeholders matched. Where several msgids match, the one with the
+// most text of its own is taken.
+fn tr_formatted(msg: &str) -> String {
+    if language() == Language::English {
+        return msg.to_string();
+    }
+    let best =
+        GERMAN.iter()
+            .filter_map(|(msgid, _)| Some((*msgid, match_msgid(msgid, msg)?)))
+            .max_by_key(|(msgid, args)| msgid.len() - 2 * args.len());
+    match best {
+        Some((msgid, args)) => {
+            let args =
+                args.iter().map(|arg| arg as &dyn Display).collect::<Vec<_>>();
+            trf(msgid, &args)
+        },
+        None => msg.to_string(),
+    }
+}
+
+// Returns what each placeholder of the msgid matches in the message, if
+// the rest of the msgid matches it.
+fn match_msgid<'a>(msgid: &str, msg: &'a str) -> Option<Vec<&'a str>> {
+    let mut pieces = msgid.split("{}");
+    let mut rest = msg.strip_prefix(pieces.next().unwrap_or(""))?;
+    let mut args = Vec::new();
+    let mut pieces = pieces.peekable();
+    while let Some(piece) = pieces.next() {
+        let end =
+            if pieces.peek().is_some() {
+                rest.find(piece)?
+            } else {
+                rest.strip_suffix(piece)?.len()
+            };
+        args.push(&rest[..end]);
+        rest = &rest[end + piece.len()..];
+    }
+    rest.is_empty().then_some(args)
+}
+
+pub fn passgen_error(err: &PassgenError) -> String {
+    match err {
+        PassgenError::InvalidSpec(msg) =>
+            tr_formatted(msg),
+        PassgenError::PolicyViolation(msg) =>
+            trf("policy cannot be satisfied: {}", &[&tr_formatted(msg)]),
+        PassgenError::InsufficientEntropy { bits, required } =>
+            trf(
+                "password entropy of {} bits is below the required {} bits",
+                &[&format!("{:.1}", bits), &format!("{:.1}", required)],
+            ),
+        PassgenError::RngFailure(err) => {
+            let msg = tr_formatted(&err.to_string());
+            trf("random number generator failed: {}", &[&msg])
+        },
+        PassgenError::HealthTestFailure(msg) => {
+            let msg = tr_formatted(msg);
+            trf("random number generator health test: {}", &[&msg])
+        },
+        PassgenError::SelfTestFailure(msg) =>
+            tr_formatted(msg),
+        PassgenError::AttemptsExhausted { attempts, acceptance_rate } =>
+            trf(
+                "gave up after {} attempts: acceptance rate ~{}, relax the \
+                 constraints",
+                &[attempts, &format!("{:.0e}", acceptance_rate)],
+            ),
+    }
+}
+
+const GERMAN: &[(&str, &str)] = &[
+    // Usage
+    ("Usage:", "Aufruf:"),
+    ("Generates random passwords.", "Erzeugt zufällige Passwörter."),
+    ("Options", "Optionen"),
+    ("apg-compatible options", "apg-kompatible Optionen"),
+    ("Other options", "Weitere Optionen"),
+    ("Exit status", "Rückgabewert"),
+    ("use this character set", "diesen Zeichenvorrat verwenden"),
+    (
+        "use the character set and rules of a preset: dns for valid DNS \
+         labels (lowercase letters, digits and inner hyphens, at most 63 \
+         characters) or url for URL path segments and query components \
+         that need no percent-encoding",
+        "Zeichenvorrat und Regeln einer Vorgabe verwenden: dns für gültige \
+         DNS-Labels (Kleinbuchstaben, Ziffern und innere Bindestriche, \
+         höchstens 63 Zeichen) oder url für URL-Pfadsegmente und \
+         Query-Bestandteile, die keine Prozentkodierung brauchen",
+    ),
+    (
+        "generate passwords of this length (default 24)",
+        "Passwörter dieser Länge erzeugen (Standard 24)",
+    ),
+    (
+        "generate this many passwords (default 1)",
+        "so viele Passwörter erzeugen (Standard 1)",
+    ),
+    (
+        "1 for random generation (0, pronounceable, is not supported)",
+        "1 für zufällige Erzeugung (0, aussprechbar, wird nicht \
+         unterstützt)",
+    ),
+    (
+        "generate passwords of at least this length",
+        "Passwörter mit mindestens dieser Länge erzeugen",
+    ),
+    (
+        "generate passwords of at most this length",
+        "Passwörter mit höchstens dieser Länge erzeugen",
+    ),
+    (
+        "use these character classes (S symbols, N numbers, C capitals, L \
+         lowercase); a lowercase mode letter allows the class, an uppercase \
+         letter requires it",
+        "diese Zeichenklassen verwenden (S Sonderzeichen, N Ziffern, C \
+         Großbuchstaben, L Kleinbuchstaben); ein kleiner Modusbuchstabe \
+         erlaubt die Klasse, ein großer verlangt sie",
+    ),
+    (
+        "exclude these characters from the character set",
+        "diese Zeichen aus dem Zeichenvorrat ausschließen",
+    ),
+    (
+        "print records of these fields, each from a registered generator \
+         given as @name or from password options",
+        "Datensätze aus diesen Feldern ausgeben, jedes aus einem als @name \
+         angegebenen registrierten Generator oder aus Passwortoptionen",
+    ),
+    (
+        "read the generators for --fields from this file instead of \
+         generators.json beside the profiles file",
+        "die Generatoren für --fields aus dieser Datei statt aus \
+         generators.json neben der Profildatei lesen",
+    ),
+    (
+        "exclude the characters easily confused with others in a medium: \
+         sans-serif, printed-form or handwritten",
+        "die in einem Medium leicht mit anderen verwechselten Zeichen \
+         ausschließen: sans-serif, printed-form oder handwritten",
+    ),
+    (
+        "read more confusables profiles from this file instead of \
+         confusables.json beside the profiles file",
+        "weitere Verwechslungsprofile aus dieser Datei statt aus \
+         confusables.json neben der Profildatei lesen",
+    ),
+    (
+        "draw each character from a class chosen by its share, as in \
+         letters:60%,digits:25%,symbols:15%, in place of a character set",
+        "jedes Zeichen aus einer nach ihrem Anteil gewählten Klasse ziehen, \
+         etwa letters:60%,digits:25%,symbols:15%, anstelle eines \
+         Zeichensatzes",
+    ),
+    (
+        "report the charset size, entropy, policy, random number source and \
+         timing on standard error",
+        "Größe des Zeichenvorrats, Entropie, Richtlinie, Zufallsquelle und \
+         Laufzeit auf der Standardfehlerausgabe melden",
+    ),
+    (
+        "do not show a progress bar when generating many passwords on a \
+         terminal",
+        "beim Erzeugen vieler Passwörter auf einem Terminal keinen \
+         Fortschrittsbalken anzeigen",
+    ),
+    (
+        "report the wall-clock time, bytes of entropy consumed, generation \
+         rate and peak memory on standard error after generating",
+        "nach dem Erzeugen Laufzeit, verbrauchte Entropie in Bytes, \
+         Erzeugungsrate und maximalen Speicherverbrauch auf der \
+         Standardfehlerausgabe melden",
+    ),
+    (
+        "append a line of JSON to the file each time passwords are \
+         generated, recording when, the label, count, entropy and policy, \
+         but never the passwords",
+        "bei jedem Erzeugen eine Zeile JSON an die Datei anhängen, mit \
+         Zeit, Bezeichnung, Anzahl, Entropie und Richtlinie, aber nie den \
+         Passwörtern",
+    ),
+    (
+        "the label of the entries in the audit log, by default the \
+         insert-pass entry, and with --format csv or json, of each record",
+        "die Bezeichnung der Einträge im Prüfprotokoll, standardmäßig der \
+         Eintrag von insert-pass, und mit --format csv oder json die jedes \
+         Datensatzes",
+    ),
+    (
+        "fail before generating if the job would hold more memory than \
+         this, in bytes or with a K, M, G or T suffix",
+        "vor dem Erzeugen abbrechen, wenn der Auftrag mehr Speicher als \
+         diesen belegen würde, in Bytes oder mit der Endung K, M, G oder T",
+    ),
+    (
+        "print the effective settings and entropy without generating any \
+         passwords",
+        "die wirksamen Einstellungen und die Entropie ausgeben, ohne \
+         Passwörter zu erzeugen",
+    ),
+    (
+        "fail unless each password has at least this much entropy",
+        "abbrechen, wenn ein Passwort weniger Entropie als diese hätte",
+    ),
+    (
+        "give up on a password after drawing this many candidates that fail \
+         the policy",
+        "ein Passwort aufgeben, nachdem so viele Kandidaten gezogen wurden, \
+         die die Richtlinie nicht erfüllen",
+    ),
+    (
+        "give up on a password after drawing candidates that fail the \
+         policy for this many seconds",
+        "ein Passwort aufgeben, nachdem so viele Sekunden lang Kandidaten \
+         gezogen wurden, die die Richtlinie nicht erfüllen",
+    ),
+    (
+        "pass each password through this program, which is given it on \
+         standard input and prints the password to use or exits with 1 to \
+         reject it",
+        "jedes Passwort durch dieses Programm leiten, das es auf der \
+         Standardeingabe erhält und das zu verwendende Passwort ausgibt oder \
+         mit 1 endet, um es zu verwerfen",
+    ),
+    (
+        "apply these comma-separated transforms in order to each password: \
+         leet replaces each of the letters a, e, i, o, s and t with its \
+         look-alike digit with probability one half, adding no guaranteed \
+         entropy, and insert-symbols inserts a random symbol at a random \
+         position, adding 5 bits",
+        "diese durch Kommas getrennten Umformungen der Reihe nach auf jedes \
+         Passwort anwenden: leet ersetzt jeden der Buchstaben a, e, i, o, s \
+         und t mit Wahrscheinlichkeit einhalb durch die ähnlich aussehende \
+         Ziffer, ohne garantierten Entropiegewinn, und insert-symbols fügt \
+         an einer zufälligen Stelle ein zufälliges Symbol ein, was 5 Bit \
+         hinzufügt",
+    ),
+    (
+        "do not ask for confirmation on a terminal before generating \
+         passwords with less than 64 bits of entropy",
+        "auf einem Terminal nicht nachfragen, bevor Passwörter mit weniger \
+         als 64 Bit Entropie erzeugt werden",
+    ),
+    (
+        "leave core dumps and debugger attachment enabled, for debugging",
+        "Speicherabbilder und das Anhängen von Debuggern zur Fehlersuche \
+         zulassen",
+    ),
+    (
+        "on Linux, restrict the process to the system calls needed to \
+         generate and print passwords; the default except with --batch, \
+         --check-hibp, --store or insert-pass",
+        "unter Linux den Prozess auf die Systemaufrufe beschränken, die zum \
+         Erzeugen und Ausgeben von Passwörtern nötig sind; Standard, außer \
+         bei --batch, --check-hibp, --store oder insert-pass",
+    ),
+    ("do not restrict system calls", "Systemaufrufe nicht beschränken"),
+    (
+        "redraw any password that appears in breaches known to Have I Been \
+         Pwned, sending only the first five hexadecimal digits of its SHA-1 \
+         hash; needs network access and a build with the hibp feature",
+        "jedes Passwort neu ziehen, das in Have I Been Pwned bekannten \
+         Datenlecks vorkommt, wobei nur die ersten fünf Hexadezimalziffern \
+         seines SHA-1-Hashes gesendet werden; benötigt Netzwerkzugriff und \
+         einen Build mit dem Feature hibp",
+    ),
+    (
+        "redraw any password that appears in a local breach list: a Pwned \
+         Passwords SHA-1 dump ordered by hash, or a Bloom filter built from \
+         one by hibp build",
+        "jedes Passwort neu ziehen, das in einer lokalen Liste von \
+         Datenlecks vorkommt: einem nach Hash sortierten SHA-1-Abzug von \
+         Pwned Passwords oder einem daraus mit hibp build erstellten \
+         Bloom-Filter",
+    ),
+    (
+        "encrypt the output to this recipient, with age if it is an age \
+         public key and with gpg otherwise, writing the armored ciphertext to \
+         standard output",
+        "die Ausgabe für diesen Empfänger verschlüsseln, mit age, wenn er \
+         ein öffentlicher age-Schlüssel ist, sonst mit gpg, und den \
+         Geheimtext mit ASCII-Armor auf die Standardausgabe schreiben",
+    ),
+    (
+        "draw from the operating system's random number generator (the \
+         default) or from a ChaCha20 generator reseeded from it after every \
+         mebibyte, which is faster for bulk jobs",
+        "aus dem Zufallszahlengenerator des Betriebssystems ziehen (Standard) \
+         oder aus einem ChaCha20-Generator, der nach jedem Mebibyte daraus \
+         neu initialisiert wird, was bei großen Mengen schneller ist",
+    ),
+    (
+        "when the operating system's random number generator fails, either \
+         fail (the default) or continue with a ChaCha20 generator seeded from \
+         it beforehand",
+        "bei einem Ausfall des Zufallszahlengenerators des Betriebssystems \
+         entweder abbrechen (Standard) oder mit einem zuvor daraus \
+         initialisierten ChaCha20-Generator fortfahren",
+    ),
+    (
+        "run the continuous health tests assuming this much min-entropy per \
+         output byte, 8 by default",
+        "die fortlaufenden Gesundheitstests mit dieser Min-Entropie je \
+         ausgegebenem Byte durchführen, standardmäßig 8",
+    ),
+    (
+        "set the health tests' false positive probability to 2 to the power \
+         of this, -30 by default",
+        "die Fehlalarmwahrscheinlichkeit der Gesundheitstests auf 2 hoch \
+         diesen Wert setzen, standardmäßig -30",
+    ),
+    (
+        "set the adaptive proportion test's window, 512 bytes by default",
+        "das Fenster des Adaptive-Proportion-Tests setzen, standardmäßig \
+         512 Byte",
+    ),
+    (
+        "mix output from this device into the random number generator's \
+         output, never replacing it; yubikey sends random challenges to slot \
+         2 with ykchalresp and needs a build with the yubikey feature, and \
+         hwrng reads the Linux hardware random number generator /dev/hwrng",
+        "Ausgabe dieses Geräts in die Ausgabe des Zufallszahlengenerators \
+         einmischen, ohne sie zu ersetzen; yubikey sendet mit ykchalresp \
+         zufällige Challenges an Slot 2 und erfordert einen Build mit dem \
+         Feature yubikey, hwrng liest den Hardware-Zufallszahlengenerator \
+         /dev/hwrng von Linux",
+    ),
+    (
+        "mix output from the x86 RDSEED instruction into the random number \
+         generator's output, never replacing it",
+        "Ausgabe des x86-Befehls RDSEED in die Ausgabe des \
+         Zufallszahlengenerators einmischen, ohne sie zu ersetzen",
+    ),
+    (
+        "ask for keyboard mashing, dice rolls or a file and mix a hash of it \
+         into the random number generator's output",
+        "nach zufälligen Tastenanschlägen, Würfelwürfen oder einer Datei \
+         fragen und einen Hash davon in die Ausgabe des \
+         Zufallszahlengenerators einmischen",
+    ),
+    (
+        "print each password as N shares, one per line, any K of which \
+         recover it with the recover command",
+        "jedes Passwort als N Anteile ausgeben, einen pro Zeile, von denen \
+         beliebige K es mit dem Befehl recover wiederherstellen",
+    ),
+    ("print passwords in columns", "Passwörter in Spalten ausgeben"),
+    ("print one password per line", "ein Passwort pro Zeile ausgeben"),
+    (
+        "print passwords as text, as csv with a header line, as json with \
+         one object per line or as lines for chpasswd, in a field named \
+         password, or after the command for tokens and codes, with code for \
+         otp; ansible-vault prints one password encrypted as a !vault \
+         string, and spelling each character with its spelling word and \
+         Morse code",
+        "Passwörter als Text, als CSV mit Kopfzeile, als JSON mit einem \
+         Objekt pro Zeile oder als Zeilen für chpasswd ausgeben, in einem \
+         Feld namens password, bei Tokens und Codes nach dem Befehl \
+         benannt, bei otp code; ansible-vault gibt ein Passwort als \
+         verschlüsselten !vault-String aus, spelling jedes Zeichen mit \
+         seinem Buchstabierwort und Morsecode",
+    ),
+    (
+        "encrypt with the vault password in this file, or printed by it if \
+         it is executable, for --format ansible-vault",
+        "für --format ansible-vault mit dem Vault-Passwort in dieser Datei \
+         verschlüsseln, oder mit dem von ihr ausgegebenen, wenn sie \
+         ausführbar ist",
+    ),
+    (
+        "generate a password for each username in the file, one per line, \
+         and print it after the username",
+        "für jeden Benutzernamen in der Datei, einer pro Zeile, ein Passwort \
+         erzeugen und nach dem Benutzernamen ausgeben",
+    ),
+    (
+        "with --users, add a SHA-512 crypt hash of each password, which \
+         --format chpasswd prints instead of the password",
+        "mit --users zu jedem Passwort einen SHA-512-crypt-Hash hinzufügen, \
+         den --format chpasswd anstelle des Passworts ausgibt",
+    ),
+    (
+        "print each password again beside it, split into pieces to read \
+         aloud",
+        "jedes Passwort daneben erneut ausgeben, zum Vorlesen in Stücke \
+         geteilt",
+    ),
+    (
+        "print each password spaced out, then one character a line with its \
+         position and class",
+        "jedes Passwort gesperrt ausgeben, dann ein Zeichen pro Zeile mit \
+         seiner Position und Klasse",
+    ),
+    (
+        "print passwords in short groups for a Braille display",
+        "Passwörter in kurzen Gruppen für eine Braillezeile ausgeben",
+    ),
+    (
+        "interactively ask for the password length, character classes and \
+         number of passwords when they are not given as options",
+        "Passwortlänge, Zeichenklassen und Anzahl der Passwörter interaktiv \
+         abfragen, wenn sie nicht als Optionen angegeben sind",
+    ),
+    (
+        "read one specification per line from standard input and generate \
+         passwords for each in order; a line holds either options in the \
+         syntax above or a JSON object with the fields charset, mode, \
+         exclude, length, min_length, max_length, count and min_entropy, and \
+         other options given on the command line apply to every line",
+        "eine Spezifikation pro Zeile von der Standardeingabe lesen und für \
+         jede der Reihe nach Passwörter erzeugen; eine Zeile enthält \
+         entweder Optionen in obiger Syntax oder ein JSON-Objekt mit den \
+         Feldern charset, mode, exclude, length, min_length, max_length, \
+         count und min_entropy, und auf der Kommandozeile angegebene \
+         Optionen gelten für jede Zeile",
+    ),
+    (
+        "When several passwords are printed to a terminal, they are laid out \
+         in columns fitting the terminal width unless the character set \
+         contains a space.",
+        "Werden mehrere Passwörter auf einem Terminal ausgegeben, werden sie \
+         passend zur Terminalbreite in Spalten angeordnet, sofern der \
+         Zeichenvorrat kein Leerzeichen enthält.",
+    ),
+    (
+        "The password length and number of passwords may also be given as \
+         positional arguments, so \"passgen 32 5\" is equivalent to \
+         \"passgen -l 32 -n 5\".",
+        "Passwortlänge und Anzahl der Passwörter können auch als \
+         Positionsargumente angegeben werden, \"passgen 32 5\" entspricht \
+         also \"passgen -l 32 -n 5\".",
+    ),
+    (
+        "The charset specification language is a subset of the character \
+         set language for regular expressions. Only characters and ranges \
+         are allowed. Literal hyphens and backslashes must be escaped. Other \
+         characters must not be escaped. An initial caret may be used to \
+         invert the character set with respect to typeable ASCII \
+         characters.",
+        "Die Sprache für Zeichenvorräte ist eine Teilmenge der Sprache für \
+         Zeichenklassen in regulären Ausdrücken. Nur Zeichen und Bereiche \
+         sind erlaubt. Bindestriche und Backslashes müssen maskiert werden, \
+         andere Zeichen dürfen nicht maskiert werden. Ein führendes \
+         Zirkumflex kehrt den Zeichenvorrat bezüglich der tippbaren \
+         ASCII-Zeichen um.",
+    ),
+    (
+        "The entropy reported with -v and --dry-run, required by \
+         --min-entropy and given in JSON responses counts only the \
+         passwords that satisfy the policy, so required classes and \
+         characters forbidden at the ends lower it.",
+        "Die mit -v und --dry-run gemeldete, von --min-entropy verlangte \
+         und in JSON-Antworten angegebene Entropie zählt nur die \
+         Passwörter, die die Richtlinie erfüllen, sodass verlangte Klassen \
+         und an den Enden verbotene Zeichen sie verringern.",
+    ),
+    (
+        "The confusables profiles exclude all the characters often misread \
+         for one another: sans-serif excludes Il1|O0, printed-form \
+         0OQD1Il|2Z5S8B6GUV`',.;: and handwritten those and more, including \
+         the letters written alike in both cases. A confusables file holds a \
+         JSON object from each profile's name to its characters, as in \
+         {\"terminal\": \"0O1l\"}, and may replace the built-in profiles.",
+        "Die Verwechslungsprofile schließen alle Zeichen aus, die oft \
+         füreinander gehalten werden: sans-serif schließt Il1|O0 aus, \
+         printed-form 0OQD1Il|2Z5S8B6GUV`',.;: und handwritten diese und \
+         weitere, darunter die Buchstaben, die in beiden Schreibungen gleich \
+         geschrieben werden. Eine Verwechslungsdatei enthält ein JSON-Objekt \
+         vom Namen jedes Profils auf seine Zeichen, etwa \
+         {\"terminal\": \"0O1l\"}, und kann die eingebauten Profile \
+         ersetzen.",
+    ),
+    (
+        "With --compose, the classes are lowercase, uppercase, letters, \
+         digits and symbols, with whole percentages adding up to 100. \
+         Passwords have the classes in those shares on average, in no set \
+         order, and any password may stray from them. Since the characters \
+         are then not equally likely, the entropy is that of the likeliest \
+         password, with the average given beside it by -v and --dry-run.",
+        "Bei --compose sind die Klassen lowercase, uppercase, letters, \
+         digits und symbols, mit ganzen Prozentzahlen, die zusammen 100 \
+         ergeben. Passwörter enthalten die Klassen im Mittel in diesen \
+         Anteilen, in keiner festen Reihenfolge, und jedes einzelne kann \
+         davon abweichen. Da die Zeichen dann nicht gleich wahrscheinlich \
+         sind, ist die Entropie die des wahrscheinlichsten Passworts; -v und \
+         --dry-run geben den Mittelwert daneben an.",
+    ),
+    (
+        "Passwords are written out as they are generated, so the memory a \
+         job holds grows with its count only for the usernames of --users, \
+         the output collected for insert-pass, --store or \
+         --vault-password-file and the codes the otp command keeps to make \
+         them distinct. With --max-memory, those are estimated before \
+         anything is generated, and the file of usernames must be no larger \
+         than the limit before it is read.",
+        "Passwörter werden ausgegeben, sobald sie erzeugt sind, sodass der \
+         Speicher eines Auftrags nur mit den Benutzernamen von --users, der \
+         für insert-pass, --store oder --vault-password-file gesammelten \
+         Ausgabe und den Codes, die der Befehl otp behält, damit sie \
+         verschieden sind, mit der Anzahl wächst. Mit --max-memory werden \
+         diese vor dem Erzeugen geschätzt, und die Datei der Benutzernamen \
+         darf vor dem Lesen nicht größer als die Grenze sein.",
+    ),
+    (
+        "Jobs of 100000 or more passwords printed one to a line are generated \
+         on as many threads as there are processors, each drawing from a \
+         generator of its own, in batches of lines written out at once. \
+         Threads wait while a few batches each are waiting to be written, so a \
+         job is generated no faster than its output is read, and holds the \
+         same memory however large it is.",
+        "Aufträge von 100000 oder mehr Passwörtern, eines pro Zeile \
+         ausgegeben, werden auf so vielen Threads erzeugt, wie es Prozessoren \
+         gibt, jeder mit einem eigenen Generator, in Stapeln von Zeilen, die \
+         auf einmal ausgegeben werden. Die Threads warten, solange je einige \
+         Stapel auf die Ausgabe warten, sodass ein Auftrag nicht schneller \
+         erzeugt wird, als seine Ausgabe gelesen wird, und gleich viel \
+         Speicher belegt, wie groß er auch ist.",
+    ),
+    ("could not start a thread", "ein Thread konnte nicht gestartet werden"),
+    (
+        "The audit log is opened, and created readable only by its owner if \
+         missing, before anything is generated. Each line is a JSON object \
+         with the time in UTC, the label, the preset, the kind and count of \
+         secrets, the entropy of the weakest possible, any --min-entropy \
+         required, the random number source and the description -v prints, \
+         policy included. Passwords, hashes and anything else worked out \
+         from them are never logged. Dry runs are not logged, and in batch \
+         mode every line is logged to the file given on the command line.",
+        "Das Prüfprotokoll wird vor dem Erzeugen geöffnet und, falls es \
+         fehlt, nur für seinen Eigentümer lesbar angelegt. Jede Zeile ist \
+         ein JSON-Objekt mit der Zeit in UTC, der Bezeichnung, der Vorgabe, \
+         Art und Anzahl der Geheimnisse, der Entropie des schwächsten \
+         möglichen, einer mit --min-entropy verlangten Entropie, der \
+         Zufallsquelle und der Beschreibung, die -v ausgibt, samt \
+         Richtlinie. Passwörter, Hashes und alles andere daraus Abgeleitete \
+         werden nie protokolliert. Probeläufe werden nicht protokolliert, \
+         und im Stapelmodus wird jede Zeile in die auf der Befehlszeile \
+         angegebene Datei protokolliert.",
+    ),
+    (
+        "A label may have placeholders, expanded for each record with --format \
+         csv or json: {seq} for its number, counted from 1 and padded with \
+         zeros to any width given, as in svc-{seq:04}; {date} for the date in \
+         UTC; and {random} for 4 random characters of Crockford's Base32 in \
+         lowercase, or as many as given, as in {random:6}. Braces themselves \
+         are written {{ and }}. The audit log records the label as given.",
+        "Eine Bezeichnung kann Platzhalter enthalten, die mit --format csv \
+         oder json für jeden Datensatz ersetzt werden: {seq} durch seine \
+         Nummer, ab 1 gezählt und mit Nullen auf eine angegebene Breite \
+         aufgefüllt, wie in svc-{seq:04}; {date} durch das Datum in UTC; und \
+         {random} durch 4 zufällige Zeichen aus Crockfords Base32 in \
+         Kleinbuchstaben, oder so viele wie angegeben, wie in {random:6}. \
+         Klammern selbst werden als {{ und }} geschrieben. Das Prüfprotokoll \
+         verzeichnet die Bezeichnung wie angegeben.",
+    ),
+    (
+        "With --extra-entropy, --entropy-source or --mix-rdseed, the extra \
+         input is hashed with SHAKE256 and the resulting stream is XORed into \
+         the random number generator's output. The stream depends only on \
+         the input, which is fixed before the generator is read, so mixing \
+         never reduces the entropy of the output, and it stays unpredictable \
+         to an attacker who can predict the generator but does not know the \
+         input.",
+        "Mit --extra-entropy, --entropy-source oder --mix-rdseed wird die \
+         zusätzliche Eingabe mit SHAKE256 gehasht und der entstehende \
+         Datenstrom per XOR mit der Ausgabe des Zufallszahlengenerators \
+         verknüpft. Der Datenstrom hängt nur von der Eingabe ab, die \
+         feststeht, bevor der Generator gelesen wird, daher verringert das \
+         Einmischen die Entropie der Ausgabe nie, und sie bleibt für einen \
+         Angreifer unvorhersagbar, der den Generator vorhersagen kann, die \
+         Eingabe aber nicht kennt.",
+    ),
+    (
+        "Before any password is generated, 1024 bytes from the operating \
+         system's random number generator are run through the repetition count \
+         and adaptive proportion tests of NIST SP 800-90B. The tests then keep \
+         checking the entropy input: with --rng chacha, each seed drawn from \
+         the operating system, and the bytes read from any --entropy-source \
+         device. The passwords themselves are not tested byte by byte, since \
+         over a bulk job even a tiny false alarm rate adds up. If a test \
+         fails, passgen stops without printing the password being generated.",
+        "Vor dem ersten Passwort werden 1024 Byte des Zufallszahlengenerators \
+         des Betriebssystems dem Repetition-Count- und dem \
+         Adaptive-Proportion-Test aus NIST SP 800-90B unterzogen. Danach \
+         prüfen die Tests weiter die Entropieeingabe: mit --rng chacha jeden \
+         vom Betriebssystem gezogenen Startwert und die von einem \
+         --entropy-source-Gerät gelesenen Bytes. Die Passwörter selbst \
+         werden nicht Byte für Byte geprüft, da sich bei einem Massenauftrag \
+         selbst eine winzige Fehlalarmrate summiert. Schlägt ein Test fehl, \
+         bricht passgen ab, ohne das gerade erzeugte Passwort auszugeben.",
+    ),
+    (
+        "A --post-script file is a Rhai script, run for each candidate \
+         password with the candidate as the constant password. Ending with a \
+         string accepts that string as the password, changed or not, true \
+         accepts the candidate, and false rejects it and draws another; \
+         anything else, including an error the script throws or 1000 \
+         rejections in a row, fails the job. Scripts cannot load modules or \
+         reach files, the network or other programs, what they print is \
+         discarded, and they are stopped after a million operations. Since a \
+         script may reject most candidates or return the same password for \
+         all of them, the entropy of its passwords is unknown and reported as \
+         none, and a warning is given if it changes any.",
+        "Eine Datei für --post-script ist ein Rhai-Skript, das für jeden \
+         Kandidaten ausgeführt wird und ihn als Konstante password erhält. \
+         Endet es mit einer Zeichenkette, wird diese als Passwort \
+         angenommen, ob geändert oder nicht, mit true wird der Kandidat \
+         angenommen, und mit false wird er verworfen und ein neuer gezogen; \
+         alles andere, auch ein vom Skript ausgelöster Fehler oder 1000 \
+         Verwerfungen in Folge, lässt den Auftrag scheitern. Skripte können \
+         keine Module laden und weder Dateien noch das Netzwerk noch andere \
+         Programme erreichen, ihre Ausgaben werden verworfen, und sie werden \
+         nach einer Million Operationen angehalten. Da ein Skript die meisten \
+         Kandidaten verwerfen oder für alle dasselbe Passwort zurückgeben \
+         kann, ist die Entropie seiner Passwörter unbekannt und wird als \
+         keine gemeldet, und es wird gewarnt, wenn es welche ändert.",
+    ),
+    (
+        "The selftest command checks the health of the operating system's \
+         random number generator, that charset specifications survive \
+         formatting and parsing, and that generation reproduces known \
+         answers from a fixed seed.",
+        "Der Befehl selftest prüft den Zustand des Zufallszahlengenerators \
+         des Betriebssystems, dass Zeichenvorräte Formatieren und Einlesen \
+         unverändert überstehen und dass die Erzeugung aus einem festen \
+         Startwert bekannte Ergebnisse liefert.",
+    ),
+    (
+        "With --distribution, the selftest command generates 1000000 \
+         passwords, or the number given with -n, as the options for generating \
+         passwords describe, and prints how often each character was drawn and \
+         chi-squared tests of whether characters are drawn uniformly overall \
+         and at each position. It fails if any test gives a p-value below \
+         0.001 divided among the tests. Policies that require classes or \
+         forbid characters at the ends skew the distribution and cannot be \
+         tested.",
+        "Mit --distribution erzeugt der Befehl selftest 1000000 Passwörter \
+         oder die mit -n angegebene Anzahl, wie die Optionen zum Erzeugen \
+         von Passwörtern sie beschreiben, und gibt aus, wie oft jedes \
+         Zeichen gezogen wurde, sowie Chi-Quadrat-Tests, ob die Zeichen \
+         insgesamt und an jeder Position gleichverteilt gezogen werden. Er \
+         schlägt fehl, wenn ein Test einen p-Wert unter 0.001, aufgeteilt \
+         auf die Tests, ergibt. Richtlinien, die Klassen verlangen oder \
+         Zeichen an den Enden verbieten, verzerren die Verteilung und können \
+         nicht getestet werden.",
+    ),
+    (
+        "The derive command asks twice for a master password, so that a \
+         typing mistake is caught, and derives the password for a site and \
+         login from it with Argon2id, so the same inputs always give the \
+         same password and nothing needs to be stored. The counter, 1 by \
+         default, is incremented to rotate the password. The character set, \
+         length and mode options apply as when generating.",
+        "Der Befehl derive fragt zweimal nach einem Master-Passwort, damit \
+         Tippfehler auffallen, und leitet daraus mit Argon2id das Passwort \
+         für eine Website und ein Login ab, sodass dieselben Eingaben stets \
+         dasselbe Passwort ergeben und nichts gespeichert werden muss. \
+         Der Zähler, standardmäßig 1, wird erhöht, um das Passwort zu \
+         wechseln. Die Optionen für Zeichenvorrat, Länge und Modus gelten \
+         wie beim Erzeugen.",
+    ),
+    (
+        "With --save, the derive command stores the site's settings, counter \
+         and derivation version as a profile, which later derivations for the \
+         same site and login use unless options override it, so the password \
+         stays reproducible when the defaults change. Profiles are kept in \
+         passgen/profiles.json in the user's configuration directory, or in \
+         the file given with --profiles. The profiles command exports them as \
+         JSON or imports them from a file or standard input.",
+        "Mit --save speichert der Befehl derive die Einstellungen, den Zähler \
+         und die Ableitungsversion der Website als Profil, das spätere \
+         Ableitungen für dieselbe Website und dasselbe Login verwenden, \
+         sofern Optionen es nicht überschreiben, sodass das Passwort auch \
+         nach geänderten Standardwerten reproduzierbar bleibt. Profile liegen \
+         in passgen/profiles.json im Konfigurationsverzeichnis des Benutzers \
+         oder in der mit --profiles angegebenen Datei. Der Befehl profiles \
+         exportiert sie als JSON oder importiert sie aus einer Datei oder von \
+         der Standardeingabe.",
+    ),
+    (
+        "The config validate command checks the profiles, generators, \
+         confusables and pwquality.conf files, as found by default or given \
+         with --profiles, --generators, --confusables-file and --config, \
+         parsing each as the commands reading it do. Files not given that do \
+         not exist are skipped. It prints each file's path followed by ok, or \
+         by the line and column of the first problem in it and what the \
+         problem is, and fails if any file is invalid.",
+        "Der Befehl config validate prüft die Dateien der Profile, \
+         Generatoren, Verwechslungsprofile und pwquality.conf, wie sie \
+         standardmäßig gefunden oder mit --profiles, --generators, \
+         --confusables-file und --config angegeben werden, indem er jede so \
+         einliest wie die Befehle, die sie lesen. Nicht angegebene Dateien, \
+         die nicht existieren, werden übersprungen. Er gibt den Pfad jeder \
+         Datei aus, gefolgt von ok oder von Zeile und Spalte des ersten \
+         Problems darin und dessen Beschreibung, und schlägt fehl, wenn eine \
+         Datei ungültig ist.",
+    ),
+    (
+        "The token command prints random bytes encoded as lowercase \
+         hexadecimal, unpadded URL-safe Base64 or unpadded Base32, the \
+         encoding of TOTP secrets, after the prefix if one is given. The \
+         count, layout, random number generator and output options apply as \
+         when generating passwords.",
+        "Der Befehl token gibt Zufallsbytes als hexadezimale \
+         Kleinbuchstaben, als URL-sicheres Base64 ohne Auffüllung oder als \
+         Base32 ohne Auffüllung, die Kodierung von TOTP-Geheimnissen, aus, \
+         gegebenenfalls hinter dem Präfix. Die Optionen für Anzahl, \
+         Anordnung, Zufallszahlengenerator und Ausgabe gelten wie beim \
+         Erzeugen von Passwörtern.",
+    ),
+    (
+        "With --base58 or --crockford, the token command instead prints \
+         identifiers of id_len characters drawn uniformly from the Bitcoin \
+         Base58 alphabet or from Crockford's Base32 alphabet, neither of \
+         which has easily confused characters. --check appends Crockford's \
+         check symbol, the identifier's value modulo 37.",
+        "Mit --base58 oder --crockford gibt der Befehl token stattdessen \
+         Kennungen aus id_len Zeichen aus, die gleichverteilt aus dem \
+         Base58-Alphabet von Bitcoin oder aus Crockfords Base32-Alphabet \
+         gezogen werden, die beide keine leicht verwechselbaren Zeichen \
+         enthalten. --check hängt Crockfords Prüfzeichen an, den Wert der \
+         Kennung modulo 37.",
+    ),
+    (
+        "The uuid command prints version 4 UUIDs, or with --v7 version 7 \
+         UUIDs, which begin with the time in milliseconds so that they sort \
+         in order of creation. The options shared with the token command \
+         apply.",
+        "Der Befehl uuid gibt UUIDs der Version 4 aus, mit --v7 solche der \
+         Version 7, die mit der Zeit in Millisekunden beginnen und sich daher \
+         nach ihrer Erzeugung sortieren lassen. Es gelten dieselben Optionen \
+         wie beim Befehl token.",
+    ),
+    (
+        "The apikey command prints API keys made of the prefix, a random \
+         body of body_len base62 characters, 30 by default, and a CRC-32 of \
+         the body in 6 base62 characters, so that services can reject \
+         mistyped keys without a lookup. The options shared with the token \
+         command apply. The apikey verify command reads keys from standard \
+         input, one per line, and checks their prefix, length and checksum.",
+        "Der Befehl apikey gibt API-Schlüssel aus, bestehend aus dem Präfix, \
+         einem zufälligen Rumpf aus body_len Base62-Zeichen, standardmäßig \
+         30, und einer CRC-32 des Rumpfs in 6 Base62-Zeichen, sodass Dienste \
+         vertippte Schlüssel ohne Nachschlagen zurückweisen können. Es \
+         gelten dieselben Optionen wie beim Befehl token. Der Befehl apikey \
+         verify liest Schlüssel zeilenweise von der Standardeingabe und \
+         prüft Präfix, Länge und Prüfsumme.",
+    ),
+    (
+        "The license command prints license keys of 4 groups of 5 characters \
+         by default, separated by hyphens, from Crockford's Base32 alphabet. \
+         The key ends with a checksum of the rest: one Luhn mod 32 character \
+         by default, or with --checksum crc four characters holding 20 bits \
+         of the CRC-32. The options shared with the token command apply. The \
+         license verify command reads keys of the same shape from standard \
+         input, one per line, ignoring case and reading O as 0 and I and L \
+         as 1, and checks their checksums.",
+        "Der Befehl license gibt Lizenzschlüssel aus, standardmäßig 4 durch \
+         Bindestriche getrennte Gruppen aus je 5 Zeichen aus Crockfords \
+         Base32-Alphabet. Der Schlüssel endet mit einer Prüfsumme über den \
+         Rest: standardmäßig ein Luhn-mod-32-Zeichen, mit --checksum crc \
+         vier Zeichen mit 20 Bit der CRC-32. Es gelten dieselben Optionen \
+         wie beim Befehl token. Der Befehl license verify liest Schlüssel \
+         derselben Form zeilenweise von der Standardeingabe, ignoriert dabei \
+         Groß- und Kleinschreibung, liest O als 0 sowie I und L als 1 und \
+         prüft ihre Prüfsummen.",
+    ),
+    (
+        "The wifi command prints a WPA2 or WPA3 passphrase of 24 letters and \
+         digits by default, or with --psk the 64 hexadecimal digit \
+         pre-shared key derived from it and the SSID with PBKDF2. --qr adds \
+         the payload of the QR code phones scan to join the network, and \
+         --hidden marks the network as hidden in it.",
+        "Der Befehl wifi gibt eine WPA2- oder WPA3-Passphrase aus, \
+         standardmäßig 24 Buchstaben und Ziffern, mit --psk stattdessen den \
+         daraus und aus der SSID mit PBKDF2 abgeleiteten Pre-Shared Key aus \
+         64 Hexadezimalziffern. --qr fügt den Inhalt des QR-Codes hinzu, \
+         den Telefone zum Verbinden scannen, und --hidden kennzeichnet das \
+         Netz darin als verborgen.",
+    ),
+    (
+        "The mac command prints random unicast MAC addresses, marked as \
+         locally administered unless --universally-administered is given, \
+         or starting with a vendor prefix given with --vendor-prefix, which \
+         --locally-administered requires to be locally administered. The \
+         options shared with the token command apply, but low entropy is \
+         not warned about, since MAC addresses are not secret.",
+        "Der Befehl mac gibt zufällige Unicast-MAC-Adressen aus, als lokal \
+         verwaltet gekennzeichnet, sofern nicht --universally-administered \
+         angegeben ist, oder mit einem per --vendor-prefix angegebenen \
+         Herstellerpräfix beginnend, das mit --locally-administered lokal \
+         verwaltet sein muss. Es gelten dieselben Optionen wie beim Befehl \
+         token, vor geringer Entropie wird jedoch nicht gewarnt, da \
+         MAC-Adressen nicht geheim sind.",
+    ),
+    (
+        "The username command prints usernames for throwaway or service \
+         accounts: by default an adjective, a noun and a two-digit number \
+         from built-in lists joined by hyphens and no longer than 20 \
+         characters, or with --style pronounceable alternating consonants \
+         and vowels, 8 letters long by default. --max-len caps the length. \
+         The options shared with the token command apply, but low entropy is \
+         not warned about, since usernames are not secret.",
+        "Der Befehl username gibt Benutzernamen für Wegwerf- oder \
+         Dienstkonten aus: standardmäßig ein Adjektiv, ein Substantiv und \
+         eine zweistellige Zahl aus eingebauten englischen Listen, durch \
+         Bindestriche verbunden und höchstens 20 Zeichen lang, mit --style \
+         pronounceable abwechselnd Konsonanten und Vokale, standardmäßig 8 \
+         Buchstaben lang. --max-len begrenzt die Länge. Es gelten dieselben \
+         Optionen wie beim Befehl token, vor geringer Entropie wird jedoch \
+         nicht gewarnt, da Benutzernamen nicht geheim sind.",
+    ),
+    (
+        "The otp command prints a batch of 10 distinct numeric one-time \
+         codes of 8 digits by default, such as the backup codes on a scratch \
+         card. -l sets the number of digits, --luhn makes the last of them a \
+         Luhn check digit and --group-len splits each code into groups of \
+         this many digits separated by hyphens. The options shared with the \
+         token command apply, but low entropy is not warned about, since \
+         guesses at one-time codes are limited.",
+        "Der Befehl otp gibt einen Satz von standardmäßig 10 verschiedenen \
+         numerischen Einmalcodes mit 8 Ziffern aus, etwa die Ersatzcodes \
+         einer Rubbelkarte. -l legt die Anzahl der Ziffern fest, --luhn \
+         macht die letzte davon zu einer Luhn-Prüfziffer und --group-len \
+         teilt jeden Code in durch Bindestriche getrennte Gruppen dieser \
+         Größe. Es gelten dieselben Optionen wie beim Befehl token, aber vor \
+         geringer Entropie wird nicht gewarnt, da Rateversuche bei \
+         Einmalcodes begrenzt sind.",
+    ),
+    (
+        "The generator command prints passwords from a generator registered \
+         under the name, and the generators command lists the names. Crates \
+         using the library register their own; passgen registers those \
+         defined in generators.json beside the profiles file, or in the file \
+         given with --generators: a JSON object from each name to an object \
+         with one field, charset, pattern, passphrase or pin, holding the \
+         generator's settings, as in {\"mainframe\": {\"pattern\": \
+         \"uddddddd\"}}. The options shared with the token command apply.",
+        "Der Befehl generator gibt Passwörter eines unter dem Namen \
+         registrierten Generators aus, und der Befehl generators listet die \
+         Namen auf. Crates, die die Bibliothek verwenden, registrieren ihre \
+         eigenen; passgen registriert die in generators.json neben der \
+         Profildatei oder in der mit --generators angegebenen Datei \
+         definierten: ein JSON-Objekt von jedem Namen auf ein Objekt mit \
+         einem Feld, charset, pattern, passphrase oder pin, das die \
+         Einstellungen des Generators enthält, etwa {\"mainframe\": \
+         {\"pattern\": \"uddddddd\"}}. Es gelten dieselben Optionen wie beim \
+         Befehl token.",
+    ),
+    (
+        "The import command converts another tool's recipe into a generator \
+         defined under the name in the generators file: a KeePass password \
+         generator pattern, pwgen's arguments given as one argument, or a \
+         1Password recipe, either as the CLI takes it, as in \
+         letters,digits,32, or as the JSON Connect takes. A KeePass pattern \
+         drawing every character from the same set becomes a charset \
+         generator; otherwise each of its placeholders must be d, l, u or s, \
+         and it becomes a pattern. pwgen's pronounceable passwords have no \
+         equivalent and are imported as with -s.",
+        "Der Befehl import wandelt das Rezept eines anderen Werkzeugs in einen \
+         unter dem Namen in der Generatordatei definierten Generator um: ein \
+         Muster des KeePass-Passwortgenerators, die als ein Argument \
+         angegebenen Argumente von pwgen oder ein 1Password-Rezept, entweder \
+         wie die CLI es annimmt, etwa letters,digits,32, oder als JSON wie \
+         für Connect. Ein KeePass-Muster, das jedes Zeichen aus derselben \
+         Menge zieht, wird zu einem charset-Generator; andernfalls muss jeder \
+         seiner Platzhalter d, l, u oder s sein, und es wird zu einem pattern. \
+         Für die aussprechbaren Passwörter von pwgen gibt es keine \
+         Entsprechung; sie werden wie mit -s importiert.",
+    ),
+    (
+        "With --fields, each record has the fields given, in order, as in \
+         --fields 'user=@handle,pass=-l 32 -c a-z0-9,pin=@pin6'. A field is \
+         either a generator registered under the name after @, or the \
+         options that choose passwords for the main command, such as -l, \
+         -c, -M, --preset and -E. With --format csv or json the fields have \
+         the names given. --hash, --speakable and the breach checks apply to \
+         a field named password, and --min-entropy to the weakest field.",
+        "Mit --fields hat jeder Datensatz die angegebenen Felder in ihrer \
+         Reihenfolge, etwa --fields 'user=@handle,pass=-l 32 -c \
+         a-z0-9,pin=@pin6'. Ein Feld ist entweder ein Generator, der unter \
+         dem Namen nach @ registriert ist, oder die Optionen, die beim \
+         Hauptbefehl die Passwörter bestimmen, etwa -l, -c, -M, --preset und \
+         -E. Mit --format csv oder json tragen die Felder die angegebenen \
+         Namen. --hash, --speakable und die Prüfungen auf Datenlecks gelten \
+         für ein Feld namens password, --min-entropy für das schwächste \
+         Feld.",
+    ),
+    (
+        "The credentials command prints a username and a password on each \
+         line, and with --totp a TOTP secret of 20 random bytes in unpadded \
+         Base32, for test data and account onboarding. --style and \
+         --max-len choose the usernames as for the username command, and \
+         the options for generating passwords choose the passwords. With \
+         --format csv or json the fields are named username, password and \
+         totp_secret.",
+        "Der Befehl credentials gibt in jeder Zeile einen Benutzernamen und \
+         ein Passwort aus, mit --totp zusätzlich ein TOTP-Geheimnis aus 20 \
+         Zufallsbytes in Base32 ohne Auffüllung, für Testdaten und das \
+         Anlegen von Konten. --style und --max-len wählen die \
+         Benutzernamen wie beim Befehl username, die Optionen zum Erzeugen \
+         von Passwörtern wählen die Passwörter. Mit --format csv oder json \
+         heißen die Felder username, password und totp_secret.",
+    ),
+    (
+        "With --users, blank lines are skipped, and the usernames must not \
+         begin with a hyphen or contain colons or whitespace. --format \
+         chpasswd prints user:password lines to pipe into chpasswd, and with \
+         --hash user:hash lines for chpasswd -e, so that chpasswd never sees \
+         the passwords themselves. With --format csv or json and --hash, the \
+         fields are named username, password and hash, keeping the passwords \
+         to hand out alongside the hashes.",
+        "Bei --users werden Leerzeilen übersprungen, und die Benutzernamen \
+         dürfen nicht mit einem Bindestrich beginnen und keine Doppelpunkte \
+         oder Leerraum enthalten. --format chpasswd gibt Zeilen \
+         benutzer:passwort aus, die an chpasswd weitergeleitet werden, mit \
+         --hash Zeilen benutzer:hash für chpasswd -e, sodass chpasswd die \
+         Passwörter selbst nie sieht. Mit --format csv oder json und --hash \
+         heißen die Felder username, password und hash, sodass die \
+         Passwörter zum Verteilen neben den Hashes erhalten bleiben.",
+    ),
+    (
+        "With --speakable, each password is followed by a tab and the same \
+         password with spaces between its pieces: syllables of letters, \
+         pairs of digits and single other characters, with any space in the \
+         password shown as \"␣\". The password itself is unchanged. \
+         With --format csv or json the pieces are in a field named \
+         speakable.",
+        "Mit --speakable folgt auf jedes Passwort ein Tabulator und dasselbe \
+         Passwort mit Leerzeichen zwischen seinen Stücken: Silben aus \
+         Buchstaben, Ziffernpaaren und einzelnen anderen Zeichen, wobei \
+         Leerzeichen im Passwort als \"␣\" erscheinen. Das Passwort \
+         selbst bleibt unverändert. Mit --format csv oder json stehen die \
+         Stücke in einem Feld namens speakable.",
+    ),
+    (
+        "--large-print and --braille lay passwords out for users with low \
+         vision to transcribe them, leaving a blank line between them. With \
+         --braille, each password is split into groups of up to four letters \
+         of one case or digits, or of one other character, so that each \
+         group needs at most one capital or number sign, and wrapped between \
+         groups to the 40 cells of a common display.",
+        "--large-print und --braille ordnen Passwörter so an, dass \
+         sehbehinderte Nutzer sie abschreiben können, mit einer Leerzeile \
+         zwischen ihnen. Mit --braille wird jedes Passwort in Gruppen von \
+         bis zu vier Buchstaben gleicher Schreibung oder Ziffern oder einem \
+         anderen Zeichen geteilt, sodass jede Gruppe höchstens ein Groß- \
+         oder Zahlzeichen benötigt, und zwischen Gruppen auf die 40 Module \
+         einer üblichen Braillezeile umbrochen.",
+    ),
+    ("lowercase letter", "Kleinbuchstabe"),
+    ("uppercase letter", "Großbuchstabe"),
+    ("digit", "Ziffer"),
+    ("symbol", "Sonderzeichen"),
+    ("space", "Leerzeichen"),
+    ("other character", "anderes Zeichen"),
+    (
+        "With --format spelling, each password is followed by one line for \
+         each character giving its position, its class, its word in the \
+         ICAO spelling alphabet or a name for a symbol, and its \
+         International Morse code where it has one, as an aid to reading \
+         passwords out over radio or phone. Digits are given as pronounced \
+         on the radio, such as Tree and Niner.",
+        "Mit --format spelling folgt auf jedes Passwort für jedes Zeichen \
+         eine Zeile mit seiner Position, seiner Klasse, seinem Wort im \
+         ICAO-Buchstabieralphabet oder einem Namen für ein Sonderzeichen \
+         und seinem internationalen Morsecode, falls es einen hat, als \
+         Hilfe beim Vorlesen von Passwörtern über Funk oder Telefon. Ziffern \
+         stehen so, wie sie im Funk gesprochen werden, etwa Tree und Niner.",
+    ),
+    ("exclamation mark", "Ausrufezeichen"),
+    ("double quote", "Anführungszeichen"),
+    ("hash", "Raute"),
+    ("dollar sign", "Dollarzeichen"),
+    ("percent sign", "Prozentzeichen"),
+    ("ampersand", "Und-Zeichen"),
+    ("apostrophe", "Apostroph"),
+    ("left parenthesis", "runde Klammer auf"),
+    ("right parenthesis", "runde Klammer zu"),
+    ("asterisk", "Sternchen"),
+    ("plus sign", "Pluszeichen"),
+    ("comma", "Komma"),
+    ("hyphen", "Bindestrich"),
+    ("full stop", "Punkt"),
+    ("slash", "Schrägstrich"),
+    ("colon", "Doppelpunkt"),
+    ("semicolon", "Semikolon"),
+    ("less-than sign", "Kleiner-als-Zeichen"),
+    ("equals sign", "Gleichheitszeichen"),
+    ("greater-than sign", "Größer-als-Zeichen"),
+    ("question mark", "Fragezeichen"),
+    ("at sign", "At-Zeichen"),
+    ("left bracket", "eckige Klammer auf"),
+    ("backslash", "Backslash"),
+    ("right bracket", "eckige Klammer zu"),
+    ("caret", "Zirkumflex"),
+    ("underscore", "Unterstrich"),
+    ("backtick", "Gravis"),
+    ("left brace", "geschweifte Klammer auf"),
+    ("vertical bar", "senkrechter Strich"),
+    ("right brace", "geschweifte Klammer zu"),
+    ("tilde", "Tilde"),
+    (
+        "With --format ansible-vault, the password is printed as the !vault \
+         tagged string ansible-vault encrypt_string prints, in version 1.1 \
+         of the vault format, to paste after a variable's name in \
+         group_vars or a playbook.",
+        "Mit --format ansible-vault wird das Passwort als mit !vault \
+         markierter String ausgegeben, wie ihn ansible-vault encrypt_string \
+         ausgibt, in Version 1.1 des Vault-Formats, zum Einfügen hinter dem \
+         Namen einer Variable in group_vars oder einem Playbook.",
+    ),
+    (
+        "The pin command prints numeric PINs, 6 digits long by default. It \
+         redraws weak PINs: common ones, a shorter block of digits repeated, \
+         runs of consecutive digits and, for 4 digits, years from 1900 to \
+         2099. The entropy reported with -v counts only the PINs that \
+         remain. --allow-weak keeps every PIN.",
+        "Der Befehl pin gibt numerische PINs aus, standardmäßig 6 Ziffern \
+         lang. Schwache PINs werden neu gezogen: häufige, Wiederholungen \
+         eines kürzeren Ziffernblocks, Folgen aufeinanderfolgender Ziffern \
+         und bei 4 Ziffern Jahreszahlen von 1900 bis 2099. Die mit -v \
+         angegebene Entropie zählt nur die verbleibenden PINs. Mit \
+         --allow-weak bleibt jede PIN erhalten.",
+    ),
+    (
+        "The card command prints a password card: a grid of random \
+         characters with rows A to Z and 26 numbered columns by default, \
+         from which passwords are read along a path starting at a memorised \
+         cell. The characters are the typeable ones other than space unless \
+         -c is given. --html prints the card as a web page laid out for \
+         printing. With --derive, the card is derived from a master password \
+         with Argon2id as by the derive command, so that it can be printed \
+         again; --derivation-version selects an older derivation.",
+        "Der Befehl card gibt eine Passwortkarte aus: ein Raster zufälliger \
+         Zeichen mit den Zeilen A bis Z und standardmäßig 26 nummerierten \
+         Spalten, aus dem Passwörter entlang eines Pfads ab einer gemerkten \
+         Zelle abgelesen werden. Ohne -c werden die tippbaren Zeichen außer \
+         dem Leerzeichen verwendet. --html gibt die Karte als druckfertige \
+         Webseite aus. Mit --derive wird die Karte wie beim Befehl derive \
+         mit Argon2id aus einem Master-Passwort abgeleitet, sodass sie \
+         erneut gedruckt werden kann; --derivation-version wählt eine \
+         ältere Ableitung.",
+    ),
+    (
+        "The strengthen command reads an existing password without echoing \
+         it and prints it with enough random characters added, at the end or \
+         with --inject at random positions, to reach the target entropy, 80 \
+         bits by default. The existing password is credited with the \
+         deliberately low estimate of NIST SP 800-63-1 for passwords people \
+         choose. The characters are the typeable ones other than space \
+         unless -c is given. The estimates before and after are reported on \
+         standard error.",
+        "Der Befehl strengthen liest ein bestehendes Passwort ohne Echo ein \
+         und gibt es mit so vielen angehängten oder mit --inject an \
+         zufälligen Stellen eingefügten Zufallszeichen aus, dass die \
+         Zielentropie erreicht wird, standardmäßig 80 Bit. Dem bestehenden \
+         Passwort wird die bewusst niedrige Schätzung aus NIST SP 800-63-1 \
+         für von Menschen gewählte Passwörter angerechnet. Ohne -c werden \
+         die tippbaren Zeichen außer dem Leerzeichen verwendet. Die \
+         Schätzungen vorher und nachher werden auf der Standardfehlerausgabe \
+         gemeldet.",
+    ),
+    (
+        "The audit command reports on each password in the file, or on \
+         standard input, one per line, or with --pairs each user:password \
+         pair: its estimated entropy, its score or the reason it fails the \
+         checks of the score command, which take the same --config and \
+         --wordlist, whether it is breached with --check-hibp or \
+         --check-offline, and the lines of any duplicates and of any near \
+         duplicates, within 2 edits of it or the number given with --distance, \
+         0 to not look for them. The report never includes the passwords. It \
+         fails if any entry fails the checks, is breached or is duplicated or \
+         nearly so.",
+        "Der Befehl audit berichtet über jedes Passwort in der Datei oder auf \
+         der Standardeingabe, eines pro Zeile, oder mit --pairs über jedes \
+         Paar user:password: seine geschätzte Entropie, seine Bewertung oder \
+         den Grund, aus dem es die Prüfungen des Befehls score nicht \
+         besteht, die dieselben --config und --wordlist nehmen, ob es mit \
+         --check-hibp oder --check-offline kompromittiert ist, und die \
+         Zeilen etwaiger Duplikate und Beinahe-Duplikate, die höchstens 2 \
+         Änderungen oder die mit --distance angegebene Zahl entfernt sind, \
+         0, um nicht nach ihnen zu suchen. Der Bericht enthält nie die \
+         Passwörter. Er schlägt fehl, wenn ein Eintrag die Prüfungen nicht \
+         besteht, kompromittiert ist oder (beinahe) doppelt vorkommt.",
+    ),
+    (
+        "The entropy command prints how long attackers would take on average \
+         to guess a password with the entropy given with --bits, or else \
+         estimated as by the strengthen command for a password read without \
+         echoing it. For a password, the character classes it uses are \
+         reported too, with its naive entropy, which it would have were it \
+         drawn at random from them and which overstates the strength of \
+         passwords people choose. The attackers are online-throttled, a login \
+         form allowing 100 guesses an hour; offline-bcrypt-gpu, 8 GPUs \
+         guessing 50000 bcrypt hashes of cost 10 a second; and \
+         offline-md5-cluster, 100 GPUs guessing 1.6e13 MD5 hashes a second. \
+         --attacker adds an attacker making the given number of guesses a \
+         second, or changes the rate of one of these.",
+        "Der Befehl entropy gibt aus, wie lange Angreifer im Mittel \
+         bräuchten, um ein Passwort mit der mit --bits angegebenen Entropie \
+         zu erraten, oder sonst der wie beim Befehl strengthen geschätzten \
+         eines ohne Echo eingelesenen Passworts. Für ein Passwort werden auch \
+         die verwendeten Zeichenklassen gemeldet, mit seiner naiven Entropie, \
+         die es hätte, wäre es zufällig aus ihnen gezogen, und die die \
+         Stärke von Passwörtern, die Menschen wählen, überschätzt. Die \
+         Angreifer sind online-throttled, ein Anmeldeformular, das 100 \
+         Versuche pro Stunde zulässt; offline-bcrypt-gpu, 8 GPUs, die 50000 \
+         bcrypt-Hashes mit Kosten 10 pro Sekunde raten; und \
+         offline-md5-cluster, 100 GPUs, die 1.6e13 MD5-Hashes pro Sekunde \
+         raten. --attacker fügt einen Angreifer mit der angegebenen Zahl von \
+         Versuchen pro Sekunde hinzu oder ändert die Rate eines dieser.",
+    ),
+    (
+        "With --zxcvbn, the entropy command instead estimates, as zxcvbn does, \
+         the guesses an attacker trying the patterns people use would need: \
+         common passwords and words, forwards, backwards, capitalised or with \
+         l33t substitutions; runs of adjacent keys; repeats; sequences; and \
+         years and dates, with the rest guessed character by character. The \
+         parts of the password matched are reported by position, without their \
+         characters, and the times are those to make the guesses. Only short \
+         built-in lists of words ship; --dictionary adds a list of one word \
+         per line, from most to least common.",
+        "Mit --zxcvbn schätzt der Befehl entropy stattdessen wie zxcvbn, wie \
+         viele Versuche ein Angreifer benötigt, der die Muster probiert, die \
+         Menschen verwenden: häufige Passwörter und Wörter, vorwärts, \
+         rückwärts, großgeschrieben oder mit l33t-Ersetzungen; Folgen \
+         benachbarter Tasten; Wiederholungen; Sequenzen; und Jahre und Daten, \
+         wobei der Rest Zeichen für Zeichen geraten wird. Die erkannten Teile \
+         des Passworts werden nach Position gemeldet, ohne ihre Zeichen, und \
+         die Zeiten sind die für diese Versuche. Nur kurze eingebaute \
+         Wortlisten werden mitgeliefert; --dictionary fügt eine Liste mit \
+         einem Wort pro Zeile hinzu, vom häufigsten zum seltensten.",
+    ),
+    (
+        "The score command checks the passwords on standard input, one per \
+         line, as libpwquality does, with the settings of \
+         /etc/security/pwquality.conf or the file given with --config. Each \
+         password that passes is given a score from 0 at the minimum length to \
+         100 at twice that, and each that fails the reason. The dictionary \
+         check looks words up in the plain word list given with --wordlist \
+         rather than a cracklib dictionary, and --user names the user whose \
+         name the password must not contain.",
+        "Der Befehl score prüft die Passwörter auf der Standardeingabe, \
+         eines pro Zeile, wie libpwquality es tut, mit den Einstellungen aus \
+         /etc/security/pwquality.conf oder der mit --config angegebenen \
+         Datei. Jedes Passwort, das besteht, erhält eine Bewertung von 0 bei \
+         der Mindestlänge bis 100 beim Doppelten davon, und jedes, das \
+         durchfällt, den Grund. Die Wörterbuchprüfung schlägt Wörter in \
+         der mit --wordlist angegebenen einfachen Wortliste statt in einem \
+         cracklib-Wörterbuch nach, und --user nennt den Benutzer, dessen \
+         Namen das Passwort nicht enthalten darf.",
+    ),
+    (
+        "The insert-pass command generates one password as the options for \
+         generating passwords describe and puts it into the entry of that \
+         name in the password store of pass, in PASSWORD_STORE_DIR or \
+         ~/.password-store, encrypted with gpg to the keys in the nearest \
+         .gpg-id file, and prints it. Each --field adds a line of the name, \
+         a colon and the value after the password. An existing entry is \
+         only replaced with -f, or with --in-place, which replaces its first \
+         line and keeps the others. The change is committed if the store is \
+         a git repository.",
+        "Der Befehl insert-pass erzeugt ein Passwort, wie es die Optionen zum \
+         Erzeugen von Passwörtern beschreiben, legt es im Eintrag dieses \
+         Namens im Passwortspeicher von pass ab, in PASSWORD_STORE_DIR oder \
+         ~/.password-store, mit gpg für die Schlüssel in der \
+         nächstgelegenen .gpg-id-Datei verschlüsselt, und gibt es aus. Jedes \
+         --field fügt nach dem Passwort eine Zeile aus dem Namen, einem \
+         Doppelpunkt und dem Wert hinzu. Ein vorhandener Eintrag wird nur mit \
+         -f ersetzt oder mit --in-place, das seine erste Zeile ersetzt und die \
+         übrigen behält. Ist der Speicher ein Git-Repository, wird die \
+         Änderung committet.",
+    ),
+    (
+        "The hibp command reads passwords from standard input, one per \
+         line, and reports for each whether it appears in breaches known to \
+         Have I Been Pwned, checking them as --check-hibp does, or with \
+         --offline as --check-offline does. It fails if any does. The hibp \
+         build command builds a Bloom filter from a Pwned Passwords SHA-1 \
+         dump ordered by hash, for checks on machines without network \
+         access; it is much smaller than the dump, at the cost of reporting \
+         one in 1000 other passwords as breached by default, or the rate \
+         given with --fp-rate.",
+        "Der Befehl hibp liest Passwörter zeilenweise von der \
+         Standardeingabe und meldet für jedes, ob es in Datenlecks \
+         vorkommt, die Have I Been Pwned bekannt sind, und prüft sie dabei \
+         wie --check-hibp oder mit --offline wie --check-offline. Er \
+         schlägt fehl, wenn eines davon vorkommt. Der Befehl hibp build \
+         erstellt aus einem nach Hash sortierten SHA-1-Abzug von Pwned \
+         Passwords einen Bloom-Filter für Prüfungen auf Rechnern ohne \
+         Netzwerkzugang; er ist viel kleiner als der Abzug, meldet dafür \
+         aber standardmäßig eines von 1000 anderen Passwörtern als \
+         kompromittiert, oder den mit --fp-rate angegebenen Anteil.",
+    ),
+    (
+        "The retrieve command prints the secret saved into the platform \
+         keyring by --store keyring under the service and account.",
+        "Der Befehl retrieve gibt das Geheimnis aus, das --store keyring \
+         unter dem Dienst und dem Konto im Schlüsselbund der Plattform \
+         gespeichert hat.",
+    ),
+    (
+        "The recover command reads the shares of one password printed by \
+         --split from standard input, one per line, and prints the password.",
+        "Der Befehl recover liest die mit --split ausgegebenen Anteile eines \
+         Passworts zeilenweise von der Standardeingabe und gibt das Passwort \
+         aus.",
+    ),
+    (
+        "The man command prints this manual in roff format for installation \
+         as a man page.",
+        "Der Befehl man gibt diese Anleitung im roff-Format zur Installation \
+         als Manpage aus.",
+    ),
+    ("success", "Erfolg"),
+    ("other failure", "sonstiger Fehler"),
+    (
+        "usage error or invalid charset specification",
+        "Aufruffehler oder ungültiger Zeichenvorrat",
+    ),
+    (
+        "policy cannot be satisfied, at all or within the limits",
+        "Richtlinie nicht erfüllbar, überhaupt oder in den Grenzen",
+    ),
+    (
+        "password entropy below --min-entropy",
+        "Passwortentropie unter --min-entropy",
+    ),
+    ("random number generator failure", "Fehler des Zufallszahlengenerators"),
+    ("I/O error", "Ein-/Ausgabefehler"),
+    (
+        "random number generator failed a health test",
+        "Zufallszahlengenerator hat einen Gesundheitstest nicht bestanden",
+    ),
+    (
+        "Warnings are written to standard error prefixed with \"warning: \".",
+        "Warnungen werden mit dem Präfix \"warning: \" auf die \
+         Standardfehlerausgabe geschrieben.",
+    ),
+    // Prompts
+    ("Password length", "Passwortlänge"),
+    (
+        "Character classes (S symbols, N numbers, C capitals, L lowercase; \
+         uppercase letters are required)",
+        "Zeichenklassen (S Sonderzeichen, N Ziffern, C Großbuchstaben, L \
+         Kleinbuchstaben; Großbuchstaben sind Pflicht)",
+    ),
+    ("any typeable", "alle tippbaren"),
+    ("Number of passwords", "Anzahl der Passwörter"),
+    ("Generate anyway?", "Trotzdem erzeugen?"),
+    ("y/N", "j/N"),
+    ("please answer yes or no", "bitte mit ja oder nein antworten"),
+    ("unexpected end of input", "unerwartetes Ende der Eingabe"),
+    (
+        "Extra entropy from k keyboard, d dice or a file path",
+        "Zusätzliche Entropie aus k Tastatur, d Würfeln oder einem Dateipfad",
+    ),
+    (
+        "Type random keys, then press Enter",
+        "Zufällige Tasten drücken, dann Enter",
+    ),
+    ("Master password", "Master-Passwort"),
+    ("Repeat the master password", "Master-Passwort wiederholen"),
+    ("Password", "Passwort"),
+    (
+        "--target must be a positive number of bits",
+        "--target muss eine positive Anzahl von Bits sein",
+    ),
+    (
+        "estimated entropy: {} bits before, {} bits after",
+        "geschätzte Entropie: vorher {} Bit, nachher {} Bit",
+    ),
+    ("random characters added: {}", "hinzugefügte Zufallszeichen: {}"),
+    ("Dice rolls from 1 to 6", "Würfelergebnisse von 1 bis 6"),
+    (
+        "please enter only the digits 1 to 6",
+        "bitte nur die Ziffern 1 bis 6 eingeben",
+    ),
+    // Warnings
+    (
+        "password entropy is only {} bits; consider a longer password or a \
+         larger character set",
+        "die Passwortentropie beträgt nur {} Bit; ein längeres Passwort oder \
+         ein größerer Zeichenvorrat wäre besser",
+    ),
+    (
+        "could not disable core dumps: {}",
+        "Speicherabbilder konnten nicht deaktiviert werden: {}",
+    ),
+    (
+        "could not install the sandbox: {}",
+        "die Sandbox konnte nicht eingerichtet werden: {}",
+    ),
+    (
+        "the operating system's random number generator failed; some \
+         passwords were drawn from the ChaCha20 fallback",
+        "der Zufallszahlengenerator des Betriebssystems ist ausgefallen; \
+         einige Passwörter stammen aus dem ChaCha20-Ersatzgenerator",
+    ),
+    ("could not run {}: {}", "{} konnte nicht ausgeführt werden: {}"),
+    (
+        "could not run \"{}\"",
+        "\"{}\" konnte nicht ausgeführt werden",
+    ),
+    ("{} exited with {}", "{} wurde mit {} beendet"),
+    // Errors
+    ("line {}", "Zeile {}"),
+    (
+        "line {} is not user:password",
+        "Zeile {} ist nicht user:password",
+    ),
+    ("entries with findings: {}", "Einträge mit Befunden: {}"),
+    ("--bits must be a number of bits", "--bits muss eine Zahl von Bits sein"),
+    ("invalid attacker: \"{}\"", "ungültiger Angreifer: \"{}\""),
+    ("line {} of \"{}\"", "Zeile {} von \"{}\""),
+    ("unknown setting: {}", "unbekannte Einstellung: {}"),
+    (
+        "invalid value for {}: \"{}\"",
+        "ungültiger Wert für {}: \"{}\"",
+    ),
+    (
+        "passwords failing the checks: {}",
+        "Passwörter, die die Prüfungen nicht bestehen: {}",
+    ),
+    ("the password is a palindrome", "das Passwort ist ein Palindrom"),
+    (
+        "the password contains more than {} same characters consecutively",
+        "das Passwort enthält mehr als {} gleiche Zeichen hintereinander",
+    ),
+    (
+        "the password contains a monotonic sequence longer than {} \
+         characters",
+        "das Passwort enthält eine monotone Folge von mehr als {} Zeichen",
+    ),
+    (
+        "the password contains the user name in some form",
+        "das Passwort enthält den Benutzernamen in irgendeiner Form",
+    ),
+    (
+        "the password contains forbidden words in some form",
+        "das Passwort enthält verbotene Wörter in irgendeiner Form",
+    ),
+    (
+        "the password fails the dictionary check: {}",
+        "das Passwort besteht die Wörterbuchprüfung nicht: {}",
+    ),
+    (
+        "the password contains more than {} characters of the same class \
+         consecutively",
+        "das Passwort enthält mehr als {} Zeichen derselben Klasse \
+         hintereinander",
+    ),
+    (
+        "the password contains fewer than {} digits",
+        "das Passwort enthält weniger als {} Ziffern",
+    ),
+    (
+        "the password contains fewer than {} uppercase letters",
+        "das Passwort enthält weniger als {} Großbuchstaben",
+    ),
+    (
+        "the password contains fewer than {} lowercase letters",
+        "das Passwort enthält weniger als {} Kleinbuchstaben",
+    ),
+    (
+        "the password contains fewer than {} non-alphanumeric characters",
+        "das Passwort enthält weniger als {} nicht alphanumerische Zeichen",
+    ),
+    (
+        "the password contains fewer than {} character classes",
+        "das Passwort enthält weniger als {} Zeichenklassen",
+    ),
+    (
+        "the password is shorter than {} characters",
+        "das Passwort ist kürzer als {} Zeichen",
+    ),
+    ("it is all whitespace", "es besteht nur aus Leerraum"),
+    (
+        "it does not contain enough DIFFERENT characters",
+        "es enthält nicht genug VERSCHIEDENE Zeichen",
+    ),
+    (
+        "it is too simplistic/systematic",
+        "es ist zu einfach/systematisch",
+    ),
+    (
+        "it is based on a dictionary word",
+        "es beruht auf einem Wörterbuchwort",
+    ),
+    (
+        "it is based on a (reversed) dictionary word",
+        "es beruht auf einem (umgekehrten) Wörterbuchwort",
+    ),
+    (
+        "--zxcvbn and --bits cannot be used together",
+        "--zxcvbn und --bits können nicht zusammen verwendet werden",
+    ),
+    (
+        "--prompt and --batch cannot be used together",
+        "--prompt und --batch können nicht zusammen verwendet werden",
+    ),
+    (
+        "--extra-entropy and --batch cannot be used together",
+        "--extra-entropy und --batch können nicht zusammen verwendet werden",
+    ),
+    (
+        "--entropy-source and --batch cannot be used together",
+        "--entropy-source und --batch können nicht zusammen verwendet werden",
+    ),
+    (
+        "this build does not support YubiKeys",
+        "dieser Build unterstützt keine YubiKeys",
+    ),
+    (
+        "RDSEED is only available on x86 processors",
+        "RDSEED ist nur auf x86-Prozessoren verfügbar",
+    ),
+    (
+        "the processor does not support RDSEED",
+        "der Prozessor unterstützt RDSEED nicht",
+    ),
+    (
+        "derive needs both --site and --login",
+        "derive benötigt sowohl --site als auch --login",
+    ),
+    ("master password is empty", "das Master-Passwort ist leer"),
+    (
+        "could not find a configuration directory; use --profiles",
+        "kein Konfigurationsverzeichnis gefunden; --profiles verwenden",
+    ),
+    ("could not write \"{}\"", "\"{}\" konnte nicht geschrieben werden"),
+    ("could not open \"{}\"", "\"{}\" konnte nicht geöffnet werden"),
+    ("imported {} profiles", "{} Profile importiert"),
+    ("could not read \"{}\"", "\"{}\" konnte nicht gelesen werden"),
+    ("--batch cannot be nested", "--batch kann nicht verschachtelt werden"),
+    (
+        "in batch mode, --audit-log must be given on the command line",
+        "im Stapelmodus muss --audit-log auf der Befehlszeile angegeben \
+         werden",
+    ),
+    (
+        "this build does not support --check-hibp",
+        "dieser Build unterstützt --check-hibp nicht",
+    ),
+    (
+        "{} does not take --check-hibp or --check-offline",
+        "{} nimmt keine Optionen --check-hibp oder --check-offline",
+    ),
+    (
+        "--check-hibp and --check-offline cannot be used together",
+        "--check-hibp und --check-offline können nicht zusammen verwendet \
+         werden",
+    ),
+    (
+        "the breach list is malformed",
+        "die Liste der Datenlecks ist fehlerhaft",
+    ),
+    (
+        "false positive rate must be in (0, 1)",
+        "die Falsch-positiv-Rate muss in (0, 1) liegen",
+    ),
+    ("hashes added: {}", "hinzugefügte Hashes: {}"),
+    (
+        "could not find the password store",
+        "der Passwortspeicher wurde nicht gefunden",
+    ),
+    ("invalid entry name: \"{}\"", "ungültiger Eintragsname: \"{}\""),
+    (
+        "the password store has no entry \"{}\"",
+        "der Passwortspeicher hat keinen Eintrag \"{}\"",
+    ),
+    (
+        "the password store already has an entry \"{}\"; use --in-place \
+         or -f",
+        "der Passwortspeicher hat bereits einen Eintrag \"{}\"; \
+         --in-place oder -f verwenden",
+    ),
+    (
+        "no .gpg-id in the password store at \"{}\"",
+        "keine .gpg-id im Passwortspeicher unter \"{}\"",
+    ),
+    ("invalid field: \"{}\"", "ungültiges Feld: \"{}\""),
+    (
+        "insert-pass generates one password and does not take --batch, \
+         --split, --encrypt-to, --format or --users",
+        "insert-pass erzeugt ein Passwort und nimmt keine Optionen --batch, \
+         --split, --encrypt-to, --format oder --users",
+    ),
+    (
+        "insert-pass and --sandbox cannot be used together",
+        "insert-pass und --sandbox können nicht zusammen verwendet werden",
+    ),
+    (
+        "save the password into the platform keyring under --service and \
+         --account instead of printing it: the Secret Service through \
+         secret-tool, the macOS keychain or the Windows Credential Manager; \
+         needs a build with the keyring feature",
+        "das Passwort unter --service und --account im Schlüsselbund der \
+         Plattform speichern, statt es auszugeben: im Secret Service über \
+         secret-tool, im macOS-Schlüsselbund oder in der Windows-\
+         Anmeldeinformationsverwaltung; benötigt einen Build mit dem \
+         Feature keyring",
+    ),
+    (
+        "the service to save the password under with --store",
+        "der Dienst, unter dem --store das Passwort speichert",
+    ),
+    (
+        "the account to save the password under with --store",
+        "das Konto, unter dem --store das Passwort speichert",
+    ),
+    (
+        "this build does not support --store keyring",
+        "dieser Build unterstützt --store keyring nicht",
+    ),
+    ("unknown store: \"{}\"", "unbekannter Speicher: \"{}\""),
+    (
+        "--service, --account, --entry, --uri, --path and --key need \
+         --store",
+        "--service, --account, --entry, --uri, --path und --key benötigen \
+         --store",
+    ),
+    (
+        "--store keyring needs --service and --account and does not take \
+         --entry, --uri, --path or --key",
+        "--store keyring benötigt --service und --account und nimmt kein \
+         --entry, --uri, --path oder --key",
+    ),
+    (
+        "--store vault needs --path and --key and does not take --service, \
+         --account, --entry or --uri",
+        "--store vault benötigt --path und --key und nimmt kein --service, \
+         --account, --entry oder --uri",
+    ),
+    (
+        "--store {} needs --entry and does not take --service, --account, \
+         --path or --key",
+        "--store {} benötigt --entry und nimmt kein --service, --account, \
+         --path oder --key",
+    ),
+    (
+        "--store {} generates one password and does not take --batch, \
+         --split, --encrypt-to, --format, --users, credentials or \
+         insert-pass",
+        "--store {} erzeugt ein Passwort und nimmt keine Optionen --batch, \
+         --split, --encrypt-to, --format, --users, credentials oder \
+         insert-pass",
+    ),
+    (
+        "--store {} does not take --batch, --split, --encrypt-to, --format, \
+         --hash or insert-pass",
+        "--store {} nimmt keine Optionen --batch, --split, --encrypt-to, \
+         --format, --hash oder insert-pass",
+    ),
+    (
+        "--entry cannot give a username with credentials or --users, which \
+         give them",
+        "--entry kann bei credentials oder --users, die Benutzernamen \
+         liefern, keinen Benutzernamen angeben",
+    ),
+    (
+        "add the passwords as entries named by --entry to the KeePass 4 \
+         database in the file, creating it if needed, instead of printing \
+         them",
+        "die Passwörter als mit --entry benannte Einträge zur KeePass-4-\
+         Datenbank in der Datei hinzufügen, die bei Bedarf angelegt wird, \
+         statt sie auszugeben",
+    ),
+    (
+        "the title and username of the entries added with --store kdbx or \
+         bitwarden, or the name of the credential with --store \
+         systemd-creds",
+        "Titel und Benutzername der mit --store kdbx oder bitwarden \
+         hinzugefügten Einträge oder der Name der Anmeldeinformation bei \
+         --store systemd-creds",
+    ),
+    (
+        "the URI of the entries added with --store kdbx or bitwarden",
+        "die URI der mit --store kdbx oder bitwarden hinzugefügten \
+         Einträge",
+    ),
+    (
+        "create login items named by --entry in the Bitwarden or \
+         Vaultwarden vault bw is unlocked for, instead of printing the \
+         passwords",
+        "mit --entry benannte Anmeldeeinträge im Bitwarden- oder \
+         Vaultwarden-Tresor anlegen, für den bw entsperrt ist, statt die \
+         Passwörter auszugeben",
+    ),
+    (
+        "With --store bitwarden, the items are created with bw create item, \
+         and bw must be logged in with its vault unlocked, with the session \
+         key that bw unlock prints exported as BW_SESSION. With credentials \
+         the generated username and any TOTP secret go into the item too, \
+         and with -n each password gets an item of its own.",
+        "Mit --store bitwarden werden die Einträge mit bw create item \
+         angelegt, und bw muss mit entsperrtem Tresor angemeldet sein, mit \
+         dem Sitzungsschlüssel, den bw unlock ausgibt, als BW_SESSION \
+         exportiert. Mit credentials kommen auch der erzeugte Benutzername \
+         und ein etwaiges TOTP-Geheimnis in den Eintrag, und mit -n bekommt \
+         jedes Passwort einen eigenen Eintrag.",
+    ),
+    (
+        "the Bitwarden vault is locked; unlock it with bw unlock and export \
+         BW_SESSION",
+        "der Bitwarden-Tresor ist gesperrt; mit bw unlock entsperren und \
+         BW_SESSION exportieren",
+    ),
+    (
+        "bw is not logged in; log in with bw login",
+        "bw ist nicht angemeldet; mit bw login anmelden",
+    ),
+    (
+        "write the password under --key into the secret at --path in \
+         HashiCorp Vault's KV version 2 engine, keeping the secret's other \
+         keys, instead of printing it; needs a build with the vault feature",
+        "das Passwort unter --key in das Geheimnis unter --path in der \
+         KV-Engine Version 2 von HashiCorp Vault schreiben, wobei die \
+         anderen Schlüssel des Geheimnisses erhalten bleiben, statt es \
+         auszugeben; benötigt einen Build mit dem Feature vault",
+    ),
+    (
+        "the secret to write with --store vault, under the engine mounted \
+         at the first component",
+        "das Geheimnis, das --store vault schreibt, in der Engine, die an \
+         der ersten Komponente eingehängt ist",
+    ),
+    (
+        "the key to write the password under with --store vault",
+        "der Schlüssel, unter dem --store vault das Passwort schreibt",
+    ),
+    (
+        "With --store vault, Vault is reached at VAULT_ADDR with the token \
+         in VAULT_TOKEN or ~/.vault-token, and in the namespace in \
+         VAULT_NAMESPACE if set. The secret is read before the password is \
+         generated and written back with check-and-set, so the write fails \
+         if the secret changed in between.",
+        "Mit --store vault wird Vault unter VAULT_ADDR mit dem Token aus \
+         VAULT_TOKEN oder ~/.vault-token erreicht, und im Namensraum aus \
+         VAULT_NAMESPACE, falls gesetzt. Das Geheimnis wird gelesen, bevor \
+         das Passwort erzeugt wird, und mit Check-and-Set zurückgeschrieben, \
+         sodass das Schreiben fehlschlägt, wenn sich das Geheimnis \
+         zwischendurch geändert hat.",
+    ),
+    (
+        "print the password encrypted by systemd-creds as the systemd \
+         credential named by --entry",
+        "das Passwort von systemd-creds verschlüsselt als die mit --entry \
+         benannte systemd-Anmeldeinformation ausgeben",
+    ),
+    (
+        "With --store systemd-creds, the credential is encrypted without a \
+         trailing newline, with the host key, the TPM or both as \
+         systemd-creds chooses, for a unit's LoadCredentialEncrypted= or \
+         /etc/credstore.encrypted.",
+        "Mit --store systemd-creds wird die Anmeldeinformation ohne \
+         abschließenden Zeilenumbruch verschlüsselt, mit dem \
+         Host-Schlüssel, dem TPM oder beidem, wie systemd-creds es wählt, \
+         für LoadCredentialEncrypted= einer Unit oder \
+         /etc/credstore.encrypted.",
+    ),
+    (
+        "The native-host command serves a browser extension over the native \
+         messaging protocol of Chrome and Firefox; the host manifest's path \
+         must name a script that runs it. Each request is a JSON object with \
+         op generate and the fields of a JSON specification for --batch, or \
+         with op check and a password. The response gives the passwords and \
+         their entropy_bits, or for a check the estimate of the strengthen \
+         command as entropy_bits. With --check-hibp or --check-offline, \
+         breached passwords are redrawn, and a check gives whether the \
+         password is breached. An id in a request is returned in its \
+         response, and a failed request gets an error.",
+        "Der Befehl native-host bedient eine Browsererweiterung über das \
+         Native-Messaging-Protokoll von Chrome und Firefox; der Pfad im \
+         Host-Manifest muss ein Skript nennen, das ihn ausführt. Jede \
+         Anfrage ist ein JSON-Objekt mit op generate und den Feldern einer \
+         JSON-Spezifikation für --batch oder mit op check und einem \
+         password. Die Antwort enthält die passwords und ihre \
+         entropy_bits, bei einer Prüfung die Schätzung des Befehls \
+         strengthen als entropy_bits. Mit --check-hibp oder \
+         --check-offline werden kompromittierte Passwörter neu gezogen, \
+         und eine Prüfung gibt in breached an, ob das Passwort \
+         kompromittiert ist. Eine id in einer \
+         Anfrage wird in ihrer Antwort zurückgegeben, und eine \
+         fehlgeschlagene Anfrage erhält einen error.",
+    ),
+    (
+        "The serve command answers POST /generate on 127.0.0.1:7878, or the \
+         loopback address and port given with --listen, for tools on the same \
+         host. The body is a JSON specification as for --batch, and the \
+         response a JSON object with the passwords and their entropy_bits, or \
+         an error. Requests must send one of the tokens in the file, which \
+         only its owner may read, as a bearer token; the file has one to a \
+         line, after a name and a space if it is to have one. At most 60 \
+         requests a minute are answered from each client address, or the limit \
+         given with --rate, and with --quota, at most so many passwords are \
+         generated with each token in each window. GET /status, with any of \
+         the tokens, gives the requests counted and refused, and the passwords \
+         generated and quota remaining for each token. Needs a build with the \
+         server feature.",
+        "Der Befehl serve beantwortet POST /generate auf 127.0.0.1:7878 oder \
+         der mit --listen angegebenen Loopback-Adresse und dem Port, für \
+         Werkzeuge auf demselben Host. Der Rumpf ist eine JSON-Spezifikation \
+         wie bei --batch, die Antwort ein JSON-Objekt mit den passwords und \
+         ihren entropy_bits oder einem error. Anfragen müssen eines der \
+         Tokens aus der Datei, die nur ihr Besitzer lesen darf, als \
+         Bearer-Token senden; die Datei enthält eines pro Zeile, nach einem \
+         Namen und einem Leerzeichen, falls es einen haben soll. Es werden \
+         höchstens 60 Anfragen pro Minute von jeder Client-Adresse \
+         beantwortet, oder die mit --rate angegebene Grenze, und mit --quota \
+         werden mit jedem Token höchstens so viele Passwörter in jedem \
+         Zeitfenster erzeugt. GET /status gibt mit einem beliebigen der Tokens \
+         die gezählten und abgelehnten Anfragen sowie für jedes Token die \
+         erzeugten Passwörter und das verbleibende Kontingent an. Benötigt \
+         einen Build mit dem Feature server.",
+    ),
+    (
+        "The grpc command serves the gRPC service of proto/passgen.proto on \
+         127.0.0.1:7879, or the loopback address and port given with --listen: \
+         Generate streams count passwords to a specification as for --batch, \
+         Check estimates a password's entropy, and Entropy gives a \
+         specification's entropy and the size of its character set. Calls must \
+         send one of the tokens in the file as a bearer token in their \
+         authorization metadata, as for serve, and are limited by --rate and \
+         --quota in the same way, a Generate call's passwords all counting \
+         against the quota when it starts. Passwords are generated no faster \
+         than the client reads them. Needs a build with the grpc feature.",
+        "Der Befehl grpc bietet den gRPC-Dienst aus proto/passgen.proto auf \
+         127.0.0.1:7879 oder der mit --listen angegebenen Loopback-Adresse und \
+         dem Port an: Generate streamt count Passwörter nach einer \
+         Spezifikation wie bei --batch, Check schätzt die Entropie eines \
+         Passworts, und Entropy gibt die Entropie einer Spezifikation und die \
+         Größe ihres Zeichensatzes an. Aufrufe müssen eines der Tokens aus \
+         der Datei als Bearer-Token in ihren authorization-Metadaten senden, \
+         wie bei serve, und werden durch --rate und --quota ebenso begrenzt, \
+         wobei alle Passwörter eines Generate-Aufrufs bei seinem Beginn auf \
+         das Kontingent angerechnet werden. Passwörter werden nicht schneller \
+         erzeugt, als der Client sie liest. Benötigt einen Build mit dem \
+         Feature grpc.",
+    ),
+    (
+        "The daemon command serves requests as the native-host command does on \
+         a Unix socket only its owner can connect to, \
+         $XDG_RUNTIME_DIR/passgen.sock or the path given with --socket, one \
+         JSON object per line answered by one per line. With --wordlist, \
+         requests with op passphrase get passphrases of words from the file, \
+         taking the last field of each line, with 6 words joined by - unless \
+         words or separator say otherwise. The word list and breach list are \
+         loaded once when the daemon starts. With --rate and --quota, the \
+         daemon's requests, which all come from its owner, are limited \
+         together, and op status gives what has been counted.",
+        "Der Befehl daemon beantwortet Anfragen wie der Befehl native-host auf \
+         einem Unix-Socket, mit dem sich nur sein Besitzer verbinden kann, \
+         $XDG_RUNTIME_DIR/passgen.sock oder dem mit --socket angegebenen Pfad, \
+         ein JSON-Objekt pro Zeile, beantwortet mit einem pro Zeile. Mit \
+         --wordlist erhalten Anfragen mit op passphrase Passphrasen aus \
+         Wörtern der Datei, wobei das letzte Feld jeder Zeile genommen wird, \
+         mit 6 durch - verbundenen Wörtern, sofern words oder separator \
+         nichts anderes angeben. Die Wortliste und die Liste der Datenlecks \
+         werden einmal beim Start des Daemons geladen. Mit --rate und --quota \
+         werden die Anfragen an den Daemon, die alle von seinem Besitzer \
+         kommen, gemeinsam begrenzt, und op status gibt an, was gezählt \
+         wurde.",
+    ),
+    (
+        "The limits of --rate and --quota are a count in a window, as in 60/m \
+         or 1000/12h, of seconds, minutes, hours or days with a suffix of s, \
+         m, h or d; a count alone is a minute's. A rate allows bursts of up to \
+         the count, refilled evenly over the window. A quota's window starts \
+         with the first password generated in it, and a request that would go \
+         over the quota is refused whole, with status 429 from serve.",
+        "Die Grenzen von --rate und --quota sind eine Anzahl in einem \
+         Zeitfenster, wie 60/m oder 1000/12h, aus Sekunden, Minuten, Stunden \
+         oder Tagen mit der Endung s, m, h oder d; eine Anzahl allein gilt \
+         für eine Minute. Eine Rate erlaubt Schübe bis zur Anzahl, die \
+         gleichmäßig über das Zeitfenster aufgefüllt werden. Das \
+         Zeitfenster eines Kontingents beginnt mit dem ersten darin erzeugten \
+         Passwort, und eine Anfrage, die das Kontingent überschreiten würde, \
+         wird ganz abgelehnt, bei serve mit dem Status 429.",
+    ),
+    (
+        "The keyfile command writes a keyfile of random bytes for \
+         cryptsetup, 4096 bytes by default or the size given with --size, \
+         from 32 bytes to 8 MiB. The file is created readable only by its \
+         owner. An existing file is only replaced with -f.",
+        "Der Befehl keyfile schreibt eine Schlüsseldatei aus Zufallsbytes \
+         für cryptsetup, standardmäßig 4096 Bytes oder die mit --size \
+         angegebene Größe, von 32 Bytes bis 8 MiB. Die Datei wird nur für \
+         ihren Besitzer lesbar angelegt. Eine bestehende Datei wird nur mit \
+         -f ersetzt.",
+    ),
+    (
+        "--store systemd-creds needs --entry and does not take --service, \
+         --account, --uri, --path or --key",
+        "--store systemd-creds benötigt --entry und nimmt kein --service, \
+         --account, --uri, --path oder --key",
+    ),
+    (
+        "invalid credential name: \"{}\"",
+        "ungültiger Name der Anmeldeinformation: \"{}\"",
+    ),
+    (
+        "the keyfile size must be from {} to {} bytes",
+        "die Größe der Schlüsseldatei muss zwischen {} und {} Bytes liegen",
+    ),
+    (
+        "\"{}\" already exists; use -f to replace it",
+        "\"{}\" existiert bereits; -f verwenden, um es zu ersetzen",
+    ),
+    (
+        "this build does not support --store vault",
+        "dieser Build unterstützt --store vault nicht",
+    ),
+    (
+        "this build does not support serve",
+        "dieser Build unterstützt serve nicht",
+    ),
+    (
+        "this build does not support grpc",
+        "dieser Build unterstützt grpc nicht",
+    ),
+    ("{} needs --token-file", "{} benötigt --token-file"),
+    (
+        "a valid bearer token is needed",
+        "ein gültiges Bearer-Token wird benötigt",
+    ),
+    ("invalid limit: \"{}\"", "ungültige Grenze: \"{}\""),
+    (
+        "the quota allows at most {} passwords at once",
+        "das Kontingent erlaubt höchstens {} Passwörter auf einmal",
+    ),
+    (
+        "quota exceeded; try again in {} seconds",
+        "Kontingent überschritten; in {} Sekunden erneut versuchen",
+    ),
+    (
+        "too many requests; try again in {} seconds",
+        "zu viele Anfragen; in {} Sekunden erneut versuchen",
+    ),
+    (
+        "token name repeated in \"{}\": \"{}\"",
+        "Tokenname in \"{}\" wiederholt: \"{}\"",
+    ),
+    ("invalid address: \"{}\"", "ungültige Adresse: \"{}\""),
+    (
+        "{} only listens on loopback addresses",
+        "{} lauscht nur auf Loopback-Adressen",
+    ),
+    ("could not listen on {}", "auf {} konnte nicht gelauscht werden"),
+    ("listening on {}", "lausche auf {}"),
+    (
+        "\"{}\" must be readable only by its owner",
+        "\"{}\" darf nur für seinen Besitzer lesbar sein",
+    ),
+    ("the token in \"{}\" is empty", "das Token in \"{}\" ist leer"),
+    ("invalid Vault path: \"{}\"", "ungültiger Vault-Pfad: \"{}\""),
+    ("VAULT_ADDR is not set", "VAULT_ADDR ist nicht gesetzt"),
+    (
+        "no Vault token in VAULT_TOKEN or ~/.vault-token",
+        "kein Vault-Token in VAULT_TOKEN oder ~/.vault-token",
+    ),
+    ("could not reach Vault: {}", "Vault nicht erreichbar: {}"),
+    (
+        "Vault refused the request: {}",
+        "Vault hat die Anfrage abgelehnt: {}",
+    ),
+    (
+        "With --store kdbx, the database password is asked for on the \
+         terminal, twice if the database is new. A new database is \
+         encrypted with ChaCha20 under a key derived with Argon2id; \
+         existing databases keep their settings. With credentials the \
+         generated username and any TOTP secret go into the entry too, and \
+         with -n each password gets an entry of its own.",
+        "Mit --store kdbx wird das Datenbankpasswort am Terminal abgefragt, \
+         bei einer neuen Datenbank zweimal. Eine neue Datenbank wird mit \
+         ChaCha20 unter einem mit Argon2id abgeleiteten Schlüssel \
+         verschlüsselt; bestehende Datenbanken behalten ihre \
+         Einstellungen. Mit credentials kommen auch der erzeugte \
+         Benutzername und ein etwaiges TOTP-Geheimnis in den Eintrag, und \
+         mit -n bekommt jedes Passwort einen eigenen Eintrag.",
+    ),
+    ("Database password", "Datenbankpasswort"),
+    ("New database password", "Neues Datenbankpasswort"),
+    ("Repeat the database password", "Datenbankpasswort wiederholen"),
+    ("the database password is empty", "das Datenbankpasswort ist leer"),
+    (
+        "the passwords do not match",
+        "die Passwörter stimmen nicht überein",
+    ),
+    (
+        "\"{}\" is not a KeePass 4 database",
+        "\"{}\" ist keine KeePass-4-Datenbank",
+    ),
+    ("the database is malformed", "die Datenbank ist fehlerhaft"),
+    (
+        "the database password is wrong, or the database is corrupt",
+        "das Datenbankpasswort ist falsch oder die Datenbank ist beschädigt",
+    ),
+    (
+        "the database uses an unsupported key derivation",
+        "die Datenbank verwendet eine nicht unterstützte \
+         Schlüsselableitung",
+    ),
+    (
+        "the database uses an unsupported cipher",
+        "die Datenbank verwendet eine nicht unterstützte Verschlüsselung",
+    ),
+    (
+        "--store and --sandbox cannot be used together",
+        "--store und --sandbox können nicht zusammen verwendet werden",
+    ),
+    (
+        "--store and --batch cannot be used together",
+        "--store und --batch können nicht zusammen verwendet werden",
+    ),
+    (
+        "the keyring has no entry for service \"{}\" and account \"{}\"",
+        "der Schlüsselbund hat keinen Eintrag für den Dienst \"{}\" und \
+         das Konto \"{}\"",
+    ),
+    (
+        "the secret is too long for the keyring",
+        "das Geheimnis ist zu lang für den Schlüsselbund",
+    ),
+    (
+        "the keyring entry is not valid text",
+        "der Eintrag im Schlüsselbund ist kein gültiger Text",
+    ),
+    (
+        "could not access the keyring: {}",
+        "auf den Schlüsselbund konnte nicht zugegriffen werden: {}",
+    ),
+    (
+        "--check-hibp and --sandbox cannot be used together",
+        "--check-hibp und --sandbox können nicht zusammen verwendet werden",
+    ),
+    (
+        "could not query Have I Been Pwned: {}",
+        "Have I Been Pwned konnte nicht abgefragt werden: {}",
+    ),
+    (
+        "Have I Been Pwned sent an invalid response",
+        "Have I Been Pwned hat eine ungültige Antwort gesendet",
+    ),
+    (
+        "{} passwords in a row appear in breaches; consider a longer \
+         password",
+        "{} Passwörter in Folge kommen in Datenlecks vor; ein längeres \
+         Passwort wäre ratsam",
+    ),
+    ("breached passwords: {}", "Passwörter in Datenlecks: {}"),
+    (
+        "credentials does not take --batch",
+        "credentials nimmt keine Option --batch",
+    ),
+    (
+        "credentials does not take --users",
+        "credentials nimmt keine Option --users",
+    ),
+    (
+        "--split cannot be used with --format, --users or credentials",
+        "--split kann nicht mit --format, --users oder credentials \
+         verwendet werden",
+    ),
+    (
+        "--speakable cannot be used with --split, --format chpasswd or \
+         ansible-vault, --store or insert-pass",
+        "--speakable kann nicht mit --split, --format chpasswd oder \
+         ansible-vault, --store oder insert-pass verwendet werden",
+    ),
+    (
+        "{} prints passwords alone, as text, and cannot be used with \
+         --format, --users, --split, --speakable, credentials, --store or \
+         insert-pass",
+        "{} gibt Passwörter allein als Text aus und kann nicht mit --format, \
+         --users, --split, --speakable, credentials, --store oder \
+         insert-pass verwendet werden",
+    ),
+    (
+        "--users gives the number of passwords, which cannot also be given",
+        "--users bestimmt die Anzahl der Passwörter, die nicht zusätzlich \
+         angegeben werden kann",
+    ),
+    ("--hash needs --users", "--hash benötigt --users"),
+    (
+        "--label needs --audit-log, or --format csv or json",
+        "--label benötigt --audit-log, oder --format csv oder json",
+    ),
+    ("invalid label: \"{}\"", "ungültige Bezeichnung: \"{}\""),
+    (
+        "message of {} bytes is too long",
+        "Nachricht von {} Bytes ist zu lang",
+    ),
+    (
+        "the response would be too long",
+        "die Antwort wäre zu lang",
+    ),
+    ("unknown op: \"{}\"", "unbekannte op: \"{}\""),
+    (
+        "generate does not take a password, words or separator",
+        "generate nimmt kein password, words oder separator",
+    ),
+    ("passphrase needs a word list", "passphrase benötigt eine Wortliste"),
+    (
+        "passphrase takes only words, separator and count",
+        "passphrase nimmt nur words, separator und count",
+    ),
+    ("words must be from 1 to {}", "words muss zwischen 1 und {} liegen"),
+    ("count must be from 1 to {}", "count muss zwischen 1 und {} liegen"),
+    (
+        "daemon needs --socket when XDG_RUNTIME_DIR is not set",
+        "daemon benötigt --socket, wenn XDG_RUNTIME_DIR nicht gesetzt ist",
+    ),
+    (
+        "this system does not support daemon",
+        "dieses System unterstützt daemon nicht",
+    ),
+    (
+        "\"{}\" exists and is not a socket",
+        "\"{}\" existiert und ist kein Socket",
+    ),
+    (
+        "a daemon is already listening on \"{}\"",
+        "auf \"{}\" lauscht bereits ein Daemon",
+    ),
+    (
+        "could not remove \"{}\"",
+        "\"{}\" konnte nicht entfernt werden",
+    ),
+    (
+        "could not listen on \"{}\"",
+        "auf \"{}\" konnte nicht gelauscht werden",
+    ),
+    ("listening on \"{}\"", "lausche auf \"{}\""),
+    ("check needs a password", "check benötigt ein password"),
+    (
+        "at most {} passwords can be generated at once",
+        "es können höchstens {} Passwörter auf einmal erzeugt werden",
+    ),
+    (
+        "--format ansible-vault and --vault-password-file need each other",
+        "--format ansible-vault und --vault-password-file benötigen \
+         einander",
+    ),
+    (
+        "--format ansible-vault encrypts one password and does not take \
+         --batch, --split, --encrypt-to, --users, --store, credentials or \
+         insert-pass",
+        "--format ansible-vault verschlüsselt ein Passwort und nimmt keine \
+         Optionen --batch, --split, --encrypt-to, --users, --store, \
+         credentials oder insert-pass",
+    ),
+    (
+        "the vault password in \"{}\" is empty",
+        "das Vault-Passwort in \"{}\" ist leer",
+    ),
+    (
+        "--format chpasswd needs --users",
+        "--format chpasswd benötigt --users",
+    ),
+    ("invalid username: \"{}\"", "ungültiger Benutzername: \"{}\""),
+    ("no usernames in \"{}\"", "keine Benutzernamen in \"{}\""),
+    (
+        "-c and -M cannot be used together",
+        "-c und -M können nicht zusammen verwendet werden",
+    ),
+    (
+        "--preset cannot be used with -c or -M",
+        "--preset kann nicht mit -c oder -M verwendet werden",
+    ),
+    ("character set is empty", "der Zeichenvorrat ist leer"),
+    (
+        "pronounceable generation is not supported",
+        "aussprechbare Passwörter werden nicht unterstützt",
+    ),
+    ("unknown command: \"{}\"", "unbekannter Befehl: \"{}\""),
+    ("invalid number: \"{}\"", "ungültige Zahl: \"{}\""),
+    ("invalid mode character: \"{}\"", "ungültiges Modus-Zeichen: \"{}\""),
+    ("unknown transform: \"{}\"", "unbekannte Umformung: \"{}\""),
+    (
+        "post-script \"{}\" failed: {}",
+        "post-script \"{}\" ist fehlgeschlagen: {}",
+    ),
+    (
+        "returned neither a password nor true or false",
+        "hat weder ein Passwort noch true oder false zurückgegeben",
+    ),
+    (
+        "returned an empty password or one with a line break",
+        "hat ein leeres Passwort oder eines mit Zeilenumbruch zurückgegeben",
+    ),
+    ("rejected {} candidates in a row", "hat {} Kandidaten in Folge verworfen"),
+    (
+        "the post-script changed passwords, so nothing is known of their \
+         entropy",
+        "das post-script hat Passwörter geändert, daher ist über ihre \
+         Entropie nichts bekannt",
+    ),
+    ("invalid split: \"{}\"", "ungültige Aufteilung: \"{}\""),
+    ("invalid size: \"{}\"", "ungültige Größe: \"{}\""),
+    ("unknown generator: \"{}\"", "unbekannter Generator: \"{}\""),
+    (
+        "generator already defined: \"{}\"",
+        "Generator bereits definiert: \"{}\"",
+    ),
+    ("imported generator \"{}\"", "Generator \"{}\" importiert"),
+    (
+        "could not find a configuration directory; use --generators",
+        "kein Konfigurationsverzeichnis gefunden; --generators verwenden",
+    ),
+    ("invalid KeePass pattern: \"{}\"", "ungültiges KeePass-Muster: \"{}\""),
+    (
+        "KeePass pattern has no passgen equivalent: \"{}\"",
+        "KeePass-Muster ohne Entsprechung in passgen: \"{}\"",
+    ),
+    ("invalid pwgen arguments: \"{}\"", "ungültige pwgen-Argumente: \"{}\""),
+    (
+        "invalid 1Password recipe: \"{}\"",
+        "ungültiges 1Password-Rezept: \"{}\"",
+    ),
+    (
+        "pwgen's pronounceable passwords are imported as random ones, as with \
+         -s",
+        "die aussprechbaren Passwörter von pwgen werden wie mit -s als \
+         zufällige importiert",
+    ),
+    (
+        "number of attempts must not be zero",
+        "die Anzahl der Versuche darf nicht null sein",
+    ),
+    ("timeout must be a positive number", "die Zeitgrenze muss positiv sein"),
+    (
+        "gave up after {} attempts: acceptance rate ~{}, relax the \
+         constraints",
+        "nach {} Versuchen aufgegeben: Annahmerate ~{}, die Vorgaben \
+         lockern",
+    ),
+    (
+        "the job needs about {} bytes of memory, more than the {} allowed",
+        "der Auftrag benötigt etwa {} Bytes Speicher, mehr als die \
+         erlaubten {}",
+    ),
+    (
+        "password length given both as an option and an argument",
+        "Passwortlänge sowohl als Option als auch als Argument angegeben",
+    ),
+    (
+        "number of passwords given both as an option and an argument",
+        "Anzahl der Passwörter sowohl als Option als auch als Argument \
+         angegeben",
+    ),
+    ("unterminated quote", "nicht abgeschlossenes Anführungszeichen"),
+    (
+        "password length must not be zero",
+        "die Passwortlänge darf nicht null sein",
+    ),
+    (
+        "number of passwords must not be zero",
+        "die Anzahl der Passwörter darf nicht null sein",
+    ),
+    (
+        "health test min-entropy must be in (0, 8]",
+        "die Min-Entropie der Gesundheitstests muss in (0, 8] liegen",
+    ),
+    (
+        "health test alpha must be in [-64, 0)",
+        "das Alpha der Gesundheitstests muss in [-64, 0) liegen",
+    ),
+    (
+        "health test window must be at least 2",
+        "das Fenster der Gesundheitstests muss mindestens 2 sein",
+    ),
+    (
+        "token needs exactly one encoding",
+        "token braucht genau eine Kodierung",
+    ),
+    ("{} has no check symbol", "{} hat kein Prüfzeichen"),
+    (
+        "token length must not be zero",
+        "die Tokenlänge darf nicht null sein",
+    ),
+    (
+        "{} does not take character set, length, --prompt or --batch \
+         options",
+        "{} nimmt keine Optionen für Zeichenvorrat, Länge, --prompt oder \
+         --batch",
+    ),
+    ("invalid keys: {}", "ungültige Schlüssel: {}"),
+    ("invalid configuration files: {}", "ungültige Konfigurationsdateien: {}"),
+    (
+        "license key has no room for random characters",
+        "der Lizenzschlüssel lässt keinen Platz für Zufallszeichen",
+    ),
+    ("wifi needs --ssid", "wifi braucht --ssid"),
+    (
+        "WPA passphrase must be 8 to 63 characters long",
+        "die WPA-Passphrase muss 8 bis 63 Zeichen lang sein",
+    ),
+    (
+        "WPA passphrase must be 8 to 63 printable ASCII characters",
+        "die WPA-Passphrase muss aus 8 bis 63 druckbaren ASCII-Zeichen \
+         bestehen",
+    ),
+    (
+        "SSID must be 1 to 32 bytes long",
+        "die SSID muss 1 bis 32 Byte lang sein",
+    ),
+    (
+        "vendor prefix is a multicast prefix",
+        "das Herstellerpräfix ist ein Multicast-Präfix",
+    ),
+    (
+        "vendor prefix is not locally administered",
+        "das Herstellerpräfix ist nicht lokal verwaltet",
+    ),
+    (
+        "--universally-administered cannot be used with \
+         --locally-administered or --vendor-prefix",
+        "--universally-administered kann nicht mit --locally-administered \
+         oder --vendor-prefix verwendet werden",
+    ),
+    ("self-test failed", "Selbsttest fehlgeschlagen"),
+    (
+        "--distribution needs a charset without required classes or \
+         characters forbidden at the ends, which skew it",
+        "--distribution benötigt einen Zeichenvorrat ohne verlangte Klassen \
+         oder an den Enden verbotene Zeichen, die ihn verzerren",
+    ),
+    (
+        "--distribution needs at least two characters",
+        "--distribution benötigt mindestens zwei Zeichen",
+    ),
+    ("policy cannot be satisfied: {}", "Richtlinie nicht erfüllbar: {}"),
+    (
+        "password entropy of {} bits is below the required {} bits",
+        "die Passwortentropie von {} Bit liegt unter den geforderten {} Bit",
+    ),
+    (
+        "random number generator failed: {}",
+        "der Zufallszahlengenerator ist ausgefallen: {}",
+    ),
+    (
+        "random number generator health test: {}",
+        "Zustandstest des Zufallszahlengenerators: {}",
+    ),
+    ("hyphens must be escaped", "Bindestriche müssen maskiert werden"),
+    ("empty charset specification", "leerer Zeichenvorrat"),
+    (
+        "found untypeable or non-ASCII character",
+        "nicht tippbares oder Nicht-ASCII-Zeichen gefunden",
+    ),
+    (
+        "unterminated escape sequence",
+        "nicht abgeschlossene Escape-Sequenz",
+    ),
+    ("unterminated character range", "nicht abgeschlossener Zeichenbereich"),
+    (
+        "minimum length exceeds maximum length",
+        "die Mindestlänge übersteigt die Höchstlänge",
+    ),
+    (
+        "composition parts must be class:share",
+        "Teile einer Zusammensetzung müssen klasse:anteil lauten",
+    ),
+    (
+        "unknown class in composition",
+        "unbekannte Klasse in der Zusammensetzung",
+    ),
+    (
+        "composition shares must be whole percentages above 0",
+        "Anteile einer Zusammensetzung müssen ganze Prozentzahlen über 0 \
+         sein",
+    ),
+    (
+        "class repeated in composition",
+        "Klasse in der Zusammensetzung wiederholt",
+    ),
+    (
+        "composition shares must add up to 100%",
+        "Anteile einer Zusammensetzung müssen zusammen 100% ergeben",
+    ),
+    (
+        "--compose cannot be used with -c, -M, --preset, -E or \
+         --confusables",
+        "--compose kann nicht mit -c, -M, --preset, -E oder --confusables \
+         verwendet werden",
+    ),
+    ("invalid field: \"{}\"", "ungültiges Feld: \"{}\""),
+    ("field repeated: \"{}\"", "Feld wiederholt: \"{}\""),
+    (
+        "field {} takes either a generator or password options, not both",
+        "Feld {} nimmt entweder einen Generator oder Passwortoptionen, nicht \
+         beides",
+    ),
+    (
+        "--fields cannot be used with --compose, credentials or other \
+         commands",
+        "--fields kann nicht mit --compose, credentials oder anderen Befehlen \
+         verwendet werden",
+    ),
+    (
+        "unknown confusables profile: \"{}\"",
+        "unbekanntes Verwechslungsprofil: \"{}\"",
+    ),
+    (
+        "password length is less than the number of required classes",
+        "die Passwortlänge ist kleiner als die Anzahl der geforderten Klassen",
+    ),
+    (
+        "maximum length exceeds the policy's maximum length",
+        "die Höchstlänge übersteigt die Höchstlänge der Richtlinie",
+    ),
+    (
+        "every character is forbidden at the ends",
+        "jedes Zeichen ist am Anfang und Ende verboten",
+    ),
+    (
+        "password length leaves too little room between the ends",
+        "die Passwortlänge lässt zwischen Anfang und Ende zu wenig Platz",
+    ),
+    (
+        "more passwords requested than can be distinct",
+        "mehr Passwörter angefordert, als verschieden sein können",
+    ),
+    (
+        "code has no room for random digits",
+        "der Code hat keinen Platz für zufällige Ziffern",
+    ),
+    ("no shares", "keine Anteile"),
+    (
+        "shares have different thresholds",
+        "die Anteile haben unterschiedliche Schwellenwerte",
+    ),
+    (
+        "shares have different lengths",
+        "die Anteile haben unterschiedliche Längen",
+    ),
+    (
+        "invalid escape sequence: \"\\{}\"",
+        "ungültige Escape-Sequenz: \"\\{}\"",
+    ),
+    (
+        "derivation version {} is not supported",
+        "die Ableitungsversion {} wird nicht unterstützt",
+    ),
+    (
+        "character set has no {} characters",
+        "der Zeichenvorrat enthält keine Zeichen der Klasse {}",
+    ),
+    ("invalid prefix: \"{}\"", "ungültiges Präfix: \"{}\""),
+    (
+        "a generator named \"{}\" is already registered",
+        "ein Generator namens \"{}\" ist bereits registriert",
+    ),
+    ("invalid share: \"{}\"", "ungültiger Anteil: \"{}\""),
+    ("share {} is repeated", "Anteil {} kommt mehrfach vor"),
+    (
+        "{} shares are needed but only {} were given",
+        "{} Anteile werden benötigt, aber nur {} wurden angegeben",
+    ),
+    (
+        "share {} does not agree with the others",
+        "Anteil {} passt nicht zu den übrigen",
+    ),
+    (
+        "no {} username fits in {} characters",
+        "kein Benutzername im Stil {} passt in {} Zeichen",
+    ),
+    ("invalid YubiKey slot: {}", "ungültiger YubiKey-Steckplatz: {}"),
+    (
+        "could not run ykchalresp: {}",
+        "ykchalresp konnte nicht ausgeführt werden: {}",
+    ),
+    ("ykchalresp exited with {}", "ykchalresp endete mit {}"),
+    (
+        "repetition count test failed: {} consecutive samples of {}",
+        "Wiederholungszähltest fehlgeschlagen: {} aufeinanderfolgende \
+         Stichproben von {}",
+    ),
+    (
+        "adaptive proportion test failed: {} samples of {} in a window of {}",
+        "Adaptive-Proportion-Test fehlgeschlagen: {} Stichproben von {} in \
+         einem Fenster von {}",
+    ),
+    (
+        "generated {} outside the charset",
+        "{} außerhalb des Zeichenvorrats erzeugt",
+    ),
+    (
+        "charset specification \"{}\" did not round trip",
+        "die Zeichenvorratsangabe \"{}\" übersteht Formatieren und erneutes \
+         Einlesen nicht",
+    ),
+    (
+        "expected \"{}\" from charset \"{}\" but generated \"{}\"",
+        "\"{}\" aus dem Zeichenvorrat \"{}\" erwartet, aber \"{}\" erzeugt",
+    ),
+    ("length: {}", "Länge: {}"),
+    ("count: {}", "Anzahl: {}"),
+    ("weak PINs rejected: {}", "verworfene schwache PINs: {}"),
+    ("entropy per PIN: {} bits", "Entropie je PIN: {} Bit"),
+    ("charset size: {}", "Größe des Zeichenvorrats: {}"),
+    ("columns: {}", "Spalten: {}"),
+    ("derivation version: {}", "Ableitungsversion: {}"),
+    ("entropy per cell: {} bits", "Entropie je Zelle: {} Bit"),
+    ("rng: {}", "Zufallszahlengenerator: {}"),
+    (
+        "rng failure policy: {}",
+        "bei Ausfall des Zufallszahlengenerators: {}",
+    ),
+    (
+        "health tests: repetition cutoff {}, adaptive proportion cutoff {} \
+         of {}",
+        "Gesundheitstests: Wiederholungsgrenze {}, Grenze des \
+         Adaptive-Proportion-Tests {} von {}",
+    ),
+    (
+        "extra entropy: {} bytes mixed in",
+        "zusätzliche Entropie: {} Byte eingemischt",
+    ),
+    ("entropy token: {}", "Entropie-Token: {}"),
+    (
+        "breached passwords redrawn: {}",
+        "neu gezogene Passwörter aus Datenlecks: {}",
+    ),
+    ("time: {} ms", "Zeit: {} ms"),
+    ("wall-clock time: {} s", "verstrichene Zeit: {} s"),
+    ("entropy consumed: {} bytes", "verbrauchte Entropie: {} Byte"),
+    ("rate: {} passwords/s", "Rate: {} Passwörter/s"),
+    ("peak memory: {} KiB", "Spitzenspeicher: {} KiB"),
+    ("peak memory: unavailable", "Spitzenspeicher: nicht verfügbar"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_arguments_of_formatted_messages() {
+        assert_eq!(
+            match_msgid("share {} is repeated", "share 3 is repeated"),
+            Some(vec!["3"]),
+        );
+        assert_eq!(
+            match_msgid(
+                "{} shares are needed but only {} were given",
+                "3 shares are needed but only 2 were given",
+            ),
+            Some(vec!["3", "2"]),
+        );
+        assert_eq!(
+            match_msgid("invalid share: \"{}\"", "invalid share: \"a: b\""),
+            Some(vec!["a: b"]),
+        );
+        assert_eq!(match_msgid("no shares", "no shares"), Some(vec![]));
+        assert_eq!(
+            match_msgid("share {} is repeated", "share 3 is repeated twice"),
+            None,
+        );
+        assert_eq!(match_msgid("no shares", "no shares given"), None);
+    }
+}
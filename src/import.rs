@@ -0,0 +1,495 @@
+use anyhow::{anyhow, Result};
+use passgen::{
+    CharClass,
+    Charset,
+    CharsetGenerator,
+    PatternGenerator,
+    Policy,
+};
+use serde::Deserialize;
+use std::num::NonZeroUsize;
+
+use crate::generators::Definition;
+use crate::i18n::{tr, trf};
+
+// Other tools' recipes are converted into generator definitions that make
+// the same passwords. A KeePass pattern becomes a charset generator if each
+// of its characters is drawn from the same set, or otherwise a pattern if
+// each is drawn from one of the classes passgen patterns have. pwgen's
+// arguments and 1Password's recipes, which choose character sets and a
+// length, become charset generators requiring a character of each set the
+// tool guarantees one of.
+
+// No recipe needs more.
+const MAX_PATTERN_LEN: usize = 1024;
+// As 1Password makes them by default.
+const DEFAULT_1PASSWORD_LEN: usize = 32;
+const DEFAULT_PWGEN_LEN: usize = 8;
+// The characters pwgen leaves out with -B and -v.
+const PWGEN_AMBIGUOUS: &str = "B8G6I1l0OQDS5Z2";
+const PWGEN_VOWELS: &str = "01aeiouyAEIOUY";
+
+#[derive(Clone, PartialEq)]
+enum Element {
+    Literal(u8),
+    Set(Charset),
+}
+
+/// Converts a pattern of KeePass's password generator.
+pub fn keepass_pattern(pattern: &str) -> Result<Definition> {
+    let err = || anyhow!(trf("invalid KeePass pattern: \"{}\"", &[&pattern]));
+    let unsupported = || {
+        anyhow!(trf(
+            "KeePass pattern has no passgen equivalent: \"{}\"",
+            &[&pattern],
+        ))
+    };
+    let mut elements = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => {
+                let ch = chars.next().ok_or_else(err)?;
+                let byte = literal(ch).ok_or_else(err)?;
+                elements.push(Element::Literal(byte));
+            },
+            '[' => {
+                let mut included = Vec::new();
+                let mut excluded = Vec::new();
+                let mut excluding = false;
+                loop {
+                    let ch = chars.next().ok_or_else(err)?;
+                    let set =
+                        if excluding { &mut excluded } else { &mut included };
+                    match ch {
+                        ']' => break,
+                        '^' => excluding = true,
+                        '\\' => {
+                            let ch = chars.next().ok_or_else(err)?;
+                            set.push(literal(ch).ok_or_else(err)?);
+                        },
+                        ch => match placeholder(ch) {
+                            Some(charset) => set.extend_from_slice(&charset),
+                            None => set.push(literal(ch).ok_or_else(err)?),
+                        },
+                    }
+                }
+                included.retain(|byte| !excluded.contains(byte));
+                let set = Charset::new(included).map_err(|_| err())?;
+                elements.push(Element::Set(set));
+            },
+            '{' => {
+                let count =
+                    chars.by_ref()
+                        .take_while(|&ch| ch != '}')
+                        .collect::<String>();
+                let count = count.parse::<usize>().map_err(|_| err())?;
+                let element = elements.pop().ok_or_else(err)?;
+                if elements.len() + count > MAX_PATTERN_LEN {
+                    return Err(err());
+                }
+                elements.extend(std::iter::repeat_n(element, count));
+            },
+            ch => match placeholder(ch) {
+                Some(charset) => elements.push(Element::Set(charset)),
+                None => {
+                    let byte = literal(ch).ok_or_else(err)?;
+                    elements.push(Element::Literal(byte));
+                },
+            },
+        }
+        if elements.len() > MAX_PATTERN_LEN {
+            return Err(err());
+        }
+    }
+    let len = NonZeroUsize::new(elements.len()).ok_or_else(err)?;
+    if let Element::Set(charset) = &elements[0] {
+        if elements.iter().all(|element| element == &elements[0]) {
+            let policy = Policy::default();
+            let generator =
+                CharsetGenerator::new(charset.clone(), len, len, policy)?;
+            return Ok(Definition::Charset(generator));
+        }
+    }
+    let mut passgen_pattern = String::new();
+    for element in &elements {
+        match element {
+            Element::Literal(byte) => {
+                if b"ludsx\\".contains(byte) {
+                    passgen_pattern.push('\\');
+                }
+                passgen_pattern.push(*byte as char);
+            },
+            Element::Set(charset) => {
+                let class =
+                    [
+                        (CharClass::Lowercase, 'l'),
+                        (CharClass::Uppercase, 'u'),
+                        (CharClass::Digits, 'd'),
+                        (CharClass::Symbols, 's'),
+                    ]
+                    .into_iter()
+                    .find(|(class, _)| class.chars() == charset.as_bytes());
+                let (_, class) = class.ok_or_else(unsupported)?;
+                passgen_pattern.push(class);
+            },
+        }
+    }
+    Ok(Definition::Pattern(PatternGenerator::new(passgen_pattern)?))
+}
+
+// The characters of a KeePass placeholder.
+fn placeholder(ch: char) -> Option<Charset> {
+    let spec =
+        match ch {
+            'a' => "a-z0-9",
+            'A' => "a-zA-Z0-9",
+            'U' => "A-Z0-9",
+            'd' => "0-9",
+            'h' => "0-9a-f",
+            'H' => "0-9A-F",
+            'l' => "a-z",
+            'L' => "a-zA-Z",
+            'u' => "A-Z",
+            'p' => ",.;:",
+            'b' => "()[]{}<>",
+            's' => "!-/:-@[-`{-~",
+            'S' => "!-~",
+            'v' => "aeiou",
+            'V' => "aeiouAEIOU",
+            'Z' => "AEIOU",
+            'c' => "b-df-hj-np-tv-z",
+            'C' => "b-df-hj-np-tv-zB-DF-HJ-NP-TV-Z",
+            'z' => "B-DF-HJ-NP-TV-Z",
+            _ => return None,
+        };
+    Charset::parse(spec).ok()
+}
+
+fn literal(ch: char) -> Option<u8> {
+    u8::try_from(ch).ok().filter(|&byte| Charset::new([byte]).is_ok())
+}
+
+/// Converts pwgen's arguments, split at whitespace, returning whether they
+/// ask for pronounceable passwords, which are converted as random ones.
+pub fn pwgen_args(args: &str) -> Result<(Definition, bool)> {
+    let err = || anyhow!(trf("invalid pwgen arguments: \"{}\"", &[&args]));
+    let mut pwgen = Pwgen {
+        uppercase: true,
+        digits: true,
+        symbols: false,
+        secure: false,
+        removed: String::new(),
+    };
+    let mut positionals = Vec::new();
+    let mut words = args.split_whitespace().peekable();
+    words.next_if_eq(&"pwgen");
+    while let Some(word) = words.next() {
+        if let Some(long) = word.strip_prefix("--") {
+            let (name, value) =
+                match long.split_once('=') {
+                    Some((name, value)) => (name, Some(value)),
+                    None => (long, None),
+                };
+            let flag =
+                match name {
+                    "capitalize" => 'c',
+                    "no-capitalize" => 'A',
+                    "numerals" => 'n',
+                    "no-numerals" => '0',
+                    "symbols" => 'y',
+                    "secure" => 's',
+                    "ambiguous" => 'B',
+                    "no-vowels" => 'v',
+                    "remove-chars" => 'r',
+                    "num-passwords" => 'N',
+                    _ => return Err(err()),
+                };
+            let value =
+                match value {
+                    None if Pwgen::takes_value(flag) =>
+                        Some(words.next().ok_or_else(err)?),
+                    value => value,
+                };
+            pwgen.set(flag, value).ok_or_else(err)?;
+        } else if let Some(flags) = word.strip_prefix('-') {
+            for (idx, flag) in flags.char_indices() {
+                if Pwgen::takes_value(flag) {
+                    let value =
+                        match &flags[idx + 1..] {
+                            "" => words.next().ok_or_else(err)?,
+                            rest => rest,
+                        };
+                    pwgen.set(flag, Some(value)).ok_or_else(err)?;
+                    break;
+                }
+                pwgen.set(flag, None).ok_or_else(err)?;
+            }
+        } else {
+            positionals.push(word);
+        }
+    }
+    let len =
+        match positionals[..] {
+            [] => DEFAULT_PWGEN_LEN,
+            // The number of passwords is left to whoever runs the generator.
+            [len] | [len, _] => len.parse().map_err(|_| err())?,
+            _ => return Err(err()),
+        };
+    let mut classes = vec![CharClass::Lowercase];
+    let mut required = Vec::new();
+    for (wanted, class) in [
+        (pwgen.uppercase, CharClass::Uppercase),
+        (pwgen.digits, CharClass::Digits),
+        (pwgen.symbols, CharClass::Symbols),
+    ] {
+        if wanted {
+            classes.push(class);
+            required.push(class);
+        }
+    }
+    let definition =
+        charset_definition(&classes, &pwgen.removed, len, required)?;
+    Ok((definition, !pwgen.secure))
+}
+
+// The settings of pwgen's options.
+struct Pwgen {
+    uppercase: bool,
+    digits: bool,
+    symbols: bool,
+    secure: bool,
+    removed: String,
+}
+
+impl Pwgen {
+    fn takes_value(flag: char) -> bool {
+        flag == 'r' || flag == 'N'
+    }
+
+    // Sets the option of the short flag, returning None if there is none.
+    fn set(&mut self, flag: char, value: Option<&str>) -> Option<()> {
+        match (flag, value) {
+            ('c', None) => self.uppercase = true,
+            ('A', None) => self.uppercase = false,
+            ('n', None) => self.digits = true,
+            ('0', None) => self.digits = false,
+            ('y', None) => self.symbols = true,
+            ('s', None) => self.secure = true,
+            ('B', None) => self.removed.push_str(PWGEN_AMBIGUOUS),
+            ('v', None) => self.removed.push_str(PWGEN_VOWELS),
+            ('r', Some(chars)) => self.removed.push_str(chars),
+            // Layout and counts, which generators do not have.
+            ('1' | 'C', None) | ('N', Some(_)) => {},
+            _ => return None,
+        }
+        Some(())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct Recipe {
+    #[serde(default)]
+    length: Option<usize>,
+    #[serde(default)]
+    character_sets: Option<Vec<String>>,
+    #[serde(default)]
+    exclude_characters: String,
+}
+
+/// Converts a 1Password recipe, either as the CLI takes it, as in
+/// letters,digits,32, or as the JSON Connect takes.
+pub fn one_password_recipe(recipe: &str) -> Result<Definition> {
+    let err = || anyhow!(trf("invalid 1Password recipe: \"{}\"", &[&recipe]));
+    let Recipe { length, character_sets, exclude_characters } =
+        if recipe.trim_start().starts_with('{') {
+            serde_json::from_str(recipe)?
+        } else {
+            let mut length = None;
+            let mut character_sets = Vec::new();
+            for part in recipe.split(',').map(str::trim) {
+                if part.starts_with(|ch: char| ch.is_ascii_digit()) {
+                    length = Some(part.parse().map_err(|_| err())?);
+                } else {
+                    character_sets.push(part.to_string());
+                }
+            }
+            Recipe {
+                length,
+                character_sets: Some(character_sets),
+                exclude_characters: String::new(),
+            }
+        };
+    let mut classes = Vec::new();
+    let mut required = Vec::new();
+    let sets =
+        character_sets.unwrap_or_else(|| {
+            ["letters", "digits", "symbols"].map(String::from).to_vec()
+        });
+    for set in sets {
+        match set.to_ascii_lowercase().as_str() {
+            "letters" => {
+                classes.extend([CharClass::Lowercase, CharClass::Uppercase]);
+            },
+            "digits" => {
+                classes.push(CharClass::Digits);
+                required.push(CharClass::Digits);
+            },
+            "symbols" => {
+                classes.push(CharClass::Symbols);
+                required.push(CharClass::Symbols);
+            },
+            _ => return Err(err()),
+        }
+    }
+    let len = length.unwrap_or(DEFAULT_1PASSWORD_LEN);
+    charset_definition(&classes, &exclude_characters, len, required)
+}
+
+fn charset_definition(
+    classes: &[CharClass],
+    excluded: &str,
+    len: usize,
+    required: Vec<CharClass>,
+) -> Result<Definition> {
+    let chars =
+        classes.iter().flat_map(|class| class.chars()).collect::<Vec<_>>();
+    let charset = Charset::new(chars)?.without(excluded.as_bytes())?;
+    // A class left out entirely cannot be required.
+    let required =
+        required.into_iter()
+            .filter(|class| charset.iter().any(|&byte| class.contains(byte)))
+            .collect();
+    let len =
+        NonZeroUsize::new(len)
+            .ok_or_else(|| anyhow!(tr("password length must not be zero")))?;
+    let policy = Policy { required, ..Policy::default() };
+    let generator = CharsetGenerator::new(charset, len, len, policy)?;
+    Ok(Definition::Charset(generator))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The characters, length and required classes of a charset generator.
+    fn charset(definition: Definition) -> (Vec<u8>, usize, Vec<CharClass>) {
+        let Definition::Charset(generator) = definition else {
+            panic!("not a charset generator");
+        };
+        assert_eq!(generator.min_length(), generator.max_length());
+        (
+            generator.charset().as_bytes().to_vec(),
+            generator.min_length().get(),
+            generator.policy().required.clone(),
+        )
+    }
+
+    fn pattern(definition: Definition) -> String {
+        let Definition::Pattern(generator) = definition else {
+            panic!("not a pattern generator");
+        };
+        generator.pattern().to_string()
+    }
+
+    fn set(spec: &str) -> Vec<u8> {
+        Charset::parse(spec).unwrap().as_bytes().to_vec()
+    }
+
+    #[test]
+    fn keepass_patterns_of_one_set_become_charsets() {
+        let (chars, len, required) =
+            charset(keepass_pattern("a{12}").unwrap());
+        assert_eq!((chars, len), (set("a-z0-9"), 12));
+        assert!(required.is_empty());
+        let (chars, len, _) = charset(keepass_pattern("hhhh").unwrap());
+        assert_eq!((chars, len), (set("0-9a-f"), 4));
+        let (chars, len, _) =
+            charset(keepass_pattern("[dH^\\A]{3}").unwrap());
+        assert_eq!((chars, len), (set("0-9B-F"), 3));
+    }
+
+    #[test]
+    fn keepass_patterns_of_classes_become_patterns() {
+        let definition = keepass_pattern("uL{0}llld{2}\\-s").unwrap();
+        assert_eq!(pattern(definition), "ullldd-s");
+        // Literals that are pattern classes are escaped.
+        assert_eq!(pattern(keepass_pattern("\\ud").unwrap()), "\\ud");
+        assert_eq!(pattern(keepass_pattern("x[d]").unwrap()), "\\xd");
+    }
+
+    #[test]
+    fn invalid_keepass_patterns_are_rejected() {
+        for pattern in [
+            "", "\\", "[ab", "{3}", "d{x}", "d{2000}", "[^d]", "dé", "a{0}",
+        ] {
+            assert!(keepass_pattern(pattern).is_err(), "{}", pattern);
+        }
+        // Sets with no passgen class.
+        assert!(keepass_pattern("dh").is_err());
+        assert!(keepass_pattern("d[ab]").is_err());
+    }
+
+    #[test]
+    fn pwgen_arguments_become_charsets() {
+        let (definition, pronounceable) = pwgen_args("pwgen").unwrap();
+        assert!(pronounceable);
+        let (chars, len, required) = charset(definition);
+        assert_eq!((chars, len), (set("a-zA-Z0-9"), DEFAULT_PWGEN_LEN));
+        let expected = [CharClass::Uppercase, CharClass::Digits];
+        assert_eq!(required, expected);
+        let (definition, pronounceable) =
+            pwgen_args("-sy -N 5 --remove-chars=abc 20 5").unwrap();
+        assert!(!pronounceable);
+        let (chars, len, required) = charset(definition);
+        let mut expected_chars = set("a-zA-Z0-9!-/:-@[-`{-~");
+        expected_chars.retain(|byte| !b"abc".contains(byte));
+        assert_eq!((chars, len), (expected_chars, 20));
+        assert_eq!(required.len(), 3);
+        let (definition, _) = pwgen_args("-A0 -Bv -r xyz 12").unwrap();
+        let (chars, _, required) = charset(definition);
+        let mut expected_chars = set("a-z");
+        let removed = format!("{}{}xyz", PWGEN_AMBIGUOUS, PWGEN_VOWELS);
+        expected_chars.retain(|&byte| !removed.contains(char::from(byte)));
+        assert_eq!(chars, expected_chars);
+        assert!(required.is_empty());
+    }
+
+    #[test]
+    fn invalid_pwgen_arguments_are_rejected() {
+        let everything = "-A0 -r abcdefghijklmnopqrstuvwxyz";
+        for args in ["-q", "--colour", "-r", "x", "8 2 1", "0", everything] {
+            assert!(pwgen_args(args).is_err(), "{}", args);
+        }
+    }
+
+    #[test]
+    fn one_password_recipes_become_charsets() {
+        let (chars, len, required) =
+            charset(one_password_recipe("letters,digits,20").unwrap());
+        assert_eq!((chars, len), (set("a-zA-Z0-9"), 20));
+        assert_eq!(required, [CharClass::Digits]);
+        let (chars, len, required) =
+            charset(one_password_recipe("{}").unwrap());
+        assert_eq!(chars, set("a-zA-Z0-9!-/:-@[-`{-~"));
+        assert_eq!(len, DEFAULT_1PASSWORD_LEN);
+        assert_eq!(required, [CharClass::Digits, CharClass::Symbols]);
+        let json =
+            r#"{"length": 8, "characterSets": ["DIGITS", "symbols"],
+                "excludeCharacters": "0123456789"}"#;
+        let (chars, len, required) =
+            charset(one_password_recipe(json).unwrap());
+        assert_eq!((chars, len), (set("!-/:-@[-`{-~"), 8));
+        assert_eq!(required, [CharClass::Symbols]);
+    }
+
+    #[test]
+    fn invalid_one_password_recipes_are_rejected() {
+        for recipe in
+            ["letters,emoji", "letters,0", "letters,2x", "{\"colour\": 1}"]
+        {
+            assert!(one_password_recipe(recipe).is_err(), "{}", recipe);
+        }
+    }
+}
@@ -0,0 +1,936 @@
+use aes::cipher::{BlockDecrypt, BlockEncrypt, KeyInit};
+use aes::{Aes256, Block};
+use anyhow::{anyhow, Context, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use hmac::{Hmac, Mac};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use rand_chacha::ChaCha20Rng;
+use rand_core::SeedableRng;
+use sha2::{Digest, Sha256, Sha512};
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::base64;
+use crate::i18n::{tr, trf};
+
+// A KeePass 4 database is a header, its SHA-256 hash and HMAC, and then the
+// encrypted payload in blocks that each carry an HMAC. The payload is an
+// inner header and an XML document, in which the values marked as
+// protected are further encrypted with a stream cipher keyed from the inner
+// header. Only a password is supported as the key, not key files.
+//
+// Databases are only ever appended to, so the XML document is kept as text
+// rather than parsed. While it is in memory, protected values hold the
+// Base64 of their plaintext, and they are encrypted again when it is saved.
+
+const SIGNATURE: [u8; 8] = [0x03, 0xd9, 0xa2, 0x9a, 0x67, 0xfb, 0x4b, 0xb5];
+const VERSION_4_0: u32 = 0x0004_0000;
+
+const FIELD_END: u8 = 0;
+const FIELD_CIPHER: u8 = 2;
+const FIELD_COMPRESSION: u8 = 3;
+const FIELD_MASTER_SEED: u8 = 4;
+const FIELD_IV: u8 = 7;
+const FIELD_KDF: u8 = 11;
+const HEADER_END_DATA: &[u8] = b"\r\n\r\n";
+
+const INNER_FIELD_END: u8 = 0;
+const INNER_FIELD_STREAM_ID: u8 = 1;
+const INNER_FIELD_STREAM_KEY: u8 = 2;
+const INNER_STREAM_CHACHA20: u32 = 3;
+
+const CIPHER_AES256: [u8; 16] = [
+    0x31, 0xc1, 0xf2, 0xe6, 0xbf, 0x71, 0x43, 0x50, 0xbe, 0x58, 0x05, 0x21,
+    0x6a, 0xfc, 0x5a, 0xff,
+];
+const CIPHER_CHACHA20: [u8; 16] = [
+    0xd6, 0x03, 0x8a, 0x2b, 0x8b, 0x6f, 0x4c, 0xb5, 0xa5, 0x24, 0x33, 0x9a,
+    0x31, 0xdb, 0xb5, 0x9a,
+];
+const KDF_AES: [u8; 16] = [
+    0xc9, 0xd9, 0xf3, 0x9a, 0x62, 0x8a, 0x44, 0x60, 0xbf, 0x74, 0x0d, 0x08,
+    0xc1, 0x8a, 0x4f, 0xea,
+];
+const KDF_ARGON2D: [u8; 16] = [
+    0xef, 0x63, 0x6d, 0xdf, 0x8c, 0x29, 0x44, 0x4b, 0x91, 0xf7, 0xa9, 0xa4,
+    0x03, 0xe3, 0x0a, 0x0c,
+];
+const KDF_ARGON2ID: [u8; 16] = [
+    0x9e, 0x29, 0x8b, 0x19, 0x56, 0xdb, 0x47, 0x73, 0xb2, 0x3d, 0xfc, 0x3e,
+    0xc6, 0xf0, 0xa1, 0xe6,
+];
+
+// The types of values in the variant dictionary holding KDF parameters.
+const VARIANT_END: u8 = 0x00;
+const VARIANT_U32: u8 = 0x04;
+const VARIANT_U64: u8 = 0x05;
+const VARIANT_BYTES: u8 = 0x42;
+const VARIANT_VERSION: u16 = 0x0100;
+
+// New databases use ChaCha20 and Argon2id with these parameters.
+const NEW_KDF_MEMORY: u64 = 64 * 1024 * 1024;
+const NEW_KDF_ITERATIONS: u64 = 10;
+const NEW_KDF_PARALLELISM: u32 = 2;
+
+const BLOCK_SIZE: usize = 1024 * 1024;
+// The header's HMAC is keyed as if it were a block with this index.
+const HEADER_INDEX: u64 = u64::MAX;
+// Seconds from 0001-01-01, where KeePass counts time from, to 1970-01-01.
+const UNIX_EPOCH_SECS: i64 = 62_135_596_800;
+pub struct Database {
+    path: PathBuf,
+    version: u32,
+    // The header fields other than the end, in order.
+    fields: Vec<(u8, Vec<u8>)>,
+    transformed_key: Zeroizing<[u8; 32]>,
+    // The inner header fields other than those keying the stream cipher,
+    // such as attachments.
+    inner_fields: Vec<(u8, Zeroizing<Vec<u8>>)>,
+    xml: Zeroizing<String>,
+}
+
+/// An entry to add to a database.
+pub struct NewEntry<'a> {
+    pub title: &'a str,
+    pub username: &'a str,
+    pub password: &'a str,
+    pub url: Option<&'a str>,
+    /// A TOTP secret in Base32.
+    pub totp_secret: Option<&'a str>,
+}
+
+impl Database {
+    /// Creates an empty database, which is only written when saved.
+    pub fn create(path: &Path, password: &str) -> Result<Self> {
+        let mut salt = [0; 32];
+        OsRng.fill_bytes(&mut salt);
+        let mut kdf = Vec::new();
+        kdf.extend_from_slice(&VARIANT_VERSION.to_le_bytes());
+        write_variant(&mut kdf, VARIANT_BYTES, "$UUID", &KDF_ARGON2ID);
+        write_variant(&mut kdf, VARIANT_BYTES, "S", &salt);
+        let parallelism = NEW_KDF_PARALLELISM.to_le_bytes();
+        write_variant(&mut kdf, VARIANT_U32, "P", &parallelism);
+        let memory = NEW_KDF_MEMORY.to_le_bytes();
+        write_variant(&mut kdf, VARIANT_U64, "M", &memory);
+        let iterations = NEW_KDF_ITERATIONS.to_le_bytes();
+        write_variant(&mut kdf, VARIANT_U64, "I", &iterations);
+        write_variant(&mut kdf, VARIANT_U32, "V", &0x13u32.to_le_bytes());
+        kdf.push(VARIANT_END);
+        let fields = vec![
+            (FIELD_CIPHER, CIPHER_CHACHA20.to_vec()),
+            (FIELD_COMPRESSION, 1u32.to_le_bytes().to_vec()),
+            // The seed and IV are replaced whenever the database is saved.
+            (FIELD_MASTER_SEED, vec![0; 32]),
+            (FIELD_IV, vec![0; 12]),
+            (FIELD_KDF, kdf),
+        ];
+        let transformed_key = transform_key(&fields, password)?;
+        let now = timestamp();
+        let xml = format!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\" standalone=\"yes\"?>\n\
+             <KeePassFile>\n\
+             \t<Meta>\n\
+             \t\t<Generator>passgen</Generator>\n\
+             \t\t<MemoryProtection>\n\
+             \t\t\t<ProtectTitle>False</ProtectTitle>\n\
+             \t\t\t<ProtectUserName>False</ProtectUserName>\n\
+             \t\t\t<ProtectPassword>True</ProtectPassword>\n\
+             \t\t\t<ProtectURL>False</ProtectURL>\n\
+             \t\t\t<ProtectNotes>False</ProtectNotes>\n\
+             \t\t</MemoryProtection>\n\
+             \t</Meta>\n\
+             \t<Root>\n\
+             \t\t<Group>\n\
+             \t\t\t<UUID>{}</UUID>\n\
+             \t\t\t<Name>Root</Name>\n\
+             \t\t\t{}\n\
+             \t\t\t<IsExpanded>True</IsExpanded>\n\
+             \t\t</Group>\n\
+             \t\t<DeletedObjects/>\n\
+             \t</Root>\n\
+             </KeePassFile>\n",
+            new_uuid(),
+            times(&now),
+        );
+        Ok(Database {
+            path: path.to_path_buf(),
+            version: VERSION_4_0,
+            fields,
+            transformed_key,
+            inner_fields: Vec::new(),
+            xml: Zeroizing::new(xml),
+        })
+    }
+
+    /// Decrypts a database.
+    pub fn open(path: &Path, password: &str) -> Result<Self> {
+        let context = || trf("could not read \"{}\"", &[&path.display()]);
+        let data = fs::read(path).with_context(context)?;
+        let not_kdbx4 = || {
+            let msg =
+                trf("\"{}\" is not a KeePass 4 database", &[&path.display()]);
+            anyhow!(msg)
+        };
+        if !data.starts_with(&SIGNATURE) {
+            return Err(not_kdbx4());
+        }
+        let mut reader = Reader { data: &data, pos: SIGNATURE.len() };
+        let version = reader.u32()?;
+        if version >> 16 != 4 {
+            return Err(not_kdbx4());
+        }
+        let mut fields = Vec::new();
+        loop {
+            let id = reader.u8()?;
+            let len = reader.u32()? as usize;
+            let value = reader.take(len)?;
+            if id == FIELD_END {
+                break;
+            }
+            fields.push((id, value.to_vec()));
+        }
+        let header = &data[..reader.pos];
+        let hash = reader.take(32)?;
+        if Sha256::digest(header)[..] != *hash {
+            return Err(malformed());
+        }
+        let transformed_key = transform_key(&fields, password)?;
+        let keys = PayloadKeys::new(&fields, &transformed_key)?;
+        let mac = reader.take(32)?;
+        if keys.mac(HEADER_INDEX, &[header]).verify_slice(mac).is_err() {
+            let msg = tr(
+                "the database password is wrong, or the database is corrupt",
+            );
+            return Err(anyhow!(msg));
+        }
+        let mut ciphertext = Vec::new();
+        for idx in 0u64.. {
+            let mac = reader.take(32)?;
+            let len_bytes = reader.take(4)?;
+            let len = u32::from_le_bytes(len_bytes.try_into().unwrap());
+            let block = reader.take(len as usize)?;
+            let idx_bytes = idx.to_le_bytes();
+            let block_mac = keys.mac(idx, &[&idx_bytes, len_bytes, block]);
+            if block_mac.verify_slice(mac).is_err() {
+                return Err(malformed());
+            }
+            if len == 0 {
+                break;
+            }
+            ciphertext.extend_from_slice(block);
+        }
+        let mut payload = decrypt(&fields, &keys.cipher, ciphertext)?;
+        match compression(&fields)? {
+            false => {},
+            true => {
+                let mut decompressed = Zeroizing::new(Vec::new());
+                GzDecoder::new(&payload[..])
+                    .read_to_end(&mut decompressed)
+                    .map_err(|_| malformed())?;
+                payload = decompressed;
+            },
+        }
+        let mut reader = Reader { data: &payload, pos: 0 };
+        let mut inner_fields = Vec::new();
+        let mut stream_key = None;
+        loop {
+            let id = reader.u8()?;
+            let len = reader.u32()? as usize;
+            let value = reader.take(len)?;
+            match id {
+                INNER_FIELD_END => break,
+                INNER_FIELD_STREAM_ID => {
+                    let stream_id =
+                        value.try_into().ok().map(u32::from_le_bytes);
+                    if stream_id != Some(INNER_STREAM_CHACHA20) {
+                        return Err(malformed());
+                    }
+                },
+                INNER_FIELD_STREAM_KEY => {
+                    stream_key = Some(Zeroizing::new(value.to_vec()));
+                },
+                _ => {
+                    inner_fields.push((id, Zeroizing::new(value.to_vec())));
+                },
+            }
+        }
+        let stream_key = stream_key.ok_or_else(malformed)?;
+        let xml =
+            std::str::from_utf8(&payload[reader.pos..])
+                .map_err(|_| malformed())?;
+        let xml = xor_protected(xml, &mut inner_stream(&stream_key))?;
+        Ok(Database {
+            path: path.to_path_buf(),
+            version,
+            fields,
+            transformed_key,
+            inner_fields,
+            xml,
+        })
+    }
+
+    /// Adds an entry to the root group.
+    pub fn add_entry(&mut self, entry: &NewEntry) -> Result<()> {
+        let pos = root_group_end(&self.xml).ok_or_else(malformed)?;
+        let now = timestamp();
+        let mut xml = Zeroizing::new(String::new());
+        xml.push_str("\t\t\t<Entry>\n");
+        xml.push_str(&format!("\t\t\t\t<UUID>{}</UUID>\n", new_uuid()));
+        xml.push_str("\t\t\t\t<IconID>0</IconID>\n");
+        xml.push_str(&format!("\t\t\t\t{}\n", times(&now)));
+        push_string(&mut xml, "Title", entry.title);
+        push_string(&mut xml, "UserName", entry.username);
+        push_protected(&mut xml, "Password", entry.password);
+        if let Some(url) = entry.url {
+            push_string(&mut xml, "URL", url);
+        }
+        if let Some(secret) = entry.totp_secret {
+            // The otpauth URI that KeePassXC and most mobile apps read.
+            let uri =
+                Zeroizing::new(format!(
+                    "otpauth://totp/{}:{}?secret={}&issuer={}",
+                    percent_encode(entry.title),
+                    percent_encode(entry.username),
+                    secret,
+                    percent_encode(entry.title),
+                ));
+            push_protected(&mut xml, "otp", &uri);
+        }
+        xml.push_str("\t\t\t</Entry>\n");
+        // Inserted after the indentation of the closing tag, which then
+        // follows the entry.
+        let line_start = self.xml[..pos].rfind('\n').map_or(0, |idx| idx + 1);
+        self.xml.insert_str(line_start, &xml);
+        Ok(())
+    }
+
+    /// Encrypts the database with a new master seed, IV and inner stream
+    /// key, replacing the file.
+    pub fn save(&mut self) -> Result<()> {
+        for (id, value) in &mut self.fields {
+            if *id == FIELD_MASTER_SEED || *id == FIELD_IV {
+                OsRng.fill_bytes(value);
+            }
+        }
+        let keys = PayloadKeys::new(&self.fields, &self.transformed_key)?;
+        let mut stream_key = Zeroizing::new([0; 64]);
+        OsRng.fill_bytes(&mut stream_key[..]);
+        let mut payload = Zeroizing::new(Vec::new());
+        let stream_id = INNER_STREAM_CHACHA20.to_le_bytes();
+        write_field(&mut payload, INNER_FIELD_STREAM_ID, &stream_id);
+        write_field(&mut payload, INNER_FIELD_STREAM_KEY, &stream_key[..]);
+        for (id, value) in &self.inner_fields {
+            write_field(&mut payload, *id, value);
+        }
+        write_field(&mut payload, INNER_FIELD_END, &[]);
+        let xml = xor_protected(&self.xml, &mut inner_stream(&stream_key[..]))?;
+        payload.extend_from_slice(xml.as_bytes());
+        if compression(&self.fields)? {
+            let mut encoder =
+                GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&payload)?;
+            payload = Zeroizing::new(encoder.finish()?);
+        }
+        let ciphertext = encrypt(&self.fields, &keys.cipher, payload)?;
+        let mut data = Vec::new();
+        data.extend_from_slice(&SIGNATURE);
+        data.extend_from_slice(&self.version.to_le_bytes());
+        for (id, value) in &self.fields {
+            write_field(&mut data, *id, value);
+        }
+        write_field(&mut data, FIELD_END, HEADER_END_DATA);
+        let hash = Sha256::digest(&data);
+        let mac = keys.mac(HEADER_INDEX, &[&data]).finalize().into_bytes();
+        data.extend_from_slice(&hash);
+        data.extend_from_slice(&mac);
+        let blocks = ciphertext.chunks(BLOCK_SIZE).chain([&[][..]]);
+        for (idx, block) in (0u64..).zip(blocks) {
+            let len_bytes = (block.len() as u32).to_le_bytes();
+            let idx_bytes = idx.to_le_bytes();
+            let mac = keys.mac(idx, &[&idx_bytes, &len_bytes, block]);
+            data.extend_from_slice(&mac.finalize().into_bytes());
+            data.extend_from_slice(&len_bytes);
+            data.extend_from_slice(block);
+        }
+        // Writing to a temporary file first leaves the old database intact
+        // if writing fails.
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+        let context =
+            || trf("could not write \"{}\"", &[&self.path.display()]);
+        let write = || -> std::io::Result<()> {
+            // The file is created readable only by its owner, rather than
+            // made so once the database is in it. A temporary file left by
+            // a failed save may have been created otherwise.
+            let _ = fs::remove_file(&tmp_path);
+            let mut options = OpenOptions::new();
+            options.write(true).create_new(true);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+            let mut file = options.open(&tmp_path)?;
+            file.write_all(&data)?;
+            file.sync_all()?;
+            if let Ok(metadata) = fs::metadata(&self.path) {
+                fs::set_permissions(&tmp_path, metadata.permissions())?;
+            }
+            fs::rename(&tmp_path, &self.path)
+        };
+        if let Err(err) = write() {
+            let _ = fs::remove_file(&tmp_path);
+            return Err(err).with_context(context);
+        }
+        Ok(())
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes =
+            self.pos.checked_add(len)
+                .and_then(|end| self.data.get(self.pos..end))
+                .ok_or_else(malformed)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+fn malformed() -> anyhow::Error {
+    anyhow!(tr("the database is malformed"))
+}
+
+fn field(fields: &[(u8, Vec<u8>)], id: u8) -> Result<&[u8]> {
+    fields.iter()
+        .find(|(field_id, _)| *field_id == id)
+        .map(|(_, value)| &value[..])
+        .ok_or_else(malformed)
+}
+
+fn compression(fields: &[(u8, Vec<u8>)]) -> Result<bool> {
+    match field(fields, FIELD_COMPRESSION)? {
+        [0, 0, 0, 0] => Ok(false),
+        [1, 0, 0, 0] => Ok(true),
+        _ => Err(malformed()),
+    }
+}
+
+fn write_field(out: &mut Vec<u8>, id: u8, value: &[u8]) {
+    out.push(id);
+    out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    out.extend_from_slice(value);
+}
+
+fn write_variant(out: &mut Vec<u8>, kind: u8, key: &str, value: &[u8]) {
+    out.push(kind);
+    out.extend_from_slice(&(key.len() as u32).to_le_bytes());
+    out.extend_from_slice(key.as_bytes());
+    out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    out.extend_from_slice(value);
+}
+
+// Reads the variant dictionary of KDF parameters into keys and values.
+fn read_variants(data: &[u8]) -> Result<Vec<(String, &[u8])>> {
+    let mut reader = Reader { data, pos: 0 };
+    let version = reader.take(2)?;
+    if version[1] != (VARIANT_VERSION >> 8) as u8 {
+        return Err(malformed());
+    }
+    let mut variants = Vec::new();
+    while reader.u8()? != VARIANT_END {
+        let key_len = reader.u32()? as usize;
+        let key = String::from_utf8_lossy(reader.take(key_len)?).into_owned();
+        let value_len = reader.u32()? as usize;
+        variants.push((key, reader.take(value_len)?));
+    }
+    Ok(variants)
+}
+
+// Derives the key the payload keys come from, stretching the password with
+// the database's KDF.
+fn transform_key(
+    fields: &[(u8, Vec<u8>)],
+    password: &str,
+) -> Result<Zeroizing<[u8; 32]>> {
+    let variants = read_variants(field(fields, FIELD_KDF)?)?;
+    let variant = |key: &str| {
+        variants.iter()
+            .find(|(variant_key, _)| variant_key == key)
+            .map(|(_, value)| *value)
+            .ok_or_else(malformed)
+    };
+    let u32_variant = |key| -> Result<u32> {
+        Ok(u32::from_le_bytes(variant(key)?.try_into()?))
+    };
+    let u64_variant = |key| -> Result<u64> {
+        Ok(u64::from_le_bytes(variant(key)?.try_into()?))
+    };
+    let unsupported =
+        || anyhow!(tr("the database uses an unsupported key derivation"));
+    // The composite key of a single password.
+    let password_hash = Zeroizing::new(sha256(&[password.as_bytes()]));
+    let composite = Zeroizing::new(sha256(&[&password_hash[..]]));
+    let mut transformed = Zeroizing::new([0; 32]);
+    let uuid = variant("$UUID")?;
+    if uuid == KDF_AES {
+        let seed = variant("S")?;
+        let rounds = u64_variant("R")?;
+        let aes = Aes256::new_from_slice(seed).map_err(|_| malformed())?;
+        let mut key = composite.clone();
+        for half in key.chunks_exact_mut(16) {
+            let half = Block::from_mut_slice(half);
+            for _ in 0..rounds {
+                aes.encrypt_block(half);
+            }
+        }
+        *transformed = sha256(&[&key[..]]);
+        return Ok(transformed);
+    }
+    let algorithm =
+        if uuid == KDF_ARGON2D {
+            Algorithm::Argon2d
+        } else if uuid == KDF_ARGON2ID {
+            Algorithm::Argon2id
+        } else {
+            return Err(unsupported());
+        };
+    // A secret key or associated data would be given as K or A.
+    if variant("K").is_ok() || variant("A").is_ok() {
+        return Err(unsupported());
+    }
+    let version =
+        match u32_variant("V")? {
+            0x10 => Version::V0x10,
+            0x13 => Version::V0x13,
+            _ => return Err(unsupported()),
+        };
+    let memory_kib = u32::try_from(u64_variant("M")? / 1024)?;
+    let iterations = u32::try_from(u64_variant("I")?)?;
+    let parallelism = u32_variant("P")?;
+    let params =
+        Params::new(memory_kib, iterations, parallelism, Some(32))
+            .map_err(|_| unsupported())?;
+    Argon2::new(algorithm, version, params)
+        .hash_password_into(&composite[..], variant("S")?, &mut transformed[..])
+        .map_err(|_| unsupported())?;
+    Ok(transformed)
+}
+
+// The key of the payload cipher, and the key that the HMAC keys of the
+// header and blocks come from.
+struct PayloadKeys {
+    cipher: Zeroizing<[u8; 32]>,
+    hmac: Zeroizing<[u8; 64]>,
+}
+
+impl PayloadKeys {
+    fn new(
+        fields: &[(u8, Vec<u8>)],
+        transformed_key: &[u8; 32],
+    ) -> Result<Self> {
+        let seed = field(fields, FIELD_MASTER_SEED)?;
+        let cipher = sha256(&[seed, transformed_key]);
+        let hmac = sha512(&[seed, transformed_key, &[1]]);
+        Ok(PayloadKeys {
+            cipher: Zeroizing::new(cipher),
+            hmac: Zeroizing::new(hmac),
+        })
+    }
+
+    // The HMAC of the block with this index, or of the header, to be
+    // finalized or verified.
+    fn mac(&self, idx: u64, message: &[&[u8]]) -> Hmac<Sha256> {
+        let idx_bytes = idx.to_le_bytes();
+        let key = Zeroizing::new(sha512(&[&idx_bytes, &self.hmac[..]]));
+        // HMAC takes keys of any length.
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&key[..]).unwrap();
+        for part in message {
+            mac.update(part);
+        }
+        mac
+    }
+}
+
+fn decrypt(
+    fields: &[(u8, Vec<u8>)],
+    key: &[u8; 32],
+    mut data: Vec<u8>,
+) -> Result<Zeroizing<Vec<u8>>> {
+    let iv = field(fields, FIELD_IV)?;
+    let cipher = field(fields, FIELD_CIPHER)?;
+    if cipher == CIPHER_CHACHA20 {
+        let nonce = iv.try_into().map_err(|_| malformed())?;
+        ChaCha20::new(key, nonce).apply_keystream(&mut data);
+        return Ok(Zeroizing::new(data));
+    }
+    if cipher != CIPHER_AES256 {
+        return Err(anyhow!(tr("the database uses an unsupported cipher")));
+    }
+    let mut prev: [u8; 16] = iv.try_into().map_err(|_| malformed())?;
+    if !data.len().is_multiple_of(16) {
+        return Err(malformed());
+    }
+    let mut data = Zeroizing::new(data);
+    let aes = Aes256::new(key.into());
+    for block in data.chunks_exact_mut(16) {
+        let block = Block::from_mut_slice(block);
+        let ciphertext = *block;
+        aes.decrypt_block(block);
+        for (byte, prev) in block.iter_mut().zip(prev) {
+            *byte ^= prev;
+        }
+        prev = ciphertext.into();
+    }
+    // PKCS #7 padding.
+    let pad_len = *data.last().ok_or_else(malformed)? as usize;
+    let padded =
+        (1..=16).contains(&pad_len)
+            && data[data.len().saturating_sub(pad_len)..].iter()
+                .all(|&byte| byte as usize == pad_len);
+    if !padded || pad_len > data.len() {
+        return Err(malformed());
+    }
+    let len = data.len() - pad_len;
+    data.truncate(len);
+    Ok(data)
+}
+
+fn encrypt(
+    fields: &[(u8, Vec<u8>)],
+    key: &[u8; 32],
+    mut data: Zeroizing<Vec<u8>>,
+) -> Result<Vec<u8>> {
+    let iv = field(fields, FIELD_IV)?;
+    let cipher = field(fields, FIELD_CIPHER)?;
+    if cipher == CIPHER_CHACHA20 {
+        let nonce = iv.try_into().map_err(|_| malformed())?;
+        ChaCha20::new(key, nonce).apply_keystream(&mut data);
+        return Ok(std::mem::take(&mut *data));
+    }
+    if cipher != CIPHER_AES256 {
+        return Err(anyhow!(tr("the database uses an unsupported cipher")));
+    }
+    let mut prev: [u8; 16] = iv.try_into().map_err(|_| malformed())?;
+    let pad_len = 16 - data.len() % 16;
+    let padded_len = data.len() + pad_len;
+    data.resize(padded_len, pad_len as u8);
+    let aes = Aes256::new(key.into());
+    for block in data.chunks_exact_mut(16) {
+        let block = Block::from_mut_slice(block);
+        for (byte, prev) in block.iter_mut().zip(prev) {
+            *byte ^= prev;
+        }
+        aes.encrypt_block(block);
+        prev = (*block).into();
+    }
+    Ok(std::mem::take(&mut *data))
+}
+
+fn sha256(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+fn sha512(parts: &[&[u8]]) -> [u8; 64] {
+    let mut hasher = Sha512::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+// ChaCha20 as in RFC 8439, with a 32-bit block counter and a 96-bit nonce.
+// The generator's 64-bit counter and 64-bit stream together fill the same
+// four words, so the first nonce word becomes the high half of the counter.
+// The generator hands out whole words, dropping the rest of one only partly
+// used, so the keystream is drawn a block at a time and the unused bytes
+// kept for the next call.
+struct ChaCha20 {
+    rng: ChaCha20Rng,
+    block: Zeroizing<[u8; 64]>,
+    pos: usize,
+}
+
+impl ChaCha20 {
+    fn new(key: &[u8; 32], nonce: &[u8; 12]) -> Self {
+        let mut rng = ChaCha20Rng::from_seed(*key);
+        let high = u32::from_le_bytes(nonce[..4].try_into().unwrap());
+        rng.set_stream(u64::from_le_bytes(nonce[4..].try_into().unwrap()));
+        rng.set_word_pos(u128::from(high) << 32 << 4);
+        ChaCha20 { rng, block: Zeroizing::new([0; 64]), pos: 64 }
+    }
+
+    fn apply_keystream(&mut self, data: &mut [u8]) {
+        for byte in data {
+            if self.pos == self.block.len() {
+                self.rng.fill_bytes(&mut self.block[..]);
+                self.pos = 0;
+            }
+            *byte ^= self.block[self.pos];
+            self.pos += 1;
+        }
+    }
+}
+
+fn inner_stream(stream_key: &[u8]) -> ChaCha20 {
+    let mut hash = Zeroizing::new(sha512(&[stream_key]));
+    let key = hash[..32].try_into().unwrap();
+    let nonce = hash[32..44].try_into().unwrap();
+    let stream = ChaCha20::new(key, nonce);
+    hash.zeroize();
+    stream
+}
+
+// XORs every protected value in document order with the inner stream,
+// turning ciphertext into plaintext or back, both in Base64.
+fn xor_protected(
+    xml: &str,
+    stream: &mut ChaCha20,
+) -> Result<Zeroizing<String>> {
+    let mut out = Zeroizing::new(String::with_capacity(xml.len()));
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        let end = rest[start..].find('>').ok_or_else(malformed)? + start;
+        let tag = &rest[start..=end];
+        out.push_str(&rest[..=end]);
+        rest = &rest[end + 1..];
+        let protected =
+            tag.starts_with("<Value ")
+                && !tag.ends_with("/>")
+                && tag.contains("Protected=\"True\"");
+        if protected {
+            let len = rest.find('<').ok_or_else(malformed)?;
+            let mut value =
+                base64::decode(rest[..len].trim()).ok_or_else(malformed)?;
+            stream.apply_keystream(&mut value);
+            base64::encode(&value, &mut out);
+            rest = &rest[len..];
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+// Returns the position of the closing tag of the group under Root.
+fn root_group_end(xml: &str) -> Option<usize> {
+    let root = xml.find("<Root>")?;
+    let group = xml[root..].find("<Group>")? + root;
+    let mut depth = 0;
+    let mut pos = group;
+    while let Some(start) = xml[pos..].find('<') {
+        let start = pos + start;
+        let tag = &xml[start..];
+        if tag.starts_with("<Group>") || tag.starts_with("<Group ") {
+            depth += 1;
+        } else if tag.starts_with("</Group>") {
+            depth -= 1;
+            if depth == 0 {
+                return Some(start);
+            }
+        }
+        pos = start + 1;
+    }
+    None
+}
+
+fn push_string(xml: &mut String, key: &str, value: &str) {
+    xml.push_str("\t\t\t\t<String>\n");
+    xml.push_str(&format!("\t\t\t\t\t<Key>{}</Key>\n", escape(key)));
+    xml.push_str(&format!("\t\t\t\t\t<Value>{}</Value>\n", escape(value)));
+    xml.push_str("\t\t\t\t</String>\n");
+}
+
+fn push_protected(xml: &mut String, key: &str, value: &str) {
+    xml.push_str("\t\t\t\t<String>\n");
+    xml.push_str(&format!("\t\t\t\t\t<Key>{}</Key>\n", escape(key)));
+    xml.push_str("\t\t\t\t\t<Value Protected=\"True\">");
+    base64::encode(value.as_bytes(), xml);
+    xml.push_str("</Value>\n");
+    xml.push_str("\t\t\t\t</String>\n");
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for &byte in text.as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+fn new_uuid() -> String {
+    let mut uuid = [0; 16];
+    OsRng.fill_bytes(&mut uuid);
+    let mut encoded = String::new();
+    base64::encode(&uuid, &mut encoded);
+    encoded
+}
+
+// The current time in KeePass's format: the Base64 of the little-endian
+// seconds since 0001-01-01.
+fn timestamp() -> String {
+    let unix_secs =
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64);
+    let mut encoded = String::new();
+    base64::encode(&(unix_secs + UNIX_EPOCH_SECS).to_le_bytes(), &mut encoded);
+    encoded
+}
+
+fn times(now: &str) -> String {
+    format!(
+        "<Times>\
+         <CreationTime>{now}</CreationTime>\
+         <LastModificationTime>{now}</LastModificationTime>\
+         <LastAccessTime>{now}</LastAccessTime>\
+         <ExpiryTime>{now}</ExpiryTime>\
+         <Expires>False</Expires>\
+         <UsageCount>0</UsageCount>\
+         <LocationChanged>{now}</LocationChanged>\
+         </Times>",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Finds the value of a string field, decoding it if protected.
+    fn value(xml: &str, key: &str) -> Option<String> {
+        let key_pos = xml.find(&format!("<Key>{}</Key>", key))?;
+        let rest = &xml[key_pos..];
+        let start = rest.find("<Value")?;
+        let start = rest[start..].find('>')? + start + 1;
+        let end = rest.find("</Value>")?;
+        let value = &rest[start..end];
+        if rest[..start].contains("Protected=\"True\"") {
+            String::from_utf8(base64::decode(value)?.to_vec()).ok()
+        } else {
+            Some(value.to_string())
+        }
+    }
+
+    fn unhex(text: &str) -> Vec<u8> {
+        (0..text.len())
+            .step_by(2)
+            .map(|idx| u8::from_str_radix(&text[idx..idx + 2], 16).unwrap())
+            .collect()
+    }
+
+    // The CBC-AES256 example of NIST SP 800-38A, appendix F.2.5, followed
+    // by the block of padding.
+    #[test]
+    fn aes_cbc_matches_sp_800_38a_example() {
+        let key =
+            unhex(
+                "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914\
+                 dff4",
+            );
+        let key = key.as_slice().try_into().unwrap();
+        let fields = [
+            (FIELD_CIPHER, CIPHER_AES256.to_vec()),
+            (FIELD_IV, unhex("000102030405060708090a0b0c0d0e0f")),
+        ];
+        let plaintext =
+            unhex(
+                "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af\
+                 8e5130c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417b\
+                 e66c3710",
+            );
+        let ciphertext =
+            encrypt(&fields, key, Zeroizing::new(plaintext.clone())).unwrap();
+        let expected =
+            unhex(
+                "f58c4c04d6e5f1ba779eabfb5f7bfbd69cfc4e967edb808d679f777bc670\
+                 2c7d39f23369a9d9bacfa530e26304231461b2eb05e2c39be9fcda6c1907\
+                 8c6a9d1b",
+            );
+        assert_eq!(ciphertext.len(), expected.len() + 16);
+        assert_eq!(ciphertext[..expected.len()], expected);
+        let decrypted = decrypt(&fields, key, ciphertext).unwrap();
+        assert_eq!(*decrypted, plaintext);
+    }
+
+    #[test]
+    fn saved_database_opens_with_its_entries() {
+        let dir = std::env::temp_dir();
+        let path =
+            dir.join(format!("passgen-kdbx-{}.kdbx", std::process::id()));
+        let mut database = Database::create(&path, "master").unwrap();
+        let entry = NewEntry {
+            title: "Example",
+            username: "alice",
+            password: "correct horse <battery>",
+            url: Some("https://example.com/"),
+            totp_secret: Some("JBSWY3DPEHPK3PXP"),
+        };
+        database.add_entry(&entry).unwrap();
+        let saved = database.save();
+        let opened = Database::open(&path, "master");
+        let wrong = Database::open(&path, "not the master password");
+        #[cfg(unix)]
+        let mode = {
+            use std::os::unix::fs::PermissionsExt;
+            fs::metadata(&path).map(|metadata| metadata.permissions().mode())
+        };
+        let _ = fs::remove_file(&path);
+        saved.unwrap();
+        let xml = &opened.unwrap().xml;
+        assert_eq!(value(xml, "Title").as_deref(), Some("Example"));
+        assert_eq!(value(xml, "UserName").as_deref(), Some("alice"));
+        assert_eq!(
+            value(xml, "Password").as_deref(),
+            Some("correct horse <battery>"),
+        );
+        assert_eq!(
+            value(xml, "otp").as_deref(),
+            Some(
+                "otpauth://totp/Example:alice?secret=JBSWY3DPEHPK3PXP\
+                 &issuer=Example"
+            ),
+        );
+        assert!(wrong.is_err());
+        #[cfg(unix)]
+        assert_eq!(mode.unwrap() & 0o777, 0o600);
+    }
+}
@@ -0,0 +1,68 @@
+use anyhow::{anyhow, Context, Result};
+use rand::RngCore;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use zeroize::Zeroizing;
+
+use crate::i18n::trf;
+
+// Keyfiles for cryptsetup are raw random bytes, which it uses whole as the
+// passphrase of a key slot. They are created readable only by their owner,
+// and replaced only by removing the old file first, so that a keyfile never
+// keeps looser permissions it already had.
+
+/// The size of keyfiles by default, in bytes.
+pub const DEFAULT_SIZE: usize = 4096;
+/// The smallest size allowed: 256 bits, as much as any cipher's key.
+pub const MIN_SIZE: usize = 32;
+/// The largest keyfile cryptsetup reads by default.
+pub const MAX_SIZE: usize = 8 * 1024 * 1024;
+
+const CHUNK_SIZE: usize = 4096;
+
+/// Writes a keyfile of random bytes, replacing any file at the path only if
+/// asked.
+pub fn write(
+    path: &Path,
+    size: usize,
+    overwrite: bool,
+    rng: &mut impl RngCore,
+) -> Result<()> {
+    let context = || trf("could not write \"{}\"", &[&path.display()]);
+    if overwrite {
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                return Err(err).with_context(context);
+            },
+            _ => {},
+        }
+    } else if path.exists() {
+        let path = path.display();
+        let msg = trf("\"{}\" already exists; use -f to replace it", &[&path]);
+        return Err(anyhow!(msg));
+    }
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o400);
+    let mut file = options.open(path).with_context(context)?;
+    let mut chunk = Zeroizing::new([0; CHUNK_SIZE]);
+    let mut remaining = size;
+    let mut fill = || -> Result<()> {
+        while remaining > 0 {
+            let chunk = &mut chunk[..remaining.min(CHUNK_SIZE)];
+            rng.try_fill_bytes(chunk)?;
+            file.write_all(chunk)?;
+            remaining -= chunk.len();
+        }
+        file.sync_all()?;
+        Ok(())
+    };
+    let result = fill();
+    // A partial keyfile is removed rather than left to be enrolled.
+    if result.is_err() {
+        let _ = fs::remove_file(path);
+    }
+    result.with_context(context)
+}
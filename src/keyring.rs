@@ -0,0 +1,303 @@
+use anyhow::{anyhow, Result};
+use zeroize::Zeroizing;
+
+use crate::i18n::trf;
+
+// Secrets are kept in the platform's keyring under a service and an account
+// name: in the Secret Service through secret-tool on Linux and other Unix
+// systems, in the login keychain through security on macOS, and in the
+// Credential Manager on Windows. The secret only ever passes through pipes
+// to the helper programs, never their arguments.
+
+pub struct KeyringEntry {
+    service: String,
+    account: String,
+}
+
+impl KeyringEntry {
+    pub fn new(service: String, account: String) -> Self {
+        KeyringEntry { service, account }
+    }
+
+    /// Saves the secret, replacing any already saved for the entry.
+    pub fn store(&self, secret: &str) -> Result<()> {
+        platform::store(&self.service, &self.account, secret)
+    }
+
+    /// Reads the secret saved for the entry.
+    pub fn retrieve(&self) -> Result<Zeroizing<String>> {
+        platform::lookup(&self.service, &self.account)?.ok_or_else(|| {
+            let msg = trf(
+                "the keyring has no entry for service \"{}\" and account \
+                 \"{}\"",
+                &[&self.service, &self.account],
+            );
+            anyhow!(msg)
+        })
+    }
+}
+
+#[cfg(all(feature = "keyring", unix))]
+mod helper {
+    use anyhow::{anyhow, Result};
+    use std::io::{Read, Write};
+    use std::process::{Command, ExitStatus, Stdio};
+    use zeroize::Zeroizing;
+
+    use crate::i18n::trf;
+
+    // Runs the program with the input on standard input, returning its exit
+    // status and standard output.
+    pub fn run(
+        program: &str,
+        args: &[&str],
+        input: &str,
+    ) -> Result<(ExitStatus, Zeroizing<String>)> {
+        let mut child =
+            Command::new(program)
+                .args(args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .spawn()
+                .map_err(|err| {
+                    anyhow!(trf("could not run {}: {}", &[&program, &err]))
+                })?;
+        let mut stdin = child.stdin.take().unwrap();
+        stdin.write_all(input.as_bytes())?;
+        drop(stdin);
+        let mut output = Zeroizing::new(String::new());
+        child.stdout.take().unwrap().read_to_string(&mut output)?;
+        Ok((child.wait()?, output))
+    }
+
+    pub fn check_status(program: &str, status: ExitStatus) -> Result<()> {
+        if !status.success() {
+            return Err(anyhow!(trf("{} exited with {}", &[&program, &status])));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(feature = "keyring", unix, not(target_os = "macos")))]
+mod platform {
+    use anyhow::Result;
+    use zeroize::Zeroizing;
+
+    use super::helper::{check_status, run};
+
+    const SECRET_TOOL: &str = "secret-tool";
+
+    pub fn store(service: &str, account: &str, secret: &str) -> Result<()> {
+        let label = format!("--label={} ({})", service, account);
+        let args = [
+            "store",
+            &label,
+            "service",
+            service,
+            "account",
+            account,
+        ];
+        let (status, _) = run(SECRET_TOOL, &args, secret)?;
+        check_status(SECRET_TOOL, status)
+    }
+
+    pub fn lookup(
+        service: &str,
+        account: &str,
+    ) -> Result<Option<Zeroizing<String>>> {
+        let args = ["lookup", "service", service, "account", account];
+        let (status, secret) = run(SECRET_TOOL, &args, "")?;
+        // secret-tool fails without a message when nothing matches.
+        if !status.success() && secret.is_empty() {
+            return Ok(None);
+        }
+        check_status(SECRET_TOOL, status)?;
+        Ok(Some(secret))
+    }
+}
+
+#[cfg(all(feature = "keyring", target_os = "macos"))]
+mod platform {
+    use anyhow::Result;
+    use zeroize::Zeroizing;
+
+    use super::helper::{check_status, run};
+
+    const SECURITY: &str = "security";
+    // The status security exits with when no item matches.
+    const ITEM_NOT_FOUND: i32 = 44;
+
+    pub fn store(service: &str, account: &str, secret: &str) -> Result<()> {
+        // In interactive mode security reads its commands from standard
+        // input, which keeps the secret out of its arguments.
+        let command = Zeroizing::new(format!(
+            "add-generic-password -U -s {} -a {} -w {}\n",
+            quote(service),
+            quote(account),
+            Zeroizing::new(quote(secret)).as_str(),
+        ));
+        let (status, _) = run(SECURITY, &["-i"], &command)?;
+        check_status(SECURITY, status)
+    }
+
+    pub fn lookup(
+        service: &str,
+        account: &str,
+    ) -> Result<Option<Zeroizing<String>>> {
+        let args =
+            ["find-generic-password", "-s", service, "-a", account, "-w"];
+        let (status, mut secret) = run(SECURITY, &args, "")?;
+        if status.code() == Some(ITEM_NOT_FOUND) {
+            return Ok(None);
+        }
+        check_status(SECURITY, status)?;
+        if secret.ends_with('\n') {
+            secret.pop();
+        }
+        Ok(Some(secret))
+    }
+
+    fn quote(word: &str) -> String {
+        let mut quoted = String::with_capacity(word.len() + 2);
+        quoted.push('"');
+        for ch in word.chars() {
+            if ch == '"' || ch == '\\' {
+                quoted.push('\\');
+            }
+            quoted.push(ch);
+        }
+        quoted.push('"');
+        quoted
+    }
+}
+
+#[cfg(all(feature = "keyring", windows))]
+mod platform {
+    use anyhow::{anyhow, Result};
+    use std::io;
+    use std::ptr;
+    use windows_sys::Win32::Foundation::{ERROR_NOT_FOUND, FILETIME};
+    use windows_sys::Win32::Security::Credentials::{
+        CredFree,
+        CredReadW,
+        CredWriteW,
+        CREDENTIALW,
+        CRED_MAX_CREDENTIAL_BLOB_SIZE,
+        CRED_PERSIST_LOCAL_MACHINE,
+        CRED_TYPE_GENERIC,
+    };
+    use zeroize::Zeroizing;
+
+    use crate::i18n::{tr, trf};
+
+    // Generic credentials are named as the keyring crate names them, so
+    // that other programs using it can find them. The secret is stored as
+    // UTF-16, as the Credential Manager shows it.
+
+    pub fn store(service: &str, account: &str, secret: &str) -> Result<()> {
+        let mut target = wide(&target_name(service, account));
+        let mut user = wide(account);
+        let mut blob =
+            Zeroizing::new(
+                secret.encode_utf16()
+                    .flat_map(u16::to_le_bytes)
+                    .collect::<Vec<_>>(),
+            );
+        if blob.len() > CRED_MAX_CREDENTIAL_BLOB_SIZE as usize {
+            return Err(anyhow!(tr("the secret is too long for the keyring")));
+        }
+        let credential = CREDENTIALW {
+            Flags: 0,
+            Type: CRED_TYPE_GENERIC,
+            TargetName: target.as_mut_ptr(),
+            Comment: ptr::null_mut(),
+            LastWritten: FILETIME { dwLowDateTime: 0, dwHighDateTime: 0 },
+            CredentialBlobSize: blob.len() as u32,
+            CredentialBlob: blob.as_mut_ptr(),
+            Persist: CRED_PERSIST_LOCAL_MACHINE,
+            AttributeCount: 0,
+            Attributes: ptr::null_mut(),
+            TargetAlias: ptr::null_mut(),
+            UserName: user.as_mut_ptr(),
+        };
+        if unsafe { CredWriteW(&credential, 0) } == 0 {
+            return Err(keyring_error(io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+
+    pub fn lookup(
+        service: &str,
+        account: &str,
+    ) -> Result<Option<Zeroizing<String>>> {
+        let target = wide(&target_name(service, account));
+        let mut credential = ptr::null_mut::<CREDENTIALW>();
+        let found =
+            unsafe {
+                let target = target.as_ptr();
+                CredReadW(target, CRED_TYPE_GENERIC, 0, &mut credential)
+            };
+        if found == 0 {
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() == Some(ERROR_NOT_FOUND as i32) {
+                return Ok(None);
+            }
+            return Err(keyring_error(err));
+        }
+        let units =
+            unsafe {
+                let credential = &*credential;
+                let len = credential.CredentialBlobSize as usize;
+                let blob =
+                    if len == 0 {
+                        &[][..]
+                    } else {
+                        std::slice::from_raw_parts(
+                            credential.CredentialBlob,
+                            len,
+                        )
+                    };
+                Zeroizing::new(
+                    blob.chunks_exact(2)
+                        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                        .collect::<Vec<_>>(),
+                )
+            };
+        unsafe { CredFree(credential.cast()) };
+        let secret = String::from_utf16(&units).map_err(|_| {
+            anyhow!(tr("the keyring entry is not valid text"))
+        })?;
+        Ok(Some(Zeroizing::new(secret)))
+    }
+
+    fn target_name(service: &str, account: &str) -> String {
+        format!("{}.{}", account, service)
+    }
+
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(Some(0)).collect()
+    }
+
+    fn keyring_error(err: io::Error) -> anyhow::Error {
+        anyhow!(trf("could not access the keyring: {}", &[&err]))
+    }
+}
+
+#[cfg(not(all(feature = "keyring", any(unix, windows))))]
+mod platform {
+    use anyhow::{anyhow, Result};
+    use zeroize::Zeroizing;
+
+    use crate::i18n::tr;
+
+    pub fn store(_service: &str, _account: &str, _secret: &str) -> Result<()> {
+        Err(anyhow!(tr("this build does not support --store keyring")))
+    }
+
+    pub fn lookup(
+        _service: &str,
+        _account: &str,
+    ) -> Result<Option<Zeroizing<String>>> {
+        Err(anyhow!(tr("this build does not support --store keyring")))
+    }
+}
@@ -0,0 +1,200 @@
+use anyhow::{anyhow, Result};
+use passgen::{Generator, IdAlphabet, IdGenerator};
+use rand_core::CryptoRngCore;
+use std::num::NonZeroUsize;
+use std::time::SystemTime;
+
+use crate::audit;
+use crate::i18n::trf;
+
+// A label is text with placeholders in braces: {seq} for the number of the
+// record, counted from 1 and padded with zeros to any width given, as in
+// {seq:04}; {date} for the date in UTC when the job ran; and {random} for
+// random characters of Crockford's Base32 in lowercase, 4 of them unless
+// another count is given, as in {random:6}. Braces themselves are written
+// {{ and }}.
+
+const DEFAULT_RANDOM_LEN: usize = 4;
+// Wider than any count of records.
+const MAX_SEQ_WIDTH: usize = 20;
+const MAX_RANDOM_LEN: usize = 64;
+
+/// A label given with --label, expanded for each record.
+pub struct Label {
+    template: String,
+    pieces: Vec<Piece>,
+    date: String,
+}
+
+enum Piece {
+    Text(String),
+    Seq(usize),
+    Date,
+    Random(IdGenerator),
+}
+
+impl Label {
+    /// Parses the template, failing on an unknown or unclosed placeholder.
+    pub fn parse(template: &str) -> Result<Self> {
+        let err = || anyhow!(trf("invalid label: \"{}\"", &[&template]));
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        let mut rest = template;
+        while let Some(ch) = rest.chars().next() {
+            rest = &rest[ch.len_utf8()..];
+            match ch {
+                '{' | '}' if rest.starts_with(ch) => {
+                    rest = &rest[1..];
+                    text.push(ch);
+                },
+                '{' => {
+                    let (placeholder, after) =
+                        rest.split_once('}').ok_or_else(err)?;
+                    rest = after;
+                    if !text.is_empty() {
+                        pieces.push(Piece::Text(std::mem::take(&mut text)));
+                    }
+                    let piece = parse_placeholder(placeholder);
+                    pieces.push(piece.ok_or_else(err)?);
+                },
+                '}' => return Err(err()),
+                ch => text.push(ch),
+            }
+        }
+        if !text.is_empty() {
+            pieces.push(Piece::Text(text));
+        }
+        let mut date = audit::timestamp(SystemTime::now());
+        date.truncate("YYYY-MM-DD".len());
+        Ok(Label { template: template.to_string(), pieces, date })
+    }
+
+    /// Returns the template as given.
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// Returns the label of the record with this number.
+    pub fn expand(
+        &self,
+        seq: usize,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<String> {
+        let mut label = String::new();
+        for piece in &self.pieces {
+            match piece {
+                Piece::Text(text) => label.push_str(text),
+                Piece::Seq(width) => {
+                    label.push_str(&format!("{:0width$}", seq, width = width));
+                },
+                Piece::Date => label.push_str(&self.date),
+                Piece::Random(generator) => {
+                    let start = label.len();
+                    generator.generate(rng, &mut label)?;
+                    label[start..].make_ascii_lowercase();
+                },
+            }
+        }
+        Ok(label)
+    }
+}
+
+fn parse_placeholder(placeholder: &str) -> Option<Piece> {
+    let (name, arg) =
+        match placeholder.split_once(':') {
+            Some((name, arg)) => (name, Some(arg)),
+            None => (placeholder, None),
+        };
+    let number = |max: usize| {
+        arg.and_then(|arg| arg.parse::<usize>().ok())
+            .filter(|&number| number <= max)
+    };
+    match (name, arg) {
+        ("seq", None) => Some(Piece::Seq(0)),
+        ("seq", Some(_)) => number(MAX_SEQ_WIDTH).map(Piece::Seq),
+        ("date", None) => Some(Piece::Date),
+        ("random", _) => {
+            let len =
+                match arg {
+                    Some(_) => number(MAX_RANDOM_LEN)?,
+                    None => DEFAULT_RANDOM_LEN,
+                };
+            let len = NonZeroUsize::new(len)?;
+            IdGenerator::new(IdAlphabet::Crockford, len, false)
+                .ok()
+                .map(Piece::Random)
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::ChaCha20Rng;
+    use rand::SeedableRng;
+
+    fn expand(template: &str, seq: usize) -> String {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        Label::parse(template).unwrap().expand(seq, &mut rng).unwrap()
+    }
+
+    #[test]
+    fn sequence_numbers_are_padded_to_the_width() {
+        assert_eq!(expand("user-{seq}", 7), "user-7");
+        assert_eq!(expand("user-{seq:04}", 7), "user-0007");
+        assert_eq!(expand("{seq:2}", 12345), "12345");
+        assert_eq!(expand("{seq}{seq:3}", 42), "42042");
+    }
+
+    #[test]
+    fn braces_are_doubled() {
+        assert_eq!(expand("{{seq}} {{{seq}}}", 3), "{seq} {3}");
+        assert_eq!(expand("ümlaut {seq} }}", 1), "ümlaut 1 }");
+    }
+
+    #[test]
+    fn date_is_today_in_utc() {
+        let before = audit::timestamp(SystemTime::now());
+        let label = expand("on {date}", 1);
+        let after = audit::timestamp(SystemTime::now());
+        // Either side of midnight.
+        let dates = [&before[..10], &after[..10]];
+        assert!(dates.iter().any(|date| label == format!("on {}", date)));
+    }
+
+    #[test]
+    fn random_characters_are_lowercase_crockford() {
+        let label = expand("{random}", 1);
+        assert_eq!(label.len(), DEFAULT_RANDOM_LEN);
+        let label = expand("k-{random:12}", 1);
+        assert_eq!(label.len(), 14);
+        let alphabet = "0123456789abcdefghjkmnpqrstvwxyz";
+        assert!(label[2..].chars().all(|ch| alphabet.contains(ch)));
+        let label = Label::parse("{random}").unwrap();
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let first = label.expand(1, &mut rng).unwrap();
+        assert_ne!(label.expand(1, &mut rng).unwrap(), first);
+    }
+
+    #[test]
+    fn invalid_templates_are_rejected() {
+        for template in [
+            "{",
+            "}",
+            "{seq",
+            "a}b",
+            "{name}",
+            "{seq:x}",
+            "{seq:21}",
+            "{date:iso}",
+            "{random:0}",
+            "{random:65}",
+            "{random:-1}",
+        ] {
+            assert!(Label::parse(template).is_err(), "{}", template);
+        }
+        assert_eq!(expand("{seq:20}", 1).len(), 20);
+        assert_eq!(expand("{random:64}", 1).len(), 64);
+    }
+}
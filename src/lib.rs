@@ -1,160 +1,247 @@
-use anyhow::*;
-use bitvec::array::BitArray;
-use bitvec::prelude::Lsb0;
-use num_bigint::BigUint;
-use num_integer::Integer;
-use num_traits::cast::ToPrimitive;
-use rand::RngCore;
-use rand::rngs::OsRng;
+//! Random password generation from a character set specification.
+//!
+//! Build a [`PasswordSpec`] describing the passwords to generate, then draw
+//! them from the operating system's random number generator:
+//!
+//! ```
+//! use passgen::prelude::*;
+//! use std::num::NonZeroUsize;
+//!
+//! let spec =
+//!     PasswordSpec::builder()
+//!         .charset(Charset::parse("a-z0-9")?)
+//!         .length(NonZeroUsize::new(16).unwrap())
+//!         .count(NonZeroUsize::new(3).unwrap())
+//!         .build()?;
+//! for password in spec.generate()? {
+//!     println!("{}", password);
+//! }
+//! # Ok::<(), PassgenError>(())
+//! ```
+//!
+//! # Stability
+//!
+//! Everything re-exported by [`prelude`] follows semantic versioning, as
+//! does the rest of the documented API. Items hidden from the documentation
+//! exist to support the command line tool and may change in any release.
 
-use self::CharsetParserState::*;
+#![deny(missing_docs)]
 
-#[derive(Copy, Clone)]
-enum CharsetParserState {
-    Start,
-    Char(u8),
-    Escape,
-    Range(u8),
-    RangeEscape(u8),
-}
+use std::error::Error;
+use std::fmt;
 
-const HYPHEN: u8 = 45;
-const BACKSLASH: u8 = 92;
-const CARET: u8 = 94;
+mod apikey;
+mod attack;
+#[cfg(feature = "arbitrary")]
+mod arbitrary_support;
+mod card;
+mod charset;
+mod compose;
+#[cfg(feature = "derive")]
+mod derive;
+mod drbg;
+mod encoded;
+mod fallback;
+mod generator;
+mod guesses;
+mod hwrng;
+mod license;
+mod mac;
+mod memory;
+#[cfg(feature = "mixing")]
+mod mixing;
+mod observer;
+mod otp;
+mod passphrase;
+mod pattern;
+mod pin;
+mod policy;
+mod provenance;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod rdseed;
+mod registry;
+mod sampler;
+#[cfg(feature = "secrecy")]
+mod secret;
+#[cfg(feature = "serde")]
+mod serde_support;
+mod shamir;
+#[cfg(feature = "async")]
+mod stream;
+mod strengthen;
+mod token;
+mod transform;
+mod username;
+mod uuid;
+#[cfg(feature = "wifi")]
+mod wifi;
+#[cfg(feature = "yubikey")]
+mod yubikey;
 
-const TYPEABLE: [u64; 2] = [0xffff_ffff_0000_0000, 0x7fff_ffff_ffff_ffff];
+/// Continuous health tests for random number generator output.
+pub mod health;
+#[doc(hidden)]
+pub mod output;
+/// The stable types most programs need, for glob import.
+pub mod prelude;
+/// Startup self-tests of the generator and its dependencies.
+pub mod selftest;
 
-pub fn generate(
-    charset_spec: Option<&String>,
-    password_len: usize,
-    num_passwords: usize,
-) -> Result<Vec<String>> {
-    let charset =
-        match charset_spec {
-            Some(charset_spec) =>
-                parse_charset_spec(charset_spec)?,
-            None => {
-                let charset = BitArray::<_, Lsb0>::from(TYPEABLE);
-                charset.iter_ones().map(|i| i as u8).collect()
-            },
-        };
-    let base = charset.len();
-    let mut value = {
-        let total_chars = password_len * num_passwords;
-        let num_bits = BigUint::from(base).pow(total_chars as u32).bits();
-        let num_bytes = (num_bits / 8) + 1;
-        let mut buffer = vec![0u8; num_bytes as usize];
-        OsRng.fill_bytes(&mut buffer);
-        BigUint::from_bytes_le(&buffer)
-    };
-    let base = base.into();
-    let mut passwords = Vec::with_capacity(num_passwords);
-    loop {
-        let mut password_bytes = Vec::with_capacity(password_len);
-        for _ in 0..password_len {
-            let (quo, rem) = value.div_mod_floor(&base);
-            value = quo;
-            let idx = rem.to_usize().unwrap();
-            let ch = charset[idx];
-            password_bytes.push(ch);
-        }
-        let string = String::from_utf8(password_bytes).unwrap();
-        passwords.push(string);
-        if passwords.len() == num_passwords {
-            break;
+pub use apikey::ApiKeyGenerator;
+pub use attack::AttackerProfile;
+pub use card::{PasswordCard, CARD_ROWS};
+pub use charset::{format_charset_spec, parse_charset_spec, Charset};
+pub use compose::ComposedGenerator;
+#[cfg(feature = "derive")]
+pub use derive::{
+    derive_card,
+    derive_password,
+    DerivationVersion,
+    SiteContext,
+};
+pub use drbg::ChaChaDrbg;
+pub use encoded::{IdAlphabet, IdGenerator, TokenEncoding, TokenGenerator};
+pub use fallback::{FallbackRng, RngFailurePolicy};
+pub use generator::{
+    entropy_bits,
+    CharsetGenerator,
+    Generator,
+    PasswordSpec,
+    PasswordSpecBuilder,
+    Passwords,
+};
+pub use guesses::{
+    estimate_guesses,
+    estimate_guesses_with,
+    Dictionaries,
+    GuessEstimate,
+    GuessPattern,
+    PatternMatch,
+};
+pub use hwrng::{HwRng, HWRNG_PATH};
+pub use license::{LicenseChecksum, LicenseKeyGenerator};
+pub use mac::MacGenerator;
+#[cfg(feature = "mixing")]
+pub use mixing::EntropyMixer;
+pub use observer::GenerationObserver;
+pub use otp::OtpCodeGenerator;
+pub use passphrase::PassphraseGenerator;
+pub use pattern::PatternGenerator;
+pub use pin::PinGenerator;
+pub use policy::{CharClass, Policy, Preset};
+pub use provenance::EntropySource;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub use rdseed::RdSeed;
+pub use registry::{
+    register_generator,
+    registered_generator,
+    registered_generators,
+    SharedGenerator,
+};
+#[cfg(feature = "secrecy")]
+pub use secrecy::{ExposeSecret, SecretString};
+pub use shamir::{recover_secret, split_secret, Share};
+#[cfg(feature = "async")]
+pub use stream::PasswordStream;
+pub use strengthen::{estimate_entropy, Placement, Strengthener};
+pub use token::EntropyToken;
+pub use transform::{Transform, Transformed};
+pub use username::{UsernameGenerator, UsernameStyle};
+pub use uuid::{UuidGenerator, UuidVersion};
+#[cfg(feature = "wifi")]
+pub use wifi::{
+    wifi_qr_payload,
+    wpa_psk,
+    MAX_SSID_LEN,
+    WPA_PASSPHRASE_LEN,
+};
+#[cfg(feature = "yubikey")]
+pub use yubikey::YubiKey;
+
+/// The result type returned throughout the library.
+pub type Result<T> = std::result::Result<T, PassgenError>;
+
+/// An error raised while parsing a specification or generating passwords.
+#[derive(Debug)]
+pub enum PassgenError {
+    /// The specification is malformed.
+    InvalidSpec(String),
+    /// The policy cannot be satisfied by the character set and length.
+    PolicyViolation(String),
+    /// The password entropy is below the required number of bits.
+    InsufficientEntropy {
+        /// The entropy of the weakest password, in bits.
+        bits: f64,
+        /// The required entropy, in bits.
+        required: f64,
+    },
+    /// The random number generator failed.
+    RngFailure(rand::Error),
+    /// The random number generator output failed a health test.
+    HealthTestFailure(String),
+    /// A self-test failed.
+    SelfTestFailure(String),
+    /// No candidate for a password satisfied the policy within the limits
+    /// on resampling.
+    AttemptsExhausted {
+        /// The candidates drawn before giving up.
+        attempts: u64,
+        /// The fraction of candidates that satisfy the policy.
+        acceptance_rate: f64,
+    },
+}
+
+impl fmt::Display for PassgenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PassgenError::InvalidSpec(msg) =>
+                write!(f, "{}", msg),
+            PassgenError::PolicyViolation(msg) =>
+                write!(f, "policy cannot be satisfied: {}", msg),
+            PassgenError::InsufficientEntropy { bits, required } =>
+                write!(
+                    f,
+                    "password entropy of {:.1} bits is below the required \
+                     {:.1} bits",
+                    bits,
+                    required,
+                ),
+            PassgenError::RngFailure(err) =>
+                write!(f, "random number generator failed: {}", err),
+            PassgenError::HealthTestFailure(msg) =>
+                write!(f, "random number generator health test: {}", msg),
+            PassgenError::SelfTestFailure(msg) =>
+                write!(f, "{}", msg),
+            PassgenError::AttemptsExhausted { attempts, acceptance_rate } =>
+                write!(
+                    f,
+                    "gave up after {} attempts: acceptance rate ~{:.0e}, \
+                     relax the constraints",
+                    attempts,
+                    acceptance_rate,
+                ),
         }
     }
-    Ok(passwords)
 }
 
-pub fn parse_charset_spec(charset_spec: &String) -> Result<Vec<u8>> {
-    fn err_escape_hyphen() -> Result<Vec<u8>> {
-        Err(anyhow!("hyphens must be escaped"))
-    }
-    fn err_invalid_escape(byte: u8) -> Result<Vec<u8>> {
-        Err(anyhow!("invalid escape sequence: \"\\{}\"", byte as char))
-    }
-    if charset_spec.is_empty() {
-        return Err(anyhow!("empty charset specification"));
-    }
-    let bytes = charset_spec.as_bytes();
-    let invert = bytes[0] == CARET;
-    let mut bytes = bytes.iter();
-    if invert {
-        bytes.next();
-    }
-    let mut state = Start;
-    let mut result = BitArray::<_, Lsb0>::from([0u64; 2]);
-    let typeable = BitArray::<_, Lsb0>::from(TYPEABLE);
-    for &byte in bytes {
-        if !typeable.get(byte as usize).unwrap() {
-            return Err(anyhow!("found untypeable or non-ASCII character"));
-        }
-        match (state, byte) {
-            (Start, HYPHEN) => {
-                return err_escape_hyphen();
-            },
-            (Start, BACKSLASH) => {
-                state = Escape;
-            },
-            (Start, byte) => {
-                result.set(byte as usize, true);
-                state = Char(byte);
-            },
-            (Char(prev), HYPHEN) => {
-                state = Range(prev);
-            },
-            (Char(_), BACKSLASH) => {
-                state = Escape;
-            },
-            (Char(_), byte) => {
-                result.set(byte as usize, true);
-            },
-            (Escape, byte) => {
-                if byte != HYPHEN && byte != BACKSLASH {
-                    return err_invalid_escape(byte);
-                }
-                result.set(byte as usize, true);
-                state = Char(byte);
-            },
-            (Range(_), HYPHEN) => {
-                return err_escape_hyphen();
-            },
-            (Range(start), BACKSLASH) => {
-                state = RangeEscape(start);
-            },
-            (Range(start), end) => {
-                for byte in (start + 1)..=end {
-                    result.set(byte as usize, true);
-                }
-                state = Start;
-            },
-            (RangeEscape(start), end) => {
-                if byte != HYPHEN && byte != BACKSLASH {
-                    return err_invalid_escape(byte);
-                }
-                for byte in (start + 1)..=end {
-                    result.set(byte as usize, true);
-                }
-                state = Start;
-            }
+impl Error for PassgenError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PassgenError::RngFailure(err) => Some(err),
+            _ => None,
         }
     }
-    match state {
-        Escape | RangeEscape(_) =>
-            Err(anyhow!("unterminated escape sequence")),
-        Range(_) =>
-            Err(anyhow!("unterminated character range")),
-        _ => {
-            if invert {
-                let tmp = result;
-                result = typeable;
-                result &= !tmp;
-            }
-            if result.not_any() {
-                return Err(anyhow!("character set is empty"))
-            }
-            Ok(result.iter_ones().map(|i| i as u8).collect())
-        }
+}
+
+pub(crate) fn invalid_spec(msg: impl Into<String>) -> PassgenError {
+    PassgenError::InvalidSpec(msg.into())
+}
+
+// Unwraps errors that wrappers such as the health tests pass through the
+// random number generator interface.
+pub(crate) fn rng_failure(err: rand::Error) -> PassgenError {
+    match err.take_inner().downcast::<PassgenError>() {
+        Ok(err) => *err,
+        Err(inner) => PassgenError::RngFailure(rand::Error::new(inner)),
     }
 }
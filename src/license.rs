@@ -0,0 +1,268 @@
+use rand_core::CryptoRngCore;
+use std::fmt;
+use std::num::NonZeroUsize;
+
+use crate::generator::{entropy_bits, Generator};
+use crate::memory::SecretBuf;
+use crate::sampler::sample;
+use crate::{invalid_spec, Result};
+
+// Crockford's Base32 alphabet, which leaves out I, L, O and U.
+const DIGITS: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const SEPARATOR: char = '-';
+
+/// The checksum at the end of a license key.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LicenseChecksum {
+    /// One Luhn mod 32 check character, which catches every single-character
+    /// error and most swaps of adjacent characters.
+    #[default]
+    LuhnModN,
+    /// Four characters holding the low 20 bits of the CRC-32 of the rest of
+    /// the key.
+    Crc,
+}
+
+impl LicenseChecksum {
+    fn len(self) -> usize {
+        match self {
+            LicenseChecksum::LuhnModN => 1,
+            LicenseChecksum::Crc => 4,
+        }
+    }
+
+    fn compute(self, payload: &[u8]) -> Vec<u8> {
+        match self {
+            LicenseChecksum::LuhnModN => {
+                let values = payload.iter().map(|&byte| digit_value(byte));
+                vec![DIGITS[luhn_check_value(values, DIGITS.len())]]
+            },
+            LicenseChecksum::Crc => {
+                let crc = crc32fast::hash(payload);
+                (0..4)
+                    .rev()
+                    .map(|idx| DIGITS[(crc >> (5 * idx)) as usize & 0x1f])
+                    .collect()
+            },
+        }
+    }
+}
+
+impl fmt::Display for LicenseChecksum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LicenseChecksum::LuhnModN => write!(f, "luhn mod 32"),
+            LicenseChecksum::Crc => write!(f, "crc-32"),
+        }
+    }
+}
+
+// The Luhn mod N check value of a sequence of values below N.
+pub(crate) fn luhn_check_value(
+    values: impl DoubleEndedIterator<Item = usize>,
+    base: usize,
+) -> usize {
+    let sum =
+        values.rev()
+            .enumerate()
+            .map(|(pos, value)| {
+                let factor = if pos % 2 == 0 { 2 } else { 1 };
+                let addend = factor * value;
+                addend / base + addend % base
+            })
+            .sum::<usize>();
+    (base - sum % base) % base
+}
+
+// Appends the characters in groups of this length, separated by hyphens.
+pub(crate) fn push_grouped(out: &mut String, chars: &[u8], group_len: usize) {
+    for (idx, group) in chars.chunks(group_len).enumerate() {
+        if idx > 0 {
+            out.push(SEPARATOR);
+        }
+        out.extend(group.iter().copied().map(char::from));
+    }
+}
+
+fn digit_value(byte: u8) -> usize {
+    DIGITS.iter().position(|&digit| digit == byte).unwrap()
+}
+
+/// Generates license keys of hyphen-separated groups of characters from an
+/// unambiguous alphabet, the last of which are a checksum of the rest.
+#[derive(Clone, Debug)]
+pub struct LicenseKeyGenerator {
+    num_groups: NonZeroUsize,
+    group_len: NonZeroUsize,
+    checksum: LicenseChecksum,
+}
+
+impl LicenseKeyGenerator {
+    /// The default number of groups.
+    pub const DEFAULT_NUM_GROUPS: NonZeroUsize = NonZeroUsize::new(4).unwrap();
+    /// The default number of characters in each group.
+    pub const DEFAULT_GROUP_LEN: NonZeroUsize = NonZeroUsize::new(5).unwrap();
+
+    /// Creates a generator for keys of this shape, failing if the checksum
+    /// would leave no random characters.
+    pub fn new(
+        num_groups: NonZeroUsize,
+        group_len: NonZeroUsize,
+        checksum: LicenseChecksum,
+    ) -> Result<Self> {
+        let generator = LicenseKeyGenerator { num_groups, group_len, checksum };
+        if generator.num_chars() <= checksum.len() {
+            let msg = "license key has no room for random characters";
+            return Err(invalid_spec(msg));
+        }
+        Ok(generator)
+    }
+
+    /// Returns the number of groups.
+    pub fn num_groups(&self) -> NonZeroUsize {
+        self.num_groups
+    }
+
+    /// Returns the number of characters in each group.
+    pub fn group_length(&self) -> NonZeroUsize {
+        self.group_len
+    }
+
+    /// Returns the checksum.
+    pub fn checksum(&self) -> LicenseChecksum {
+        self.checksum
+    }
+
+    /// Returns the length of each key in characters, including separators.
+    pub fn length(&self) -> usize {
+        self.num_chars() + self.num_groups.get() - 1
+    }
+
+    fn num_chars(&self) -> usize {
+        self.num_groups.get() * self.group_len.get()
+    }
+
+    /// Returns whether a key has this generator's shape and a matching
+    /// checksum. Case, separators and the characters Crockford's Base32
+    /// reads as others (O as 0, I and L as 1) are forgiven, since keys are
+    /// typed by hand.
+    pub fn verify(&self, key: &str) -> bool {
+        let mut chars = Vec::new();
+        for ch in key.chars().filter(|&ch| ch != SEPARATOR) {
+            let byte =
+                match ch.to_ascii_uppercase() {
+                    'O' => b'0',
+                    'I' | 'L' => b'1',
+                    ch if ch.is_ascii() && DIGITS.contains(&(ch as u8)) =>
+                        ch as u8,
+                    _ => return false,
+                };
+            chars.push(byte);
+        }
+        if chars.len() != self.num_chars() {
+            return false;
+        }
+        let (payload, check) =
+            chars.split_at(chars.len() - self.checksum.len());
+        self.checksum.compute(payload) == check
+    }
+}
+
+impl Generator for LicenseKeyGenerator {
+    fn generate(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+    ) -> Result<()> {
+        let num_random = self.num_chars() - self.checksum.len();
+        let mut chars = SecretBuf::zeroed(self.num_chars());
+        sample(rng, DIGITS, &mut chars[..num_random])?;
+        let check = self.checksum.compute(&chars[..num_random]);
+        chars[num_random..].copy_from_slice(&check);
+        push_grouped(out, &chars, self.group_len.get());
+        Ok(())
+    }
+
+    fn entropy_bits(&self) -> f64 {
+        entropy_bits(DIGITS, self.num_chars() - self.checksum.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::ChaCha20Rng;
+    use rand::SeedableRng;
+
+    fn generator(checksum: LicenseChecksum) -> LicenseKeyGenerator {
+        let four = NonZeroUsize::new(4).unwrap();
+        LicenseKeyGenerator::new(four, four, checksum).unwrap()
+    }
+
+    // 7992739871 has the check digit 3 in the decimal Luhn algorithm.
+    #[test]
+    fn luhn_check_value_matches_decimal_luhn() {
+        let digits = [7, 9, 9, 2, 7, 3, 9, 8, 7, 1];
+        assert_eq!(luhn_check_value(digits.into_iter(), 10), 3);
+        assert_eq!(luhn_check_value([0].into_iter(), 10), 0);
+    }
+
+    #[test]
+    fn keys_end_with_their_checksum() {
+        let luhn = generator(LicenseChecksum::LuhnModN);
+        assert!(luhn.verify("ABCD-EFGH-JKMN-PQRM"));
+        let crc = generator(LicenseChecksum::Crc);
+        // The low 20 bits of the CRC-32 of "0123456789AB".
+        assert!(crc.verify("0123-4567-89AB-KKAR"));
+    }
+
+    #[test]
+    fn typing_slips_are_forgiven() {
+        let luhn = generator(LicenseChecksum::LuhnModN);
+        assert!(luhn.verify("abcdefghjkmnpqrm"));
+        let crc = generator(LicenseChecksum::Crc);
+        assert!(crc.verify("o123-4567-89ab-kkar"));
+        assert!(crc.verify("O123-4567-89AB-KKAR"));
+    }
+
+    #[test]
+    fn tampered_keys_are_rejected() {
+        for checksum in [LicenseChecksum::LuhnModN, LicenseChecksum::Crc] {
+            let generator = generator(checksum);
+            let mut rng = ChaCha20Rng::seed_from_u64(0);
+            let mut key = String::new();
+            generator.generate(&mut rng, &mut key).unwrap();
+            assert_eq!(key.len(), generator.length());
+            assert!(generator.verify(&key));
+            // Every single-character change.
+            for (idx, original) in key.char_indices() {
+                if original == SEPARATOR {
+                    continue;
+                }
+                for &digit in DIGITS {
+                    let digit = char::from(digit);
+                    if digit == original {
+                        continue;
+                    }
+                    let mut tampered = key.clone();
+                    tampered.replace_range(idx..idx + 1, &digit.to_string());
+                    assert!(!generator.verify(&tampered), "{}", tampered);
+                }
+            }
+            assert!(!generator.verify(&key[..key.len() - 1]));
+            assert!(!generator.verify(&format!("{}0", key)));
+            assert!(!generator.verify(&key.replacen('-', "U", 1)));
+        }
+    }
+
+    #[test]
+    fn checksum_must_leave_random_characters() {
+        let one = NonZeroUsize::new(1).unwrap();
+        let four = NonZeroUsize::new(4).unwrap();
+        let crc = LicenseChecksum::Crc;
+        assert!(LicenseKeyGenerator::new(one, four, crc).is_err());
+        let luhn = LicenseChecksum::LuhnModN;
+        assert!(LicenseKeyGenerator::new(one, one, luhn).is_err());
+    }
+}
@@ -0,0 +1,381 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::i18n::trf;
+
+// Limits are a count in a window, as in 60/m or 1000/12h. Rates are kept
+// with token buckets holding a window's worth of requests, refilled evenly,
+// so that bursts up to the limit are allowed. Quotas are counted in fixed
+// windows, each starting with the first password generated in it, so that
+// no more than the quota is ever generated in one.
+
+/// A count allowed in each window.
+#[derive(Copy, Clone, Debug)]
+pub struct Limit {
+    pub count: u64,
+    pub window: Duration,
+}
+
+impl Limit {
+    /// Parses a count in a window such as 60/m or 1000/12h, of seconds,
+    /// minutes, hours or days with a suffix of s, m, h or d. Without a
+    /// window, the count is a minute's.
+    #[cfg(any(unix, feature = "server"))]
+    pub fn parse(limit_str: &str) -> Result<Self> {
+        let err = || anyhow!(trf("invalid limit: \"{}\"", &[&limit_str]));
+        let (count, window) =
+            limit_str.split_once('/').unwrap_or((limit_str, "m"));
+        let count = count.parse::<u64>().map_err(|_| err())?;
+        let unit_secs =
+            match window.char_indices().last() {
+                Some((_, 's')) => 1,
+                Some((_, 'm')) => 60,
+                Some((_, 'h')) => 60 * 60,
+                Some((_, 'd')) => 24 * 60 * 60,
+                _ => return Err(err()),
+            };
+        let num_units =
+            match &window[..window.len() - 1] {
+                "" => 1,
+                num_units => num_units.parse::<u64>().map_err(|_| err())?,
+            };
+        let window_secs =
+            num_units.checked_mul(unit_secs).ok_or_else(err)?;
+        if count == 0 || window_secs == 0 {
+            return Err(err());
+        }
+        Ok(Limit { count, window: Duration::from_secs(window_secs) })
+    }
+}
+
+/// A token bucket holding a window's worth of requests.
+pub struct RateLimiter {
+    capacity: f64,
+    tokens: f64,
+    // Tokens added per second.
+    refill: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    #[cfg(any(unix, feature = "server"))]
+    pub fn new(limit: Limit) -> Self {
+        let capacity = limit.count as f64;
+        RateLimiter {
+            capacity,
+            tokens: capacity,
+            refill: capacity / limit.window.as_secs_f64(),
+            updated: Instant::now(),
+        }
+    }
+
+    /// Takes a token, or returns how long until one is available.
+    pub fn take(&mut self) -> Option<Duration> {
+        self.refill();
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return None;
+        }
+        Some(Duration::from_secs_f64((1.0 - self.tokens) / self.refill))
+    }
+
+    /// Returns whether the bucket is full, so that forgetting it loses
+    /// nothing.
+    #[cfg(feature = "server")]
+    pub fn is_full(&mut self) -> bool {
+        self.refill();
+        self.tokens >= self.capacity
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill).min(self.capacity);
+        self.updated = now;
+    }
+}
+
+/// Counts the passwords generated for a client, refusing any over its
+/// quota, if it has one.
+pub struct Quota {
+    limit: Option<Limit>,
+    // The passwords generated in the window, which started at the time.
+    used: u64,
+    started: Instant,
+    generated: u64,
+    refused: u64,
+}
+
+impl Quota {
+    #[cfg(any(unix, feature = "server"))]
+    pub fn new(limit: Option<Limit>) -> Self {
+        Quota {
+            limit,
+            used: 0,
+            started: Instant::now(),
+            generated: 0,
+            refused: 0,
+        }
+    }
+
+    /// Counts the passwords about to be generated, failing with
+    /// `LimitExceeded` if they would take the client over its quota.
+    pub fn take(&mut self, count: u64) -> Result<()> {
+        if let Some(limit) = self.limit {
+            if count > limit.count {
+                self.refused += 1;
+                let msg = trf(
+                    "the quota allows at most {} passwords at once",
+                    &[&limit.count],
+                );
+                return Err(anyhow!(msg));
+            }
+            if self.used == 0 || self.started.elapsed() >= limit.window {
+                self.used = 0;
+                self.started = Instant::now();
+            }
+            if self.used + count > limit.count {
+                self.refused += 1;
+                let retry_after =
+                    limit.window.saturating_sub(self.started.elapsed());
+                return Err(LimitExceeded { retry_after, quota: true }.into());
+            }
+            self.used += count;
+        }
+        self.generated += count;
+        Ok(())
+    }
+
+    /// Returns the counts of passwords generated and refused so far.
+    pub fn status(&self) -> QuotaStatus {
+        let (quota, remaining) =
+            match self.limit {
+                Some(limit)
+                    if self.used == 0
+                        || self.started.elapsed() >= limit.window =>
+                {
+                    (Some(limit.count), Some(limit.count))
+                },
+                Some(limit) =>
+                    (Some(limit.count), Some(limit.count - self.used)),
+                None => (None, None),
+            };
+        QuotaStatus {
+            generated: self.generated,
+            refused: self.refused,
+            quota,
+            remaining,
+        }
+    }
+}
+
+/// What a quota has counted.
+#[derive(Serialize)]
+pub struct QuotaStatus {
+    pub generated: u64,
+    // The requests refused for going over the quota.
+    pub refused: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quota: Option<u64>,
+    // What is left of the quota in the current window.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining: Option<u64>,
+}
+
+/// The error for a request over a rate limit or quota.
+#[derive(Debug)]
+pub struct LimitExceeded {
+    pub retry_after: Duration,
+    // Whether the request was over a quota rather than a rate.
+    pub quota: bool,
+}
+
+impl LimitExceeded {
+    /// Returns the whole seconds to wait before trying again.
+    pub fn retry_after_secs(&self) -> u64 {
+        self.retry_after.as_secs() + 1
+    }
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg =
+            if self.quota {
+                "quota exceeded; try again in {} seconds"
+            } else {
+                "too many requests; try again in {} seconds"
+            };
+        write!(f, "{}", trf(msg, &[&self.retry_after_secs()]))
+    }
+}
+
+impl Error for LimitExceeded {}
+
+/// The limits on a daemon, whose clients are all its owner's and so share
+/// them, with counts of its requests.
+pub struct Limits {
+    rate: Option<RateLimiter>,
+    pub quota: Quota,
+    requests: u64,
+    rate_limited: u64,
+}
+
+impl Limits {
+    // Only the daemon, which is built for Unix alone, keeps its limits
+    // together.
+    #[cfg(unix)]
+    pub fn new(rate: Option<Limit>, quota: Option<Limit>) -> Self {
+        Limits {
+            rate: rate.map(RateLimiter::new),
+            quota: Quota::new(quota),
+            requests: 0,
+            rate_limited: 0,
+        }
+    }
+
+    /// Counts a request, failing with `LimitExceeded` if it is over the
+    /// rate.
+    pub fn admit(&mut self) -> Result<()> {
+        self.requests += 1;
+        let wait = self.rate.as_mut().and_then(RateLimiter::take);
+        if let Some(retry_after) = wait {
+            self.rate_limited += 1;
+            return Err(LimitExceeded { retry_after, quota: false }.into());
+        }
+        Ok(())
+    }
+
+    pub fn status(&self) -> LimitsStatus {
+        LimitsStatus {
+            requests: self.requests,
+            rate_limited: self.rate_limited,
+            quota: self.quota.status(),
+        }
+    }
+}
+
+/// What a daemon's limits have counted.
+#[derive(Serialize)]
+pub struct LimitsStatus {
+    pub requests: u64,
+    pub rate_limited: u64,
+    #[serde(flatten)]
+    pub quota: QuotaStatus,
+}
+
+#[cfg(all(test, any(unix, feature = "server")))]
+mod tests {
+    use super::*;
+
+    fn limit(limit_str: &str) -> Limit {
+        Limit::parse(limit_str).unwrap()
+    }
+
+    // Moves the start of the quota's window back by its length, as if it
+    // had passed.
+    fn end_window(quota: &mut Quota) {
+        let window = quota.limit.unwrap().window;
+        quota.started = Instant::now().checked_sub(window).unwrap();
+    }
+
+    #[test]
+    fn limits_are_parsed() {
+        for (limit_str, count, secs) in [
+            ("60", 60, 60),
+            ("60/m", 60, 60),
+            ("5/s", 5, 1),
+            ("1000/12h", 1000, 12 * 60 * 60),
+            ("3/2d", 3, 2 * 24 * 60 * 60),
+        ] {
+            let limit = limit(limit_str);
+            assert_eq!(limit.count, count, "{}", limit_str);
+            let window = Duration::from_secs(secs);
+            assert_eq!(limit.window, window, "{}", limit_str);
+        }
+        for limit_str in
+            ["", "0/m", "5/0m", "5/", "5/w", "-1/m", "5/1.5h", "m", "5/ms"]
+        {
+            assert!(Limit::parse(limit_str).is_err(), "{}", limit_str);
+        }
+        assert!(Limit::parse(&format!("1/{}d", u64::MAX)).is_err());
+    }
+
+    #[test]
+    fn rate_allows_a_burst_then_waits_for_refills() {
+        let mut rate = RateLimiter::new(limit("3/h"));
+        for _ in 0..3 {
+            assert_eq!(rate.take(), None);
+        }
+        // A token comes every 20 minutes.
+        let wait = rate.take().unwrap();
+        assert!(wait <= Duration::from_secs(20 * 60));
+        assert!(wait > Duration::from_secs(20 * 60 - 5));
+        rate.tokens = 0.5;
+        let wait = rate.take().unwrap();
+        assert!(wait <= Duration::from_secs(10 * 60));
+        assert!(wait > Duration::from_secs(10 * 60 - 5));
+    }
+
+    #[test]
+    fn quota_refuses_passwords_over_it_until_the_window_ends() {
+        let mut quota = Quota::new(Some(limit("10/h")));
+        quota.take(4).unwrap();
+        quota.take(6).unwrap();
+        let err = quota.take(1).unwrap_err();
+        let exceeded = err.downcast_ref::<LimitExceeded>().unwrap();
+        assert!(exceeded.quota);
+        assert!(exceeded.retry_after_secs() <= 60 * 60);
+        assert!(exceeded.retry_after_secs() > 60 * 60 - 5);
+        let status = quota.status();
+        assert_eq!(status.generated, 10);
+        assert_eq!(status.refused, 1);
+        assert_eq!(status.quota, Some(10));
+        assert_eq!(status.remaining, Some(0));
+        end_window(&mut quota);
+        assert_eq!(quota.status().remaining, Some(10));
+        quota.take(3).unwrap();
+        assert_eq!(quota.status().remaining, Some(7));
+        assert_eq!(quota.status().generated, 13);
+    }
+
+    #[test]
+    fn requests_larger_than_the_quota_are_refused_outright() {
+        let mut quota = Quota::new(Some(limit("10/h")));
+        let err = quota.take(11).unwrap_err();
+        assert!(err.downcast_ref::<LimitExceeded>().is_none());
+        let status = quota.status();
+        assert_eq!(status.refused, 1);
+        assert_eq!(status.generated, 0);
+        assert_eq!(status.remaining, Some(10));
+    }
+
+    #[test]
+    fn without_a_quota_passwords_are_only_counted() {
+        let mut quota = Quota::new(None);
+        quota.take(u64::MAX / 2).unwrap();
+        quota.take(1).unwrap();
+        let status = quota.status();
+        assert_eq!(status.generated, u64::MAX / 2 + 1);
+        assert_eq!((status.quota, status.remaining), (None, None));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn limits_count_requests_and_those_over_the_rate() {
+        let mut limits = Limits::new(Some(limit("2/m")), None);
+        limits.admit().unwrap();
+        limits.admit().unwrap();
+        let err = limits.admit().unwrap_err();
+        assert!(!err.downcast_ref::<LimitExceeded>().unwrap().quota);
+        let status = limits.status();
+        assert_eq!((status.requests, status.rate_limited), (3, 1));
+        let mut limits = Limits::new(None, None);
+        for _ in 0..100 {
+            limits.admit().unwrap();
+        }
+        assert_eq!(limits.status().rate_limited, 0);
+    }
+}
@@ -0,0 +1,161 @@
+use rand_core::CryptoRngCore;
+
+use crate::generator::Generator;
+use crate::{invalid_spec, rng_failure, Result};
+
+const MULTICAST_BIT: u8 = 0x01;
+const LOCAL_BIT: u8 = 0x02;
+const MAC_LEN: usize = 17;
+
+/// Generates random unicast MAC addresses, written as six lowercase
+/// hexadecimal pairs separated by colons.
+#[derive(Clone, Debug)]
+pub struct MacGenerator {
+    vendor_prefix: Option<[u8; 3]>,
+    locally_administered: bool,
+}
+
+impl MacGenerator {
+    /// Creates a generator for addresses that start with the vendor prefix,
+    /// if one is given, and are marked as locally administered if asked.
+    /// Fails if the prefix is a multicast prefix or is not locally
+    /// administered when the addresses must be.
+    pub fn new(
+        vendor_prefix: Option<[u8; 3]>,
+        locally_administered: bool,
+    ) -> Result<Self> {
+        if let Some(prefix) = vendor_prefix {
+            if prefix[0] & MULTICAST_BIT != 0 {
+                return Err(invalid_spec("vendor prefix is a multicast prefix"));
+            }
+            if locally_administered && prefix[0] & LOCAL_BIT == 0 {
+                let msg = "vendor prefix is not locally administered";
+                return Err(invalid_spec(msg));
+            }
+        }
+        Ok(MacGenerator { vendor_prefix, locally_administered })
+    }
+
+    /// Parses a vendor prefix of three hexadecimal pairs separated by colons
+    /// or hyphens.
+    pub fn parse_prefix(prefix_str: &str) -> Result<[u8; 3]> {
+        let err = || invalid_spec(format!("invalid prefix: {:?}", prefix_str));
+        let mut prefix = [0u8; 3];
+        let mut parts = prefix_str.split([':', '-']);
+        for byte in prefix.iter_mut() {
+            let part = parts.next().ok_or_else(err)?;
+            if part.len() != 2 {
+                return Err(err());
+            }
+            *byte = u8::from_str_radix(part, 16).map_err(|_| err())?;
+        }
+        if parts.next().is_some() {
+            return Err(err());
+        }
+        Ok(prefix)
+    }
+
+    /// Returns the vendor prefix, if any.
+    pub fn vendor_prefix(&self) -> Option<[u8; 3]> {
+        self.vendor_prefix
+    }
+
+    /// Returns whether the addresses are marked as locally administered.
+    pub fn locally_administered(&self) -> bool {
+        self.locally_administered
+    }
+
+    /// Returns the length of each address in characters.
+    pub fn length(&self) -> usize {
+        MAC_LEN
+    }
+}
+
+impl Generator for MacGenerator {
+    fn generate(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+    ) -> Result<()> {
+        let mut bytes = [0u8; 6];
+        rng.try_fill_bytes(&mut bytes).map_err(rng_failure)?;
+        match self.vendor_prefix {
+            Some(prefix) => bytes[..3].copy_from_slice(&prefix),
+            None => {
+                bytes[0] &= !(MULTICAST_BIT | LOCAL_BIT);
+                if self.locally_administered {
+                    bytes[0] |= LOCAL_BIT;
+                }
+            },
+        }
+        for (idx, byte) in bytes.iter().enumerate() {
+            if idx > 0 {
+                out.push(':');
+            }
+            out.push_str(&format!("{:02x}", byte));
+        }
+        Ok(())
+    }
+
+    fn entropy_bits(&self) -> f64 {
+        match self.vendor_prefix {
+            Some(_) => 24.0,
+            None => 46.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::ChaCha20Rng;
+    use rand::SeedableRng;
+
+    fn first_octets(generator: &MacGenerator) -> Vec<u8> {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        (0..256)
+            .map(|_| {
+                let mut mac = String::new();
+                generator.generate(&mut rng, &mut mac).unwrap();
+                assert_eq!(mac.len(), MAC_LEN);
+                u8::from_str_radix(&mac[..2], 16).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn addresses_are_unicast_and_locally_administered_if_asked() {
+        let local = MacGenerator::new(None, true).unwrap();
+        for octet in first_octets(&local) {
+            assert_eq!(octet & (MULTICAST_BIT | LOCAL_BIT), LOCAL_BIT);
+        }
+        let universal = MacGenerator::new(None, false).unwrap();
+        for octet in first_octets(&universal) {
+            assert_eq!(octet & (MULTICAST_BIT | LOCAL_BIT), 0);
+        }
+    }
+
+    #[test]
+    fn vendor_prefix_is_kept() {
+        let prefix = MacGenerator::parse_prefix("02-00-5e").unwrap();
+        assert_eq!(prefix, [0x02, 0x00, 0x5e]);
+        let generator = MacGenerator::new(Some(prefix), true).unwrap();
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let mut mac = String::new();
+        generator.generate(&mut rng, &mut mac).unwrap();
+        assert!(mac.starts_with("02:00:5e:"));
+        assert_eq!(generator.entropy_bits(), 24.0);
+    }
+
+    #[test]
+    fn unsuitable_prefixes_are_rejected() {
+        let multicast = MacGenerator::parse_prefix("01:00:5e").unwrap();
+        assert!(MacGenerator::new(Some(multicast), false).is_err());
+        let universal = MacGenerator::parse_prefix("00:1a:2b").unwrap();
+        assert!(MacGenerator::new(Some(universal), false).is_ok());
+        assert!(MacGenerator::new(Some(universal), true).is_err());
+        for prefix in ["00:1a", "00:1a:2b:3c", "0:1a:2b", "00:1a:zz"] {
+            assert!(MacGenerator::parse_prefix(prefix).is_err(), "{}", prefix);
+        }
+    }
+}
@@ -1,76 +1,5471 @@
-mod lib;
-
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
+use indicatif::{ProgressBar, ProgressStyle};
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use terminal_size::{terminal_size, Width};
+use tracing::debug;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::field::MakeExt;
+use tracing_subscriber::fmt::format::debug_fn;
+use zeroize::Zeroizing;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+#[cfg(feature = "server")]
+use std::net::{SocketAddr, TcpListener};
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::audit::{AuditEntry, AuditLog};
+use crate::config::ConfigFile;
+use crate::crypt::sha512_crypt;
+use crate::encrypt::{encrypt_credential, Encryptor};
+use crate::bitwarden::{LoginItem, Vault};
+use crate::breaches::{Appearance, BreachList};
+use crate::console::Utf8Console;
+use crate::hibp::PwnedPasswords;
+use crate::i18n::{tr, trf};
+use crate::kdbx::{Database, NewEntry};
+use crate::keyring::KeyringEntry;
+use crate::label::Label;
+#[cfg(any(unix, feature = "server"))]
+use crate::limits::Limit;
+use crate::limits::{Limits, LimitsStatus, Quota};
+use crate::pass::PasswordStore;
+use crate::postscript::{PostScript, Script};
+use crate::profiles::{Profile, Profiles};
+use crate::usage::{Arg, Word};
+use crate::vault::KvSecret;
+
+mod ansible;
+mod audit;
+mod base64;
+mod bitwarden;
+mod breaches;
+mod bulk;
+mod config;
+mod confusables;
+mod console;
+mod crypt;
+#[cfg(unix)]
+mod daemon;
+mod encrypt;
+mod generators;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod harden;
+mod hibp;
+mod i18n;
+mod import;
+mod kdbx;
+mod keyfile;
+mod keyring;
+mod label;
+mod limits;
+mod native_host;
+mod pass;
+mod postscript;
+mod profiles;
+mod pwquality;
+mod sandbox;
+#[cfg(feature = "server")]
+mod server;
+mod similarity;
+mod tty;
+mod usage;
+mod vault;
+
+use passgen::health::{self, HealthTestedRng, HealthTests};
+use passgen::output::{
+    write_speakable, Braille, Columns, Format, LargePrint, Records, Spelling,
+    BRAILLE_LINE_WIDTH,
+};
+use passgen::selftest;
+use passgen::{
+    entropy_bits,
+    estimate_entropy,
+    estimate_guesses_with,
+    format_charset_spec,
+    derive_card,
+    derive_password,
+    parse_charset_spec,
+    recover_secret,
+    registered_generator,
+    registered_generators,
+    split_secret,
+    wifi_qr_payload,
+    wpa_psk,
+    ApiKeyGenerator,
+    AttackerProfile,
+    ChaChaDrbg,
+    CharClass,
+    Charset,
+    ComposedGenerator,
+    DerivationVersion,
+    Dictionaries,
+    EntropyMixer,
+    EntropySource,
+    EntropyToken,
+    FallbackRng,
+    Generator,
+    GuessEstimate,
+    HwRng,
+    IdAlphabet,
+    IdGenerator,
+    LicenseChecksum,
+    LicenseKeyGenerator,
+    MacGenerator,
+    OtpCodeGenerator,
+    PassgenError,
+    PassphraseGenerator,
+    PasswordCard,
+    PasswordSpec,
+    PasswordSpecBuilder,
+    Passwords,
+    PinGenerator,
+    Placement,
+    Policy,
+    Preset,
+    RngFailurePolicy,
+    Share,
+    SharedGenerator,
+    Strengthener,
+    Transform,
+    Transformed,
+    TokenEncoding,
+    TokenGenerator,
+    UsernameGenerator,
+    UsernameStyle,
+    UuidGenerator,
+    UuidVersion,
+    MAX_SSID_LEN,
+    WPA_PASSPHRASE_LEN,
+};
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+use passgen::RdSeed;
+#[cfg(feature = "yubikey")]
+use passgen::YubiKey;
 
-use lib::generate;
+const DEFAULT_PASSWORD_LEN: NonZeroUsize = NonZeroUsize::new(24).unwrap();
+const DEFAULT_NUM_PASSWORDS: NonZeroUsize = NonZeroUsize::MIN;
 
-const USAGE: &str = r#"
-  Generates random passwords.
+const WEAK_ENTROPY_BITS: f64 = 64.0;
+const PROGRESS_THRESHOLD: usize = 100_000;
+const DEFAULT_LINE_WIDTH: usize = 80;
+const DEFAULT_PIN_LEN: NonZeroUsize = NonZeroUsize::new(6).unwrap();
+const DEFAULT_STRENGTHEN_BITS: f64 = 80.0;
+const DEFAULT_CARD_COLUMNS: NonZeroUsize = NonZeroUsize::new(26).unwrap();
+const DEFAULT_NUM_OTP_CODES: NonZeroUsize = NonZeroUsize::new(10).unwrap();
+const DEFAULT_BLOOM_FP_RATE: f64 = 0.001;
+// The length RFC 4226 recommends for HOTP and TOTP secrets.
+const TOTP_SECRET_LEN: NonZeroUsize = NonZeroUsize::new(20).unwrap();
+const DEFAULT_WIFI_PASSPHRASE_LEN: NonZeroUsize =
+    NonZeroUsize::new(24).unwrap();
+// Letters and digits only, so that the passphrase can be typed on a
+// television remote and needs no escaping in a QR code.
+const WIFI_CHARSET_SPEC: &str = "a-zA-Z0-9";
+const TOKEN_ENTROPY_BYTES: usize = 64;
+const HEALTH_STARTUP_SAMPLES: usize = 1024;
+// Edits between passwords that audit counts as near-duplicates.
+const DEFAULT_SIMILAR_DISTANCE: usize = 2;
+const DEFAULT_DISTRIBUTION_SAMPLES: NonZeroUsize =
+    NonZeroUsize::new(1_000_000).unwrap();
+// The chance of the distribution test failing a uniform generator.
+const DISTRIBUTION_SIGNIFICANCE: f64 = 0.001;
+// What a string kept in a collection costs beyond its characters: the
+// String itself, its slot and the allocator's bookkeeping.
+const HELD_STRING_OVERHEAD: u64 = 64;
+// Room for a field's name, quoting and separator in collected output.
+const RECORD_FIELD_OVERHEAD: u64 = 32;
+#[cfg(feature = "server")]
+const DEFAULT_LISTEN: &str = "127.0.0.1:7878";
+#[cfg(feature = "grpc")]
+const DEFAULT_GRPC_LISTEN: &str = "127.0.0.1:7879";
+// More than any page or script needs.
+const MAX_REQUESTED_PASSWORDS: usize = 1000;
+const DEFAULT_PASSPHRASE_WORDS: usize = 6;
+const MAX_PASSPHRASE_WORDS: usize = 100;
+const DEFAULT_PASSPHRASE_SEPARATOR: &str = "-";
+#[cfg(feature = "yubikey")]
+const YUBIKEY_SLOT: u8 = 2;
 
-  Options:
-    -c charset_spec     use this character set
-    -l password_len     generate passwords of this length (default 24)
-    -n num_password     generate this many passwords (default 1)
+const EXIT_FAILURE: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_POLICY: i32 = 3;
+const EXIT_ENTROPY: i32 = 4;
+const EXIT_RNG: i32 = 5;
+const EXIT_IO: i32 = 6;
+const EXIT_HEALTH: i32 = 7;
 
-  The charset specification language is a subset of the character set language
-  for regular expressions. Only characters and ranges are allowed. Literal
-  hyphens and backslashes must be escaped. Other characters must not be
-  escaped. An initial caret may be used to invert the character set with
-  respect to typeable ASCII characters.
-"#;
+#[derive(Debug)]
+struct UsageError(anyhow::Error);
 
-const DEFAULT_PASSWORD_LEN: usize = 24;
-const DEFAULT_NUM_PASSWORDS: usize = 1;
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#}", self.0)
+    }
+}
+
+impl Error for UsageError {}
 
 fn main() {
-    if let Err(msg) = run() {
-        eprintln!("{}", msg);
+    init_logging();
+    // The console is restored before exiting, which skips destructors.
+    let result = {
+        let _console = Utf8Console::new();
+        run()
+    };
+    if let Err(err) = result {
+        let broken_pipe =
+            err.downcast_ref::<io::Error>()
+                .is_some_and(|err| err.kind() == io::ErrorKind::BrokenPipe);
+        if !broken_pipe {
+            eprintln!("{}", error_message(&err));
+        }
+        process::exit(exit_code(&err));
+    }
+}
+
+fn error_message(err: &anyhow::Error) -> String {
+    let mut messages = Vec::new();
+    for cause in err.chain() {
+        if let Some(UsageError(err)) = cause.downcast_ref::<UsageError>() {
+            messages.push(error_message(err));
+            break;
+        }
+        if let Some(err) = cause.downcast_ref::<PassgenError>() {
+            messages.push(i18n::passgen_error(err));
+            break;
+        }
+        messages.push(cause.to_string());
+    }
+    messages.join(": ")
+}
+
+// Fields that could hold generated secrets. Their values are never written
+// to the log, whatever is recorded at the call site.
+const REDACTED_FIELDS: &[&str] = &["password", "candidate", "secret"];
+
+fn init_logging() {
+    let fields =
+        debug_fn(|writer, field, value| {
+            if REDACTED_FIELDS.contains(&field.name()) {
+                write!(writer, "{}=[redacted]", field)
+            } else if field.name() == "message" {
+                write!(writer, "{:?}", value)
+            } else {
+                write!(writer, "{}={:?}", field, value)
+            }
+        });
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(io::stderr)
+        .with_ansi(io::stderr().is_terminal())
+        .fmt_fields(fields.delimited(" "))
+        .init();
+}
+
+fn exit_code(err: &anyhow::Error) -> i32 {
+    if err.is::<UsageError>() {
+        return EXIT_USAGE;
+    }
+    if let Some(err) = err.downcast_ref::<PassgenError>() {
+        return match err {
+            PassgenError::InvalidSpec(_) => EXIT_USAGE,
+            PassgenError::PolicyViolation(_) => EXIT_POLICY,
+            PassgenError::InsufficientEntropy { .. } => EXIT_ENTROPY,
+            PassgenError::RngFailure(_) => EXIT_RNG,
+            PassgenError::HealthTestFailure(_) => EXIT_HEALTH,
+            PassgenError::SelfTestFailure(_) => EXIT_FAILURE,
+            PassgenError::AttemptsExhausted { .. } => EXIT_POLICY,
+        };
+    }
+    if err.is::<io::Error>() {
+        return EXIT_IO;
+    }
+    EXIT_FAILURE
+}
+
+fn warn(msg: &str) {
+    // The prefix is part of the output contract, so it is not translated.
+    eprintln!("warning: {}", msg);
+}
+
+struct Options {
+    charset_spec: Option<String>,
+    mode: Option<String>,
+    preset: Option<Preset>,
+    exclude_chars: Option<String>,
+    // The easily confused characters the profiles of --confusables
+    // exclude.
+    confusable_chars: Option<String>,
+    // The fields of each record given with --fields, with their
+    // generators.
+    fields: Option<Vec<(String, SharedGenerator)>>,
+    // The classes' shares given with --compose.
+    composition: Option<String>,
+    min_len: Option<NonZeroUsize>,
+    max_len: Option<NonZeroUsize>,
+    num_passwords: Option<NonZeroUsize>,
+    verbose: bool,
+    dry_run: bool,
+    min_entropy: Option<f64>,
+    batch: bool,
+    quiet: bool,
+    prompt: bool,
+    layout: Option<Layout>,
+    accessible: Option<Accessible>,
+    stats: bool,
+    // The log --audit-log appends to, and the label of its entries and,
+    // in CSV and JSON, of each record.
+    audit_log: Option<PathBuf>,
+    label: Option<Label>,
+    // The most memory in bytes the job may hold, from --max-memory.
+    max_memory: Option<u64>,
+    // The threads bulk jobs are generated on, one if not known.
+    threads: Option<NonZeroUsize>,
+    // Limits on drawing candidates for each password.
+    max_attempts: Option<NonZeroU64>,
+    timeout: Option<Duration>,
+    force: bool,
+    no_harden: bool,
+    sandbox: Option<bool>,
+    encrypt_to: Option<String>,
+    split: Option<(u8, u8)>,
+    rng: RngKind,
+    rng_fallback: RngFailurePolicy,
+    extra_entropy: bool,
+    extra_input: Option<Zeroizing<Vec<u8>>>,
+    entropy_tokens: Vec<TokenKind>,
+    health_min_entropy: f64,
+    health_alpha_log2: f64,
+    health_window: usize,
+    token: Option<Token>,
+    transforms: Vec<Transform>,
+    // Compiled as the options are parsed, before the sandbox is installed.
+    post_script: Option<Script>,
+    format: Format,
+    // Whether --format ansible-vault was given, which prints the password
+    // as text before encrypting it.
+    ansible_vault: bool,
+    vault_password_file: Option<PathBuf>,
+    users_file: Option<PathBuf>,
+    // The usernames read from the file, one per password.
+    users: Option<Vec<String>>,
+    hash: bool,
+    speakable: bool,
+    credentials: Option<Credentials>,
+    check_hibp: bool,
+    check_offline: Option<PathBuf>,
+    pass_entry: Option<PassEntry>,
+    store: Option<Store>,
+}
+
+enum Layout {
+    Columns,
+    Lines,
+}
+
+// Layouts for transcribing passwords, for users with low vision or over
+// radio or phone.
+#[derive(Clone, Copy)]
+enum Accessible {
+    LargePrint,
+    Braille,
+    Spelling,
+}
+
+impl Accessible {
+    fn option(self) -> &'static str {
+        match self {
+            Accessible::LargePrint => "--large-print",
+            Accessible::Braille => "--braille",
+            Accessible::Spelling => "--format spelling",
+        }
+    }
+}
+
+enum TokenKind {
+    YubiKey,
+    HwRng,
+    RdSeed,
+}
+
+impl TokenKind {
+    fn name(&self) -> &'static str {
+        match self {
+            TokenKind::YubiKey => "yubikey",
+            TokenKind::HwRng => "hwrng",
+            TokenKind::RdSeed => "rdseed",
+        }
+    }
+}
+
+enum Token {
+    Bytes(TokenGenerator),
+    Id(IdGenerator),
+    Uuid(UuidGenerator),
+    ApiKey(ApiKeyGenerator),
+    License(LicenseKeyGenerator),
+    Mac(MacGenerator),
+    Username(UsernameGenerator),
+    Otp(OtpCodeGenerator),
+    // A generator registered under the name.
+    Registered(String, SharedGenerator),
+}
+
+// Where the insert-pass command puts the password, and what it adds.
+struct PassEntry {
+    name: String,
+    in_place: bool,
+    fields: Vec<(String, String)>,
+}
+
+// Where --store saves generated secrets instead of printing them.
+enum Store {
+    Keyring(KeyringEntry),
+    Kdbx(PathBuf, StoreEntry),
+    Bitwarden(StoreEntry),
+    Vault(VaultEntry),
+    // The name of a systemd credential.
+    SystemdCreds(String),
+}
+
+impl Store {
+    fn kind(&self) -> &'static str {
+        match self {
+            Store::Keyring(_) => "keyring",
+            Store::Kdbx(..) => "kdbx",
+            Store::Bitwarden(_) => "bitwarden",
+            Store::Vault(_) => "vault",
+            Store::SystemdCreds(_) => "systemd-creds",
+        }
+    }
+}
+
+// A store opened or checked before any password is generated for it.
+enum OpenStore {
+    Kdbx(Database),
+    Bitwarden(Vault),
+    Vault(KvSecret),
+}
+
+// The entry --store kdbx adds to a KeePass database, or --store bitwarden
+// to a Bitwarden vault, for each password.
+struct StoreEntry {
+    title: String,
+    username: Option<String>,
+    uri: Option<String>,
+}
+
+// The key --store vault writes under in the secret at the path.
+struct VaultEntry {
+    path: String,
+    key: String,
+}
+
+// What the credentials command generates alongside each password.
+struct Credentials {
+    username: UsernameGenerator,
+    totp: Option<TokenGenerator>,
+}
+
+enum RngKind {
+    Os,
+    ChaCha,
+}
+
+impl RngKind {
+    fn source(&self) -> EntropySource {
+        match self {
+            RngKind::Os => EntropySource::Os,
+            RngKind::ChaCha => EntropySource::OsSeeded,
+        }
     }
 }
 
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BatchSpec {
+    charset: Option<String>,
+    mode: Option<String>,
+    exclude: Option<String>,
+    length: Option<usize>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    count: Option<usize>,
+    min_entropy: Option<f64>,
+}
+
+// A request to the native messaging host or the daemon: with op generate,
+// the fields of a JSON specification for --batch, with op check, a password,
+// and with op passphrase, the number of words, separator and count.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NativeRequest {
+    // Returned in the response, so that it can be matched to the request.
+    id: Option<serde_json::Value>,
+    op: String,
+    password: Option<String>,
+    charset: Option<String>,
+    mode: Option<String>,
+    exclude: Option<String>,
+    length: Option<usize>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    count: Option<usize>,
+    min_entropy: Option<f64>,
+    words: Option<usize>,
+    separator: Option<String>,
+}
+
+enum NativeReply {
+    Generated {
+        passwords: Vec<Zeroizing<String>>,
+        entropy_bits: f64,
+    },
+    Checked {
+        entropy_bits: f64,
+        // Whether the password appears in breaches, if they are checked.
+        breached: Option<bool>,
+    },
+    Status(LimitsStatus),
+}
+
+// The response to a request to the native messaging host or the server.
+#[derive(Default, Serialize)]
+struct JsonResponse<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    passwords: Option<Vec<&'a str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entropy_bits: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    breached: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<&'a LimitsStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
 fn run() -> Result<()> {
-    let args = env::args().collect::<Vec<_>>();
-    let mut args = args.iter();
-    args.next();
-    let mut charset_spec = None;
-    let mut password_len = DEFAULT_PASSWORD_LEN;
-    let mut num_passwords = DEFAULT_NUM_PASSWORDS;
-    loop {
-        match (args.next().map(|s| s.as_str()), args.next()) {
-            (Some("-c"), Some(charset_spec_value)) => {
-                charset_spec = Some(charset_spec_value);
-            },
-            (Some("-l"), Some(password_len_str)) => {
-                password_len = password_len_str.parse::<usize>()?;
-                if password_len == 0 {
-                    let msg = "password length must not be zero";
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if let Some("selftest") = args.first().map(|arg| arg.as_str()) {
+        if let Some("--distribution") = args.get(1).map(|arg| arg.as_str()) {
+            return run_distribution(&args[2..]);
+        }
+        if args.len() > 1 {
+            return Err(UsageError(anyhow!(usage())).into());
+        }
+        return run_selftest();
+    }
+    if let Some("man") = args.first().map(|arg| arg.as_str()) {
+        if args.len() > 1 {
+            return Err(UsageError(anyhow!(usage())).into());
+        }
+        io::stdout().lock().write_all(usage::render_man().as_bytes())?;
+        return Ok(());
+    }
+    if let Some("derive") = args.first().map(|arg| arg.as_str()) {
+        return run_derive(&args[1..]);
+    }
+    if let Some("profiles") = args.first().map(|arg| arg.as_str()) {
+        return run_profiles(&args[1..]);
+    }
+    if let Some("config") = args.first().map(|arg| arg.as_str()) {
+        return run_config(&args[1..]);
+    }
+    if let Some("import") = args.first().map(|arg| arg.as_str()) {
+        return run_import(&args[1..]);
+    }
+    if let Some("generators") = args.first().map(|arg| arg.as_str()) {
+        return run_generators(&args[1..]);
+    }
+    if let Some("wifi") = args.first().map(|arg| arg.as_str()) {
+        return run_wifi(&args[1..]);
+    }
+    if let Some("pin") = args.first().map(|arg| arg.as_str()) {
+        return run_pin(&args[1..]);
+    }
+    if let Some("card") = args.first().map(|arg| arg.as_str()) {
+        return run_card(&args[1..]);
+    }
+    if let Some("audit") = args.first().map(|arg| arg.as_str()) {
+        return run_audit(&args[1..]);
+    }
+    if let Some("entropy") = args.first().map(|arg| arg.as_str()) {
+        return run_entropy(&args[1..]);
+    }
+    if let Some("score") = args.first().map(|arg| arg.as_str()) {
+        return run_score(&args[1..]);
+    }
+    if let Some("strengthen") = args.first().map(|arg| arg.as_str()) {
+        return run_strengthen(&args[1..]);
+    }
+    if let Some("hibp") = args.first().map(|arg| arg.as_str()) {
+        return run_hibp(&args[1..]);
+    }
+    if let Some("retrieve") = args.first().map(|arg| arg.as_str()) {
+        return run_retrieve(&args[1..]);
+    }
+    if let Some("keyfile") = args.first().map(|arg| arg.as_str()) {
+        return run_keyfile(&args[1..]);
+    }
+    if let Some("native-host") = args.first().map(|arg| arg.as_str()) {
+        return run_native_host(&args[1..]);
+    }
+    if let Some("serve") = args.first().map(|arg| arg.as_str()) {
+        return run_serve(&args[1..]);
+    }
+    if let Some("grpc") = args.first().map(|arg| arg.as_str()) {
+        return run_grpc(&args[1..]);
+    }
+    if let Some("daemon") = args.first().map(|arg| arg.as_str()) {
+        return run_daemon(&args[1..]);
+    }
+    if let [command, mode, ..] = &args[..] {
+        if command == "apikey" && mode == "verify" {
+            return run_apikey_verify(&args[2..]);
+        }
+        if command == "license" && mode == "verify" {
+            return run_license_verify(&args[2..]);
+        }
+    }
+    if let Some("recover") = args.first().map(|arg| arg.as_str()) {
+        if args.len() > 1 {
+            return Err(UsageError(anyhow!(usage())).into());
+        }
+        return run_recover();
+    }
+    let mut options =
+        match args.first().map(|arg| arg.as_str()) {
+            Some("token") => parse_token_args(&args[1..]),
+            Some("uuid") => parse_uuid_args(&args[1..]),
+            Some("apikey") => parse_apikey_args(&args[1..]),
+            Some("license") => parse_license_args(&args[1..]),
+            Some("mac") => parse_mac_args(&args[1..]),
+            Some("username") => parse_username_args(&args[1..]),
+            Some("otp") => parse_otp_args(&args[1..]),
+            Some("generator") => parse_generator_args(&args[1..]),
+            Some("credentials") => parse_credentials_args(&args[1..]),
+            Some("insert-pass") => parse_insert_pass_args(&args[1..]),
+            _ => parse_args(&args),
+        }
+        .map_err(UsageError)?;
+    check_store(&options).map_err(UsageError)?;
+    check_ansible_vault(&options).map_err(UsageError)?;
+    // Entries are filled in from the records read back as JSON.
+    if let Some(Store::Kdbx(..) | Store::Bitwarden(_)) = options.store {
+        options.format = Format::Json;
+    }
+    if !options.no_harden {
+        if let Err(err) = harden::harden() {
+            let msg = trf("could not disable core dumps: {}", &[&err]);
+            warn(&msg);
+        }
+    }
+    if let Some(path) = &options.users_file {
+        // The list is held whole, so it must fit before it is read.
+        if let Some(max_memory) = options.max_memory {
+            let metadata = fs::metadata(path).with_context(|| {
+                trf("could not read \"{}\"", &[&path.display()])
+            })?;
+            check_memory(metadata.len(), max_memory)?;
+        }
+        let users = read_users(path)?;
+        options.num_passwords = NonZeroUsize::new(users.len());
+        options.users = Some(users);
+    }
+    if options.prompt {
+        if options.batch {
+            let msg = tr("--prompt and --batch cannot be used together");
+            return Err(UsageError(anyhow!(msg)).into());
+        }
+        prompt_missing(&mut options)?;
+    }
+    if options.extra_entropy {
+        if options.batch {
+            let msg = tr("--extra-entropy and --batch cannot be used together");
+            return Err(UsageError(anyhow!(msg)).into());
+        }
+        options.extra_input = Some(read_extra_entropy()?);
+    }
+    if !options.entropy_tokens.is_empty() && options.batch {
+        let msg = tr("--entropy-source and --batch cannot be used together");
+        return Err(UsageError(anyhow!(msg)).into());
+    }
+    // Tokens are read before the sandbox is installed, since reading them
+    // may need devices or helper programs. Their output is only ever mixed
+    // into the operating system's generator, never used in its place.
+    for kind in &options.entropy_tokens {
+        let mut token = open_token(kind)?;
+        let mut bytes = Zeroizing::new(vec![0u8; TOKEN_ENTROPY_BYTES]);
+        token.fill(&mut bytes)?;
+        health_tests(&options).feed(&bytes)?;
+        options.extra_input
+            .get_or_insert_with(|| Zeroizing::new(Vec::new()))
+            .extend_from_slice(&bytes);
+    }
+    // Found before the sandbox is installed, which denies the system calls
+    // and files it needs.
+    options.threads = thread::available_parallelism().ok();
+    let mut encryptor =
+        match &options.encrypt_to {
+            Some(recipient) => Some(Encryptor::spawn(recipient)?),
+            None => None,
+        };
+    let line_width =
+        if encryptor.is_none() && io::stdout().is_terminal() {
+            let width = terminal_size().map(|(Width(width), _)| width as usize);
+            Some(width.unwrap_or(DEFAULT_LINE_WIDTH))
+        } else {
+            None
+        };
+    let mut stdout = io::stdout().lock();
+    // The breach list is mapped before the sandbox is installed, since
+    // opening files is not allowed afterwards.
+    let pwned =
+        if options.batch { None } else { breach_checker(&options)? };
+    // So is the audit log, which in batch mode takes the entries of every
+    // line.
+    let mut audit_log =
+        match &options.audit_log {
+            Some(path) if !options.dry_run => Some(AuditLog::open(path)?),
+            _ => None,
+        };
+    // The entry is checked before the password is generated, so that none
+    // is generated for an entry that cannot take it.
+    let pass_insert =
+        match &options.pass_entry {
+            Some(entry) if !options.dry_run => {
+                let store = PasswordStore::open()?;
+                let kept =
+                    store.prepare(&entry.name, entry.in_place, options.force)?;
+                Some((entry, store, kept))
+            },
+            _ => None,
+        };
+    if options.check_hibp && options.sandbox == Some(true) {
+        let msg = tr("--check-hibp and --sandbox cannot be used together");
+        return Err(UsageError(anyhow!(msg)).into());
+    }
+    if pass_insert.is_some() && options.sandbox == Some(true) {
+        let msg = tr("insert-pass and --sandbox cannot be used together");
+        return Err(UsageError(anyhow!(msg)).into());
+    }
+    let store = options.store.as_ref().filter(|_| !options.dry_run);
+    if store.is_some() && options.sandbox == Some(true) {
+        let msg = tr("--store and --sandbox cannot be used together");
+        return Err(UsageError(anyhow!(msg)).into());
+    }
+    // The vault password is read before the sandbox is installed, since a
+    // password file may be a script to run.
+    let vault_password =
+        match &options.vault_password_file {
+            Some(path) if !options.dry_run =>
+                Some(ansible::read_password(path)?),
+            _ => None,
+        };
+    // Like the password store, a database or vault is opened before any
+    // password is generated for it.
+    let mut open_store =
+        match store {
+            Some(Store::Kdbx(path, _)) =>
+                Some(OpenStore::Kdbx(open_kdbx(path)?)),
+            Some(Store::Bitwarden(_)) =>
+                Some(OpenStore::Bitwarden(Vault::open()?)),
+            Some(Store::Vault(entry)) => {
+                let secret = KvSecret::open(&entry.path, entry.key.clone())?;
+                Some(OpenStore::Vault(secret))
+            },
+            _ => None,
+        };
+    // Batch mode reads specifications as it goes, so it is only sandboxed
+    // on request. Checking passwords online needs the network, and
+    // inserting them into the password store, the keyring or a Bitwarden
+    // vault needs helper programs, and writing a database needs files.
+    let sandbox =
+        !options.batch
+            && !options.check_hibp
+            && pass_insert.is_none()
+            && store.is_none();
+    if options.sandbox.unwrap_or(sandbox) {
+        let child = encryptor.as_ref().map(Encryptor::id);
+        if let Err(err) = sandbox::install(child) {
+            let msg = trf("could not install the sandbox: {}", &[&err]);
+            warn(&msg);
+        }
+    }
+    let mut pass_buf = Zeroizing::new(Vec::new());
+    let out: &mut dyn Write =
+        match &mut encryptor {
+            Some(encryptor) => encryptor,
+            None
+                if pass_insert.is_some()
+                    || store.is_some()
+                    || vault_password.is_some() =>
+                &mut *pass_buf,
+            None => &mut stdout,
+        };
+    if !options.batch {
+        let interactive =
+            !options.force
+                && io::stdin().is_terminal()
+                && io::stderr().is_terminal();
+        execute(
+            &options,
+            out,
+            line_width,
+            interactive,
+            pwned.as_ref(),
+            audit_log.as_mut(),
+        )?;
+        if let Some((entry, store, kept)) = pass_insert {
+            insert_pass(entry, &store, kept, &pass_buf)?;
+            stdout.write_all(&pass_buf)?;
+        }
+        let secret = || std::str::from_utf8(&pass_buf);
+        match (store, &mut open_store) {
+            (Some(Store::Keyring(keyring)), _) => {
+                keyring.store(secret()?.trim_end_matches('\n'))?;
+            },
+            (Some(Store::Kdbx(_, entry)), Some(OpenStore::Kdbx(database))) =>
+                add_kdbx_entries(entry, database, &pass_buf)?,
+            (
+                Some(Store::Bitwarden(entry)),
+                Some(OpenStore::Bitwarden(vault)),
+            ) => add_bitwarden_items(entry, vault, &pass_buf)?,
+            (Some(Store::Vault(_)), Some(OpenStore::Vault(kv_secret))) =>
+                kv_secret.write(secret()?.trim_end_matches('\n'))?,
+            (Some(Store::SystemdCreds(name)), _) =>
+                encrypt_credential(name, secret()?.trim_end_matches('\n'))?,
+            _ => {},
+        }
+        if let Some(password) = &vault_password {
+            let secret = secret()?.trim_end_matches('\n');
+            let encrypted = ansible::encrypt_string(secret, password)?;
+            stdout.write_all(encrypted.as_bytes())?;
+        }
+    } else {
+        run_batch(&args, out, line_width, audit_log.as_mut())?;
+    }
+    if let Some(encryptor) = encryptor {
+        encryptor.finish()?;
+    }
+    Ok(())
+}
+
+fn run_batch(
+    args: &[String],
+    out: &mut dyn Write,
+    line_width: Option<usize>,
+    mut audit_log: Option<&mut AuditLog>,
+) -> Result<()> {
+    let defaults = args.iter().filter(|arg| *arg != "--batch");
+    for (idx, line) in io::stdin().lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let context = trf("line {}", &[&(idx + 1)]);
+        let mut line_args = defaults.clone().cloned().collect::<Vec<_>>();
+        line_args.extend(
+            parse_batch_line(&line)
+                .map_err(UsageError)
+                .context(context.clone())?,
+        );
+        let options =
+            parse_args(&line_args)
+                .map_err(UsageError)
+                .context(context.clone())?;
+        if options.batch {
+            let err = UsageError(anyhow!(tr("--batch cannot be nested")));
+            return Err(anyhow::Error::new(err).context(context));
+        }
+        if options.store.is_some() {
+            let msg = tr("--store and --batch cannot be used together");
+            let err = UsageError(anyhow!(msg));
+            return Err(anyhow::Error::new(err).context(context));
+        }
+        // The log is opened once, for every line.
+        if options.audit_log.is_some()
+            && audit_log.is_none()
+            && !options.dry_run
+        {
+            let msg = tr(
+                "in batch mode, --audit-log must be given on the command line",
+            );
+            let err = UsageError(anyhow!(msg));
+            return Err(anyhow::Error::new(err).context(context));
+        }
+        let pwned = breach_checker(&options).context(context.clone())?;
+        // Standard input holds the specifications, so there is no way to
+        // confirm weak requests.
+        execute(
+            &options,
+            out,
+            line_width,
+            false,
+            pwned.as_ref(),
+            audit_log.as_deref_mut(),
+        )
+        .context(context)?;
+    }
+    Ok(())
+}
+
+fn run_selftest() -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    let mut failed = false;
+    for check in selftest::run() {
+        match check.result {
+            Ok(()) => {
+                writeln!(stdout, "{}: ok", check.name)?;
+            },
+            Err(err) => {
+                writeln!(stdout, "{}: FAILED ({})", check.name, err)?;
+                failed = true;
+            },
+        }
+    }
+    if failed {
+        let msg = tr("self-test failed").to_string();
+        return Err(PassgenError::SelfTestFailure(msg).into());
+    }
+    Ok(())
+}
+
+// Draws many passwords from the spec the options describe and tests that
+// every character of its charset is drawn equally often, overall and at
+// each position.
+fn run_distribution(args: &[String]) -> Result<()> {
+    let mut options = parse_args(args).map_err(UsageError)?;
+    options.num_passwords.get_or_insert(DEFAULT_DISTRIBUTION_SAMPLES);
+    let spec = resolve(&options)?.build()?;
+    let policy = spec.policy();
+    if !policy.required.is_empty() || !policy.forbidden_at_ends.is_empty() {
+        let msg =
+            tr("--distribution needs a charset without required classes or \
+                characters forbidden at the ends, which skew it");
+        return Err(UsageError(anyhow!(msg)).into());
+    }
+    if spec.charset().len() < 2 {
+        let msg = tr("--distribution needs at least two characters");
+        return Err(UsageError(anyhow!(msg)).into());
+    }
+    let mut rng = os_rng()?;
+    let distribution =
+        selftest::Distribution::sample(&spec, &mut rng, spec.count().get())?;
+    let mut stdout = io::stdout().lock();
+    let total = distribution.counts.iter().sum::<u64>() as f64;
+    let expected = total / distribution.chars.len() as f64;
+    for (&byte, &count) in distribution.chars.iter().zip(&distribution.counts)
+    {
+        let deviation = (count as f64 / expected - 1.0) * 100.0;
+        writeln!(
+            stdout,
+            "{:?}: {} ({:+.2}%)",
+            char::from(byte),
+            count,
+            deviation,
+        )?;
+    }
+    let positions = distribution.positions();
+    // The significance is shared out among the tests, so that one of them
+    // failing by chance stays as unlikely however many positions there are.
+    let threshold = DISTRIBUTION_SIGNIFICANCE / (positions.len() + 1) as f64;
+    let tests =
+        std::iter::once(("overall".to_string(), distribution.overall()))
+            .chain(positions.into_iter().enumerate().map(|(idx, test)| {
+                (format!("position {}", idx + 1), test)
+            }));
+    let mut failed = false;
+    for (name, test) in tests {
+        let passed = test.p_value >= threshold;
+        failed |= !passed;
+        writeln!(
+            stdout,
+            "{}: chi-squared {:.1} with {} degrees of freedom, p = {:.4}: {}",
+            name,
+            test.statistic,
+            test.degrees_of_freedom,
+            test.p_value,
+            if passed { "ok" } else { "FAILED" },
+        )?;
+    }
+    if failed {
+        let msg = tr("self-test failed").to_string();
+        return Err(PassgenError::SelfTestFailure(msg).into());
+    }
+    Ok(())
+}
+
+struct DeriveArgs {
+    site: String,
+    login: String,
+    counter: Option<u32>,
+    profiles_path: Option<PathBuf>,
+    save: bool,
+    rest: Vec<String>,
+}
+
+fn run_derive(args: &[String]) -> Result<()> {
+    let args = parse_derive_args(args).map_err(UsageError)?;
+    let options = parse_args(&args.rest).map_err(UsageError)?;
+    if !options.no_harden {
+        if let Err(err) = harden::harden() {
+            let msg = trf("could not disable core dumps: {}", &[&err]);
+            warn(&msg);
+        }
+    }
+    let profiles_path = profiles_path(args.profiles_path)?;
+    let mut profiles = Profiles::load(&profiles_path)?;
+    let saved = profiles.find(&args.site, &args.login);
+    // A saved profile is used as is unless options override its settings,
+    // in which case the current derivation version applies.
+    let overridden =
+        options.charset_spec.is_some()
+            || options.mode.is_some()
+            || options.preset.is_some()
+            || options.exclude_chars.is_some()
+            || options.min_len.is_some()
+            || options.max_len.is_some();
+    let (generator, version) =
+        match saved {
+            Some(profile) if !overridden =>
+                (profile.generator.clone(), profile.version),
+            _ => {
+                let spec = resolve(&options).map_err(UsageError)?.build()?;
+                let version = DerivationVersion::CURRENT.number();
+                (spec.generator().clone(), version)
+            },
+        };
+    let counter =
+        args.counter
+            .or(saved.map(|profile| profile.counter))
+            .unwrap_or(1);
+    let DeriveArgs { site, login, .. } = args;
+    let profile = Profile { site, login, counter, version, generator };
+    let version = DerivationVersion::from_number(profile.version)?;
+    let master_password = read_master_password()?;
+    let password =
+        derive_password(
+            version,
+            master_password.as_bytes(),
+            &profile.context(),
+            &profile.generator,
+        )?;
+    writeln!(io::stdout().lock(), "{}", *Zeroizing::new(password))?;
+    if args.save {
+        profiles.insert(profile);
+        profiles.save(&profiles_path)?;
+    }
+    Ok(())
+}
+
+// A mistyped master password derives a different password without any
+// error, so it is asked for twice, as a new database password is.
+fn read_master_password() -> Result<Zeroizing<String>> {
+    let password = tty::read_hidden(tr("Master password"))?;
+    let repeated = tty::read_hidden(tr("Repeat the master password"))?;
+    if password != repeated {
+        return Err(anyhow!(tr("the passwords do not match")));
+    }
+    Ok(password)
+}
+
+// Separates the arguments specific to derivation from the options shared
+// with generation.
+fn parse_derive_args(args: &[String]) -> Result<DeriveArgs> {
+    let mut site = None;
+    let mut login = None;
+    let mut counter = None;
+    let mut profiles_path = None;
+    let mut save = false;
+    let mut rest = Vec::new();
+    for arg in read_args(&[usage::DERIVE], args)? {
+        match arg {
+            Arg::Flag("--site", value) => {
+                site = Some(value.to_string());
+            },
+            Arg::Flag("--login", value) => {
+                login = Some(value.to_string());
+            },
+            Arg::Flag("--counter", value) => {
+                counter = Some(parse_number(value)?);
+            },
+            Arg::Flag("--profiles", value) => {
+                profiles_path = Some(PathBuf::from(value));
+            },
+            Arg::Flag("--save", _) => {
+                save = true;
+            },
+            Arg::Other(arg) => {
+                rest.push(arg.clone());
+            },
+            Arg::Flag(..) => {
+                return Err(anyhow!(usage()));
+            },
+        }
+    }
+    let (Some(site), Some(login)) = (site, login) else {
+        return Err(anyhow!(tr("derive needs both --site and --login")));
+    };
+    Ok(DeriveArgs { site, login, counter, profiles_path, save, rest })
+}
+
+// Separates the token mode and prefix from the options shared with
+// generation.
+fn parse_token_args(args: &[String]) -> Result<Options> {
+    enum Encoding {
+        Bytes(TokenEncoding),
+        Id(IdAlphabet),
+    }
+    let mut encoding = None;
+    let mut prefix = String::new();
+    let mut check = false;
+    let mut rest = Vec::new();
+    let synopses = [usage::TOKEN_BYTES, usage::TOKEN_ID];
+    for arg in read_args(&synopses, args)? {
+        let (flag, value) =
+            match arg {
+                Arg::Flag(flag, value) => (flag, value),
+                Arg::Other(arg) => {
+                    rest.push(arg.clone());
+                    continue;
+                },
+            };
+        let kind =
+            match flag {
+                "--hex" => Encoding::Bytes(TokenEncoding::Hex),
+                "--base64url" => Encoding::Bytes(TokenEncoding::Base64Url),
+                "--base32" => Encoding::Bytes(TokenEncoding::Base32),
+                "--base58" => Encoding::Id(IdAlphabet::Base58),
+                "--crockford" => Encoding::Id(IdAlphabet::Crockford),
+                "--prefix" => {
+                    prefix = value.to_string();
+                    continue;
+                },
+                "--check" => {
+                    check = true;
+                    continue;
+                },
+                _ => {
+                    return Err(anyhow!(usage()));
+                },
+            };
+        if encoding.is_some() {
+            return Err(anyhow!(tr("token needs exactly one encoding")));
+        }
+        let token_len = parse_number(value)?;
+        let token_len =
+            NonZeroUsize::new(token_len)
+                .ok_or_else(|| anyhow!(tr("token length must not be zero")))?;
+        encoding = Some((kind, token_len));
+    }
+    let token =
+        match encoding {
+            Some((Encoding::Bytes(encoding), num_bytes)) => {
+                if check {
+                    let msg = trf("{} has no check symbol", &[&encoding]);
                     return Err(anyhow!(msg));
                 }
+                let token = TokenGenerator::new(encoding, num_bytes);
+                Token::Bytes(token.prefix(prefix))
             },
-            (Some("-n"), Some(num_passwords_str)) => {
-                num_passwords = num_passwords_str.parse::<usize>()?;
-                if num_passwords == 0 {
-                    let msg = "number of passwords must not be zero";
+            Some((Encoding::Id(alphabet), id_len)) => {
+                if check && alphabet != IdAlphabet::Crockford {
+                    let msg = trf("{} has no check symbol", &[&alphabet]);
                     return Err(anyhow!(msg));
                 }
+                let id = IdGenerator::new(alphabet, id_len, check)?;
+                Token::Id(id.prefix(prefix))
+            },
+            None => {
+                return Err(anyhow!(tr("token needs exactly one encoding")));
+            },
+        };
+    with_token("token", &rest, token)
+}
+
+fn parse_uuid_args(args: &[String]) -> Result<Options> {
+    let mut version = UuidVersion::V4;
+    let mut rest = Vec::new();
+    for arg in read_args(&[usage::UUID], args)? {
+        match arg {
+            Arg::Flag("--v4", _) => {
+                version = UuidVersion::V4;
+            },
+            Arg::Flag("--v7", _) => {
+                version = UuidVersion::V7;
+            },
+            Arg::Other(arg) => {
+                rest.push(arg.clone());
+            },
+            Arg::Flag(..) => {
+                return Err(anyhow!(usage()));
+            },
+        }
+    }
+    with_token("uuid", &rest, Token::Uuid(UuidGenerator::new(version)))
+}
+
+// Separates the prefix and body length from the options shared with
+// generation.
+fn parse_apikey_args(args: &[String]) -> Result<Options> {
+    let (apikey, rest) = parse_apikey_format(args)?;
+    with_token("apikey", &rest, Token::ApiKey(apikey))
+}
+
+fn parse_apikey_format(
+    args: &[String],
+) -> Result<(ApiKeyGenerator, Vec<String>)> {
+    let mut prefix = String::new();
+    let mut body_len = ApiKeyGenerator::DEFAULT_BODY_LEN;
+    let mut rest = Vec::new();
+    for arg in read_args(&[usage::APIKEY], args)? {
+        match arg {
+            Arg::Flag("--prefix", value) => {
+                prefix = value.to_string();
+            },
+            Arg::Flag("-l", value) => {
+                body_len = parse_len(value)?;
+            },
+            Arg::Other(arg) => {
+                rest.push(arg.clone());
+            },
+            Arg::Flag(..) => {
+                return Err(anyhow!(usage()));
+            },
+        }
+    }
+    Ok((ApiKeyGenerator::new(prefix, body_len), rest))
+}
+
+fn run_apikey_verify(args: &[String]) -> Result<()> {
+    let (apikey, rest) = parse_apikey_format(args).map_err(UsageError)?;
+    if !rest.is_empty() {
+        return Err(UsageError(anyhow!(usage())).into());
+    }
+    verify_keys(|key| apikey.verify(key))
+}
+
+// Separates the key shape from the options shared with generation.
+fn parse_license_args(args: &[String]) -> Result<Options> {
+    let (license, rest) = parse_license_format(args)?;
+    with_token("license", &rest, Token::License(license))
+}
+
+fn parse_license_format(
+    args: &[String],
+) -> Result<(LicenseKeyGenerator, Vec<String>)> {
+    let mut num_groups = LicenseKeyGenerator::DEFAULT_NUM_GROUPS;
+    let mut group_len = LicenseKeyGenerator::DEFAULT_GROUP_LEN;
+    let mut checksum = LicenseChecksum::LuhnModN;
+    let mut rest = Vec::new();
+    for arg in read_args(&[usage::LICENSE], args)? {
+        match arg {
+            Arg::Flag("--groups", value) => {
+                num_groups = parse_count(value)?;
+            },
+            Arg::Flag("--group-len", value) => {
+                group_len = parse_len(value)?;
+            },
+            Arg::Flag("--checksum", value) => {
+                checksum =
+                    match value {
+                        "luhn" => LicenseChecksum::LuhnModN,
+                        "crc" => LicenseChecksum::Crc,
+                        _ => {
+                            return Err(anyhow!(usage()));
+                        },
+                    };
             },
-            (Some(_), _) => {
+            Arg::Other(arg) => {
+                rest.push(arg.clone());
+            },
+            Arg::Flag(..) => {
                 return Err(anyhow!(usage()));
             },
-            (None, _) => {
-                break;
+        }
+    }
+    let license = LicenseKeyGenerator::new(num_groups, group_len, checksum)?;
+    Ok((license, rest))
+}
+
+fn run_license_verify(args: &[String]) -> Result<()> {
+    let (license, rest) = parse_license_format(args).map_err(UsageError)?;
+    if !rest.is_empty() {
+        return Err(UsageError(anyhow!(usage())).into());
+    }
+    verify_keys(|key| license.verify(key))
+}
+
+// Checks passwords read from standard input, one per line, so that they
+// stay out of the process list.
+fn run_hibp(args: &[String]) -> Result<()> {
+    if let [command, rest @ ..] = args {
+        if command == "build" {
+            return run_hibp_build(rest);
+        }
+    }
+    let args = read_args(&[usage::HIBP], args).map_err(UsageError)?;
+    let pwned =
+        match args[..] {
+            [] => {
+                if !cfg!(feature = "hibp") {
+                    let msg = tr("this build does not support --check-hibp");
+                    return Err(UsageError(anyhow!(msg)).into());
+                }
+                PwnedPasswords::online()
+            },
+            [Arg::Flag("--offline", path)] =>
+                PwnedPasswords::offline(BreachList::open(Path::new(path))?),
+            _ => return Err(UsageError(anyhow!(usage())).into()),
+        };
+    let mut stdout = io::stdout().lock();
+    let mut num_breached = 0;
+    for (idx, line) in io::stdin().lines().enumerate() {
+        let line = Zeroizing::new(line?);
+        let password = line.trim_end_matches('\r');
+        if password.is_empty() {
+            continue;
+        }
+        let context = trf("line {}", &[&(idx + 1)]);
+        match pwned.lookup(password)? {
+            Appearance::Absent => writeln!(stdout, "{}: ok", context)?,
+            Appearance::Seen(count) => {
+                writeln!(stdout, "{}: breached {} times", context, count)?;
+                num_breached += 1;
+            },
+            Appearance::Likely => {
+                writeln!(stdout, "{}: probably breached", context)?;
+                num_breached += 1;
             },
         }
     }
-    let passwords = generate(charset_spec, password_len, num_passwords)?;
-    for password in passwords {
-        println!("{}", password);
+    if num_breached > 0 {
+        let msg = trf("breached passwords: {}", &[&num_breached]);
+        return Err(anyhow!(msg));
     }
     Ok(())
 }
 
-fn usage() -> String {
-    let program_name = env::args().next().unwrap_or("".to_string());
-    format!("\n  Usage: {} [options]\n{}", program_name, USAGE)
+fn run_hibp_build(args: &[String]) -> Result<()> {
+    let mut fp_rate = DEFAULT_BLOOM_FP_RATE;
+    let mut paths = Vec::new();
+    for arg in read_args(&[usage::HIBP_BUILD], args).map_err(UsageError)? {
+        match arg {
+            Arg::Flag("--fp-rate", value) => {
+                fp_rate = parse_number::<f64>(value).map_err(UsageError)?;
+                if !(fp_rate > 0.0 && fp_rate < 1.0) {
+                    let msg = tr("false positive rate must be in (0, 1)");
+                    return Err(UsageError(anyhow!(msg)).into());
+                }
+            },
+            Arg::Other(arg) => paths.push(Path::new(arg)),
+            Arg::Flag(..) => return Err(UsageError(anyhow!(usage())).into()),
+        }
+    }
+    let [dump, out] = paths[..] else {
+        return Err(UsageError(anyhow!(usage())).into());
+    };
+    let num_hashes = breaches::build_bloom(dump, out, fp_rate)?;
+    eprintln!("{}", trf("hashes added: {}", &[&num_hashes]));
+    Ok(())
+}
+
+// Checks the keys on standard input, one per line, so that they stay out of
+// the process list.
+fn verify_keys(verify: impl Fn(&str) -> bool) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    let mut num_invalid = 0;
+    for (idx, line) in io::stdin().lines().enumerate() {
+        let line = Zeroizing::new(line?);
+        let key = line.trim();
+        if key.is_empty() {
+            continue;
+        }
+        let context = trf("line {}", &[&(idx + 1)]);
+        if verify(key) {
+            writeln!(stdout, "{}: ok", context)?;
+        } else {
+            writeln!(stdout, "{}: invalid", context)?;
+            num_invalid += 1;
+        }
+    }
+    if num_invalid > 0 {
+        return Err(anyhow!(trf("invalid keys: {}", &[&num_invalid])));
+    }
+    Ok(())
+}
+
+fn parse_mac_args(args: &[String]) -> Result<Options> {
+    let mut vendor_prefix = None;
+    let mut locally_administered = false;
+    let mut universally_administered = false;
+    let mut rest = Vec::new();
+    for arg in read_args(&[usage::MAC], args)? {
+        match arg {
+            Arg::Flag("--vendor-prefix", value) => {
+                vendor_prefix = Some(MacGenerator::parse_prefix(value)?);
+            },
+            Arg::Flag("--locally-administered", _) => {
+                locally_administered = true;
+            },
+            Arg::Flag("--universally-administered", _) => {
+                universally_administered = true;
+            },
+            Arg::Other(arg) => {
+                rest.push(arg.clone());
+            },
+            Arg::Flag(..) => {
+                return Err(anyhow!(usage()));
+            },
+        }
+    }
+    if universally_administered
+        && (locally_administered || vendor_prefix.is_some())
+    {
+        let msg = tr(
+            "--universally-administered cannot be used with \
+             --locally-administered or --vendor-prefix",
+        );
+        return Err(anyhow!(msg));
+    }
+    // A vendor prefix brings its own bit, which --locally-administered
+    // checks, and random addresses are locally administered unless asked
+    // otherwise, so that they cannot clash with any vendor's.
+    let locally_administered =
+        locally_administered
+            || (vendor_prefix.is_none() && !universally_administered);
+    let mac = MacGenerator::new(vendor_prefix, locally_administered)?;
+    with_token("mac", &rest, Token::Mac(mac))
+}
+
+fn parse_username_args(args: &[String]) -> Result<Options> {
+    let mut style = UsernameStyle::Words;
+    let mut max_len = None;
+    let mut rest = Vec::new();
+    for arg in read_args(&[usage::USERNAME], args)? {
+        match arg {
+            Arg::Flag("--style", value) => {
+                style =
+                    match value {
+                        "words" => UsernameStyle::Words,
+                        "pronounceable" => UsernameStyle::Pronounceable,
+                        _ => {
+                            return Err(anyhow!(usage()));
+                        },
+                    };
+            },
+            Arg::Flag("--max-len", value) => {
+                max_len = Some(parse_len(value)?);
+            },
+            Arg::Other(arg) => {
+                rest.push(arg.clone());
+            },
+            Arg::Flag(..) => {
+                return Err(anyhow!(usage()));
+            },
+        }
+    }
+    let max_len = max_len.unwrap_or_else(|| style.default_max_length());
+    let username = UsernameGenerator::new(style, max_len)?;
+    with_token("username", &rest, Token::Username(username))
+}
+
+fn parse_otp_args(args: &[String]) -> Result<Options> {
+    let mut code_len = OtpCodeGenerator::DEFAULT_CODE_LEN;
+    let mut check_digit = false;
+    let mut group_len = None;
+    let mut rest = Vec::new();
+    for arg in read_args(&[usage::OTP], args)? {
+        match arg {
+            Arg::Flag("-l", value) => {
+                code_len = parse_len(value)?;
+            },
+            Arg::Flag("--luhn", _) => {
+                check_digit = true;
+            },
+            Arg::Flag("--group-len", value) => {
+                group_len = Some(parse_len(value)?);
+            },
+            Arg::Other(arg) => {
+                rest.push(arg.clone());
+            },
+            Arg::Flag(..) => {
+                return Err(anyhow!(usage()));
+            },
+        }
+    }
+    let mut otp = OtpCodeGenerator::new(code_len, check_digit)?;
+    if let Some(group_len) = group_len {
+        otp = otp.grouped(group_len);
+    }
+    let mut options = with_token("otp", &rest, Token::Otp(otp))?;
+    options.num_passwords.get_or_insert(DEFAULT_NUM_OTP_CODES);
+    Ok(options)
+}
+
+// Takes the generator's name and the file defining generators from the
+// options shared with generation.
+fn parse_generator_args(args: &[String]) -> Result<Options> {
+    let Some((name, args)) =
+        args.split_first().filter(|(name, _)| !name.starts_with('-'))
+    else {
+        return Err(anyhow!(usage()));
+    };
+    let mut path = None;
+    let mut rest = Vec::new();
+    for arg in read_args(&[usage::GENERATOR], args)? {
+        match arg {
+            Arg::Flag("--generators", value) => {
+                path = Some(PathBuf::from(value));
+            },
+            Arg::Other(arg) => {
+                rest.push(arg.clone());
+            },
+            Arg::Flag(..) => {
+                return Err(anyhow!(usage()));
+            },
+        }
+    }
+    load_generators(path)?;
+    let generator =
+        registered_generator(name).ok_or_else(|| {
+            anyhow!(trf("unknown generator: \"{}\"", &[name]))
+        })?;
+    let token = Token::Registered(name.clone(), generator);
+    let mut options = with_token("generator", &rest, token)?;
+    // Columns would need the longest password known in advance.
+    options.layout.get_or_insert(Layout::Lines);
+    Ok(options)
+}
+
+// Registers the generators defined in the file, or in the default one if
+// there is a configuration directory to hold it.
+// Parses --fields, a comma-separated list of fields each given as
+// name=@generator, for a registered generator, or as name=options, for
+// password options as the main command takes them.
+fn parse_fields(
+    spec: &str,
+    generators_file: Option<PathBuf>,
+) -> Result<Vec<(String, SharedGenerator)>> {
+    let mut fields = Vec::<(String, SharedGenerator)>::new();
+    let mut generators_file = Some(generators_file);
+    // A comma only separates fields where the next begins, so that options
+    // may contain commas.
+    let mut parts = Vec::<String>::new();
+    for piece in spec.split(',') {
+        match parts.last_mut() {
+            Some(part) if !is_field_start(piece) => {
+                part.push(',');
+                part.push_str(piece);
+            },
+            _ => parts.push(piece.to_string()),
+        }
+    }
+    for part in &parts {
+        let invalid = || anyhow!(trf("invalid field: \"{}\"", &[part]));
+        if !is_field_start(part) {
+            return Err(invalid());
+        }
+        let (name, definition) = part.split_once('=').ok_or_else(invalid)?;
+        let name = name.trim();
+        let mixed = || {
+            let msg =
+                "field {} takes either a generator or password options, not \
+                 both";
+            anyhow!(trf(msg, &[&name]))
+        };
+        if fields.iter().any(|(other, _)| other == name) {
+            return Err(anyhow!(trf("field repeated: \"{}\"", &[&name])));
+        }
+        let words =
+            definition.split_whitespace()
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+        let generator: SharedGenerator =
+            match words.first().and_then(|word| word.strip_prefix('@')) {
+                Some(generator_name) => {
+                    if words.len() > 1 {
+                        return Err(mixed());
+                    }
+                    // The file is read once, however many fields use it.
+                    if let Some(path) = generators_file.take() {
+                        load_generators(path)?;
+                    }
+                    registered_generator(generator_name).ok_or_else(|| {
+                        let msg = "unknown generator: \"{}\"";
+                        anyhow!(trf(msg, &[&generator_name]))
+                    })?
+                },
+                None => {
+                    if words.iter().any(|word| word.starts_with('@')) {
+                        return Err(mixed());
+                    }
+                    let options = parse_args(&words)?;
+                    let spec = resolve(&options)?.build()?;
+                    Arc::new(spec.generator().clone())
+                },
+            };
+        fields.push((name.to_string(), generator));
+    }
+    Ok(fields)
+}
+
+// Whether the text begins a field of --fields, with a name and an equals
+// sign.
+fn is_field_start(text: &str) -> bool {
+    text.split_once('=').is_some_and(|(name, _)| {
+        let name = name.trim();
+        !name.is_empty()
+            && name.chars().all(|ch| {
+                ch.is_ascii_alphanumeric() || ch == '_' || ch == '-'
+            })
+    })
+}
+
+fn load_generators(path: Option<PathBuf>) -> Result<()> {
+    match path.or_else(generators::default_path) {
+        Some(path) => generators::load(&path),
+        None => Ok(()),
+    }
+}
+
+fn run_generators(args: &[String]) -> Result<()> {
+    let mut path = None;
+    for arg in read_args(&[usage::GENERATORS], args).map_err(UsageError)? {
+        match arg {
+            Arg::Flag("--generators", value) => {
+                path = Some(PathBuf::from(value));
+            },
+            _ => return Err(UsageError(anyhow!(usage())).into()),
+        }
+    }
+    load_generators(path)?;
+    let mut stdout = io::stdout().lock();
+    for name in registered_generators() {
+        writeln!(stdout, "{}", name)?;
+    }
+    Ok(())
+}
+
+// Converts another tool's recipe into a generator defined in the
+// generators file.
+fn run_import(args: &[String]) -> Result<()> {
+    let mut path = None;
+    let mut positionals = Vec::new();
+    for arg in read_args(&[usage::IMPORT], args).map_err(UsageError)? {
+        match arg {
+            Arg::Flag("--generators", value) => {
+                path = Some(PathBuf::from(value));
+            },
+            // A recipe may itself look like an option.
+            Arg::Other(arg) => {
+                positionals.push(arg.as_str());
+            },
+            Arg::Flag(..) => {
+                return Err(UsageError(anyhow!(usage())).into());
+            },
+        }
+    }
+    let [kind, name, recipe] = positionals[..] else {
+        return Err(UsageError(anyhow!(usage())).into());
+    };
+    let definition =
+        match kind {
+            "keepass-pattern" => import::keepass_pattern(recipe)?,
+            "pwgen-args" => {
+                let (definition, pronounceable) = import::pwgen_args(recipe)?;
+                if pronounceable {
+                    let msg =
+                        tr("pwgen's pronounceable passwords are imported as \
+                            random ones, as with -s");
+                    warn(msg);
+                }
+                definition
+            },
+            "1password-recipe" => import::one_password_recipe(recipe)?,
+            _ => {
+                return Err(UsageError(anyhow!(usage())).into());
+            },
+        };
+    let path = path.or_else(generators::default_path).ok_or_else(|| {
+        let msg =
+            tr("could not find a configuration directory; use --generators");
+        UsageError(anyhow!(msg))
+    })?;
+    generators::define(&path, name, &definition)?;
+    eprintln!("{}", trf("imported generator \"{}\"", &[&name]));
+    Ok(())
+}
+
+fn parse_credentials_args(args: &[String]) -> Result<Options> {
+    let mut style = UsernameStyle::Words;
+    let mut max_len = None;
+    let mut totp = false;
+    let mut rest = Vec::new();
+    for arg in read_args(&[usage::CREDENTIALS], args)? {
+        match arg {
+            Arg::Flag("--style", value) => {
+                style =
+                    match value {
+                        "words" => UsernameStyle::Words,
+                        "pronounceable" => UsernameStyle::Pronounceable,
+                        _ => {
+                            return Err(anyhow!(usage()));
+                        },
+                    };
+            },
+            Arg::Flag("--max-len", value) => {
+                max_len = Some(parse_len(value)?);
+            },
+            Arg::Flag("--totp", _) => {
+                totp = true;
+            },
+            Arg::Other(arg) => {
+                rest.push(arg.clone());
+            },
+            Arg::Flag(..) => {
+                return Err(anyhow!(usage()));
+            },
+        }
+    }
+    let max_len = max_len.unwrap_or_else(|| style.default_max_length());
+    let username = UsernameGenerator::new(style, max_len)?;
+    let totp =
+        totp.then(|| {
+            TokenGenerator::new(TokenEncoding::Base32, TOTP_SECRET_LEN)
+        });
+    let mut options = parse_args(&rest)?;
+    if options.batch {
+        return Err(anyhow!(tr("credentials does not take --batch")));
+    }
+    if options.users_file.is_some() {
+        return Err(anyhow!(tr("credentials does not take --users")));
+    }
+    options.credentials = Some(Credentials { username, totp });
+    Ok(options)
+}
+
+fn parse_insert_pass_args(args: &[String]) -> Result<Options> {
+    let Some((name, args)) = args.split_first() else {
+        return Err(anyhow!(usage()));
+    };
+    if name.starts_with('-') {
+        return Err(anyhow!(usage()));
+    }
+    let mut in_place = false;
+    let mut fields = Vec::new();
+    let mut rest = Vec::new();
+    for arg in read_args(&[usage::INSERT_PASS], args)? {
+        match arg {
+            Arg::Flag("--in-place", _) => {
+                in_place = true;
+            },
+            Arg::Flag("--field", field) => {
+                let Some((key, value)) = field.split_once('=') else {
+                    let msg = trf("invalid field: \"{}\"", &[&field]);
+                    return Err(anyhow!(msg));
+                };
+                fields.push((key.to_string(), value.to_string()));
+            },
+            Arg::Other(arg) => {
+                rest.push(arg.clone());
+            },
+            Arg::Flag(..) => {
+                return Err(anyhow!(usage()));
+            },
+        }
+    }
+    let mut options = parse_args(&rest)?;
+    let conflicting =
+        options.num_passwords.is_some_and(|count| count.get() > 1)
+            || options.batch
+            || options.split.is_some()
+            || options.encrypt_to.is_some()
+            || options.format != Format::Text
+            || options.users_file.is_some();
+    if conflicting {
+        let msg = tr(
+            "insert-pass generates one password and does not take --batch, \
+             --split, --encrypt-to, --format or --users",
+        );
+        return Err(anyhow!(msg));
+    }
+    let name = name.clone();
+    options.pass_entry = Some(PassEntry { name, in_place, fields });
+    Ok(options)
+}
+
+// Parses the options shared with generation for a command that prints
+// tokens, which must not describe a character set or length.
+fn with_token(command: &str, args: &[String], token: Token) -> Result<Options> {
+    let mut options = parse_args(args)?;
+    let conflicting =
+        options.charset_spec.is_some()
+            || options.mode.is_some()
+            || options.preset.is_some()
+            || options.exclude_chars.is_some()
+            || options.min_len.is_some()
+            || options.max_len.is_some()
+            || options.prompt
+            || options.batch;
+    if conflicting {
+        let msg = trf(
+            "{} does not take character set, length, --prompt or --batch \
+             options",
+            &[&command],
+        );
+        return Err(anyhow!(msg));
+    }
+    if options.check_hibp || options.check_offline.is_some() {
+        let msg = trf(
+            "{} does not take --check-hibp or --check-offline",
+            &[&command],
+        );
+        return Err(anyhow!(msg));
+    }
+    options.token = Some(token);
+    Ok(options)
+}
+
+fn profiles_path(path: Option<PathBuf>) -> Result<PathBuf> {
+    path.or_else(Profiles::default_path).ok_or_else(|| {
+        let msg =
+            tr("could not find a configuration directory; use --profiles");
+        UsageError(anyhow!(msg)).into()
+    })
+}
+
+// Checks each settings file given, or each that exists where it is looked
+// for by default, reporting what is wrong with each on standard output.
+fn run_config(args: &[String]) -> Result<()> {
+    let [command, args @ ..] = args else {
+        return Err(UsageError(anyhow!(usage())).into());
+    };
+    if command != "validate" {
+        return Err(UsageError(anyhow!(usage())).into());
+    }
+    let mut files = [
+        (ConfigFile::Profiles, None, Profiles::default_path()),
+        (ConfigFile::Generators, None, generators::default_path()),
+        (ConfigFile::Confusables, None, confusables::default_path()),
+        (
+            ConfigFile::Pwquality,
+            None,
+            Some(PathBuf::from(pwquality::DEFAULT_CONFIG)),
+        ),
+    ];
+    for arg in read_args(&[usage::CONFIG], args).map_err(UsageError)? {
+        let (idx, value) =
+            match arg {
+                Arg::Flag("--profiles", value) => (0, value),
+                Arg::Flag("--generators", value) => (1, value),
+                Arg::Flag("--confusables-file", value) => (2, value),
+                Arg::Flag("--config", value) => (3, value),
+                _ => {
+                    return Err(UsageError(anyhow!(usage())).into());
+                },
+            };
+        files[idx].1 = Some(PathBuf::from(value));
+    }
+    let mut stdout = io::stdout().lock();
+    let mut num_invalid = 0;
+    for (kind, given, default) in files {
+        let path =
+            match (given, default) {
+                (Some(path), _) => path,
+                (None, Some(path)) if path.exists() => path,
+                (None, _) => continue,
+            };
+        match config::validate(kind, &path) {
+            None => writeln!(stdout, "{}: ok", path.display())?,
+            Some(problem) => {
+                writeln!(stdout, "{}:{}", path.display(), problem)?;
+                num_invalid += 1;
+            },
+        }
+    }
+    if num_invalid > 0 {
+        let msg = trf("invalid configuration files: {}", &[&num_invalid]);
+        return Err(anyhow!(msg));
+    }
+    Ok(())
+}
+
+fn run_profiles(args: &[String]) -> Result<()> {
+    let mut path = None;
+    let mut positionals = Vec::new();
+    for arg in read_args(&[usage::PROFILES], args).map_err(UsageError)? {
+        match arg {
+            Arg::Flag("--profiles", value) => {
+                path = Some(PathBuf::from(value));
+            },
+            Arg::Other(arg) => {
+                positionals.push(arg.as_str());
+            },
+            Arg::Flag(..) => {
+                return Err(UsageError(anyhow!(usage())).into());
+            },
+        }
+    }
+    let path = profiles_path(path)?;
+    match positionals[..] {
+        ["export"] => {
+            let profiles = Profiles::load(&path)?;
+            io::stdout().lock().write_all(profiles.to_json()?.as_bytes())?;
+        },
+        ["import"] | ["import", _] => {
+            let json =
+                match positionals.get(1) {
+                    Some(&"-") | None => io::read_to_string(io::stdin())?,
+                    Some(file) => {
+                        let context = trf("could not read \"{}\"", &[file]);
+                        fs::read_to_string(file).context(context)?
+                    },
+                };
+            let imported = Profiles::from_json(&json)?;
+            let mut profiles = Profiles::load(&path)?;
+            let num_imported = profiles.merge(imported);
+            profiles.save(&path)?;
+            let msg = trf("imported {} profiles", &[&num_imported]);
+            eprintln!("{}", msg);
+        },
+        _ => {
+            return Err(UsageError(anyhow!(usage())).into());
+        },
+    }
+    Ok(())
+}
+
+fn run_retrieve(args: &[String]) -> Result<()> {
+    let mut service = None;
+    let mut account = None;
+    for arg in read_args(&[usage::RETRIEVE], args).map_err(UsageError)? {
+        match arg {
+            Arg::Flag("--service", value) => {
+                service = Some(value.to_string());
+            },
+            Arg::Flag("--account", value) => {
+                account = Some(value.to_string());
+            },
+            _ => return Err(UsageError(anyhow!(usage())).into()),
+        }
+    }
+    let (Some(service), Some(account)) = (service, account) else {
+        return Err(UsageError(anyhow!(usage())).into());
+    };
+    if !cfg!(feature = "keyring") {
+        let msg = tr("this build does not support --store keyring");
+        return Err(UsageError(anyhow!(msg)).into());
+    }
+    if let Err(err) = harden::harden() {
+        let msg = trf("could not disable core dumps: {}", &[&err]);
+        warn(&msg);
+    }
+    let secret = KeyringEntry::new(service, account).retrieve()?;
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(secret.as_bytes())?;
+    writeln!(stdout)?;
+    Ok(())
+}
+
+fn run_keyfile(args: &[String]) -> Result<()> {
+    let mut path = None;
+    let mut size = keyfile::DEFAULT_SIZE;
+    let mut force = false;
+    for arg in read_args(&[usage::KEYFILE], args).map_err(UsageError)? {
+        match arg {
+            Arg::Flag("--size", value) => {
+                size = parse_number(value).map_err(UsageError)?;
+            },
+            Arg::Flag("-f" | "--force", _) => {
+                force = true;
+            },
+            Arg::Other(arg) if !arg.starts_with('-') && path.is_none() => {
+                path = Some(PathBuf::from(arg));
+            },
+            _ => return Err(UsageError(anyhow!(usage())).into()),
+        }
+    }
+    let Some(path) = path else {
+        return Err(UsageError(anyhow!(usage())).into());
+    };
+    if !(keyfile::MIN_SIZE..=keyfile::MAX_SIZE).contains(&size) {
+        let msg = trf(
+            "the keyfile size must be from {} to {} bytes",
+            &[&keyfile::MIN_SIZE, &keyfile::MAX_SIZE],
+        );
+        return Err(UsageError(anyhow!(msg)).into());
+    }
+    if let Err(err) = harden::harden() {
+        let msg = trf("could not disable core dumps: {}", &[&err]);
+        warn(&msg);
+    }
+    let mut rng = os_rng()?;
+    keyfile::write(&path, size, force, &mut rng)
+}
+
+// Serves a browser extension over the native messaging protocol until the
+// browser closes standard input.
+fn run_native_host(args: &[String]) -> Result<()> {
+    let mut check_hibp = false;
+    let mut check_offline = None;
+    for arg in read_args(&[usage::NATIVE_HOST], args).map_err(UsageError)? {
+        match arg {
+            Arg::Flag("--check-hibp", _) => {
+                if !cfg!(feature = "hibp") {
+                    let msg = tr("this build does not support --check-hibp");
+                    return Err(UsageError(anyhow!(msg)).into());
+                }
+                check_hibp = true;
+            },
+            Arg::Flag("--check-offline", path) => {
+                check_offline = Some(PathBuf::from(path));
+            },
+            // Browsers pass the path of the host's manifest and the ID or
+            // origin of the extension, which are not needed.
+            _ => {},
+        }
+    }
+    if let Err(err) = harden::harden() {
+        let msg = trf("could not disable core dumps: {}", &[&err]);
+        warn(&msg);
+    }
+    let pwned =
+        match check_offline {
+            _ if check_hibp => Some(PwnedPasswords::online()),
+            Some(path) =>
+                Some(PwnedPasswords::offline(BreachList::open(&path)?)),
+            None => None,
+        };
+    if !check_hibp {
+        if let Err(err) = sandbox::install(None) {
+            let msg = trf("could not install the sandbox: {}", &[&err]);
+            warn(&msg);
+        }
+    }
+    let mut rng = os_rng()?;
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+    while let Some(message) = native_host::read_message(&mut stdin)? {
+        let response = serve_native_request(
+            &message,
+            &mut rng,
+            pwned.as_ref(),
+            None,
+            None,
+        )?;
+        native_host::write_message(&mut stdout, response.as_bytes())?;
+    }
+    Ok(())
+}
+
+// Answers a request to the native messaging host or the daemon, with the
+// error in the response if the request cannot be served. With limits, as
+// for the daemon, op status gives what they have counted.
+fn serve_native_request(
+    message: &[u8],
+    rng: &mut dyn CryptoRngCore,
+    pwned: Option<&PwnedPasswords>,
+    wordlist: Option<&PassphraseGenerator>,
+    mut limits: Option<&mut Limits>,
+) -> Result<Zeroizing<String>> {
+    let mut request = serde_json::from_slice::<NativeRequest>(message);
+    let id = request.as_mut().ok().and_then(|request| request.id.take());
+    let reply =
+        request
+            .map_err(anyhow::Error::from)
+            .and_then(|request| {
+                if let Some(limits) = limits.as_deref_mut() {
+                    limits.admit()?;
+                    if request.op == "status" {
+                        return Ok(NativeReply::Status(limits.status()));
+                    }
+                }
+                let quota = limits.map(|limits| &mut limits.quota);
+                match request.op.as_str() {
+                    "generate" => generate_native(request, rng, pwned, quota),
+                    "check" => check_native(request, pwned),
+                    "passphrase" => {
+                        generate_passphrases(
+                            request,
+                            rng,
+                            pwned,
+                            wordlist,
+                            quota,
+                        )
+                    },
+                    op => Err(anyhow!(trf("unknown op: \"{}\"", &[&op]))),
+                }
+            });
+    let mut response = JsonResponse { id, ..JsonResponse::default() };
+    match &reply {
+        Ok(NativeReply::Generated { passwords, entropy_bits }) => {
+            let passwords =
+                passwords.iter().map(|password| password.as_str()).collect();
+            response.passwords = Some(passwords);
+            response.entropy_bits = Some(*entropy_bits);
+        },
+        Ok(NativeReply::Checked { entropy_bits, breached }) => {
+            response.entropy_bits = Some(*entropy_bits);
+            response.breached = *breached;
+        },
+        Ok(NativeReply::Status(status)) => {
+            response.status = Some(status);
+        },
+        Err(err) => {
+            response.error = Some(error_message(err));
+        },
+    }
+    let json = Zeroizing::new(serde_json::to_string(&response)?);
+    if json.len() <= native_host::MAX_MESSAGE_LEN {
+        return Ok(json);
+    }
+    let response = JsonResponse {
+        id: response.id,
+        error: Some(tr("the response would be too long").to_string()),
+        ..JsonResponse::default()
+    };
+    Ok(Zeroizing::new(serde_json::to_string(&response)?))
+}
+
+fn generate_native(
+    request: NativeRequest,
+    rng: &mut dyn CryptoRngCore,
+    pwned: Option<&PwnedPasswords>,
+    quota: Option<&mut Quota>,
+) -> Result<NativeReply> {
+    if request.password.is_some()
+        || request.words.is_some()
+        || request.separator.is_some()
+    {
+        let msg = tr("generate does not take a password, words or separator");
+        return Err(anyhow!(msg));
+    }
+    let spec = BatchSpec {
+        charset: request.charset,
+        mode: request.mode,
+        exclude: request.exclude,
+        length: request.length,
+        min_length: request.min_length,
+        max_length: request.max_length,
+        count: request.count,
+        min_entropy: request.min_entropy,
+    };
+    let (passwords, entropy_bits) =
+        generate_requested(spec, rng, pwned, quota)?;
+    Ok(NativeReply::Generated { passwords, entropy_bits })
+}
+
+// Generates the passwords a JSON specification asks for through the native
+// messaging host, the daemon or the server, returning them with their
+// entropy, and counting them against any quota.
+fn generate_requested(
+    spec: BatchSpec,
+    rng: &mut dyn CryptoRngCore,
+    pwned: Option<&PwnedPasswords>,
+    quota: Option<&mut Quota>,
+) -> Result<(Vec<Zeroizing<String>>, f64)> {
+    let spec = requested_spec(spec)?;
+    let count = spec.count().get();
+    if count > MAX_REQUESTED_PASSWORDS {
+        let msg = trf(
+            "at most {} passwords can be generated at once",
+            &[&MAX_REQUESTED_PASSWORDS],
+        );
+        return Err(anyhow!(msg));
+    }
+    let bits = spec.generator().entropy_bits();
+    if let Some(quota) = quota {
+        quota.take(count as u64)?;
+    }
+    let mut passwords = Vec::with_capacity(count);
+    while passwords.len() < count {
+        let mut password = Zeroizing::new(String::new());
+        spec.generator().generate(rng, &mut password)?;
+        if let Some(pwned) = pwned {
+            if pwned.breached(&password)? {
+                continue;
+            }
+        }
+        passwords.push(password);
+    }
+    Ok((passwords, bits))
+}
+
+// Builds the specification a request asks for, failing if it falls short
+// of the entropy the request requires.
+fn requested_spec(spec: BatchSpec) -> Result<PasswordSpec> {
+    let options = parse_args(&batch_spec_args(spec))?;
+    let spec = resolve(&options)?.build()?;
+    let bits = spec.generator().entropy_bits();
+    if let Some(required) = options.min_entropy.filter(|&req| bits < req) {
+        return Err(PassgenError::InsufficientEntropy { bits, required }.into());
+    }
+    Ok(spec)
+}
+
+// Generates passphrases of words drawn from the daemon's word list.
+fn generate_passphrases(
+    request: NativeRequest,
+    rng: &mut dyn CryptoRngCore,
+    pwned: Option<&PwnedPasswords>,
+    wordlist: Option<&PassphraseGenerator>,
+    quota: Option<&mut Quota>,
+) -> Result<NativeReply> {
+    let Some(wordlist) = wordlist else {
+        return Err(anyhow!(tr("passphrase needs a word list")));
+    };
+    let password_fields = [
+        request.password.is_some(),
+        request.charset.is_some(),
+        request.mode.is_some(),
+        request.exclude.is_some(),
+        request.length.is_some(),
+        request.min_length.is_some(),
+        request.max_length.is_some(),
+        request.min_entropy.is_some(),
+    ];
+    if password_fields.contains(&true) {
+        let msg = tr("passphrase takes only words, separator and count");
+        return Err(anyhow!(msg));
+    }
+    let num_words = request.words.unwrap_or(DEFAULT_PASSPHRASE_WORDS);
+    if !(1..=MAX_PASSPHRASE_WORDS).contains(&num_words) {
+        let msg =
+            trf("words must be from 1 to {}", &[&MAX_PASSPHRASE_WORDS]);
+        return Err(anyhow!(msg));
+    }
+    let count = request.count.unwrap_or(1);
+    if !(1..=MAX_REQUESTED_PASSWORDS).contains(&count) {
+        let msg = trf(
+            "count must be from 1 to {}",
+            &[&MAX_REQUESTED_PASSWORDS],
+        );
+        return Err(anyhow!(msg));
+    }
+    if let Some(quota) = quota {
+        quota.take(count as u64)?;
+    }
+    let separator =
+        request.separator.as_deref().unwrap_or(DEFAULT_PASSPHRASE_SEPARATOR);
+    let mut passwords = Vec::with_capacity(count);
+    while passwords.len() < count {
+        let mut password = Zeroizing::new(String::new());
+        for idx in 0..num_words {
+            if idx > 0 {
+                password.push_str(separator);
+            }
+            wordlist.generate(rng, &mut password)?;
+        }
+        if let Some(pwned) = pwned {
+            if pwned.breached(&password)? {
+                continue;
+            }
+        }
+        passwords.push(password);
+    }
+    let entropy_bits = num_words as f64 * wordlist.entropy_bits();
+    Ok(NativeReply::Generated { passwords, entropy_bits })
+}
+
+fn check_native(
+    request: NativeRequest,
+    pwned: Option<&PwnedPasswords>,
+) -> Result<NativeReply> {
+    let Some(password) = request.password.map(Zeroizing::new) else {
+        return Err(anyhow!(tr("check needs a password")));
+    };
+    let breached =
+        match pwned {
+            Some(pwned) => Some(pwned.breached(&password)?),
+            None => None,
+        };
+    let entropy_bits = estimate_entropy(&password);
+    Ok(NativeReply::Checked { entropy_bits, breached })
+}
+
+// The options of serve and grpc, which listen on a loopback address for
+// clients with tokens from a file.
+#[cfg(feature = "server")]
+struct ListenArgs {
+    addr: SocketAddr,
+    token_file: PathBuf,
+    rate: Limit,
+    quota: Option<Limit>,
+}
+
+#[cfg(feature = "server")]
+fn parse_listen_args(
+    command: &str,
+    synopsis: &[Word],
+    default_listen: &str,
+    args: &[String],
+) -> Result<ListenArgs> {
+    let mut listen = default_listen.to_string();
+    let mut token_file = None;
+    let mut rate = server::DEFAULT_RATE;
+    let mut quota = None;
+    for arg in read_args(&[synopsis], args).map_err(UsageError)? {
+        match arg {
+            Arg::Flag("--listen", value) => {
+                listen = value.to_string();
+            },
+            Arg::Flag("--token-file", value) => {
+                token_file = Some(PathBuf::from(value));
+            },
+            Arg::Flag("--rate", value) => {
+                rate = Limit::parse(value).map_err(UsageError)?;
+            },
+            Arg::Flag("--quota", value) => {
+                quota = Some(Limit::parse(value).map_err(UsageError)?);
+            },
+            _ => {
+                return Err(UsageError(anyhow!(usage())).into());
+            },
+        }
+    }
+    let Some(token_file) = token_file else {
+        let msg = trf("{} needs --token-file", &[&command]);
+        return Err(UsageError(anyhow!(msg)).into());
+    };
+    let addr =
+        listen.parse::<SocketAddr>().map_err(|_| {
+            UsageError(anyhow!(trf("invalid address: \"{}\"", &[&listen])))
+        })?;
+    // Neither API is encrypted, so they must not leave the host.
+    if !addr.ip().is_loopback() {
+        let msg = trf("{} only listens on loopback addresses", &[&command]);
+        return Err(UsageError(anyhow!(msg)).into());
+    }
+    if let Err(err) = harden::harden() {
+        let msg = trf("could not disable core dumps: {}", &[&err]);
+        warn(&msg);
+    }
+    Ok(ListenArgs { addr, token_file, rate, quota })
+}
+
+// Serves POST /generate on a loopback address until killed.
+#[cfg(feature = "server")]
+fn run_serve(args: &[String]) -> Result<()> {
+    let ListenArgs { addr, token_file, rate, quota } =
+        parse_listen_args("serve", usage::SERVE, DEFAULT_LISTEN, args)?;
+    let mut clients = server::Clients::read(&token_file, rate, quota)?;
+    let mut rng = os_rng()?;
+    let context = || trf("could not listen on {}", &[&addr]);
+    let listener = TcpListener::bind(addr).with_context(context)?;
+    eprintln!("{}", trf("listening on {}", &[&listener.local_addr()?]));
+    server::serve(&listener, &mut clients, |body, quota| {
+        let result =
+            serde_json::from_slice::<BatchSpec>(body)
+                .map_err(anyhow::Error::from)
+                .and_then(|spec| {
+                    generate_requested(spec, &mut rng, None, Some(quota))
+                });
+        let mut response = JsonResponse::default();
+        let status =
+            match &result {
+                Ok((passwords, entropy_bits)) => {
+                    let passwords =
+                        passwords.iter()
+                            .map(|password| password.as_str())
+                            .collect();
+                    response.passwords = Some(passwords);
+                    response.entropy_bits = Some(*entropy_bits);
+                    200
+                },
+                Err(err) => {
+                    response.error = Some(error_message(err));
+                    match exit_code(err) {
+                        _ if err.is::<limits::LimitExceeded>() => 429,
+                        EXIT_RNG | EXIT_HEALTH => 500,
+                        _ => 400,
+                    }
+                },
+            };
+        Ok((status, Zeroizing::new(serde_json::to_string(&response)?)))
+    })
+}
+
+#[cfg(not(feature = "server"))]
+fn run_serve(_: &[String]) -> Result<()> {
+    let msg = tr("this build does not support serve");
+    Err(UsageError(anyhow!(msg)).into())
+}
+
+// Serves the gRPC service of proto/passgen.proto on a loopback address
+// until killed.
+#[cfg(feature = "grpc")]
+fn run_grpc(args: &[String]) -> Result<()> {
+    let ListenArgs { addr, token_file, rate, quota } =
+        parse_listen_args("grpc", usage::GRPC, DEFAULT_GRPC_LISTEN, args)?;
+    let clients = server::Clients::read(&token_file, rate, quota)?;
+    grpc_rng()?;
+    let context = || trf("could not listen on {}", &[&addr]);
+    let listener = TcpListener::bind(addr).with_context(context)?;
+    eprintln!("{}", trf("listening on {}", &[&listener.local_addr()?]));
+    let backend = grpc::Backend { spec: grpc_spec, rng: grpc_rng };
+    grpc::serve(listener, clients, backend)
+}
+
+#[cfg(feature = "grpc")]
+fn grpc_spec(spec: grpc::Spec) -> Result<PasswordSpec, String> {
+    let len = |len: Option<u64>| {
+        len.map(|len| usize::try_from(len).unwrap_or(usize::MAX))
+    };
+    let spec = BatchSpec {
+        charset: spec.charset,
+        mode: spec.mode,
+        exclude: spec.exclude,
+        length: len(spec.length),
+        min_length: len(spec.min_length),
+        max_length: len(spec.max_length),
+        count: None,
+        min_entropy: spec.min_entropy,
+    };
+    requested_spec(spec).map_err(|err| error_message(&err))
+}
+
+#[cfg(feature = "grpc")]
+fn grpc_rng() -> Result<OsRng> {
+    os_rng()
+}
+
+#[cfg(not(feature = "grpc"))]
+fn run_grpc(_: &[String]) -> Result<()> {
+    let msg = tr("this build does not support grpc");
+    Err(UsageError(anyhow!(msg)).into())
+}
+
+// Serves requests like those to the native messaging host on a Unix socket
+// until killed, with any word list and breach list loaded once up front.
+#[cfg(unix)]
+fn run_daemon(args: &[String]) -> Result<()> {
+    let mut socket = None;
+    let mut wordlist = None;
+    let mut check_hibp = false;
+    let mut check_offline = None;
+    let mut rate = None;
+    let mut quota = None;
+    for arg in read_args(&[usage::DAEMON], args).map_err(UsageError)? {
+        match arg {
+            Arg::Flag("--socket", value) => {
+                socket = Some(PathBuf::from(value));
+            },
+            Arg::Flag("--wordlist", value) => {
+                wordlist = Some(PathBuf::from(value));
+            },
+            Arg::Flag("--check-hibp", _) => {
+                if !cfg!(feature = "hibp") {
+                    let msg = tr("this build does not support --check-hibp");
+                    return Err(UsageError(anyhow!(msg)).into());
+                }
+                check_hibp = true;
+            },
+            Arg::Flag("--check-offline", value) => {
+                check_offline = Some(PathBuf::from(value));
+            },
+            Arg::Flag("--rate", value) => {
+                rate = Some(Limit::parse(value).map_err(UsageError)?);
+            },
+            Arg::Flag("--quota", value) => {
+                quota = Some(Limit::parse(value).map_err(UsageError)?);
+            },
+            _ => {
+                return Err(UsageError(anyhow!(usage())).into());
+            },
+        }
+    }
+    let socket =
+        match socket {
+            Some(socket) => socket,
+            None => {
+                let Some(dir) = env::var_os("XDG_RUNTIME_DIR") else {
+                    let msg =
+                        tr("daemon needs --socket when XDG_RUNTIME_DIR is \
+                            not set");
+                    return Err(UsageError(anyhow!(msg)).into());
+                };
+                PathBuf::from(dir).join("passgen.sock")
+            },
+        };
+    if let Err(err) = harden::harden() {
+        let msg = trf("could not disable core dumps: {}", &[&err]);
+        warn(&msg);
+    }
+    let wordlist = wordlist.as_deref().map(read_wordlist).transpose()?;
+    let pwned =
+        match check_offline {
+            _ if check_hibp => Some(PwnedPasswords::online()),
+            Some(path) =>
+                Some(PwnedPasswords::offline(BreachList::open(&path)?)),
+            None => None,
+        };
+    let mut rng = os_rng()?;
+    let mut limits = Limits::new(rate, quota);
+    let listener = daemon::bind(&socket)?;
+    eprintln!("{}", trf("listening on \"{}\"", &[&socket.display()]));
+    daemon::serve(listener, |line| {
+        serve_native_request(
+            line,
+            &mut rng,
+            pwned.as_ref(),
+            wordlist.as_ref(),
+            Some(&mut limits),
+        )
+    })
+}
+
+#[cfg(not(unix))]
+fn run_daemon(_: &[String]) -> Result<()> {
+    let msg = tr("this system does not support daemon");
+    Err(UsageError(anyhow!(msg)).into())
+}
+
+fn run_recover() -> Result<()> {
+    if let Err(err) = harden::harden() {
+        let msg = trf("could not disable core dumps: {}", &[&err]);
+        warn(&msg);
+    }
+    let mut shares = Vec::new();
+    for line in io::stdin().lines() {
+        let line = Zeroizing::new(line?);
+        if line.trim().is_empty() {
+            continue;
+        }
+        shares.push(line.parse::<Share>()?);
+    }
+    let secret = Zeroizing::new(recover_secret(&shares)?);
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(&secret)?;
+    writeln!(stdout)?;
+    Ok(())
+}
+
+fn run_pin(args: &[String]) -> Result<()> {
+    let mut pin_len = DEFAULT_PIN_LEN;
+    let mut num_pins = DEFAULT_NUM_PASSWORDS;
+    let mut allow_weak = false;
+    let mut verbose = false;
+    for arg in read_args(&[usage::PIN], args).map_err(UsageError)? {
+        match arg {
+            Arg::Flag("-l" | "--length", value) => {
+                pin_len = parse_len(value).map_err(UsageError)?;
+            },
+            Arg::Flag("-n" | "--count", value) => {
+                num_pins = parse_count(value).map_err(UsageError)?;
+            },
+            Arg::Flag("--allow-weak", _) => {
+                allow_weak = true;
+            },
+            Arg::Flag("-v" | "--verbose", _) => {
+                verbose = true;
+            },
+            _ => {
+                return Err(UsageError(anyhow!(usage())).into());
+            },
+        }
+    }
+    if let Err(err) = harden::harden() {
+        let msg = trf("could not disable core dumps: {}", &[&err]);
+        warn(&msg);
+    }
+    let generator = PinGenerator::new(pin_len).reject_weak(!allow_weak);
+    let rng = os_rng()?;
+    let pins =
+        Passwords::new(&generator, num_pins.get(), rng)
+            .with_source(EntropySource::Os);
+    let mut stdout = io::stdout().lock();
+    for pin in pins {
+        writeln!(stdout, "{}", *Zeroizing::new(pin?))?;
+    }
+    if verbose {
+        eprintln!("{}", trf("length: {}", &[&pin_len]));
+        eprintln!("{}", trf("count: {}", &[&num_pins]));
+        if let Some(num_rejected) = generator.num_rejected() {
+            eprintln!("{}", trf("weak PINs rejected: {}", &[&num_rejected]));
+        }
+        let entropy = format!("{:.1}", generator.entropy_bits());
+        eprintln!("{}", trf("entropy per PIN: {} bits", &[&entropy]));
+    }
+    Ok(())
+}
+
+fn run_card(args: &[String]) -> Result<()> {
+    let mut charset_spec = None;
+    let mut num_columns = DEFAULT_CARD_COLUMNS;
+    let mut html = false;
+    let mut derive = false;
+    let mut version = DerivationVersion::CURRENT;
+    let mut verbose = false;
+    for arg in read_args(&[usage::CARD], args).map_err(UsageError)? {
+        match arg {
+            Arg::Flag("-c", value) => {
+                charset_spec = Some(value.to_string());
+            },
+            Arg::Flag("--columns", value) => {
+                num_columns = parse_count(value).map_err(UsageError)?;
+            },
+            Arg::Flag("--html", _) => {
+                html = true;
+            },
+            Arg::Flag("--derive", _) => {
+                derive = true;
+            },
+            Arg::Flag("--derivation-version", value) => {
+                let number = parse_number(value).map_err(UsageError)?;
+                version = DerivationVersion::from_number(number)?;
+            },
+            Arg::Flag("-v" | "--verbose", _) => {
+                verbose = true;
+            },
+            _ => {
+                return Err(UsageError(anyhow!(usage())).into());
+            },
+        }
+    }
+    let charset =
+        match charset_spec {
+            Some(charset_spec) =>
+                parse_charset_spec(&charset_spec)
+                    .map_err(|err| UsageError(err.into()))?,
+            None => Charset::typeable().without(b" ")?,
+        };
+    if let Err(err) = harden::harden() {
+        let msg = trf("could not disable core dumps: {}", &[&err]);
+        warn(&msg);
+    }
+    let card =
+        if derive {
+            let master_password = read_master_password()?;
+            let master_password = master_password.as_bytes();
+            derive_card(version, master_password, &charset, num_columns)?
+        } else {
+            let mut rng = os_rng()?;
+            PasswordCard::generate(&mut rng, &charset, num_columns)?
+        };
+    let rendered =
+        Zeroizing::new(if html { card.to_html() } else { card.to_text() });
+    io::stdout().lock().write_all(rendered.as_bytes())?;
+    if verbose {
+        eprintln!("{}", trf("charset size: {}", &[&charset.len()]));
+        eprintln!("{}", trf("columns: {}", &[&num_columns]));
+        if derive {
+            eprintln!("{}", trf("derivation version: {}", &[&version]));
+        }
+        let entropy = format!("{:.1}", entropy_bits(&charset, 1));
+        eprintln!("{}", trf("entropy per cell: {} bits", &[&entropy]));
+    }
+    Ok(())
+}
+
+fn run_strengthen(args: &[String]) -> Result<()> {
+    let mut charset_spec = None;
+    let mut target_bits = DEFAULT_STRENGTHEN_BITS;
+    let mut placement = Placement::Append;
+    for arg in read_args(&[usage::STRENGTHEN], args).map_err(UsageError)? {
+        match arg {
+            Arg::Flag("-c", value) => {
+                charset_spec = Some(value.to_string());
+            },
+            Arg::Flag("--target", value) => {
+                target_bits = parse_number(value).map_err(UsageError)?;
+                if !(target_bits > 0.0 && target_bits.is_finite()) {
+                    let msg = tr("--target must be a positive number of bits");
+                    return Err(UsageError(anyhow!(msg)).into());
+                }
+            },
+            Arg::Flag("--inject", _) => {
+                placement = Placement::Inject;
+            },
+            _ => {
+                return Err(UsageError(anyhow!(usage())).into());
+            },
+        }
+    }
+    let charset =
+        match charset_spec {
+            Some(charset_spec) =>
+                parse_charset_spec(&charset_spec)
+                    .map_err(|err| UsageError(err.into()))?,
+            None => Charset::typeable().without(b" ")?,
+        };
+    if let Err(err) = harden::harden() {
+        let msg = trf("could not disable core dumps: {}", &[&err]);
+        warn(&msg);
+    }
+    let strengthener =
+        Strengthener::new(charset, target_bits).placement(placement);
+    let password = tty::read_hidden(tr("Password"))?;
+    let mut rng = os_rng()?;
+    let strengthened =
+        Zeroizing::new(strengthener.strengthen(&mut rng, &password)?);
+    writeln!(io::stdout().lock(), "{}", *strengthened)?;
+    // The report goes to standard error so that the password can be piped
+    // on alone.
+    let before = estimate_entropy(&password);
+    let after = strengthener.entropy_after(&password);
+    let num_added = strengthener.num_added(&password);
+    eprintln!("{}", trf(
+        "estimated entropy: {} bits before, {} bits after",
+        &[&format!("{:.1}", before), &format!("{:.1}", after)],
+    ));
+    eprintln!("{}", trf("random characters added: {}", &[&num_added]));
+    Ok(())
+}
+
+// Reports on each password in a list, or each user's password in a list of
+// user:password pairs, without the passwords themselves: its estimated
+// entropy, whether it passes the pwquality checks, whether it is breached
+// and which other entries share it or nearly do.
+fn run_audit(args: &[String]) -> Result<()> {
+    let mut path = None;
+    let mut pairs = false;
+    let mut max_distance = DEFAULT_SIMILAR_DISTANCE;
+    let mut format = Format::Text;
+    let mut config = None;
+    let mut wordlist = None;
+    let mut check_hibp = false;
+    let mut check_offline = None;
+    for arg in read_args(&[usage::AUDIT], args).map_err(UsageError)? {
+        match arg {
+            Arg::Flag("--pairs", _) => {
+                pairs = true;
+            },
+            Arg::Flag("--distance", value) => {
+                max_distance = parse_number(value).map_err(UsageError)?;
+            },
+            Arg::Flag("--format", value) => {
+                format =
+                    match value {
+                        "text" => Format::Text,
+                        "csv" => Format::Csv,
+                        "json" => Format::Json,
+                        _ => return Err(UsageError(anyhow!(usage())).into()),
+                    };
+            },
+            Arg::Flag("--config", value) => {
+                config = Some(PathBuf::from(value));
+            },
+            Arg::Flag("--wordlist", value) => {
+                wordlist = Some(PathBuf::from(value));
+            },
+            Arg::Flag("--check-hibp", _) => {
+                if !cfg!(feature = "hibp") {
+                    let msg = tr("this build does not support --check-hibp");
+                    return Err(UsageError(anyhow!(msg)).into());
+                }
+                check_hibp = true;
+            },
+            Arg::Flag("--check-offline", value) => {
+                check_offline = Some(PathBuf::from(value));
+            },
+            Arg::Other(arg) if path.is_none() && !arg.starts_with('-') => {
+                path = Some(PathBuf::from(arg));
+            },
+            _ => {
+                return Err(UsageError(anyhow!(usage())).into());
+            },
+        }
+    }
+    if let Err(err) = harden::harden() {
+        let msg = trf("could not disable core dumps: {}", &[&err]);
+        warn(&msg);
+    }
+    let default_config = Path::new(pwquality::DEFAULT_CONFIG);
+    let settings =
+        match config {
+            Some(path) => pwquality::Settings::read(&path)?,
+            None if default_config.exists() =>
+                pwquality::Settings::read(default_config)?,
+            None => pwquality::Settings::default(),
+        };
+    let words =
+        match wordlist {
+            Some(path) => pwquality::read_words(&path)?,
+            None => HashSet::new(),
+        };
+    let pwned =
+        match check_offline {
+            _ if check_hibp => Some(PwnedPasswords::online()),
+            Some(path) =>
+                Some(PwnedPasswords::offline(BreachList::open(&path)?)),
+            None => None,
+        };
+    let text =
+        match &path {
+            Some(path) => {
+                let context =
+                    || trf("could not read \"{}\"", &[&path.display()]);
+                Zeroizing::new(fs::read_to_string(path).with_context(context)?)
+            },
+            None => {
+                let mut text = Zeroizing::new(String::new());
+                io::Read::read_to_string(&mut io::stdin(), &mut text)?;
+                text
+            },
+        };
+    // Each entry is its line number, any user and the password.
+    let mut entries = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.is_empty() {
+            continue;
+        }
+        let (user, password) =
+            if pairs {
+                let Some((user, password)) = line.split_once(':') else {
+                    let line_num = idx + 1;
+                    let msg = trf("line {} is not user:password", &[&line_num]);
+                    return Err(anyhow!(msg));
+                };
+                (Some(user), password)
+            } else {
+                (None, line)
+            };
+        entries.push((idx + 1, user, password));
+    }
+    let mut lines_by_password = HashMap::<&str, Vec<usize>>::new();
+    for &(line, _, password) in &entries {
+        lines_by_password.entry(password).or_default().push(line);
+    }
+    let mut similar_lines = vec![Vec::new(); entries.len()];
+    if max_distance > 0 {
+        let passwords =
+            entries.iter()
+                .map(|&(_, _, password)| password)
+                .collect::<Vec<_>>();
+        for (first, second) in
+            similarity::similar_pairs(&passwords, max_distance)
+        {
+            similar_lines[first].push(entries[second].0);
+            similar_lines[second].push(entries[first].0);
+        }
+        for lines in &mut similar_lines {
+            lines.sort_unstable();
+        }
+    }
+    let mut names = vec!["line"];
+    if pairs {
+        names.push("user");
+    }
+    names.extend(["entropy_bits", "score", "policy"]);
+    if pwned.is_some() {
+        names.push("breached");
+    }
+    names.push("duplicates");
+    if max_distance > 0 {
+        names.push("similar");
+    }
+    let mut output = Records::new(io::stdout().lock(), format, &names);
+    let mut num_flagged = 0;
+    for (idx, &(line, user, password)) in entries.iter().enumerate() {
+        let mut values = vec![line.to_string()];
+        values.extend(user.map(str::to_string));
+        values.push(format!("{:.1}", estimate_entropy(password)));
+        let mut flagged = false;
+        match settings.score(password, user, &words) {
+            Ok(score) => {
+                values.push(score.to_string());
+                values.push("pass".to_string());
+            },
+            Err(reason) => {
+                values.push(String::new());
+                values.push(reason);
+                flagged = true;
+            },
+        }
+        if let Some(pwned) = &pwned {
+            let breached =
+                match pwned.lookup(password)? {
+                    Appearance::Absent => "no".to_string(),
+                    Appearance::Seen(count) => count.to_string(),
+                    Appearance::Likely => "likely".to_string(),
+                };
+            flagged |= breached != "no";
+            values.push(breached);
+        }
+        let duplicates =
+            lines_by_password[password]
+                .iter()
+                .filter(|&&other| other != line)
+                .map(usize::to_string)
+                .collect::<Vec<_>>();
+        flagged |= !duplicates.is_empty();
+        values.push(duplicates.join(" "));
+        if max_distance > 0 {
+            let similar =
+                similar_lines[idx]
+                    .iter()
+                    .map(usize::to_string)
+                    .collect::<Vec<_>>();
+            flagged |= !similar.is_empty();
+            values.push(similar.join(" "));
+        }
+        let values = values.iter().map(String::as_str).collect::<Vec<_>>();
+        output.write(&values)?;
+        num_flagged += usize::from(flagged);
+    }
+    output.finish()?;
+    if num_flagged > 0 {
+        let msg = trf("entries with findings: {}", &[&num_flagged]);
+        return Err(anyhow!(msg));
+    }
+    Ok(())
+}
+
+// Prints how long each attacker would take to guess a password with the
+// given entropy, or the estimated entropy of a password read in.
+fn run_entropy(args: &[String]) -> Result<()> {
+    let mut bits = None;
+    let mut zxcvbn = false;
+    let mut dictionaries = Dictionaries::built_in();
+    let mut attackers = AttackerProfile::defaults();
+    for arg in read_args(&[usage::ENTROPY], args).map_err(UsageError)? {
+        match arg {
+            Arg::Flag("--bits", value) => {
+                let value = parse_number::<f64>(value).map_err(UsageError)?;
+                if !(value >= 0.0 && value.is_finite()) {
+                    let msg = tr("--bits must be a number of bits");
+                    return Err(UsageError(anyhow!(msg)).into());
+                }
+                bits = Some(value);
+            },
+            Arg::Flag("--zxcvbn", _) => zxcvbn = true,
+            Arg::Flag("--dictionary", value) => {
+                let path = value;
+                let context = || trf("could not read \"{}\"", &[&path]);
+                let text = fs::read_to_string(path).with_context(context)?;
+                let words =
+                    text.lines().map(str::trim).filter(|word| !word.is_empty());
+                dictionaries.add(words);
+            },
+            Arg::Flag("--attacker", value) => {
+                let attacker = parse_attacker(value).map_err(UsageError)?;
+                match attackers.iter_mut().find(|other| {
+                    other.name == attacker.name
+                }) {
+                    Some(other) => *other = attacker,
+                    None => attackers.push(attacker),
+                }
+            },
+            _ => {
+                return Err(UsageError(anyhow!(usage())).into());
+            },
+        }
+    }
+    if zxcvbn && bits.is_some() {
+        let msg = tr("--zxcvbn and --bits cannot be used together");
+        return Err(UsageError(anyhow!(msg)).into());
+    }
+    let mut stdout = io::stdout().lock();
+    // The guesses an attacker needs on average.
+    let guesses =
+        match bits {
+            Some(bits) => (bits - 1.0).exp2(),
+            None => {
+                if let Err(err) = harden::harden() {
+                    let msg = trf("could not disable core dumps: {}", &[&err]);
+                    warn(&msg);
+                }
+                let password = tty::read_hidden(tr("Password"))?;
+                // What the password would be worth were it random, to
+                // compare with the estimate for a password a person chose.
+                let (classes, pool_size) = infer_charset(&password);
+                let len = password.chars().count();
+                let naive_bits = len as f64 * (pool_size as f64).log2();
+                let classes =
+                    classes.iter()
+                        .map(CharClass::to_string)
+                        .collect::<Vec<_>>();
+                writeln!(stdout, "classes: {}", classes.join(", "))?;
+                writeln!(
+                    stdout,
+                    "naive entropy: {:.1} bits ({} characters of {})",
+                    naive_bits.max(0.0),
+                    len,
+                    pool_size,
+                )?;
+                if zxcvbn {
+                    let estimate =
+                        estimate_guesses_with(&password, &dictionaries);
+                    print_guess_estimate(&mut stdout, &estimate)?;
+                    estimate.guesses
+                } else {
+                    let bits = estimate_entropy(&password);
+                    writeln!(stdout, "entropy: {:.1} bits", bits)?;
+                    (bits - 1.0).exp2()
+                }
+            },
+        };
+    if let Some(bits) = bits {
+        writeln!(stdout, "entropy: {:.1} bits", bits)?;
+    }
+    let name_width =
+        attackers.iter().map(|attacker| attacker.name.len()).max();
+    for attacker in &attackers {
+        let rate = attacker.guesses_per_second;
+        let rate =
+            if rate < 1.0 {
+                format!("{:.3}/s", rate)
+            } else if rate < 1e6 {
+                format!("{:.0}/s", rate)
+            } else {
+                format!("{:.1e}/s", rate)
+            };
+        writeln!(
+            stdout,
+            "{:<width$}  {:>10}  {}",
+            attacker.name,
+            rate,
+            format_duration(attacker.guess_seconds(guesses)),
+            width = name_width.unwrap_or(0),
+        )?;
+    }
+    Ok(())
+}
+
+// Prints the guesses with the parts of the password they are counted over,
+// by position, leaving out the characters.
+fn print_guess_estimate(
+    out: &mut impl Write,
+    estimate: &GuessEstimate,
+) -> Result<()> {
+    writeln!(
+        out,
+        "guesses: {:.1e} (10^{:.1})",
+        estimate.guesses,
+        estimate.guesses_log10(),
+    )?;
+    for pattern_match in &estimate.matches {
+        let (first, last) = (pattern_match.start + 1, pattern_match.end);
+        let position =
+            if first == last {
+                first.to_string()
+            } else {
+                format!("{}-{}", first, last)
+            };
+        writeln!(
+            out,
+            "  {}: {} ({:.0} guesses)",
+            position,
+            pattern_match.pattern,
+            pattern_match.guesses,
+        )?;
+    }
+    Ok(())
+}
+
+// Gives the character classes the password uses and the number of
+// characters in them, counting any other characters, such as spaces, one
+// by one.
+fn infer_charset(password: &str) -> (Vec<CharClass>, usize) {
+    let all_classes = [
+        CharClass::Lowercase,
+        CharClass::Uppercase,
+        CharClass::Digits,
+        CharClass::Symbols,
+    ];
+    let classes =
+        all_classes
+            .into_iter()
+            .filter(|&class| {
+                password.bytes().any(|byte| class.contains(byte))
+            })
+            .collect::<Vec<_>>();
+    let others =
+        password.chars()
+            .filter(|&ch| {
+                !u8::try_from(ch).is_ok_and(|byte| {
+                    all_classes.iter().any(|class| class.contains(byte))
+                })
+            })
+            .collect::<HashSet<_>>();
+    let pool_size =
+        classes.iter().map(|class| class.chars().len()).sum::<usize>()
+            + others.len();
+    (classes, pool_size)
+}
+
+// Parses an attacker given as name=guesses_per_second.
+fn parse_attacker(spec: &str) -> Result<AttackerProfile> {
+    let invalid = || anyhow!(trf("invalid attacker: \"{}\"", &[&spec]));
+    let (name, rate) = spec.split_once('=').ok_or_else(invalid)?;
+    let rate = rate.parse::<f64>().map_err(|_| invalid())?;
+    if name.is_empty() || !(rate > 0.0 && rate.is_finite()) {
+        return Err(invalid());
+    }
+    Ok(AttackerProfile::new(name, rate))
+}
+
+fn format_duration(seconds: f64) -> String {
+    const UNITS: &[(&str, f64)] = &[
+        ("minutes", 60.0),
+        ("hours", 60.0 * 60.0),
+        ("days", 24.0 * 60.0 * 60.0),
+        ("years", 365.25 * 24.0 * 60.0 * 60.0),
+    ];
+    if seconds < 1.0 {
+        return "less than a second".to_string();
+    }
+    let (unit, value) =
+        UNITS.iter()
+            .rev()
+            .find(|&&(_, len)| seconds >= len)
+            .map_or(("seconds", seconds), |&(unit, len)| (unit, seconds / len));
+    if value < 1e6 {
+        format!("{:.1} {}", value, unit)
+    } else {
+        format!("{:.1e} {}", value, unit)
+    }
+}
+
+// Checks and scores the passwords on standard input, one per line, as
+// libpwquality would.
+fn run_score(args: &[String]) -> Result<()> {
+    let mut config = None;
+    let mut wordlist = None;
+    let mut user = None;
+    for arg in read_args(&[usage::SCORE], args).map_err(UsageError)? {
+        match arg {
+            Arg::Flag("--config", value) => {
+                config = Some(PathBuf::from(value));
+            },
+            Arg::Flag("--wordlist", value) => {
+                wordlist = Some(PathBuf::from(value));
+            },
+            Arg::Flag("--user", value) => {
+                user = Some(value.to_string());
+            },
+            _ => {
+                return Err(UsageError(anyhow!(usage())).into());
+            },
+        }
+    }
+    let default_config = Path::new(pwquality::DEFAULT_CONFIG);
+    let settings =
+        match config {
+            Some(path) => pwquality::Settings::read(&path)?,
+            None if default_config.exists() =>
+                pwquality::Settings::read(default_config)?,
+            None => pwquality::Settings::default(),
+        };
+    let words =
+        match wordlist {
+            Some(path) => pwquality::read_words(&path)?,
+            None => HashSet::new(),
+        };
+    if let Err(err) = harden::harden() {
+        let msg = trf("could not disable core dumps: {}", &[&err]);
+        warn(&msg);
+    }
+    let mut stdout = io::stdout().lock();
+    let mut num_failed = 0;
+    for (idx, line) in io::stdin().lines().enumerate() {
+        let line = Zeroizing::new(line?);
+        let password = line.trim_end_matches('\r');
+        if password.is_empty() {
+            continue;
+        }
+        let context = trf("line {}", &[&(idx + 1)]);
+        match settings.score(password, user.as_deref(), &words) {
+            Ok(score) => writeln!(stdout, "{}: {}", context, score)?,
+            Err(reason) => {
+                writeln!(stdout, "{}: {}", context, reason)?;
+                num_failed += 1;
+            },
+        }
+    }
+    if num_failed > 0 {
+        let msg = trf("passwords failing the checks: {}", &[&num_failed]);
+        return Err(anyhow!(msg));
+    }
+    Ok(())
+}
+
+fn run_wifi(args: &[String]) -> Result<()> {
+    let mut ssid = None;
+    let mut passphrase_len = DEFAULT_WIFI_PASSPHRASE_LEN;
+    let mut psk = false;
+    let mut qr = false;
+    let mut hidden = false;
+    for arg in read_args(&[usage::WIFI], args).map_err(UsageError)? {
+        match arg {
+            Arg::Flag("--ssid", value) => {
+                ssid = Some(value.to_string());
+            },
+            Arg::Flag("-l" | "--length", value) => {
+                passphrase_len = parse_len(value).map_err(UsageError)?;
+            },
+            Arg::Flag("--psk", _) => {
+                psk = true;
+            },
+            Arg::Flag("--qr", _) => {
+                qr = true;
+            },
+            Arg::Flag("--hidden", _) => {
+                hidden = true;
+            },
+            _ => {
+                return Err(UsageError(anyhow!(usage())).into());
+            },
+        }
+    }
+    let Some(ssid) = ssid else {
+        let msg = tr("wifi needs --ssid");
+        return Err(UsageError(anyhow!(msg)).into());
+    };
+    if ssid.is_empty() || ssid.len() > MAX_SSID_LEN {
+        let msg = tr("SSID must be 1 to 32 bytes long");
+        return Err(UsageError(anyhow!(msg)).into());
+    }
+    if !WPA_PASSPHRASE_LEN.contains(&passphrase_len.get()) {
+        let msg = tr("WPA passphrase must be 8 to 63 characters long");
+        return Err(UsageError(anyhow!(msg)).into());
+    }
+    if let Err(err) = harden::harden() {
+        let msg = trf("could not disable core dumps: {}", &[&err]);
+        warn(&msg);
+    }
+    let spec =
+        PasswordSpec::builder()
+            .charset(parse_charset_spec(WIFI_CHARSET_SPEC)?)
+            .length(passphrase_len)
+            .build()?;
+    let mut rng = os_rng()?;
+    let mut passphrase = Zeroizing::new(String::new());
+    spec.generator().generate(&mut rng, &mut passphrase)?;
+    let mut stdout = io::stdout().lock();
+    if psk {
+        let psk = wpa_psk(&passphrase, &ssid)?;
+        let hex =
+            psk.iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>();
+        writeln!(stdout, "{}", *Zeroizing::new(hex))?;
+    } else {
+        writeln!(stdout, "{}", *passphrase)?;
+    }
+    if qr {
+        let payload = wifi_qr_payload(&ssid, &passphrase, hidden);
+        writeln!(stdout, "{}", *Zeroizing::new(payload))?;
+    }
+    Ok(())
+}
+
+fn confirm() -> Result<bool> {
+    let default = tr("y/N");
+    prompt(tr("Generate anyway?"), default, |answer| {
+        if answer == default {
+            return Ok(false);
+        }
+        match answer.to_ascii_lowercase().as_str() {
+            "y" | "yes" | "j" | "ja" => Ok(true),
+            "n" | "no" | "nein" => Ok(false),
+            _ => Err(anyhow!(tr("please answer yes or no"))),
+        }
+    })
+}
+
+fn prompt_missing(options: &mut Options) -> Result<()> {
+    if options.min_len.is_none() && options.max_len.is_none() {
+        let default = DEFAULT_PASSWORD_LEN.to_string();
+        let password_len = prompt(tr("Password length"), &default, parse_len)?;
+        options.min_len = Some(password_len);
+        options.max_len = Some(password_len);
+    }
+    let has_charset =
+        options.charset_spec.is_some()
+            || options.mode.is_some()
+            || options.preset.is_some();
+    if !has_charset {
+        let question =
+            tr("Character classes (S symbols, N numbers, C capitals, L \
+                lowercase; uppercase letters are required)");
+        let any = tr("any typeable");
+        options.mode =
+            prompt(question, any, |mode| {
+                if mode == any {
+                    return Ok(None);
+                }
+                parse_mode(mode)?;
+                Ok(Some(mode.to_string()))
+            })?;
+    }
+    if options.num_passwords.is_none() {
+        let default = DEFAULT_NUM_PASSWORDS.to_string();
+        let num_passwords =
+            prompt(tr("Number of passwords"), &default, parse_count)?;
+        options.num_passwords = Some(num_passwords);
+    }
+    Ok(())
+}
+
+fn prompt<T>(
+    question: &str,
+    default: &str,
+    parse: impl Fn(&str) -> Result<T>,
+) -> Result<T> {
+    let stdin = io::stdin();
+    loop {
+        eprint!("{} [{}]: ", question, default);
+        io::stderr().flush()?;
+        let mut answer = String::new();
+        if stdin.read_line(&mut answer)? == 0 {
+            return Err(anyhow!(tr("unexpected end of input")));
+        }
+        let answer = answer.trim();
+        let answer = if answer.is_empty() { default } else { answer };
+        match parse(answer) {
+            Ok(value) => return Ok(value),
+            Err(err) => eprintln!("{:#}", err),
+        }
+    }
+}
+
+fn open_token(kind: &TokenKind) -> Result<Box<dyn EntropyToken>> {
+    match kind {
+        #[cfg(feature = "yubikey")]
+        TokenKind::YubiKey => Ok(Box::new(YubiKey::new(YUBIKEY_SLOT)?)),
+        #[cfg(not(feature = "yubikey"))]
+        TokenKind::YubiKey => {
+            let msg = tr("this build does not support YubiKeys");
+            Err(UsageError(anyhow!(msg)).into())
+        },
+        TokenKind::HwRng => Ok(Box::new(HwRng::open()?)),
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        TokenKind::RdSeed => Ok(Box::new(RdSeed::new()?)),
+        #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+        TokenKind::RdSeed => {
+            let msg = tr("RDSEED is only available on x86 processors");
+            Err(UsageError(anyhow!(msg)).into())
+        },
+    }
+}
+
+fn read_extra_entropy() -> Result<Zeroizing<Vec<u8>>> {
+    fn read_line(question: &str) -> Result<Zeroizing<String>> {
+        eprint!("{}: ", question);
+        io::stderr().flush()?;
+        let mut answer = Zeroizing::new(String::new());
+        if io::stdin().read_line(&mut answer)? == 0 {
+            return Err(anyhow!(tr("unexpected end of input")));
+        }
+        Ok(answer)
+    }
+    let question = tr("Extra entropy from k keyboard, d dice or a file path");
+    let source = prompt(question, "k", |answer| Ok(answer.to_string()))?;
+    match source.as_str() {
+        "k" => loop {
+            let answer = read_line(tr("Type random keys, then press Enter"))?;
+            let answer = answer.trim_end_matches(['\r', '\n']);
+            if !answer.is_empty() {
+                return Ok(Zeroizing::new(answer.as_bytes().to_vec()));
+            }
+        },
+        "d" => loop {
+            let answer = read_line(tr("Dice rolls from 1 to 6"))?;
+            let rolls =
+                Zeroizing::new(
+                    answer.bytes()
+                        .filter(|byte| !byte.is_ascii_whitespace())
+                        .collect::<Vec<_>>(),
+                );
+            let is_roll = |byte: &u8| (b'1'..=b'6').contains(byte);
+            if !rolls.is_empty() && rolls.iter().all(is_roll) {
+                return Ok(rolls);
+            }
+            eprintln!("{}", tr("please enter only the digits 1 to 6"));
+        },
+        path => {
+            let context = trf("could not read \"{}\"", &[&path]);
+            Ok(Zeroizing::new(fs::read(path).context(context)?))
+        },
+    }
+}
+
+// Puts the generated password into the password store, in the multi-line
+// format pass uses: the password on the first line, followed by the lines
+// kept from the existing entry and any new fields.
+fn insert_pass(
+    entry: &PassEntry,
+    store: &PasswordStore,
+    kept: Option<Zeroizing<String>>,
+    generated: &[u8],
+) -> Result<()> {
+    let generated = std::str::from_utf8(generated)?;
+    let mut contents = Zeroizing::new(generated.to_string());
+    if let Some(kept) = &kept {
+        contents.push_str(kept);
+        if !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+    }
+    for (key, value) in &entry.fields {
+        contents.push_str(&format!("{}: {}\n", key, value));
+    }
+    store.write(&entry.name, &contents)?;
+    let message =
+        if entry.in_place {
+            format!("Replace generated password for {}.", entry.name)
+        } else {
+            format!("Add generated password for {}.", entry.name)
+        };
+    store.commit(&entry.name, &message)
+}
+
+// Reads the usernames given with --users, one per line, skipping blank
+// lines. Each must be fit to come before the colon of a line chpasswd or
+// newusers reads.
+fn read_users(path: &Path) -> Result<Vec<String>> {
+    let context = || trf("could not read \"{}\"", &[&path.display()]);
+    let text = fs::read_to_string(path).with_context(context)?;
+    let mut users = Vec::new();
+    for line in text.lines() {
+        let user = line.trim();
+        if user.is_empty() {
+            continue;
+        }
+        let valid =
+            !user.starts_with('-')
+                && !user.contains(|ch: char| {
+                    ch == ':' || ch.is_whitespace() || ch.is_control()
+                });
+        if !valid {
+            return Err(anyhow!(trf("invalid username: \"{}\"", &[&user])));
+        }
+        users.push(user.to_string());
+    }
+    if users.is_empty() {
+        let msg = trf("no usernames in \"{}\"", &[&path.display()]);
+        return Err(anyhow!(msg));
+    }
+    Ok(users)
+}
+
+// Reads a word list of one word per line, taking the last field of each
+// line so that diceware lists, which number their words, can be used as
+// they are.
+#[cfg(unix)]
+fn read_wordlist(path: &Path) -> Result<PassphraseGenerator> {
+    let context = || trf("could not read \"{}\"", &[&path.display()]);
+    let text = fs::read_to_string(path).with_context(context)?;
+    let words = text.lines().filter_map(|line| line.split_whitespace().last());
+    PassphraseGenerator::new(words, NonZeroUsize::MIN, "")
+        .with_context(context)
+}
+
+// Checks that the options generate what --store can save: exactly one
+// secret for the keyring, Vault or a systemd credential, since an entry,
+// key or credential holds one, and records rather than formatted output for
+// a database or Bitwarden.
+fn check_store(options: &Options) -> Result<()> {
+    let conflicting =
+        options.batch
+            || options.split.is_some()
+            || options.encrypt_to.is_some()
+            || options.format != Format::Text
+            || options.pass_entry.is_some();
+    match &options.store {
+        None => {},
+        Some(
+            store @
+                (Store::Keyring(_) | Store::Vault(_) | Store::SystemdCreds(_)),
+        ) => {
+            let conflicting =
+                conflicting
+                    || options.num_passwords.is_some_and(|count| {
+                        count.get() > 1
+                    })
+                    || options.credentials.is_some()
+                    || options.users_file.is_some();
+            if conflicting {
+                let msg = trf(
+                    "--store {} generates one password and does not take \
+                     --batch, --split, --encrypt-to, --format, --users, \
+                     credentials or insert-pass",
+                    &[&store.kind()],
+                );
+                return Err(anyhow!(msg));
+            }
+        },
+        Some(store @ (Store::Kdbx(_, entry) | Store::Bitwarden(entry))) => {
+            if conflicting || options.hash {
+                let msg = trf(
+                    "--store {} does not take --batch, --split, \
+                     --encrypt-to, --format, --hash or insert-pass",
+                    &[&store.kind()],
+                );
+                return Err(anyhow!(msg));
+            }
+            let usernames =
+                options.credentials.is_some() || options.users_file.is_some();
+            if entry.username.is_some() && usernames {
+                let msg = tr(
+                    "--entry cannot give a username with credentials or \
+                     --users, which give them",
+                );
+                return Err(anyhow!(msg));
+            }
+        },
+    }
+    Ok(())
+}
+
+// The name of a character's class in large print.
+fn class_name(class: Option<CharClass>, ch: char) -> &'static str {
+    match class {
+        Some(CharClass::Lowercase) => tr("lowercase letter"),
+        Some(CharClass::Uppercase) => tr("uppercase letter"),
+        Some(CharClass::Digits) => tr("digit"),
+        Some(CharClass::Symbols) => tr("symbol"),
+        None if ch == ' ' => tr("space"),
+        None => tr("other character"),
+    }
+}
+
+// The spoken name of a symbol or space with --format spelling.
+fn symbol_name(ch: char) -> Option<&'static str> {
+    let name =
+        match ch {
+            '!' => tr("exclamation mark"),
+            '"' => tr("double quote"),
+            '#' => tr("hash"),
+            '$' => tr("dollar sign"),
+            '%' => tr("percent sign"),
+            '&' => tr("ampersand"),
+            '\'' => tr("apostrophe"),
+            '(' => tr("left parenthesis"),
+            ')' => tr("right parenthesis"),
+            '*' => tr("asterisk"),
+            '+' => tr("plus sign"),
+            ',' => tr("comma"),
+            '-' => tr("hyphen"),
+            '.' => tr("full stop"),
+            '/' => tr("slash"),
+            ':' => tr("colon"),
+            ';' => tr("semicolon"),
+            '<' => tr("less-than sign"),
+            '=' => tr("equals sign"),
+            '>' => tr("greater-than sign"),
+            '?' => tr("question mark"),
+            '@' => tr("at sign"),
+            '[' => tr("left bracket"),
+            '\\' => tr("backslash"),
+            ']' => tr("right bracket"),
+            '^' => tr("caret"),
+            '_' => tr("underscore"),
+            '`' => tr("backtick"),
+            '{' => tr("left brace"),
+            '|' => tr("vertical bar"),
+            '}' => tr("right brace"),
+            '~' => tr("tilde"),
+            ' ' => tr("space"),
+            _ => return None,
+        };
+    Some(name)
+}
+
+// Checks that the options generate one secret for --format ansible-vault
+// to encrypt, since an encrypted string holds one.
+fn check_ansible_vault(options: &Options) -> Result<()> {
+    let conflicting =
+        options.num_passwords.is_some_and(|count| count.get() > 1)
+            || options.batch
+            || options.split.is_some()
+            || options.encrypt_to.is_some()
+            || options.users_file.is_some()
+            || options.store.is_some()
+            || options.credentials.is_some()
+            || options.pass_entry.is_some();
+    if options.ansible_vault && conflicting {
+        let msg = tr(
+            "--format ansible-vault encrypts one password and does not take \
+             --batch, --split, --encrypt-to, --users, --store, credentials \
+             or insert-pass",
+        );
+        return Err(anyhow!(msg));
+    }
+    Ok(())
+}
+
+// Opens the database --store kdbx adds entries to, or creates it, asking
+// for its password.
+fn open_kdbx(path: &Path) -> Result<Database> {
+    if path.exists() {
+        let password = tty::read_hidden(tr("Database password"))?;
+        return Database::open(path, &password);
+    }
+    let password = tty::read_hidden(tr("New database password"))?;
+    if password.is_empty() {
+        return Err(anyhow!(tr("the database password is empty")));
+    }
+    let repeated = tty::read_hidden(tr("Repeat the database password"))?;
+    if password != repeated {
+        return Err(anyhow!(tr("the passwords do not match")));
+    }
+    Database::create(path, &password)
+}
+
+// A record generated for --store kdbx or bitwarden, read back from its
+// JSON.
+struct Record {
+    username: Option<Zeroizing<String>>,
+    password: Zeroizing<String>,
+    totp_secret: Option<Zeroizing<String>>,
+}
+
+fn read_records(generated: &[u8]) -> Result<Vec<Record>> {
+    let mut records = Vec::new();
+    for line in std::str::from_utf8(generated)?.lines() {
+        let mut fields =
+            serde_json::from_str::<BTreeMap<String, String>>(line)?;
+        let username = fields.remove("username").map(Zeroizing::new);
+        let totp_secret = fields.remove("totp_secret").map(Zeroizing::new);
+        // The field left is the password, or the token or code.
+        let password =
+            fields.pop_first()
+                .map(|(_, password)| Zeroizing::new(password))
+                .unwrap_or_default();
+        records.push(Record { username, password, totp_secret });
+    }
+    Ok(records)
+}
+
+// Adds an entry for each record generated, taking the username and TOTP
+// secret from credentials, and saves the database.
+fn add_kdbx_entries(
+    entry: &StoreEntry,
+    database: &mut Database,
+    generated: &[u8],
+) -> Result<()> {
+    for record in read_records(generated)? {
+        let username =
+            record.username.as_deref()
+                .map(String::as_str)
+                .or(entry.username.as_deref())
+                .unwrap_or("");
+        database.add_entry(&NewEntry {
+            title: &entry.title,
+            username,
+            password: &record.password,
+            url: entry.uri.as_deref(),
+            totp_secret: record.totp_secret.as_deref().map(String::as_str),
+        })?;
+    }
+    database.save()
+}
+
+// Creates a login item for each record generated, as add_kdbx_entries adds
+// entries.
+fn add_bitwarden_items(
+    entry: &StoreEntry,
+    vault: &Vault,
+    generated: &[u8],
+) -> Result<()> {
+    for record in read_records(generated)? {
+        let username =
+            record.username.as_deref()
+                .map(String::as_str)
+                .or(entry.username.as_deref());
+        vault.create(&LoginItem {
+            name: &entry.title,
+            username,
+            password: &record.password,
+            uri: entry.uri.as_deref(),
+            totp_secret: record.totp_secret.as_deref().map(String::as_str),
+        })?;
+    }
+    Ok(())
+}
+
+fn breach_checker(options: &Options) -> Result<Option<PwnedPasswords>> {
+    if options.check_hibp {
+        return Ok(Some(PwnedPasswords::online()));
+    }
+    match &options.check_offline {
+        Some(path) => {
+            let list = BreachList::open(path)?;
+            Ok(Some(PwnedPasswords::offline(list)))
+        },
+        None => Ok(None),
+    }
+}
+
+fn execute(
+    options: &Options,
+    out: &mut dyn Write,
+    line_width: Option<usize>,
+    interactive: bool,
+    pwned: Option<&PwnedPasswords>,
+    audit_log: Option<&mut AuditLog>,
+) -> Result<()> {
+    let spec;
+    let composed;
+    let transformed;
+    let mut job =
+        match (&options.token, &options.composition) {
+            (Some(token), _) => Job::token(token, options),
+            (None, Some(composition)) => {
+                composed = compose(options, composition)?;
+                Job::composed(&composed, options)
+            },
+            (None, None) => {
+                spec = resolve(options).map_err(UsageError)?.build()?;
+                Job::spec(&spec)
+            },
+        };
+    if !options.transforms.is_empty() {
+        let transforms = options.transforms.clone();
+        transformed = Transformed::new(job.generator, transforms);
+        job = job.transformed(&transformed);
+    }
+    let post_scripted =
+        options.post_script.as_ref().map(|script| {
+            PostScript::new(job.generator, script)
+        });
+    if let Some(post_scripted) = &post_scripted {
+        job = job.post_scripted(post_scripted);
+    }
+    if let Some(credentials) = &options.credentials {
+        job = job.credentials(credentials);
+    }
+    if let Some(fields) = &options.fields {
+        let conflicting =
+            options.credentials.is_some()
+                || options.token.is_some()
+                || options.composition.is_some();
+        if conflicting {
+            let msg = tr(
+                "--fields cannot be used with --compose, credentials or \
+                 other commands",
+            );
+            return Err(UsageError(anyhow!(msg)).into());
+        }
+        job = job.columns(fields);
+    }
+    let records =
+        job.credentials.is_some()
+            || options.users.is_some()
+            || options.format != Format::Text;
+    if options.split.is_some() && records {
+        let msg =
+            tr("--split cannot be used with --format, --users or credentials");
+        return Err(UsageError(anyhow!(msg)).into());
+    }
+    let collected =
+        options.pass_entry.is_some()
+            || options.store.is_some()
+            || options.vault_password_file.is_some();
+    let conflicting =
+        options.split.is_some()
+            || options.format == Format::Chpasswd
+            || collected;
+    if options.speakable && conflicting {
+        let msg = tr(
+            "--speakable cannot be used with --split, --format chpasswd or \
+             ansible-vault, --store or insert-pass",
+        );
+        return Err(UsageError(anyhow!(msg)).into());
+    }
+    let conflicting = conflicting || records || options.speakable;
+    if let Some(accessible) = options.accessible.filter(|_| conflicting) {
+        let msg = trf(
+            "{} prints passwords alone, as text, and cannot be used with \
+             --format, --users, --split, --speakable, credentials, --store or \
+             insert-pass",
+            &[&accessible.option()],
+        );
+        return Err(UsageError(anyhow!(msg)).into());
+    }
+    // The entropy of passwords from a post-script is unknown rather than
+    // low, so they are only warned of if the script changes any.
+    let min_entropy = job.generator.entropy_bits();
+    match options.min_entropy {
+        Some(required) if min_entropy < required => {
+            let bits = min_entropy;
+            let err = PassgenError::InsufficientEntropy { bits, required };
+            return Err(err.into());
+        },
+        None if post_scripted.is_some() => (),
+        None if job.secret && min_entropy < WEAK_ENTROPY_BITS => {
+            let msg = trf(
+                "password entropy is only {} bits; consider a longer \
+                 password or a larger character set",
+                &[&format!("{:.1}", min_entropy)],
+            );
+            warn(&msg);
+            if interactive && !options.dry_run && !confirm()? {
+                let bits = min_entropy;
+                let required = WEAK_ENTROPY_BITS;
+                let err = PassgenError::InsufficientEntropy { bits, required };
+                return Err(err.into());
+            }
+        },
+        _ => (),
+    }
+    if let Some(max_memory) = options.max_memory {
+        check_memory(job_memory(&job, options), max_memory)?;
+    }
+    if options.dry_run {
+        if let Some(charset) = job.charset {
+            writeln!(out, "charset: {}", format_charset_spec(charset))?;
+        }
+        for line in &job.description {
+            writeln!(out, "{}", line)?;
+        }
+        return Ok(());
+    }
+    let start = Instant::now();
+    let tests = health_tests(options);
+    let rct_cutoff = tests.repetition_count().cutoff();
+    let apt = tests.adaptive_proportion();
+    let apt_cutoff = (apt.cutoff(), apt.window());
+    let usage = RngUsage::default();
+    let show_progress =
+        !options.quiet
+            && job.count.get() >= PROGRESS_THRESHOLD
+            && io::stderr().is_terminal();
+    let progress =
+        if show_progress {
+            let progress = ProgressBar::new(job.count.get() as u64);
+            let style =
+                ProgressStyle::with_template(
+                    "{bar:40} {pos}/{len} {per_sec} eta {eta}",
+                )?;
+            progress.set_style(style);
+            progress
+        } else {
+            ProgressBar::hidden()
+        };
+    // Long runs of passwords printed one to a line are generated on as
+    // many threads as there are processors.
+    let bulk =
+        job.count.get() >= bulk::BULK_THRESHOLD
+            && job.fields().len() == 1
+            && options.users.is_none()
+            && options.split.is_none()
+            && options.accessible.is_none()
+            && options.format == Format::Text
+            && !options.speakable
+            && !use_columns(&job, options, line_width)
+            && pwned.is_none()
+            && !job.unique;
+    if bulk {
+        let threads = options.threads.map_or(1, NonZeroUsize::get);
+        bulk::write_lines(
+            out,
+            job.generator,
+            job.count,
+            job.max_len,
+            threads,
+            &progress,
+            |write| with_job_rng(options, &usage, write),
+        )?;
+    } else {
+        with_job_rng(options, &usage, |rng| {
+            write_job(&job, options, out, line_width, pwned, rng, &progress)
+        })?;
+    }
+    progress.finish_and_clear();
+    if post_scripted.as_ref().is_some_and(PostScript::changed) {
+        warn(tr(
+            "the post-script changed passwords, so nothing is known of their \
+             entropy",
+        ));
+    }
+    if usage.fell_back.load(Ordering::Relaxed) {
+        warn(tr(
+            "the operating system's random number generator failed; some \
+             passwords were drawn from the ChaCha20 fallback",
+        ));
+    }
+    if options.verbose {
+        for line in &job.description {
+            eprintln!("{}", line);
+        }
+        eprintln!("{}", trf("rng: {}", &[&options.rng.source()]));
+        let policy = &options.rng_fallback;
+        eprintln!("{}", trf("rng failure policy: {}", &[policy]));
+        let msg =
+            "health tests: repetition cutoff {}, adaptive proportion cutoff \
+             {} of {}";
+        eprintln!("{}", trf(msg, &[&rct_cutoff, &apt_cutoff.0, &apt_cutoff.1]));
+        if let Some(extra) = &options.extra_input {
+            let msg = "extra entropy: {} bytes mixed in";
+            eprintln!("{}", trf(msg, &[&extra.len()]));
+        }
+        for kind in &options.entropy_tokens {
+            eprintln!("{}", trf("entropy token: {}", &[&kind.name()]));
+        }
+        if let Some(pwned) = pwned {
+            let num_breached = pwned.num_breached();
+            let msg = "breached passwords redrawn: {}";
+            eprintln!("{}", trf(msg, &[&num_breached]));
+        }
+        let millis = start.elapsed().as_secs_f64() * 1000.0;
+        eprintln!("{}", trf("time: {} ms", &[&format!("{:.3}", millis)]));
+    }
+    if options.stats {
+        let elapsed = start.elapsed().as_secs_f64();
+        let rate = job.count.get() as f64 / elapsed;
+        let elapsed = format!("{:.3}", elapsed);
+        eprintln!("{}", trf("wall-clock time: {} s", &[&elapsed]));
+        let entropy_bytes = usage.bytes.load(Ordering::Relaxed);
+        eprintln!("{}", trf("entropy consumed: {} bytes", &[&entropy_bytes]));
+        let rate = format!("{:.0}", rate);
+        eprintln!("{}", trf("rate: {} passwords/s", &[&rate]));
+        match peak_memory_kib() {
+            Some(peak) => eprintln!("{}", trf("peak memory: {} KiB", &[&peak])),
+            None => eprintln!("{}", tr("peak memory: unavailable")),
+        }
+    }
+    if let Some(audit_log) = audit_log {
+        let label =
+            options.label.as_ref()
+                .map(Label::template)
+                .or(options.pass_entry.as_ref().map(|entry| &*entry.name));
+        audit_log.record(&AuditEntry {
+            label,
+            preset: options.preset,
+            kind: job.name,
+            count: job.count.get(),
+            entropy_bits: min_entropy,
+            min_entropy_bits: options.min_entropy,
+            rng: options.rng.source().to_string(),
+            description: &job.description,
+        })?;
+    }
+    Ok(())
+}
+
+// Generates the job's passwords and writes them out as the options ask.
+fn write_job(
+    job: &Job,
+    options: &Options,
+    out: &mut dyn Write,
+    line_width: Option<usize>,
+    pwned: Option<&PwnedPasswords>,
+    mut rng: &mut dyn CryptoRngCore,
+    progress: &ProgressBar,
+) -> Result<()> {
+    // Labels draw their random characters from a generator of their own,
+    // as the passwords' is held by the iterator drawing them.
+    let mut labels =
+        match &options.label {
+            Some(label)
+                if matches!(options.format, Format::Csv | Format::Json) =>
+            {
+                Some((label, FallbackRng::new(options.rng_fallback)?))
+            },
+            _ => None,
+        };
+    let labelled = labels.is_some();
+    let mut next_label = |seq| {
+        labels.as_mut()
+            .map(|(label, label_rng)| label.expand(seq, label_rng))
+            .transpose()
+    };
+    let fields = job.fields();
+    if fields.len() > 1 || options.users.is_some() {
+        let mut names =
+            fields.iter().map(|&(name, _)| name).collect::<Vec<_>>();
+        // chpasswd -e reads hashes in place of passwords.
+        let hash_only = options.format == Format::Chpasswd;
+        if options.users.is_some() {
+            names.insert(0, "username");
+        }
+        if labelled {
+            names.insert(0, "label");
+        }
+        if options.speakable {
+            names.push("speakable");
+        }
+        if options.hash && !hash_only {
+            names.push("hash");
+        }
+        let mut output = Records::new(out, options.format, &names);
+        for idx in 0..job.count.get() {
+            let mut values = Vec::with_capacity(names.len());
+            if let Some(label) = next_label(idx + 1)? {
+                values.push(Zeroizing::new(label));
+            }
+            if let Some(users) = &options.users {
+                values.push(Zeroizing::new(users[idx].clone()));
+            }
+            let mut speakable = None;
+            for &(name, generator) in &fields {
+                let value =
+                    loop {
+                        let mut value = Zeroizing::new(String::new());
+                        generator.generate(&mut rng, &mut value)?;
+                        match pwned {
+                            Some(pwned) if name == job.name => {
+                                if !pwned.breached(&value)? {
+                                    break value;
+                                }
+                            },
+                            _ => break value,
+                        }
+                    };
+                if options.speakable && name == job.name {
+                    let mut spaced = Zeroizing::new(String::new());
+                    write_speakable(&value, &mut spaced);
+                    speakable = Some(spaced);
+                }
+                if options.hash && name == job.name {
+                    let hash = Zeroizing::new(sha512_crypt(&value, &mut rng)?);
+                    if !hash_only {
+                        values.push(value);
+                    }
+                    values.push(hash);
+                    continue;
+                }
+                values.push(value);
+            }
+            if let Some(speakable) = speakable {
+                let at = values.len() - usize::from(options.hash);
+                values.insert(at, speakable);
+            }
+            let values =
+                values.iter().map(|value| value.as_str()).collect::<Vec<_>>();
+            output.write(&values)?;
+            progress.inc(1);
+        }
+        output.finish()?;
+    } else {
+        // Breached passwords are skipped, so with the check there is no
+        // telling how many must be drawn.
+        let num_draws =
+            if pwned.is_some() { usize::MAX } else { job.count.get() };
+        let mut passwords =
+            Passwords::new(job.generator, num_draws, &mut rng)
+                .with_source(options.rng.source());
+        if job.unique {
+            passwords = passwords.unique()?;
+        }
+        let passwords =
+            passwords
+                .filter_map(|password| unbreached(password, pwned))
+                .take(job.count.get());
+        if let Some((threshold, num_shares)) = options.split {
+            let mut share_rng = FallbackRng::new(options.rng_fallback)?;
+            for (idx, password) in passwords.enumerate() {
+                let password = password?;
+                let secret = password.as_bytes();
+                let shares = split_secret(
+                    secret,
+                    threshold,
+                    num_shares,
+                    &mut share_rng,
+                )?;
+                if idx > 0 {
+                    writeln!(out)?;
+                }
+                for share in shares {
+                    writeln!(out, "{}", *Zeroizing::new(share.to_string()))?;
+                }
+                progress.inc(1);
+            }
+        } else if let Some(accessible) = options.accessible {
+            match accessible {
+                Accessible::LargePrint => {
+                    let mut output = LargePrint::new(out, class_name);
+                    for password in passwords {
+                        output.write(&password?)?;
+                        progress.inc(1);
+                    }
+                    output.finish()?;
+                },
+                Accessible::Braille => {
+                    let mut output = Braille::new(out, BRAILLE_LINE_WIDTH);
+                    for password in passwords {
+                        output.write(&password?)?;
+                        progress.inc(1);
+                    }
+                    output.finish()?;
+                },
+                Accessible::Spelling => {
+                    let mut output =
+                        Spelling::new(out, class_name, symbol_name);
+                    for password in passwords {
+                        output.write(&password?)?;
+                        progress.inc(1);
+                    }
+                    output.finish()?;
+                },
+            }
+        } else if options.format != Format::Text || options.speakable {
+            let mut names = vec![job.name];
+            if labelled {
+                names.insert(0, "label");
+            }
+            if options.speakable {
+                names.push("speakable");
+            }
+            let mut output = Records::new(out, options.format, &names);
+            for (idx, password) in passwords.enumerate() {
+                let password = password?;
+                let label = next_label(idx + 1)?;
+                let mut values = Vec::with_capacity(names.len());
+                values.extend(label.as_deref());
+                values.push(password.as_str());
+                let mut spaced = Zeroizing::new(String::new());
+                if options.speakable {
+                    write_speakable(&password, &mut spaced);
+                    values.push(&spaced);
+                }
+                output.write(&values)?;
+                progress.inc(1);
+            }
+            output.finish()?;
+        } else {
+            let mut output =
+                if use_columns(job, options, line_width) {
+                    let line_width = line_width.unwrap_or(DEFAULT_LINE_WIDTH);
+                    Columns::new(out, line_width, job.max_len)
+                } else {
+                    Columns::single(out)
+                };
+            for password in passwords {
+                output.write(&password?)?;
+                progress.inc(1);
+            }
+            output.finish()?;
+        }
+    }
+    Ok(())
+}
+
+// Whether passwords printed as text are laid out in columns rather than
+// one to a line.
+fn use_columns(
+    job: &Job,
+    options: &Options,
+    line_width: Option<usize>,
+) -> bool {
+    match options.layout {
+        Some(Layout::Columns) => true,
+        Some(Layout::Lines) => false,
+        None =>
+            line_width.is_some()
+                && job.count.get() > 1
+                && !job.charset.is_some_and(|c| c.contains(&b' ')),
+    }
+}
+
+// What the generators of a job have drawn, across all its threads.
+#[derive(Default)]
+struct RngUsage {
+    bytes: AtomicU64,
+    // Whether the operating system's generator failed, so that some
+    // passwords were drawn from the fallback.
+    fell_back: AtomicBool,
+}
+
+// Returns the operating system's generator once start-up samples drawn from
+// it pass the health tests. Its output is tested no further: the continuous
+// tests are for entropy input, and on every byte of a bulk job or of a
+// long-running server even a tiny false alarm rate would add up.
+fn os_rng() -> Result<OsRng> {
+    HealthTestedRng::new(OsRng, HealthTests::default())
+        .startup(HEALTH_STARTUP_SAMPLES)?;
+    Ok(OsRng)
+}
+
+fn health_tests(options: &Options) -> HealthTests {
+    HealthTests::new(
+        options.health_min_entropy,
+        options.health_alpha_log2,
+        options.health_window,
+    )
+}
+
+// Builds the generator the options ask for, with its health tests run at
+// start-up and any extra input mixed in, and passes it to the function,
+// counting what is drawn from it.
+fn with_job_rng<T>(
+    options: &Options,
+    usage: &RngUsage,
+    use_rng: impl FnOnce(&mut dyn CryptoRngCore) -> Result<T>,
+) -> Result<T> {
+    let mut fallback = FallbackRng::new(options.rng_fallback)?;
+    let mut drbg;
+    let rng: &mut dyn CryptoRngCore =
+        match options.rng {
+            RngKind::Os => {
+                // The operating system's output is the passwords' entropy
+                // input, tested with start-up samples drawn before any
+                // password.
+                HealthTestedRng::new(&mut fallback, health_tests(options))
+                    .startup(HEALTH_STARTUP_SAMPLES)?;
+                &mut fallback
+            },
+            RngKind::ChaCha => {
+                drbg = ChaChaDrbg::with_health_tests(
+                    ChaChaDrbg::DEFAULT_RESEED_INTERVAL,
+                    health_tests(options),
+                )?;
+                &mut drbg
+            },
+        };
+    let mut mixer;
+    let rng: &mut dyn CryptoRngCore =
+        match &options.extra_input {
+            Some(extra) => {
+                mixer = EntropyMixer::new(rng, extra);
+                &mut mixer
+            },
+            None => rng,
+        };
+    let mut rng = CountingRng { rng, count: &usage.bytes };
+    let result = use_rng(&mut rng);
+    if fallback.fell_back() {
+        usage.fell_back.store(true, Ordering::Relaxed);
+    }
+    result
+}
+
+// Estimates the memory the job holds beyond what every job needs: the
+// usernames, any output collected for a store and any passwords remembered
+// so that they are distinct.
+fn job_memory(job: &Job, options: &Options) -> u64 {
+    let count = job.count.get() as u64;
+    let password_len = job.max_len as u64;
+    let mut needed = 0;
+    if let Some(users) = &options.users {
+        needed +=
+            users.iter()
+                .map(|user| user.len() as u64 + HELD_STRING_OVERHEAD)
+                .sum::<u64>();
+    }
+    let collected =
+        options.pass_entry.is_some()
+            || options.store.is_some()
+            || options.vault_password_file.is_some();
+    if collected {
+        let num_fields = job.fields().len() as u64;
+        let record_len = num_fields * (password_len + RECORD_FIELD_OVERHEAD);
+        needed = needed.saturating_add(count.saturating_mul(record_len));
+    }
+    if job.unique {
+        let held_len = password_len + HELD_STRING_OVERHEAD;
+        needed = needed.saturating_add(count.saturating_mul(held_len));
+    }
+    needed
+}
+
+fn check_memory(needed: u64, max_memory: u64) -> Result<()> {
+    if needed > max_memory {
+        let msg = trf(
+            "the job needs about {} bytes of memory, more than the {} \
+             allowed",
+            &[&needed, &max_memory],
+        );
+        return Err(anyhow!(msg));
+    }
+    Ok(())
+}
+
+// Passes on a password unless it appears in breaches, in which case it is
+// dropped so that another is drawn.
+fn unbreached(
+    password: passgen::Result<String>,
+    pwned: Option<&PwnedPasswords>,
+) -> Option<Result<Zeroizing<String>>> {
+    let password =
+        match password {
+            Ok(password) => Zeroizing::new(password),
+            Err(err) => return Some(Err(err.into())),
+        };
+    match pwned.map(|pwned| pwned.breached(&password)) {
+        Some(Ok(true)) => None,
+        Some(Err(err)) => Some(Err(err)),
+        Some(Ok(false)) | None => Some(Ok(password)),
+    }
+}
+
+struct CountingRng<'a, R> {
+    rng: R,
+    count: &'a AtomicU64,
+}
+
+impl<R: RngCore> RngCore for CountingRng<'_, R> {
+    fn next_u32(&mut self) -> u32 {
+        self.count.fetch_add(4, Ordering::Relaxed);
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.count.fetch_add(8, Ordering::Relaxed);
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.count.fetch_add(dest.len() as u64, Ordering::Relaxed);
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.count.fetch_add(dest.len() as u64, Ordering::Relaxed);
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+impl<R: CryptoRng> CryptoRng for CountingRng<'_, R> {}
+
+// Asks the kernel rather than reading /proc/self/status, which the sandbox
+// cannot open.
+#[cfg(unix)]
+fn peak_memory_kib() -> Option<u64> {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    let max_rss = u64::try_from(unsafe { usage.assume_init() }.ru_maxrss).ok()?;
+    // Given in bytes on macOS and in KiB elsewhere.
+    if cfg!(target_os = "macos") {
+        Some(max_rss / 1024)
+    } else {
+        Some(max_rss)
+    }
+}
+
+#[cfg(not(unix))]
+fn peak_memory_kib() -> Option<u64> {
+    None
+}
+
+fn resolve(options: &Options) -> Result<PasswordSpecBuilder> {
+    let (charset, policy) =
+        match (&options.charset_spec, &options.mode, options.preset) {
+            (Some(_), Some(_), _) => {
+                let msg = tr("-c and -M cannot be used together");
+                return Err(anyhow!(msg));
+            },
+            (Some(_), _, Some(_)) | (_, Some(_), Some(_)) => {
+                let msg = tr("--preset cannot be used with -c or -M");
+                return Err(anyhow!(msg));
+            },
+            (None, None, Some(preset)) => {
+                debug!(preset = %preset, "using preset");
+                (preset.charset(), preset.policy())
+            },
+            (Some(charset_spec), None, None) =>
+                (parse_charset_spec(charset_spec)?, Policy::default()),
+            (None, Some(mode), None) => {
+                debug!(mode = %mode, "using apg mode string");
+                parse_mode(mode)?
+            },
+            (None, None, None) => {
+                debug!("using typeable characters");
+                (Charset::typeable(), Policy::default())
+            },
+        };
+    let charset =
+        match &options.exclude_chars {
+            Some(exclude_chars) => {
+                let remaining = charset.without(exclude_chars.as_bytes())?;
+                let excluded = charset.len() - remaining.len();
+                debug!(excluded, "excluded characters");
+                remaining
+            },
+            None => charset,
+        };
+    let charset =
+        match &options.confusable_chars {
+            Some(confusable_chars) => {
+                let remaining = charset.without(confusable_chars.as_bytes())?;
+                let excluded = charset.len() - remaining.len();
+                debug!(excluded, "excluded confusable characters");
+                remaining
+            },
+            None => charset,
+        };
+    let min_len = options.min_len.unwrap_or(DEFAULT_PASSWORD_LEN);
+    let max_len =
+        options.max_len.unwrap_or_else(|| min_len.max(DEFAULT_PASSWORD_LEN));
+    let num_passwords = options.num_passwords.unwrap_or(DEFAULT_NUM_PASSWORDS);
+    let mut builder =
+        PasswordSpec::builder()
+            .charset(charset)
+            .min_length(min_len)
+            .max_length(max_len)
+            .count(num_passwords)
+            .policy(policy);
+    if let Some(max_attempts) = options.max_attempts {
+        builder = builder.max_attempts(max_attempts);
+    }
+    if let Some(timeout) = options.timeout {
+        builder = builder.timeout(timeout);
+    }
+    Ok(builder)
+}
+
+// Builds the generator for --compose, which takes the place of the
+// character set and policy.
+fn compose(options: &Options, composition: &str) -> Result<ComposedGenerator> {
+    let conflicting =
+        options.charset_spec.is_some()
+            || options.mode.is_some()
+            || options.preset.is_some()
+            || options.exclude_chars.is_some()
+            || options.confusable_chars.is_some();
+    if conflicting {
+        let msg = tr(
+            "--compose cannot be used with -c, -M, --preset, -E or \
+             --confusables",
+        );
+        return Err(UsageError(anyhow!(msg)).into());
+    }
+    let min_len = options.min_len.unwrap_or(DEFAULT_PASSWORD_LEN);
+    let max_len =
+        options.max_len.unwrap_or_else(|| min_len.max(DEFAULT_PASSWORD_LEN));
+    Ok(ComposedGenerator::new(composition, min_len, max_len)?)
+}
+
+// What execute generates, from a password specification, a composition or
+// a token mode.
+struct Job<'a> {
+    generator: &'a (dyn Generator + Sync),
+    // The name of the field the generator fills in records.
+    name: &'a str,
+    credentials: Option<&'a Credentials>,
+    // The fields given with --fields, in place of the generator's.
+    columns: Option<&'a [(String, SharedGenerator)]>,
+    count: NonZeroUsize,
+    max_len: usize,
+    charset: Option<&'a Charset>,
+    description: Vec<String>,
+    // Whether low entropy deserves a warning, which it does unless the
+    // output is public or guesses at it are limited.
+    secret: bool,
+    // Whether every output must differ from the others.
+    unique: bool,
+}
+
+impl<'a> Job<'a> {
+    fn spec(spec: &'a PasswordSpec) -> Self {
+        Job {
+            generator: spec.generator(),
+            name: "password",
+            credentials: None,
+            columns: None,
+            count: spec.count(),
+            max_len: spec.max_length().get(),
+            charset: Some(spec.charset()),
+            description: describe(spec),
+            secret: true,
+            unique: false,
+        }
+    }
+
+    fn composed(generator: &'a ComposedGenerator, options: &Options) -> Self {
+        let count = options.num_passwords.unwrap_or(DEFAULT_NUM_PASSWORDS);
+        let min_len = generator.min_length();
+        let max_len = generator.max_length();
+        let mut description = vec![format!("composition: {}", generator)];
+        if min_len == max_len {
+            description.push(format!("length: {}", min_len));
+        } else {
+            description.push(format!("length: {} to {}", min_len, max_len));
+        }
+        description.push(format!("count: {}", count));
+        description.push(format!(
+            "entropy per password: {:.1} bits for the likeliest, {:.1} bits \
+             on average",
+            generator.entropy_bits(),
+            generator.average_entropy_bits(),
+        ));
+        Job {
+            generator,
+            name: "password",
+            credentials: None,
+            columns: None,
+            count,
+            max_len: max_len.get(),
+            charset: None,
+            description,
+            secret: true,
+            unique: false,
+        }
+    }
+
+    fn transformed(mut self, transformed: &'a Transformed) -> Self {
+        let transforms = transformed.transforms();
+        let names =
+            transforms.iter()
+                .map(|transform| transform.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+        let added_bits =
+            transformed.entropy_bits() - self.generator.entropy_bits();
+        self.description.push(format!(
+            "transforms: {} (+{:.1} bits)",
+            names,
+            added_bits,
+        ));
+        self.max_len +=
+            transforms.iter()
+                .map(|transform| transform.added_length())
+                .sum::<usize>();
+        self.generator = transformed;
+        self
+    }
+
+    fn post_scripted(mut self, post_scripted: &'a PostScript) -> Self {
+        let path = post_scripted.path().display();
+        let line = format!("post-script: {} (entropy unknown)", path);
+        self.description.push(line);
+        self.generator = post_scripted;
+        self
+    }
+
+    fn credentials(mut self, credentials: &'a Credentials) -> Self {
+        let username = &credentials.username;
+        self.description.push(format!("username style: {}", username.style()));
+        if let Some(totp) = &credentials.totp {
+            let line = format!("totp secret: {} bits", totp.entropy_bits());
+            self.description.push(line);
+        }
+        self.credentials = Some(credentials);
+        self
+    }
+
+    // The fields of each record are those given instead, with the weakest
+    // checked against --min-entropy, and the one named password hashed,
+    // checked for breaches and made speakable.
+    fn columns(mut self, columns: &'a [(String, SharedGenerator)]) -> Self {
+        let weakest =
+            columns.iter().min_by(|a, b| {
+                a.1.entropy_bits().total_cmp(&b.1.entropy_bits())
+            });
+        if let Some((_, generator)) = weakest {
+            self.generator = &**generator;
+        }
+        if let [(name, _)] = columns {
+            self.name = name;
+        }
+        self.description.clear();
+        for (name, generator) in columns {
+            let entropy = generator.entropy_bits();
+            let line = format!("field {}: {:.1} bits", name, entropy);
+            self.description.push(line);
+        }
+        self.description.push(format!("count: {}", self.count));
+        self.max_len = DEFAULT_LINE_WIDTH;
+        self.charset = None;
+        // Some fields, such as usernames and PINs, are meant to be weak.
+        self.secret = false;
+        self.columns = Some(columns);
+        self
+    }
+
+    // Returns the fields of each record, with their names.
+    fn fields(&self) -> Vec<(&'a str, &'a dyn Generator)> {
+        if let Some(columns) = self.columns {
+            return columns.iter()
+                .map(|(name, generator)| {
+                    (name.as_str(), &**generator as &dyn Generator)
+                })
+                .collect();
+        }
+        let Some(credentials) = self.credentials else {
+            return vec![(self.name, self.generator)];
+        };
+        let mut fields = vec![
+            ("username", &credentials.username as &dyn Generator),
+            (self.name, self.generator),
+        ];
+        if let Some(totp) = &credentials.totp {
+            fields.push(("totp_secret", totp));
+        }
+        fields
+    }
+
+    fn token(token: &'a Token, options: &Options) -> Self {
+        let count = options.num_passwords.unwrap_or(DEFAULT_NUM_PASSWORDS);
+        let name =
+            match token {
+                Token::Bytes(_) | Token::Id(_) => "token",
+                Token::Uuid(_) => "uuid",
+                Token::ApiKey(_) => "apikey",
+                Token::License(_) => "license",
+                Token::Mac(_) => "mac",
+                Token::Username(_) => "username",
+                Token::Otp(_) => "code",
+                Token::Registered(..) => "password",
+            };
+        let (generator, max_len, mut description): (
+            &(dyn Generator + Sync),
+            _,
+            _,
+        ) =
+            match token {
+                Token::Bytes(token) => {
+                    let description = vec![
+                        format!("encoding: {}", token.encoding()),
+                        format!("random bytes: {}", token.num_bytes()),
+                    ];
+                    (token, token.length(), description)
+                },
+                Token::Id(id) => {
+                    let mut description = vec![
+                        format!("encoding: {}", id.alphabet()),
+                        format!("random characters: {}", id.id_length()),
+                    ];
+                    if id.has_check() {
+                        description.push("check symbol: yes".to_string());
+                    }
+                    (id, id.length(), description)
+                },
+                Token::ApiKey(apikey) => {
+                    let num_chars = apikey.body_length();
+                    let mut description =
+                        vec![format!("random characters: {}", num_chars)];
+                    let prefix = apikey.prefix();
+                    if !prefix.is_empty() {
+                        description.push(format!("prefix: {}", prefix));
+                    }
+                    (apikey, apikey.length(), description)
+                },
+                Token::License(license) => {
+                    let description = vec![
+                        format!("groups: {}", license.num_groups()),
+                        format!("group length: {}", license.group_length()),
+                        format!("checksum: {}", license.checksum()),
+                    ];
+                    (license, license.length(), description)
+                },
+                Token::Uuid(uuid) => {
+                    let description =
+                        vec![format!("uuid version: {}", uuid.version())];
+                    (uuid, uuid.length(), description)
+                },
+                Token::Mac(mac) => {
+                    let mut description = Vec::new();
+                    if let Some(prefix) = mac.vendor_prefix() {
+                        let prefix =
+                            prefix.iter()
+                                .map(|byte| format!("{:02x}", byte))
+                                .collect::<Vec<_>>()
+                                .join(":");
+                        description.push(format!("vendor prefix: {}", prefix));
+                    }
+                    if mac.locally_administered() {
+                        description.push("locally administered".to_string());
+                    }
+                    (mac, mac.length(), description)
+                },
+                Token::Username(username) => {
+                    let max_len = username.max_length();
+                    let description = vec![
+                        format!("style: {}", username.style()),
+                        format!("maximum length: {}", max_len),
+                    ];
+                    (username, max_len.get(), description)
+                },
+                Token::Otp(otp) => {
+                    let mut description =
+                        vec![format!("code length: {}", otp.code_length())];
+                    if otp.has_check_digit() {
+                        description.push("check digit: luhn".to_string());
+                    }
+                    if let Some(group_len) = otp.group_length() {
+                        let line = format!("group length: {}", group_len);
+                        description.push(line);
+                    }
+                    (otp, otp.length(), description)
+                },
+                // Nothing bounds the length of what a registered generator
+                // produces, so a line stands in for it.
+                Token::Registered(name, generator) => {
+                    let description = vec![format!("generator: {}", name)];
+                    (&**generator, DEFAULT_LINE_WIDTH, description)
+                },
+            };
+        let secret =
+            !matches!(
+                token,
+                Token::Mac(_) | Token::Username(_) | Token::Otp(_),
+            );
+        let unique = matches!(token, Token::Otp(_));
+        description.push(format!("count: {}", count));
+        let entropy = generator.entropy_bits();
+        description.push(format!("entropy per token: {:.1} bits", entropy));
+        Job {
+            generator,
+            name,
+            credentials: None,
+            columns: None,
+            count,
+            max_len,
+            charset: None,
+            description,
+            secret,
+            unique,
+        }
+    }
+}
+
+fn describe(spec: &PasswordSpec) -> Vec<String> {
+    let charset = spec.charset();
+    let min_len = spec.min_length();
+    let max_len = spec.max_length();
+    let policy = spec.policy();
+    let mut lines = Vec::new();
+    lines.push(format!("charset size: {}", charset.len()));
+    if min_len == max_len {
+        lines.push(format!("length: {}", min_len));
+    } else {
+        lines.push(format!("length: {} to {}", min_len, max_len));
+    }
+    lines.push(format!("count: {}", spec.count()));
+    let mut rules = Vec::new();
+    if !policy.required.is_empty() {
+        let required =
+            policy.required.iter()
+                .map(|class| class.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+        rules.push(format!("require {}", required));
+    }
+    if !policy.forbidden_at_ends.is_empty() {
+        rules.push(format!("no {:?} at the ends", policy.forbidden_at_ends));
+    }
+    if let Some(max_len) = policy.max_length {
+        rules.push(format!("at most {} characters", max_len));
+    }
+    if rules.is_empty() {
+        lines.push("policy: none".to_string());
+    } else {
+        lines.push(format!("policy: {}", rules.join("; ")));
+    }
+    if min_len == max_len {
+        let entropy = policy.entropy_bits(charset, min_len.get());
+        lines.push(format!("entropy per password: {:.1} bits", entropy));
+    } else {
+        let min_entropy = policy.entropy_bits(charset, min_len.get());
+        let max_entropy = policy.entropy_bits(charset, max_len.get());
+        lines.push(format!(
+            "entropy per password: {:.1} to {:.1} bits",
+            min_entropy,
+            max_entropy,
+        ));
+    }
+    lines
+}
+
+fn parse_args(args: &[String]) -> Result<Options> {
+    let mut options = Options {
+        charset_spec: None,
+        mode: None,
+        preset: None,
+        exclude_chars: None,
+        confusable_chars: None,
+        composition: None,
+        fields: None,
+        min_len: None,
+        max_len: None,
+        num_passwords: None,
+        verbose: false,
+        dry_run: false,
+        min_entropy: None,
+        batch: false,
+        quiet: false,
+        prompt: false,
+        layout: None,
+        accessible: None,
+        stats: false,
+        audit_log: None,
+        label: None,
+        max_memory: None,
+        threads: None,
+        max_attempts: None,
+        timeout: None,
+        force: false,
+        no_harden: false,
+        sandbox: None,
+        encrypt_to: None,
+        split: None,
+        rng: RngKind::Os,
+        rng_fallback: RngFailurePolicy::Fail,
+        extra_entropy: false,
+        extra_input: None,
+        entropy_tokens: Vec::new(),
+        health_min_entropy: health::DEFAULT_MIN_ENTROPY,
+        health_alpha_log2: health::DEFAULT_ALPHA_LOG2,
+        health_window: health::DEFAULT_WINDOW,
+        token: None,
+        transforms: Vec::new(),
+        post_script: None,
+        format: Format::Text,
+        ansible_vault: false,
+        vault_password_file: None,
+        users_file: None,
+        users: None,
+        hash: false,
+        speakable: false,
+        credentials: None,
+        check_hibp: false,
+        check_offline: None,
+        pass_entry: None,
+        store: None,
+    };
+    let mut store = None;
+    // The database given with --store kdbx.
+    let mut kdbx_path = None;
+    let mut confusables = Vec::new();
+    let mut fields = None;
+    let mut generators_file = None;
+    let mut confusables_file = None;
+    let mut service = None;
+    let mut account = None;
+    let mut entry = None;
+    let mut uri = None;
+    // The secret and key given for --store vault.
+    let mut kv_path = None;
+    let mut kv_key = None;
+    let mut positionals = Vec::new();
+    let args = usage::parse_options(args).ok_or_else(|| anyhow!(usage()))?;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (flag, value) =
+            match arg {
+                Arg::Flag(flag, value) => (flag, value),
+                Arg::Other(arg) if arg.starts_with('-') => {
+                    return Err(anyhow!(usage()));
+                },
+                Arg::Other(arg) => {
+                    positionals.push(arg);
+                    continue;
+                },
+            };
+        match flag {
+            "-c" => {
+                options.charset_spec = Some(value.to_string());
+            },
+            "-l" => {
+                let password_len = parse_len(value)?;
+                options.min_len = Some(password_len);
+                options.max_len = Some(password_len);
+            },
+            "-n" => {
+                options.num_passwords = Some(parse_count(value)?);
+            },
+            "-a" => {
+                match value {
+                    "1" => (),
+                    "0" => {
+                        let msg =
+                            tr("pronounceable generation is not supported");
+                        return Err(anyhow!(msg));
+                    },
+                    _ => {
+                        return Err(anyhow!(usage()));
+                    },
+                }
+            },
+            "-m" => {
+                options.min_len = Some(parse_len(value)?);
+            },
+            "-x" => {
+                options.max_len = Some(parse_len(value)?);
+            },
+            "-M" => {
+                options.mode = Some(value.to_string());
+            },
+            "--preset" => {
+                let preset =
+                    match value {
+                        "dns" => Preset::Dns,
+                        "url" => Preset::Url,
+                        _ => return Err(anyhow!(usage())),
+                    };
+                options.preset = Some(preset);
+            },
+            "--transform" => {
+                for name in value.split(',') {
+                    let transform =
+                        name.parse::<Transform>().map_err(|_| {
+                            anyhow!(trf("unknown transform: \"{}\"", &[&name]))
+                        })?;
+                    options.transforms.push(transform);
+                }
+            },
+            "--post-script" => {
+                options.post_script = Some(Script::load(Path::new(value))?);
+            },
+            "--format" => {
+                let format = value;
+                options.ansible_vault = format == "ansible-vault";
+                if format == "spelling" {
+                    options.accessible = Some(Accessible::Spelling);
+                }
+                options.format =
+                    match format {
+                        "text" => Format::Text,
+                        "csv" => Format::Csv,
+                        "json" => Format::Json,
+                        "chpasswd" => Format::Chpasswd,
+                        "ansible-vault" => Format::Text,
+                        "spelling" => Format::Text,
+                        _ => {
+                            return Err(anyhow!(usage()));
+                        },
+                    };
+            },
+            "-E" => {
+                options.exclude_chars = Some(value.to_string());
+            },
+            "--fields" => {
+                fields = Some(value);
+            },
+            "--generators" => {
+                generators_file = Some(PathBuf::from(value));
+            },
+            "--confusables" => {
+                let names = value.split(',').map(str::to_string);
+                confusables.extend(names);
+            },
+            "--confusables-file" => {
+                confusables_file = Some(PathBuf::from(value));
+            },
+            "-v" | "--verbose" => {
+                options.verbose = true;
+            },
+            "--min-entropy" => {
+                options.min_entropy = Some(parse_number(value)?);
+            },
+            "-q" | "--quiet" => {
+                options.quiet = true;
+            },
+            "-C" => {
+                options.layout = Some(Layout::Columns);
+            },
+            "-1" => {
+                options.layout = Some(Layout::Lines);
+            },
+            "--prompt" => {
+                options.prompt = true;
+            },
+            "--batch" => {
+                options.batch = true;
+            },
+            "--stats" => {
+                options.stats = true;
+            },
+            "--audit-log" => {
+                options.audit_log = Some(PathBuf::from(value));
+            },
+            "--label" => {
+                options.label = Some(Label::parse(value)?);
+            },
+            "--max-memory" => {
+                options.max_memory = Some(parse_size(value)?);
+            },
+            "--compose" => {
+                options.composition = Some(value.to_string());
+            },
+            "--max-attempts" => {
+                let max_attempts = parse_number(value)?;
+                let max_attempts =
+                    NonZeroU64::new(max_attempts).ok_or_else(|| {
+                        anyhow!(tr("number of attempts must not be zero"))
+                    })?;
+                options.max_attempts = Some(max_attempts);
+            },
+            "--timeout" => {
+                let secs = parse_number::<f64>(value)?;
+                let timeout =
+                    Duration::try_from_secs_f64(secs)
+                        .ok()
+                        .filter(|timeout| !timeout.is_zero())
+                        .ok_or_else(|| {
+                            anyhow!(tr("timeout must be a positive number"))
+                        })?;
+                options.timeout = Some(timeout);
+            },
+            "-f" | "--force" => {
+                options.force = true;
+            },
+            "--no-harden" => {
+                options.no_harden = true;
+            },
+            "--sandbox" => {
+                options.sandbox = Some(true);
+            },
+            "--no-sandbox" => {
+                options.sandbox = Some(false);
+            },
+            "--encrypt-to" => {
+                options.encrypt_to = Some(value.to_string());
+            },
+            "--rng" => {
+                options.rng =
+                    match value {
+                        "os" => RngKind::Os,
+                        "chacha" => RngKind::ChaCha,
+                        _ => {
+                            return Err(anyhow!(usage()));
+                        },
+                    };
+            },
+            "--rng-fallback" => {
+                options.rng_fallback =
+                    match value {
+                        "fail" => RngFailurePolicy::Fail,
+                        "chacha" => RngFailurePolicy::ChaCha,
+                        _ => {
+                            return Err(anyhow!(usage()));
+                        },
+                    };
+            },
+            "--entropy-source" => {
+                let kind =
+                    match value {
+                        "yubikey" => TokenKind::YubiKey,
+                        "hwrng" => TokenKind::HwRng,
+                        _ => {
+                            return Err(anyhow!(usage()));
+                        },
+                    };
+                options.entropy_tokens.push(kind);
+            },
+            "--health-min-entropy" => {
+                let bits = parse_number::<f64>(value)?;
+                if !(bits > 0.0 && bits <= 8.0) {
+                    let msg = tr("health test min-entropy must be in (0, 8]");
+                    return Err(anyhow!(msg));
+                }
+                options.health_min_entropy = bits;
+            },
+            "--health-alpha" => {
+                let alpha_log2 = parse_number::<f64>(value)?;
+                if !(-64.0..0.0).contains(&alpha_log2) {
+                    let msg = tr("health test alpha must be in [-64, 0)");
+                    return Err(anyhow!(msg));
+                }
+                options.health_alpha_log2 = alpha_log2;
+            },
+            "--health-window" => {
+                let window = parse_number::<usize>(value)?;
+                if window < 2 {
+                    let msg = tr("health test window must be at least 2");
+                    return Err(anyhow!(msg));
+                }
+                options.health_window = window;
+            },
+            "--mix-rdseed" => {
+                options.entropy_tokens.push(TokenKind::RdSeed);
+            },
+            "--extra-entropy" => {
+                options.extra_entropy = true;
+            },
+            "--split" => {
+                options.split = Some(parse_split(value)?);
+            },
+            "--dry-run" => {
+                options.dry_run = true;
+            },
+            "--check-hibp" => {
+                if !cfg!(feature = "hibp") {
+                    let msg = tr("this build does not support --check-hibp");
+                    return Err(anyhow!(msg));
+                }
+                options.check_hibp = true;
+            },
+            "--vault-password-file" => {
+                let path = value.into();
+                options.vault_password_file = Some(path);
+            },
+            "--users" => {
+                options.users_file = Some(value.into());
+            },
+            "--hash" => {
+                options.hash = true;
+            },
+            "--speakable" => {
+                options.speakable = true;
+            },
+            "--large-print" => {
+                options.accessible = Some(Accessible::LargePrint);
+            },
+            "--braille" => {
+                options.accessible = Some(Accessible::Braille);
+            },
+            "--check-offline" => {
+                options.check_offline = Some(value.into());
+            },
+            "--store" => {
+                let kind = value;
+                match kind {
+                    "keyring" if !cfg!(feature = "keyring") => {
+                        let msg =
+                            tr("this build does not support --store keyring");
+                        return Err(anyhow!(msg));
+                    },
+                    "keyring" => {},
+                    "kdbx" => {
+                        let Some(Arg::Other(path)) = args.next() else {
+                            return Err(anyhow!(usage()));
+                        };
+                        kdbx_path = Some(PathBuf::from(path));
+                    },
+                    "bitwarden" => {},
+                    "vault" if !cfg!(feature = "vault") => {
+                        let msg =
+                            tr("this build does not support --store vault");
+                        return Err(anyhow!(msg));
+                    },
+                    "vault" => {},
+                    "systemd-creds" => {},
+                    _ => {
+                        let msg = trf("unknown store: \"{}\"", &[&kind]);
+                        return Err(anyhow!(msg));
+                    },
+                }
+                store = Some(kind);
+            },
+            "--service" => {
+                service = Some(value.to_string());
+            },
+            "--account" => {
+                account = Some(value.to_string());
+            },
+            "--entry" => {
+                entry = Some(value.to_string());
+            },
+            "--uri" => {
+                uri = Some(value.to_string());
+            },
+            "--path" => {
+                kv_path = Some(value.to_string());
+            },
+            "--key" => {
+                kv_key = Some(value.to_string());
+            },
+            _ => return Err(anyhow!(usage())),
+        }
+    }
+    let mut positionals = positionals.into_iter();
+    if let Some(password_len_str) = positionals.next() {
+        if password_len_str.parse::<usize>().is_err() {
+            let msg = trf("unknown command: \"{}\"", &[password_len_str]);
+            return Err(anyhow!(msg));
+        }
+        if options.min_len.is_some() || options.max_len.is_some() {
+            let msg =
+                tr("password length given both as an option and an argument");
+            return Err(anyhow!(msg));
+        }
+        let password_len = parse_len(password_len_str)?;
+        options.min_len = Some(password_len);
+        options.max_len = Some(password_len);
+    }
+    if let Some(num_passwords_str) = positionals.next() {
+        if options.num_passwords.is_some() {
+            let msg = tr(
+                "number of passwords given both as an option and an argument",
+            );
+            return Err(anyhow!(msg));
+        }
+        options.num_passwords = Some(parse_count(num_passwords_str)?);
+    }
+    if positionals.next().is_some() {
+        return Err(anyhow!(usage()));
+    }
+    if let Some(fields) = fields {
+        options.fields = Some(parse_fields(fields, generators_file)?);
+    }
+    // Read now, before the sandbox shuts off the file system.
+    if !confusables.is_empty() {
+        let path = confusables_file.or_else(confusables::default_path);
+        let excluded = confusables::excluded(&confusables, path.as_deref())?;
+        options.confusable_chars = Some(excluded);
+    }
+    if options.users_file.is_some() && options.num_passwords.is_some() {
+        let msg = tr(
+            "--users gives the number of passwords, which cannot also be \
+             given",
+        );
+        return Err(anyhow!(msg));
+    }
+    if options.hash && options.users_file.is_none() {
+        return Err(anyhow!(tr("--hash needs --users")));
+    }
+    let labelled = matches!(options.format, Format::Csv | Format::Json);
+    if options.label.is_some() && options.audit_log.is_none() && !labelled {
+        let msg = tr("--label needs --audit-log, or --format csv or json");
+        return Err(anyhow!(msg));
+    }
+    if options.ansible_vault != options.vault_password_file.is_some() {
+        let msg = tr(
+            "--format ansible-vault and --vault-password-file need each \
+             other",
+        );
+        return Err(anyhow!(msg));
+    }
+    if options.format == Format::Chpasswd && options.users_file.is_none() {
+        return Err(anyhow!(tr("--format chpasswd needs --users")));
+    }
+    if options.check_hibp && options.check_offline.is_some() {
+        let msg =
+            tr("--check-hibp and --check-offline cannot be used together");
+        return Err(anyhow!(msg));
+    }
+    // Which of the options naming where to save passwords were given, for
+    // each kind of store.
+    let names_keyring = service.is_some() || account.is_some();
+    let names_entry = entry.is_some() || uri.is_some();
+    let names_kv = kv_path.is_some() || kv_key.is_some();
+    options.store =
+        match store {
+            None if !names_keyring && !names_entry && !names_kv => None,
+            None => {
+                let msg = tr(
+                    "--service, --account, --entry, --uri, --path and --key \
+                     need --store",
+                );
+                return Err(anyhow!(msg));
+            },
+            Some("keyring") => {
+                match (service, account) {
+                    (Some(service), Some(account))
+                        if !names_entry && !names_kv =>
+                    {
+                        let entry = KeyringEntry::new(service, account);
+                        Some(Store::Keyring(entry))
+                    },
+                    _ => {
+                        let msg = tr(
+                            "--store keyring needs --service and --account \
+                             and does not take --entry, --uri, --path or \
+                             --key",
+                        );
+                        return Err(anyhow!(msg));
+                    },
+                }
+            },
+            Some("vault") => {
+                match (kv_path, kv_key) {
+                    (Some(path), Some(key)) if !names_keyring && !names_entry =>
+                        Some(Store::Vault(VaultEntry { path, key })),
+                    _ => {
+                        let msg = tr(
+                            "--store vault needs --path and --key and does \
+                             not take --service, --account, --entry or --uri",
+                        );
+                        return Err(anyhow!(msg));
+                    },
+                }
+            },
+            Some("systemd-creds") => {
+                let misplaced = names_keyring || names_kv || uri.is_some();
+                let name =
+                    match entry {
+                        Some(name) if !misplaced => name,
+                        _ => {
+                            let msg = tr(
+                                "--store systemd-creds needs --entry and does \
+                                 not take --service, --account, --uri, --path \
+                                 or --key",
+                            );
+                            return Err(anyhow!(msg));
+                        },
+                    };
+                let invalid =
+                    name.is_empty()
+                        || name == "."
+                        || name == ".."
+                        || name.contains('/');
+                if invalid {
+                    let msg = trf("invalid credential name: \"{}\"", &[&name]);
+                    return Err(anyhow!(msg));
+                }
+                Some(Store::SystemdCreds(name))
+            },
+            Some(kind) => {
+                let entry =
+                    match entry {
+                        Some(entry) if !names_keyring && !names_kv => entry,
+                        _ => {
+                            let msg = trf(
+                                "--store {} needs --entry and does not take \
+                                 --service, --account, --path or --key",
+                                &[&kind],
+                            );
+                            return Err(anyhow!(msg));
+                        },
+                    };
+                let (title, username) =
+                    match entry.split_once('/') {
+                        Some((title, username)) =>
+                            (title.to_string(), Some(username.to_string())),
+                        None => (entry, None),
+                    };
+                let entry = StoreEntry { title, username, uri };
+                match kdbx_path {
+                    Some(path) => Some(Store::Kdbx(path, entry)),
+                    None => Some(Store::Bitwarden(entry)),
+                }
+            },
+        };
+    Ok(options)
+}
+
+fn parse_batch_line(line: &str) -> Result<Vec<String>> {
+    if line.trim_start().starts_with('{') {
+        let spec = serde_json::from_str::<BatchSpec>(line)?;
+        return Ok(batch_spec_args(spec));
+    }
+    let mut args = Vec::new();
+    let mut chars = line.chars();
+    let mut arg = None::<String>;
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\'' | '"' => {
+                let arg = arg.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some(next) if next == ch => break,
+                        Some(next) => arg.push(next),
+                        None => return Err(anyhow!(tr("unterminated quote"))),
+                    }
+                }
+            },
+            ch if ch.is_whitespace() => {
+                args.extend(arg.take());
+            },
+            ch => {
+                arg.get_or_insert_with(String::new).push(ch);
+            },
+        }
+    }
+    args.extend(arg);
+    Ok(args)
+}
+
+// Returns the options equivalent to a JSON specification.
+fn batch_spec_args(spec: BatchSpec) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut push = |flag: &str, value: Option<String>| {
+        if let Some(value) = value {
+            args.push(flag.to_string());
+            args.push(value);
+        }
+    };
+    push("-c", spec.charset);
+    push("-M", spec.mode);
+    push("-E", spec.exclude);
+    push("-l", spec.length.map(|len| len.to_string()));
+    push("-m", spec.min_length.map(|len| len.to_string()));
+    push("-x", spec.max_length.map(|len| len.to_string()));
+    push("-n", spec.count.map(|count| count.to_string()));
+    push("--min-entropy", spec.min_entropy.map(|bits| bits.to_string()));
+    args
+}
+
+fn parse_number<T: FromStr>(number_str: &str) -> Result<T> {
+    number_str.parse::<T>()
+        .map_err(|_| anyhow!(trf("invalid number: \"{}\"", &[&number_str])))
+}
+
+fn parse_len(password_len_str: &str) -> Result<NonZeroUsize> {
+    let password_len = parse_number(password_len_str)?;
+    NonZeroUsize::new(password_len)
+        .ok_or_else(|| anyhow!(tr("password length must not be zero")))
+}
+
+fn parse_count(num_passwords_str: &str) -> Result<NonZeroUsize> {
+    let num_passwords = parse_number(num_passwords_str)?;
+    NonZeroUsize::new(num_passwords)
+        .ok_or_else(|| anyhow!(tr("number of passwords must not be zero")))
+}
+
+// Parses a number of bytes, or of kibibytes, mebibytes, gibibytes or
+// tebibytes with a suffix of K, M, G or T.
+fn parse_size(size_str: &str) -> Result<u64> {
+    let err = || anyhow!(trf("invalid size: \"{}\"", &[&size_str]));
+    let (number, shift) =
+        match size_str.char_indices().last() {
+            Some((idx, 'K')) => (&size_str[..idx], 10),
+            Some((idx, 'M')) => (&size_str[..idx], 20),
+            Some((idx, 'G')) => (&size_str[..idx], 30),
+            Some((idx, 'T')) => (&size_str[..idx], 40),
+            _ => (size_str, 0),
+        };
+    let number = number.parse::<u64>().map_err(|_| err())?;
+    number.checked_mul(1 << shift).ok_or_else(err)
+}
+
+fn parse_split(split_str: &str) -> Result<(u8, u8)> {
+    let err = || anyhow!(trf("invalid split: \"{}\"", &[&split_str]));
+    let (threshold, num_shares) = split_str.split_once("of").ok_or_else(err)?;
+    let threshold = threshold.parse::<u8>().map_err(|_| err())?;
+    let num_shares = num_shares.parse::<u8>().map_err(|_| err())?;
+    if threshold == 0 || threshold > num_shares {
+        return Err(err());
+    }
+    Ok((threshold, num_shares))
+}
+
+fn parse_mode(mode: &str) -> Result<(Charset, Policy)> {
+    let mut charset = Vec::new();
+    let mut policy = Policy::default();
+    for ch in mode.chars() {
+        let class =
+            match ch.to_ascii_uppercase() {
+                'S' => CharClass::Symbols,
+                'N' => CharClass::Digits,
+                'C' => CharClass::Uppercase,
+                'L' => CharClass::Lowercase,
+                _ => {
+                    let msg = trf("invalid mode character: \"{}\"", &[&ch]);
+                    return Err(anyhow!(msg));
+                },
+            };
+        for byte in class.chars() {
+            if !charset.contains(&byte) {
+                charset.push(byte);
+            }
+        }
+        if ch.is_ascii_uppercase() && !policy.required.contains(&class) {
+            policy.required.push(class);
+        }
+    }
+    if charset.is_empty() {
+        return Err(anyhow!(tr("character set is empty")));
+    }
+    Ok((Charset::new(charset)?, policy))
+}
+
+fn usage() -> String {
+    let program_name = env::args().next().unwrap_or("".to_string());
+    usage::render(&program_name)
+}
+
+// Reads the arguments of a command by the options its synopses show, so
+// that it accepts no option help leaves out.
+fn read_args<'a>(
+    synopses: &[&[Word]],
+    args: &'a [String],
+) -> Result<Vec<Arg<'a>>> {
+    usage::parse(synopses, args).ok_or_else(|| anyhow!(usage()))
 }
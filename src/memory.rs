@@ -0,0 +1,81 @@
+use std::ops::{Deref, DerefMut};
+use tracing::debug;
+use zeroize::Zeroize;
+
+// Heap buffers for secrets, locked into memory so that they are not swapped
+// to disk, and zeroed before they are freed. Locking is best effort: when it
+// fails, usually because RLIMIT_MEMLOCK is low, the buffer is used unlocked.
+
+pub(crate) struct SecretBuf {
+    bytes: Box<[u8]>,
+    locked: bool,
+}
+
+impl SecretBuf {
+    // The buffer never grows, so the locked pages stay the ones in use.
+    pub(crate) fn zeroed(len: usize) -> Self {
+        let bytes = vec![0u8; len].into_boxed_slice();
+        let locked = !bytes.is_empty() && lock(&bytes);
+        if !bytes.is_empty() && !locked {
+            debug!(len, "could not lock secret buffer, using it unlocked");
+        }
+        SecretBuf { bytes, locked }
+    }
+}
+
+impl Deref for SecretBuf {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl DerefMut for SecretBuf {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.bytes
+    }
+}
+
+impl Drop for SecretBuf {
+    fn drop(&mut self) {
+        self.bytes.zeroize();
+        if self.locked {
+            unlock(&self.bytes);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn lock(bytes: &[u8]) -> bool {
+    unsafe { libc::mlock(bytes.as_ptr().cast(), bytes.len()) == 0 }
+}
+
+#[cfg(unix)]
+fn unlock(bytes: &[u8]) {
+    unsafe {
+        libc::munlock(bytes.as_ptr().cast(), bytes.len());
+    }
+}
+
+#[cfg(windows)]
+fn lock(bytes: &[u8]) -> bool {
+    use windows_sys::Win32::System::Memory::VirtualLock;
+    unsafe { VirtualLock(bytes.as_ptr().cast(), bytes.len()) != 0 }
+}
+
+#[cfg(windows)]
+fn unlock(bytes: &[u8]) {
+    use windows_sys::Win32::System::Memory::VirtualUnlock;
+    unsafe {
+        VirtualUnlock(bytes.as_ptr().cast(), bytes.len());
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn lock(_: &[u8]) -> bool {
+    false
+}
+
+#[cfg(not(any(unix, windows)))]
+fn unlock(_: &[u8]) {}
@@ -0,0 +1,67 @@
+use rand::{CryptoRng, RngCore};
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::{Shake256, Shake256Reader};
+use zeroize::Zeroize;
+
+const DOMAIN: &[u8] = b"passgen extra entropy v1";
+
+/// A random number generator with extra entropy supplied by the user mixed
+/// into its output.
+///
+/// The extra input is hashed with SHAKE256, prefixed with a domain
+/// separation label, and the resulting stream is XORed into the wrapped
+/// generator's output byte for byte. The stream depends only on the input,
+/// which is fixed before the generator is read, so mixing is a bijection of
+/// the generator's output: it has exactly as much entropy as before, and
+/// stays unpredictable to an attacker who knows the generator's output but
+/// not the input.
+pub struct EntropyMixer<R> {
+    rng: R,
+    stream: Shake256Reader,
+}
+
+impl<R: RngCore> EntropyMixer<R> {
+    /// Mixes a hash of `extra` into the output of `rng`.
+    pub fn new(rng: R, extra: &[u8]) -> Self {
+        let mut hasher = Shake256::default();
+        hasher.update(DOMAIN);
+        hasher.update(&(extra.len() as u64).to_le_bytes());
+        hasher.update(extra);
+        EntropyMixer { rng, stream: hasher.finalize_xof() }
+    }
+
+    fn mix(&mut self, dest: &mut [u8]) {
+        let mut pad = [0u8; 64];
+        for chunk in dest.chunks_mut(pad.len()) {
+            let pad = &mut pad[..chunk.len()];
+            self.stream.read(pad);
+            for (byte, pad_byte) in chunk.iter_mut().zip(pad.iter()) {
+                *byte ^= pad_byte;
+            }
+        }
+        pad.zeroize();
+    }
+}
+
+impl<R: RngCore> RngCore for EntropyMixer<R> {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest);
+        self.mix(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)?;
+        self.mix(dest);
+        Ok(())
+    }
+}
+
+impl<R: CryptoRng> CryptoRng for EntropyMixer<R> {}
@@ -0,0 +1,42 @@
+use anyhow::{anyhow, Result};
+use std::io::{self, Read, Write};
+use zeroize::Zeroizing;
+
+use crate::i18n::trf;
+
+// Chrome and Firefox talk to native messaging hosts over standard input and
+// output, in messages of UTF-8 JSON each preceded by its length as a 32-bit
+// integer in native byte order. Chrome accepts messages of at most 1 MiB
+// from the host, and nothing a host is asked for needs longer requests.
+
+/// The longest message either way, in bytes.
+pub const MAX_MESSAGE_LEN: usize = 1024 * 1024;
+
+/// Reads the next message, or nothing at the end of input.
+pub fn read_message(
+    input: &mut impl Read,
+) -> Result<Option<Zeroizing<Vec<u8>>>> {
+    let mut len = [0; 4];
+    match input.read_exact(&mut len) {
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+            return Ok(None);
+        },
+        result => result?,
+    }
+    let len = u32::from_ne_bytes(len) as usize;
+    if len > MAX_MESSAGE_LEN {
+        let msg = trf("message of {} bytes is too long", &[&len]);
+        return Err(anyhow!(msg));
+    }
+    let mut message = Zeroizing::new(vec![0; len]);
+    input.read_exact(&mut message)?;
+    Ok(Some(message))
+}
+
+/// Writes a message and flushes the output.
+pub fn write_message(output: &mut impl Write, message: &[u8]) -> Result<()> {
+    output.write_all(&(message.len() as u32).to_ne_bytes())?;
+    output.write_all(message)?;
+    output.flush()?;
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use rand::{CryptoRng, RngCore};
+
+/// Callbacks for events during generation, for collecting metrics or
+/// throttling without changing the generators. Every method does nothing by
+/// default.
+///
+/// Passwords themselves are never passed to an observer.
+pub trait GenerationObserver {
+    /// Called after this many bytes are drawn from the random number
+    /// generator.
+    fn entropy_drawn(&self, num_bytes: usize) {
+        let _ = num_bytes;
+    }
+
+    /// Called when a candidate password fails the policy and is resampled.
+    /// Attempts are numbered from one.
+    fn candidate_rejected(&self, attempt: usize) {
+        let _ = attempt;
+    }
+
+    /// Called when a password of this length is emitted.
+    fn password_emitted(&self, password_len: usize) {
+        let _ = password_len;
+    }
+}
+
+/// The observer that ignores every event.
+impl GenerationObserver for () {}
+
+// Reports every draw from the wrapped random number generator.
+pub(crate) struct ObservedRng<'a, R: ?Sized> {
+    pub(crate) rng: &'a mut R,
+    pub(crate) observer: &'a dyn GenerationObserver,
+}
+
+impl<R: RngCore + ?Sized> RngCore for ObservedRng<'_, R> {
+    fn next_u32(&mut self) -> u32 {
+        self.observer.entropy_drawn(4);
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.observer.entropy_drawn(8);
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.observer.entropy_drawn(dest.len());
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)?;
+        self.observer.entropy_drawn(dest.len());
+        Ok(())
+    }
+}
+
+impl<R: CryptoRng + ?Sized> CryptoRng for ObservedRng<'_, R> {}
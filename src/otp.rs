@@ -0,0 +1,179 @@
+use rand_core::CryptoRngCore;
+use std::num::NonZeroUsize;
+
+use crate::generator::{entropy_bits, Generator};
+use crate::license::{luhn_check_value, push_grouped};
+use crate::memory::SecretBuf;
+use crate::sampler::sample;
+use crate::{invalid_spec, Result};
+
+const DIGITS: &[u8; 10] = b"0123456789";
+
+/// Generates numeric one-time codes, such as the backup codes printed on a
+/// scratch card, optionally ending in a Luhn check digit.
+#[derive(Clone, Debug)]
+pub struct OtpCodeGenerator {
+    code_len: NonZeroUsize,
+    check_digit: bool,
+    group_len: Option<NonZeroUsize>,
+}
+
+impl OtpCodeGenerator {
+    /// The default number of digits in each code.
+    pub const DEFAULT_CODE_LEN: NonZeroUsize = NonZeroUsize::new(8).unwrap();
+
+    /// Creates a generator for codes of this many digits, the last of which
+    /// is a Luhn check digit if asked. Fails if the check digit would leave
+    /// no random digits.
+    pub fn new(code_len: NonZeroUsize, check_digit: bool) -> Result<Self> {
+        if check_digit && code_len.get() == 1 {
+            return Err(invalid_spec("code has no room for random digits"));
+        }
+        Ok(OtpCodeGenerator { code_len, check_digit, group_len: None })
+    }
+
+    /// Splits each code into groups of this many digits, separated by
+    /// hyphens.
+    pub fn grouped(mut self, group_len: NonZeroUsize) -> Self {
+        self.group_len = Some(group_len);
+        self
+    }
+
+    /// Returns the number of digits in each code.
+    pub fn code_length(&self) -> NonZeroUsize {
+        self.code_len
+    }
+
+    /// Returns whether each code ends in a Luhn check digit.
+    pub fn has_check_digit(&self) -> bool {
+        self.check_digit
+    }
+
+    /// Returns the number of digits in each group, if codes are grouped.
+    pub fn group_length(&self) -> Option<NonZeroUsize> {
+        self.group_len
+    }
+
+    /// Returns the length of each code in characters, including separators.
+    pub fn length(&self) -> usize {
+        let code_len = self.code_len.get();
+        let num_separators =
+            match self.group_len {
+                Some(group_len) => code_len.div_ceil(group_len.get()) - 1,
+                None => 0,
+            };
+        code_len + num_separators
+    }
+
+    fn num_random(&self) -> usize {
+        self.code_len.get() - usize::from(self.check_digit)
+    }
+}
+
+impl Generator for OtpCodeGenerator {
+    fn generate(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+    ) -> Result<()> {
+        let num_random = self.num_random();
+        let mut digits = SecretBuf::zeroed(self.code_len.get());
+        sample(rng, DIGITS, &mut digits[..num_random])?;
+        if self.check_digit {
+            let values =
+                digits[..num_random].iter().map(|&byte| (byte - b'0') as usize);
+            digits[num_random] = DIGITS[luhn_check_value(values, DIGITS.len())];
+        }
+        let group_len = self.group_len.unwrap_or(self.code_len);
+        push_grouped(out, &digits, group_len.get());
+        Ok(())
+    }
+
+    fn entropy_bits(&self) -> f64 {
+        entropy_bits(DIGITS, self.num_random())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::Passwords;
+    use rand_chacha::ChaCha20Rng;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+
+    // The Luhn check of a whole number, check digit included.
+    fn passes_luhn(code: &str) -> bool {
+        let sum =
+            code.bytes()
+                .rev()
+                .map(|byte| (byte - b'0') as usize)
+                .enumerate()
+                .map(|(pos, digit)| {
+                    if pos % 2 == 1 {
+                        let doubled = digit * 2;
+                        doubled / 10 + doubled % 10
+                    } else {
+                        digit
+                    }
+                })
+                .sum::<usize>();
+        sum % 10 == 0
+    }
+
+    fn codes(generator: &OtpCodeGenerator, count: usize) -> Vec<String> {
+        let rng = ChaCha20Rng::seed_from_u64(0);
+        Passwords::new(generator, count, rng)
+            .collect::<Result<Vec<_>>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn check_digit_passes_luhn() {
+        assert!(passes_luhn("79927398713"));
+        assert!(!passes_luhn("79927398710"));
+        let code_len = NonZeroUsize::new(10).unwrap();
+        let generator = OtpCodeGenerator::new(code_len, true).unwrap();
+        assert_eq!(generator.entropy_bits(), entropy_bits(DIGITS, 9));
+        for code in codes(&generator, 1000) {
+            assert_eq!(code.len(), 10);
+            assert!(passes_luhn(&code), "{}", code);
+        }
+        let one = NonZeroUsize::new(1).unwrap();
+        assert!(OtpCodeGenerator::new(one, true).is_err());
+    }
+
+    #[test]
+    fn codes_are_grouped() {
+        let code_len = NonZeroUsize::new(8).unwrap();
+        let group_len = NonZeroUsize::new(3).unwrap();
+        let generator =
+            OtpCodeGenerator::new(code_len, true).unwrap().grouped(group_len);
+        assert_eq!(generator.length(), 10);
+        for code in codes(&generator, 100) {
+            let groups = code.split('-').collect::<Vec<_>>();
+            let lens = groups.iter().map(|group| group.len());
+            assert_eq!(lens.sum::<usize>(), 8);
+            assert_eq!(groups[0].len(), 3);
+            assert_eq!(groups[2].len(), 2);
+            assert!(passes_luhn(&groups.concat()), "{}", code);
+        }
+    }
+
+    // Every two-digit code, once each, and no more.
+    #[test]
+    fn unique_codes_are_distinct() {
+        let code_len = NonZeroUsize::new(2).unwrap();
+        let generator = OtpCodeGenerator::new(code_len, false).unwrap();
+        let rng = ChaCha20Rng::seed_from_u64(0);
+        let codes =
+            Passwords::new(&generator, 100, rng)
+                .unique()
+                .unwrap()
+                .collect::<Result<HashSet<_>>>()
+                .unwrap();
+        assert_eq!(codes.len(), 100);
+        let rng = ChaCha20Rng::seed_from_u64(0);
+        assert!(Passwords::new(&generator, 101, rng).unique().is_err());
+    }
+}
@@ -0,0 +1,566 @@
+use std::io::{self, Write};
+use std::ops::Range;
+
+use crate::CharClass;
+
+const COLUMN_GAP: usize = 2;
+// The spaces between characters in large print.
+const LARGE_PRINT_GAP: usize = 3;
+// The most characters in a group for a Braille display.
+const BRAILLE_GROUP_LEN: usize = 4;
+
+/// The cells of a common Braille display.
+pub const BRAILLE_LINE_WIDTH: usize = 40;
+
+/// Writes passwords in columns, filling each line before the next.
+pub struct Columns<W> {
+    out: W,
+    column_width: usize,
+    num_columns: usize,
+    column: usize,
+    padding: usize,
+}
+
+impl<W: Write> Columns<W> {
+    /// Lays out as many columns of this password width as fit the line.
+    pub fn new(out: W, line_width: usize, password_width: usize) -> Self {
+        let column_width = password_width + COLUMN_GAP;
+        let num_columns = ((line_width + COLUMN_GAP) / column_width).max(1);
+        Columns { out, column_width, num_columns, column: 0, padding: 0 }
+    }
+
+    /// Writes one password per line.
+    pub fn single(out: W) -> Self {
+        Columns { out, column_width: 0, num_columns: 1, column: 0, padding: 0 }
+    }
+
+    /// Writes the next password.
+    pub fn write(&mut self, password: &str) -> io::Result<()> {
+        if self.column > 0 {
+            write!(self.out, "{:1$}", "", self.padding)?;
+        }
+        write!(self.out, "{}", password)?;
+        self.column += 1;
+        if self.column == self.num_columns {
+            writeln!(self.out)?;
+            self.column = 0;
+        } else {
+            let len = password.chars().count();
+            self.padding = self.column_width.saturating_sub(len).max(1);
+        }
+        Ok(())
+    }
+
+    /// Ends the last line and flushes the writer.
+    pub fn finish(mut self) -> io::Result<()> {
+        if self.column > 0 {
+            writeln!(self.out)?;
+        }
+        self.out.flush()
+    }
+}
+
+/// Writes passwords in large print for transcribing them: each spaced out
+/// on one line, then one character a line with its position and the name
+/// the function gives its class.
+pub struct LargePrint<W, F> {
+    out: W,
+    class_name: F,
+    started: bool,
+}
+
+impl<W: Write, F: Fn(Option<CharClass>, char) -> &'static str>
+    LargePrint<W, F>
+{
+    /// Writes passwords naming the class of each character, or of one in
+    /// no class, with the function.
+    pub fn new(out: W, class_name: F) -> Self {
+        LargePrint { out, class_name, started: false }
+    }
+
+    /// Writes the next password.
+    pub fn write(&mut self, password: &str) -> io::Result<()> {
+        if self.started {
+            writeln!(self.out)?;
+        }
+        self.started = true;
+        for (idx, ch) in password.chars().enumerate() {
+            if idx > 0 {
+                write!(self.out, "{:1$}", "", LARGE_PRINT_GAP)?;
+            }
+            write!(self.out, "{}", visible(ch))?;
+        }
+        writeln!(self.out)?;
+        let len = password.chars().count();
+        let position_width = len.to_string().len();
+        for (idx, ch) in password.chars().enumerate() {
+            let class_name = (self.class_name)(char_class(ch), ch);
+            writeln!(
+                self.out,
+                "{:>3$}  {}  {}",
+                idx + 1,
+                visible(ch),
+                class_name,
+                position_width,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the writer.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Writes passwords for reading on a Braille display: in groups of at most
+/// four letters of one case or digits, or of one other character, so that
+/// each needs at most one capital or number indicator, and wrapped between
+/// groups to the display's width.
+pub struct Braille<W> {
+    out: W,
+    line_width: usize,
+    started: bool,
+}
+
+impl<W: Write> Braille<W> {
+    /// Writes passwords on lines of at most this many cells, where they
+    /// fit.
+    pub fn new(out: W, line_width: usize) -> Self {
+        Braille { out, line_width, started: false }
+    }
+
+    /// Writes the next password.
+    pub fn write(&mut self, password: &str) -> io::Result<()> {
+        if self.started {
+            writeln!(self.out)?;
+        }
+        self.started = true;
+        let mut line_len = 0;
+        for group in braille_groups(password) {
+            let text = &password[group];
+            let first = text.chars().next().and_then(char_class);
+            // A capital or number indicator takes a cell of its own.
+            let indicator =
+                matches!(first, Some(CharClass::Uppercase | CharClass::Digits));
+            let len = text.chars().count() + usize::from(indicator);
+            if line_len > 0 && line_len + 1 + len > self.line_width {
+                writeln!(self.out)?;
+                line_len = 0;
+            }
+            if line_len > 0 {
+                write!(self.out, " ")?;
+                line_len += 1;
+            }
+            for ch in text.chars() {
+                write!(self.out, "{}", visible(ch))?;
+            }
+            line_len += len;
+        }
+        writeln!(self.out)
+    }
+
+    /// Flushes the writer.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Writes passwords to spell out over radio or phone: each on a line of its
+/// own, then one character a line with its position, class, word from the
+/// ICAO spelling alphabet and Morse code, where it has them. The functions
+/// name the characters' classes and symbols, which have no spelling word.
+pub struct Spelling<W, F, G> {
+    out: W,
+    class_name: F,
+    symbol_name: G,
+    started: bool,
+}
+
+impl<W, F, G> Spelling<W, F, G>
+where
+    W: Write,
+    F: Fn(Option<CharClass>, char) -> &'static str,
+    G: Fn(char) -> Option<&'static str>,
+{
+    /// Writes passwords naming characters with the functions.
+    pub fn new(out: W, class_name: F, symbol_name: G) -> Self {
+        Spelling { out, class_name, symbol_name, started: false }
+    }
+
+    /// Writes the next password.
+    pub fn write(&mut self, password: &str) -> io::Result<()> {
+        if self.started {
+            writeln!(self.out)?;
+        }
+        self.started = true;
+        writeln!(self.out, "{}", password)?;
+        let names = |ch: char| {
+            let class_name = (self.class_name)(char_class(ch), ch);
+            let word = spelling_word(ch).or_else(|| (self.symbol_name)(ch));
+            (class_name, word.unwrap_or(""))
+        };
+        let len = password.chars().count();
+        let position_width = len.to_string().len();
+        let (class_width, word_width) =
+            password.chars().map(names).fold((0, 0), |widths, (class, word)| {
+                (
+                    widths.0.max(class.chars().count()),
+                    widths.1.max(word.chars().count()),
+                )
+            });
+        for (idx, ch) in password.chars().enumerate() {
+            let (class_name, word) = names(ch);
+            let position = idx + 1;
+            let ch = visible(ch);
+            write!(self.out, "{:>2$}  {}", position, ch, position_width)?;
+            // Columns are padded only when another follows.
+            match (word, morse_code(ch)) {
+                ("", None) => writeln!(self.out, "  {}", class_name)?,
+                (word, None) => {
+                    writeln!(
+                        self.out,
+                        "  {:2$}  {}",
+                        class_name,
+                        word,
+                        class_width,
+                    )?
+                },
+                (word, Some(code)) => {
+                    writeln!(
+                        self.out,
+                        "  {:3$}  {:4$}  {}",
+                        class_name,
+                        word,
+                        code,
+                        class_width,
+                        word_width,
+                    )?
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Flushes the writer.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/// Returns the word for a letter or digit in the ICAO spelling alphabet.
+pub fn spelling_word(ch: char) -> Option<&'static str> {
+    let ch = ch.to_ascii_lowercase();
+    if ch.is_ascii_lowercase() {
+        Some(LETTER_WORDS[usize::from(ch as u8 - b'a')])
+    } else if ch.is_ascii_digit() {
+        Some(DIGIT_WORDS[usize::from(ch as u8 - b'0')])
+    } else {
+        None
+    }
+}
+
+/// Returns the International Morse code for a character, in dots and
+/// dashes.
+pub fn morse_code(ch: char) -> Option<&'static str> {
+    let ch = ch.to_ascii_lowercase();
+    if ch.is_ascii_lowercase() {
+        return Some(LETTER_MORSE[usize::from(ch as u8 - b'a')]);
+    }
+    if ch.is_ascii_digit() {
+        return Some(DIGIT_MORSE[usize::from(ch as u8 - b'0')]);
+    }
+    PUNCTUATION_MORSE
+        .iter()
+        .find(|&&(punctuation, _)| punctuation == ch)
+        .map(|&(_, code)| code)
+}
+
+const LETTER_WORDS: [&str; 26] = [
+    "Alfa", "Bravo", "Charlie", "Delta", "Echo", "Foxtrot", "Golf", "Hotel",
+    "India", "Juliett", "Kilo", "Lima", "Mike", "November", "Oscar", "Papa",
+    "Quebec", "Romeo", "Sierra", "Tango", "Uniform", "Victor", "Whiskey",
+    "X-ray", "Yankee", "Zulu",
+];
+
+// As pronounced on the radio.
+const DIGIT_WORDS: [&str; 10] = [
+    "Zero", "One", "Two", "Tree", "Fower", "Fife", "Six", "Seven", "Eight",
+    "Niner",
+];
+
+const LETTER_MORSE: [&str; 26] = [
+    ".-", "-...", "-.-.", "-..", ".", "..-.", "--.", "....", "..", ".---",
+    "-.-", ".-..", "--", "-.", "---", ".--.", "--.-", ".-.", "...", "-",
+    "..-", "...-", ".--", "-..-", "-.--", "--..",
+];
+
+const DIGIT_MORSE: [&str; 10] = [
+    "-----", ".----", "..---", "...--", "....-", ".....", "-....", "--...",
+    "---..", "----.",
+];
+
+// ITU-R M.1677-1 and the codes in common use beside it.
+const PUNCTUATION_MORSE: &[(char, &str)] = &[
+    ('.', ".-.-.-"),
+    (',', "--..--"),
+    ('?', "..--.."),
+    ('\'', ".----."),
+    ('!', "-.-.--"),
+    ('/', "-..-."),
+    ('(', "-.--."),
+    (')', "-.--.-"),
+    ('&', ".-..."),
+    (':', "---..."),
+    (';', "-.-.-."),
+    ('=', "-...-"),
+    ('+', ".-.-."),
+    ('-', "-....-"),
+    ('_', "..--.-"),
+    ('"', ".-..-."),
+    ('$', "...-..-"),
+    ('@', ".--.-."),
+];
+
+// The ranges of the password's groups for a Braille display.
+fn braille_groups(password: &str) -> Vec<Range<usize>> {
+    let mut groups = Vec::<Range<usize>>::new();
+    let mut prev = None;
+    let mut len = 0;
+    for (pos, ch) in password.char_indices() {
+        let class = char_class(ch);
+        let grouped =
+            matches!(
+                class,
+                Some(
+                    CharClass::Lowercase
+                        | CharClass::Uppercase
+                        | CharClass::Digits
+                ),
+            );
+        let end = pos + ch.len_utf8();
+        match groups.last_mut() {
+            Some(group)
+                if grouped && class == prev && len < BRAILLE_GROUP_LEN =>
+            {
+                group.end = end;
+                len += 1;
+            },
+            _ => {
+                groups.push(pos..end);
+                len = 1;
+            },
+        }
+        prev = class;
+    }
+    groups
+}
+
+fn char_class(ch: char) -> Option<CharClass> {
+    let byte = u8::try_from(ch).ok()?;
+    [
+        CharClass::Lowercase,
+        CharClass::Uppercase,
+        CharClass::Digits,
+        CharClass::Symbols,
+    ]
+    .into_iter()
+    .find(|class| class.contains(byte))
+}
+
+// Shows a space, which would not be seen, as a symbol for one.
+fn visible(ch: char) -> char {
+    if ch == ' ' {
+        '␣'
+    } else {
+        ch
+    }
+}
+
+/// The format of records of named fields, such as a username and password.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// The fields of each record on one line, separated by tabs.
+    #[default]
+    Text,
+    /// Comma-separated values as in RFC 4180, after a header of field
+    /// names.
+    Csv,
+    /// One JSON object per line, keyed by field name.
+    Json,
+    /// The fields of each record on one line, separated by colons, as
+    /// chpasswd reads a username and password.
+    Chpasswd,
+}
+
+/// Writes records of named fields, one per line.
+pub struct Records<W> {
+    out: W,
+    format: Format,
+    names: Vec<String>,
+    started: bool,
+}
+
+impl<W: Write> Records<W> {
+    /// Writes records with fields of these names in this format.
+    pub fn new(out: W, format: Format, names: &[&str]) -> Self {
+        let names = names.iter().map(|name| name.to_string()).collect();
+        Records { out, format, names, started: false }
+    }
+
+    /// Writes the next record, whose values are in the order of the names.
+    pub fn write(&mut self, values: &[&str]) -> io::Result<()> {
+        if !self.started && self.format == Format::Csv {
+            let names = self.names.iter().map(String::as_str);
+            write_csv_line(&mut self.out, names)?;
+        }
+        self.started = true;
+        match self.format {
+            Format::Text | Format::Chpasswd => {
+                let separator =
+                    if self.format == Format::Text { "\t" } else { ":" };
+                for (idx, value) in values.iter().enumerate() {
+                    if idx > 0 {
+                        write!(self.out, "{}", separator)?;
+                    }
+                    write!(self.out, "{}", value)?;
+                }
+                writeln!(self.out)
+            },
+            Format::Csv =>
+                write_csv_line(&mut self.out, values.iter().copied()),
+            Format::Json => {
+                write!(self.out, "{{")?;
+                for (idx, (name, value)) in
+                    self.names.iter().zip(values).enumerate()
+                {
+                    if idx > 0 {
+                        write!(self.out, ",")?;
+                    }
+                    write_json_string(&mut self.out, name)?;
+                    write!(self.out, ":")?;
+                    write_json_string(&mut self.out, value)?;
+                }
+                writeln!(self.out, "}}")
+            },
+        }
+    }
+
+    /// Flushes the writer.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+// Fields are written a piece at a time, so that secrets are not copied
+// into buffers that outlive them.
+fn write_csv_line<'a>(
+    out: &mut impl Write,
+    fields: impl Iterator<Item = &'a str>,
+) -> io::Result<()> {
+    for (idx, field) in fields.enumerate() {
+        if idx > 0 {
+            write!(out, ",")?;
+        }
+        let quoted = field.contains([',', '"', '\r', '\n']);
+        if !quoted {
+            write!(out, "{}", field)?;
+            continue;
+        }
+        write!(out, "\"")?;
+        for (idx, piece) in field.split('"').enumerate() {
+            if idx > 0 {
+                write!(out, "\"\"")?;
+            }
+            write!(out, "{}", piece)?;
+        }
+        write!(out, "\"")?;
+    }
+    // RFC 4180 ends lines with CRLF.
+    write!(out, "\r\n")
+}
+
+fn write_json_string(out: &mut impl Write, value: &str) -> io::Result<()> {
+    write!(out, "\"")?;
+    for ch in value.chars() {
+        match ch {
+            '"' => write!(out, "\\\"")?,
+            '\\' => write!(out, "\\\\")?,
+            '\n' => write!(out, "\\n")?,
+            '\r' => write!(out, "\\r")?,
+            '\t' => write!(out, "\\t")?,
+            ch if ch < ' ' => write!(out, "\\u{:04x}", ch as u32)?,
+            ch => write!(out, "{}", ch)?,
+        }
+    }
+    write!(out, "\"")
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Piece {
+    Letter,
+    Digit,
+    Other,
+}
+
+/// Appends the password with spaces where it reads aloud in pieces: between
+/// syllables of letters, pairs of digits and each other character. Spaces
+/// in the password itself are shown as "␣".
+pub fn write_speakable(password: &str, out: &mut String) {
+    // Reserved at once, so that no reallocation leaves copies behind.
+    out.reserve(password.len() * 4);
+    let is_vowel = |ch: char| "aeiouyAEIOUY".contains(ch);
+    let piece = |ch: char| {
+        if ch.is_ascii_alphabetic() {
+            Piece::Letter
+        } else if ch.is_ascii_digit() {
+            Piece::Digit
+        } else {
+            Piece::Other
+        }
+    };
+    let mut chars = password.chars().peekable();
+    let mut prev = None;
+    // The length of the current piece, whether it has a vowel and the
+    // consonants after its last.
+    let mut len = 0;
+    let mut has_vowel = false;
+    let mut coda = 0;
+    while let Some(ch) = chars.next() {
+        let kind = piece(ch);
+        let next_vowel = chars.peek().is_some_and(|&next| is_vowel(next));
+        let breaks =
+            match kind {
+                _ if prev != Some(kind) => prev.is_some(),
+                Piece::Other => true,
+                Piece::Digit => len == 2,
+                // A consonant starts a syllable before a vowel, after at
+                // most two others ending the last, or after three without
+                // a vowel, and no syllable is longer than five letters.
+                Piece::Letter => {
+                    len == 5
+                        || !is_vowel(ch)
+                            && if has_vowel {
+                                next_vowel || coda == 2
+                            } else {
+                                len == 3
+                            }
+                },
+            };
+        if breaks {
+            out.push(' ');
+            len = 0;
+            has_vowel = false;
+            coda = 0;
+        }
+        len += 1;
+        if is_vowel(ch) {
+            has_vowel = true;
+            coda = 0;
+        } else {
+            coda += 1;
+        }
+        out.push(visible(ch));
+        prev = Some(kind);
+    }
+}
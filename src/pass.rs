@@ -0,0 +1,210 @@
+use anyhow::{anyhow, Context, Result};
+use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use zeroize::Zeroizing;
+
+use crate::i18n::{tr, trf};
+
+// Entries in pass's password store are files of GPG ciphertext, encrypted
+// to the keys listed in the nearest .gpg-id file above them. The password
+// is the first line of the plaintext, and any lines after it hold other
+// details of the account. Plaintext only ever passes through pipes to gpg.
+
+const GPG_ID_FILE: &str = ".gpg-id";
+const ENTRY_EXTENSION: &str = "gpg";
+
+pub struct PasswordStore {
+    dir: PathBuf,
+}
+
+impl PasswordStore {
+    /// Finds the store in PASSWORD_STORE_DIR or ~/.password-store.
+    pub fn open() -> Result<Self> {
+        let dir =
+            env::var_os("PASSWORD_STORE_DIR")
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+                .or_else(|| {
+                    let home = PathBuf::from(env::var_os("HOME")?);
+                    Some(home.join(".password-store"))
+                })
+                .ok_or_else(|| {
+                    anyhow!(tr("could not find the password store"))
+                })?;
+        Ok(PasswordStore { dir })
+    }
+
+    // Returns the path of an entry's file, refusing names that would leave
+    // the store.
+    fn entry_path(&self, name: &str) -> Result<PathBuf> {
+        let escapes =
+            Path::new(name).components().any(|component| {
+                !matches!(component, Component::Normal(_))
+            });
+        if name.is_empty() || escapes {
+            return Err(anyhow!(trf("invalid entry name: \"{}\"", &[&name])));
+        }
+        Ok(self.dir.join(format!("{}.{}", name, ENTRY_EXTENSION)))
+    }
+
+    /// Checks that a generated password can be put into an entry before
+    /// it is generated. In place, the password replaces only the first
+    /// line of the existing entry, and the lines after it are returned.
+    /// Otherwise an existing entry is only replaced if asked.
+    pub fn prepare(
+        &self,
+        name: &str,
+        in_place: bool,
+        overwrite: bool,
+    ) -> Result<Option<Zeroizing<String>>> {
+        let exists = self.entry_path(name)?.exists();
+        if in_place {
+            if !exists {
+                let msg =
+                    trf("the password store has no entry \"{}\"", &[&name]);
+                return Err(anyhow!(msg));
+            }
+            let plaintext = self.read(name)?;
+            let rest =
+                plaintext.split_once('\n').map_or("", |(_, rest)| rest);
+            return Ok(Some(Zeroizing::new(rest.to_string())));
+        }
+        if exists && !overwrite {
+            let msg = trf(
+                "the password store already has an entry \"{}\"; use \
+                 --in-place or -f",
+                &[&name],
+            );
+            return Err(anyhow!(msg));
+        }
+        Ok(None)
+    }
+
+    // Returns the keys in the .gpg-id file nearest the entry.
+    fn recipients(&self, path: &Path) -> Result<Vec<String>> {
+        for dir in path.ancestors().skip(1) {
+            let gpg_id = dir.join(GPG_ID_FILE);
+            if gpg_id.is_file() {
+                let context =
+                    || trf("could not read \"{}\"", &[&gpg_id.display()]);
+                let ids = fs::read_to_string(&gpg_id).with_context(context)?;
+                let ids =
+                    ids.lines()
+                        .map(|line| line.split('#').next().unwrap().trim())
+                        .filter(|id| !id.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                return Ok(ids);
+            }
+            if dir == self.dir {
+                break;
+            }
+        }
+        let dir = self.dir.display();
+        let msg = trf("no .gpg-id in the password store at \"{}\"", &[&dir]);
+        Err(anyhow!(msg))
+    }
+
+    // Decrypts an entry.
+    fn read(&self, name: &str) -> Result<Zeroizing<String>> {
+        let path = self.entry_path(name)?;
+        let mut child =
+            gpg()
+                .args(["--decrypt", "--quiet", "--batch"])
+                .arg(&path)
+                .stdout(Stdio::piped())
+                .spawn()
+                .map_err(could_not_run)?;
+        let mut plaintext = Zeroizing::new(String::new());
+        child.stdout.take().unwrap().read_to_string(&mut plaintext)?;
+        check_status(child.wait()?)?;
+        Ok(plaintext)
+    }
+
+    /// Encrypts the contents into an entry, replacing any already there.
+    pub fn write(&self, name: &str, contents: &str) -> Result<()> {
+        let path = self.entry_path(name)?;
+        let recipients = self.recipients(&path)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Encrypting to a temporary file first leaves the old entry intact
+        // if gpg fails.
+        let tmp_path = path.with_extension("gpg.tmp");
+        let mut command = gpg();
+        command.args([
+            "--encrypt",
+            "--quiet",
+            "--batch",
+            "--yes",
+            "--compress-algo=none",
+            "--no-encrypt-to",
+        ]);
+        for recipient in &recipients {
+            command.args(["--recipient", recipient]);
+        }
+        let mut child =
+            command
+                .arg("--output")
+                .arg(&tmp_path)
+                .stdin(Stdio::piped())
+                .spawn()
+                .map_err(could_not_run)?;
+        let mut stdin = child.stdin.take().unwrap();
+        stdin.write_all(contents.as_bytes())?;
+        drop(stdin);
+        if let Err(err) = check_status(child.wait()?) {
+            let _ = fs::remove_file(&tmp_path);
+            return Err(err);
+        }
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    /// Commits the entry if the store is a git repository, as pass does.
+    pub fn commit(&self, name: &str, message: &str) -> Result<()> {
+        if !self.dir.join(".git").exists() {
+            return Ok(());
+        }
+        let path = self.entry_path(name)?;
+        let git = |args: &[&std::ffi::OsStr]| -> Result<()> {
+            let status =
+                Command::new("git")
+                    .arg("-C")
+                    .arg(&self.dir)
+                    .args(args)
+                    .stdout(Stdio::null())
+                    .status()
+                    .map_err(|err| {
+                        anyhow!(trf("could not run {}: {}", &[&"git", &err]))
+                    })?;
+            if !status.success() {
+                let msg = trf("{} exited with {}", &[&"git", &status]);
+                return Err(anyhow!(msg));
+            }
+            Ok(())
+        };
+        git(&["add".as_ref(), "--".as_ref(), path.as_os_str()])?;
+        git(&["commit".as_ref(), "-m".as_ref(), message.as_ref()])
+    }
+}
+
+fn gpg() -> Command {
+    let mut command = Command::new("gpg");
+    command.stdin(Stdio::null());
+    command
+}
+
+fn could_not_run(err: std::io::Error) -> anyhow::Error {
+    anyhow!(trf("could not run {}: {}", &[&"gpg", &err]))
+}
+
+fn check_status(status: std::process::ExitStatus) -> Result<()> {
+    if !status.success() {
+        return Err(anyhow!(trf("{} exited with {}", &[&"gpg", &status])));
+    }
+    Ok(())
+}
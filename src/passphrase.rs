@@ -0,0 +1,69 @@
+use rand_core::CryptoRngCore;
+use std::num::NonZeroUsize;
+
+use crate::generator::Generator;
+use crate::sampler::random_below;
+use crate::{invalid_spec, Result};
+
+/// Generates passphrases of words drawn uniformly from a word list.
+#[derive(Clone, Debug)]
+pub struct PassphraseGenerator {
+    words: Vec<String>,
+    num_words: NonZeroUsize,
+    separator: String,
+}
+
+impl PassphraseGenerator {
+    /// Creates a generator joining this many words with the separator,
+    /// failing if the word list is empty. Duplicate words are ignored.
+    pub fn new(
+        words: impl IntoIterator<Item = impl Into<String>>,
+        num_words: NonZeroUsize,
+        separator: impl Into<String>,
+    ) -> Result<Self> {
+        let mut words = words.into_iter().map(Into::into).collect::<Vec<_>>();
+        words.sort_unstable();
+        words.dedup();
+        if words.is_empty() {
+            return Err(invalid_spec("word list is empty"));
+        }
+        let separator = separator.into();
+        Ok(PassphraseGenerator { words, num_words, separator })
+    }
+
+    /// Returns the sorted, distinct words passphrases are drawn from.
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    /// Returns the number of words in each passphrase.
+    pub fn num_words(&self) -> NonZeroUsize {
+        self.num_words
+    }
+
+    /// Returns the separator placed between words.
+    pub fn separator(&self) -> &str {
+        &self.separator
+    }
+}
+
+impl Generator for PassphraseGenerator {
+    fn generate(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+    ) -> Result<()> {
+        for idx in 0..self.num_words.get() {
+            if idx > 0 {
+                out.push_str(&self.separator);
+            }
+            let word = &self.words[random_below(rng, self.words.len())?];
+            out.push_str(word);
+        }
+        Ok(())
+    }
+
+    fn entropy_bits(&self) -> f64 {
+        self.num_words.get() as f64 * (self.words.len() as f64).log2()
+    }
+}
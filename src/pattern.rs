@@ -0,0 +1,97 @@
+use rand_core::CryptoRngCore;
+
+use crate::charset::{is_typeable, typeable_charset};
+use crate::generator::Generator;
+use crate::policy::CharClass;
+use crate::sampler::random_below;
+use crate::{invalid_spec, Result};
+
+#[derive(Clone, Debug)]
+enum Element {
+    Literal(u8),
+    Class(Vec<u8>),
+}
+
+/// Generates passwords following a pattern of character classes.
+///
+/// In the pattern, `l` stands for a lowercase letter, `u` for an uppercase
+/// letter, `d` for a digit, `s` for a symbol and `x` for any typeable
+/// character. A backslash makes the next character literal, and other
+/// typeable characters stand for themselves.
+#[derive(Clone, Debug)]
+pub struct PatternGenerator {
+    pattern: String,
+    elements: Vec<Element>,
+}
+
+impl PatternGenerator {
+    /// Parses the pattern, failing on untypeable characters or a trailing
+    /// backslash.
+    pub fn new(pattern: impl Into<String>) -> Result<Self> {
+        let pattern = pattern.into();
+        let mut elements = Vec::new();
+        let mut bytes = pattern.bytes();
+        while let Some(byte) = bytes.next() {
+            if !is_typeable(byte) {
+                let msg = "found untypeable or non-ASCII character";
+                return Err(invalid_spec(msg));
+            }
+            let element =
+                match byte {
+                    b'l' => Element::Class(CharClass::Lowercase.chars()),
+                    b'u' => Element::Class(CharClass::Uppercase.chars()),
+                    b'd' => Element::Class(CharClass::Digits.chars()),
+                    b's' => Element::Class(CharClass::Symbols.chars()),
+                    b'x' => Element::Class(typeable_charset()),
+                    b'\\' => match bytes.next() {
+                        Some(byte) if is_typeable(byte) =>
+                            Element::Literal(byte),
+                        Some(_) => {
+                            let msg = "found untypeable or non-ASCII character";
+                            return Err(invalid_spec(msg));
+                        },
+                        None => {
+                            let msg = "unterminated escape sequence";
+                            return Err(invalid_spec(msg));
+                        },
+                    },
+                    byte => Element::Literal(byte),
+                };
+            elements.push(element);
+        }
+        Ok(PatternGenerator { pattern, elements })
+    }
+
+    /// Returns the pattern as given.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+}
+
+impl Generator for PatternGenerator {
+    fn generate(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+    ) -> Result<()> {
+        for element in &self.elements {
+            let byte =
+                match element {
+                    Element::Literal(byte) => *byte,
+                    Element::Class(chars) =>
+                        chars[random_below(rng, chars.len())?],
+                };
+            out.push(byte as char);
+        }
+        Ok(())
+    }
+
+    fn entropy_bits(&self) -> f64 {
+        self.elements.iter()
+            .map(|element| match element {
+                Element::Literal(_) => 0.0,
+                Element::Class(chars) => (chars.len() as f64).log2(),
+            })
+            .sum()
+    }
+}
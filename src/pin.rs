@@ -0,0 +1,244 @@
+use rand_core::CryptoRngCore;
+use std::collections::BTreeSet;
+use std::num::NonZeroUsize;
+use tracing::debug;
+
+use crate::generator::{entropy_bits, Generator};
+use crate::memory::SecretBuf;
+use crate::observer::GenerationObserver;
+use crate::policy::CharClass;
+use crate::sampler::sample;
+use crate::Result;
+
+// Common PINs from published analyses of leaked PIN sets that the patterns
+// below do not already catch: keypad shapes, words spelt on the keypad and
+// dates.
+const COMMON_PINS: &[&str] = &[
+    "0852", "1004", "1122", "1230", "1984", "2468", "2580", "5683", "6969",
+    "7410", "8520", "112233", "123321", "147258", "159753", "246810",
+    "258369", "520131", "654123", "789456",
+];
+
+/// Generates numeric PINs of a fixed length, optionally rejecting weak ones.
+#[derive(Clone, Debug)]
+pub struct PinGenerator {
+    digits: Vec<u8>,
+    pin_len: NonZeroUsize,
+    reject_weak: bool,
+}
+
+impl PinGenerator {
+    /// Creates a generator for PINs of this many digits.
+    pub fn new(pin_len: NonZeroUsize) -> Self {
+        let digits = CharClass::Digits.chars();
+        PinGenerator { digits, pin_len, reject_weak: false }
+    }
+
+    /// Sets whether to redraw PINs that are common, repeat a shorter block
+    /// of digits, run in sequence or look like a year.
+    pub fn reject_weak(mut self, reject_weak: bool) -> Self {
+        self.reject_weak = reject_weak;
+        self
+    }
+
+    /// Returns the number of digits in each PIN.
+    pub fn length(&self) -> NonZeroUsize {
+        self.pin_len
+    }
+
+    /// Returns whether weak PINs are redrawn.
+    pub fn rejects_weak(&self) -> bool {
+        self.reject_weak
+    }
+
+    /// Returns the number of PINs of this length that are rejected as weak,
+    /// or `None` if it is too large to count exactly.
+    pub fn num_rejected(&self) -> Option<u128> {
+        if !self.reject_weak {
+            return Some(0);
+        }
+        let pin_len = self.pin_len.get();
+        let num_pins = 10u128.checked_pow(pin_len as u32)?;
+        let num_periodic = num_pins - num_aperiodic(pin_len);
+        let num_other =
+            other_weak_pins(pin_len).iter()
+                .filter(|pin| !is_periodic(pin))
+                .count();
+        Some(num_periodic + num_other as u128)
+    }
+}
+
+fn is_weak_pin(pin: &[u8]) -> bool {
+    is_periodic(pin) || other_weak_pins(pin.len()).contains(pin)
+}
+
+// Whether the PIN is a shorter block of digits repeated, such as 1111 or
+// 123123.
+fn is_periodic(pin: &[u8]) -> bool {
+    (1..pin.len())
+        .filter(|&period| pin.len().is_multiple_of(period))
+        .any(|period| pin[period..] == pin[..pin.len() - period])
+}
+
+// Counts the PINs of this length that are not periodic, by Möbius inversion
+// over the periods dividing the length. The length is small enough for the
+// powers to fit.
+fn num_aperiodic(pin_len: usize) -> u128 {
+    (1..=pin_len)
+        .filter(|&num_blocks| pin_len.is_multiple_of(num_blocks))
+        .map(|num_blocks| {
+            let count = 10i128.pow((pin_len / num_blocks) as u32);
+            mobius(num_blocks) * count
+        })
+        .sum::<i128>() as u128
+}
+
+fn mobius(mut n: usize) -> i128 {
+    let mut result = 1;
+    let mut factor = 2;
+    while factor * factor <= n {
+        if n.is_multiple_of(factor) {
+            n /= factor;
+            if n.is_multiple_of(factor) {
+                return 0;
+            }
+            result = -result;
+        }
+        factor += 1;
+    }
+    if n > 1 {
+        result = -result;
+    }
+    result
+}
+
+// The weak PINs of this length that are not found by the periodicity test:
+// runs of consecutive digits, years and common PINs.
+fn other_weak_pins(pin_len: usize) -> BTreeSet<Vec<u8>> {
+    let mut pins = BTreeSet::new();
+    if (2..=10).contains(&pin_len) {
+        for first in 0..=10 - pin_len {
+            let run =
+                (first..first + pin_len)
+                    .map(|digit| b'0' + digit as u8)
+                    .collect::<Vec<_>>();
+            pins.insert(run.iter().rev().copied().collect());
+            pins.insert(run);
+        }
+    }
+    if pin_len == 4 {
+        for year in 1900..2100 {
+            pins.insert(year.to_string().into_bytes());
+        }
+    }
+    for pin in COMMON_PINS {
+        if pin.len() == pin_len {
+            pins.insert(pin.as_bytes().to_vec());
+        }
+    }
+    pins
+}
+
+impl Generator for PinGenerator {
+    fn generate(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+    ) -> Result<()> {
+        self.generate_observed(rng, out, &())
+    }
+
+    fn generate_observed(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+        observer: &dyn GenerationObserver,
+    ) -> Result<()> {
+        let mut pin = SecretBuf::zeroed(self.pin_len.get());
+        let mut attempt = 1;
+        loop {
+            sample(rng, &self.digits, &mut pin)?;
+            if !self.reject_weak || !is_weak_pin(&pin) {
+                break;
+            }
+            debug!(attempt, "weak PIN rejected");
+            observer.candidate_rejected(attempt);
+            attempt += 1;
+        }
+        out.extend(pin.iter().copied().map(char::from));
+        Ok(())
+    }
+
+    // Rejection leaves the remaining PINs equally likely, so the entropy is
+    // that of a uniform choice among them.
+    fn entropy_bits(&self) -> f64 {
+        let pin_len = self.pin_len.get();
+        match self.num_rejected() {
+            Some(num_rejected) if num_rejected > 0 => {
+                let num_pins = 10u128.pow(pin_len as u32);
+                ((num_pins - num_rejected) as f64).log2()
+            },
+            _ => entropy_bits(&self.digits, pin_len),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::ChaCha20Rng;
+    use rand::SeedableRng;
+
+    fn generator(pin_len: usize) -> PinGenerator {
+        PinGenerator::new(NonZeroUsize::new(pin_len).unwrap()).reject_weak(true)
+    }
+
+    #[test]
+    fn weak_pins_are_recognised() {
+        for pin in ["1111", "1212", "123123", "1234", "4321", "1984", "2031"] {
+            assert!(is_weak_pin(pin.as_bytes()), "{}", pin);
+        }
+        for pin in ["8203", "5917", "386104", "1231"] {
+            assert!(!is_weak_pin(pin.as_bytes()), "{}", pin);
+        }
+    }
+
+    #[test]
+    fn common_pins_are_weak() {
+        for pin in COMMON_PINS {
+            assert!(is_weak_pin(pin.as_bytes()), "{}", pin);
+        }
+    }
+
+    #[test]
+    fn rejected_pins_are_counted_exactly() {
+        for pin_len in 1..=6 {
+            // As is_weak_pin, without building the set for every PIN.
+            let others = other_weak_pins(pin_len);
+            let num_weak =
+                (0..10u32.pow(pin_len as u32))
+                    .map(|pin| format!("{:0width$}", pin, width = pin_len))
+                    .map(String::into_bytes)
+                    .filter(|pin| is_periodic(pin) || others.contains(pin))
+                    .count();
+            let generator = generator(pin_len);
+            assert_eq!(generator.num_rejected(), Some(num_weak as u128));
+        }
+        assert_eq!(generator(4).num_rejected(), Some(321));
+        assert_eq!(generator(4).reject_weak(false).num_rejected(), Some(0));
+    }
+
+    #[test]
+    fn weak_pins_are_redrawn() {
+        let generator = generator(4);
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        for _ in 0..1000 {
+            let mut pin = String::new();
+            generator.generate(&mut rng, &mut pin).unwrap();
+            assert_eq!(pin.len(), 4);
+            assert!(!is_weak_pin(pin.as_bytes()), "{}", pin);
+        }
+        let entropy = generator.entropy_bits();
+        assert_eq!(entropy, ((10_000 - 321) as f64).log2());
+    }
+}
@@ -0,0 +1,242 @@
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::num::NonZeroUsize;
+
+use crate::charset::{chars_of, Charset};
+
+/// A class of typeable characters that a policy can require.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum CharClass {
+    /// The letters `a` to `z`.
+    Lowercase,
+    /// The letters `A` to `Z`.
+    Uppercase,
+    /// The digits `0` to `9`.
+    Digits,
+    /// Typeable characters other than letters, digits and space.
+    Symbols,
+}
+
+const LOWERCASE: u128 = 0x07ff_fffe_0000_0000_0000_0000_0000_0000;
+const UPPERCASE: u128 = 0x0000_0000_07ff_fffe_0000_0000_0000_0000;
+const DIGITS: u128 = 0x0000_0000_0000_0000_03ff_0000_0000_0000;
+const SYMBOLS: u128 = 0x7800_0001_f800_0001_fc00_fffe_0000_0000;
+
+impl CharClass {
+    fn mask(self) -> u128 {
+        match self {
+            CharClass::Lowercase => LOWERCASE,
+            CharClass::Uppercase => UPPERCASE,
+            CharClass::Digits => DIGITS,
+            CharClass::Symbols => SYMBOLS,
+        }
+    }
+
+    /// Returns the characters in this class.
+    pub fn chars(self) -> Vec<u8> {
+        chars_of(self.mask())
+    }
+
+    /// Returns whether the character is in this class.
+    pub fn contains(self, byte: u8) -> bool {
+        byte < 128 && self.mask() & (1 << byte) != 0
+    }
+}
+
+impl fmt::Display for CharClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name =
+            match self {
+                CharClass::Lowercase => "lowercase",
+                CharClass::Uppercase => "uppercase",
+                CharClass::Digits => "digit",
+                CharClass::Symbols => "symbol",
+            };
+        write!(f, "{}", name)
+    }
+}
+
+/// Constraints that every generated password must satisfy.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct Policy {
+    /// Classes of which each password must contain at least one character.
+    pub required: Vec<CharClass>,
+    /// Characters that may not start or end a password.
+    pub forbidden_at_ends: String,
+    /// The greatest length a password may have, if limited.
+    pub max_length: Option<NonZeroUsize>,
+}
+
+impl Policy {
+    /// Returns whether the password satisfies this policy.
+    pub fn is_satisfied_by(&self, password: &[u8]) -> bool {
+        let required_present =
+            self.required.iter().all(|&class| {
+                password.iter().any(|&byte| class.contains(byte))
+            });
+        let ends_allowed =
+            [password.first(), password.last()]
+                .into_iter()
+                .flatten()
+                .all(|&byte| self.is_allowed_at_ends(byte));
+        let short_enough =
+            self.max_length
+                .is_none_or(|max_len| password.len() <= max_len.get());
+        required_present && ends_allowed && short_enough
+    }
+
+    /// Returns whether a password may start or end with the character.
+    pub fn is_allowed_at_ends(&self, byte: u8) -> bool {
+        !self.forbidden_at_ends.as_bytes().contains(&byte)
+    }
+
+    /// Returns the entropy in bits of a password of this length drawn
+    /// uniformly from those of the character set that satisfy this policy,
+    /// which is negative infinity if none do.
+    pub fn entropy_bits(&self, charset: &[u8], password_len: usize) -> f64 {
+        let too_long =
+            self.max_length.is_some_and(|max_len| password_len > max_len.get());
+        if password_len == 0 || too_long {
+            return f64::NEG_INFINITY;
+        }
+        let mut classes = Vec::new();
+        for &class in &self.required {
+            if !classes.contains(&class) {
+                classes.push(class);
+            }
+        }
+        // By inclusion and exclusion, the passwords lacking no required
+        // class, counted for each set of classes by the characters left
+        // without them and those of them allowed at the ends.
+        let terms =
+            (0..1_usize << classes.len())
+                .map(|subset| {
+                    let lacking =
+                        classes.iter()
+                            .enumerate()
+                            .filter(|&(idx, _)| subset & 1 << idx != 0)
+                            .fold(0, |mask, (_, class)| mask | class.mask());
+                    let left =
+                        charset.iter()
+                            .filter(|&&byte| {
+                                byte >= 128 || lacking & 1 << byte == 0
+                            })
+                            .collect::<Vec<_>>();
+                    let num_ends =
+                        left.iter()
+                            .filter(|&&&byte| self.is_allowed_at_ends(byte))
+                            .count();
+                    (subset.count_ones() % 2 == 1, left.len(), num_ends)
+                })
+                .collect::<Vec<_>>();
+        let count = |num_chars: usize, num_ends: usize| {
+            if password_len == 1 {
+                return Some(num_ends as u128);
+            }
+            let inner_len = u32::try_from(password_len - 2).ok()?;
+            let inner = (num_chars as u128).checked_pow(inner_len)?;
+            ((num_ends * num_ends) as u128).checked_mul(inner)
+        };
+        // Counted exactly while the counts fit in 128 bits.
+        let exact =
+            terms.iter().try_fold(
+                (0_u128, 0_u128),
+                |(added, subtracted), &(odd, num_chars, num_ends)| {
+                    let term = count(num_chars, num_ends)?;
+                    if odd {
+                        Some((added, subtracted.checked_add(term)?))
+                    } else {
+                        Some((added.checked_add(term)?, subtracted))
+                    }
+                },
+            );
+        if let Some((added, subtracted)) = exact {
+            return ((added - subtracted) as f64).log2();
+        }
+        // Beyond that, as a fraction of the passwords lacking nothing, which
+        // is too far from zero at such lengths for rounding to matter.
+        let (_, all_chars, all_ends) = terms[0];
+        let inner_len = (password_len - 2) as f64;
+        let fraction =
+            terms.iter()
+                .map(|&(odd, num_chars, num_ends)| {
+                    let term =
+                        (num_ends as f64 / all_ends as f64).powi(2)
+                            * (num_chars as f64 / all_chars as f64)
+                                .powf(inner_len);
+                    if odd { -term } else { term }
+                })
+                .sum::<f64>();
+        2.0 * (all_ends as f64).log2()
+            + inner_len * (all_chars as f64).log2()
+            + fraction.log2()
+    }
+}
+
+/// A character set and policy for passwords that must fit somewhere
+/// particular without escaping.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[non_exhaustive]
+pub enum Preset {
+    /// Lowercase letters, digits and hyphens, neither starting nor ending
+    /// with a hyphen and at most 63 characters long, so that every password
+    /// is a valid DNS label.
+    Dns,
+    /// The characters RFC 3986 leaves unreserved, so that every password can
+    /// be a URL path segment or query component without percent-encoding.
+    /// Passwords neither start nor end with a dot, so that none is a dot
+    /// segment such as `..`.
+    Url,
+}
+
+const MAX_DNS_LABEL_LEN: NonZeroUsize = NonZeroUsize::new(63).unwrap();
+
+impl Preset {
+    /// Returns the characters passwords are drawn from.
+    pub fn charset(self) -> Charset {
+        let chars: &[u8] =
+            match self {
+                Preset::Dns => b"abcdefghijklmnopqrstuvwxyz0123456789-",
+                Preset::Url =>
+                    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz\
+                      0123456789-._~",
+            };
+        Charset::new(chars).unwrap()
+    }
+
+    /// Returns the policy every password must satisfy.
+    pub fn policy(self) -> Policy {
+        match self {
+            Preset::Dns => Policy {
+                forbidden_at_ends: "-".to_string(),
+                max_length: Some(MAX_DNS_LABEL_LEN),
+                ..Policy::default()
+            },
+            Preset::Url => Policy {
+                forbidden_at_ends: ".".to_string(),
+                ..Policy::default()
+            },
+        }
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Preset::Dns => write!(f, "dns"),
+            Preset::Url => write!(f, "url"),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use passgen::{GenerationObserver, Generator, PassgenError, Result};
+use rand_core::CryptoRngCore;
+use rhai::{Dynamic, Engine, Scope, AST};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use zeroize::Zeroizing;
+
+use crate::i18n::{tr, trf};
+
+// A post-processing script is a Rhai script run for each candidate
+// password, which it is given as the constant `password`. The value it
+// ends with decides what happens to the candidate: a string is the
+// password to use, which may be the candidate unchanged, true accepts the
+// candidate and false rejects it, in which case another is drawn. Anything
+// else, including an error the script throws, fails the job.
+//
+// Scripts are embedded rather than run as programs so that they can be
+// sandboxed: the engine has no module loading and no functions that reach
+// the file system, the network or other processes, print and debug go
+// nowhere, and the operations a script performs and the sizes of the
+// values it builds are limited.
+
+// Rejecting more candidates than this in a row fails the job.
+const MAX_ATTEMPTS: u64 = 1000;
+const MAX_OPERATIONS: u64 = 1_000_000;
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_EXPR_DEPTH: usize = 64;
+const MAX_FUNCTION_EXPR_DEPTH: usize = 32;
+const MAX_SIZE: usize = 4096;
+
+/// A compiled post-processing script.
+#[derive(Clone)]
+pub struct Script {
+    path: PathBuf,
+    ast: AST,
+}
+
+impl Script {
+    /// Reads and compiles the script at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let source =
+            fs::read_to_string(path).map_err(|err| failure(path, &err))?;
+        let ast =
+            engine().compile(&source).map_err(|err| failure(path, &err))?;
+        Ok(Script { path: path.to_path_buf(), ast })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Passes each password from another generator through a script.
+pub struct PostScript<'a> {
+    generator: &'a (dyn Generator + Sync),
+    script: &'a Script,
+    engine: Engine,
+    changed: AtomicBool,
+}
+
+impl<'a> PostScript<'a> {
+    pub fn new(
+        generator: &'a (dyn Generator + Sync),
+        script: &'a Script,
+    ) -> Self {
+        PostScript {
+            generator,
+            script,
+            engine: engine(),
+            changed: AtomicBool::new(false),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        self.script.path()
+    }
+
+    /// Returns whether the script has returned a password other than the
+    /// candidate it was given.
+    pub fn changed(&self) -> bool {
+        self.changed.load(Ordering::Relaxed)
+    }
+
+    // Returns the password the script returns for the candidate, or None
+    // if it rejects it.
+    fn run(&self, candidate: &str) -> Result<Option<Zeroizing<String>>> {
+        let mut scope = Scope::new();
+        scope.push_constant("password", candidate.to_string());
+        let value =
+            self.engine
+                .eval_ast_with_scope::<Dynamic>(&mut scope, &self.script.ast)
+                .map_err(|err| self.failure(&err))?;
+        if let Ok(accepted) = value.as_bool() {
+            let password = Zeroizing::new(candidate.to_string());
+            return Ok(accepted.then_some(password));
+        }
+        let password =
+            value.into_string()
+                .map(Zeroizing::new)
+                .map_err(|_| {
+                    let reason =
+                        tr("returned neither a password nor true or false");
+                    self.failure(&reason)
+                })?;
+        if password.is_empty() || password.contains(['\n', '\r']) {
+            let reason =
+                tr("returned an empty password or one with a line break");
+            return Err(self.failure(&reason));
+        }
+        Ok(Some(password))
+    }
+
+    fn failure(&self, reason: &dyn std::fmt::Display) -> PassgenError {
+        failure(self.path(), reason)
+    }
+}
+
+impl Generator for PostScript<'_> {
+    fn generate(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+    ) -> Result<()> {
+        self.generate_observed(rng, out, &())
+    }
+
+    fn generate_observed(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+        observer: &dyn GenerationObserver,
+    ) -> Result<()> {
+        for attempt in 1..=MAX_ATTEMPTS {
+            let mut candidate = Zeroizing::new(String::new());
+            self.generator.generate_observed(rng, &mut candidate, observer)?;
+            if let Some(password) = self.run(&candidate)? {
+                if *password != *candidate {
+                    self.changed.store(true, Ordering::Relaxed);
+                }
+                out.push_str(&password);
+                return Ok(());
+            }
+            observer.candidate_rejected(attempt as usize);
+        }
+        let reason =
+            trf("rejected {} candidates in a row", &[&MAX_ATTEMPTS]);
+        Err(self.failure(&reason))
+    }
+
+    // A script may reject all but a few candidates or map them all to the
+    // same password, so nothing is known of the entropy of what it returns.
+    fn entropy_bits(&self) -> f64 {
+        0.0
+    }
+}
+
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(MAX_CALL_LEVELS)
+        .set_max_expr_depths(MAX_EXPR_DEPTH, MAX_FUNCTION_EXPR_DEPTH)
+        .set_max_string_size(MAX_SIZE)
+        .set_max_array_size(MAX_SIZE)
+        .set_max_map_size(MAX_SIZE)
+        .disable_symbol("eval")
+        .on_print(|_| {})
+        .on_debug(|_, _, _| {});
+    engine
+}
+
+fn failure(path: &Path, reason: &dyn std::fmt::Display) -> PassgenError {
+    let path = path.display();
+    let msg = trf("post-script \"{}\" failed: {}", &[&path, reason]);
+    PassgenError::InvalidSpec(msg)
+}
@@ -0,0 +1,8 @@
+pub use crate::{
+    CharClass,
+    Charset,
+    Generator,
+    PassgenError,
+    PasswordSpec,
+    Policy,
+};
@@ -0,0 +1,116 @@
+use anyhow::{Context, Result};
+use passgen::{CharsetGenerator, SiteContext};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::trf;
+
+// Profiles record every setting a derived password depends on, including
+// the derivation version, so that it can be derived again after the
+// defaults change.
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub site: String,
+    pub login: String,
+    pub counter: u32,
+    pub version: u32,
+    pub generator: CharsetGenerator,
+}
+
+impl Profile {
+    pub fn context(&self) -> SiteContext {
+        SiteContext {
+            site: self.site.clone(),
+            login: self.login.clone(),
+            counter: self.counter,
+        }
+    }
+
+    fn is_for(&self, site: &str, login: &str) -> bool {
+        self.site.eq_ignore_ascii_case(site) && self.login == login
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profiles {
+    profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    /// Returns the profiles file in the user's configuration directory.
+    pub fn default_path() -> Option<PathBuf> {
+        let config_dir =
+            env::var_os("XDG_CONFIG_HOME")
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+                .or_else(|| {
+                    env::var_os("HOME")
+                        .map(|home| PathBuf::from(home).join(".config"))
+                })
+                .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+        Some(config_dir.join("passgen").join("profiles.json"))
+    }
+
+    /// Loads the profiles, treating a missing file as empty.
+    pub fn load(path: &Path) -> Result<Self> {
+        let context = || trf("could not read \"{}\"", &[&path.display()]);
+        match fs::read_to_string(path) {
+            Ok(json) => Profiles::from_json(&json).with_context(context),
+            Err(err) if err.kind() == io::ErrorKind::NotFound =>
+                Ok(Profiles::default()),
+            Err(err) => Err(err).with_context(context),
+        }
+    }
+
+    /// Writes the profiles to a temporary file and moves it into place, so
+    /// that an interrupted save leaves the old profiles intact.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let context = || trf("could not write \"{}\"", &[&path.display()]);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(context)?;
+        }
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, self.to_json()?).with_context(context)?;
+        fs::rename(&temp_path, path).with_context(context)?;
+        Ok(())
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        let mut json = serde_json::to_string_pretty(self)?;
+        json.push('\n');
+        Ok(json)
+    }
+
+    pub fn find(&self, site: &str, login: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|profile| profile.is_for(site, login))
+    }
+
+    /// Adds a profile, replacing any for the same site and login.
+    pub fn insert(&mut self, profile: Profile) {
+        match self.profiles.iter_mut()
+            .find(|other| other.is_for(&profile.site, &profile.login))
+        {
+            Some(other) => *other = profile,
+            None => self.profiles.push(profile),
+        }
+    }
+
+    /// Adds every profile from another set, returning how many there were.
+    pub fn merge(&mut self, other: Profiles) -> usize {
+        let num_profiles = other.profiles.len();
+        for profile in other.profiles {
+            self.insert(profile);
+        }
+        num_profiles
+    }
+}
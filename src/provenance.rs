@@ -0,0 +1,38 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Where the randomness behind a set of passwords came from, recorded so
+/// that it can be reported alongside them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+#[non_exhaustive]
+pub enum EntropySource {
+    /// The operating system's random number generator.
+    Os,
+    /// A ChaCha20 generator periodically reseeded from the operating
+    /// system's random number generator.
+    OsSeeded,
+    /// A hardware token or other dedicated random number generator.
+    HardwareToken,
+    /// A generator seeded with a fixed value, for testing only.
+    SeededTest,
+    /// A generator supplied by the caller without a declared source.
+    Unspecified,
+}
+
+impl fmt::Display for EntropySource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name =
+            match self {
+                EntropySource::Os => "operating system",
+                EntropySource::OsSeeded =>
+                    "chacha20 reseeded from the operating system",
+                EntropySource::HardwareToken => "hardware token",
+                EntropySource::SeededTest => "seeded (testing only)",
+                EntropySource::Unspecified => "unspecified",
+            };
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,501 @@
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::i18n::{tr, trf};
+
+// Checks as libpwquality makes them, read from the same pwquality.conf, in
+// the same order and with the same meaning of each setting. Cracklib's
+// packed dictionaries are not read; its dictionary check looks words up in
+// a plain word list instead, after undoing case and common substitutions.
+// Passwords that pass are scored from 0, at the minimum length, to 100, at
+// twice that, counting the credits given for character classes towards
+// the length as libpwquality does.
+
+/// Where libpwquality reads its settings.
+pub const DEFAULT_CONFIG: &str = "/etc/security/pwquality.conf";
+
+// libpwquality never requires fewer characters.
+const BASE_MIN_LENGTH: i64 = 6;
+// Cracklib's thresholds.
+const MIN_DIFFERENT: usize = 5;
+const MAX_STEPS: usize = 4;
+const MIN_WORD_LEN: usize = 4;
+
+// Settings libpwquality takes that do not apply to a password checked alone.
+const IGNORED: &[&str] = &[
+    "difok",
+    "gecoscheck",
+    "dictpath",
+    "enforcing",
+    "retry",
+    "enforce_for_root",
+    "local_users_only",
+];
+
+/// The settings of pwquality.conf that apply to scoring.
+#[derive(Clone, Debug)]
+pub struct Settings {
+    min_length: i64,
+    digit_credit: i64,
+    upper_credit: i64,
+    lower_credit: i64,
+    other_credit: i64,
+    min_classes: i64,
+    max_repeat: i64,
+    max_sequence: i64,
+    max_class_repeat: i64,
+    user_check: bool,
+    user_substr: i64,
+    dict_check: bool,
+    bad_words: Vec<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            min_length: 8,
+            digit_credit: 0,
+            upper_credit: 0,
+            lower_credit: 0,
+            other_credit: 0,
+            min_classes: 0,
+            max_repeat: 0,
+            max_sequence: 0,
+            max_class_repeat: 0,
+            user_check: true,
+            user_substr: 0,
+            dict_check: true,
+            bad_words: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the settings from a pwquality.conf file, leaving those it does
+    /// not set at libpwquality's defaults.
+    pub fn read(path: &Path) -> Result<Self> {
+        let context = || trf("could not read \"{}\"", &[&path.display()]);
+        let text = fs::read_to_string(path).with_context(context)?;
+        Settings::parse(&text).map_err(|(line_num, err)| {
+            let path = path.display();
+            err.context(trf("line {} of \"{}\"", &[&line_num, &path]))
+        })
+    }
+
+    /// Parses the settings, failing with the number of the first line that
+    /// does not hold a valid setting.
+    pub fn parse(text: &str) -> Result<Self, (usize, anyhow::Error)> {
+        let mut settings = Settings::default();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (name, value) =
+                match line.split_once('=') {
+                    Some((name, value)) => (name.trim(), value.trim()),
+                    None => (line, ""),
+                };
+            settings.set(name, value).map_err(|err| (idx + 1, err))?;
+        }
+        Ok(settings)
+    }
+
+    fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        let number = || {
+            value.parse::<i64>().map_err(|_| {
+                anyhow!(trf("invalid value for {}: \"{}\"", &[&name, &value]))
+            })
+        };
+        match name {
+            "minlen" => self.min_length = number()?.max(BASE_MIN_LENGTH),
+            "dcredit" => self.digit_credit = number()?,
+            "ucredit" => self.upper_credit = number()?,
+            "lcredit" => self.lower_credit = number()?,
+            "ocredit" => self.other_credit = number()?,
+            "minclass" => self.min_classes = number()?,
+            "maxrepeat" => self.max_repeat = number()?,
+            "maxsequence" => self.max_sequence = number()?,
+            "maxclassrepeat" => self.max_class_repeat = number()?,
+            "usercheck" => self.user_check = number()? != 0,
+            "usersubstr" => self.user_substr = number()?,
+            "dictcheck" => self.dict_check = number()? != 0,
+            "badwords" => {
+                self.bad_words =
+                    value.split_whitespace().map(str::to_lowercase).collect();
+            },
+            name if IGNORED.contains(&name) => {},
+            name => return Err(anyhow!(trf("unknown setting: {}", &[&name]))),
+        }
+        Ok(())
+    }
+
+    /// Scores the password from 0 to 100, or gives why it fails the checks.
+    /// Dictionary words are looked up in the word list, which must be in
+    /// lowercase.
+    pub fn score(
+        &self,
+        password: &str,
+        user: Option<&str>,
+        words: &HashSet<String>,
+    ) -> Result<u32, String> {
+        if is_palindrome(password) {
+            return Err(tr("the password is a palindrome").to_string());
+        }
+        let size = self.credited_size(password)?;
+        if self.max_repeat > 0 && longest_run(password) > self.max_repeat {
+            let msg =
+                "the password contains more than {} same characters \
+                 consecutively";
+            return Err(trf(msg, &[&self.max_repeat]));
+        }
+        if self.max_sequence > 0
+            && longest_sequence(password) > self.max_sequence
+        {
+            let msg =
+                "the password contains a monotonic sequence longer than {} \
+                 characters";
+            return Err(trf(msg, &[&self.max_sequence]));
+        }
+        let lowercase = password.to_lowercase();
+        if let Some(user) = user.filter(|_| self.user_check) {
+            if contains_word(&lowercase, &user.to_lowercase(), self.user_substr)
+            {
+                let msg = "the password contains the user name in some form";
+                return Err(tr(msg).to_string());
+            }
+        }
+        let bad_word =
+            self.bad_words
+                .iter()
+                .any(|word| contains_word(&lowercase, word, self.user_substr));
+        if bad_word {
+            let msg = "the password contains forbidden words in some form";
+            return Err(tr(msg).to_string());
+        }
+        if self.dict_check {
+            if let Some(reason) = cracklib_check(password, words) {
+                let msg = "the password fails the dictionary check: {}";
+                return Err(trf(msg, &[&tr(reason)]));
+            }
+        }
+        let over = size - self.min_length;
+        Ok((over * 100 / self.min_length).clamp(0, 100) as u32)
+    }
+
+    // Gives the length with any credits for character classes added, or
+    // why the password is too short or has too few of a class.
+    fn credited_size(&self, password: &str) -> Result<i64, String> {
+        let mut counts = [0; 4];
+        let mut prev_class = None;
+        let mut same_class = 0;
+        for ch in password.chars() {
+            let class = class(ch);
+            if prev_class == Some(class) {
+                same_class += 1;
+            } else {
+                prev_class = Some(class);
+                same_class = 1;
+            }
+            if self.max_class_repeat > 1 && same_class > self.max_class_repeat
+            {
+                let msg =
+                    "the password contains more than {} characters of the \
+                     same class consecutively";
+                return Err(trf(msg, &[&self.max_class_repeat]));
+            }
+            counts[class] += 1;
+        }
+        let credits = [
+            (self.digit_credit, "the password contains fewer than {} digits"),
+            (
+                self.upper_credit,
+                "the password contains fewer than {} uppercase letters",
+            ),
+            (
+                self.lower_credit,
+                "the password contains fewer than {} lowercase letters",
+            ),
+            (
+                self.other_credit,
+                "the password contains fewer than {} non-alphanumeric \
+                 characters",
+            ),
+        ];
+        let mut size = self.min_length;
+        for (count, (credit, msg)) in counts.iter().zip(credits) {
+            if credit >= 0 {
+                size -= (*count).min(credit);
+            } else if *count < -credit {
+                return Err(trf(msg, &[&-credit]));
+            }
+        }
+        let num_classes = counts.iter().filter(|&&count| count > 0).count();
+        if self.min_classes > 0 && (num_classes as i64) < self.min_classes {
+            let msg = "the password contains fewer than {} character classes";
+            return Err(trf(msg, &[&self.min_classes]));
+        }
+        let len = password.chars().count() as i64;
+        if len < size {
+            let msg = "the password is shorter than {} characters";
+            return Err(trf(msg, &[&size]));
+        }
+        Ok(len + self.min_length - size)
+    }
+}
+
+/// Reads a word list of one word per line for the dictionary check.
+pub fn read_words(path: &Path) -> Result<HashSet<String>> {
+    let context = || trf("could not read \"{}\"", &[&path.display()]);
+    let text = fs::read_to_string(path).with_context(context)?;
+    let words =
+        text.lines()
+            .map(|line| line.trim().to_lowercase())
+            .filter(|word| !word.is_empty())
+            .collect();
+    Ok(words)
+}
+
+// Digits, uppercase letters, lowercase letters and anything else.
+fn class(ch: char) -> usize {
+    if ch.is_ascii_digit() {
+        0
+    } else if ch.is_uppercase() {
+        1
+    } else if ch.is_lowercase() {
+        2
+    } else {
+        3
+    }
+}
+
+fn is_palindrome(password: &str) -> bool {
+    password.chars().eq(password.chars().rev())
+}
+
+// The most times a character is repeated in a row.
+fn longest_run(password: &str) -> i64 {
+    let chars = password.chars().collect::<Vec<_>>();
+    chars.chunk_by(|a, b| a == b).map(|run| run.len() as i64).max().unwrap_or(0)
+}
+
+// The longest run of characters each one after or before the last, as in
+// "abcd" or "4321".
+fn longest_sequence(password: &str) -> i64 {
+    let chars = password.chars().map(u32::from).collect::<Vec<_>>();
+    let mut longest = chars.len().min(1);
+    let mut len = 1;
+    let mut step = 0;
+    for pair in chars.windows(2) {
+        let next = i64::from(pair[1]) - i64::from(pair[0]);
+        if next.abs() == 1 && (len == 1 || next == step) {
+            len += 1;
+        } else if next.abs() == 1 {
+            len = 2;
+        } else {
+            len = 1;
+        }
+        step = next;
+        longest = longest.max(len);
+    }
+    longest as i64
+}
+
+// Whether the password contains the word, forwards or backwards, or with a
+// substring length of at least 3, any part of the word that long.
+fn contains_word(password: &str, word: &str, substr_len: i64) -> bool {
+    if word.is_empty() {
+        return false;
+    }
+    let reversed = word.chars().rev().collect::<String>();
+    if password.contains(word) || password.contains(&reversed) {
+        return true;
+    }
+    let Ok(substr_len) = usize::try_from(substr_len) else {
+        return false;
+    };
+    if substr_len < 3 {
+        return false;
+    }
+    let chars = word.chars().collect::<Vec<_>>();
+    chars.windows(substr_len).any(|part| {
+        password.contains(&part.iter().collect::<String>())
+    })
+}
+
+// Cracklib's reasons for rejecting a password, as it words them.
+fn cracklib_check(
+    password: &str,
+    words: &HashSet<String>,
+) -> Option<&'static str> {
+    let chars = password.chars().collect::<Vec<_>>();
+    if chars.iter().all(|ch| ch.is_whitespace()) {
+        return Some("it is all whitespace");
+    }
+    if chars.iter().collect::<HashSet<_>>().len() < MIN_DIFFERENT {
+        return Some("it does not contain enough DIFFERENT characters");
+    }
+    let num_steps =
+        chars.windows(2)
+            .filter(|pair| {
+                (i64::from(u32::from(pair[1])) - i64::from(u32::from(pair[0])))
+                    .abs()
+                    == 1
+            })
+            .count();
+    if num_steps > MAX_STEPS {
+        return Some("it is too simplistic/systematic");
+    }
+    for base in dictionary_bases(password) {
+        if base.chars().count() < MIN_WORD_LEN {
+            continue;
+        }
+        if words.contains(&base) {
+            return Some("it is based on a dictionary word");
+        }
+        if words.contains(&base.chars().rev().collect::<String>()) {
+            return Some("it is based on a (reversed) dictionary word");
+        }
+    }
+    None
+}
+
+// The words the password may be based on: in lowercase, with any digits
+// and symbols at either end removed, and with digits and symbols that stand
+// in for letters turned back into them either before or after that.
+fn dictionary_bases(password: &str) -> [String; 2] {
+    let unleet = |ch: char| {
+        match ch {
+            '0' => 'o',
+            '1' | '!' | '|' => 'l',
+            '3' => 'e',
+            '4' | '@' => 'a',
+            '5' | '$' => 's',
+            '7' | '+' => 't',
+            ch => ch,
+        }
+    };
+    let trim = |text: &str| {
+        text.trim_matches(|ch: char| !ch.is_alphabetic()).to_string()
+    };
+    let lowercase = password.to_lowercase();
+    let unleeted = lowercase.chars().map(unleet).collect::<String>();
+    [trim(&lowercase).chars().map(unleet).collect(), trim(&unleeted)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(conf: &str) -> Settings {
+        Settings::parse(conf).unwrap()
+    }
+
+    fn score(settings: &Settings, password: &str) -> Result<u32, String> {
+        settings.score(password, None, &HashSet::new())
+    }
+
+    #[test]
+    fn score_runs_from_the_minimum_length_to_twice_it() {
+        let settings = Settings::default();
+        assert_eq!(score(&settings, "xq7Lm2Rv"), Ok(0));
+        assert_eq!(score(&settings, "xq7Lm2Rv9Tkb"), Ok(50));
+        assert_eq!(score(&settings, "xq7Lm2Rv9TkbwPzh"), Ok(100));
+        assert_eq!(score(&settings, "xq7Lm2Rv9TkbwPzhfGjs"), Ok(100));
+        let msg = "the password is shorter than {} characters";
+        assert_eq!(score(&settings, "xq7Lm2R"), Err(trf(msg, &[&8])));
+    }
+
+    #[test]
+    fn credits_count_towards_the_length() {
+        // Three digits, of which two are credited.
+        let settings = parse("dcredit = 2\n");
+        assert_eq!(score(&settings, "xq7Lm2Rv9Tkb"), Ok(75));
+        assert_eq!(score(&settings, "xq7Lm2"), Ok(0));
+        let settings = parse("dcredit = -4\n");
+        let msg = "the password contains fewer than {} digits";
+        assert_eq!(score(&settings, "xq7Lm2Rv9Tkb"), Err(trf(msg, &[&4])));
+        let settings = parse("minclass = 4\n");
+        let msg = "the password contains fewer than {} character classes";
+        assert_eq!(score(&settings, "xq7Lm2Rv9Tkb"), Err(trf(msg, &[&4])));
+        assert_eq!(score(&settings, "xq7Lm2Rv9Tk%"), Ok(50));
+    }
+
+    #[test]
+    fn minimum_length_is_at_least_six() {
+        let settings = parse("minlen = 3\n");
+        let msg = "the password is shorter than {} characters";
+        assert_eq!(score(&settings, "xq7Lm"), Err(trf(msg, &[&6])));
+        assert_eq!(score(&settings, "xq7Lm2"), Ok(0));
+    }
+
+    #[test]
+    fn repeats_and_sequences_are_limited() {
+        assert!(score(&Settings::default(), "abcxyzzyxcba").is_err());
+        let settings = parse("maxrepeat = 2\n");
+        assert!(score(&settings, "xq7LLm2Rv9Tk").is_ok());
+        assert!(score(&settings, "xq7LLLm2Rv9T").is_err());
+        let settings = parse("maxsequence = 3\n");
+        assert!(score(&settings, "xq7abcRv9Tkm").is_ok());
+        assert!(score(&settings, "xq7abcdRv9Tk").is_err());
+        assert!(score(&settings, "xq7dcbaRv9Tk").is_err());
+        let settings = parse("maxclassrepeat = 3\n");
+        assert!(score(&settings, "xq7Lm2Rv9Tkb").is_ok());
+        assert!(score(&settings, "xqmwL72Rv9Tk").is_err());
+    }
+
+    #[test]
+    fn user_name_and_bad_words_are_rejected() {
+        let words = HashSet::new();
+        let settings = Settings::default();
+        let user = Some("Nick");
+        assert!(settings.score("xq7nickRv9Tk", user, &words).is_err());
+        assert!(settings.score("xq7KCINRv9Tk", user, &words).is_err());
+        assert!(settings.score("xq7nicRv9Tkb", user, &words).is_ok());
+        let settings = parse("usersubstr = 3\n");
+        assert!(settings.score("xq7nicRv9Tkb", user, &words).is_err());
+        let settings = parse("usercheck = 0\n");
+        assert!(settings.score("xq7nickRv9Tk", user, &words).is_ok());
+        let settings = parse("badwords = Tiger lion\n");
+        assert!(score(&settings, "xq7TIGERv9Tk").is_err());
+        assert!(score(&settings, "xq7noilRv9Tk").is_err());
+        assert!(score(&settings, "xq7tigRv9Tkb").is_ok());
+    }
+
+    #[test]
+    fn dictionary_check_undoes_case_and_substitutions() {
+        let words = HashSet::from(["dragon".to_string()]);
+        let settings = Settings::default();
+        let check = |password| settings.score(password, None, &words);
+        assert!(check("Dr4g0n!!2024").is_err());
+        assert!(check("2024N0G4RD").is_err());
+        assert!(check("Dr4g0nXq7Lm2").is_ok());
+        let settings = parse("dictcheck = 0\n");
+        assert!(settings.score("Dr4g0n!!2024", None, &words).is_ok());
+    }
+
+    #[test]
+    fn cracklib_rejects_simple_passwords() {
+        let words = HashSet::new();
+        let reason = |password| cracklib_check(password, &words);
+        assert_eq!(reason("        "), Some("it is all whitespace"));
+        let msg = "it does not contain enough DIFFERENT characters";
+        assert_eq!(reason("aaaabbbbcccc"), Some(msg));
+        let msg = "it is too simplistic/systematic";
+        assert_eq!(reason("abcdefxq7L"), Some(msg));
+        assert_eq!(reason("xq7Lm2Rv9Tkb"), None);
+    }
+
+    #[test]
+    fn parse_reports_the_line_of_an_invalid_setting() {
+        let conf = "# comment\nminlen = 10 # ten\n\nretry = 3\ndcredit = x\n";
+        let (line_num, _) = Settings::parse(conf).unwrap_err();
+        assert_eq!(line_num, 5);
+        let (line_num, _) = Settings::parse("colour = 1\n").unwrap_err();
+        assert_eq!(line_num, 1);
+        let conf = "# comment\nminlen = 10 # ten\n\nretry = 3\n";
+        assert_eq!(parse(conf).min_length, 10);
+    }
+}
@@ -0,0 +1,75 @@
+#[cfg(target_arch = "x86")]
+use std::arch::x86::_rdseed32_step;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::_rdseed64_step;
+
+use crate::token::EntropyToken;
+use crate::{invalid_spec, PassgenError, Result};
+
+// RDSEED fails transiently when the processor's entropy conditioner is
+// drained, so each word is retried a bounded number of times.
+const RDSEED_RETRIES: usize = 1000;
+
+/// The x86 RDSEED instruction, which returns output straight from the
+/// processor's entropy conditioner.
+#[derive(Clone, Debug)]
+pub struct RdSeed {
+    _private: (),
+}
+
+impl RdSeed {
+    /// Fails unless the processor supports RDSEED.
+    pub fn new() -> Result<Self> {
+        if !std::arch::is_x86_feature_detected!("rdseed") {
+            return Err(invalid_spec("the processor does not support RDSEED"));
+        }
+        Ok(RdSeed { _private: () })
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "rdseed")]
+unsafe fn rdseed_word() -> Option<[u8; 8]> {
+    let mut word = 0;
+    for _ in 0..RDSEED_RETRIES {
+        if _rdseed64_step(&mut word) == 1 {
+            return Some(word.to_le_bytes());
+        }
+        std::hint::spin_loop();
+    }
+    None
+}
+
+#[cfg(target_arch = "x86")]
+#[target_feature(enable = "rdseed")]
+unsafe fn rdseed_word() -> Option<[u8; 4]> {
+    let mut word = 0;
+    for _ in 0..RDSEED_RETRIES {
+        if _rdseed32_step(&mut word) == 1 {
+            return Some(word.to_le_bytes());
+        }
+        std::hint::spin_loop();
+    }
+    None
+}
+
+impl EntropyToken for RdSeed {
+    fn name(&self) -> &str {
+        "rdseed"
+    }
+
+    fn fill(&mut self, dest: &mut [u8]) -> Result<()> {
+        for chunk in dest.chunks_mut(std::mem::size_of::<usize>()) {
+            // SAFETY: RdSeed is only constructed when the processor
+            // supports the instruction.
+            let word = unsafe { rdseed_word() };
+            let word =
+                word.ok_or_else(|| {
+                    let msg = "RDSEED returned no output";
+                    PassgenError::HealthTestFailure(msg.to_string())
+                })?;
+            chunk.copy_from_slice(&word[..chunk.len()]);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::generator::Generator;
+use crate::{invalid_spec, Result};
+
+// Generators are registered for the whole process, so that the code adding
+// them, such as a crate with an organization's own formats, need not be the
+// code looking them up by name, such as a command line.
+
+/// A generator that can be shared between threads, as registered ones are.
+pub type SharedGenerator = Arc<dyn Generator + Send + Sync>;
+
+static REGISTRY: Mutex<BTreeMap<String, SharedGenerator>> =
+    Mutex::new(BTreeMap::new());
+
+/// Registers a generator under the name, failing if the name is empty or
+/// already taken.
+pub fn register_generator(
+    name: &str,
+    generator: Box<dyn Generator + Send + Sync>,
+) -> Result<()> {
+    if name.is_empty() {
+        return Err(invalid_spec("generator name must not be empty"));
+    }
+    // The map is never left half-updated, so a panic elsewhere while the
+    // lock was held does not spoil it.
+    let mut registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+    if registry.contains_key(name) {
+        let msg =
+            format!("a generator named \"{}\" is already registered", name);
+        return Err(invalid_spec(msg));
+    }
+    registry.insert(name.to_string(), Arc::from(generator));
+    Ok(())
+}
+
+/// Returns the generator registered under the name, if any.
+pub fn registered_generator(name: &str) -> Option<SharedGenerator> {
+    let registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+    registry.get(name).cloned()
+}
+
+/// Returns the names of the registered generators in order.
+pub fn registered_generators() -> Vec<String> {
+    let registry = REGISTRY.lock().unwrap_or_else(PoisonError::into_inner);
+    registry.keys().cloned().collect()
+}
@@ -0,0 +1,75 @@
+#[cfg(feature = "bigint")]
+use num_bigint::BigUint;
+#[cfg(feature = "bigint")]
+use num_integer::Integer;
+#[cfg(feature = "bigint")]
+use num_traits::cast::ToPrimitive;
+use rand::RngCore;
+use tracing::debug;
+
+#[cfg(feature = "bigint")]
+use crate::memory::SecretBuf;
+use crate::{rng_failure, Result};
+
+pub(crate) fn random_below(
+    rng: &mut (impl RngCore + ?Sized),
+    bound: usize,
+) -> Result<usize> {
+    let bound = bound as u64;
+    let zone = u64::MAX - (u64::MAX - bound + 1) % bound;
+    loop {
+        let mut buffer = [0u8; 8];
+        rng.try_fill_bytes(&mut buffer).map_err(rng_failure)?;
+        let value = u64::from_le_bytes(buffer);
+        if value <= zone {
+            return Ok((value % bound) as usize);
+        }
+        debug!(bound, "sample out of range, retrying");
+    }
+}
+
+// Fills the password with characters drawn independently by rejection
+// sampling.
+#[cfg(not(feature = "bigint"))]
+pub(crate) fn sample(
+    rng: &mut (impl RngCore + ?Sized),
+    charset: &[u8],
+    password: &mut [u8],
+) -> Result<()> {
+    for byte in password.iter_mut() {
+        *byte = charset[random_below(rng, charset.len())?];
+    }
+    Ok(())
+}
+
+// Draws the whole password as one integer below base^len by rejection
+// sampling, then fills the password with its digits. This consumes close to
+// the minimum number of random bits, at the cost of big-integer arithmetic.
+#[cfg(feature = "bigint")]
+pub(crate) fn sample(
+    rng: &mut (impl RngCore + ?Sized),
+    charset: &[u8],
+    password: &mut [u8],
+) -> Result<()> {
+    let base = BigUint::from(charset.len());
+    let bound = base.pow(password.len() as u32);
+    let num_bits = bound.bits();
+    let num_bytes = num_bits.div_ceil(8) as usize;
+    let mut buffer = SecretBuf::zeroed(num_bytes);
+    let mut value = loop {
+        rng.try_fill_bytes(&mut buffer).map_err(rng_failure)?;
+        buffer[num_bytes - 1] &= 0xff >> (num_bytes as u64 * 8 - num_bits);
+        let value = BigUint::from_bytes_le(&buffer);
+        if value < bound {
+            break value;
+        }
+        debug!(num_bits, "sample out of range, retrying");
+    };
+    for byte in password.iter_mut() {
+        let (quo, rem) = value.div_mod_floor(&base);
+        value = quo;
+        let idx = rem.to_usize().unwrap();
+        *byte = charset[idx];
+    }
+    Ok(())
+}
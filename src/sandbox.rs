@@ -0,0 +1,184 @@
+use std::io;
+
+// Restricts the process to the system calls that generating and printing
+// passwords needs, so that a compromised dependency cannot open files, make
+// network connections or start other programs. Other system calls fail with
+// EPERM rather than killing the process, so that optional features degrade
+// instead of crashing.
+
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64"),
+))]
+mod filter {
+    use libc::sock_filter;
+
+    #[cfg(target_arch = "x86_64")]
+    pub const AUDIT_ARCH: u32 = 0xc000_003e;
+    #[cfg(target_arch = "aarch64")]
+    pub const AUDIT_ARCH: u32 = 0xc000_00b7;
+
+    // Offsets into struct seccomp_data.
+    const NR_OFFSET: u32 = 0;
+    const ARCH_OFFSET: u32 = 4;
+    const ARG0_OFFSET: u32 = 16;
+    const ARG1_OFFSET: u32 = 24;
+
+    const ALLOWED: &[libc::c_long] = &[
+        libc::SYS_read,
+        libc::SYS_write,
+        libc::SYS_writev,
+        libc::SYS_close,
+        libc::SYS_fstat,
+        libc::SYS_statx,
+        libc::SYS_brk,
+        libc::SYS_mmap,
+        libc::SYS_munmap,
+        libc::SYS_mremap,
+        libc::SYS_mprotect,
+        libc::SYS_madvise,
+        libc::SYS_mlock,
+        libc::SYS_munlock,
+        libc::SYS_futex,
+        libc::SYS_getrandom,
+        libc::SYS_clock_gettime,
+        libc::SYS_sched_yield,
+        libc::SYS_rt_sigaction,
+        libc::SYS_rt_sigprocmask,
+        libc::SYS_rt_sigreturn,
+        libc::SYS_sigaltstack,
+        libc::SYS_getpid,
+        // For the peak memory reported by --stats.
+        libc::SYS_getrusage,
+        libc::SYS_gettid,
+        libc::SYS_tgkill,
+        libc::SYS_wait4,
+        libc::SYS_exit,
+        // Made by each new thread.
+        libc::SYS_set_robust_list,
+        libc::SYS_rseq,
+        libc::SYS_exit_group,
+    ];
+
+    // Terminal queries, made when drawing the progress bar. Other ioctls,
+    // such as TIOCSTI, could inject input into the terminal.
+    const ALLOWED_IOCTLS: &[libc::c_ulong] = &[libc::TCGETS, libc::TIOCGWINSZ];
+
+    // The flags glibc and musl start threads with. New processes, which
+    // clone would start with other flags, are denied.
+    const THREAD_CLONE_FLAGS: &[u32] = &[0x003d_0f00, 0x007d_0f00];
+
+    const DENY: u32 = libc::SECCOMP_RET_ERRNO | libc::EPERM as u32;
+    // For calls that libc falls back from when they are missing.
+    const MISSING: u32 = libc::SECCOMP_RET_ERRNO | libc::ENOSYS as u32;
+
+    fn load(offset: u32) -> sock_filter {
+        let code = (libc::BPF_LD | libc::BPF_W | libc::BPF_ABS) as u16;
+        sock_filter { code, jt: 0, jf: 0, k: offset }
+    }
+
+    fn jump_eq(value: u32, jt: u8, jf: u8) -> sock_filter {
+        let code = (libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K) as u16;
+        sock_filter { code, jt, jf, k: value }
+    }
+
+    fn ret(action: u32) -> sock_filter {
+        let code = (libc::BPF_RET | libc::BPF_K) as u16;
+        sock_filter { code, jt: 0, jf: 0, k: action }
+    }
+
+    // Allows the system call only when the argument at the offset has one
+    // of the values.
+    fn push_restricted(
+        program: &mut Vec<sock_filter>,
+        nr: libc::c_long,
+        offset: u32,
+        values: &[u32],
+    ) {
+        let block_len = 2 * values.len() as u8 + 2;
+        program.push(jump_eq(nr as u32, 0, block_len));
+        program.push(load(offset));
+        for &value in values {
+            program.push(jump_eq(value, 0, 1));
+            program.push(ret(libc::SECCOMP_RET_ALLOW));
+        }
+        program.push(ret(DENY));
+    }
+
+    // Builds a filter that also lets the process kill its child, if it has
+    // one.
+    pub fn program(child: Option<u32>) -> Vec<sock_filter> {
+        let mut program = vec![
+            load(ARCH_OFFSET),
+            jump_eq(AUDIT_ARCH, 1, 0),
+            ret(libc::SECCOMP_RET_KILL_PROCESS),
+            load(NR_OFFSET),
+        ];
+        for &nr in ALLOWED {
+            program.push(jump_eq(nr as u32, 0, 1));
+            program.push(ret(libc::SECCOMP_RET_ALLOW));
+        }
+        let requests =
+            ALLOWED_IOCTLS.iter()
+                .map(|&request| request as u32)
+                .collect::<Vec<_>>();
+        push_restricted(&mut program, libc::SYS_ioctl, ARG1_OFFSET, &requests);
+        // clone3 takes its flags in memory the filter cannot read, so libc is
+        // made to use clone instead.
+        program.push(jump_eq(libc::SYS_clone3 as u32, 0, 1));
+        program.push(ret(MISSING));
+        push_restricted(
+            &mut program,
+            libc::SYS_clone,
+            ARG0_OFFSET,
+            THREAD_CLONE_FLAGS,
+        );
+        if let Some(pid) = child {
+            push_restricted(&mut program, libc::SYS_kill, ARG0_OFFSET, &[pid]);
+        }
+        program.push(ret(DENY));
+        program
+    }
+}
+
+/// Installs the system call filter, allowing the process to kill its child
+/// if it has one. The filter cannot be removed, and applies to the rest of
+/// the process's life.
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64"),
+))]
+pub fn install(child: Option<u32>) -> io::Result<()> {
+    let mut program = filter::program(child);
+    let prog =
+        libc::sock_fprog {
+            len: program.len() as u16,
+            filter: program.as_mut_ptr(),
+        };
+    // Required to install a filter without CAP_SYS_ADMIN.
+    if unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let result =
+        unsafe {
+            libc::syscall(
+                libc::SYS_seccomp,
+                libc::SECCOMP_SET_MODE_FILTER,
+                0,
+                &prog as *const libc::sock_fprog,
+            )
+        };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Does nothing, as system call filtering is only supported on Linux.
+#[cfg(not(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64"),
+)))]
+pub fn install(_: Option<u32>) -> io::Result<()> {
+    Ok(())
+}
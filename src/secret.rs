@@ -0,0 +1,25 @@
+use rand::{CryptoRng, RngCore};
+use secrecy::SecretString;
+use std::iter::Map;
+
+use crate::{PasswordSpec, Passwords, Result};
+
+type ToSecret = fn(Result<String>) -> Result<SecretString>;
+
+impl PasswordSpec {
+    /// Generates all the passwords as secrets using the operating system's
+    /// random number generator.
+    ///
+    /// Unlike `String`, a `SecretString` has no `Display` and a redacted
+    /// `Debug`, and is zeroed when dropped.
+    pub fn generate_secret(&self) -> Result<Vec<SecretString>> {
+        self.passwords().secrets().collect()
+    }
+}
+
+impl<'a, R: RngCore + CryptoRng> Passwords<'a, R> {
+    /// Converts this into an iterator over the passwords as secrets.
+    pub fn secrets(self) -> Map<Self, ToSecret> {
+        self.map(|password| password.map(SecretString::from))
+    }
+}
@@ -0,0 +1,294 @@
+use rand::{RngCore, SeedableRng};
+use rand::rngs::OsRng;
+use rand_chacha::ChaCha20Rng;
+use rand_core::CryptoRngCore;
+use std::num::NonZeroUsize;
+use zeroize::Zeroizing;
+
+use crate::charset::typeable_charset;
+use crate::health::HealthTests;
+use crate::{
+    format_charset_spec,
+    parse_charset_spec,
+    EntropySource,
+    PassgenError,
+    Generator,
+    PasswordSpec,
+    Result,
+};
+
+const HEALTH_SAMPLE_BYTES: usize = 1 << 16;
+const ROUND_TRIP_SPECS: &[&str] = &[
+    "a-z",
+    "^a-z",
+    "a-zA-Z0-9",
+    "\\-\\\\",
+    "^^",
+    "_a-z",
+    " -~",
+    "!-/:-@[-`{-~",
+    "^ ",
+];
+const ROUND_TRIP_RANDOM: usize = 1000;
+const KNOWN_ANSWER_SEED: [u8; 32] = [0; 32];
+#[cfg(not(feature = "bigint"))]
+const KNOWN_ANSWERS: &[(&str, usize, &str)] = &[
+    ("a-z", 16, "aficvidrgtsnzhej"),
+    ("0-9", 8, "04103911"),
+    (" -~", 24, "i\\WT+!@=OPRYeue?=YN;p?Up"),
+];
+#[cfg(feature = "bigint")]
+const KNOWN_ANSWERS: &[(&str, usize, &str)] = &[
+    ("a-z", 16, "kxwljcbngyamymsh"),
+    ("0-9", 8, "62485927"),
+    (" -~", 24, "LEr#)cI;D:=\"_#,E;`\\+Q t+"),
+];
+
+/// The outcome of one self-test.
+pub struct Check {
+    /// A short name for the check.
+    pub name: &'static str,
+    /// Whether the check passed.
+    pub result: Result<()>,
+}
+
+/// A chi-squared test of whether characters were drawn uniformly from a
+/// charset.
+#[derive(Clone, Debug)]
+pub struct ChiSquared {
+    /// The statistic, the sum over characters of the squared difference
+    /// between the observed and expected counts over the expected count.
+    pub statistic: f64,
+    /// One less than the number of characters in the charset.
+    pub degrees_of_freedom: usize,
+    /// The probability of a statistic at least this large from a uniform
+    /// draw.
+    pub p_value: f64,
+}
+
+impl ChiSquared {
+    /// Tests counts of the characters of a charset, in its order.
+    pub fn new(counts: &[u64]) -> Self {
+        let total = counts.iter().sum::<u64>() as f64;
+        let expected = total / counts.len() as f64;
+        let statistic =
+            counts.iter()
+                .map(|&count| (count as f64 - expected).powi(2) / expected)
+                .sum::<f64>();
+        let degrees_of_freedom = counts.len().saturating_sub(1);
+        let p_value =
+            upper_gamma(degrees_of_freedom as f64 / 2.0, statistic / 2.0);
+        ChiSquared { statistic, degrees_of_freedom, p_value }
+    }
+}
+
+/// How often each character of a charset was drawn across many passwords,
+/// overall and at each position.
+#[derive(Clone, Debug)]
+pub struct Distribution {
+    /// The characters, in the order of the counts.
+    pub chars: Vec<u8>,
+    /// How often each character was drawn overall.
+    pub counts: Vec<u64>,
+    /// How often each character was drawn at each position, for the
+    /// positions some password reached.
+    pub position_counts: Vec<Vec<u64>>,
+}
+
+impl Distribution {
+    /// Generates this many passwords from the spec and counts their
+    /// characters, which must all be from its charset.
+    pub fn sample(
+        spec: &PasswordSpec,
+        rng: &mut dyn CryptoRngCore,
+        num_passwords: usize,
+    ) -> Result<Self> {
+        let chars = spec.charset().as_bytes().to_vec();
+        let mut index = [usize::MAX; 256];
+        for (idx, &byte) in chars.iter().enumerate() {
+            index[usize::from(byte)] = idx;
+        }
+        let mut counts = vec![0; chars.len()];
+        let mut position_counts = Vec::<Vec<u64>>::new();
+        let mut password = Zeroizing::new(String::new());
+        for _ in 0..num_passwords {
+            password.clear();
+            spec.generator().generate(rng, &mut password)?;
+            for (pos, byte) in password.bytes().enumerate() {
+                let idx = index[usize::from(byte)];
+                if idx == usize::MAX {
+                    let msg =
+                        format!("generated {:?} outside the charset", byte);
+                    return Err(PassgenError::SelfTestFailure(msg));
+                }
+                if pos == position_counts.len() {
+                    position_counts.push(vec![0; chars.len()]);
+                }
+                counts[idx] += 1;
+                position_counts[pos][idx] += 1;
+            }
+        }
+        Ok(Distribution { chars, counts, position_counts })
+    }
+
+    /// Tests the counts of characters overall.
+    pub fn overall(&self) -> ChiSquared {
+        ChiSquared::new(&self.counts)
+    }
+
+    /// Tests the counts of characters at each position.
+    pub fn positions(&self) -> Vec<ChiSquared> {
+        self.position_counts
+            .iter()
+            .map(|counts| ChiSquared::new(counts))
+            .collect()
+    }
+}
+
+/// Runs every self-test, continuing after failures.
+pub fn run() -> Vec<Check> {
+    vec![
+        Check { name: "rng health", result: check_rng_health() },
+        Check { name: "charset round trip", result: check_round_trip() },
+        Check { name: "known answers", result: check_known_answers() },
+    ]
+}
+
+fn check_rng_health() -> Result<()> {
+    let mut buffer = vec![0u8; HEALTH_SAMPLE_BYTES];
+    OsRng.try_fill_bytes(&mut buffer).map_err(PassgenError::RngFailure)?;
+    HealthTests::default().feed(&buffer)
+}
+
+fn check_round_trip() -> Result<()> {
+    fn round_trip(charset: &[u8]) -> Result<()> {
+        let spec = format_charset_spec(charset);
+        let parsed = parse_charset_spec(&spec)?;
+        if parsed.as_bytes() != charset {
+            let msg =
+                format!("charset specification {:?} did not round trip", spec);
+            return Err(PassgenError::SelfTestFailure(msg));
+        }
+        Ok(())
+    }
+    for spec in ROUND_TRIP_SPECS {
+        round_trip(&parse_charset_spec(spec)?)?;
+    }
+    let typeable = typeable_charset();
+    for _ in 0..ROUND_TRIP_RANDOM {
+        let mut mask = [0u8; 16];
+        OsRng.try_fill_bytes(&mut mask).map_err(PassgenError::RngFailure)?;
+        let mask = u128::from_le_bytes(mask);
+        let charset =
+            typeable.iter()
+                .copied()
+                .filter(|&byte| mask & (1 << byte) != 0)
+                .collect::<Vec<_>>();
+        if !charset.is_empty() {
+            round_trip(&charset)?;
+        }
+    }
+    Ok(())
+}
+
+fn check_known_answers() -> Result<()> {
+    let mut rng = ChaCha20Rng::from_seed(KNOWN_ANSWER_SEED);
+    for &(spec, password_len, expected) in KNOWN_ANSWERS {
+        let password_spec =
+            PasswordSpec::builder()
+                .charset(parse_charset_spec(spec)?)
+                .length(NonZeroUsize::new(password_len).unwrap())
+                .build()?;
+        let password =
+            password_spec.passwords_with_rng(&mut rng)
+                .with_source(EntropySource::SeededTest)
+                .next()
+                .unwrap()?;
+        if password != expected {
+            let msg = format!(
+                "expected {:?} from charset {:?} but generated {:?}",
+                expected,
+                spec,
+                password,
+            );
+            return Err(PassgenError::SelfTestFailure(msg));
+        }
+    }
+    Ok(())
+}
+
+// The regularized upper incomplete gamma function Q(a, x), by its series
+// for small x and its continued fraction otherwise, as in Numerical Recipes.
+fn upper_gamma(a: f64, x: f64) -> f64 {
+    const MAX_ITERATIONS: usize = 1000;
+    const EPSILON: f64 = 1e-15;
+    const TINY: f64 = 1e-300;
+    if x <= 0.0 || a <= 0.0 {
+        return 1.0;
+    }
+    let scale = (-x + a * x.ln() - ln_gamma(a)).exp();
+    if x < a + 1.0 {
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut denom = a;
+        for _ in 0..MAX_ITERATIONS {
+            denom += 1.0;
+            term *= x / denom;
+            sum += term;
+            if term.abs() < sum.abs() * EPSILON {
+                break;
+            }
+        }
+        return (1.0 - sum * scale).max(0.0);
+    }
+    let mut b = x + 1.0 - a;
+    let mut c = 1.0 / TINY;
+    let mut d = 1.0 / b;
+    let mut h = d;
+    for idx in 1..MAX_ITERATIONS {
+        let an = -(idx as f64) * (idx as f64 - a);
+        b += 2.0;
+        d = an * d + b;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = b + an / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    scale * h
+}
+
+// The Lanczos approximation, for x of at least 1/2.
+fn ln_gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    let x = x - 1.0;
+    let sum =
+        COEFFICIENTS[1..]
+            .iter()
+            .enumerate()
+            .fold(COEFFICIENTS[0], |sum, (idx, coefficient)| {
+                sum + coefficient / (x + idx as f64 + 1.0)
+            });
+    let t = x + G + 0.5;
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t
+        + sum.ln()
+}
@@ -0,0 +1,260 @@
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::time::Duration;
+
+use crate::{
+    format_charset_spec,
+    parse_charset_spec,
+    Charset,
+    CharsetGenerator,
+    PassphraseGenerator,
+    PasswordSpec,
+    PatternGenerator,
+    PinGenerator,
+    Policy,
+};
+
+// Character sets are written as charset specifications so that stored specs
+// stay readable and editable by hand. Deserialization goes through the same
+// validation as the constructors.
+
+impl Serialize for Charset {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        format_charset_spec(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Charset {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let charset_spec = String::deserialize(deserializer)?;
+        parse_charset_spec(&charset_spec).map_err(D::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawPasswordSpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    charset: Option<Charset>,
+    #[serde(default, skip_serializing)]
+    length: Option<NonZeroUsize>,
+    #[serde(default)]
+    min_length: Option<NonZeroUsize>,
+    #[serde(default)]
+    max_length: Option<NonZeroUsize>,
+    #[serde(default)]
+    count: Option<NonZeroUsize>,
+    #[serde(default)]
+    policy: Policy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_attempts: Option<NonZeroU64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timeout_secs: Option<f64>,
+}
+
+// Timeouts are written as seconds, as they are given on the command line.
+fn timeout_from_secs<E: serde::de::Error>(secs: f64) -> Result<Duration, E> {
+    Duration::try_from_secs_f64(secs)
+        .ok()
+        .filter(|timeout| !timeout.is_zero())
+        .ok_or_else(|| E::custom("timeout must be a positive number"))
+}
+
+impl Serialize for PasswordSpec {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        RawPasswordSpec {
+            charset: Some(self.charset().clone()),
+            length: None,
+            min_length: Some(self.min_length()),
+            max_length: Some(self.max_length()),
+            count: Some(self.count()),
+            policy: self.policy().clone(),
+            max_attempts: self.max_attempts(),
+            timeout_secs: self.timeout().map(|timeout| timeout.as_secs_f64()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PasswordSpec {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = RawPasswordSpec::deserialize(deserializer)?;
+        let mut builder = PasswordSpec::builder().policy(raw.policy);
+        if let Some(charset) = raw.charset {
+            builder = builder.charset(charset);
+        }
+        if let Some(password_len) = raw.length {
+            builder = builder.length(password_len);
+        }
+        if let Some(min_len) = raw.min_length {
+            builder = builder.min_length(min_len);
+        }
+        if let Some(max_len) = raw.max_length {
+            builder = builder.max_length(max_len);
+        }
+        if let Some(num_passwords) = raw.count {
+            builder = builder.count(num_passwords);
+        }
+        if let Some(max_attempts) = raw.max_attempts {
+            builder = builder.max_attempts(max_attempts);
+        }
+        if let Some(secs) = raw.timeout_secs {
+            builder = builder.timeout(timeout_from_secs(secs)?);
+        }
+        builder.build().map_err(D::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawCharsetGenerator {
+    charset: Charset,
+    min_length: NonZeroUsize,
+    max_length: NonZeroUsize,
+    #[serde(default)]
+    policy: Policy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_attempts: Option<NonZeroU64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timeout_secs: Option<f64>,
+}
+
+impl Serialize for CharsetGenerator {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        RawCharsetGenerator {
+            charset: self.charset().clone(),
+            min_length: self.min_length(),
+            max_length: self.max_length(),
+            policy: self.policy().clone(),
+            max_attempts: self.max_attempts(),
+            timeout_secs: self.timeout().map(|timeout| timeout.as_secs_f64()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CharsetGenerator {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let RawCharsetGenerator {
+            charset,
+            min_length,
+            max_length,
+            policy,
+            max_attempts,
+            timeout_secs,
+        } = RawCharsetGenerator::deserialize(deserializer)?;
+        let mut generator =
+            CharsetGenerator::new(charset, min_length, max_length, policy)
+                .map_err(D::Error::custom)?;
+        if let Some(max_attempts) = max_attempts {
+            generator = generator.with_max_attempts(max_attempts);
+        }
+        if let Some(secs) = timeout_secs {
+            generator = generator.with_timeout(timeout_from_secs(secs)?);
+        }
+        Ok(generator)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawPassphraseGenerator {
+    words: Vec<String>,
+    num_words: NonZeroUsize,
+    separator: String,
+}
+
+impl Serialize for PassphraseGenerator {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        RawPassphraseGenerator {
+            words: self.words().to_vec(),
+            num_words: self.num_words(),
+            separator: self.separator().to_string(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PassphraseGenerator {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = RawPassphraseGenerator::deserialize(deserializer)?;
+        PassphraseGenerator::new(raw.words, raw.num_words, raw.separator)
+            .map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for PatternGenerator {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.pattern().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PatternGenerator {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let pattern = String::deserialize(deserializer)?;
+        PatternGenerator::new(pattern).map_err(D::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawPinGenerator {
+    length: NonZeroUsize,
+    #[serde(default)]
+    reject_weak: bool,
+}
+
+impl Serialize for PinGenerator {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let raw =
+            RawPinGenerator {
+                length: self.length(),
+                reject_weak: self.rejects_weak(),
+            };
+        raw.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PinGenerator {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = RawPinGenerator::deserialize(deserializer)?;
+        Ok(PinGenerator::new(raw.length).reject_weak(raw.reject_weak))
+    }
+}
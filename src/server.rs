@@ -0,0 +1,460 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::debug;
+use zeroize::Zeroizing;
+
+use crate::i18n::trf;
+use crate::limits::{Limit, Quota, QuotaStatus, RateLimiter};
+
+// A deliberately small HTTP/1.1 server for scripts on the same host. Each
+// connection carries one request, served in turn, and is closed after the
+// response. Requests must carry one of the tokens as a bearer token, and
+// are limited in rate by client address whether or not they do, so that no
+// token can be guessed quickly. What each token's client generates counts
+// against its own quota, so that a client whose token is stolen cannot
+// drain more than that.
+
+const MAX_HEAD_LEN: usize = 8 * 1024;
+const MAX_BODY_LEN: usize = 64 * 1024;
+// How long a client may take to send its whole request, however slowly it
+// trickles in, since others wait meanwhile.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Clients whose buckets are full are forgotten beyond this many.
+const MAX_CLIENTS: usize = 1024;
+
+/// The requests each client may make by default.
+pub const DEFAULT_RATE: Limit =
+    Limit { count: 60, window: Duration::from_secs(60) };
+
+// A bearer token and the quota of passwords generated with it.
+struct Client {
+    name: String,
+    token: Zeroizing<String>,
+    quota: Quota,
+    requests: u64,
+}
+
+// Reads the bearer tokens from the file, which must not be readable by
+// other users, one to a line after any name and a space, each with the
+// quota.
+fn read_tokens(path: &Path, quota: Option<Limit>) -> Result<Vec<Client>> {
+    let context = || trf("could not read \"{}\"", &[&path.display()]);
+    let metadata = fs::metadata(path).with_context(context)?;
+    #[cfg(unix)]
+    if std::os::unix::fs::PermissionsExt::mode(&metadata.permissions())
+        & 0o077
+        != 0
+    {
+        let path = path.display();
+        let msg = trf("\"{}\" must be readable only by its owner", &[&path]);
+        return Err(anyhow!(msg));
+    }
+    #[cfg(not(unix))]
+    let _ = metadata;
+    let text =
+        Zeroizing::new(fs::read_to_string(path).with_context(context)?);
+    let mut clients = Vec::<Client>::new();
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        // Unnamed tokens are numbered.
+        let (name, token) =
+            match line.rsplit_once(' ') {
+                Some((name, token)) => (name.trim().to_string(), token),
+                None => ((clients.len() + 1).to_string(), line),
+            };
+        if clients.iter().any(|client| client.name == name) {
+            let msg = trf(
+                "token name repeated in \"{}\": \"{}\"",
+                &[&path.display(), &name],
+            );
+            return Err(anyhow!(msg));
+        }
+        clients.push(Client {
+            name,
+            token: Zeroizing::new(token.to_string()),
+            quota: Quota::new(quota),
+            requests: 0,
+        });
+    }
+    if clients.is_empty() {
+        let msg = trf("the token in \"{}\" is empty", &[&path.display()]);
+        return Err(anyhow!(msg));
+    }
+    Ok(clients)
+}
+
+// What the server has counted, for GET /status.
+#[derive(Default, Serialize)]
+struct Counters {
+    requests: u64,
+    rate_limited: u64,
+    unauthorized: u64,
+}
+
+#[derive(Serialize)]
+struct Status<'a> {
+    #[serde(flatten)]
+    counters: &'a Counters,
+    // The client addresses with requests counted against their rate.
+    clients: usize,
+    tokens: Vec<TokenStatus<'a>>,
+}
+
+#[derive(Serialize)]
+struct TokenStatus<'a> {
+    name: &'a str,
+    requests: u64,
+    #[serde(flatten)]
+    quota: QuotaStatus,
+}
+
+/// Serves POST /generate forever, answering each request body with the
+/// status and JSON the handler gives for it, given the quota of the token
+/// the request carries, and GET /status with what has been counted.
+pub fn serve<H>(
+    listener: &TcpListener,
+    clients: &mut Clients,
+    mut handler: H,
+) -> Result<()>
+where
+    H: FnMut(&[u8], &mut Quota) -> Result<(u16, Zeroizing<String>)>,
+{
+    for stream in listener.incoming() {
+        let mut stream =
+            match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    debug!(%err, "could not accept a connection");
+                    continue;
+                },
+            };
+        let status =
+            match respond(&mut stream, clients, &mut handler) {
+                Ok(status) => status,
+                Err(err) => {
+                    debug!(%err, "could not serve a request");
+                    continue;
+                },
+            };
+        debug!(status, "served a request");
+    }
+    Ok(())
+}
+
+// Serves the request on the connection, returning the response's status.
+fn respond<H>(
+    stream: &mut TcpStream,
+    clients: &mut Clients,
+    handler: &mut H,
+) -> Result<u16>
+where
+    H: FnMut(&[u8], &mut Quota) -> Result<(u16, Zeroizing<String>)>,
+{
+    let deadline = Instant::now() + READ_TIMEOUT;
+    stream.set_write_timeout(Some(READ_TIMEOUT))?;
+    if let Some(wait) = clients.admit(stream.peer_addr()?.ip()) {
+        let retry_after = format!("Retry-After: {}\r\n", wait.as_secs() + 1);
+        return write_error(stream, 429, &retry_after);
+    }
+    let Some(request) = Request::read(stream, deadline)? else {
+        return write_error(stream, 400, "");
+    };
+    let method =
+        match request.path.as_str() {
+            "/generate" => "POST",
+            "/status" => "GET",
+            _ => return write_error(stream, 404, ""),
+        };
+    if request.method != method {
+        return write_error(stream, 405, &format!("Allow: {}\r\n", method));
+    }
+    let bearer = request.bearer.as_deref().map(|bearer| bearer.as_str());
+    let Some(quota) = clients.authorize(bearer) else {
+        return write_error(stream, 401, "WWW-Authenticate: Bearer\r\n");
+    };
+    if method == "GET" {
+        let json = clients.status()?;
+        write_response(stream, 200, "", &json)?;
+        return Ok(200);
+    }
+    let Some(len) = request.content_len else {
+        return write_error(stream, 411, "");
+    };
+    if len > MAX_BODY_LEN {
+        return write_error(stream, 413, "");
+    }
+    let mut body = Zeroizing::new(vec![0; len]);
+    let mut num_read = request.rest.len().min(len);
+    body[..num_read].copy_from_slice(&request.rest[..num_read]);
+    while num_read < len {
+        match read_by(stream, &mut body[num_read..], deadline)? {
+            0 => {
+                let eof = io::Error::from(io::ErrorKind::UnexpectedEof);
+                return Err(eof.into());
+            },
+            num_body_read => num_read += num_body_read,
+        }
+    }
+    let (status, json) =
+        match handler(&body, clients.quota(quota)) {
+            Ok(response) => response,
+            Err(err) => {
+                debug!(%err, "could not handle a request");
+                return write_error(stream, 500, "");
+            },
+        };
+    write_response(stream, status, "", &json)?;
+    Ok(status)
+}
+
+/// The clients of a server: the tokens they must carry, with their quotas,
+/// and the rate of requests from each address.
+pub struct Clients {
+    tokens: Vec<Client>,
+    rate: Limit,
+    // The rate of requests from each client address.
+    limiters: HashMap<IpAddr, RateLimiter>,
+    counters: Counters,
+}
+
+impl Clients {
+    /// Reads the bearer tokens from the file, which must not be readable by
+    /// other users, one to a line after any name and a space, each with
+    /// the quota, for clients limited to the rate.
+    pub fn read(
+        path: &Path,
+        rate: Limit,
+        quota: Option<Limit>,
+    ) -> Result<Self> {
+        Ok(Clients {
+            tokens: read_tokens(path, quota)?,
+            rate,
+            limiters: HashMap::new(),
+            counters: Counters::default(),
+        })
+    }
+
+    /// Counts a request from the address, returning how long until it may
+    /// be made if it is over the rate.
+    pub fn admit(&mut self, addr: IpAddr) -> Option<Duration> {
+        self.counters.requests += 1;
+        if self.limiters.len() >= MAX_CLIENTS {
+            self.limiters.retain(|_, limiter| !limiter.is_full());
+        }
+        let rate = self.rate;
+        let wait =
+            self.limiters
+                .entry(addr)
+                .or_insert_with(|| RateLimiter::new(rate))
+                .take();
+        if wait.is_some() {
+            self.counters.rate_limited += 1;
+        }
+        wait
+    }
+
+    /// Returns the token the bearer is, to find its quota with, comparing it
+    /// with every token so that the time taken does not tell which.
+    pub fn authorize(&mut self, bearer: Option<&str>) -> Option<usize> {
+        let found =
+            bearer.and_then(|bearer| {
+                self.tokens.iter()
+                    .enumerate()
+                    .map(|(idx, client)| {
+                        (idx, equal(bearer.as_bytes(), client.token.as_bytes()))
+                    })
+                    .fold(None, |found, (idx, equal)| {
+                        if equal { Some(idx) } else { found }
+                    })
+            });
+        match found {
+            Some(idx) => self.tokens[idx].requests += 1,
+            None => self.counters.unauthorized += 1,
+        }
+        found
+    }
+
+    /// Returns the quota of the token `authorize` found.
+    pub fn quota(&mut self, token: usize) -> &mut Quota {
+        &mut self.tokens[token].quota
+    }
+
+    /// Returns what has been counted, as JSON.
+    pub fn status(&self) -> Result<String> {
+        let tokens =
+            self.tokens.iter()
+                .map(|client| TokenStatus {
+                    name: &client.name,
+                    requests: client.requests,
+                    quota: client.quota.status(),
+                })
+                .collect();
+        let status = Status {
+            counters: &self.counters,
+            clients: self.limiters.len(),
+            tokens,
+        };
+        Ok(serde_json::to_string(&status)?)
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    bearer: Option<Zeroizing<String>>,
+    content_len: Option<usize>,
+    // What was read past the head, the start of the body.
+    rest: Zeroizing<Vec<u8>>,
+}
+
+impl Request {
+    // Reads the request's head, or nothing if it is malformed or too long.
+    fn read(
+        stream: &mut TcpStream,
+        deadline: Instant,
+    ) -> io::Result<Option<Self>> {
+        let mut chunk = Zeroizing::new([0; 1024]);
+        // Never reallocated, so that no copies of the token are left.
+        let mut buf =
+            Zeroizing::new(Vec::with_capacity(MAX_HEAD_LEN + chunk.len()));
+        let head_len =
+            loop {
+                if let Some(pos) = find(&buf, b"\r\n\r\n") {
+                    break pos;
+                }
+                if buf.len() > MAX_HEAD_LEN {
+                    return Ok(None);
+                }
+                let num_read = read_by(stream, &mut chunk[..], deadline)?;
+                if num_read == 0 {
+                    return Ok(None);
+                }
+                buf.extend_from_slice(&chunk[..num_read]);
+            };
+        let Ok(head) = std::str::from_utf8(&buf[..head_len]) else {
+            return Ok(None);
+        };
+        let mut lines = head.split("\r\n");
+        let mut request_line = lines.next().unwrap_or("").split(' ');
+        let (Some(method), Some(path), Some(version), None) = (
+            request_line.next(),
+            request_line.next(),
+            request_line.next(),
+            request_line.next(),
+        ) else {
+            return Ok(None);
+        };
+        if !version.starts_with("HTTP/1.") {
+            return Ok(None);
+        }
+        let mut request = Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            bearer: None,
+            content_len: None,
+            rest: Zeroizing::new(buf[head_len + 4..].to_vec()),
+        };
+        for line in lines {
+            let Some((name, value)) = line.split_once(':') else {
+                return Ok(None);
+            };
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                let Ok(len) = value.parse() else {
+                    return Ok(None);
+                };
+                request.content_len = Some(len);
+            } else if name.eq_ignore_ascii_case("authorization") {
+                let bearer =
+                    value.split_once(' ')
+                        .filter(|(scheme, _)| {
+                            scheme.eq_ignore_ascii_case("bearer")
+                        })
+                        .map(|(_, bearer)| bearer.trim().to_string());
+                request.bearer = bearer.map(Zeroizing::new);
+            } else if name.eq_ignore_ascii_case("transfer-encoding") {
+                // Chunked bodies are not supported.
+                return Ok(None);
+            }
+        }
+        Ok(Some(request))
+    }
+}
+
+// Reads what has arrived on the connection, failing if the deadline passes
+// first.
+fn read_by(
+    stream: &mut TcpStream,
+    buf: &mut [u8],
+    deadline: Instant,
+) -> io::Result<usize> {
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    if remaining.is_zero() {
+        return Err(io::ErrorKind::TimedOut.into());
+    }
+    stream.set_read_timeout(Some(remaining))?;
+    stream.read(buf)
+}
+
+fn write_error(
+    stream: &mut TcpStream,
+    status: u16,
+    headers: &str,
+) -> Result<u16> {
+    let json = format!("{{\"error\":\"{}\"}}", reason(status).to_lowercase());
+    write_response(stream, status, headers, &json)?;
+    Ok(status)
+}
+
+fn write_response(
+    stream: &mut TcpStream,
+    status: u16,
+    headers: &str,
+    json: &str,
+) -> io::Result<()> {
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nCache-Control: no-store\r\n\
+         Connection: close\r\n{}\r\n",
+        status,
+        reason(status),
+        json.len() + 1,
+        headers,
+    );
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(json.as_bytes())?;
+    stream.write_all(b"\n")?;
+    stream.flush()
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        413 => "Content Too Large",
+        429 => "Too Many Requests",
+        _ => "Internal Server Error",
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+// Compares in time independent of where the bytes differ.
+fn equal(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
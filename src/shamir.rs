@@ -0,0 +1,271 @@
+use rand::{CryptoRng, RngCore};
+use std::fmt;
+use std::str::FromStr;
+use zeroize::Zeroize;
+
+use crate::memory::SecretBuf;
+use crate::{invalid_spec, PassgenError, Result};
+
+// Shamir's secret sharing over GF(256), byte by byte. Each byte of the
+// secret is the constant term of a random polynomial of degree one less than
+// the threshold, and share x holds the polynomials' values at x.
+
+// Multiplies in GF(256) modulo the AES polynomial, without branching on the
+// operands.
+fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    for _ in 0..8 {
+        product ^= a & (b & 1).wrapping_neg();
+        let carry = (a >> 7).wrapping_neg();
+        a = (a << 1) ^ (carry & 0x1b);
+        b >>= 1;
+    }
+    product
+}
+
+// Inverts a non-zero element as a^254.
+fn inv(a: u8) -> u8 {
+    let mut result = 1;
+    let mut power = a;
+    for _ in 0..7 {
+        power = mul(power, power);
+        result = mul(result, power);
+    }
+    result
+}
+
+/// One share of a secret split by [`split_secret`], printed as the
+/// threshold, the share's index and its bytes in hexadecimal, separated by
+/// hyphens.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Share {
+    threshold: u8,
+    index: u8,
+    bytes: Vec<u8>,
+}
+
+impl Share {
+    /// Returns the number of shares needed to recover the secret.
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// Returns the index of this share, from one.
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+}
+
+impl fmt::Debug for Share {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Share")
+            .field("threshold", &self.threshold)
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for Share {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}-", self.threshold, self.index)?;
+        for byte in &self.bytes {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Share {
+    type Err = PassgenError;
+
+    fn from_str(share_str: &str) -> Result<Self> {
+        let err = || invalid_spec(format!("invalid share: {:?}", share_str));
+        let mut parts = share_str.trim().splitn(3, '-');
+        let mut next = || parts.next().ok_or_else(err);
+        let threshold = next()?.parse::<u8>().map_err(|_| err())?;
+        let index = next()?.parse::<u8>().map_err(|_| err())?;
+        let hex = next()?;
+        if threshold == 0 || index == 0 {
+            return Err(err());
+        }
+        if hex.is_empty() || hex.len() % 2 != 0 || !hex.is_ascii() {
+            return Err(err());
+        }
+        let bytes =
+            (0..hex.len())
+                .step_by(2)
+                .map(|idx| u8::from_str_radix(&hex[idx..idx + 2], 16))
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|_| err())?;
+        Ok(Share { threshold, index, bytes })
+    }
+}
+
+impl Drop for Share {
+    fn drop(&mut self) {
+        self.bytes.zeroize();
+    }
+}
+
+/// Splits a secret into shares, any `threshold` of which recover it and
+/// fewer of which reveal nothing about it.
+pub fn split_secret<R: RngCore + CryptoRng>(
+    secret: &[u8],
+    threshold: u8,
+    num_shares: u8,
+    rng: &mut R,
+) -> Result<Vec<Share>> {
+    if threshold == 0 || threshold > num_shares {
+        let msg = "share threshold must be between 1 and the number of shares";
+        return Err(invalid_spec(msg));
+    }
+    if secret.is_empty() {
+        return Err(invalid_spec("secret is empty"));
+    }
+    let degree = threshold as usize - 1;
+    let mut coeffs = SecretBuf::zeroed(degree * secret.len());
+    rng.try_fill_bytes(&mut coeffs).map_err(PassgenError::RngFailure)?;
+    let shares =
+        (1..=num_shares)
+            .map(|index| {
+                let bytes =
+                    secret.iter()
+                        .enumerate()
+                        .map(|(pos, &byte)| {
+                            let coeffs = &coeffs[pos * degree..][..degree];
+                            let higher =
+                                coeffs.iter().rev().fold(0, |acc, &coeff| {
+                                    mul(acc, index) ^ coeff
+                                });
+                            mul(higher, index) ^ byte
+                        })
+                        .collect();
+                Share { threshold, index, bytes }
+            })
+            .collect();
+    Ok(shares)
+}
+
+/// Recovers a secret from at least as many shares as its threshold, failing
+/// if the shares do not belong together. Shares beyond the threshold must
+/// agree with the secret the others recover.
+pub fn recover_secret(shares: &[Share]) -> Result<Vec<u8>> {
+    let first = shares.first().ok_or_else(|| invalid_spec("no shares"))?;
+    let threshold = first.threshold as usize;
+    for (pos, share) in shares.iter().enumerate() {
+        if share.threshold != first.threshold {
+            return Err(invalid_spec("shares have different thresholds"));
+        }
+        if share.bytes.len() != first.bytes.len() {
+            return Err(invalid_spec("shares have different lengths"));
+        }
+        if shares[..pos].iter().any(|other| other.index == share.index) {
+            let msg = format!("share {} is repeated", share.index);
+            return Err(invalid_spec(msg));
+        }
+    }
+    if shares.len() < threshold {
+        let msg = format!(
+            "{} shares are needed but only {} were given",
+            threshold,
+            shares.len(),
+        );
+        return Err(invalid_spec(msg));
+    }
+    let (shares, extra) = shares.split_at(threshold);
+    for share in extra {
+        let mut expected = interpolate(shares, share.index);
+        let differences =
+            expected.iter()
+                .zip(&share.bytes)
+                .fold(0, |acc, (byte, other)| acc | (byte ^ other));
+        expected.zeroize();
+        if differences != 0 {
+            let msg =
+                format!("share {} does not agree with the others", share.index);
+            return Err(invalid_spec(msg));
+        }
+    }
+    Ok(interpolate(shares, 0))
+}
+
+// Evaluates the polynomials through the shares at x.
+fn interpolate(shares: &[Share], x: u8) -> Vec<u8> {
+    // Lagrange basis polynomials evaluated at x. Subtraction in GF(256) is
+    // addition, which is exclusive or.
+    let weights =
+        shares.iter()
+            .map(|share| {
+                shares.iter()
+                    .filter(|other| other.index != share.index)
+                    .fold(1, |acc, other| {
+                        let denom = other.index ^ share.index;
+                        mul(acc, mul(other.index ^ x, inv(denom)))
+                    })
+            })
+            .collect::<Vec<_>>();
+    (0..shares[0].bytes.len())
+        .map(|pos| {
+            shares.iter()
+                .zip(&weights)
+                .fold(0, |acc, (share, &weight)| {
+                    acc ^ mul(share.bytes[pos], weight)
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::ChaCha20Rng;
+    use rand::SeedableRng;
+
+    const SECRET: &[u8] = b"correct horse battery staple";
+
+    fn split(threshold: u8, num_shares: u8) -> Vec<Share> {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        split_secret(SECRET, threshold, num_shares, &mut rng).unwrap()
+    }
+
+    #[test]
+    fn any_threshold_of_shares_recovers_the_secret() {
+        let shares = split(3, 5);
+        for a in 0..5 {
+            for b in a + 1..5 {
+                for c in b + 1..5 {
+                    let subset = [&shares[a], &shares[b], &shares[c]];
+                    let subset = subset.map(Share::clone);
+                    assert_eq!(recover_secret(&subset).unwrap(), SECRET);
+                }
+            }
+        }
+        assert_eq!(recover_secret(&shares).unwrap(), SECRET);
+        assert_eq!(recover_secret(&split(1, 1)).unwrap(), SECRET);
+    }
+
+    #[test]
+    fn printed_shares_parse() {
+        for share in split(2, 3) {
+            assert!(share.to_string().parse::<Share>().unwrap() == share);
+        }
+    }
+
+    #[test]
+    fn too_few_shares_fail() {
+        assert!(recover_secret(&split(3, 5)[..2]).is_err());
+        assert!(recover_secret(&[]).is_err());
+    }
+
+    #[test]
+    fn extra_shares_that_disagree_fail() {
+        let mut shares = split(2, 4);
+        shares[3].bytes[0] ^= 1;
+        assert!(recover_secret(&shares[..3]).is_ok());
+        assert!(recover_secret(&shares).is_err());
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        let other = split_secret(SECRET, 2, 4, &mut rng).unwrap();
+        shares[3] = other[3].clone();
+        assert!(recover_secret(&shares).is_err());
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use zeroize::Zeroizing;
+
+// Near-duplicates are found as in PassJoin: each string is cut into one
+// more segment than the distance, so that a string within the distance of
+// it must contain one of the segments unedited, shifted by no more than
+// the distance. Only strings sharing a segment that way are compared, with
+// an edit distance computed only along the diagonals within the distance
+// of the main one and given up as soon as every cell of a row exceeds it.
+
+/// Returns the pairs of indices, in order, of the strings that differ but
+/// are within the Levenshtein distance of each other.
+pub fn similar_pairs(
+    strings: &[&str],
+    max_distance: usize,
+) -> Vec<(usize, usize)> {
+    let chars =
+        strings.iter()
+            .map(|string| Zeroizing::new(string.chars().collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+    // The strings with each segment, by their length and the segment's
+    // number.
+    let mut index = HashMap::<(usize, usize, &[char]), Vec<usize>>::new();
+    for (idx, string) in chars.iter().enumerate() {
+        for (num, range) in segments(string.len(), max_distance).enumerate() {
+            let key = (string.len(), num, &string[range]);
+            index.entry(key).or_default().push(idx);
+        }
+    }
+    let mut pairs = Vec::new();
+    let mut candidates = Vec::<usize>::new();
+    for (probe, string) in chars.iter().enumerate() {
+        candidates.clear();
+        let min_len = string.len().saturating_sub(max_distance);
+        for len in min_len..=string.len() + max_distance {
+            for (num, range) in segments(len, max_distance).enumerate() {
+                let Some(last_start) = string.len().checked_sub(range.len())
+                else {
+                    continue;
+                };
+                let first_start = range.start.saturating_sub(max_distance);
+                let last_start = last_start.min(range.start + max_distance);
+                for start in first_start..=last_start {
+                    let part = &string[start..start + range.len()];
+                    if let Some(found) = index.get(&(len, num, part)) {
+                        candidates.extend(
+                            found.iter().filter(|&&other| other < probe),
+                        );
+                    }
+                }
+            }
+        }
+        candidates.sort_unstable();
+        candidates.dedup();
+        for &other in &candidates {
+            let (short, long) =
+                if chars[other].len() <= string.len() {
+                    (&chars[other], string)
+                } else {
+                    (string, &chars[other])
+                };
+            if short != long && within_distance(short, long, max_distance) {
+                pairs.push((other, probe));
+            }
+        }
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+// Cuts a string of this length into one more segment than the distance,
+// as evenly as possible.
+fn segments(
+    len: usize,
+    max_distance: usize,
+) -> impl Iterator<Item = Range<usize>> {
+    let num_segments = max_distance + 1;
+    (0..num_segments).map(move |num| {
+        num * len / num_segments..(num + 1) * len / num_segments
+    })
+}
+
+// Whether the Levenshtein distance between the strings, the first no longer
+// than the second, is at most the maximum.
+fn within_distance(short: &[char], long: &[char], max: usize) -> bool {
+    if long.len() - short.len() > max {
+        return false;
+    }
+    // Any distance beyond the maximum is as good as another.
+    let beyond = max + 1;
+    let mut prev = vec![beyond; long.len() + 1];
+    let mut row = vec![beyond; long.len() + 1];
+    for (idx, cell) in prev.iter_mut().enumerate().take(max + 1) {
+        *cell = idx;
+    }
+    for idx in 1..=short.len() {
+        let start = idx.saturating_sub(max).max(1);
+        let end = (idx + max).min(long.len());
+        // The cell left of the band holds a value from two rows before.
+        row[start - 1] = if idx <= max { idx } else { beyond };
+        let mut row_min = row[start - 1];
+        for col in start..=end {
+            let substitution =
+                prev[col - 1] + usize::from(short[idx - 1] != long[col - 1]);
+            let cell =
+                substitution
+                    .min(prev[col] + 1)
+                    .min(row[col - 1] + 1)
+                    .min(beyond);
+            row[col] = cell;
+            row_min = row_min.min(cell);
+        }
+        if row_min > max {
+            return false;
+        }
+        std::mem::swap(&mut prev, &mut row);
+    }
+    prev[long.len()] <= max
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    // The Levenshtein distance, with the whole table.
+    fn distance(a: &[char], b: &[char]) -> usize {
+        let mut prev = (0..=b.len()).collect::<Vec<_>>();
+        for (idx, &ca) in a.iter().enumerate() {
+            let mut row = vec![idx + 1];
+            for (col, &cb) in b.iter().enumerate() {
+                let cell =
+                    (prev[col] + usize::from(ca != cb))
+                        .min(prev[col + 1] + 1)
+                        .min(row[col] + 1);
+                row.push(cell);
+            }
+            prev = row;
+        }
+        prev[b.len()]
+    }
+
+    // Short strings over a small alphabet, so that many are close.
+    fn random_strings(rng: &mut ChaCha20Rng, count: usize) -> Vec<String> {
+        (0..count)
+            .map(|_| {
+                let len = rng.gen_range(0..8);
+                (0..len).map(|_| rng.gen_range('a'..='c')).collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn banded_distance_agrees_with_the_whole_table() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let strings = random_strings(&mut rng, 60);
+        for a in &strings {
+            for b in &strings {
+                let a = a.chars().collect::<Vec<_>>();
+                let b = b.chars().collect::<Vec<_>>();
+                let (short, long) =
+                    if a.len() <= b.len() { (&a, &b) } else { (&b, &a) };
+                let actual = distance(short, long);
+                for max in 0..4 {
+                    let within = within_distance(short, long, max);
+                    assert_eq!(within, actual <= max, "{:?} {:?}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn similar_pairs_agrees_with_comparing_every_pair() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let strings = random_strings(&mut rng, 80);
+        let strings = strings.iter().map(String::as_str).collect::<Vec<_>>();
+        for max in 0..4 {
+            let mut expected = Vec::new();
+            for probe in 0..strings.len() {
+                for other in 0..probe {
+                    let a = strings[other].chars().collect::<Vec<_>>();
+                    let b = strings[probe].chars().collect::<Vec<_>>();
+                    if a != b && distance(&a, &b) <= max {
+                        expected.push((other, probe));
+                    }
+                }
+            }
+            expected.sort_unstable();
+            assert_eq!(similar_pairs(&strings, max), expected);
+        }
+    }
+
+    #[test]
+    fn identical_strings_are_not_pairs() {
+        let strings = ["hunter2", "hunter2", "hunter3", "Hunter22"];
+        assert_eq!(similar_pairs(&strings, 0), []);
+        assert_eq!(similar_pairs(&strings, 1), [(0, 2), (1, 2)]);
+        let pairs = [(0, 2), (0, 3), (1, 2), (1, 3)];
+        assert_eq!(similar_pairs(&strings, 2), pairs);
+    }
+
+    #[test]
+    fn distance_counts_characters_not_bytes() {
+        assert_eq!(similar_pairs(&["äöü", "aöü"], 1), [(0, 1)]);
+        assert_eq!(similar_pairs(&["äöü", "aou"], 2), []);
+    }
+}
@@ -0,0 +1,58 @@
+use futures_core::Stream;
+use rand::rngs::OsRng;
+use secrecy::SecretString;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc;
+use tokio::task;
+
+use crate::{PasswordSpec, Result};
+
+// Enough to keep the blocking task busy without generating far ahead of a
+// slow consumer.
+const CHANNEL_CAPACITY: usize = 64;
+
+/// A stream of passwords generated on tokio's blocking thread pool.
+///
+/// Dropping the stream stops generation after the password in progress.
+pub struct PasswordStream {
+    receiver: mpsc::Receiver<Result<SecretString>>,
+}
+
+impl Stream for PasswordStream {
+    type Item = Result<SecretString>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl PasswordSpec {
+    /// Returns a stream of the passwords using the operating system's random
+    /// number generator.
+    ///
+    /// Entropy is read and passwords are generated on tokio's blocking
+    /// thread pool, so the stream can be consumed from async code without
+    /// stalling the executor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside a tokio runtime.
+    pub fn generate_stream(&self) -> PasswordStream {
+        let (sender, receiver) = mpsc::channel(CHANNEL_CAPACITY);
+        let spec = self.clone();
+        task::spawn_blocking(move || {
+            for password in spec.passwords_with_rng(OsRng) {
+                let stop = password.is_err();
+                let password = password.map(SecretString::from);
+                if sender.blocking_send(password).is_err() || stop {
+                    break;
+                }
+            }
+        });
+        PasswordStream { receiver }
+    }
+}
@@ -0,0 +1,129 @@
+use rand_core::CryptoRngCore;
+use std::fmt;
+
+use crate::charset::Charset;
+use crate::generator::entropy_bits;
+use crate::memory::SecretBuf;
+use crate::sampler::{random_below, sample};
+use crate::Result;
+
+// Bonus bits for a password with both uppercase and non-alphabetic
+// characters.
+const COMPOSITION_BONUS: f64 = 6.0;
+
+/// Estimates the entropy in bits of a password chosen by a person, with the
+/// rule of thumb from NIST SP 800-63-1: 4 bits for the first character, 2
+/// for each of the next 7, 1.5 for each of the next 12 and 1 for each after
+/// that, plus 6 bits if it has both uppercase and non-alphabetic
+/// characters. The estimate is deliberately low, since people choose
+/// passwords predictably.
+pub fn estimate_entropy(password: &str) -> f64 {
+    let mut bits =
+        (0..password.chars().count())
+            .map(|idx| {
+                match idx {
+                    0 => 4.0,
+                    1..=7 => 2.0,
+                    8..=19 => 1.5,
+                    _ => 1.0,
+                }
+            })
+            .fold(0.0, |bits, char_bits| bits + char_bits);
+    let has_uppercase = password.chars().any(|ch| ch.is_uppercase());
+    let has_non_alphabetic = password.chars().any(|ch| !ch.is_alphabetic());
+    if has_uppercase && has_non_alphabetic {
+        bits += COMPOSITION_BONUS;
+    }
+    bits
+}
+
+/// Where a [`Strengthener`] puts the random characters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Placement {
+    /// After the existing password.
+    #[default]
+    Append,
+    /// Each at a random position among the existing characters. The
+    /// positions are not counted as entropy.
+    Inject,
+}
+
+impl fmt::Display for Placement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Placement::Append => write!(f, "append"),
+            Placement::Inject => write!(f, "inject"),
+        }
+    }
+}
+
+/// Adds random characters to an existing password until its estimated
+/// entropy reaches a target, for passwords that must be partly kept.
+#[derive(Clone, Debug)]
+pub struct Strengthener {
+    charset: Charset,
+    target_bits: f64,
+    placement: Placement,
+}
+
+impl Strengthener {
+    /// Creates a strengthener adding characters from the character set
+    /// until passwords reach the target entropy.
+    pub fn new(charset: Charset, target_bits: f64) -> Self {
+        let placement = Placement::default();
+        Strengthener { charset, target_bits, placement }
+    }
+
+    /// Sets where the random characters go.
+    pub fn placement(mut self, placement: Placement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    /// Returns the number of random characters the password needs, counting
+    /// its own entropy as [`estimate_entropy`] does.
+    pub fn num_added(&self, password: &str) -> usize {
+        let missing = self.target_bits - estimate_entropy(password);
+        if missing <= 0.0 {
+            return 0;
+        }
+        let bits_per_char = entropy_bits(&self.charset, 1);
+        (missing / bits_per_char).ceil() as usize
+    }
+
+    /// Returns the estimated entropy of the password once strengthened.
+    pub fn entropy_after(&self, password: &str) -> f64 {
+        let added = entropy_bits(&self.charset, self.num_added(password));
+        estimate_entropy(password) + added
+    }
+
+    /// Returns the password with random characters added.
+    pub fn strengthen(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        password: &str,
+    ) -> Result<String> {
+        let mut added = SecretBuf::zeroed(self.num_added(password));
+        sample(rng, &self.charset, &mut added)?;
+        // Reserving the final length up front keeps the password from
+        // being copied as it grows.
+        let mut strengthened =
+            String::with_capacity(password.len() + added.len());
+        strengthened.push_str(password);
+        for &byte in added.iter() {
+            let idx =
+                match self.placement {
+                    Placement::Append => strengthened.len(),
+                    Placement::Inject => {
+                        let num_chars = strengthened.chars().count();
+                        let pos = random_below(rng, num_chars + 1)?;
+                        strengthened.char_indices()
+                            .nth(pos)
+                            .map_or(strengthened.len(), |(idx, _)| idx)
+                    },
+                };
+            strengthened.insert(idx, char::from(byte));
+        }
+        Ok(strengthened)
+    }
+}
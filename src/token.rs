@@ -0,0 +1,12 @@
+use crate::Result;
+
+/// A hardware token or other device that supplies entropy, typically mixed
+/// into the operating system's random number generator with an
+/// [`EntropyMixer`](crate::EntropyMixer).
+pub trait EntropyToken {
+    /// Returns a short name for the token, for reporting.
+    fn name(&self) -> &str;
+
+    /// Fills `dest` with bytes from the token.
+    fn fill(&mut self, dest: &mut [u8]) -> Result<()>;
+}
@@ -0,0 +1,171 @@
+use rand_core::CryptoRngCore;
+use std::fmt;
+use std::str::FromStr;
+use zeroize::Zeroize;
+
+use crate::generator::{entropy_bits, Generator};
+use crate::observer::GenerationObserver;
+use crate::policy::CharClass;
+use crate::sampler::random_below;
+use crate::{invalid_spec, PassgenError, Result};
+
+// Letters and the characters that stand in for them in leetspeak.
+const LEET: &[(char, char)] = &[
+    ('a', '4'),
+    ('e', '3'),
+    ('i', '1'),
+    ('o', '0'),
+    ('s', '5'),
+    ('t', '7'),
+];
+
+/// A random change made to each password after it is generated, for sites
+/// that demand symbols or digits in otherwise word-based passwords.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Transform {
+    /// Replaces each of the letters `a`, `e`, `i`, `o`, `s` and `t`, in
+    /// either case, with its look-alike digit with probability one half.
+    /// This adds no entropy to the weakest password, which may have none of
+    /// those letters, and can take some away from passwords that already
+    /// have those digits, so it is meant for passphrases.
+    Leet,
+    /// Inserts a random symbol at a random position, adding the entropy of
+    /// the symbol.
+    InsertSymbols,
+}
+
+impl Transform {
+    /// Returns the entropy in bits the transform adds to the weakest
+    /// password.
+    pub fn entropy_bits(self) -> f64 {
+        match self {
+            Transform::Leet => 0.0,
+            Transform::InsertSymbols =>
+                entropy_bits(&CharClass::Symbols.chars(), 1),
+        }
+    }
+
+    /// Returns the number of characters the transform adds.
+    pub fn added_length(self) -> usize {
+        match self {
+            Transform::Leet => 0,
+            Transform::InsertSymbols => 1,
+        }
+    }
+
+    fn apply(
+        self,
+        rng: &mut dyn CryptoRngCore,
+        password: &mut String,
+    ) -> Result<()> {
+        match self {
+            Transform::Leet => {
+                let mut leet = String::with_capacity(password.len());
+                for ch in password.chars() {
+                    let lower = ch.to_ascii_lowercase();
+                    let digit =
+                        LEET.iter()
+                            .find(|&&(letter, _)| letter == lower)
+                            .map(|&(_, digit)| digit);
+                    match digit {
+                        Some(digit) if random_below(rng, 2)? == 1 =>
+                            leet.push(digit),
+                        _ => leet.push(ch),
+                    }
+                }
+                password.zeroize();
+                *password = leet;
+            },
+            Transform::InsertSymbols => {
+                let symbols = CharClass::Symbols.chars();
+                let symbol = symbols[random_below(rng, symbols.len())?];
+                let num_chars = password.chars().count();
+                let pos = random_below(rng, num_chars + 1)?;
+                let idx =
+                    password.char_indices()
+                        .nth(pos)
+                        .map_or(password.len(), |(idx, _)| idx);
+                password.insert(idx, char::from(symbol));
+            },
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Transform::Leet => write!(f, "leet"),
+            Transform::InsertSymbols => write!(f, "insert-symbols"),
+        }
+    }
+}
+
+impl FromStr for Transform {
+    type Err = PassgenError;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match name {
+            "leet" => Ok(Transform::Leet),
+            "insert-symbols" => Ok(Transform::InsertSymbols),
+            _ => Err(invalid_spec(format!("unknown transform: {:?}", name))),
+        }
+    }
+}
+
+/// Applies transforms in order to each password from another generator.
+pub struct Transformed<'a> {
+    generator: &'a (dyn Generator + Sync),
+    transforms: Vec<Transform>,
+}
+
+impl<'a> Transformed<'a> {
+    /// Creates a generator applying the transforms to the other generator's
+    /// passwords.
+    pub fn new(
+        generator: &'a (dyn Generator + Sync),
+        transforms: Vec<Transform>,
+    ) -> Self {
+        Transformed { generator, transforms }
+    }
+
+    /// Returns the transforms, in the order they are applied.
+    pub fn transforms(&self) -> &[Transform] {
+        &self.transforms
+    }
+}
+
+impl Generator for Transformed<'_> {
+    fn generate(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+    ) -> Result<()> {
+        self.generate_observed(rng, out, &())
+    }
+
+    fn generate_observed(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+        observer: &dyn GenerationObserver,
+    ) -> Result<()> {
+        let start = out.len();
+        self.generator.generate_observed(rng, out, observer)?;
+        let mut password = out.split_off(start);
+        for transform in &self.transforms {
+            transform.apply(rng, &mut password)?;
+        }
+        out.push_str(&password);
+        password.zeroize();
+        Ok(())
+    }
+
+    fn entropy_bits(&self) -> f64 {
+        self.transforms.iter()
+            .fold(self.generator.entropy_bits(), |bits, transform| {
+                bits + transform.entropy_bits()
+            })
+    }
+}
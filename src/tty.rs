@@ -0,0 +1,79 @@
+use anyhow::{anyhow, Result};
+use std::io::{self, IsTerminal, Write};
+use zeroize::Zeroizing;
+
+use crate::i18n::tr;
+
+// Reads a secret from standard input, without echoing it when standard input
+// is a terminal. Without a terminal, as when piped, it is read as is.
+pub fn read_hidden(question: &str) -> Result<Zeroizing<String>> {
+    eprint!("{}: ", question);
+    io::stderr().flush()?;
+    let interactive = io::stdin().is_terminal();
+    let mut answer = Zeroizing::new(String::new());
+    // Echo is restored at the end of the block, before the newline that
+    // went unechoed is printed.
+    let (num_read, hidden) = {
+        let echo_off = if interactive { EchoOff::new() } else { None };
+        (io::stdin().read_line(&mut answer), echo_off.is_some())
+    };
+    if hidden {
+        eprintln!();
+    }
+    if num_read? == 0 {
+        return Err(anyhow!(tr("unexpected end of input")));
+    }
+    let len = answer.trim_end_matches(['\r', '\n']).len();
+    answer.truncate(len);
+    Ok(answer)
+}
+
+// Restores the terminal's settings when dropped.
+#[cfg(unix)]
+struct EchoOff {
+    original: libc::termios,
+}
+
+#[cfg(unix)]
+impl EchoOff {
+    fn new() -> Option<Self> {
+        let mut settings = unsafe { std::mem::zeroed::<libc::termios>() };
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut settings) } != 0 {
+            return None;
+        }
+        let original = settings;
+        settings.c_lflag &= !libc::ECHO;
+        let result =
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &settings)
+            };
+        if result != 0 {
+            return None;
+        }
+        Some(EchoOff { original })
+    }
+}
+
+#[cfg(unix)]
+impl Drop for EchoOff {
+    fn drop(&mut self) {
+        unsafe {
+            libc::tcsetattr(
+                libc::STDIN_FILENO,
+                libc::TCSAFLUSH,
+                &self.original,
+            );
+        }
+    }
+}
+
+// Echo is left on elsewhere.
+#[cfg(not(unix))]
+struct EchoOff;
+
+#[cfg(not(unix))]
+impl EchoOff {
+    fn new() -> Option<Self> {
+        None
+    }
+}
@@ -0,0 +1,1310 @@
+use crate::i18n::tr;
+
+pub struct OptionHelp {
+    pub flags: &'static str,
+    pub arg: Option<&'static str>,
+    pub help: &'static str,
+}
+
+pub struct Section {
+    pub title: &'static str,
+    pub options: &'static [OptionHelp],
+}
+
+const fn opt(
+    flags: &'static str,
+    arg: Option<&'static str>,
+    help: &'static str,
+) -> OptionHelp {
+    OptionHelp { flags, arg, help }
+}
+
+/// An option as a synopsis shows it and a parser reads it: its spellings,
+/// separated by |, and the argument any of them takes.
+pub struct Flag {
+    pub spellings: &'static str,
+    pub arg: Option<&'static str>,
+    pub required: bool,
+    pub repeated: bool,
+}
+
+/// A word of a synopsis.
+pub enum Word {
+    /// Shown as it is: the command, its operands or [options], which stands
+    /// for the options of the sections below, read by the main parser.
+    Text(&'static str),
+    Flag(Flag),
+}
+
+const fn text(text: &'static str) -> Word {
+    Word::Text(text)
+}
+
+const fn flag(spellings: &'static str, arg: Option<&'static str>) -> Word {
+    Word::Flag(Flag { spellings, arg, required: false, repeated: false })
+}
+
+const fn required(spellings: &'static str, arg: Option<&'static str>) -> Word {
+    Word::Flag(Flag { spellings, arg, required: true, repeated: false })
+}
+
+const fn repeated(spellings: &'static str, arg: Option<&'static str>) -> Word {
+    Word::Flag(Flag { spellings, arg, required: false, repeated: true })
+}
+
+const OPTIONS: Word = text("[options]");
+
+pub const MAIN: &[Word] = &[text("[options] [password_len [num_passwords]]")];
+pub const DERIVE: &[Word] = &[
+    text("derive"),
+    required("--site", Some("site")),
+    required("--login", Some("login")),
+    flag("--counter", Some("counter")),
+    flag("--save", None),
+    flag("--profiles", Some("file")),
+    OPTIONS,
+];
+pub const PROFILES: &[Word] = &[
+    text("profiles export|import [file]"),
+    flag("--profiles", Some("file")),
+];
+pub const CONFIG: &[Word] = &[
+    text("config validate"),
+    flag("--profiles", Some("file")),
+    flag("--generators", Some("file")),
+    flag("--confusables-file", Some("file")),
+    flag("--config", Some("file")),
+];
+pub const TOKEN_BYTES: &[Word] = &[
+    text("token"),
+    required("--hex|--base64url|--base32", Some("num_bytes")),
+    flag("--prefix", Some("prefix")),
+    OPTIONS,
+];
+pub const TOKEN_ID: &[Word] = &[
+    text("token"),
+    required("--base58|--crockford", Some("id_len")),
+    flag("--check", None),
+    flag("--prefix", Some("prefix")),
+    OPTIONS,
+];
+pub const UUID: &[Word] = &[text("uuid"), flag("--v4|--v7", None), OPTIONS];
+pub const APIKEY: &[Word] = &[
+    text("apikey"),
+    flag("--prefix", Some("prefix")),
+    flag("-l", Some("body_len")),
+    OPTIONS,
+];
+pub const APIKEY_VERIFY: &[Word] = &[
+    text("apikey verify"),
+    flag("--prefix", Some("prefix")),
+    flag("-l", Some("body_len")),
+];
+pub const LICENSE: &[Word] = &[
+    text("license"),
+    flag("--groups", Some("n")),
+    flag("--group-len", Some("n")),
+    flag("--checksum", Some("luhn|crc")),
+    OPTIONS,
+];
+pub const LICENSE_VERIFY: &[Word] = &[
+    text("license verify"),
+    flag("--groups", Some("n")),
+    flag("--group-len", Some("n")),
+    flag("--checksum", Some("luhn|crc")),
+];
+pub const WIFI: &[Word] = &[
+    text("wifi"),
+    required("--ssid", Some("ssid")),
+    flag("-l|--length", Some("passphrase_len")),
+    flag("--psk", None),
+    flag("--qr", None),
+    flag("--hidden", None),
+];
+pub const MAC: &[Word] = &[
+    text("mac"),
+    flag("--locally-administered", None),
+    flag("--universally-administered", None),
+    flag("--vendor-prefix", Some("xx:xx:xx")),
+    OPTIONS,
+];
+pub const USERNAME: &[Word] = &[
+    text("username"),
+    flag("--style", Some("words|pronounceable")),
+    flag("--max-len", Some("max_len")),
+    OPTIONS,
+];
+pub const OTP: &[Word] = &[
+    text("otp"),
+    flag("-l", Some("code_len")),
+    flag("--luhn", None),
+    flag("--group-len", Some("n")),
+    OPTIONS,
+];
+pub const GENERATOR: &[Word] = &[
+    text("generator name"),
+    flag("--generators", Some("file")),
+    OPTIONS,
+];
+pub const GENERATORS: &[Word] = &[
+    text("generators"),
+    flag("--generators", Some("file")),
+];
+pub const IMPORT: &[Word] = &[
+    text("import keepass-pattern|pwgen-args|1password-recipe name recipe"),
+    flag("--generators", Some("file")),
+];
+pub const CREDENTIALS: &[Word] = &[
+    text("credentials"),
+    flag("--style", Some("words|pronounceable")),
+    flag("--max-len", Some("max_len")),
+    flag("--totp", None),
+    OPTIONS,
+];
+pub const PIN: &[Word] = &[
+    text("pin"),
+    flag("-l|--length", Some("pin_len")),
+    flag("-n|--count", Some("num_pins")),
+    flag("--allow-weak", None),
+    flag("-v|--verbose", None),
+];
+pub const CARD: &[Word] = &[
+    text("card"),
+    flag("-c", Some("charset_spec")),
+    flag("--columns", Some("n")),
+    flag("--html", None),
+    flag("--derive", None),
+    flag("--derivation-version", Some("n")),
+    flag("-v|--verbose", None),
+];
+pub const STRENGTHEN: &[Word] = &[
+    text("strengthen"),
+    flag("-c", Some("charset_spec")),
+    flag("--target", Some("bits")),
+    flag("--inject", None),
+];
+pub const AUDIT: &[Word] = &[
+    text("audit"),
+    flag("--pairs", None),
+    flag("--distance", Some("n")),
+    flag("--format", Some("text|csv|json")),
+    flag("--config", Some("file")),
+    flag("--wordlist", Some("file")),
+    flag("--check-hibp", None),
+    flag("--check-offline", Some("file")),
+    text("[file]"),
+];
+pub const ENTROPY: &[Word] = &[
+    text("entropy"),
+    flag("--bits", Some("n")),
+    flag("--zxcvbn", None),
+    repeated("--dictionary", Some("file")),
+    repeated("--attacker", Some("name=rate")),
+];
+pub const SCORE: &[Word] = &[
+    text("score"),
+    flag("--config", Some("file")),
+    flag("--wordlist", Some("file")),
+    flag("--user", Some("name")),
+];
+pub const INSERT_PASS: &[Word] = &[
+    text("insert-pass entry"),
+    flag("--in-place", None),
+    repeated("--field", Some("name=value")),
+    OPTIONS,
+];
+pub const HIBP: &[Word] = &[text("hibp"), flag("--offline", Some("file"))];
+pub const HIBP_BUILD: &[Word] = &[
+    text("hibp build dump_file bloom_file"),
+    flag("--fp-rate", Some("rate")),
+];
+pub const RETRIEVE: &[Word] = &[
+    text("retrieve"),
+    required("--service", Some("service")),
+    required("--account", Some("account")),
+];
+pub const KEYFILE: &[Word] = &[
+    text("keyfile file"),
+    flag("--size", Some("bytes")),
+    flag("-f|--force", None),
+];
+pub const NATIVE_HOST: &[Word] = &[
+    text("native-host"),
+    flag("--check-hibp", None),
+    flag("--check-offline", Some("file")),
+];
+pub const SERVE: &[Word] = &[
+    text("serve"),
+    required("--token-file", Some("file")),
+    flag("--listen", Some("address")),
+    flag("--rate", Some("limit")),
+    flag("--quota", Some("limit")),
+];
+pub const GRPC: &[Word] = &[
+    text("grpc"),
+    required("--token-file", Some("file")),
+    flag("--listen", Some("address")),
+    flag("--rate", Some("limit")),
+    flag("--quota", Some("limit")),
+];
+pub const DAEMON: &[Word] = &[
+    text("daemon"),
+    flag("--socket", Some("path")),
+    flag("--wordlist", Some("file")),
+    flag("--rate", Some("limit")),
+    flag("--quota", Some("limit")),
+    flag("--check-hibp", None),
+    flag("--check-offline", Some("file")),
+];
+pub const SELFTEST: &[Word] = &[text("selftest")];
+pub const DISTRIBUTION: &[Word] = &[
+    text("selftest"),
+    required("--distribution", None),
+    OPTIONS,
+];
+pub const RECOVER: &[Word] = &[text("recover")];
+pub const MAN: &[Word] = &[text("man")];
+
+// Help and the manual page show the synopses in this order.
+const SYNOPSES: &[&[Word]] = &[
+    MAIN,
+    DERIVE,
+    PROFILES,
+    CONFIG,
+    TOKEN_BYTES,
+    TOKEN_ID,
+    UUID,
+    APIKEY,
+    APIKEY_VERIFY,
+    LICENSE,
+    LICENSE_VERIFY,
+    WIFI,
+    MAC,
+    USERNAME,
+    OTP,
+    GENERATOR,
+    GENERATORS,
+    IMPORT,
+    CREDENTIALS,
+    PIN,
+    CARD,
+    STRENGTHEN,
+    AUDIT,
+    ENTROPY,
+    SCORE,
+    INSERT_PASS,
+    HIBP,
+    HIBP_BUILD,
+    RETRIEVE,
+    KEYFILE,
+    NATIVE_HOST,
+    SERVE,
+    GRPC,
+    DAEMON,
+    SELFTEST,
+    DISTRIBUTION,
+    RECOVER,
+    MAN,
+];
+
+pub const DESCRIPTION: &str = "Generates random passwords.";
+
+pub const SECTIONS: &[Section] = &[
+    Section {
+        title: "Options",
+        options: &[
+            opt("-c", Some("charset_spec"), "use this character set"),
+            opt(
+                "--preset",
+                Some("dns|url"),
+                "use the character set and rules of a preset: dns for valid \
+                 DNS labels (lowercase letters, digits and inner hyphens, at \
+                 most 63 characters) or url for URL path segments and query \
+                 components that need no percent-encoding",
+            ),
+            opt(
+                "-l",
+                Some("password_len"),
+                "generate passwords of this length (default 24)",
+            ),
+            opt(
+                "-n",
+                Some("num_passwords"),
+                "generate this many passwords (default 1)",
+            ),
+        ],
+    },
+    Section {
+        title: "apg-compatible options",
+        options: &[
+            opt(
+                "-a",
+                Some("algorithm"),
+                "1 for random generation (0, pronounceable, is not \
+                 supported)",
+            ),
+            opt(
+                "-m",
+                Some("min_len"),
+                "generate passwords of at least this length",
+            ),
+            opt(
+                "-x",
+                Some("max_len"),
+                "generate passwords of at most this length",
+            ),
+            opt(
+                "-M",
+                Some("mode"),
+                "use these character classes (S symbols, N numbers, C \
+                 capitals, L lowercase); a lowercase mode letter allows the \
+                 class, an uppercase letter requires it",
+            ),
+            opt(
+                "-E",
+                Some("exclude_chars"),
+                "exclude these characters from the character set",
+            ),
+            opt(
+                "--confusables",
+                Some("profile,..."),
+                "exclude the characters easily confused with others in a \
+                 medium: sans-serif, printed-form or handwritten",
+            ),
+            opt(
+                "--confusables-file",
+                Some("file"),
+                "read more confusables profiles from this file instead of \
+                 confusables.json beside the profiles file",
+            ),
+            opt(
+                "--compose",
+                Some("composition"),
+                "draw each character from a class chosen by its share, as \
+                 in letters:60%,digits:25%,symbols:15%, in place of a \
+                 character set",
+            ),
+        ],
+    },
+    Section {
+        title: "Other options",
+        options: &[
+            opt(
+                "-v, --verbose",
+                None,
+                "report the charset size, entropy, policy, random number \
+                 source and timing on standard error",
+            ),
+            opt(
+                "-q, --quiet",
+                None,
+                "do not show a progress bar when generating many passwords \
+                 on a terminal",
+            ),
+            opt(
+                "--stats",
+                None,
+                "report the wall-clock time, bytes of entropy consumed, \
+                 generation rate and peak memory on standard error after \
+                 generating",
+            ),
+            opt(
+                "--audit-log",
+                Some("file"),
+                "append a line of JSON to the file each time passwords are \
+                 generated, recording when, the label, count, entropy and \
+                 policy, but never the passwords",
+            ),
+            opt(
+                "--label",
+                Some("label"),
+                "the label of the entries in the audit log, by default the \
+                 insert-pass entry, and with --format csv or json, of each \
+                 record",
+            ),
+            opt(
+                "--max-memory",
+                Some("size"),
+                "fail before generating if the job would hold more memory \
+                 than this, in bytes or with a K, M, G or T suffix",
+            ),
+            opt(
+                "--dry-run",
+                None,
+                "print the effective settings and entropy without \
+                 generating any passwords",
+            ),
+            opt(
+                "--check-hibp",
+                None,
+                "redraw any password that appears in breaches known to Have \
+                 I Been Pwned, sending only the first five hexadecimal \
+                 digits of its SHA-1 hash; needs network access and a build \
+                 with the hibp feature",
+            ),
+            opt(
+                "--check-offline",
+                Some("file"),
+                "redraw any password that appears in a local breach list: \
+                 a Pwned Passwords SHA-1 dump ordered by hash, or a Bloom \
+                 filter built from one by hibp build",
+            ),
+            opt(
+                "--min-entropy",
+                Some("bits"),
+                "fail unless each password has at least this much entropy",
+            ),
+            opt(
+                "--max-attempts",
+                Some("n"),
+                "give up on a password after drawing this many candidates \
+                 that fail the policy",
+            ),
+            opt(
+                "--timeout",
+                Some("secs"),
+                "give up on a password after drawing candidates that fail \
+                 the policy for this many seconds",
+            ),
+            opt(
+                "--transform",
+                Some("transforms"),
+                "apply these comma-separated transforms in order to each \
+                 password: leet replaces each of the letters a, e, i, o, s \
+                 and t with its look-alike digit with probability one half, \
+                 adding no guaranteed entropy, and insert-symbols inserts a \
+                 random symbol at a random position, adding 5 bits",
+            ),
+            opt(
+                "--post-script",
+                Some("file"),
+                "pass each password through this Rhai script, which is given \
+                 it as password and ends with the password to use, or with \
+                 true to accept it or false to reject it",
+            ),
+            opt(
+                "-f, --force",
+                None,
+                "do not ask for confirmation on a terminal before generating \
+                 passwords with less than 64 bits of entropy",
+            ),
+            opt(
+                "--no-harden",
+                None,
+                "leave core dumps and debugger attachment enabled, for \
+                 debugging",
+            ),
+            opt(
+                "--sandbox",
+                None,
+                "on Linux, restrict the process to the system calls needed \
+                 to generate and print passwords; the default except with \
+                 --batch, --check-hibp, --store or insert-pass",
+            ),
+            opt("--no-sandbox", None, "do not restrict system calls"),
+            opt(
+                "--encrypt-to",
+                Some("recipient"),
+                "encrypt the output to this recipient, with age if it is an \
+                 age public key and with gpg otherwise, writing the armored \
+                 ciphertext to standard output",
+            ),
+            opt(
+                "--store",
+                Some("keyring"),
+                "save the password into the platform keyring under \
+                 --service and --account instead of printing it: the Secret \
+                 Service through secret-tool, the macOS keychain or the \
+                 Windows Credential Manager; needs a build with the keyring \
+                 feature",
+            ),
+            opt(
+                "--store",
+                Some("kdbx file"),
+                "add the passwords as entries named by --entry to the \
+                 KeePass 4 database in the file, creating it if needed, \
+                 instead of printing them",
+            ),
+            opt(
+                "--store",
+                Some("bitwarden"),
+                "create login items named by --entry in the Bitwarden or \
+                 Vaultwarden vault bw is unlocked for, instead of printing \
+                 the passwords",
+            ),
+            opt(
+                "--store",
+                Some("vault"),
+                "write the password under --key into the secret at --path \
+                 in HashiCorp Vault's KV version 2 engine, keeping the \
+                 secret's other keys, instead of printing it; needs a build \
+                 with the vault feature",
+            ),
+            opt(
+                "--store",
+                Some("systemd-creds"),
+                "print the password encrypted by systemd-creds as the \
+                 systemd credential named by --entry",
+            ),
+            opt(
+                "--service",
+                Some("service"),
+                "the service to save the password under with --store",
+            ),
+            opt(
+                "--account",
+                Some("account"),
+                "the account to save the password under with --store",
+            ),
+            opt(
+                "--entry",
+                Some("title[/username]"),
+                "the title and username of the entries added with --store \
+                 kdbx or bitwarden, or the name of the credential with \
+                 --store systemd-creds",
+            ),
+            opt(
+                "--uri",
+                Some("uri"),
+                "the URI of the entries added with --store kdbx or \
+                 bitwarden",
+            ),
+            opt(
+                "--path",
+                Some("mount/secret"),
+                "the secret to write with --store vault, under the engine \
+                 mounted at the first component",
+            ),
+            opt(
+                "--key",
+                Some("key"),
+                "the key to write the password under with --store vault",
+            ),
+            opt(
+                "--rng",
+                Some("os|chacha"),
+                "draw from the operating system's random number generator \
+                 (the default) or from a ChaCha20 generator reseeded from it \
+                 after every mebibyte, which is faster for bulk jobs",
+            ),
+            opt(
+                "--rng-fallback",
+                Some("fail|chacha"),
+                "when the operating system's random number generator fails, \
+                 either fail (the default) or continue with a ChaCha20 \
+                 generator seeded from it beforehand",
+            ),
+            opt(
+                "--health-min-entropy",
+                Some("bits"),
+                "run the continuous health tests assuming this much \
+                 min-entropy per output byte, 8 by default",
+            ),
+            opt(
+                "--health-alpha",
+                Some("log2"),
+                "set the health tests' false positive probability to 2 to \
+                 the power of this, -30 by default",
+            ),
+            opt(
+                "--health-window",
+                Some("samples"),
+                "set the adaptive proportion test's window, 512 bytes by \
+                 default",
+            ),
+            opt(
+                "--entropy-source",
+                Some("yubikey|hwrng"),
+                "mix output from this device into the random number \
+                 generator's output, never replacing it; yubikey sends \
+                 random challenges to slot 2 with ykchalresp and needs a \
+                 build with the yubikey feature, and hwrng reads the Linux \
+                 hardware random number generator /dev/hwrng",
+            ),
+            opt(
+                "--mix-rdseed",
+                None,
+                "mix output from the x86 RDSEED instruction into the random \
+                 number generator's output, never replacing it",
+            ),
+            opt(
+                "--extra-entropy",
+                None,
+                "ask for keyboard mashing, dice rolls or a file and mix a \
+                 hash of it into the random number generator's output",
+            ),
+            opt(
+                "--split",
+                Some("KofN"),
+                "print each password as N shares, one per line, any K of \
+                 which recover it with the recover command",
+            ),
+            opt("-C", None, "print passwords in columns"),
+            opt("-1", None, "print one password per line"),
+            opt(
+                "--format",
+                Some("text|csv|json|chpasswd|ansible-vault|spelling"),
+                "print passwords as text, as csv with a header line, as \
+                 json with one object per line or as lines for chpasswd, in \
+                 a field named password, or after the command for tokens \
+                 and codes, with code for otp; ansible-vault prints one \
+                 password encrypted as a !vault string, and spelling each \
+                 character with its spelling word and Morse code",
+            ),
+            opt(
+                "--vault-password-file",
+                Some("file"),
+                "encrypt with the vault password in this file, or printed \
+                 by it if it is executable, for --format ansible-vault",
+            ),
+            opt(
+                "--users",
+                Some("file"),
+                "generate a password for each username in the file, one \
+                 per line, and print it after the username",
+            ),
+            opt(
+                "--fields",
+                Some("name=definition,..."),
+                "print records of these fields, each from a registered \
+                 generator given as @name or from password options",
+            ),
+            opt(
+                "--generators",
+                Some("file"),
+                "read the generators for --fields from this file instead of \
+                 generators.json beside the profiles file",
+            ),
+            opt(
+                "--hash",
+                None,
+                "with --users, add a SHA-512 crypt hash of each password, \
+                 which --format chpasswd prints instead of the password",
+            ),
+            opt(
+                "--speakable",
+                None,
+                "print each password again beside it, split into pieces to \
+                 read aloud",
+            ),
+            opt(
+                "--large-print",
+                None,
+                "print each password spaced out, then one character a line \
+                 with its position and class",
+            ),
+            opt(
+                "--braille",
+                None,
+                "print passwords in short groups for a Braille display",
+            ),
+            opt(
+                "--prompt",
+                None,
+                "interactively ask for the password length, character \
+                 classes and number of passwords when they are not given as \
+                 options",
+            ),
+            opt(
+                "--batch",
+                None,
+                "read one specification per line from standard input and \
+                 generate passwords for each in order; a line holds either \
+                 options in the syntax above or a JSON object with the \
+                 fields charset, mode, exclude, length, min_length, \
+                 max_length, count and min_entropy, and other options given \
+                 on the command line apply to every line",
+            ),
+        ],
+    },
+];
+
+pub const NOTES: &[&str] = &[
+    "When several passwords are printed to a terminal, they are laid out in \
+     columns fitting the terminal width unless the character set contains a \
+     space.",
+    "The password length and number of passwords may also be given as \
+     positional arguments, so \"passgen 32 5\" is equivalent to \"passgen -l \
+     32 -n 5\".",
+    "The charset specification language is a subset of the character set \
+     language for regular expressions. Only characters and ranges are \
+     allowed. Literal hyphens and backslashes must be escaped. Other \
+     characters must not be escaped. An initial caret may be used to invert \
+     the character set with respect to typeable ASCII characters.",
+    "The entropy reported with -v and --dry-run, required by \
+     --min-entropy and given in JSON responses counts only the passwords \
+     that satisfy the policy, so required classes and characters forbidden \
+     at the ends lower it.",
+    "The confusables profiles exclude all the characters often misread \
+     for one another: sans-serif excludes Il1|O0, printed-form \
+     0OQD1Il|2Z5S8B6GUV`',.;: and handwritten those and more, including the \
+     letters written alike in both cases. A confusables file holds a JSON \
+     object from each profile's name to its characters, as in \
+     {\"terminal\": \"0O1l\"}, and may replace the built-in profiles.",
+    "With --compose, the classes are lowercase, uppercase, letters, digits \
+     and symbols, with whole percentages adding up to 100. Passwords have \
+     the classes in those shares on average, in no set order, and any \
+     password may stray from them. Since the characters are then not \
+     equally likely, the entropy is that of the likeliest password, with \
+     the average given beside it by -v and --dry-run.",
+    "A --post-script file is a Rhai script, run for each candidate password \
+     with the candidate as the constant password. Ending with a string \
+     accepts that string as the password, changed or not, true accepts the \
+     candidate, and false rejects it and draws another; anything else, \
+     including an error the script throws or 1000 rejections in a row, \
+     fails the job. Scripts cannot load modules or reach files, the network \
+     or other programs, what they print is discarded, and they are stopped \
+     after a million operations. Since a script may reject most candidates \
+     or return the same password for all of them, the entropy of its \
+     passwords is unknown and reported as none, and a warning is given if \
+     it changes any.",
+    "Passwords are written out as they are generated, so the memory a job \
+     holds grows with its count only for the usernames of --users, the \
+     output collected for insert-pass, --store or --vault-password-file and \
+     the codes the otp command keeps to make them distinct. With \
+     --max-memory, those are estimated before anything is generated, and \
+     the file of usernames must be no larger than the limit before it is \
+     read.",
+    "Jobs of 100000 or more passwords printed one to a line are generated on \
+     as many threads as there are processors, each drawing from a generator of \
+     its own, in batches of lines written out at once. Threads wait while a \
+     few batches each are waiting to be written, so a job is generated no \
+     faster than its output is read, and holds the same memory however large \
+     it is.",
+    "The audit log is opened, and created readable only by its owner if \
+     missing, before anything is generated. Each line is a JSON object with \
+     the time in UTC, the label, the preset, the kind and count of secrets, \
+     the entropy of the weakest possible, any --min-entropy required, the \
+     random number source and the description -v prints, policy included. \
+     Passwords, hashes and anything else worked out from them are never \
+     logged. Dry runs are not logged, and in batch mode every line is \
+     logged to the file given on the command line.",
+    "A label may have placeholders, expanded for each record with --format csv \
+     or json: {seq} for its number, counted from 1 and padded with zeros to \
+     any width given, as in svc-{seq:04}; {date} for the date in UTC; and \
+     {random} for 4 random characters of Crockford's Base32 in lowercase, or \
+     as many as given, as in {random:6}. Braces themselves are written {{ and \
+     }}. The audit log records the label as given.",
+    "With --extra-entropy, --entropy-source or --mix-rdseed, the extra \
+     input is hashed with SHAKE256 and the resulting stream is XORed into \
+     the random number generator's output. The stream depends only on the \
+     input, which is fixed before the generator is read, so mixing never \
+     reduces the entropy of the output, and it stays unpredictable to an \
+     attacker who can predict the generator but does not know the input.",
+    "Before any password is generated, 1024 bytes from the operating system's \
+     random number generator are run through the repetition count and adaptive \
+     proportion tests of NIST SP 800-90B. The tests then keep checking the \
+     entropy input: with --rng chacha, each seed drawn from the operating \
+     system, and the bytes read from any --entropy-source device. The \
+     passwords themselves are not tested byte by byte, since over a bulk job \
+     even a tiny false alarm rate adds up. If a test fails, passgen stops \
+     without printing the password being generated.",
+    "The selftest command checks the health of the operating system's random \
+     number generator, that charset specifications survive formatting and \
+     parsing, and that generation reproduces known answers from a fixed \
+     seed.",
+    "With --distribution, the selftest command generates 1000000 \
+     passwords, or the number given with -n, as the options for generating \
+     passwords describe, and prints how often each character was drawn and \
+     chi-squared tests of whether characters are drawn uniformly overall \
+     and at each position. It fails if any test gives a p-value below \
+     0.001 divided among the tests. Policies that require classes or \
+     forbid characters at the ends skew the distribution and cannot be tested.",
+    "The derive command asks twice for a master password, so that a typing \
+     mistake is caught, and derives the password for a site and login from \
+     it with Argon2id, so the same inputs always give the same password and \
+     nothing needs to be stored. The counter, 1 by default, is incremented \
+     to rotate the password. The character set, length and mode options \
+     apply as when generating.",
+    "With --save, the derive command stores the site's settings, counter \
+     and derivation version as a profile, which later derivations for the \
+     same site and login use unless options override it, so the password \
+     stays reproducible when the defaults change. Profiles are kept in \
+     passgen/profiles.json in the user's configuration directory, or in the \
+     file given with --profiles. The profiles command exports them as JSON \
+     or imports them from a file or standard input.",
+    "The config validate command checks the profiles, generators, confusables \
+     and pwquality.conf files, as found by default or given with --profiles, \
+     --generators, --confusables-file and --config, parsing each as the \
+     commands reading it do. Files not given that do not exist are skipped. It \
+     prints each file's path followed by ok, or by the line and column of the \
+     first problem in it and what the problem is, and fails if any file is \
+     invalid.",
+    "The token command prints random bytes encoded as lowercase \
+     hexadecimal, unpadded URL-safe Base64 or unpadded Base32, the \
+     encoding of TOTP secrets, after the prefix if one is given. The count, \
+     layout, random number generator and output options apply as when \
+     generating passwords.",
+    "With --base58 or --crockford, the token command instead prints \
+     identifiers of id_len characters drawn uniformly from the Bitcoin \
+     Base58 alphabet or from Crockford's Base32 alphabet, neither of which \
+     has easily confused characters. --check appends Crockford's check \
+     symbol, the identifier's value modulo 37.",
+    "The uuid command prints version 4 UUIDs, or with --v7 version 7 \
+     UUIDs, which begin with the time in milliseconds so that they sort in \
+     order of creation. The options shared with the token command apply.",
+    "The apikey command prints API keys made of the prefix, a random body \
+     of body_len base62 characters, 30 by default, and a CRC-32 of the body \
+     in 6 base62 characters, so that services can reject mistyped keys \
+     without a lookup. The options shared with the token command apply. \
+     The apikey verify command reads keys from standard input, one per \
+     line, and checks their prefix, length and checksum.",
+    "The license command prints license keys of 4 groups of 5 characters \
+     by default, separated by hyphens, from Crockford's Base32 alphabet. \
+     The key ends with a checksum of the rest: one Luhn mod 32 character \
+     by default, or with --checksum crc four characters holding 20 bits of \
+     the CRC-32. The options shared with the token command apply. The \
+     license verify command reads keys of the same shape from standard \
+     input, one per line, ignoring case and reading O as 0 and I and L as \
+     1, and checks their checksums.",
+    "The wifi command prints a WPA2 or WPA3 passphrase of 24 letters and \
+     digits by default, or with --psk the 64 hexadecimal digit pre-shared \
+     key derived from it and the SSID with PBKDF2. --qr adds the payload of \
+     the QR code phones scan to join the network, and --hidden marks the \
+     network as hidden in it.",
+    "The mac command prints random unicast MAC addresses, marked as \
+     locally administered unless --universally-administered is given, or \
+     starting with a vendor prefix given with --vendor-prefix, which \
+     --locally-administered requires to be locally administered. The \
+     options shared with the token command apply, but low entropy is not \
+     warned about, since MAC addresses are not secret.",
+    "The username command prints usernames for throwaway or service \
+     accounts: by default an adjective, a noun and a two-digit number from \
+     built-in lists joined by hyphens and no longer than 20 characters, or \
+     with --style pronounceable alternating consonants and vowels, 8 \
+     letters long by default. --max-len caps the length. The options \
+     shared with the token command apply, but low entropy is not warned \
+     about, since usernames are not secret.",
+    "The otp command prints a batch of 10 distinct numeric one-time codes \
+     of 8 digits by default, such as the backup codes on a scratch card. \
+     -l sets the number of digits, --luhn makes the last of them a Luhn \
+     check digit and --group-len splits each code into groups of this many \
+     digits separated by hyphens. The options shared with the token \
+     command apply, but low entropy is not warned about, since guesses at \
+     one-time codes are limited.",
+    "The generator command prints passwords from a generator registered \
+     under the name, and the generators command lists the names. Crates \
+     using the library register their own; passgen registers those defined \
+     in generators.json beside the profiles file, or in the file given with \
+     --generators: a JSON object from each name to an object with one field, \
+     charset, pattern, passphrase or pin, holding the generator's settings, \
+     as in {\"mainframe\": {\"pattern\": \"uddddddd\"}}. The options shared \
+     with the token command apply.",
+    "The import command converts another tool's recipe into a generator \
+     defined under the name in the generators file: a KeePass password \
+     generator pattern, pwgen's arguments given as one argument, or a \
+     1Password recipe, either as the CLI takes it, as in letters,digits,32, or \
+     as the JSON Connect takes. A KeePass pattern drawing every character from \
+     the same set becomes a charset generator; otherwise each of its \
+     placeholders must be d, l, u or s, and it becomes a pattern. pwgen's \
+     pronounceable passwords have no equivalent and are imported as with -s.",
+    "With --fields, each record has the fields given, in order, as in \
+     --fields 'user=@handle,pass=-l 32 -c a-z0-9,pin=@pin6'. A field is \
+     either a generator registered under the name after @, or the options \
+     that choose passwords for the main command, such as -l, -c, -M, \
+     --preset and -E. With --format csv or json the fields have the names \
+     given. --hash, --speakable and the breach checks apply to a field \
+     named password, and --min-entropy to the weakest field.",
+    "The credentials command prints a username and a password on each \
+     line, and with --totp a TOTP secret of 20 random bytes in unpadded \
+     Base32, for test data and account onboarding. --style and --max-len \
+     choose the usernames as for the username command, and the options \
+     for generating passwords choose the passwords. With --format csv or \
+     json the fields are named username, password and totp_secret.",
+    "With --users, blank lines are skipped, and the usernames must not \
+     begin with a hyphen or contain colons or whitespace. --format \
+     chpasswd prints user:password lines to pipe into chpasswd, and with \
+     --hash user:hash lines for chpasswd -e, so that chpasswd never sees \
+     the passwords themselves. With --format csv or json and --hash, the \
+     fields are named username, password and hash, keeping the passwords \
+     to hand out alongside the hashes.",
+    "With --speakable, each password is followed by a tab and the same \
+     password with spaces between its pieces: syllables of letters, pairs \
+     of digits and single other characters, with any space in the password \
+     shown as \"␣\". The password itself is unchanged. With --format \
+     csv or json the pieces are in a field named speakable.",
+    "--large-print and --braille lay passwords out for users with low \
+     vision to transcribe them, leaving a blank line between them. With \
+     --braille, each password is split into groups of up to four letters of \
+     one case or digits, or of one other character, so that each group \
+     needs at most one capital or number sign, and wrapped between groups \
+     to the 40 cells of a common display.",
+    "With --format spelling, each password is followed by one line for \
+     each character giving its position, its class, its word in the ICAO \
+     spelling alphabet or a name for a symbol, and its International Morse \
+     code where it has one, as an aid to reading passwords out over radio \
+     or phone. Digits are given as pronounced on the radio, such as Tree \
+     and Niner.",
+    "With --format ansible-vault, the password is printed as the !vault \
+     tagged string ansible-vault encrypt_string prints, in version 1.1 of \
+     the vault format, to paste after a variable's name in group_vars or a \
+     playbook.",
+    "The pin command prints numeric PINs, 6 digits long by default. It \
+     redraws weak PINs: common ones, a shorter block of digits repeated, \
+     runs of consecutive digits and, for 4 digits, years from 1900 to 2099. \
+     The entropy reported with -v counts only the PINs that remain. \
+     --allow-weak keeps every PIN.",
+    "The card command prints a password card: a grid of random characters \
+     with rows A to Z and 26 numbered columns by default, from which \
+     passwords are read along a path starting at a memorised cell. The \
+     characters are the typeable ones other than space unless -c is given. \
+     --html prints the card as a web page laid out for printing. With \
+     --derive, the card is derived from a master password with Argon2id as \
+     by the derive command, so that it can be printed again; \
+     --derivation-version selects an older derivation.",
+    "The strengthen command reads an existing password without echoing it \
+     and prints it with enough random characters added, at the end or with \
+     --inject at random positions, to reach the target entropy, 80 bits by \
+     default. The existing password is credited with the deliberately low \
+     estimate of NIST SP 800-63-1 for passwords people choose. The \
+     characters are the typeable ones other than space unless -c is given. \
+     The estimates before and after are reported on standard error.",
+    "The audit command reports on each password in the file, or on \
+     standard input, one per line, or with --pairs each user:password \
+     pair: its estimated entropy, its score or the reason it fails the \
+     checks of the score command, which take the same --config and \
+     --wordlist, whether it is breached with --check-hibp or \
+     --check-offline, and the lines of any duplicates and of any near \
+     duplicates, within 2 edits of it or the number given with --distance, \
+     0 to not look for them. The report never includes the passwords. It \
+     fails if any entry fails the checks, is breached or is duplicated or \
+     nearly so.",
+    "The entropy command prints how long attackers would take on average to \
+     guess a password with the entropy given with --bits, or else estimated as \
+     by the strengthen command for a password read without echoing it. For a \
+     password, the character classes it uses are reported too, with its naive \
+     entropy, which it would have were it drawn at random from them and which \
+     overstates the strength of passwords people choose. The attackers are \
+     online-throttled, a login form allowing 100 guesses an hour; \
+     offline-bcrypt-gpu, 8 GPUs guessing 50000 bcrypt hashes of cost 10 a \
+     second; and offline-md5-cluster, 100 GPUs guessing 1.6e13 MD5 hashes a \
+     second. --attacker adds an attacker making the given number of guesses a \
+     second, or changes the rate of one of these.",
+    "With --zxcvbn, the entropy command instead estimates, as zxcvbn does, the \
+     guesses an attacker trying the patterns people use would need: common \
+     passwords and words, forwards, backwards, capitalised or with l33t \
+     substitutions; runs of adjacent keys; repeats; sequences; and years and \
+     dates, with the rest guessed character by character. The parts of the \
+     password matched are reported by position, without their characters, and \
+     the times are those to make the guesses. Only short built-in lists of \
+     words ship; --dictionary adds a list of one word per line, from most to \
+     least common.",
+    "The score command checks the passwords on standard input, one per \
+     line, as libpwquality does, with the settings of \
+     /etc/security/pwquality.conf or the file given with --config. Each \
+     password that passes is given a score from 0 at the minimum length to \
+     100 at twice that, and each that fails the reason. The dictionary \
+     check looks words up in the plain word list given with --wordlist \
+     rather than a cracklib dictionary, and --user names the user whose \
+     name the password must not contain.",
+    "The insert-pass command generates one password as the options for \
+     generating passwords describe and puts it into the entry of that name \
+     in the password store of pass, in PASSWORD_STORE_DIR or \
+     ~/.password-store, encrypted with gpg to the keys in the nearest \
+     .gpg-id file, and prints it. Each --field adds a line of the name, a \
+     colon and the value after the password. An existing entry is only \
+     replaced with -f, or with --in-place, which replaces its first line \
+     and keeps the others. The change is committed if the store is a git \
+     repository.",
+    "The hibp command reads passwords from standard input, one per line, \
+     and reports for each whether it appears in breaches known to Have I \
+     Been Pwned, checking them as --check-hibp does, or with --offline as \
+     --check-offline does. It fails if any does. The hibp build command \
+     builds a Bloom filter from a Pwned Passwords SHA-1 dump ordered by \
+     hash, for checks on machines without network access; it is much \
+     smaller than the dump, at the cost of reporting one in 1000 other \
+     passwords as breached by default, or the rate given with --fp-rate.",
+    "With --store kdbx, the database password is asked for on the \
+     terminal, twice if the database is new. A new database is encrypted \
+     with ChaCha20 under a key derived with Argon2id; existing databases \
+     keep their settings. With credentials the generated username and any \
+     TOTP secret go into the entry too, and with -n each password gets an \
+     entry of its own.",
+    "With --store bitwarden, the items are created with bw create item, \
+     and bw must be logged in with its vault unlocked, with the session \
+     key that bw unlock prints exported as BW_SESSION. With credentials the \
+     generated username and any TOTP secret go into the item too, and with \
+     -n each password gets an item of its own.",
+    "With --store vault, Vault is reached at VAULT_ADDR with the token in \
+     VAULT_TOKEN or ~/.vault-token, and in the namespace in \
+     VAULT_NAMESPACE if set. The secret is read before the password is \
+     generated and written back with check-and-set, so the write fails if \
+     the secret changed in between.",
+    "With --store systemd-creds, the credential is encrypted without a \
+     trailing newline, with the host key, the TPM or both as systemd-creds \
+     chooses, for a unit's LoadCredentialEncrypted= or \
+     /etc/credstore.encrypted.",
+    "The keyfile command writes a keyfile of random bytes for cryptsetup, \
+     4096 bytes by default or the size given with --size, from 32 bytes to \
+     8 MiB. The file is created readable only by its owner. An existing \
+     file is only replaced with -f.",
+    "The native-host command serves a browser extension over the native \
+     messaging protocol of Chrome and Firefox; the host manifest's path \
+     must name a script that runs it. Each request is a JSON object with \
+     op generate and the fields of a JSON specification for --batch, or \
+     with op check and a password. The response gives the passwords and \
+     their entropy_bits, or for a check the estimate of the strengthen \
+     command as entropy_bits. With --check-hibp or --check-offline, \
+     breached passwords are redrawn, and a check gives whether the \
+     password is breached. An id in a request is returned in its \
+     response, and a failed request gets an error.",
+    "The serve command answers POST /generate on 127.0.0.1:7878, or the \
+     loopback address and port given with --listen, for tools on the same \
+     host. The body is a JSON specification as for --batch, and the response a \
+     JSON object with the passwords and their entropy_bits, or an error. \
+     Requests must send one of the tokens in the file, which only its owner \
+     may read, as a bearer token; the file has one to a line, after a name and \
+     a space if it is to have one. At most 60 requests a minute are answered \
+     from each client address, or the limit given with --rate, and with \
+     --quota, at most so many passwords are generated with each token in each \
+     window. GET /status, with any of the tokens, gives the requests counted \
+     and refused, and the passwords generated and quota remaining for each \
+     token. Needs a build with the server feature.",
+    "The grpc command serves the gRPC service of proto/passgen.proto on \
+     127.0.0.1:7879, or the loopback address and port given with --listen: \
+     Generate streams count passwords to a specification as for --batch, Check \
+     estimates a password's entropy, and Entropy gives a specification's \
+     entropy and the size of its character set. Calls must send one of the \
+     tokens in the file as a bearer token in their authorization metadata, as \
+     for serve, and are limited by --rate and --quota in the same way, a \
+     Generate call's passwords all counting against the quota when it starts. \
+     Passwords are generated no faster than the client reads them. Needs a \
+     build with the grpc feature.",
+    "The daemon command serves requests as the native-host command does on a \
+     Unix socket only its owner can connect to, $XDG_RUNTIME_DIR/passgen.sock \
+     or the path given with --socket, one JSON object per line answered by one \
+     per line. With --wordlist, requests with op passphrase get passphrases of \
+     words from the file, taking the last field of each line, with 6 words \
+     joined by - unless words or separator say otherwise. The word list and \
+     breach list are loaded once when the daemon starts. With --rate and \
+     --quota, the daemon's requests, which all come from its owner, are \
+     limited together, and op status gives what has been counted.",
+    "The limits of --rate and --quota are a count in a window, as in 60/m or \
+     1000/12h, of seconds, minutes, hours or days with a suffix of s, m, h or \
+     d; a count alone is a minute's. A rate allows bursts of up to the count, \
+     refilled evenly over the window. A quota's window starts with the first \
+     password generated in it, and a request that would go over the quota is \
+     refused whole, with status 429 from serve.",
+    "The retrieve command prints the secret saved into the platform \
+     keyring by --store keyring under the service and account.",
+    "The recover command reads the shares of one password printed by \
+     --split from standard input, one per line, and prints the password.",
+    "The man command prints this manual in roff format for installation as \
+     a man page.",
+];
+
+pub const EXIT_STATUSES: &[(i32, &str)] = &[
+    (0, "success"),
+    (crate::EXIT_FAILURE, "other failure"),
+    (crate::EXIT_USAGE, "usage error or invalid charset specification"),
+    (
+        crate::EXIT_POLICY,
+        "policy cannot be satisfied, at all or within the limits",
+    ),
+    (crate::EXIT_ENTROPY, "password entropy below --min-entropy"),
+    (crate::EXIT_RNG, "random number generator failure"),
+    (crate::EXIT_IO, "I/O error"),
+    (crate::EXIT_HEALTH, "random number generator failed a health test"),
+];
+
+pub const WARNINGS_NOTE: &str =
+    "Warnings are written to standard error prefixed with \"warning: \".";
+
+const LINE_WIDTH: usize = 78;
+const HELP_COLUMN: usize = 24;
+
+pub fn render(program_name: &str) -> String {
+    let mut text = String::from("\n");
+    let label = tr("Usage:");
+    let indent = " ".repeat(label.chars().count());
+    for (idx, synopsis) in SYNOPSES.iter().enumerate() {
+        let label = if idx == 0 { label } else { &indent };
+        let synopsis = render_synopsis(synopsis);
+        text.push_str(&format!("  {} {} {}\n", label, program_name, synopsis));
+    }
+    text.push_str(&format!("\n  {}\n", tr(DESCRIPTION)));
+    for section in SECTIONS {
+        text.push_str(&format!("\n  {}:\n", tr(section.title)));
+        for option in section.options {
+            let mut left = format!("    {}", option.flags);
+            if let Some(arg) = option.arg {
+                left.push(' ');
+                left.push_str(arg);
+            }
+            let width = LINE_WIDTH - HELP_COLUMN;
+            let lines = wrap(tr(option.help), width);
+            if left.chars().count() + 2 > HELP_COLUMN {
+                text.push_str(&format!("{}\n", left));
+                left.clear();
+            }
+            for line in lines {
+                text.push_str(&format!("{:2$}{}\n", left, line, HELP_COLUMN));
+                left.clear();
+            }
+        }
+    }
+    for note in NOTES {
+        text.push('\n');
+        for line in wrap(tr(note), LINE_WIDTH - 2) {
+            text.push_str(&format!("  {}\n", line));
+        }
+    }
+    text.push_str(&format!("\n  {}:\n", tr("Exit status")));
+    for (code, meaning) in EXIT_STATUSES {
+        text.push_str(&format!("    {}  {}\n", code, tr(meaning)));
+    }
+    text.push('\n');
+    for line in wrap(tr(WARNINGS_NOTE), LINE_WIDTH - 2) {
+        text.push_str(&format!("  {}\n", line));
+    }
+    text
+}
+
+fn render_synopsis(words: &[Word]) -> String {
+    let words =
+        words.iter().map(|word| match word {
+            Word::Text(text) => text.to_string(),
+            Word::Flag(flag) => {
+                let mut text = flag.spellings.to_string();
+                if let Some(arg) = flag.arg {
+                    text.push(' ');
+                    text.push_str(arg);
+                }
+                if !flag.required {
+                    text = format!("[{}]", text);
+                }
+                if flag.repeated {
+                    text.push_str("...");
+                }
+                text
+            },
+        });
+    words.collect::<Vec<_>>().join(" ")
+}
+
+/// An argument as a parser reads it.
+pub enum Arg<'a> {
+    /// An option the table shows, by the spelling given, with its argument,
+    /// or "" if it takes none.
+    Flag(&'static str, &'a str),
+    /// Anything else: an operand, or an option for the main parser.
+    Other(&'a String),
+}
+
+/// Reads the arguments of a command as its synopses show them, returning
+/// None if an option lacks its argument.
+pub fn parse<'a>(
+    synopses: &[&[Word]],
+    args: &'a [String],
+) -> Option<Vec<Arg<'a>>> {
+    let flags =
+        synopses.iter()
+            .flat_map(|words| words.iter())
+            .filter_map(|word| match word {
+                Word::Flag(flag) => Some(flag),
+                Word::Text(_) => None,
+            });
+    let spellings =
+        flags.flat_map(|flag| {
+            flag.spellings.split('|').map(|spelling| (spelling, flag.arg))
+        });
+    read(spellings.collect(), args)
+}
+
+/// Reads the arguments as the options of the sections below.
+pub fn parse_options(args: &[String]) -> Option<Vec<Arg<'_>>> {
+    let spellings =
+        SECTIONS.iter()
+            .flat_map(|section| section.options)
+            .flat_map(|option| {
+                option.flags.split(", ").map(|spelling| (spelling, option.arg))
+            });
+    read(spellings.collect(), args)
+}
+
+fn read<'a>(
+    spellings: Vec<(&'static str, Option<&'static str>)>,
+    args: &'a [String],
+) -> Option<Vec<Arg<'a>>> {
+    let mut read = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let found = spellings.iter().find(|(spelling, _)| spelling == arg);
+        match found {
+            Some((spelling, Some(_))) => {
+                read.push(Arg::Flag(spelling, args.next()?));
+            },
+            Some((spelling, None)) => read.push(Arg::Flag(spelling, "")),
+            None => read.push(Arg::Other(arg)),
+        }
+    }
+    Some(read)
+}
+
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let len = line.chars().count() + 1 + word.chars().count();
+        if !line.is_empty() && len > width {
+            lines.push(line);
+            line = String::new();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+pub fn render_man() -> String {
+    let mut text = format!(
+        ".TH PASSGEN 1 \"\" \"passgen {}\" \"User Commands\"\n",
+        env!("CARGO_PKG_VERSION"),
+    );
+    let summary = DESCRIPTION.trim_end_matches('.').to_lowercase();
+    text.push_str(&format!(".SH NAME\npassgen \\- {}\n", roff(&summary)));
+    text.push_str(".SH SYNOPSIS\n");
+    for synopsis in SYNOPSES {
+        let synopsis = roff(&render_synopsis(synopsis));
+        text.push_str(&format!(".B passgen\n{}\n.br\n", synopsis));
+    }
+    text.push_str(&format!(".SH DESCRIPTION\n{}\n", roff(DESCRIPTION)));
+    for (idx, section) in SECTIONS.iter().enumerate() {
+        if idx == 0 {
+            text.push_str(".SH OPTIONS\n");
+        } else {
+            text.push_str(&format!(".SS {}\n", roff(section.title)));
+        }
+        for option in section.options {
+            text.push_str(&format!(".TP\n\\fB{}\\fR", roff(option.flags)));
+            if let Some(arg) = option.arg {
+                text.push_str(&format!(" \\fI{}\\fR", roff(arg)));
+            }
+            text.push_str(&format!("\n{}\n", roff(option.help)));
+        }
+    }
+    text.push_str(".SH NOTES\n");
+    for note in NOTES {
+        text.push_str(&format!(".PP\n{}\n", roff(note)));
+    }
+    text.push_str(".SH \"EXIT STATUS\"\n");
+    for (code, meaning) in EXIT_STATUSES {
+        text.push_str(&format!(".TP\n.B {}\n{}\n", code, roff(meaning)));
+    }
+    text.push_str(&format!(".PP\n{}\n", roff(WARNINGS_NOTE)));
+    text
+}
+
+fn roff(text: &str) -> String {
+    let text = text.replace('\\', "\\e").replace('-', "\\-");
+    if text.starts_with('.') || text.starts_with('\'') {
+        format!("\\&{}", text)
+    } else {
+        text
+    }
+}
@@ -0,0 +1,174 @@
+use rand_core::CryptoRngCore;
+use std::fmt;
+use std::num::NonZeroUsize;
+use tracing::debug;
+
+use crate::generator::Generator;
+use crate::sampler::random_below;
+use crate::{invalid_spec, Result};
+
+const ADJECTIVES: &[&str] = &[
+    "amber", "ample", "azure", "bold", "brave", "brisk", "calm", "clever",
+    "cosmic", "crisp", "dapper", "eager", "early", "fancy", "fierce", "fluffy",
+    "gentle", "giant", "glad", "golden", "grand", "happy", "hidden", "humble",
+    "icy", "jolly", "keen", "kind", "lively", "lucky", "lunar", "mellow",
+    "merry", "mighty", "misty", "modest", "nimble", "noble", "odd", "polar",
+    "proud", "quick", "quiet", "rapid", "rosy", "rustic", "shiny", "silent",
+    "silver", "sleepy", "smooth", "snowy", "solar", "spry", "steady", "sunny",
+    "swift", "tidy", "tiny", "vivid", "warm", "wild", "witty", "zesty",
+];
+
+const NOUNS: &[&str] = &[
+    "acorn", "badger", "beacon", "bison", "breeze", "canyon", "cedar",
+    "comet", "coral", "cricket", "delta", "dune", "eagle", "ember", "falcon",
+    "fern", "fjord", "forest", "fox", "galaxy", "gecko", "glacier", "harbor",
+    "hawk", "heron", "island", "jaguar", "kettle", "koala", "lagoon", "lark",
+    "lemur", "lynx", "maple", "meadow", "meteor", "moose", "nebula", "otter",
+    "owl", "panda", "pebble", "pine", "planet", "puffin", "quartz", "raven",
+    "reef", "river", "robin", "sparrow", "spruce", "summit", "thistle",
+    "tiger", "tulip", "tundra", "valley", "walrus", "willow", "wombat", "yak",
+    "zebra", "zephyr",
+];
+
+const CONSONANTS: &[u8] = b"bdfghjklmnprstvz";
+const VOWELS: &[u8] = b"aeiou";
+const NUMBER_DIGITS: u32 = 2;
+const SEPARATOR: char = '-';
+// The fixed characters of a words username: two separators and the number.
+const WORD_OVERHEAD: usize = 2 + NUMBER_DIGITS as usize;
+
+/// The style of generated usernames.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UsernameStyle {
+    /// An adjective, a noun and a two-digit number from built-in lists,
+    /// joined by hyphens, such as `brave-otter-42`.
+    #[default]
+    Words,
+    /// Alternating consonants and vowels, such as `tadulomi`.
+    Pronounceable,
+}
+
+impl UsernameStyle {
+    /// Returns the default maximum length of usernames of this style.
+    pub fn default_max_length(self) -> NonZeroUsize {
+        let max_len =
+            match self {
+                UsernameStyle::Words => 20,
+                UsernameStyle::Pronounceable => 8,
+            };
+        NonZeroUsize::new(max_len).unwrap()
+    }
+}
+
+impl fmt::Display for UsernameStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UsernameStyle::Words => write!(f, "words"),
+            UsernameStyle::Pronounceable => write!(f, "pronounceable"),
+        }
+    }
+}
+
+/// Generates usernames for throwaway or service accounts. Usernames carry
+/// little entropy and are not meant to be secret.
+#[derive(Clone, Debug)]
+pub struct UsernameGenerator {
+    style: UsernameStyle,
+    max_len: NonZeroUsize,
+}
+
+impl UsernameGenerator {
+    /// Creates a generator for usernames of this style no longer than the
+    /// maximum, which pronounceable usernames always reach. Fails if no
+    /// username of the style fits.
+    pub fn new(style: UsernameStyle, max_len: NonZeroUsize) -> Result<Self> {
+        let generator = UsernameGenerator { style, max_len };
+        if style == UsernameStyle::Words && generator.num_word_pairs() == 0 {
+            let msg =
+                format!("no {} username fits in {} characters", style, max_len);
+            return Err(invalid_spec(msg));
+        }
+        Ok(generator)
+    }
+
+    /// Returns the style of the usernames.
+    pub fn style(&self) -> UsernameStyle {
+        self.style
+    }
+
+    /// Returns the maximum length of a username.
+    pub fn max_length(&self) -> NonZeroUsize {
+        self.max_len
+    }
+
+    fn fits(&self, adjective: &str, noun: &str) -> bool {
+        adjective.len() + noun.len() + WORD_OVERHEAD <= self.max_len.get()
+    }
+
+    fn num_word_pairs(&self) -> usize {
+        ADJECTIVES.iter()
+            .map(|adjective| {
+                NOUNS.iter().filter(|noun| self.fits(adjective, noun)).count()
+            })
+            .sum()
+    }
+}
+
+impl Generator for UsernameGenerator {
+    fn generate(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+    ) -> Result<()> {
+        match self.style {
+            UsernameStyle::Words => {
+                // Redrawing pairs that are too long keeps the rest equally
+                // likely.
+                let (adjective, noun) =
+                    loop {
+                        let adjective =
+                            ADJECTIVES[random_below(rng, ADJECTIVES.len())?];
+                        let noun = NOUNS[random_below(rng, NOUNS.len())?];
+                        if self.fits(adjective, noun) {
+                            break (adjective, noun);
+                        }
+                        debug!("username too long, retrying");
+                    };
+                let bound = 10usize.pow(NUMBER_DIGITS);
+                let number = random_below(rng, bound)?;
+                out.push_str(adjective);
+                out.push(SEPARATOR);
+                out.push_str(noun);
+                out.push(SEPARATOR);
+                let width = NUMBER_DIGITS as usize;
+                out.push_str(&format!("{:0width$}", number, width = width));
+            },
+            UsernameStyle::Pronounceable => {
+                for idx in 0..self.max_len.get() {
+                    let letters =
+                        if idx % 2 == 0 { CONSONANTS } else { VOWELS };
+                    let letter = letters[random_below(rng, letters.len())?];
+                    out.push(char::from(letter));
+                }
+            },
+        }
+        Ok(())
+    }
+
+    fn entropy_bits(&self) -> f64 {
+        match self.style {
+            UsernameStyle::Words => {
+                let num_pairs = self.num_word_pairs() as f64;
+                num_pairs.log2() + NUMBER_DIGITS as f64 * 10f64.log2()
+            },
+            UsernameStyle::Pronounceable => {
+                let max_len = self.max_len.get();
+                let num_consonants = max_len.div_ceil(2) as f64;
+                let num_vowels = (max_len / 2) as f64;
+                num_consonants * (CONSONANTS.len() as f64).log2()
+                    + num_vowels * (VOWELS.len() as f64).log2()
+            },
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use rand_core::CryptoRngCore;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::generator::Generator;
+use crate::memory::SecretBuf;
+use crate::{rng_failure, Result};
+
+const UUID_LEN: usize = 36;
+
+/// The version of the UUIDs to generate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum UuidVersion {
+    /// Version 4: 122 random bits.
+    #[default]
+    V4,
+    /// Version 7: the Unix time in milliseconds followed by 74 random bits,
+    /// so that identifiers sort by creation time.
+    V7,
+}
+
+impl fmt::Display for UuidVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UuidVersion::V4 => write!(f, "4"),
+            UuidVersion::V7 => write!(f, "7"),
+        }
+    }
+}
+
+/// Generates UUIDs in the hyphenated lowercase form of RFC 9562, which
+/// supersedes RFC 4122.
+#[derive(Clone, Debug)]
+pub struct UuidGenerator {
+    version: UuidVersion,
+}
+
+impl UuidGenerator {
+    /// Creates a generator for UUIDs of this version.
+    pub fn new(version: UuidVersion) -> Self {
+        UuidGenerator { version }
+    }
+
+    /// Returns the version of the UUIDs.
+    pub fn version(&self) -> UuidVersion {
+        self.version
+    }
+
+    /// Returns the length of each UUID in characters.
+    pub fn length(&self) -> usize {
+        UUID_LEN
+    }
+}
+
+impl Generator for UuidGenerator {
+    fn generate(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        out: &mut String,
+    ) -> Result<()> {
+        let mut bytes = SecretBuf::zeroed(16);
+        rng.try_fill_bytes(&mut bytes).map_err(rng_failure)?;
+        if self.version == UuidVersion::V7 {
+            // A clock before the epoch gives a timestamp of zero rather than
+            // an error, since the identifier is still unique.
+            let millis =
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_millis() as u64);
+            bytes[..6].copy_from_slice(&millis.to_be_bytes()[2..]);
+        }
+        let version = match self.version {
+            UuidVersion::V4 => 0x40,
+            UuidVersion::V7 => 0x70,
+        };
+        bytes[6] = (bytes[6] & 0x0f) | version;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+        for (idx, byte) in bytes.iter().enumerate() {
+            if [4, 6, 8, 10].contains(&idx) {
+                out.push('-');
+            }
+            out.push_str(&format!("{:02x}", byte));
+        }
+        Ok(())
+    }
+
+    fn entropy_bits(&self) -> f64 {
+        match self.version {
+            UuidVersion::V4 => 122.0,
+            UuidVersion::V7 => 74.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::ChaCha20Rng;
+    use rand::{CryptoRng, RngCore, SeedableRng};
+    use std::thread;
+    use std::time::Duration;
+
+    // Fills every byte with the same value, to show which bits the version
+    // and variant overwrite.
+    struct Constant(u8);
+
+    impl RngCore for Constant {
+        fn next_u32(&mut self) -> u32 {
+            u32::from_ne_bytes([self.0; 4])
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            u64::from_ne_bytes([self.0; 8])
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            dest.fill(self.0);
+        }
+
+        fn try_fill_bytes(
+            &mut self,
+            dest: &mut [u8],
+        ) -> std::result::Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    impl CryptoRng for Constant {}
+
+    fn uuid(version: UuidVersion, rng: &mut dyn CryptoRngCore) -> String {
+        let mut out = String::new();
+        UuidGenerator::new(version).generate(rng, &mut out).unwrap();
+        assert_eq!(out.len(), UUID_LEN);
+        out
+    }
+
+    fn millis() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis()
+            as u64
+    }
+
+    #[test]
+    fn version_and_variant_bits_are_set() {
+        for byte in [0x00, 0xff] {
+            let v4 = uuid(UuidVersion::V4, &mut Constant(byte));
+            let v7 = uuid(UuidVersion::V7, &mut Constant(byte));
+            assert_eq!(&v4[14..15], "4");
+            assert_eq!(&v7[14..15], "7");
+            // The variant of RFC 9562 is the bits 10 at the top of byte 8.
+            let variant = if byte == 0 { "8" } else { "b" };
+            assert_eq!(&v4[19..20], variant);
+            assert_eq!(&v7[19..20], variant);
+        }
+        let v4 = uuid(UuidVersion::V4, &mut Constant(0xff));
+        assert_eq!(v4, "ffffffff-ffff-4fff-bfff-ffffffffffff");
+        let v4 = uuid(UuidVersion::V4, &mut Constant(0x00));
+        assert_eq!(v4, "00000000-0000-4000-8000-000000000000");
+    }
+
+    #[test]
+    fn uuids_are_hyphenated_lowercase_hex() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        for _ in 0..100 {
+            let uuid = uuid(UuidVersion::V4, &mut rng);
+            for (idx, c) in uuid.char_indices() {
+                if [8, 13, 18, 23].contains(&idx) {
+                    assert_eq!(c, '-');
+                } else {
+                    assert!(matches!(c, '0'..='9' | 'a'..='f'));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn v7_uuids_begin_with_the_time_and_sort_by_it() {
+        let mut rng = ChaCha20Rng::seed_from_u64(0);
+        let before = millis();
+        let first = uuid(UuidVersion::V7, &mut rng);
+        let after = millis();
+        let timestamp = first[..13].replace('-', "");
+        let timestamp = u64::from_str_radix(&timestamp, 16).unwrap();
+        assert!((before..=after).contains(&timestamp));
+        let mut uuids = vec![first];
+        for _ in 0..3 {
+            thread::sleep(Duration::from_millis(2));
+            uuids.push(uuid(UuidVersion::V7, &mut rng));
+        }
+        assert!(uuids.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
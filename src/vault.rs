@@ -0,0 +1,240 @@
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use zeroize::Zeroizing;
+
+use crate::i18n::{tr, trf};
+
+// Secrets are written to a KV version 2 engine through Vault's HTTP API, at
+// VAULT_ADDR with the token in VAULT_TOKEN or, where the vault command
+// keeps it, ~/.vault-token. The first component of the path names the
+// engine's mount. The secret's other keys are kept: it is read before the
+// password is generated, and the new version written with check-and-set,
+// so that a write made in between fails rather than being lost.
+
+const STATUS_NOT_FOUND: u16 = 404;
+
+pub struct KvSecret {
+    client: Client,
+    key: String,
+    data: BTreeMap<String, Value>,
+    // The version the secret was read at, or 0 if it does not exist.
+    version: u64,
+}
+
+#[derive(Deserialize)]
+struct ReadResponse {
+    data: ReadData,
+}
+
+#[derive(Deserialize)]
+struct ReadData {
+    // Null if the latest version was deleted.
+    data: Option<BTreeMap<String, Value>>,
+    metadata: Metadata,
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+    version: u64,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    errors: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct WriteRequest<'a> {
+    options: WriteOptions,
+    data: BTreeMap<&'a str, Field<'a>>,
+}
+
+#[derive(Serialize)]
+struct WriteOptions {
+    cas: u64,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Field<'a> {
+    Kept(&'a Value),
+    New(&'a str),
+}
+
+impl KvSecret {
+    /// Reads the secret at the path, as mount/name, to write the key of.
+    pub fn open(path: &str, key: String) -> Result<Self> {
+        let (mount, name) =
+            path.trim_matches('/')
+                .split_once('/')
+                .filter(|(mount, name)| {
+                    !mount.is_empty()
+                        && !name.split('/').any(|component| {
+                            component.is_empty()
+                                || component == "."
+                                || component == ".."
+                        })
+                })
+                .ok_or_else(|| {
+                    anyhow!(trf("invalid Vault path: \"{}\"", &[&path]))
+                })?;
+        let addr =
+            env::var("VAULT_ADDR")
+                .ok()
+                .filter(|addr| !addr.is_empty())
+                .ok_or_else(|| anyhow!(tr("VAULT_ADDR is not set")))?;
+        let addr = addr.trim_end_matches('/');
+        let url = format!("{}/v1/{}/data/{}", addr, mount, name);
+        let namespace =
+            env::var("VAULT_NAMESPACE").ok().filter(|ns| !ns.is_empty());
+        let mut secret = KvSecret {
+            client: Client::new(url, token()?, namespace),
+            key,
+            data: BTreeMap::new(),
+            version: 0,
+        };
+        let (status, body) = secret.client.get()?;
+        if status == STATUS_NOT_FOUND {
+            // A secret whose latest version was deleted still has metadata,
+            // and its next version must follow on from it.
+            if let Ok(response) = serde_json::from_str::<ReadResponse>(&body) {
+                secret.version = response.data.metadata.version;
+            }
+            return Ok(secret);
+        }
+        check_status(status, &body)?;
+        let response = serde_json::from_str::<ReadResponse>(&body)?;
+        secret.data = response.data.data.unwrap_or_default();
+        secret.version = response.data.metadata.version;
+        Ok(secret)
+    }
+
+    /// Writes a new version of the secret with the value under the key.
+    pub fn write(&self, value: &str) -> Result<()> {
+        let mut data =
+            self.data.iter()
+                .map(|(key, value)| (key.as_str(), Field::Kept(value)))
+                .collect::<BTreeMap<_, _>>();
+        data.insert(&self.key, Field::New(value));
+        let request =
+            WriteRequest { options: WriteOptions { cas: self.version }, data };
+        let body = Zeroizing::new(serde_json::to_string(&request)?);
+        let (status, body) = self.client.post(&body)?;
+        check_status(status, &body)
+    }
+}
+
+// Sends requests to the URL of the secret's data.
+#[cfg(feature = "vault")]
+struct Client {
+    agent: ureq::Agent,
+    url: String,
+    token: Zeroizing<String>,
+    namespace: Option<String>,
+}
+
+#[cfg(not(feature = "vault"))]
+struct Client;
+
+impl Client {
+    #[cfg(feature = "vault")]
+    fn new(
+        url: String,
+        token: Zeroizing<String>,
+        namespace: Option<String>,
+    ) -> Self {
+        let agent =
+            ureq::Agent::config_builder()
+                .http_status_as_error(false)
+                .build()
+                .into();
+        Client { agent, url, token, namespace }
+    }
+
+    #[cfg(feature = "vault")]
+    fn get(&self) -> Result<(u16, Zeroizing<String>)> {
+        let mut request =
+            self.agent.get(&self.url).header("X-Vault-Token", &*self.token);
+        if let Some(namespace) = &self.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+        read_response(request.call())
+    }
+
+    #[cfg(feature = "vault")]
+    fn post(&self, body: &str) -> Result<(u16, Zeroizing<String>)> {
+        let mut request =
+            self.agent.post(&self.url).header("X-Vault-Token", &*self.token);
+        if let Some(namespace) = &self.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+        read_response(request.content_type("application/json").send(body))
+    }
+
+    #[cfg(not(feature = "vault"))]
+    fn new(
+        _url: String,
+        _token: Zeroizing<String>,
+        _namespace: Option<String>,
+    ) -> Self {
+        Client
+    }
+
+    #[cfg(not(feature = "vault"))]
+    fn get(&self) -> Result<(u16, Zeroizing<String>)> {
+        Err(anyhow!(tr("this build does not support --store vault")))
+    }
+
+    #[cfg(not(feature = "vault"))]
+    fn post(&self, _body: &str) -> Result<(u16, Zeroizing<String>)> {
+        Err(anyhow!(tr("this build does not support --store vault")))
+    }
+}
+
+fn token() -> Result<Zeroizing<String>> {
+    let token = env::var("VAULT_TOKEN").ok().filter(|token| !token.is_empty());
+    if let Some(token) = token {
+        return Ok(Zeroizing::new(token));
+    }
+    let path =
+        env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(".vault-token"))
+            .filter(|path| path.is_file())
+            .ok_or_else(|| {
+                anyhow!(tr("no Vault token in VAULT_TOKEN or ~/.vault-token"))
+            })?;
+    let context = || trf("could not read \"{}\"", &[&path.display()]);
+    let token =
+        Zeroizing::new(fs::read_to_string(&path).with_context(context)?);
+    Ok(Zeroizing::new(token.trim().to_string()))
+}
+
+#[cfg(feature = "vault")]
+fn read_response(
+    response: Result<ureq::http::Response<ureq::Body>, ureq::Error>,
+) -> Result<(u16, Zeroizing<String>)> {
+    let read = || -> std::result::Result<_, ureq::Error> {
+        let mut response = response?;
+        let body = Zeroizing::new(response.body_mut().read_to_string()?);
+        Ok((response.status().as_u16(), body))
+    };
+    read().map_err(|err| anyhow!(trf("could not reach Vault: {}", &[&err])))
+}
+
+fn check_status(status: u16, body: &str) -> Result<()> {
+    if (200..300).contains(&status) {
+        return Ok(());
+    }
+    let errors =
+        serde_json::from_str::<ErrorResponse>(body)
+            .map(|response| response.errors.join("; "))
+            .ok()
+            .filter(|errors| !errors.is_empty())
+            .unwrap_or_else(|| status.to_string());
+    Err(anyhow!(trf("Vault refused the request: {}", &[&errors])))
+}
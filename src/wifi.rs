@@ -0,0 +1,142 @@
+use sha1_smol::Sha1;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{invalid_spec, Result};
+
+const BLOCK_LEN: usize = 64;
+const DIGEST_LEN: usize = 20;
+const PSK_LEN: usize = 32;
+const PSK_ITERATIONS: u32 = 4096;
+
+/// The range of lengths of a WPA passphrase, in characters.
+pub const WPA_PASSPHRASE_LEN: std::ops::RangeInclusive<usize> = 8..=63;
+/// The maximum length of an SSID, in bytes.
+pub const MAX_SSID_LEN: usize = 32;
+
+// HMAC-SHA1 with a key no longer than a block, as PBKDF2 uses it.
+struct HmacSha1 {
+    inner_pad: [u8; BLOCK_LEN],
+    outer_pad: [u8; BLOCK_LEN],
+}
+
+impl HmacSha1 {
+    fn new(key: &[u8]) -> Self {
+        let mut inner_pad = [0x36; BLOCK_LEN];
+        let mut outer_pad = [0x5c; BLOCK_LEN];
+        for (idx, &byte) in key.iter().enumerate() {
+            inner_pad[idx] ^= byte;
+            outer_pad[idx] ^= byte;
+        }
+        HmacSha1 { inner_pad, outer_pad }
+    }
+
+    fn mac(&self, message: &[&[u8]]) -> [u8; DIGEST_LEN] {
+        let mut inner = Sha1::new();
+        inner.update(&self.inner_pad);
+        for part in message {
+            inner.update(part);
+        }
+        let mut inner_digest = inner.digest().bytes();
+        let mut outer = Sha1::new();
+        outer.update(&self.outer_pad);
+        outer.update(&inner_digest);
+        inner_digest.zeroize();
+        outer.digest().bytes()
+    }
+}
+
+impl Drop for HmacSha1 {
+    fn drop(&mut self) {
+        self.inner_pad.zeroize();
+        self.outer_pad.zeroize();
+    }
+}
+
+/// Derives the 256-bit WPA pre-shared key for a passphrase and SSID, as
+/// PBKDF2-HMAC-SHA1 with 4096 iterations per IEEE 802.11i. Fails if the
+/// passphrase is not 8 to 63 printable ASCII characters or the SSID is
+/// empty or longer than 32 bytes.
+pub fn wpa_psk(
+    passphrase: &str,
+    ssid: &str,
+) -> Result<Zeroizing<[u8; PSK_LEN]>> {
+    let printable =
+        passphrase.bytes().all(|byte| (b' '..=b'~').contains(&byte));
+    if !printable || !WPA_PASSPHRASE_LEN.contains(&passphrase.len()) {
+        let msg = "WPA passphrase must be 8 to 63 printable ASCII characters";
+        return Err(invalid_spec(msg));
+    }
+    if ssid.is_empty() || ssid.len() > MAX_SSID_LEN {
+        return Err(invalid_spec("SSID must be 1 to 32 bytes long"));
+    }
+    let hmac = HmacSha1::new(passphrase.as_bytes());
+    let mut psk = Zeroizing::new([0u8; PSK_LEN]);
+    for (idx, chunk) in psk.chunks_mut(DIGEST_LEN).enumerate() {
+        let block_idx = (idx as u32 + 1).to_be_bytes();
+        let mut block = hmac.mac(&[ssid.as_bytes(), &block_idx]);
+        let mut sum = block;
+        for _ in 1..PSK_ITERATIONS {
+            block = hmac.mac(&[&block]);
+            for (acc, byte) in sum.iter_mut().zip(&block) {
+                *acc ^= byte;
+            }
+        }
+        chunk.copy_from_slice(&sum[..chunk.len()]);
+        block.zeroize();
+        sum.zeroize();
+    }
+    Ok(psk)
+}
+
+/// Returns the payload of a QR code that joins a WPA network, in the format
+/// phones read from the camera.
+pub fn wifi_qr_payload(ssid: &str, passphrase: &str, hidden: bool) -> String {
+    let escape = |text: &str| {
+        let mut escaped = String::new();
+        for ch in text.chars() {
+            if matches!(ch, '\\' | ';' | ',' | '"' | ':') {
+                escaped.push('\\');
+            }
+            escaped.push(ch);
+        }
+        escaped
+    };
+    let mut payload =
+        format!("WIFI:T:WPA;S:{};P:{};", escape(ssid), escape(passphrase));
+    if hidden {
+        payload.push_str("H:true;");
+    }
+    payload.push(';');
+    payload
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    // The test vectors of IEEE 802.11i, annex H.4.
+    #[test]
+    fn wpa_psk_matches_ieee_802_11i_vectors() {
+        assert_eq!(
+            hex(&*wpa_psk("password", "IEEE").unwrap()),
+            "f42c6fc52df0ebef9ebb4b90b38a5f902e83fe1b135a70e23aed762e9710a12e",
+        );
+        assert_eq!(
+            hex(&*wpa_psk("ThisIsAPassword", "ThisIsASSID").unwrap()),
+            "0dc0d6eb90555ed6419756b9a15ec3e3209b63df707dd508d14581f8982721af",
+        );
+    }
+
+    #[test]
+    fn wpa_psk_rejects_invalid_passphrases_and_ssids() {
+        assert!(wpa_psk("short", "IEEE").is_err());
+        assert!(wpa_psk(&"x".repeat(64), "IEEE").is_err());
+        assert!(wpa_psk("pass\u{e9}word", "IEEE").is_err());
+        assert!(wpa_psk("password", "").is_err());
+        assert!(wpa_psk("password", &"x".repeat(33)).is_err());
+    }
+}
@@ -0,0 +1,86 @@
+use rand::rngs::OsRng;
+use rand::RngCore;
+use std::io;
+use std::process::Command;
+
+use crate::token::EntropyToken;
+use crate::{invalid_spec, PassgenError, Result};
+
+const YUBIKEY_CHALLENGE_LEN: usize = 32;
+
+/// A YubiKey slot configured for HMAC-SHA1 challenge-response, queried with
+/// random challenges through the `ykchalresp` tool.
+///
+/// Each response is keyed with a secret that never leaves the token, so it is
+/// unpredictable to anyone without the token even if they can predict the
+/// challenges.
+#[derive(Clone, Debug)]
+pub struct YubiKey {
+    slot: u8,
+}
+
+impl YubiKey {
+    /// Uses the given slot, 1 or 2.
+    pub fn new(slot: u8) -> Result<Self> {
+        if slot != 1 && slot != 2 {
+            let msg = format!("invalid YubiKey slot: {}", slot);
+            return Err(invalid_spec(msg));
+        }
+        Ok(YubiKey { slot })
+    }
+
+    fn respond(&self, challenge: &[u8]) -> io::Result<Vec<u8>> {
+        let challenge =
+            challenge.iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>();
+        let output =
+            Command::new("ykchalresp")
+                .arg(format!("-{}", self.slot))
+                .arg("-x")
+                .arg(challenge)
+                .output()
+                .map_err(|err| {
+                    let msg = format!("could not run ykchalresp: {}", err);
+                    io::Error::new(err.kind(), msg)
+                })?;
+        if !output.status.success() {
+            let msg = format!("ykchalresp exited with {}", output.status);
+            return Err(io::Error::other(msg));
+        }
+        let hex = String::from_utf8_lossy(&output.stdout);
+        let hex = hex.trim();
+        let invalid = || io::Error::other("invalid response from ykchalresp");
+        if hex.is_empty() || !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+            return Err(invalid());
+        }
+        (0..hex.len())
+            .step_by(2)
+            .map(|idx| u8::from_str_radix(&hex[idx..idx + 2], 16))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| invalid())
+    }
+}
+
+impl EntropyToken for YubiKey {
+    fn name(&self) -> &str {
+        "yubikey"
+    }
+
+    fn fill(&mut self, dest: &mut [u8]) -> Result<()> {
+        let mut filled = 0;
+        while filled < dest.len() {
+            let mut challenge = [0u8; YUBIKEY_CHALLENGE_LEN];
+            OsRng.try_fill_bytes(&mut challenge)
+                .map_err(PassgenError::RngFailure)?;
+            let response =
+                self.respond(&challenge)
+                    .map_err(rand::Error::new)
+                    .map_err(PassgenError::RngFailure)?;
+            let len = response.len().min(dest.len() - filled);
+            dest[filled..][..len].copy_from_slice(&response[..len]);
+            filled += len;
+        }
+        Ok(())
+    }
+}
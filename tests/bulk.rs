@@ -0,0 +1,43 @@
+use std::io::Read;
+use std::process::{Command, Stdio};
+
+// Bulk jobs draw from the random number generator billions of times, so
+// that anything with even a tiny chance of failing on each draw, such as
+// health tests run on every byte, all but certainly fails them.
+
+fn count_lines(num_passwords: &str, args: &[&str]) -> usize {
+    let mut child =
+        Command::new(env!("CARGO_BIN_EXE_passgen"))
+            .args(["-n", num_passwords])
+            .args(args)
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+    let mut stdout = child.stdout.take().unwrap();
+    let mut buffer = vec![0u8; 1 << 16];
+    let mut num_lines = 0;
+    loop {
+        let len = stdout.read(&mut buffer).unwrap();
+        if len == 0 {
+            break;
+        }
+        let newlines = buffer[..len].iter().filter(|&&byte| byte == b'\n');
+        num_lines += newlines.count();
+    }
+    assert!(child.wait().unwrap().success());
+    num_lines
+}
+
+#[test]
+fn bulk_job_completes() {
+    assert_eq!(count_lines("200000", &[]), 200_000);
+    assert_eq!(count_lines("200000", &["--rng", "chacha"]), 200_000);
+}
+
+// Takes many minutes, so is run only when asked for, as with
+// cargo test --release -- --ignored.
+#[test]
+#[ignore]
+fn hundred_million_passwords_complete() {
+    assert_eq!(count_lines("100000000", &[]), 100_000_000);
+}
@@ -0,0 +1,152 @@
+use std::fs;
+use std::path::PathBuf;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn passgen(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_passgen")).args(args).output().unwrap()
+}
+
+fn passgen_batch(args: &[&str], input: &str) -> Output {
+    let mut child =
+        Command::new(env!("CARGO_BIN_EXE_passgen"))
+            .arg("--batch")
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn lengths(output: &Output) -> Vec<usize> {
+    let stdout = String::from_utf8(output.stdout.clone()).unwrap();
+    stdout.lines().map(|line| line.chars().count()).collect()
+}
+
+#[test]
+fn positional_arguments_give_length_and_count() {
+    let output = passgen(&["-c", "a-z", "32", "5"]);
+    assert!(output.status.success());
+    assert_eq!(lengths(&output), [32; 5]);
+    let output = passgen(&["-c", "a-z", "12"]);
+    assert!(output.status.success());
+    assert_eq!(lengths(&output), [12]);
+    // Options and positional arguments may be mixed.
+    let output = passgen(&["-n", "2", "-c", "a-z", "16"]);
+    assert_eq!(lengths(&output), [16; 2]);
+}
+
+#[test]
+fn positional_arguments_are_checked() {
+    for args in [
+        &["12", "3", "4"][..],
+        &["-l", "10", "20"],
+        &["12", "-n", "3", "4"],
+        &["0"],
+        &["twelve"],
+    ] {
+        let output = passgen(args);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(output.stdout.is_empty());
+    }
+    let stderr = String::from_utf8(passgen(&["bogus"]).stderr).unwrap();
+    assert!(stderr.contains("unknown command: \"bogus\""));
+}
+
+// Each exit status documented in help and the manual page, with a job that
+// ends in it.
+#[test]
+fn documented_exit_statuses() {
+    let help = String::from_utf8(passgen(&["--help"]).stderr).unwrap();
+    let documented =
+        help.lines()
+            .skip_while(|line| line.trim() != "Exit status:")
+            .skip(1)
+            .map_while(|line| line.split_whitespace().next()?.parse().ok())
+            .collect::<Vec<i32>>();
+    assert_eq!(documented, [0, 1, 2, 3, 4, 5, 6, 7]);
+
+    let code = |args: &[&str]| passgen(args).status.code();
+    assert_eq!(code(&["-l", "16"]), Some(0));
+    let bad_profiles =
+        PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("bad-profiles.json");
+    fs::write(&bad_profiles, "{").unwrap();
+    let bad_profiles = bad_profiles.to_str().unwrap();
+    assert_eq!(
+        code(&["config", "validate", "--profiles", bad_profiles]),
+        Some(1),
+    );
+    assert_eq!(code(&["--bogus"]), Some(2));
+    assert_eq!(code(&["-c", "a-"]), Some(2));
+    // Four required classes cannot fit in two characters.
+    assert_eq!(code(&["-M", "SNCL", "-l", "2"]), Some(3));
+    assert_eq!(code(&["-l", "8", "--min-entropy", "200"]), Some(4));
+    assert_eq!(
+        code(&["-l", "8", "--audit-log", "/nonexistent/passgen/audit.log"]),
+        Some(6),
+    );
+    // With a false positive rate of one in two, a health test of bytes of
+    // full entropy fails within a few hundred.
+    assert_eq!(
+        code(&[
+            "-l", "64", "-n", "100",
+            "--health-min-entropy", "8", "--health-alpha", "-1",
+        ]),
+        Some(7),
+    );
+}
+
+// A device that cannot be opened is a failure of the random number
+// generator, but one that can may work, so this is only checked where
+// there is none.
+#[cfg(unix)]
+#[test]
+fn missing_hardware_generator_is_a_generator_failure() {
+    if fs::File::open("/dev/hwrng").is_ok() {
+        return;
+    }
+    let output = passgen(&["-l", "16", "--entropy-source", "hwrng"]);
+    assert_eq!(output.status.code(), Some(5));
+}
+
+#[test]
+fn batch_lines_override_command_line_defaults() {
+    let input = "-l 5\n\n{\"length\": 7, \"count\": 2}\n-c 0-9\n";
+    let output = passgen_batch(&["-l", "20", "-n", "3", "-c", "a-z"], input);
+    assert!(output.status.success());
+    assert_eq!(lengths(&output), [5, 5, 5, 7, 7, 20, 20, 20]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let (letters, digits) = stdout.split_at(stdout.len() - 3 * 21);
+    assert!(letters.lines().all(|line| {
+        line.bytes().all(|byte| byte.is_ascii_lowercase())
+    }));
+    assert!(digits.lines().all(|line| {
+        line.bytes().all(|byte| byte.is_ascii_digit())
+    }));
+}
+
+#[test]
+fn batch_lines_are_quoted_like_a_shell() {
+    let output = passgen_batch(&["-l", "12"], "-c 'a-c' -n \"2\"\n");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 2);
+    assert!(stdout.lines().all(|line| {
+        line.len() == 12 && line.bytes().all(|byte| b"abc".contains(&byte))
+    }));
+}
+
+#[test]
+fn batch_errors_give_the_line() {
+    let output = passgen_batch(&[], "-l 5\n-l x\n");
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("line 2: "));
+    let output = passgen_batch(&[], "{\"length\": \"x\"}\n");
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("line 1: "));
+}
@@ -0,0 +1,94 @@
+#![cfg(feature = "derive")]
+
+use passgen::{derive_password, CharClass, CharsetGenerator, Charset, Policy};
+use passgen::{DerivationVersion, SiteContext};
+use std::num::NonZeroUsize;
+
+// Derived passwords must never change between releases, or users of the
+// derive mode lose access to their accounts. These vectors pin every input
+// to the derivation.
+
+struct Vector {
+    master_password: &'static str,
+    site: &'static str,
+    login: &'static str,
+    counter: u32,
+    charset_spec: &'static str,
+    length: usize,
+    required: &'static [CharClass],
+    expected: &'static str,
+}
+
+const VECTORS: &[Vector] = &[
+    Vector {
+        master_password: "correct horse battery staple",
+        site: "example.com",
+        login: "alice",
+        counter: 1,
+        charset_spec: " -~",
+        length: 24,
+        required: &[],
+        expected: "cGM9-Mdjcam|PyGwX'qZ8V=R",
+    },
+    Vector {
+        master_password: "correct horse battery staple",
+        site: "example.com",
+        login: "alice",
+        counter: 2,
+        charset_spec: " -~",
+        length: 24,
+        required: &[],
+        expected: "m@obzRW5u?\"H/P2FH61LVrsp",
+    },
+    Vector {
+        master_password: "correct horse battery staple",
+        site: "example.org",
+        login: "bob",
+        counter: 1,
+        charset_spec: "a-zA-Z0-9",
+        length: 16,
+        required: &[CharClass::Uppercase, CharClass::Digits],
+        expected: "5LjYwZtAnvS0SowP",
+    },
+    Vector {
+        master_password: "hunter2",
+        site: "bank.example",
+        login: "alice@example.com",
+        counter: 7,
+        charset_spec: "0-9",
+        length: 6,
+        required: &[],
+        expected: "444147",
+    },
+];
+
+fn derive(vector: &Vector) -> String {
+    let length = NonZeroUsize::new(vector.length).unwrap();
+    let generator =
+        CharsetGenerator::new(
+            Charset::parse(vector.charset_spec).unwrap(),
+            length,
+            length,
+            Policy {
+                required: vector.required.to_vec(),
+                ..Policy::default()
+            },
+        )
+        .unwrap();
+    let context =
+        SiteContext {
+            site: vector.site.to_string(),
+            login: vector.login.to_string(),
+            counter: vector.counter,
+        };
+    let master_password = vector.master_password.as_bytes();
+    let version = DerivationVersion::V1;
+    derive_password(version, master_password, &context, &generator).unwrap()
+}
+
+#[test]
+fn vectors_are_stable() {
+    for vector in VECTORS {
+        assert_eq!(derive(vector), vector.expected);
+    }
+}
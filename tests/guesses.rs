@@ -0,0 +1,81 @@
+use passgen::{
+    estimate_guesses,
+    estimate_guesses_with,
+    Dictionaries,
+    GuessPattern,
+};
+
+// The estimates need not match zxcvbn's exactly, but must rank passwords
+// as an attacker would and account for every character.
+
+fn log10_guesses(password: &str) -> f64 {
+    estimate_guesses(password).guesses_log10()
+}
+
+#[test]
+fn common_passwords_are_weak() {
+    for password in ["password", "123456", "qwerty", "letmein"] {
+        assert!(log10_guesses(password) < 3.0, "{}", password);
+    }
+}
+
+#[test]
+fn variations_of_words_cost_a_little_more() {
+    let plain = log10_guesses("password");
+    for password in ["Password", "P@ssw0rd", "drowssap"] {
+        let guesses = log10_guesses(password);
+        assert!(guesses > plain && guesses < 3.0, "{}", password);
+    }
+}
+
+#[test]
+fn patterns_are_recognised() {
+    let cases = [
+        ("zxcfgh", "keyboard run"),
+        ("abcdefgh", "sequence"),
+        ("xyzxyzxyz", "repeated"),
+        ("19870512", "date"),
+    ];
+    for (password, kind) in cases {
+        let estimate = estimate_guesses(password);
+        assert_eq!(estimate.matches.len(), 1, "{}", password);
+        let pattern = estimate.matches[0].pattern.to_string();
+        assert!(pattern.starts_with(kind), "{}: {}", password, pattern);
+        assert!(estimate.guesses_log10() < 6.0, "{}", password);
+    }
+}
+
+#[test]
+fn random_passwords_are_brute_forced() {
+    let estimate = estimate_guesses("xK9#mQ2$vL7!");
+    assert_eq!(estimate.matches.len(), 1);
+    assert_eq!(estimate.matches[0].pattern, GuessPattern::Bruteforce);
+    assert!(estimate.guesses_log10() >= 12.0);
+}
+
+#[test]
+fn matches_cover_the_password_in_order() {
+    for password in ["drowssap2019", "Password1!", "qwerty!!tiger", "é1234"] {
+        let estimate = estimate_guesses(password);
+        let mut end = 0;
+        for pattern_match in &estimate.matches {
+            assert_eq!(pattern_match.start, end, "{}", password);
+            end = pattern_match.end;
+        }
+        assert_eq!(end, password.chars().count(), "{}", password);
+    }
+}
+
+#[test]
+fn added_words_are_matched() {
+    let password = "quokkaquillfeather";
+    let mut dictionaries = Dictionaries::built_in();
+    dictionaries.add(["quokka", "quill", "feather"]);
+    let with_words = estimate_guesses_with(password, &dictionaries);
+    assert!(with_words.guesses_log10() + 6.0 < log10_guesses(password));
+}
+
+#[test]
+fn empty_password_takes_one_guess() {
+    assert_eq!(estimate_guesses("").guesses, 1.0);
+}
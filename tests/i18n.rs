@@ -0,0 +1,29 @@
+use std::process::Command;
+
+// The library's messages are formatted before the command line tool sees
+// them, so they are translated by the msgid they were formatted from.
+
+fn german_error(args: &[&str]) -> String {
+    let output =
+        Command::new(env!("CARGO_BIN_EXE_passgen"))
+            .args(args)
+            .env("LC_ALL", "de_DE.UTF-8")
+            .output()
+            .unwrap();
+    assert!(!output.status.success());
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn formatted_library_messages_are_translated() {
+    let stderr = german_error(&["-c", "a\\q"]);
+    assert!(stderr.contains("ungültige Escape-Sequenz: \"\\q\""));
+    let stderr = german_error(&["username", "--max-len", "3"]);
+    assert!(stderr.contains("kein Benutzername im Stil words passt in 3"));
+}
+
+#[test]
+fn constant_library_messages_are_translated() {
+    let stderr = german_error(&["-c", "a-"]);
+    assert!(stderr.contains("nicht abgeschlossener Zeichenbereich"));
+}
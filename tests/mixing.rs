@@ -0,0 +1,88 @@
+use passgen::EntropyMixer;
+use proptest::prelude::*;
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::{RngCore, SeedableRng};
+
+// Mixing must never reduce the entropy of the generator's output. It XORs
+// in a stream that depends only on the extra input, so for a fixed input the
+// difference between mixed and unmixed output is the same whatever the
+// generator produces, which makes mixing a bijection of that output.
+
+const OUTPUT_LEN: usize = 300;
+
+fn draw(rng: &mut impl RngCore) -> Vec<u8> {
+    let mut output = vec![0u8; OUTPUT_LEN];
+    rng.try_fill_bytes(&mut output).unwrap();
+    output
+}
+
+fn xor(left: &[u8], right: &[u8]) -> Vec<u8> {
+    left.iter().zip(right).map(|(left, right)| left ^ right).collect()
+}
+
+proptest! {
+    #[test]
+    fn mixing_is_a_bijection_of_the_output(
+        extra in prop::collection::vec(any::<u8>(), 0..100),
+        seeds in prop::array::uniform2(any::<u64>()),
+    ) {
+        let pads =
+            seeds.map(|seed| {
+                let raw = draw(&mut ChaCha20Rng::seed_from_u64(seed));
+                let rng = ChaCha20Rng::seed_from_u64(seed);
+                let mixed = draw(&mut EntropyMixer::new(rng, &extra));
+                xor(&raw, &mixed)
+            });
+        prop_assert_eq!(&pads[0], &pads[1]);
+    }
+
+    #[test]
+    fn different_inputs_give_different_output(
+        extra in prop::collection::vec(any::<u8>(), 0..100),
+        seed in any::<u64>(),
+    ) {
+        let mut other = extra.clone();
+        other.push(0);
+        let rng = ChaCha20Rng::seed_from_u64(seed);
+        let mixed = draw(&mut EntropyMixer::new(rng.clone(), &extra));
+        let other_mixed = draw(&mut EntropyMixer::new(rng, &other));
+        prop_assert_ne!(mixed, other_mixed);
+    }
+}
+
+// Yields zeros, so a mixer wrapping it yields the stream it mixes in.
+struct Zeros;
+
+impl RngCore for Zeros {
+    fn next_u32(&mut self) -> u32 {
+        0
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        0
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        dest.fill(0);
+    }
+
+    fn try_fill_bytes(
+        &mut self,
+        dest: &mut [u8],
+    ) -> Result<(), rand_chacha::rand_core::Error> {
+        dest.fill(0);
+        Ok(())
+    }
+}
+
+#[test]
+fn stream_does_not_depend_on_read_sizes() {
+    let whole = draw(&mut EntropyMixer::new(Zeros, b"3141592653"));
+    let mut mixer = EntropyMixer::new(Zeros, b"3141592653");
+    let mut pieces = vec![0u8; OUTPUT_LEN];
+    for chunk in pieces.chunks_mut(7) {
+        mixer.fill_bytes(chunk);
+    }
+    assert_eq!(whole, pieces);
+    assert!(whole.iter().any(|&byte| byte != 0));
+}
@@ -0,0 +1,95 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+// Scripts are written to the target directory's temporary directory, one
+// per test, since tests run concurrently.
+
+fn script(name: &str, body: &str) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::write(&path, body).unwrap();
+    path
+}
+
+fn passgen(script: &PathBuf) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_passgen"))
+        .args(["-f", "-c", "a-z", "-l", "16", "-n", "20", "--post-script"])
+        .arg(script)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn script_changes_passwords() {
+    let output = passgen(&script("upper.rhai", "password.to_upper()\n"));
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let passwords = stdout.split_whitespace().collect::<Vec<_>>();
+    assert_eq!(passwords.len(), 20);
+    for password in passwords {
+        assert_eq!(password.len(), 16);
+        assert!(password.bytes().all(|byte| byte.is_ascii_uppercase()));
+    }
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("warning: the post-script changed passwords"));
+}
+
+#[test]
+fn script_rejects_candidates() {
+    let output = passgen(&script("reject.rhai", "password.starts_with(\"a\")"));
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let passwords = stdout.split_whitespace().collect::<Vec<_>>();
+    assert_eq!(passwords.len(), 20);
+    assert!(passwords.iter().all(|password| password.starts_with('a')));
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn failing_script_fails_the_job() {
+    let output = passgen(&script("fail.rhai", "throw \"no\";"));
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn script_must_return_a_password_or_a_verdict() {
+    let output = passgen(&script("number.rhai", "42"));
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("returned neither a password nor true or false"));
+}
+
+#[test]
+fn script_cannot_load_modules() {
+    let output = passgen(&script("import.rhai", "import \"x\" as x;\ntrue"));
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn endless_script_is_stopped() {
+    let output = passgen(&script("endless.rhai", "loop {}"));
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn printing_does_not_reach_the_output() {
+    let output = passgen(&script("print.rhai", "print(\"x\");\ntrue"));
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 20);
+    assert!(stdout.lines().all(|line| line.len() == 16));
+}
+
+#[test]
+fn entropy_of_post_scripted_passwords_is_unknown() {
+    let output =
+        Command::new(env!("CARGO_BIN_EXE_passgen"))
+            .args(["--dry-run", "--min-entropy", "1", "--post-script"])
+            .arg(script("dry.rhai", "true"))
+            .output()
+            .unwrap();
+    assert!(!output.status.success());
+}
@@ -0,0 +1,116 @@
+use passgen::{CharClass, Charset, PasswordSpec, Policy};
+use proptest::prelude::*;
+use proptest::sample::subsequence;
+use rand_chacha::ChaCha20Rng;
+use rand_chacha::rand_core::SeedableRng;
+use std::num::NonZeroUsize;
+
+// Each case is seeded, so a failure reproduces from its proptest seed. The
+// statistical bounds are loose enough that a correct sampler fails them
+// with probability well below one in a million per case.
+
+const SAMPLES_PER_CHAR: usize = 1000;
+
+fn typeable() -> Vec<u8> {
+    Charset::typeable().to_vec()
+}
+
+fn charset(max_size: usize) -> impl Strategy<Value = Charset> {
+    subsequence(typeable(), 1..=max_size)
+        .prop_map(|chars| Charset::new(chars).unwrap())
+}
+
+fn char_class() -> impl Strategy<Value = CharClass> {
+    prop_oneof![
+        Just(CharClass::Lowercase),
+        Just(CharClass::Uppercase),
+        Just(CharClass::Digits),
+        Just(CharClass::Symbols),
+    ]
+}
+
+fn non_zero(value: usize) -> NonZeroUsize {
+    NonZeroUsize::new(value).unwrap()
+}
+
+// Returns how many times each character of the charset occurs across this
+// many characters of generated passwords.
+fn counts(charset: &Charset, num_chars: usize, seed: u64) -> Vec<usize> {
+    let spec =
+        PasswordSpec::builder()
+            .charset(charset.clone())
+            .length(non_zero(num_chars))
+            .build()
+            .unwrap();
+    let rng = ChaCha20Rng::seed_from_u64(seed);
+    let password = spec.passwords_with_rng(rng).next().unwrap().unwrap();
+    let mut counts = vec![0; charset.len()];
+    for byte in password.bytes() {
+        let idx = charset.iter().position(|&ch| ch == byte).unwrap();
+        counts[idx] += 1;
+    }
+    counts
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn every_char_is_reachable(charset in charset(95), seed: u64) {
+        // With 50 draws per character, a character is missed with
+        // probability below e^-50 per character.
+        let counts = counts(&charset, 50 * charset.len(), seed);
+        prop_assert!(counts.iter().all(|&count| count > 0));
+    }
+
+    #[test]
+    fn small_charsets_are_uniform(charset in charset(10), seed: u64) {
+        prop_assume!(charset.len() > 1);
+        let num_chars = SAMPLES_PER_CHAR * charset.len();
+        let counts = counts(&charset, num_chars, seed);
+        let expected = SAMPLES_PER_CHAR as f64;
+        let chi_squared =
+            counts.iter()
+                .map(|&count| (count as f64 - expected).powi(2) / expected)
+                .sum::<f64>();
+        let df = (charset.len() - 1) as f64;
+        let bound = df + 6.0 * (2.0 * df).sqrt() + 10.0;
+        prop_assert!(
+            chi_squared < bound,
+            "chi-squared {} exceeds {} with {} degrees of freedom",
+            chi_squared,
+            bound,
+            df,
+        );
+    }
+
+    #[test]
+    fn passwords_satisfy_the_spec(
+        charset in charset(95),
+        required in prop::collection::vec(char_class(), 0..4),
+        min_len in 1usize..32,
+        extra_len in 0usize..8,
+        seed: u64,
+    ) {
+        let max_len = min_len + extra_len;
+        let policy = Policy { required, ..Policy::default() };
+        let spec =
+            PasswordSpec::builder()
+                .charset(charset.clone())
+                .min_length(non_zero(min_len))
+                .max_length(non_zero(max_len))
+                .count(non_zero(16))
+                .policy(policy.clone())
+                .build();
+        let Ok(spec) = spec else {
+            return Err(TestCaseError::reject("unsatisfiable policy"));
+        };
+        let rng = ChaCha20Rng::seed_from_u64(seed);
+        for password in spec.passwords_with_rng(rng) {
+            let password = password.unwrap();
+            prop_assert!((min_len..=max_len).contains(&password.len()));
+            prop_assert!(password.bytes().all(|byte| charset.contains(&byte)));
+            prop_assert!(policy.is_satisfied_by(password.as_bytes()));
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::process::{Command, Output};
+
+// Each command reads its options from the table its synopsis in help and
+// the manual page is generated from, so that the two cannot drift apart.
+
+fn passgen(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_passgen")).args(args).output().unwrap()
+}
+
+#[test]
+fn documented_long_options_are_accepted() {
+    let output = passgen(&["pin", "--length", "8", "--count", "3"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 3);
+    assert!(stdout.lines().all(|pin| pin.len() == 8));
+    let output = passgen(&["wifi", "--ssid", "home", "--length", "20"]);
+    assert!(output.status.success());
+    assert_eq!(output.stdout.len(), 21);
+}
+
+#[test]
+fn undocumented_options_are_rejected() {
+    for args in [&["pin", "--bogus"][..], &["wifi", "--ssid", "a", "-x"]] {
+        assert_eq!(passgen(args).status.code(), Some(2));
+    }
+}
+
+#[test]
+fn options_are_documented_in_help_and_manual_page() {
+    let help = String::from_utf8(passgen(&["--help"]).stderr).unwrap();
+    assert!(help.contains("pin [-l|--length pin_len]"));
+    assert!(help.contains("[-l|--length passphrase_len]"));
+    let man = String::from_utf8(passgen(&["man"]).stdout).unwrap();
+    assert!(man.contains("pin [\\-l|\\-\\-length pin_len]"));
+    assert!(man.contains("[\\-l|\\-\\-length passphrase_len]"));
+}
+
+// The flags the main command's parser matches on, read from its source
+// since the binary cannot be asked for them.
+fn parsed_flags() -> Vec<&'static str> {
+    let source = include_str!("../src/main.rs");
+    let start = source.find("\nfn parse_args(").unwrap();
+    let len = source[start + 1..].find("\nfn ").unwrap();
+    let mut flags = Vec::new();
+    for line in source[start..start + len].lines() {
+        let Some((pattern, _)) = line.trim().split_once("=>") else {
+            continue;
+        };
+        if !pattern.starts_with("\"-") {
+            continue;
+        }
+        for flag in pattern.split('|') {
+            flags.push(flag.trim().trim_matches('"'));
+        }
+    }
+    flags
+}
+
+#[test]
+fn every_parsed_flag_is_in_the_manual_page() {
+    let flags = parsed_flags();
+    assert!(flags.contains(&"-c") && flags.contains(&"--post-script"));
+    let man = String::from_utf8(passgen(&["man"]).stdout).unwrap();
+    let man = man.replace("\\-", "-");
+    for flag in flags {
+        let documented =
+            man.match_indices(flag).any(|(idx, _)| {
+                let after = man[idx + flag.len()..].chars().next();
+                !after.is_some_and(|ch| ch.is_alphanumeric() || ch == '-')
+            });
+        assert!(documented, "{} is not in the manual page", flag);
+    }
+}
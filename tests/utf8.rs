@@ -0,0 +1,97 @@
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+// Whatever the platform and wherever output goes, passgen writes UTF-8 as
+// it is, without a byte order mark, a conversion to a code page or line
+// endings other than \n. Character sets are ASCII, so the non-ASCII text
+// here comes from the users file and the label.
+
+const USERNAMES: [&str; 4] = ["zoë", "日本", "🦀crab", "ascii"];
+
+fn temp_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name)
+}
+
+fn users_file(name: &str) -> PathBuf {
+    let path = temp_path(name);
+    fs::write(&path, USERNAMES.join("\n") + "\n").unwrap();
+    path
+}
+
+fn passgen() -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_passgen"));
+    command.args(["-l", "16"]);
+    command
+}
+
+fn usernames(output: &[u8]) -> Vec<&[u8]> {
+    assert!(!output.contains(&b'\r'));
+    output.split(|&byte| byte == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(|line| line.split(|&byte| byte == b'\t').next().unwrap())
+        .collect()
+}
+
+#[test]
+fn piped_output_is_utf8_byte_for_byte() {
+    let users = users_file("utf8-piped.txt");
+    let output = passgen().arg("--users").arg(&users).output().unwrap();
+    assert!(output.status.success());
+    let expected =
+        USERNAMES.iter().map(|name| name.as_bytes()).collect::<Vec<_>>();
+    assert_eq!(usernames(&output.stdout), expected);
+}
+
+#[test]
+fn file_output_is_utf8_byte_for_byte() {
+    let users = users_file("utf8-file.txt");
+    let out_path = temp_path("utf8-file.out");
+    let status =
+        passgen()
+            .arg("--users")
+            .arg(&users)
+            .stdout(Stdio::from(File::create(&out_path).unwrap()))
+            .status()
+            .unwrap();
+    assert!(status.success());
+    let output = fs::read(&out_path).unwrap();
+    let expected =
+        USERNAMES.iter().map(|name| name.as_bytes()).collect::<Vec<_>>();
+    assert_eq!(usernames(&output), expected);
+}
+
+#[test]
+fn json_output_leaves_non_ascii_unescaped() {
+    let users = users_file("utf8-json.txt");
+    let output =
+        passgen()
+            .arg("--users")
+            .arg(&users)
+            .args(["--format", "json"])
+            .output()
+            .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    for name in USERNAMES {
+        assert!(stdout.contains(&format!("{{\"username\":\"{}\",", name)));
+    }
+}
+
+#[test]
+fn audit_log_keeps_label_byte_for_byte() {
+    let log = temp_path("utf8-audit.log");
+    let _ = fs::remove_file(&log);
+    let status =
+        passgen()
+            .arg("--audit-log")
+            .arg(&log)
+            .args(["--label", "Prüfung ✓"])
+            .stdout(Stdio::null())
+            .status()
+            .unwrap();
+    assert!(status.success());
+    let entry = fs::read(&log).unwrap();
+    let label = "\"label\":\"Prüfung ✓\"".as_bytes();
+    assert!(entry.windows(label.len()).any(|window| window == label));
+}